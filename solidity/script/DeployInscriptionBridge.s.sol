@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.20;
+
+import "forge-std/Script.sol";
+import "@openzeppelin/foundry-upgrades/Upgrades.sol";
+import { InscriptionBridge } from "src/InscriptionBridge.sol";
+
+/// Deploys the ERC-721 collection of the ordinals bridge behind a UUPS proxy.
+contract DeployInscriptionBridge is Script {
+    uint256 privateKey = vm.envUint("PRIVATE_KEY");
+    address minterAddress = vm.envAddress("MINTER_ADDRESS");
+    string name = vm.envOr("NAME", string("Bridged Inscriptions"));
+    string symbol = vm.envOr("SYMBOL", string("ORD"));
+    address addressZero = address(0);
+    address owner = vm.envOr("OWNER", addressZero);
+    address[] zeroAddressControllers = new address[](0);
+    address[] controllers = vm.envOr("CONTROLLERS", ",", zeroAddressControllers);
+
+    function run() external {
+        vm.startBroadcast(privateKey);
+
+        bytes memory data = abi.encodeWithSelector(
+            InscriptionBridge.initialize.selector, minterAddress, name, symbol, owner, controllers
+        );
+        address proxyAddress = Upgrades.deployUUPSProxy("InscriptionBridge.sol:InscriptionBridge", data);
+
+        console.log("Proxy address: %s", proxyAddress);
+        console.log("Implementation address: %s", Upgrades.getImplementationAddress(proxyAddress));
+
+        vm.stopBroadcast();
+    }
+}
@@ -25,6 +25,13 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
     uint8 public constant MINT_ERROR_CODE_UNEXPECTED_RECIPIENT_CHAIN_ID = 5;
     uint8 public constant MINT_ERROR_CODE_TOKENS_NOT_BRIDGED = 6;
     uint8 public constant MINT_ERROR_CODE_PROCESSING_NOT_REQUESTED = 7;
+    uint8 public constant MINT_ERROR_CODE_INSUFFICIENT_NATIVE_BALANCE = 8;
+
+    // Address used in place of ERC20 token address for the native coin of the chain.
+    address public constant NATIVE_TOKEN_ADDRESS = address(2);
+
+    // Decimals of the native coin.
+    uint8 constant NATIVE_TOKEN_DECIMALS = 18;
 
     // Gas fee for batch mint operation.
     uint256 constant COMMON_BATCH_MINT_GAS_FEE = 200000;
@@ -175,6 +182,12 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         _;
     }
 
+    /// Modifier that restricts access to the minter canister.
+    modifier onlyMinter() {
+        require(msg.sender == minterCanisterAddress, "Not a minter");
+        _;
+    }
+
     /// Add a new implementation to the allowed list
     function addAllowedImplementation(
         bytes32 bytecodeHash
@@ -207,6 +220,29 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         controllerAccessList[controller] = false;
     }
 
+    /// Updates metadata of the wrapped token registered for the given `baseTokenID`.
+    /// Zero `name`, `symbol` or `decimals` values are ignored.
+    /// Can be called only by the minter canister.
+    function updateWrappedTokenMetadata(
+        bytes32 baseTokenID,
+        bytes32 name,
+        bytes16 symbol,
+        uint8 decimals
+    ) external onlyMinter {
+        address wrappedERC20 = _baseToWrapped[baseTokenID];
+        require(wrappedERC20 != address(0), "Wrapped token not found");
+
+        updateTokenMetadata(wrappedERC20, name, symbol, decimals);
+    }
+
+    /// Marks the nonce of the mint order from `senderID` as used, so the order can never be minted.
+    /// Reverts if the order is already minted.
+    /// Can be called only by the minter canister.
+    function cancelMintOrder(bytes32 senderID, uint32 nonce) external onlyMinter {
+        require(!_isNonceUsed[senderID][nonce], "Invalid nonce");
+        _isNonceUsed[senderID][nonce] = true;
+    }
+
     /// Transfer funds to users according the signed encoded orders.
     /// Returns `processedOrders` array of error codes for each mint order;
     function batchMint(
@@ -303,7 +339,12 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
 
         // Execute the withdrawal
         _isNonceUsed[order.senderID][order.nonce] = true;
-        IERC20(order.toERC20).safeTransfer(order.recipient, order.amount);
+        if (order.toERC20 == NATIVE_TOKEN_ADDRESS) {
+            (bool success,) = payable(order.recipient).call{ value: order.amount }("");
+            require(success, "Native token transfer failed");
+        } else {
+            IERC20(order.toERC20).safeTransfer(order.recipient, order.amount);
+        }
 
         if (order.approveSpender != address(0) && order.approveAmount != 0 && isTokenWrapped) {
             WrappedToken(order.toERC20).approveByOwner(order.recipient, order.approveSpender, order.approveAmount);
@@ -341,6 +382,7 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
     ) public whenNotPaused returns (uint32) {
         require(fromERC20 != address(this), "From address must not be BTF bridge address");
         require(fromERC20 != address(0), "Invalid from address; must not be zero address");
+        require(fromERC20 != NATIVE_TOKEN_ADDRESS, "Native tokens must be deposited with depositNative");
         // Check if the token is registered on the bridge or the side is base
         require(
             isBaseSide() || (_wrappedToBase[fromERC20] != bytes32(0) && _baseToWrapped[toTokenID] != address(0)),
@@ -382,6 +424,38 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
         return operationID;
     }
 
+    /// Deposit native coins there to make possible perform a mint of the wrapped native token
+    /// on other side of the bridge. The deposited amount is the value of the transaction.
+    /// Returns operation ID if operation is succesfull.
+    function depositNative(
+        bytes32 toTokenID,
+        bytes memory recipientID,
+        bytes32 memo
+    ) external payable whenNotPaused returns (uint32) {
+        require(isBaseSide(), "Native tokens can be deposited only on base side");
+        require(msg.value > 0, "Invalid deposit amount");
+
+        // Update user information about burn operations.
+        _lastUserBurns[msg.sender].push(uint32(block.number));
+
+        uint32 operationID = operationIDCounter++;
+
+        emit BurnTokenEvent(
+            msg.sender,
+            msg.value,
+            NATIVE_TOKEN_ADDRESS,
+            recipientID,
+            toTokenID,
+            operationID,
+            bytes32(0),
+            bytes16(0),
+            NATIVE_TOKEN_DECIMALS,
+            memo
+        );
+
+        return operationID;
+    }
+
     /// Getter function for minter address
     function getMinterAddress() external view returns (address) {
         return minterCanisterAddress;
@@ -438,6 +512,11 @@ contract BTFBridge is TokenManager, UUPSUpgradeable, OwnableUpgradeable, Pausabl
             return MINT_ERROR_CODE_UNEXPECTED_RECIPIENT_CHAIN_ID;
         }
 
+        // Check if the bridge holds enough native coins to release.
+        if (order.toERC20 == NATIVE_TOKEN_ADDRESS && address(this).balance < order.amount) {
+            return MINT_ERROR_CODE_INSUFFICIENT_NATIVE_BALANCE;
+        }
+
         // Check if tokens are bridged.
         if (_wrappedToBase[order.toERC20] != bytes32(0) && _baseToWrapped[order.fromTokenID] != order.toERC20) {
             return MINT_ERROR_CODE_TOKENS_NOT_BRIDGED;
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import "@openzeppelin/contracts-upgradeable/token/ERC721/ERC721Upgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/access/OwnableUpgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+import "@openzeppelin/contracts-upgradeable/utils/PausableUpgradeable.sol";
+
+/// ERC-721 side of the ordinals bridge.
+///
+/// Every token of the collection wraps one inscription held by the ordinals bridge canister.
+/// The id of the token is the `Id256` of the inscription.
+///
+/// Mint orders are signed by the canister in the `BTFBridge` encoding, and the contract emits the
+/// `BTFBridge` events, so the canister works with the collection the same way it works with a
+/// `BTFBridge`.
+contract InscriptionBridge is ERC721Upgradeable, UUPSUpgradeable, OwnableUpgradeable, PausableUpgradeable {
+    // Error codes, matching the ones of BTFBridge:
+    uint8 public constant MINT_ERROR_CODE_OK = 0;
+    uint8 public constant MINT_ERROR_CODE_USED_NONCE = 3;
+    uint8 public constant MINT_ERROR_CODE_ZERO_RECIPIENT = 4;
+    uint8 public constant MINT_ERROR_CODE_UNEXPECTED_RECIPIENT_CHAIN_ID = 5;
+    // The order doesn't mint a new token of this collection.
+    uint8 public constant MINT_ERROR_CODE_TOKENS_NOT_BRIDGED = 6;
+    uint8 public constant MINT_ERROR_CODE_PROCESSING_NOT_REQUESTED = 7;
+
+    // Has a user's transaction nonce been used?
+    mapping(bytes32 => mapping(uint32 => bool)) private _isNonceUsed;
+
+    // Operation ID counter
+    uint32 public operationIDCounter;
+
+    // Address of minter canister
+    address public minterCanisterAddress;
+
+    /// Allowed implementations hash list
+    mapping(bytes32 => bool) public allowedImplementations;
+
+    /// Controller AccessList for adding implementations
+    mapping(address => bool) public controllerAccessList;
+
+    uint32 private constant MINT_ORDER_DATA_LEN = 269;
+
+    struct MintOrderData {
+        uint256 amount;
+        bytes32 senderID;
+        bytes32 fromTokenID;
+        address recipient;
+        address toERC20;
+        uint32 nonce;
+        uint32 recipientChainID;
+    }
+
+    // Event for mint operation
+    event MintTokenEvent(
+        uint256 amount,
+        bytes32 fromToken,
+        bytes32 senderID,
+        address toERC20,
+        address recipient,
+        uint32 nonce,
+        uint256 chargedFee
+    );
+
+    /// Event for burn operation
+    event BurnTokenEvent(
+        address sender,
+        uint256 amount,
+        address fromERC20,
+        bytes recipientID,
+        bytes32 toToken,
+        uint32 operationID,
+        bytes32 name,
+        bytes16 symbol,
+        uint8 decimals,
+        bytes32 memo
+    );
+
+    /// Event that can be emited with a notification for the minter canister
+    event NotifyMinterEvent(uint32 notificationType, address txSender, bytes userData, bytes32 memo);
+
+    /// @custom:oz-upgrades-unsafe-allow constructor
+    constructor() {
+        // Locks the contract and prevent any future re-initialization
+        _disableInitializers();
+    }
+
+    /// Initializes the InscriptionBridge contract.
+    ///
+    /// @param minterAddress The address of the ordinals bridge canister.
+    /// @param name_ The name of the collection.
+    /// @param symbol_ The symbol of the collection.
+    /// @param owner The initial owner of the contract. If set to 0x0, the caller becomes the owner.
+    /// @param controllers The initial list of authorized controllers.
+    /// @dev This function is called only once during the contract deployment.
+    function initialize(
+        address minterAddress,
+        string memory name_,
+        string memory symbol_,
+        address owner,
+        address[] memory controllers
+    ) public initializer {
+        minterCanisterAddress = minterAddress;
+        __ERC721_init(name_, symbol_);
+
+        // Set the owner
+        address newOwner = owner != address(0) ? owner : msg.sender;
+        __Ownable_init(newOwner);
+
+        // Add owner to the controller list
+        controllerAccessList[newOwner] = true;
+
+        // Add controllers
+        for (uint256 i = 0; i < controllers.length; i++) {
+            controllerAccessList[controllers[i]] = true;
+        }
+
+        __UUPSUpgradeable_init();
+        __Pausable_init();
+    }
+
+    /// Restrict who can upgrade this contract
+    function _authorizeUpgrade(
+        address newImplementation
+    ) internal view override onlyOwner {
+        require(allowedImplementations[newImplementation.codehash], "Not allowed implementation");
+    }
+
+    /// Pause the contract and prevent any future mint or burn operations
+    function pause() external onlyControllers {
+        _pause();
+    }
+
+    /// Unpause the contract
+    function unpause() external onlyControllers {
+        _unpause();
+    }
+
+    /// Modifier that restricts access to only addresses in the
+    /// `controllerAccessList`.
+    modifier onlyControllers() {
+        require(controllerAccessList[msg.sender], "Not a controller");
+        _;
+    }
+
+    /// Modifier that restricts access to the minter canister.
+    modifier onlyMinter() {
+        require(msg.sender == minterCanisterAddress, "Not a minter");
+        _;
+    }
+
+    /// Add a new implementation to the allowed list
+    function addAllowedImplementation(
+        bytes32 bytecodeHash
+    ) external onlyControllers {
+        require(!allowedImplementations[bytecodeHash], "Implementation already allowed");
+
+        allowedImplementations[bytecodeHash] = true;
+    }
+
+    /// Adds the given `controller` address to the `controllerAccessList`.
+    /// This function can only be called by the contract owner.
+    function addController(
+        address controller
+    ) external onlyOwner {
+        controllerAccessList[controller] = true;
+    }
+
+    /// Removes the given `controller` address from the `controllerAccessList`.
+    /// This function can only be called by the contract owner.
+    function removeController(
+        address controller
+    ) external onlyOwner {
+        controllerAccessList[controller] = false;
+    }
+
+    /// Emit minter notification event with the given `userData`. The ordinals bridge expects
+    /// the candid encoded `InscriptionDepositRequest` there.
+    function notifyMinter(uint32 notificationType, bytes calldata userData, bytes32 memo) external {
+        emit NotifyMinterEvent(notificationType, msg.sender, userData, memo);
+    }
+
+    /// Marks the nonce of the mint order from `senderID` as used, so the order can never be minted.
+    /// Reverts if the order is already minted.
+    /// Can be called only by the minter canister.
+    function cancelMintOrder(bytes32 senderID, uint32 nonce) external onlyMinter {
+        require(!_isNonceUsed[senderID][nonce], "Invalid nonce");
+        _isNonceUsed[senderID][nonce] = true;
+    }
+
+    /// Mints the tokens of the inscriptions according to the signed encoded orders.
+    /// Returns `processedOrders` array of error codes for each mint order.
+    function batchMint(
+        bytes calldata encodedOrders,
+        bytes calldata signature,
+        uint32[] calldata ordersToProcess
+    ) external whenNotPaused returns (uint8[] memory) {
+        require(encodedOrders.length > 0, "Expected non-empty orders batch");
+        require(encodedOrders.length % MINT_ORDER_DATA_LEN == 0, "Incorrect mint orders batch encoding");
+        _checkMinterSignature(encodedOrders, signature);
+
+        uint32 ordersNumber = uint32(encodedOrders.length) / MINT_ORDER_DATA_LEN;
+
+        bool[] memory orderIndexes = new bool[](ordersNumber);
+        if (ordersToProcess.length == 0) {
+            for (uint32 i = 0; i < ordersNumber; i++) {
+                orderIndexes[i] = true;
+            }
+        } else {
+            for (uint32 i = 0; i < ordersToProcess.length; i++) {
+                orderIndexes[ordersToProcess[i]] = true;
+            }
+        }
+
+        uint8[] memory processedOrderIndexes = new uint8[](ordersNumber);
+        for (uint32 i = 0; i < ordersNumber; i++) {
+            if (!orderIndexes[i]) {
+                processedOrderIndexes[i] = MINT_ERROR_CODE_PROCESSING_NOT_REQUESTED;
+                continue;
+            }
+
+            uint32 orderStart = MINT_ORDER_DATA_LEN * i;
+            MintOrderData memory order = _decodeOrder(encodedOrders[orderStart:orderStart + MINT_ORDER_DATA_LEN]);
+
+            uint8 orderValidationResult = _isOrderValid(order);
+            if (orderValidationResult != MINT_ERROR_CODE_OK) {
+                processedOrderIndexes[i] = orderValidationResult;
+                continue;
+            }
+
+            _isNonceUsed[order.senderID][order.nonce] = true;
+            _mint(order.recipient, uint256(order.fromTokenID));
+
+            emit MintTokenEvent(
+                order.amount, order.fromTokenID, order.senderID, order.toERC20, order.recipient, order.nonce, 0
+            );
+            processedOrderIndexes[i] = MINT_ERROR_CODE_OK;
+        }
+
+        return processedOrderIndexes;
+    }
+
+    /// Burns the token of the inscription, so the ordinals bridge sends the inscription to the
+    /// Bitcoin address encoded in `recipientID`.
+    /// The caller must own the token or be approved to transfer it.
+    /// Returns operation ID if operation is succesfull.
+    function burn(uint256 tokenId, bytes memory recipientID, bytes32 memo) external whenNotPaused returns (uint32) {
+        require(recipientID.length > 0, "Invalid recipient");
+
+        // Reverts if the token doesn't exist or the caller is not authorized to transfer it.
+        _update(address(0), tokenId, msg.sender);
+
+        uint32 operationID = operationIDCounter++;
+
+        emit BurnTokenEvent(
+            msg.sender, 1, address(this), recipientID, bytes32(tokenId), operationID, bytes32(0), bytes16(0), 0, memo
+        );
+
+        return operationID;
+    }
+
+    /// Getter function for minter address
+    function getMinterAddress() external view returns (address) {
+        return minterCanisterAddress;
+    }
+
+    /// Function to check if the mint order is valid.
+    function _isOrderValid(
+        MintOrderData memory order
+    ) private view returns (uint8) {
+        // Check recipient address is not zero
+        if (order.recipient == address(0)) {
+            return MINT_ERROR_CODE_ZERO_RECIPIENT;
+        }
+
+        // Check if nonce is not stored in the list
+        if (_isNonceUsed[order.senderID][order.nonce]) {
+            return MINT_ERROR_CODE_USED_NONCE;
+        }
+
+        // Check if withdrawal is happening on the correct chain
+        if (block.chainid != order.recipientChainID) {
+            return MINT_ERROR_CODE_UNEXPECTED_RECIPIENT_CHAIN_ID;
+        }
+
+        // Check if the order mints a single new token of this collection.
+        if (order.toERC20 != address(this) || order.amount != 1 || _ownerOf(uint256(order.fromTokenID)) != address(0))
+        {
+            return MINT_ERROR_CODE_TOKENS_NOT_BRIDGED;
+        }
+
+        return MINT_ERROR_CODE_OK;
+    }
+
+    function _decodeOrder(
+        bytes calldata encodedOrder
+    ) private pure returns (MintOrderData memory order) {
+        order.amount = uint256(bytes32(encodedOrder[:32]));
+        order.senderID = bytes32(encodedOrder[32:64]);
+        order.fromTokenID = bytes32(encodedOrder[64:96]);
+        order.recipient = address(bytes20(encodedOrder[96:116]));
+        order.toERC20 = address(bytes20(encodedOrder[116:136]));
+        order.nonce = uint32(bytes4(encodedOrder[136:140]));
+        order.recipientChainID = uint32(bytes4(encodedOrder[144:148]));
+    }
+
+    /// Function to check the signature of the minter canister
+    function _checkMinterSignature(bytes calldata data, bytes calldata signature) private view {
+        // Create a hash of the order data
+        bytes32 hash = keccak256(data);
+
+        // Recover signer from the signature
+        address signer = ECDSA.recover(hash, signature);
+
+        // Check if signer is the minter canister
+        require(signer == minterCanisterAddress, "Invalid signature");
+    }
+}
@@ -352,6 +352,74 @@ contract BTFBridgeTest is Test {
         assertEq(token.decimals(), 21);
     }
 
+    function testUpdateWrappedTokenMetadata() public {
+        bytes32 base_token_id = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        address wrapped_address = _wrappedBridge.deployERC20("Token", "TKN", 18, base_token_id);
+
+        vm.prank(_owner);
+        _wrappedBridge.updateWrappedTokenMetadata(base_token_id, bytes32("NewToken"), bytes16("NTKN"), 0);
+
+        WrappedToken token = WrappedToken(wrapped_address);
+        assertEq(bytes32(bytes(token.name())), bytes32("NewToken"));
+        assertEq(bytes16(bytes(token.symbol())), bytes16("NTKN"));
+        assertEq(token.decimals(), 18);
+    }
+
+    function testUpdateWrappedTokenMetadataByNonMinter() public {
+        bytes32 base_token_id = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        _wrappedBridge.deployERC20("Token", "TKN", 18, base_token_id);
+
+        vm.prank(_alice);
+        vm.expectRevert("Not a minter");
+        _wrappedBridge.updateWrappedTokenMetadata(base_token_id, bytes32("NewToken"), bytes16("NTKN"), 0);
+    }
+
+    function testUpdateWrappedTokenMetadataForUnknownToken() public {
+        bytes32 base_token_id = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+
+        vm.prank(_owner);
+        vm.expectRevert("Wrapped token not found");
+        _wrappedBridge.updateWrappedTokenMetadata(base_token_id, bytes32("NewToken"), bytes16("NTKN"), 0);
+    }
+
+    function testCancelMintOrder() public {
+        MintOrder memory order = _createDefaultMintOrder();
+
+        vm.prank(_owner);
+        _wrappedBridge.cancelMintOrder(order.senderID, order.nonce);
+
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = order;
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+        uint8[] memory processedOrders = _wrappedBridge.batchMint(encodedOrders, signature, new uint32[](0));
+
+        assertEq(processedOrders[0], _wrappedBridge.MINT_ERROR_CODE_USED_NONCE());
+        assertEq(WrappedToken(order.toERC20).balanceOf(order.recipient), 0);
+    }
+
+    function testCancelMintedOrder() public {
+        MintOrder memory order = _createDefaultMintOrder();
+
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = order;
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+        _wrappedBridge.batchMint(encodedOrders, signature, new uint32[](0));
+
+        vm.prank(_owner);
+        vm.expectRevert("Invalid nonce");
+        _wrappedBridge.cancelMintOrder(order.senderID, order.nonce);
+    }
+
+    function testCancelMintOrderByNonMinter() public {
+        MintOrder memory order = _createDefaultMintOrder();
+
+        vm.prank(_alice);
+        vm.expectRevert("Not a minter");
+        _wrappedBridge.cancelMintOrder(order.senderID, order.nonce);
+    }
+
     function testListTokenPairs() public {
         bytes32[3] memory base_token_ids = [
             _createIdFromPrincipal(abi.encodePacked(uint8(1))),
@@ -510,6 +578,86 @@ contract BTFBridgeTest is Test {
         _wrappedBridge.batchMint(encodedOrders, signature, ordersToProcess);
     }
 
+    function testDepositNativeBaseSide() public {
+        bytes memory principal = abi.encodePacked(uint8(1), uint8(2), uint8(3));
+        bytes32 toTokenId = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        bytes32 memo = bytes32(abi.encodePacked(uint8(0)));
+
+        vm.deal(_alice, 1000);
+        vm.prank(_alice);
+        _baseBridge.depositNative{ value: 100 }(toTokenId, principal, memo);
+
+        assertEq(address(_baseBridge).balance, 100);
+        assertEq(_alice.balance, 900);
+    }
+
+    function testDepositNativeWrappedSideShouldFail() public {
+        bytes memory principal = abi.encodePacked(uint8(1), uint8(2), uint8(3));
+        bytes32 toTokenId = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        bytes32 memo = bytes32(abi.encodePacked(uint8(0)));
+
+        vm.deal(_alice, 1000);
+        vm.prank(_alice);
+        vm.expectRevert(bytes("Native tokens can be deposited only on base side"));
+        _wrappedBridge.depositNative{ value: 100 }(toTokenId, principal, memo);
+    }
+
+    function testDepositNativeZeroAmountShouldFail() public {
+        bytes memory principal = abi.encodePacked(uint8(1), uint8(2), uint8(3));
+        bytes32 toTokenId = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        bytes32 memo = bytes32(abi.encodePacked(uint8(0)));
+
+        vm.prank(_alice);
+        vm.expectRevert(bytes("Invalid deposit amount"));
+        _baseBridge.depositNative(toTokenId, principal, memo);
+    }
+
+    function testBurnNativeAddressShouldFail() public {
+        bytes memory principal = abi.encodePacked(uint8(1), uint8(2), uint8(3));
+        bytes32 toTokenId = _createIdFromPrincipal(abi.encodePacked(uint8(1)));
+        bytes32 memo = bytes32(abi.encodePacked(uint8(0)));
+        address nativeToken = _baseBridge.NATIVE_TOKEN_ADDRESS();
+
+        vm.prank(_alice);
+        vm.expectRevert(bytes("Native tokens must be deposited with depositNative"));
+        _baseBridge.burn(100, nativeToken, toTokenId, principal, memo);
+    }
+
+    function testMintNativeBaseSide() public {
+        vm.deal(address(_baseBridge), 1000);
+
+        MintOrder memory order = _createMintOrder(_bob, _baseBridge.NATIVE_TOKEN_ADDRESS());
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = order;
+
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+
+        uint32[] memory ordersToProcess = new uint32[](0);
+        uint8[] memory processedOrders = _baseBridge.batchMint(encodedOrders, signature, ordersToProcess);
+
+        assertEq(processedOrders[0], _baseBridge.MINT_ERROR_CODE_OK());
+        assertEq(_bob.balance, order.amount);
+        assertEq(address(_baseBridge).balance, 0);
+    }
+
+    function testMintNativeInsufficientBalance() public {
+        vm.deal(address(_baseBridge), 999);
+
+        MintOrder memory order = _createMintOrder(_bob, _baseBridge.NATIVE_TOKEN_ADDRESS());
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = order;
+
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+
+        uint32[] memory ordersToProcess = new uint32[](0);
+        uint8[] memory processedOrders = _baseBridge.batchMint(encodedOrders, signature, ordersToProcess);
+
+        assertEq(processedOrders[0], _baseBridge.MINT_ERROR_CODE_INSUFFICIENT_NATIVE_BALANCE());
+        assertEq(_bob.balance, 0);
+    }
+
     function testMintCallsAreRejectedWhenPaused() public {
         vm.prank(_owner);
 
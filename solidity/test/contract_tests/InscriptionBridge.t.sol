@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "@openzeppelin/contracts/token/ERC721/IERC721.sol";
+import "src/InscriptionBridge.sol";
+import "src/test_contracts/UUPSProxy.sol";
+
+contract InscriptionBridgeTest is Test {
+    struct MintOrder {
+        uint256 amount;
+        bytes32 senderID;
+        bytes32 fromTokenID;
+        address recipient;
+        address toERC20;
+        uint32 nonce;
+        uint32 recipientChainID;
+    }
+
+    event BurnTokenEvent(
+        address sender,
+        uint256 amount,
+        address fromERC20,
+        bytes recipientID,
+        bytes32 toToken,
+        uint32 operationID,
+        bytes32 name,
+        bytes16 symbol,
+        uint8 decimals,
+        bytes32 memo
+    );
+
+    uint256 constant _OWNER_KEY = 1;
+    uint256 constant _ALICE_KEY = 2;
+    uint256 constant _BOB_KEY = 3;
+
+    uint32 constant _CHAIN_ID = 31555;
+
+    address _owner = vm.addr(_OWNER_KEY);
+    address _alice = vm.addr(_ALICE_KEY);
+    address _bob = vm.addr(_BOB_KEY);
+
+    InscriptionBridge _bridge;
+
+    function setUp() public {
+        vm.chainId(_CHAIN_ID);
+        vm.startPrank(_owner);
+
+        address[] memory initialControllers = new address[](0);
+        bytes memory initializeData = abi.encodeWithSelector(
+            InscriptionBridge.initialize.selector, _owner, "Inscriptions", "ORD", _owner, initialControllers
+        );
+
+        InscriptionBridge impl = new InscriptionBridge();
+        UUPSProxy proxy = new UUPSProxy(address(impl), initializeData);
+        _bridge = InscriptionBridge(address(proxy));
+
+        vm.stopPrank();
+    }
+
+    function testBatchMintSuccess() public {
+        MintOrder[] memory orders = new MintOrder[](2);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        orders[1] = _createMintOrder(_inscriptionId(2), 1);
+
+        uint8[] memory processedOrders = _batchMint(orders);
+
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_OK());
+        assertEq(processedOrders[1], _bridge.MINT_ERROR_CODE_OK());
+        assertEq(_bridge.ownerOf(uint256(_inscriptionId(1))), _alice);
+        assertEq(_bridge.ownerOf(uint256(_inscriptionId(2))), _alice);
+        assertEq(_bridge.balanceOf(_alice), 2);
+    }
+
+    function testBatchMintWithInvalidSignatureShouldFail() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _BOB_KEY);
+
+        vm.expectRevert("Invalid signature");
+        _bridge.batchMint(encodedOrders, signature, new uint32[](0));
+    }
+
+    function testBatchMintSkipsUsedNonce() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        _batchMint(orders);
+
+        orders[0] = _createMintOrder(_inscriptionId(2), 0);
+        uint8[] memory processedOrders = _batchMint(orders);
+
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_USED_NONCE());
+        assertEq(_bridge.balanceOf(_alice), 1);
+    }
+
+    function testBatchMintSkipsExistingToken() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        _batchMint(orders);
+
+        orders[0] = _createMintOrder(_inscriptionId(1), 1);
+        uint8[] memory processedOrders = _batchMint(orders);
+
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_TOKENS_NOT_BRIDGED());
+        assertEq(_bridge.balanceOf(_alice), 1);
+    }
+
+    function testBatchMintSkipsOrdersOfOtherTokens() public {
+        MintOrder[] memory orders = new MintOrder[](2);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        orders[0].toERC20 = address(42);
+        orders[1] = _createMintOrder(_inscriptionId(2), 1);
+        orders[1].amount = 2;
+
+        uint8[] memory processedOrders = _batchMint(orders);
+
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_TOKENS_NOT_BRIDGED());
+        assertEq(processedOrders[1], _bridge.MINT_ERROR_CODE_TOKENS_NOT_BRIDGED());
+        assertEq(_bridge.balanceOf(_alice), 0);
+    }
+
+    function testBatchMintSkipsUnexpectedChainId() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        orders[0].recipientChainID = _CHAIN_ID + 1;
+
+        uint8[] memory processedOrders = _batchMint(orders);
+
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_UNEXPECTED_RECIPIENT_CHAIN_ID());
+    }
+
+    function testCancelledOrderIsNotMinted() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+
+        vm.prank(_owner);
+        _bridge.cancelMintOrder(orders[0].senderID, 0);
+
+        uint8[] memory processedOrders = _batchMint(orders);
+        assertEq(processedOrders[0], _bridge.MINT_ERROR_CODE_USED_NONCE());
+    }
+
+    function testBurn() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        _batchMint(orders);
+
+        uint256 tokenId = uint256(_inscriptionId(1));
+        bytes memory recipientID = bytes("bcrt1q8whf4tdmhwdnpz4z7n5wqwcd0wwhxjnyd2s0gc");
+        bytes32 memo = bytes32(uint256(7));
+
+        vm.expectEmit(address(_bridge));
+        emit BurnTokenEvent(
+            _alice, 1, address(_bridge), recipientID, _inscriptionId(1), 0, bytes32(0), bytes16(0), 0, memo
+        );
+
+        vm.prank(_alice);
+        uint32 operationID = _bridge.burn(tokenId, recipientID, memo);
+
+        assertEq(operationID, 0);
+        assertEq(_bridge.balanceOf(_alice), 0);
+        vm.expectRevert(abi.encodeWithSelector(IERC721Errors.ERC721NonexistentToken.selector, tokenId));
+        _bridge.ownerOf(tokenId);
+    }
+
+    function testBurnByApprovedOperator() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        _batchMint(orders);
+
+        uint256 tokenId = uint256(_inscriptionId(1));
+        vm.prank(_alice);
+        _bridge.approve(_bob, tokenId);
+
+        vm.prank(_bob);
+        _bridge.burn(tokenId, bytes("bcrt1q8whf4tdmhwdnpz4z7n5wqwcd0wwhxjnyd2s0gc"), bytes32(0));
+
+        assertEq(_bridge.balanceOf(_alice), 0);
+    }
+
+    function testBurnByNotOwnerShouldFail() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        _batchMint(orders);
+
+        uint256 tokenId = uint256(_inscriptionId(1));
+        vm.expectRevert(abi.encodeWithSelector(IERC721Errors.ERC721InsufficientApproval.selector, _bob, tokenId));
+        vm.prank(_bob);
+        _bridge.burn(tokenId, bytes("bcrt1q8whf4tdmhwdnpz4z7n5wqwcd0wwhxjnyd2s0gc"), bytes32(0));
+    }
+
+    function testMintAndBurnAreRejectedWhenPaused() public {
+        MintOrder[] memory orders = new MintOrder[](1);
+        orders[0] = _createMintOrder(_inscriptionId(1), 0);
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+
+        vm.prank(_owner);
+        _bridge.pause();
+
+        vm.expectRevert(abi.encodeWithSignature("EnforcedPause()"));
+        _bridge.batchMint(encodedOrders, signature, new uint32[](0));
+
+        vm.expectRevert(abi.encodeWithSignature("EnforcedPause()"));
+        vm.prank(_alice);
+        _bridge.burn(uint256(_inscriptionId(1)), bytes("bcrt1q8whf4tdmhwdnpz4z7n5wqwcd0wwhxjnyd2s0gc"), bytes32(0));
+    }
+
+    function _batchMint(
+        MintOrder[] memory orders
+    ) private returns (uint8[] memory) {
+        bytes memory encodedOrders = _batchMintOrders(orders);
+        bytes memory signature = _batchMintOrdersSignature(encodedOrders, _OWNER_KEY);
+
+        return _bridge.batchMint(encodedOrders, signature, new uint32[](0));
+    }
+
+    function _createMintOrder(bytes32 inscriptionId, uint32 nonce) private view returns (MintOrder memory order) {
+        order.amount = 1;
+        order.senderID = _createIdFromAddress(_alice, 0);
+        order.fromTokenID = inscriptionId;
+        order.recipient = _alice;
+        order.toERC20 = address(_bridge);
+        order.nonce = nonce;
+        order.recipientChainID = _CHAIN_ID;
+    }
+
+    function _batchMintOrders(
+        MintOrder[] memory orders
+    ) private pure returns (bytes memory encodedOrders) {
+        for (uint256 i = 0; i < orders.length; i += 1) {
+            encodedOrders = abi.encodePacked(encodedOrders, _encodeOrder(orders[i]));
+        }
+    }
+
+    function _encodeOrder(
+        MintOrder memory order
+    ) private pure returns (bytes memory) {
+        return abi.encodePacked(
+            order.amount,
+            order.senderID,
+            order.fromTokenID,
+            order.recipient,
+            order.toERC20,
+            order.nonce,
+            uint32(0),
+            order.recipientChainID,
+            bytes32("Inscription #1"),
+            bytes16("ORD"),
+            uint8(0),
+            address(0),
+            uint256(0),
+            address(0)
+        );
+    }
+
+    function _batchMintOrdersSignature(
+        bytes memory encodedOrders,
+        uint256 privateKey
+    ) private pure returns (bytes memory) {
+        bytes32 hash = keccak256(encodedOrders);
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(privateKey, hash);
+
+        return abi.encodePacked(r, s, v);
+    }
+
+    function _inscriptionId(
+        uint8 txid
+    ) private pure returns (bytes32) {
+        return bytes32(abi.encodePacked(uint8(5), bytes31(uint248(txid))));
+    }
+
+    function _createIdFromAddress(address addr, uint32 chainID) private pure returns (bytes32) {
+        return bytes32(abi.encodePacked(uint8(1), chainID, addr));
+    }
+}
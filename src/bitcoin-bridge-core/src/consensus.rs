@@ -0,0 +1,156 @@
+use std::fmt::Debug;
+use std::future::Future;
+
+use thiserror::Error;
+
+/// Errors of the indexer consensus.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConsensusError {
+    #[error(
+        "indexers returned different result for same request: {first_response}; {another_response}"
+    )]
+    IndexersDisagree {
+        first_response: String,
+        another_response: String,
+    },
+    #[error(
+        "insufficient consensus from indexers: {received_responses}/{required_responses} responses received, {checked_indexers} indexers checked"
+    )]
+    InsufficientConsensus {
+        received_responses: usize,
+        required_responses: u8,
+        checked_indexers: usize,
+    },
+}
+
+/// Sends the request to the indexers one by one and returns the response they agree on.
+///
+/// Fails as soon as an indexer returns a response different from the first one, or when too few
+/// indexers are left to reach the `consensus_threshold` after a failed request.
+pub async fn get_indexer_consensus<I, T, E, F>(
+    indexers: impl ExactSizeIterator<Item = I>,
+    consensus_threshold: u8,
+    request: impl Fn(I) -> F,
+) -> Result<T, ConsensusError>
+where
+    T: Debug + PartialEq,
+    E: Debug,
+    F: Future<Output = Result<T, E>>,
+{
+    let indexers_count = indexers.len();
+    let mut first_result = None;
+    let mut requested = 0;
+    let mut received_responses = 0;
+    for indexer in indexers {
+        let result = request(indexer).await;
+        requested += 1;
+        if let Ok(response) = result {
+            match first_result {
+                None => first_result = Some(response),
+                Some(ref r) => {
+                    received_responses += 1;
+                    if *r != response {
+                        return Err(ConsensusError::IndexersDisagree {
+                            first_response: format!("{r:?}"),
+                            another_response: format!("{response:?}"),
+                        });
+                    }
+                }
+            }
+        } else {
+            log::warn!("Indexer responded with {result:?}");
+            if (indexers_count as u8).saturating_sub(requested)
+                < consensus_threshold.saturating_sub(received_responses)
+            {
+                return Err(ConsensusError::InsufficientConsensus {
+                    received_responses: received_responses as usize,
+                    required_responses: consensus_threshold,
+                    checked_indexers: requested as usize,
+                });
+            }
+        }
+    }
+
+    match first_result {
+        Some(v) => Ok(v),
+        None => {
+            // This means that `consensus_threshold` is 0 and none of the indexers are online
+            // or that there are not indexers configured
+            Err(ConsensusError::InsufficientConsensus {
+                received_responses: 0,
+                required_responses: consensus_threshold,
+                checked_indexers: indexers_count,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn consensus(
+        responses: &[Result<u8, &'static str>],
+        consensus_threshold: u8,
+    ) -> Result<u8, ConsensusError> {
+        get_indexer_consensus(
+            responses.iter(),
+            consensus_threshold,
+            |response| async move { *response },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_should_return_agreed_response() {
+        assert_eq!(consensus(&[Ok(1), Ok(1), Ok(1)], 2).await, Ok(1));
+    }
+
+    #[tokio::test]
+    async fn test_should_fail_if_indexers_disagree() {
+        assert_eq!(
+            consensus(&[Ok(1), Ok(2), Ok(1)], 1).await,
+            Err(ConsensusError::IndexersDisagree {
+                first_response: "1".to_string(),
+                another_response: "2".to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_tolerate_failures_within_threshold() {
+        assert_eq!(consensus(&[Err("offline"), Ok(1), Ok(1)], 1).await, Ok(1));
+    }
+
+    #[tokio::test]
+    async fn test_should_stop_when_threshold_is_unreachable() {
+        let requests = std::cell::Cell::new(0);
+        let result = get_indexer_consensus([1, 2, 3].into_iter(), 3, |_| {
+            requests.set(requests.get() + 1);
+            async { Err::<u8, _>("offline") }
+        })
+        .await;
+
+        assert_eq!(
+            result,
+            Err(ConsensusError::InsufficientConsensus {
+                received_responses: 0,
+                required_responses: 3,
+                checked_indexers: 1,
+            })
+        );
+        assert_eq!(requests.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_should_fail_without_indexers() {
+        assert_eq!(
+            consensus(&[], 0).await,
+            Err(ConsensusError::InsufficientConsensus {
+                received_responses: 0,
+                required_responses: 0,
+                checked_indexers: 0,
+            })
+        );
+    }
+}
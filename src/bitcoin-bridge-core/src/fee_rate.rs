@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use bitcoin::FeeRate;
+use ic_exports::ic_kit::ic;
+
+/// Bitcoin fee rate cached by the canister.
+pub struct FeeRateState {
+    fee_rate: FeeRate,
+    /// Last update timestamp in nanoseconds
+    last_update_timestamp: u64,
+}
+
+impl Default for FeeRateState {
+    fn default() -> Self {
+        Self {
+            fee_rate: FeeRate::ZERO,
+            last_update_timestamp: 0,
+        }
+    }
+}
+
+impl FeeRateState {
+    /// Update fee rate and the last update timestamp.
+    pub fn update(&mut self, fee_rate: FeeRate) {
+        self.fee_rate = fee_rate;
+        self.last_update_timestamp = ic::time();
+    }
+
+    /// Cached fee rate, zero if it was never updated.
+    pub fn fee_rate(&self) -> FeeRate {
+        self.fee_rate
+    }
+
+    /// Timestamp of the last update in nanoseconds, zero if it was never updated.
+    pub fn last_update_timestamp(&self) -> u64 {
+        self.last_update_timestamp
+    }
+
+    /// Elapsed time since the last fee rate update.
+    pub fn last_update_elapsed(&self) -> Duration {
+        ic::time()
+            .checked_sub(self.last_update_timestamp)
+            .map(Duration::from_nanos)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    #[test]
+    fn test_should_update_fee_rate() {
+        let ctx = MockContext::new().inject();
+        let mut state = FeeRateState::default();
+        assert_eq!(state.fee_rate(), FeeRate::ZERO);
+        assert_eq!(state.last_update_timestamp(), 0);
+
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        state.update(fee_rate);
+        ctx.add_time(Duration::from_secs(5).as_nanos() as u64);
+
+        assert_eq!(state.fee_rate(), fee_rate);
+        assert_eq!(state.last_update_elapsed(), Duration::from_secs(5));
+    }
+}
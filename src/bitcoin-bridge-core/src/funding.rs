@@ -0,0 +1,156 @@
+use bitcoin::FeeRate;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ord_rs::wallet::TxInputInfo;
+
+/// Minimum value of a BTC output to be accepted by the network (satoshis)
+pub const DUST_THRESHOLD: u64 = 546;
+
+/// Minimum fee rate increase required by the nodes to relay a replacement transaction (sat/vB)
+pub const INCREMENTAL_RELAY_FEE_RATE: u64 = 1;
+
+/// Utxo which can fund a transaction.
+pub trait FundingUtxo: Clone {
+    /// Value of the utxo in satoshis.
+    fn value(&self) -> u64;
+}
+
+impl FundingUtxo for Utxo {
+    fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl FundingUtxo for TxInputInfo {
+    fn value(&self) -> u64 {
+        self.tx_out.value.to_sat()
+    }
+}
+
+/// Returns the candidate funding inputs of a transaction, from the fewest utxos to all of them:
+/// the biggest utxo, the two biggest ones and so on.
+pub fn greedy_funding_candidates<U: FundingUtxo>(
+    mut utxos: Vec<U>,
+) -> impl Iterator<Item = Vec<U>> {
+    // sort the utxos by value; descending
+    utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.value()));
+
+    (1..=utxos.len()).map(move |count| utxos[..count].to_vec())
+}
+
+/// Selects the biggest utxos until their value covers the required amount, which depends on the
+/// number of the selected utxos.
+///
+/// Returns `None` if all the utxos together are not enough.
+pub fn select_funding_utxos<U: FundingUtxo>(
+    utxos: Vec<U>,
+    required_value: impl Fn(usize) -> u64,
+) -> Option<Vec<U>> {
+    greedy_funding_candidates(utxos).find(|candidate| {
+        let value = candidate.iter().map(FundingUtxo::value).sum::<u64>();
+        value >= required_value(candidate.len())
+    })
+}
+
+/// Splits the fee between the participants of a batch transaction pro-rata to their weights.
+/// The sum of the shares is always equal to the fee.
+pub fn split_fee(fee: u64, weights: &[u64]) -> Vec<u64> {
+    let total_weight: u64 = weights.iter().sum();
+    if total_weight == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let mut shares: Vec<u64> = weights
+        .iter()
+        .map(|weight| (fee as u128 * *weight as u128 / total_weight as u128) as u64)
+        .collect();
+
+    let mut remainder = fee - shares.iter().sum::<u64>();
+    for share in shares.iter_mut() {
+        if remainder == 0 {
+            break;
+        }
+
+        *share += 1;
+        remainder -= 1;
+    }
+
+    shares
+}
+
+/// Fee of a transaction replacing the one with the given fee and virtual size.
+///
+/// The replacement pays at least the current fee rate and, as required by BIP-125, more than
+/// the replaced transaction plus the relay fee for its own size.
+pub fn replacement_fee(replaced_fee: u64, vsize: u64, fee_rate: FeeRate) -> u64 {
+    let current_fee = fee_rate.to_sat_per_vb_ceil() * vsize;
+    current_fee.max(replaced_fee + vsize * INCREMENTAL_RELAY_FEE_RATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+
+    fn utxo(id: u8, value: u64) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![id; 32],
+                vout: 0,
+            },
+            value,
+            height: 0,
+        }
+    }
+
+    #[test]
+    fn test_should_list_greedy_funding_candidates() {
+        let candidates: Vec<Vec<u64>> =
+            greedy_funding_candidates(vec![utxo(1, 10), utxo(2, 30), utxo(3, 20)])
+                .map(|candidate| candidate.iter().map(|utxo| utxo.value).collect())
+                .collect();
+
+        assert_eq!(candidates, vec![vec![30], vec![30, 20], vec![30, 20, 10]]);
+    }
+
+    #[test]
+    fn test_should_select_fewest_funding_utxos() {
+        let utxos = vec![utxo(1, 1_000), utxo(2, 5_000), utxo(3, 3_000)];
+
+        // the required value grows with the number of inputs
+        let selected =
+            select_funding_utxos(utxos.clone(), |count| 6_000 + 500 * count as u64).unwrap();
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].value, 5_000);
+        assert_eq!(selected[1].value, 3_000);
+
+        assert!(select_funding_utxos(utxos, |_| 10_000).is_none());
+        assert!(select_funding_utxos(vec![], |_| 0).is_none());
+    }
+
+    #[test]
+    fn test_should_split_fee_pro_rata() {
+        assert_eq!(split_fee(1000, &[1, 1]), vec![500, 500]);
+        assert_eq!(split_fee(1000, &[3, 2]), vec![600, 400]);
+        assert_eq!(split_fee(1000, &[1, 1, 1]), vec![334, 333, 333]);
+        assert_eq!(split_fee(0, &[1, 2]), vec![0, 0]);
+        assert_eq!(split_fee(1000, &[0, 0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_split_fee_should_sum_up_to_the_fee() {
+        let weights = [3, 7, 2, 5, 11];
+        for fee in [1, 17, 999, 123_457] {
+            assert_eq!(split_fee(fee, &weights).iter().sum::<u64>(), fee);
+        }
+    }
+
+    #[test]
+    fn test_replacement_fee_should_cover_relay_fee() {
+        let fee_rate = FeeRate::from_sat_per_vb(2).unwrap();
+        // the current fee rate is higher than the one of the replaced transaction
+        assert_eq!(replacement_fee(100, 200, fee_rate), 400);
+        // the fee rate didn't change, but the replacement must pay more
+        assert_eq!(replacement_fee(400, 200, fee_rate), 600);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::iter;
+
+use bridge_did::init::IndexerHeader;
+use bridge_utils::http::{http_request_cycles, indexer_transform_context};
+use ic_exports::ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod, http_request,
+};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+/// Errors of the indexer requests.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IndexerRequestError {
+    #[error("indexer unavailable: {0}")]
+    Unavailable(String),
+    #[error("unexpected response from indexer: {0}")]
+    InvalidResponse(String),
+}
+
+/// Trait for a generic HTTP client that can be used to make requests to the indexer.
+pub trait HttpClient {
+    fn http_request<R: DeserializeOwned>(
+        &self,
+        url: &str,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> impl Future<Output = Result<R, IndexerRequestError>> + Send;
+}
+
+/// HTTP client implementation for the Internet Computer canisters.
+pub struct IcHttpClient {
+    /// Headers sent with the requests to each indexer url in addition to `Accept`.
+    headers: HashMap<String, Vec<IndexerHeader>>,
+}
+
+impl IcHttpClient {
+    pub fn new(headers: HashMap<String, Vec<IndexerHeader>>) -> Self {
+        Self { headers }
+    }
+
+    /// Headers of the requests to the given indexer url.
+    fn request_headers(&self, url: &str) -> Vec<HttpHeader> {
+        iter::once(HttpHeader {
+            name: "Accept".to_string(),
+            value: "application/json".to_string(),
+        })
+        .chain(
+            self.headers
+                .get(url)
+                .into_iter()
+                .flatten()
+                .map(|header| HttpHeader {
+                    name: header.name.clone(),
+                    value: header.value.clone(),
+                }),
+        )
+        .collect()
+    }
+}
+
+impl HttpClient for IcHttpClient {
+    async fn http_request<R: DeserializeOwned>(
+        &self,
+        url: &str,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> Result<R, IndexerRequestError> {
+        let headers = self.request_headers(url);
+        let url = format!("{url}/{}", uri.trim_start_matches('/'));
+
+        log::trace!("Sending indexer request to: {url}");
+
+        let request_params = CanisterHttpRequestArgument {
+            url,
+            max_response_bytes: Some(max_response_bytes),
+            method: HttpMethod::GET,
+            headers,
+            body: None,
+            transform: Some(indexer_transform_context()),
+        };
+
+        let result = http_request(request_params, http_request_cycles(max_response_bytes))
+            .await
+            .map_err(|err| IndexerRequestError::Unavailable(format!("{err:?}")))?
+            .0;
+
+        log::trace!(
+            "Indexer responded with: {} {:?} BODY: {}",
+            result.status,
+            result.headers,
+            String::from_utf8_lossy(&result.body)
+        );
+
+        serde_json::from_slice(&result.body).map_err(|err| {
+            log::error!("Failed to parse the indexer response: {err:?}");
+            IndexerRequestError::InvalidResponse(format!("{err:?}"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_send_headers_of_the_indexer() {
+        let client = IcHttpClient::new(HashMap::from([(
+            "https://indexer.com".to_string(),
+            vec![IndexerHeader {
+                name: "x-api-key".to_string(),
+                value: "secret".to_string(),
+            }],
+        )]));
+
+        let headers = client.request_headers("https://indexer.com");
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].name, "Accept");
+        assert_eq!(headers[1].name, "x-api-key");
+        assert_eq!(headers[1].value, "secret");
+
+        let headers = client.request_headers("https://another-indexer.com");
+        assert_eq!(headers.len(), 1);
+    }
+}
@@ -0,0 +1,894 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use bitcoin::address::Error as BitcoinAddressError;
+use bitcoin::bip32::{ChainCode, ChildNumber, DerivationPath, Error as Bip32Error, Xpub};
+use bitcoin::hashes::Hash as _;
+use bitcoin::key::TapTweak as _;
+use bitcoin::psbt::Psbt;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{Error as Secp256Error, Keypair, Message, Secp256k1, schnorr};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::{
+    Address, EcdsaSighashType, Network, PrivateKey, PublicKey, TapSighashType, Transaction, TxOut,
+    Witness, XOnlyPublicKey, ecdsa, taproot,
+};
+use bridge_did::schnorr::{
+    ManagementCanisterSignatureReply, ManagementCanisterSignatureRequest, SchnorrKeyId,
+    SignWithSchnorrAux,
+};
+use candid::Principal;
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::ecdsa::{SignWithEcdsaArgument, sign_with_ecdsa};
+use ord_rs::wallet::{LocalSigner, ScriptType, TxInputInfo};
+use ord_rs::{BtcTxSigner, OrdError, OrdResult};
+use thiserror::Error;
+
+use crate::master_key::{MasterKey, SchnorrMasterKey};
+
+/// Key result type
+pub type KeyResult<T> = Result<T, KeyError>;
+
+/// Key and signer error types
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum KeyError {
+    #[error("bip32 error: {0}")]
+    Bip32(#[from] Bip32Error),
+    #[error("failed to derive address: {0}")]
+    BitcoinAddress(#[from] BitcoinAddressError),
+    #[error("invalid chain code")]
+    InvalidChainCode,
+    #[error("invalid derivation path")]
+    InvalidDerivationPath,
+    #[error("invalid public key")]
+    InvalidPublicKey,
+    #[error("ord error error: {0}")]
+    OrdError(String),
+    #[error("invalid PSBT: {0}")]
+    Psbt(String),
+    #[error("secp256 error: {0}")]
+    Secp256(#[from] Secp256Error),
+    #[error("signer not initialized")]
+    SignerNotInitialized,
+    #[error("failed to sign transaction: {0}")]
+    Signing(String),
+}
+
+impl From<OrdError> for KeyError {
+    fn from(e: OrdError) -> Self {
+        KeyError::OrdError(e.to_string())
+    }
+}
+
+pub const DERIVATION_PATH_PREFIX: u8 = 7;
+
+/// Cycles attached to the `sign_with_schnorr` management canister calls.
+const SIGN_WITH_SCHNORR_CYCLES: u64 = 25_000_000_000;
+
+/// Type of the addresses the canister receives and holds the bitcoins at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    /// Segwit v0 addresses of the ECDSA keys.
+    P2wpkh,
+    /// Taproot addresses of the BIP-340 keys, spent by the key path.
+    P2tr,
+}
+
+impl AddressType {
+    /// Script type to estimate the witness size of the inputs held at the addresses of this type.
+    ///
+    /// A P2WPKH witness, the ECDSA signature and the public key, is estimated as `P2WSH`.
+    pub fn script_type(self) -> ScriptType {
+        match self {
+            AddressType::P2wpkh => ScriptType::P2WSH,
+            AddressType::P2tr => ScriptType::P2TR,
+        }
+    }
+}
+
+/// ECDSA signer of the IC. The public keys are derived locally from the master key, the
+/// signatures are requested from the management canister.
+pub struct IcEcdsaSigner {
+    master_key: MasterKey,
+    network: Network,
+}
+
+impl IcEcdsaSigner {
+    pub fn new(master_key: MasterKey, network: Network) -> Self {
+        Self {
+            master_key,
+            network,
+        }
+    }
+
+    /// Returns the public key derived from the master key with the given derivation path.
+    pub fn public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
+        derive_public_key(
+            self.master_key.public_key().expect("invalid pubkey"),
+            self.master_key.chain_code(),
+            self.network,
+            derivation_path,
+        )
+        .map_err(|_| OrdError::Custom("Failed to derive public key".to_string()))
+    }
+
+    /// Signs the message with the key of the given derivation path.
+    pub async fn sign(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<Signature, Secp256Error> {
+        let request = SignWithEcdsaArgument {
+            message_hash: message.as_ref().to_vec(),
+            derivation_path: derivation_path_to_ic(derivation_path.clone()),
+            key_id: self.master_key.key_id.clone(),
+        };
+
+        let response = sign_with_ecdsa(request)
+            .await
+            .expect("sign_with_ecdsa failed")
+            .0;
+
+        Signature::from_compact(&response.signature)
+    }
+}
+
+#[async_trait]
+impl BtcTxSigner for IcEcdsaSigner {
+    async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
+        self.public_key(derivation_path)
+    }
+
+    async fn sign_with_ecdsa(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<Signature, Secp256Error> {
+        self.sign(message, derivation_path).await
+    }
+
+    async fn schnorr_public_key(
+        &self,
+        _derivation_path: &DerivationPath,
+    ) -> OrdResult<XOnlyPublicKey> {
+        Err(OrdError::Custom(
+            "ECDSA signer has no BIP-340 keys".to_string(),
+        ))
+    }
+
+    async fn sign_with_schnorr(
+        &self,
+        _message: Message,
+        _derivation_path: &DerivationPath,
+    ) -> Result<schnorr::Signature, Secp256Error> {
+        Err(Secp256Error::IncorrectSignature)
+    }
+}
+
+/// Requests the BIP-340 signature of the message from the management canister.
+pub async fn sign_with_ic_schnorr(
+    key_id: &SchnorrKeyId,
+    message: Message,
+    derivation_path: &DerivationPath,
+    aux: Option<SignWithSchnorrAux>,
+) -> Result<schnorr::Signature, Secp256Error> {
+    let request = ManagementCanisterSignatureRequest {
+        message: message.as_ref().to_vec(),
+        derivation_path: derivation_path_to_ic(derivation_path.clone()),
+        key_id: key_id.clone(),
+        aux,
+    };
+
+    let (reply,): (ManagementCanisterSignatureReply,) =
+        ic_exports::ic_cdk::api::call::call_with_payment(
+            Principal::management_canister(),
+            "sign_with_schnorr",
+            (request,),
+            SIGN_WITH_SCHNORR_CYCLES,
+        )
+        .await
+        .map_err(|e| {
+            log::error!("Failed to call sign_with_schnorr: {:?}", e);
+            Secp256Error::InvalidSignature
+        })?;
+
+    schnorr::Signature::from_slice(&reply.signature)
+}
+
+/// BIP-340 key of the IC signer, used for the taproot addresses.
+pub struct IcTaprootKey {
+    pub master_key: SchnorrMasterKey,
+    pub key_id: SchnorrKeyId,
+}
+
+/// Signer of the IC holding the bitcoins at the P2WPKH addresses of the ECDSA keys and,
+/// if the taproot key is configured, at the P2TR addresses spent by the key path.
+pub struct IcBtcSigner {
+    ecdsa: IcEcdsaSigner,
+    network: Network,
+    taproot_key: Option<IcTaprootKey>,
+}
+
+impl IcBtcSigner {
+    pub fn new(master_key: MasterKey, network: Network, taproot_key: Option<IcTaprootKey>) -> Self {
+        Self {
+            ecdsa: IcEcdsaSigner::new(master_key, network),
+            network,
+            taproot_key,
+        }
+    }
+
+    /// Type of the addresses new deposits and change are received at.
+    pub fn address_type(&self) -> AddressType {
+        match self.taproot_key {
+            Some(_) => AddressType::P2tr,
+            None => AddressType::P2wpkh,
+        }
+    }
+
+    fn taproot_key(&self) -> OrdResult<&IcTaprootKey> {
+        self.taproot_key
+            .as_ref()
+            .ok_or_else(|| OrdError::Custom("Schnorr key is not configured".to_string()))
+    }
+}
+
+#[async_trait]
+impl BtcTxSigner for IcBtcSigner {
+    async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
+        self.ecdsa.public_key(derivation_path)
+    }
+
+    async fn sign_with_ecdsa(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<Signature, Secp256Error> {
+        self.ecdsa.sign(message, derivation_path).await
+    }
+
+    /// Returns the internal key of the taproot output. The key is derived locally from the
+    /// schnorr master key, the same way as the ECDSA keys.
+    async fn schnorr_public_key(
+        &self,
+        derivation_path: &DerivationPath,
+    ) -> OrdResult<XOnlyPublicKey> {
+        let taproot_key = self.taproot_key()?;
+        let public_key = derive_public_key(
+            taproot_key.master_key.public_key(),
+            taproot_key.master_key.chain_code(),
+            self.network,
+            derivation_path,
+        )
+        .map_err(|_| OrdError::Custom("Failed to derive schnorr public key".to_string()))?;
+
+        Ok(public_key.inner.x_only_public_key().0)
+    }
+
+    /// Signs the message with the key tweaked as specified in BIP-341 for the outputs without a
+    /// script tree, so the signature spends the taproot output by the key path.
+    async fn sign_with_schnorr(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<schnorr::Signature, Secp256Error> {
+        let taproot_key = self.taproot_key().map_err(|e| {
+            log::error!("Failed to sign with schnorr: {e}");
+            Secp256Error::InvalidSignature
+        })?;
+
+        sign_with_ic_schnorr(
+            &taproot_key.key_id,
+            message,
+            derivation_path,
+            Some(SignWithSchnorrAux::Bip341 {
+                merkle_root_hash: vec![],
+            }),
+        )
+        .await
+    }
+}
+
+/// Signer holding the private key in the canister. Used for testing only.
+pub struct LocalBtcSigner {
+    signer: LocalSigner,
+    keypair: Keypair,
+    address_type: AddressType,
+}
+
+impl LocalBtcSigner {
+    pub fn new(private_key: PrivateKey, address_type: AddressType) -> Self {
+        Self {
+            signer: LocalSigner::new(private_key),
+            keypair: Keypair::from_secret_key(&Secp256k1::new(), &private_key.inner),
+            address_type,
+        }
+    }
+
+    /// Type of the addresses new deposits and change are received at.
+    pub fn address_type(&self) -> AddressType {
+        self.address_type
+    }
+}
+
+#[async_trait]
+impl BtcTxSigner for LocalBtcSigner {
+    async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
+        self.signer.ecdsa_public_key(derivation_path).await
+    }
+
+    async fn sign_with_ecdsa(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<Signature, Secp256Error> {
+        self.signer.sign_with_ecdsa(message, derivation_path).await
+    }
+
+    async fn schnorr_public_key(
+        &self,
+        _derivation_path: &DerivationPath,
+    ) -> OrdResult<XOnlyPublicKey> {
+        Ok(self.keypair.x_only_public_key().0)
+    }
+
+    async fn sign_with_schnorr(
+        &self,
+        message: Message,
+        _derivation_path: &DerivationPath,
+    ) -> Result<schnorr::Signature, Secp256Error> {
+        let secp = Secp256k1::new();
+        let keypair = self.keypair.tap_tweak(&secp, None).to_inner();
+
+        Ok(secp.sign_schnorr_no_aux_rand(&message, &keypair))
+    }
+}
+
+/// Signs all the inputs of the transaction.
+///
+/// P2TR inputs are spent by the key path with BIP-340 signatures, all the other inputs are
+/// expected to be P2WPKH outputs of the ECDSA keys.
+pub async fn sign_transaction(
+    signer: &impl BtcTxSigner,
+    unsigned_tx: &Transaction,
+    inputs: &[TxInputInfo],
+) -> KeyResult<Transaction> {
+    let sighashes = input_sighashes(unsigned_tx, inputs)?;
+    let mut witnesses = Vec::with_capacity(inputs.len());
+    for (input, sighash) in inputs.iter().zip(sighashes) {
+        let witness = if input.tx_out.script_pubkey.is_p2tr() {
+            let sig = signer
+                .sign_with_schnorr(sighash, &input.derivation_path)
+                .await?;
+
+            Witness::p2tr_key_spend(&taproot::Signature {
+                sig,
+                hash_ty: TapSighashType::Default,
+            })
+        } else {
+            let public_key = signer.ecdsa_public_key(&input.derivation_path).await?;
+            let sig = signer
+                .sign_with_ecdsa(sighash, &input.derivation_path)
+                .await?;
+
+            Witness::p2wpkh(
+                &ecdsa::Signature {
+                    sig,
+                    hash_ty: EcdsaSighashType::All,
+                },
+                &public_key.inner,
+            )
+        };
+
+        witnesses.push(witness);
+    }
+
+    let mut tx = unsigned_tx.clone();
+    for (tx_in, witness) in tx.input.iter_mut().zip(witnesses) {
+        tx_in.witness = witness;
+    }
+
+    Ok(tx)
+}
+
+/// Returns the hashes signed for the inputs of the transaction: BIP-341 key path hashes of the
+/// P2TR inputs and BIP-143 hashes of the P2WPKH inputs.
+pub fn input_sighashes(
+    unsigned_tx: &Transaction,
+    inputs: &[TxInputInfo],
+) -> KeyResult<Vec<Message>> {
+    if unsigned_tx.input.len() != inputs.len() {
+        return Err(KeyError::Signing(
+            "number of inputs does not match the transaction".to_string(),
+        ));
+    }
+
+    let prevouts: Vec<TxOut> = inputs.iter().map(|input| input.tx_out.clone()).collect();
+    let mut sighash_cache = SighashCache::new(unsigned_tx);
+    let mut sighashes = Vec::with_capacity(inputs.len());
+    for (index, input) in inputs.iter().enumerate() {
+        let sighash = if input.tx_out.script_pubkey.is_p2tr() {
+            sighash_cache
+                .taproot_key_spend_signature_hash(
+                    index,
+                    &Prevouts::All(&prevouts),
+                    TapSighashType::Default,
+                )
+                .map_err(|e| KeyError::Signing(e.to_string()))?
+                .to_byte_array()
+        } else {
+            sighash_cache
+                .p2wpkh_signature_hash(
+                    index,
+                    &input.tx_out.script_pubkey,
+                    input.tx_out.value,
+                    EcdsaSighashType::All,
+                )
+                .map_err(|e| KeyError::Signing(e.to_string()))?
+                .to_byte_array()
+        };
+
+        sighashes.push(Message::from_digest(sighash));
+    }
+
+    Ok(sighashes)
+}
+
+/// Checks that every input of the PSBT is signed by at least `required_signatures` of the
+/// co-signer keys.
+///
+/// The co-signers sign the same hashes as the canister, see [`input_sighashes`], with ECDSA and
+/// `SIGHASH_ALL`, and put the signatures into the partial signatures of the inputs.
+pub fn verify_cosignatures(
+    psbt: &Psbt,
+    inputs: &[TxInputInfo],
+    cosigners: &[PublicKey],
+    required_signatures: usize,
+) -> KeyResult<()> {
+    let secp = Secp256k1::verification_only();
+    let sighashes = input_sighashes(&psbt.unsigned_tx, inputs)?;
+    for (index, (psbt_input, sighash)) in psbt.inputs.iter().zip(&sighashes).enumerate() {
+        let signatures = cosigners
+            .iter()
+            .filter(|cosigner| {
+                psbt_input
+                    .partial_sigs
+                    .get(*cosigner)
+                    .is_some_and(|signature| {
+                        signature.hash_ty == EcdsaSighashType::All
+                            && secp
+                                .verify_ecdsa(sighash, &signature.sig, &cosigner.inner)
+                                .is_ok()
+                    })
+            })
+            .count();
+
+        if signatures < required_signatures {
+            return Err(KeyError::Psbt(format!(
+                "input {index} has {signatures} of {required_signatures} required co-signatures"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the transit address of the given type of the user for the keys of the signer.
+pub async fn signer_transit_address(
+    signer: &impl BtcTxSigner,
+    eth_address: &H160,
+    network: Network,
+    address_type: AddressType,
+) -> KeyResult<Address> {
+    let derivation_path = get_derivation_path(eth_address)?;
+    match address_type {
+        AddressType::P2wpkh => {
+            let public_key = signer.ecdsa_public_key(&derivation_path).await?;
+            Ok(Address::p2wpkh(&public_key, network)?)
+        }
+        AddressType::P2tr => {
+            let internal_key = signer.schnorr_public_key(&derivation_path).await?;
+            Ok(Address::p2tr(
+                &Secp256k1::new(),
+                internal_key,
+                None,
+                network,
+            ))
+        }
+    }
+}
+
+/// Builds the PSBT (BIP-174) of the unsigned transaction spending the given inputs.
+///
+/// Every input carries its previous output and the origin of its key: the fingerprint of the
+/// master key and the derivation path, so an external signer can verify and sign it. The keys of
+/// the P2TR inputs are derived from the schnorr master key, the keys of the other inputs from
+/// the ECDSA master key.
+pub fn build_psbt(
+    unsigned_tx: &Transaction,
+    inputs: &[TxInputInfo],
+    network: Network,
+    ecdsa_master_key: Option<(PublicKey, ChainCode)>,
+    schnorr_master_key: Option<SchnorrMasterKey>,
+) -> KeyResult<Psbt> {
+    if unsigned_tx.input.len() != inputs.len() {
+        return Err(KeyError::Psbt(
+            "number of inputs does not match the transaction".to_string(),
+        ));
+    }
+
+    let mut psbt =
+        Psbt::from_unsigned_tx(unsigned_tx.clone()).map_err(|e| KeyError::Psbt(e.to_string()))?;
+    for (psbt_input, input) in psbt.inputs.iter_mut().zip(inputs) {
+        psbt_input.witness_utxo = Some(input.tx_out.clone());
+
+        if input.tx_out.script_pubkey.is_p2tr() {
+            let master_key = schnorr_master_key.ok_or(KeyError::SignerNotInitialized)?;
+            let master_xpub =
+                master_xpub(master_key.public_key(), master_key.chain_code(), network)?;
+            let internal_key = master_xpub
+                .derive_pub(&Secp256k1::new(), &input.derivation_path)?
+                .public_key
+                .x_only_public_key()
+                .0;
+
+            psbt_input.tap_internal_key = Some(internal_key);
+            psbt_input.tap_key_origins = BTreeMap::from([(
+                internal_key,
+                (
+                    vec![],
+                    (master_xpub.fingerprint(), input.derivation_path.clone()),
+                ),
+            )]);
+        } else {
+            let (public_key, chain_code) =
+                ecdsa_master_key.ok_or(KeyError::SignerNotInitialized)?;
+            let master_xpub = master_xpub(public_key, chain_code, network)?;
+            let public_key = master_xpub
+                .derive_pub(&Secp256k1::new(), &input.derivation_path)?
+                .public_key;
+
+            psbt_input.bip32_derivation = BTreeMap::from([(
+                public_key,
+                (master_xpub.fingerprint(), input.derivation_path.clone()),
+            )]);
+        }
+    }
+
+    Ok(psbt)
+}
+
+/// Derives the transit address of the given user from the master key.
+///
+/// P2TR addresses have no script tree, with the internal key derived the same way as the
+/// ECDSA keys of the P2WPKH addresses.
+pub fn derive_transit_address(
+    master_key: PublicKey,
+    chain_code: ChainCode,
+    network: Network,
+    eth_address: &H160,
+    address_type: AddressType,
+) -> KeyResult<Address> {
+    let derivation_path = get_derivation_path(eth_address)?;
+    let public_key = derive_public_key(master_key, chain_code, network, &derivation_path)?;
+
+    match address_type {
+        AddressType::P2wpkh => Ok(Address::p2wpkh(&public_key, network)?),
+        AddressType::P2tr => Ok(Address::p2tr(
+            &Secp256k1::new(),
+            public_key.inner.x_only_public_key().0,
+            None,
+            network,
+        )),
+    }
+}
+
+/// Derives the child public key of the master key with the given derivation path.
+pub fn derive_public_key(
+    master_key: PublicKey,
+    chain_code: ChainCode,
+    network: Network,
+    derivation_path: &DerivationPath,
+) -> KeyResult<PublicKey> {
+    let public_key = master_xpub(master_key, chain_code, network)?
+        .derive_pub(&Secp256k1::new(), derivation_path)?
+        .public_key;
+
+    Ok(PublicKey::from(public_key))
+}
+
+/// Returns the extended public key of the master key.
+pub fn master_xpub(
+    master_key: PublicKey,
+    chain_code: ChainCode,
+    network: Network,
+) -> KeyResult<Xpub> {
+    Ok(Xpub {
+        network,
+        depth: 0,
+        parent_fingerprint: Default::default(),
+        child_number: ChildNumber::from_normal_idx(0)?,
+        public_key: master_key.inner,
+        chain_code,
+    })
+}
+
+pub fn get_derivation_path_ic(eth_address: &H160) -> Vec<Vec<u8>> {
+    let mut bytes = vec![DERIVATION_PATH_PREFIX];
+    bytes.append(&mut eth_address.0.0.to_vec());
+
+    let mut dp = vec![];
+    for slice in bytes.chunks_exact(3) {
+        let mut part = vec![0];
+        part.append(&mut slice.to_vec());
+        dp.push(part);
+    }
+
+    dp
+}
+
+pub fn get_derivation_path(eth_address: &H160) -> KeyResult<DerivationPath> {
+    ic_dp_to_derivation_path(&get_derivation_path_ic(eth_address))
+}
+
+pub fn ic_dp_to_derivation_path(ic_derivation_path: &[Vec<u8>]) -> KeyResult<DerivationPath> {
+    let mut parts = vec![];
+    for part in ic_derivation_path.iter() {
+        let child_idx = u32::from_be_bytes(
+            part[..]
+                .try_into()
+                .map_err(|_| KeyError::InvalidDerivationPath)?,
+        );
+        let child = ChildNumber::from_normal_idx(child_idx)?;
+        parts.push(child);
+    }
+
+    Ok(DerivationPath::from(parts))
+}
+
+pub fn derivation_path_to_ic(derivation_path: DerivationPath) -> Vec<Vec<u8>> {
+    let vec: Vec<_> = derivation_path.into();
+    vec.into_iter()
+        .map(|child| u32::from(child).to_be_bytes().to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash as _;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, TxIn, Txid};
+
+    use super::*;
+
+    #[test]
+    fn test_should_convert_derivation_path() {
+        let eth_address = H160::from_slice(&[42; 20]);
+        let ic_derivation_path = get_derivation_path_ic(&eth_address);
+        assert_eq!(ic_derivation_path.len(), 7);
+        assert_eq!(
+            ic_derivation_path[0],
+            vec![0, DERIVATION_PATH_PREFIX, 42, 42]
+        );
+
+        let derivation_path = get_derivation_path(&eth_address).unwrap();
+        assert_eq!(derivation_path_to_ic(derivation_path), ic_derivation_path);
+    }
+
+    #[test]
+    fn test_should_reject_invalid_derivation_path() {
+        assert_eq!(
+            ic_dp_to_derivation_path(&[vec![1, 2, 3]]),
+            Err(KeyError::InvalidDerivationPath)
+        );
+    }
+
+    #[test]
+    fn test_should_derive_transit_addresses() {
+        let secp = Secp256k1::new();
+        let master_key = PublicKey::new(secp.generate_keypair(&mut rand::thread_rng()).1);
+        let chain_code = ChainCode::from([3; 32]);
+        let eth_address = H160::from_slice(&[1; 20]);
+
+        let p2wpkh = derive_transit_address(
+            master_key,
+            chain_code,
+            Network::Regtest,
+            &eth_address,
+            AddressType::P2wpkh,
+        )
+        .unwrap();
+        let p2tr = derive_transit_address(
+            master_key,
+            chain_code,
+            Network::Regtest,
+            &eth_address,
+            AddressType::P2tr,
+        )
+        .unwrap();
+        assert!(p2wpkh.script_pubkey().is_p2wpkh());
+        assert!(p2tr.script_pubkey().is_p2tr());
+
+        let another_address = derive_transit_address(
+            master_key,
+            chain_code,
+            Network::Regtest,
+            &H160::from_slice(&[2; 20]),
+            AddressType::P2wpkh,
+        )
+        .unwrap();
+        assert_ne!(p2wpkh, another_address);
+    }
+
+    #[tokio::test]
+    async fn test_should_reject_mismatched_inputs() {
+        let signer =
+            LocalBtcSigner::new(PrivateKey::generate(Network::Regtest), AddressType::P2wpkh);
+        let public_key = signer
+            .ecdsa_public_key(&DerivationPath::default())
+            .await
+            .unwrap();
+        let address = Address::p2wpkh(&public_key, Network::Regtest).unwrap();
+        let input = TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[1; 32]).unwrap(),
+                vout: 0,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            derivation_path: DerivationPath::default(),
+        };
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: input.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        };
+
+        let result = sign_transaction(&signer, &unsigned_tx, &[input.clone(), input]).await;
+        assert!(matches!(result, Err(KeyError::Signing(_))));
+    }
+
+    #[test]
+    fn test_should_verify_cosignatures() {
+        let secp = Secp256k1::new();
+        let (canister_secret_key, canister_key) = secp.generate_keypair(&mut rand::thread_rng());
+        let (cosigner_secret_key, cosigner_key) = secp.generate_keypair(&mut rand::thread_rng());
+        let (other_secret_key, other_key) = secp.generate_keypair(&mut rand::thread_rng());
+        let cosigners = [PublicKey::new(cosigner_key), PublicKey::new(other_key)];
+
+        let address = Address::p2wpkh(&PublicKey::new(canister_key), Network::Regtest).unwrap();
+        let input = TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[1; 32]).unwrap(),
+                vout: 0,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            derivation_path: DerivationPath::default(),
+        };
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: input.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        };
+        let inputs = [input];
+        let sighash = input_sighashes(&unsigned_tx, &inputs).unwrap()[0];
+        let signature = |secret_key| ecdsa::Signature {
+            sig: secp.sign_ecdsa(&sighash, secret_key),
+            hash_ty: EcdsaSighashType::All,
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 1).is_err());
+
+        // the signature of the canister key is not a co-signature
+        psbt.inputs[0].partial_sigs.insert(
+            PublicKey::new(canister_key),
+            signature(&canister_secret_key),
+        );
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 1).is_err());
+
+        // a signature put under another key is rejected
+        psbt.inputs[0]
+            .partial_sigs
+            .insert(cosigners[1], signature(&cosigner_secret_key));
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 1).is_err());
+
+        psbt.inputs[0]
+            .partial_sigs
+            .insert(cosigners[0], signature(&cosigner_secret_key));
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 1).is_ok());
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 2).is_err());
+
+        psbt.inputs[0]
+            .partial_sigs
+            .insert(cosigners[1], signature(&other_secret_key));
+        assert!(verify_cosignatures(&psbt, &inputs, &cosigners, 2).is_ok());
+    }
+
+    #[test]
+    fn test_should_build_psbt_with_key_origins() {
+        let secp = Secp256k1::new();
+        let master_key = PublicKey::new(secp.generate_keypair(&mut rand::thread_rng()).1);
+        let chain_code = ChainCode::from([3; 32]);
+        let schnorr_master_key =
+            SchnorrMasterKey::new(&master_key.to_bytes(), &chain_code.to_bytes()).unwrap();
+        let eth_address = H160::from_slice(&[1; 20]);
+        let derivation_path = get_derivation_path(&eth_address).unwrap();
+
+        let input = |id: u8, address_type| {
+            let address = derive_transit_address(
+                master_key,
+                chain_code,
+                Network::Regtest,
+                &eth_address,
+                address_type,
+            )
+            .unwrap();
+            TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[id; 32]).unwrap(),
+                    vout: 0,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(10_000),
+                    script_pubkey: address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            }
+        };
+        let inputs = vec![input(1, AddressType::P2tr), input(2, AddressType::P2wpkh)];
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![],
+        };
+
+        let psbt = build_psbt(
+            &unsigned_tx,
+            &inputs,
+            Network::Regtest,
+            Some((master_key, chain_code)),
+            Some(schnorr_master_key),
+        )
+        .unwrap();
+        let fingerprint = master_xpub(master_key, chain_code, Network::Regtest)
+            .unwrap()
+            .fingerprint();
+
+        let (_, (_, origin)) = psbt.inputs[0].tap_key_origins.iter().next().unwrap();
+        assert_eq!(origin, &(fingerprint, derivation_path.clone()));
+        assert!(psbt.inputs[0].tap_internal_key.is_some());
+        let (_, origin) = psbt.inputs[1].bip32_derivation.iter().next().unwrap();
+        assert_eq!(origin, &(fingerprint, derivation_path));
+
+        let result = build_psbt(&unsigned_tx, &inputs, Network::Regtest, None, None);
+        assert_eq!(result.unwrap_err(), KeyError::SignerNotInitialized);
+    }
+}
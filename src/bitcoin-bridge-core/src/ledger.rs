@@ -0,0 +1,265 @@
+mod used_utxo_details;
+mod utxo_details;
+mod utxo_key;
+
+use std::collections::HashMap;
+
+use bitcoin::hashes::sha256d::Hash;
+use bitcoin::{Address, Amount, OutPoint, TxOut, Txid};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, MemoryId, MemoryManager, StableBTreeMap};
+use ord_rs::wallet::TxInputInfo;
+
+pub use self::used_utxo_details::UsedUtxoDetails;
+pub use self::utxo_details::UtxoDetails;
+pub use self::utxo_key::UtxoKey;
+use crate::key::{KeyError, ic_dp_to_derivation_path};
+
+/// Ledger of the BTC utxos owned by a bridge canister.
+///
+/// A utxo used as an input of a withdrawal transaction stays in the deposited utxos until the
+/// transaction is sent, so it can be released if sending fails.
+pub struct UtxoLedger<M: Memory> {
+    /// contains a list of utxos deposited by users and the change utxos of withdrawal transactions.
+    deposited_utxos: StableBTreeMap<UtxoKey, UtxoDetails, M>,
+    /// contains a list of utxos used as inputs of withdrawal transactions.
+    used_utxos: StableBTreeMap<UtxoKey, UsedUtxoDetails, M>,
+}
+
+impl<M> UtxoLedger<M>
+where
+    M: Memory,
+{
+    /// Creates a new ledger in the memories with the given ids.
+    pub fn new(
+        memory_manager: &dyn MemoryManager<M, MemoryId>,
+        deposited_utxos_memory_id: MemoryId,
+        used_utxos_memory_id: MemoryId,
+    ) -> Self {
+        Self {
+            deposited_utxos: StableBTreeMap::new(memory_manager.get(deposited_utxos_memory_id)),
+            used_utxos: StableBTreeMap::new(memory_manager.get(used_utxos_memory_id)),
+        }
+    }
+
+    /// Adds the utxo to the store.
+    pub fn deposit(&mut self, utxo: Utxo, address: &Address, derivation_path: Vec<Vec<u8>>) {
+        let utxo_key = UtxoKey::from(&utxo.outpoint);
+
+        self.deposited_utxos.insert(
+            utxo_key,
+            UtxoDetails {
+                value: utxo.value,
+                script_buf: address.script_pubkey().into_bytes(),
+                derivation_path,
+            },
+        );
+
+        log::debug!(
+            "Added utxo {utxo_key} with value {} at address {address} to the ledger",
+            utxo.value
+        );
+    }
+
+    /// Checks whether the utxo is already known to the ledger, either as unspent or as used.
+    pub fn contains(&self, key: &UtxoKey) -> bool {
+        self.deposited_utxos.contains_key(key) || self.used_utxos.contains_key(key)
+    }
+
+    /// Checks whether the utxo is in the store and not used by a withdrawal transaction.
+    pub fn is_unspent(&self, key: &UtxoKey) -> bool {
+        self.deposited_utxos.contains_key(key) && !self.used_utxos.contains_key(key)
+    }
+
+    /// Lists all the utxos in the store which are not used by a withdrawal transaction.
+    pub fn load_unspent_utxos(&self) -> Result<HashMap<UtxoKey, TxInputInfo>, KeyError> {
+        let mut map = HashMap::new();
+
+        for (key, details) in self.deposited_utxos.iter() {
+            if self.used_utxos.contains_key(&key) {
+                continue;
+            }
+
+            map.insert(
+                key,
+                TxInputInfo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_raw_hash(*Hash::from_bytes_ref(&key.tx_id)),
+                        vout: key.vout,
+                    },
+                    tx_out: TxOut {
+                        value: Amount::from_sat(details.value),
+                        script_pubkey: details.script_buf.into(),
+                    },
+                    derivation_path: ic_dp_to_derivation_path(&details.derivation_path)?,
+                },
+            );
+        }
+
+        Ok(map)
+    }
+
+    /// Marks the utxo as used, so it is not selected by another withdrawal.
+    pub fn mark_as_used(&mut self, key: UtxoKey, address: Address) {
+        self.used_utxos.insert(
+            key,
+            UsedUtxoDetails {
+                used_at: ic::time(),
+                owner_address: address.to_string(),
+            },
+        );
+
+        log::trace!("Utxo {key} is marked as used.");
+    }
+
+    /// Marks the used utxo as spent by a transaction sent to the Bitcoin network. The utxo cannot
+    /// be released after that.
+    pub fn mark_as_spent(&mut self, key: &UtxoKey) {
+        self.deposited_utxos.remove(key);
+
+        log::trace!("Utxo {key} is marked as spent.");
+    }
+
+    /// Makes the used utxo available for new withdrawals again. Used when the transaction
+    /// spending it could not be sent.
+    pub fn release(&mut self, key: &UtxoKey) {
+        if !self.deposited_utxos.contains_key(key) {
+            log::warn!("Utxo {key} is already spent and cannot be released.");
+            return;
+        }
+
+        self.used_utxos.remove(key);
+
+        log::trace!("Utxo {key} is released.");
+    }
+
+    /// Updates the time of usage of the used utxo, so it is not checked by the cleanup of the
+    /// used utxos while the transaction spending it is not confirmed yet.
+    pub fn refresh_used_utxo(&mut self, key: &UtxoKey) {
+        if let Some(mut details) = self.used_utxos.get(key) {
+            details.used_at = ic::time();
+            self.used_utxos.insert(*key, details);
+        }
+    }
+
+    /// Lists all used utxos in the store.
+    pub fn load_used_utxos(&self) -> Vec<(UtxoKey, UsedUtxoDetails)> {
+        self.used_utxos.iter().collect()
+    }
+
+    /// Removes the spent utxo from the store.
+    pub fn remove_spent_utxo(&mut self, key: &UtxoKey) {
+        self.deposited_utxos.remove(key);
+        self.used_utxos.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+    use ic_exports::ic_kit::MockContext;
+    use ic_stable_structures::default_ic_memory_manager;
+
+    use super::*;
+
+    fn address() -> Address {
+        Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked()
+    }
+
+    fn utxo(tx_id: u8) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![tx_id; 32],
+                vout: 1,
+            },
+            value: 1000,
+            height: 0,
+        }
+    }
+
+    fn ledger() -> UtxoLedger<impl Memory> {
+        UtxoLedger::new(
+            &default_ic_memory_manager(),
+            MemoryId::new(0),
+            MemoryId::new(1),
+        )
+    }
+
+    #[test]
+    fn test_should_deposit_utxo() {
+        MockContext::new().inject();
+        let mut ledger = ledger();
+
+        ledger.deposit(utxo(0xde), &address(), vec![]);
+
+        let unspent = ledger.load_unspent_utxos().unwrap();
+        assert_eq!(unspent.len(), 1);
+        let key = UtxoKey::from(&utxo(0xde).outpoint);
+        assert_eq!(unspent[&key].tx_out.value, Amount::from_sat(1000));
+        assert!(ledger.contains(&key));
+    }
+
+    #[test]
+    fn test_should_mark_used_utxo() {
+        MockContext::new().inject();
+        let mut ledger = ledger();
+        ledger.deposit(utxo(0xaa), &address(), vec![]);
+        ledger.deposit(utxo(0xab), &address(), vec![]);
+
+        let key = UtxoKey::from(&utxo(0xaa).outpoint);
+        assert!(ledger.is_unspent(&key));
+        ledger.mark_as_used(key, address());
+
+        let unspent = ledger.load_unspent_utxos().unwrap();
+        assert_eq!(unspent.len(), 1);
+        assert!(!unspent.contains_key(&key));
+        assert!(ledger.contains(&key));
+        assert!(!ledger.is_unspent(&key));
+
+        let used = ledger.load_used_utxos();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].0, key);
+        assert_eq!(used[0].1.owner_address, address().to_string());
+
+        ledger.mark_as_spent(&key);
+        ledger.remove_spent_utxo(&key);
+        assert!(ledger.load_used_utxos().is_empty());
+        assert!(!ledger.contains(&key));
+    }
+
+    #[test]
+    fn test_should_release_used_utxo() {
+        MockContext::new().inject();
+        let mut ledger = ledger();
+        ledger.deposit(utxo(0xaa), &address(), vec![]);
+
+        let key = UtxoKey::from(&utxo(0xaa).outpoint);
+        ledger.mark_as_used(key, address());
+        assert!(ledger.load_unspent_utxos().unwrap().is_empty());
+
+        ledger.release(&key);
+        assert!(ledger.load_used_utxos().is_empty());
+        assert!(ledger.load_unspent_utxos().unwrap().contains_key(&key));
+    }
+
+    #[test]
+    fn test_should_not_release_spent_utxo() {
+        MockContext::new().inject();
+        let mut ledger = ledger();
+        ledger.deposit(utxo(0xaa), &address(), vec![]);
+
+        let key = UtxoKey::from(&utxo(0xaa).outpoint);
+        ledger.mark_as_used(key, address());
+        ledger.mark_as_spent(&key);
+        ledger.release(&key);
+
+        assert!(ledger.load_unspent_utxos().unwrap().is_empty());
+        assert_eq!(ledger.load_used_utxos().len(), 1);
+    }
+}
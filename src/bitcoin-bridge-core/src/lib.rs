@@ -0,0 +1,8 @@
+pub mod consensus;
+pub mod fee_rate;
+pub mod funding;
+pub mod http;
+pub mod key;
+pub mod ledger;
+pub mod master_key;
+pub mod utxo_provider;
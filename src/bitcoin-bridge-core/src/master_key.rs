@@ -5,8 +5,8 @@ use ic_stable_structures::stable_structures::Memory;
 use ic_stable_structures::{CellStructure as _, MemoryId, MemoryManager, StableCell, Storable};
 
 use crate::key::KeyError;
-use crate::memory::MASTER_KEY_MEMORY_ID;
 
+/// Stable storage of the IC ECDSA master key of the canister.
 pub struct MasterKeyStorage<M: Memory> {
     master_key: StableCell<Option<MasterKey>, M>,
 }
@@ -15,10 +15,10 @@ impl<M> MasterKeyStorage<M>
 where
     M: Memory,
 {
-    /// Creates a new MasterKeyStorage.
-    pub fn new(memory: &dyn MemoryManager<M, MemoryId>) -> Self {
+    /// Creates a new MasterKeyStorage in the memory with the given id.
+    pub fn new(memory: &dyn MemoryManager<M, MemoryId>, memory_id: MemoryId) -> Self {
         Self {
-            master_key: StableCell::new(memory.get(MASTER_KEY_MEMORY_ID), None)
+            master_key: StableCell::new(memory.get(memory_id), None)
                 .expect("stable memory master key initialization failed"),
         }
     }
@@ -36,6 +36,7 @@ where
     }
 }
 
+/// Public key and chain code of the IC ECDSA master key, used to derive the keys of the users.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MasterKey {
     /// Public key encoded in a bytes buffer
@@ -123,6 +124,94 @@ impl Storable for MasterKey {
     }
 }
 
+/// Stable storage of the IC BIP-340 master key of the canister.
+pub struct SchnorrMasterKeyStorage<M: Memory> {
+    master_key: StableCell<Option<SchnorrMasterKey>, M>,
+}
+
+impl<M> SchnorrMasterKeyStorage<M>
+where
+    M: Memory,
+{
+    /// Creates a new SchnorrMasterKeyStorage in the memory with the given id.
+    pub fn new(memory: &dyn MemoryManager<M, MemoryId>, memory_id: MemoryId) -> Self {
+        Self {
+            master_key: StableCell::new(memory.get(memory_id), None)
+                .expect("stable memory schnorr master key initialization failed"),
+        }
+    }
+
+    /// Returns the schnorr master key if it is configured.
+    pub fn get(&self) -> &Option<SchnorrMasterKey> {
+        self.master_key.get()
+    }
+
+    /// Sets the schnorr master key.
+    pub fn set(&mut self, master_key: SchnorrMasterKey) {
+        self.master_key
+            .set(Some(master_key))
+            .expect("failed to set schnorr master key");
+    }
+}
+
+/// BIP-340 master public key of the canister.
+///
+/// The keys of the taproot addresses are derived from it the same way as the ECDSA keys, so the
+/// addresses can be computed without calling the management canister.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrMasterKey {
+    /// Compressed public key.
+    public_key: [u8; 33],
+    chain_code: [u8; 32],
+}
+
+impl SchnorrMasterKey {
+    pub fn new(public_key: &[u8], chain_code: &[u8]) -> Result<Self, KeyError> {
+        let public_key: [u8; 33] = public_key
+            .try_into()
+            .map_err(|_| KeyError::InvalidPublicKey)?;
+        PublicKey::from_slice(&public_key).map_err(|_| KeyError::InvalidPublicKey)?;
+        let chain_code: [u8; 32] = chain_code
+            .try_into()
+            .map_err(|_| KeyError::InvalidChainCode)?;
+
+        Ok(Self {
+            public_key,
+            chain_code,
+        })
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_slice(&self.public_key).expect("schnorr master key is validated")
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        ChainCode::from(self.chain_code)
+    }
+}
+
+impl Storable for SchnorrMasterKey {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        let mut buf = Vec::with_capacity(Self::BOUND.max_size() as usize);
+        buf.extend_from_slice(&self.public_key);
+        buf.extend_from_slice(&self.chain_code);
+
+        buf.into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Self {
+            public_key: bytes[..33].try_into().expect("invalid public key"),
+            chain_code: bytes[33..65].try_into().expect("invalid chain code"),
+        }
+    }
+
+    const BOUND: ic_stable_structures::Bound = ic_stable_structures::Bound::Bounded {
+        max_size: 33 + 32,
+        is_fixed_size: true,
+    };
+}
+
 #[cfg(test)]
 mod test {
     use bitcoin::key::Secp256k1;
@@ -134,7 +223,7 @@ mod test {
     #[test]
     fn test_master_key_storage_compressed() {
         let memory_manager = default_ic_memory_manager();
-        let mut storage = MasterKeyStorage::new(&memory_manager);
+        let mut storage = MasterKeyStorage::new(&memory_manager, MemoryId::new(0));
 
         let secret_key = SecretKey::new(&mut rand::thread_rng());
         let secp_pubkey =
@@ -157,7 +246,7 @@ mod test {
     #[test]
     fn test_master_key_storage_uncompressed() {
         let memory_manager = default_ic_memory_manager();
-        let mut storage = MasterKeyStorage::new(&memory_manager);
+        let mut storage = MasterKeyStorage::new(&memory_manager, MemoryId::new(0));
 
         let secret_key = SecretKey::new(&mut rand::thread_rng());
         let secp_pubkey =
@@ -176,4 +265,26 @@ mod test {
         storage.set(master_key.clone());
         assert_eq!(storage.get().as_ref().unwrap(), &master_key);
     }
+
+    #[test]
+    fn test_schnorr_master_key_storage() {
+        let memory_manager = default_ic_memory_manager();
+        let mut storage = SchnorrMasterKeyStorage::new(&memory_manager, MemoryId::new(0));
+        assert_eq!(storage.get(), &None);
+
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let public_key =
+            bitcoin::secp256k1::PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+        let master_key = SchnorrMasterKey::new(&public_key.serialize(), &[2; 32]).unwrap();
+
+        storage.set(master_key);
+        assert_eq!(storage.get(), &Some(master_key));
+        assert_eq!(master_key.public_key().inner, public_key);
+    }
+
+    #[test]
+    fn test_should_reject_invalid_schnorr_master_key() {
+        assert!(SchnorrMasterKey::new(&[2; 32], &[2; 32]).is_err());
+        assert!(SchnorrMasterKey::new(&[0; 33], &[2; 32]).is_err());
+    }
 }
@@ -6,20 +6,34 @@ use std::time::Duration;
 
 use bitcoin::consensus::Encodable;
 use bitcoin::{Address, FeeRate, Transaction};
+use bridge_did::init::FeePriority;
+use bridge_utils::fee_estimator::{FeeEstimator, IcFeeEstimator};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{
-    BitcoinNetwork, GetCurrentFeePercentilesRequest, GetUtxosRequest, GetUtxosResponse,
-    SendTransactionRequest, bitcoin_get_current_fee_percentiles, bitcoin_get_utxos,
+    BitcoinNetwork, GetUtxosRequest, GetUtxosResponse, SendTransactionRequest, bitcoin_get_utxos,
     bitcoin_send_transaction,
 };
 use ic_exports::ic_kit::ic;
+use thiserror::Error;
+
+/// Errors of the requests to the Bitcoin canister.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UtxoProviderError {
+    #[error("failed to connect to IC BTC adapter: {0}")]
+    BtcAdapter(String),
+    #[error("failed to get the current fee rate")]
+    FeeRateRequest,
+    #[error("failed to serialize the transaction")]
+    TransactionSerialization,
+    #[error("failed to send the transaction")]
+    TransactionSending,
+}
 
-use crate::core::rune_inputs::GetInputsError;
-use crate::interface::WithdrawError;
-
-pub(crate) trait UtxoProvider {
-    async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, GetInputsError>;
-    async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError>;
-    async fn send_tx(&self, transaction: &Transaction) -> Result<(), WithdrawError>;
+/// Access to the utxos of the Bitcoin network.
+#[allow(async_fn_in_trait)]
+pub trait UtxoProvider {
+    async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, UtxoProviderError>;
+    async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError>;
+    async fn send_tx(&self, transaction: &Transaction) -> Result<(), UtxoProviderError>;
 }
 
 type IcTimestamp = u64;
@@ -49,22 +63,33 @@ thread_local! {
     static UTXO_CACHE: Rc<RefCell<BTreeSet<UtxoCacheEntry>>> = Default::default();
 }
 
+/// Utxo provider of the IC Bitcoin canister.
+///
+/// The utxo lists are cached for the given timeout, so the addresses checked several times in a
+/// short period are requested once. The cache is disabled if the timeout is zero.
 pub struct IcUtxoProvider {
     network: BitcoinNetwork,
     utxo_cache_timeout: Duration,
+    fee_estimator: IcFeeEstimator,
 }
 
-const DEFAULT_REGTEST_FEE: u64 = 100_000 * 1_000;
-
 impl IcUtxoProvider {
-    pub fn new(network: BitcoinNetwork, utxo_cache_timeout: Duration) -> Self {
+    pub fn new(
+        network: BitcoinNetwork,
+        utxo_cache_timeout: Duration,
+        fee_estimator: IcFeeEstimator,
+    ) -> Self {
         Self {
             network,
             utxo_cache_timeout,
+            fee_estimator,
         }
     }
 
-    async fn request_utxos(&self, address: &Address) -> Result<GetUtxosResponse, GetInputsError> {
+    async fn request_utxos(
+        &self,
+        address: &Address,
+    ) -> Result<GetUtxosResponse, UtxoProviderError> {
         let args = GetUtxosRequest {
             address: address.to_string(),
             network: self.network,
@@ -76,7 +101,7 @@ impl IcUtxoProvider {
         let response = bitcoin_get_utxos(args)
             .await
             .map(|value| value.0)
-            .map_err(GetInputsError::btc)?;
+            .map_err(|err| UtxoProviderError::BtcAdapter(err.1))?;
 
         log::trace!("Got UTXO list result for address {address}:");
         log::trace!("{response:?}");
@@ -122,7 +147,7 @@ impl IcUtxoProvider {
 }
 
 impl UtxoProvider for IcUtxoProvider {
-    async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, GetInputsError> {
+    async fn get_utxos(&self, address: &Address) -> Result<GetUtxosResponse, UtxoProviderError> {
         match self.get_cached_utxos(address) {
             Some(v) => {
                 log::trace!("UTXO list for address {address} found in cache");
@@ -139,38 +164,17 @@ impl UtxoProvider for IcUtxoProvider {
         }
     }
 
-    async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
-        let args = GetCurrentFeePercentilesRequest {
-            network: self.network,
-        };
-        let response = bitcoin_get_current_fee_percentiles(args)
+    async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError> {
+        self.fee_estimator
+            .estimate_fee_rate(FeePriority::Medium)
             .await
             .map_err(|err| {
-                log::error!("Failed to get current fee rate: {err:?}");
-                WithdrawError::FeeRateRequest
-            })?
-            .0;
-
-        let middle_percentile = match self.network {
-            BitcoinNetwork::Regtest => DEFAULT_REGTEST_FEE,
-            _ if response.is_empty() => {
-                log::error!("Empty response for fee rate request");
-                return Err(WithdrawError::FeeRateRequest);
-            }
-            _ => response[response.len() / 2],
-        };
-
-        log::trace!("Received fee rate percentiles: {response:?}");
-
-        log::info!("Using fee rate {}", middle_percentile / 1000);
-
-        FeeRate::from_sat_per_vb(middle_percentile / 1000).ok_or_else(|| {
-            log::error!("Invalid fee rate received from IC: {middle_percentile}");
-            WithdrawError::FeeRateRequest
-        })
+                log::error!("Failed to get current fee rate: {err}");
+                UtxoProviderError::FeeRateRequest
+            })
     }
 
-    async fn send_tx(&self, transaction: &Transaction) -> Result<(), WithdrawError> {
+    async fn send_tx(&self, transaction: &Transaction) -> Result<(), UtxoProviderError> {
         log::trace!(
             "Sending transaction {} to the bitcoin adapter",
             transaction.txid()
@@ -181,7 +185,7 @@ impl UtxoProvider for IcUtxoProvider {
             .consensus_encode(&mut serialized)
             .map_err(|err| {
                 log::error!("Failed to serialize transaction: {err:?}");
-                WithdrawError::TransactionSerialization
+                UtxoProviderError::TransactionSerialization
             })?;
 
         log::trace!(
@@ -196,7 +200,7 @@ impl UtxoProvider for IcUtxoProvider {
         };
         bitcoin_send_transaction(request).await.map_err(|err| {
             log::error!("Failed to send transaction: {err:?}");
-            WithdrawError::TransactionSending
+            UtxoProviderError::TransactionSending
         })?;
 
         log::trace!("Transaction {} sent to the adapter", transaction.txid());
@@ -209,6 +213,7 @@ impl UtxoProvider for IcUtxoProvider {
 mod tests {
     use bitcoin::secp256k1::Secp256k1;
     use bitcoin::{Network, PublicKey};
+    use bridge_did::init::FeeEstimatorConfig;
     use ic_exports::ic_kit::MockContext;
 
     use super::*;
@@ -220,6 +225,14 @@ mod tests {
         Address::p2pkh(&public_key, Network::Bitcoin)
     }
 
+    fn test_provider(utxo_cache_timeout: Duration) -> IcUtxoProvider {
+        IcUtxoProvider::new(
+            BitcoinNetwork::Mainnet,
+            utxo_cache_timeout,
+            IcFeeEstimator::new(BitcoinNetwork::Mainnet, FeeEstimatorConfig::default()),
+        )
+    }
+
     fn mock_response(v: u32) -> GetUtxosResponse {
         GetUtxosResponse {
             utxos: vec![],
@@ -232,7 +245,7 @@ mod tests {
     #[test]
     fn cached_items_with_same_ts() {
         MockContext::new().inject();
-        let provider = IcUtxoProvider::new(BitcoinNetwork::Mainnet, Duration::from_secs(1));
+        let provider = test_provider(Duration::from_secs(1));
         let a1 = address();
         let r1 = mock_response(1);
         let a2 = address();
@@ -248,7 +261,7 @@ mod tests {
     #[test]
     fn cached_items_are_removed() {
         let ctx = MockContext::new().inject();
-        let provider = IcUtxoProvider::new(BitcoinNetwork::Mainnet, Duration::from_secs(60));
+        let provider = test_provider(Duration::from_secs(60));
         let a1 = address();
         let r1 = mock_response(1);
         let a2 = address();
@@ -271,7 +284,7 @@ mod tests {
     #[tokio::test]
     async fn get_utxos_returns_cached_value() {
         let ctx = MockContext::new().inject();
-        let provider = IcUtxoProvider::new(BitcoinNetwork::Mainnet, Duration::from_secs(60));
+        let provider = test_provider(Duration::from_secs(60));
         let a1 = address();
         let r1 = mock_response(1);
 
@@ -285,7 +298,7 @@ mod tests {
     #[should_panic(expected = "call_new should only be called inside canisters")]
     async fn get_utxos_requests_if_not_in_cache() {
         let ctx = MockContext::new().inject();
-        let provider = IcUtxoProvider::new(BitcoinNetwork::Mainnet, Duration::from_secs(60));
+        let provider = test_provider(Duration::from_secs(60));
         let a1 = address();
         let r1 = mock_response(1);
         let a2 = address();
@@ -299,7 +312,7 @@ mod tests {
     #[test]
     fn should_skip_caching_if_disabled() {
         MockContext::new().inject();
-        let provider = IcUtxoProvider::new(BitcoinNetwork::Mainnet, Duration::from_secs(0));
+        let provider = test_provider(Duration::from_secs(0));
         let a1 = address();
         let r1 = mock_response(1);
         let a2 = address();
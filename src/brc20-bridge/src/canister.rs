@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use bridge_canister::BridgeCanister;
@@ -7,33 +7,48 @@ use bridge_canister::runtime::service::ServiceOrder;
 use bridge_canister::runtime::service::fetch_logs::FetchBtfBridgeEventsService;
 use bridge_canister::runtime::service::mint_tx::SendMintTxService;
 use bridge_canister::runtime::service::sign_orders::SignMintOrdersService;
+use bridge_canister::runtime::service::timer::ServiceTimer;
 use bridge_canister::runtime::service::update_evm_params::RefreshEvmParamsService;
 use bridge_canister::runtime::state::config::ConfigStorage;
 use bridge_canister::runtime::{BridgeRuntime, RuntimeState};
-use bridge_did::init::BridgeInitData;
-use bridge_did::init::brc20::Brc20BridgeConfig;
+use bridge_did::brc20_info::Brc20Tick;
+use bridge_did::init::brc20::{Brc20BridgeConfig, MemoDepositConfig};
+use bridge_did::init::{
+    BridgeInitData, DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig,
+    IndexerHeader, WithdrawalApprovalConfig, WithdrawalBatchConfig,
+};
+use bridge_did::inscription::InscriptionId;
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::{Memo, OperationLog};
 use bridge_utils::common::Pagination;
 use candid::Principal;
 use did::H160;
 use ic_canister::{Canister, Idl, PreUpdate, generate_idl, init, post_upgrade, query, update};
+use ic_exports::ic_cdk;
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     EcdsaPublicKeyArgument, ecdsa_public_key,
 };
+use ic_exports::ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
+use ic_exports::ic_kit::ic;
 use ic_exports::ledger::Subaccount;
 use ic_log::canister::{LogCanister, LogState};
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_storage::IcStorage;
 
 use crate::canister::inspect::inspect_is_owner;
-use crate::interface::GetAddressError;
+use crate::constants::{
+    BRC20_TOKENS_REFRESH_INTERVAL, DEPOSIT_WATCHER_INTERVAL, MEMO_DEPOSIT_INTERVAL,
+};
+use crate::interface::{GetAddressError, LedgerSummary, WithdrawError};
 use crate::ops::{
+    BATCH_TRANSFER_SERVICE_ID, BRC20_TOKENS_REFRESH_SERVICE_ID, BatchTransferService,
     Brc20BridgeOpImpl, Brc20BtfEventsHandler, Brc20MintOrderHandler, Brc20MintTxHandler,
-    FETCH_BTF_EVENTS_SERVICE_ID, REFRESH_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID,
-    SIGN_MINT_ORDER_SERVICE_ID,
+    Brc20TokensRefreshService, DEPOSIT_WATCHER_SERVICE_ID, DepositWatcherService,
+    FETCH_BTF_EVENTS_SERVICE_ID, MEMO_DEPOSIT_SERVICE_ID, MemoDepositService,
+    REFRESH_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID, SIGN_MINT_ORDER_SERVICE_ID,
+    refund_memo_deposit,
 };
-use crate::state::Brc20State;
+use crate::state::{Brc20State, RefundableMemoDeposit};
 
 mod inspect;
 
@@ -55,11 +70,16 @@ impl Brc20Bridge {
     #[init]
     pub fn init(&mut self, bridge_init_data: BridgeInitData, brc20_config: Brc20BridgeConfig) {
         self.init_bridge(bridge_init_data, Self::run_scheduler);
-        get_brc20_state().borrow_mut().configure(brc20_config);
+        let state = get_brc20_state();
+        state.borrow_mut().configure(brc20_config);
+        state.borrow().init_state_version();
     }
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        if let Err(err) = get_brc20_state().borrow_mut().migrate() {
+            ic::trap(&format!("Failed to migrate the state: {err}"));
+        }
         self.bridge_post_upgrade(Self::run_scheduler)
     }
 
@@ -76,6 +96,53 @@ impl Brc20Bridge {
             .map_err(GetAddressError::from)
     }
 
+    /// Returns the bitcoin address shared by all users to deposit BRC20 tokens with a memo. The
+    /// recipient of such a deposit is read from the metadata of the transfer inscription.
+    #[query]
+    pub fn get_memo_deposit_address(&self) -> Result<String, GetAddressError> {
+        crate::key::get_transit_address(&get_brc20_state(), &H160::default())
+            .map(|v| v.to_string())
+            .map_err(GetAddressError::from)
+    }
+
+    /// Returns the transfers to the memo deposit address which could not be minted. Their tokens
+    /// can be refunded by the owner.
+    #[query]
+    pub fn get_refundable_memo_deposits(&self) -> Vec<(InscriptionId, RefundableMemoDeposit)> {
+        get_brc20_state().borrow().refundable_memo_deposits()
+    }
+
+    /// Returns the BTC fee in sats charged for a deposit requested now. The fee is locked into
+    /// the deposit operation when it is created.
+    #[query]
+    pub fn get_deposit_fee_quote(&self) -> DepositFeeQuote {
+        get_brc20_state().borrow().deposit_fee_quote()
+    }
+
+    /// Returns the deposit address of the given Ethereum address and starts watching it, so that
+    /// the BRC20 tokens sent to the address are bridged to the given wrapped tokens without a
+    /// deposit request.
+    ///
+    /// The registration fee configured for the deposit watcher must be attached to the call in
+    /// cycles.
+    #[update]
+    pub fn register_deposit_address(
+        &self,
+        eth_address: H160,
+        dst_tokens: HashMap<Brc20Tick, H160>,
+    ) -> Result<String, GetAddressError> {
+        let state = get_brc20_state();
+        let address = crate::key::get_transit_address(&state, &eth_address)?;
+        let fee = state.borrow_mut().watch_deposit_address(
+            eth_address,
+            dst_tokens,
+            ic_cdk::api::call::msg_cycles_available(),
+        )?;
+        ic_cdk::api::call::msg_cycles_accept(fee);
+
+        Ok(address.to_string())
+    }
+
     /// Retrieves all operations for the given ETH wallet address whose
     /// id is greater than or equal to `min_included_id` if provided.
     /// The operations are then paginated with the given `pagination` parameters,
@@ -130,6 +197,17 @@ impl Brc20Bridge {
             .get_memos_by_user_address(&user_id)
     }
 
+    /// Returns the summary of the utxos known to the bridge. Only available to the owner.
+    ///
+    /// Reveal utxos carry the withdrawn inscriptions, so unlike the rune bridge utxos they are
+    /// never consolidated.
+    #[query]
+    pub fn get_ledger_summary(&self) -> LedgerSummary {
+        inspect_is_owner(self.config());
+
+        get_brc20_state().borrow().ledger().summary()
+    }
+
     #[update]
     pub async fn admin_configure_ecdsa(&self) {
         inspect_is_owner(self.config());
@@ -165,6 +243,128 @@ impl Brc20Bridge {
             .configure_indexers(indexer_urls);
     }
 
+    /// Sets the headers sent with the requests to each of the indexer urls, e.g. API keys.
+    ///
+    /// The headers are never returned by the canister queries.
+    #[update]
+    pub fn admin_set_indexer_headers(&self, headers: HashMap<String, Vec<IndexerHeader>>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state().borrow_mut().set_indexer_headers(headers);
+    }
+
+    /// Enables automatic detection of the deposits to the registered deposit addresses with the
+    /// given configuration, or disables it if `None`.
+    #[update]
+    pub fn admin_configure_deposit_watcher(&self, config: Option<DepositWatcherConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_deposit_watcher_config(config);
+    }
+
+    /// Enables minting of the BRC20 transfers sent to the memo deposit address with the given
+    /// configuration, or disables it if `None`.
+    #[update]
+    pub fn admin_configure_memo_deposits(&self, config: Option<MemoDepositConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_memo_deposit_config(config);
+    }
+
+    /// Sends the tokens of the transfer to the memo deposit address, which could not be minted,
+    /// back to the address the transfer came from.
+    #[update]
+    pub fn admin_refund_memo_deposit(
+        &self,
+        inscription_id: InscriptionId,
+    ) -> Result<OperationId, WithdrawError> {
+        inspect_is_owner(self.config());
+
+        refund_memo_deposit(get_runtime_state(), inscription_id)
+    }
+
+    /// Enables batching of the BRC20 transfers of the withdrawals with the given configuration,
+    /// or disables it if `None`.
+    #[update]
+    pub fn admin_configure_withdrawal_batch(&self, config: Option<WithdrawalBatchConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_withdrawal_batch_config(config);
+    }
+
+    /// Sets the thresholds above which the withdrawals wait for the approval of the owner, or
+    /// sends all the withdrawals without approval if `None`.
+    #[update]
+    pub fn admin_configure_withdrawal_approval(&self, config: Option<WithdrawalApprovalConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_withdrawal_approval_config(config);
+    }
+
+    /// Approves the withdrawal awaiting the approval of the owner. The transactions of the
+    /// withdrawal are then created, signed by the canister and sent.
+    #[update]
+    pub fn admin_approve_withdrawal(&self, operation_id: OperationId) -> Result<(), WithdrawError> {
+        inspect_is_owner(self.config());
+
+        let operation = get_runtime_state()
+            .borrow()
+            .operations
+            .get(operation_id)
+            .and_then(Brc20BridgeOpImpl::approve_withdrawal)
+            .ok_or_else(|| {
+                WithdrawError::InvalidRequest(format!(
+                    "operation {operation_id} does not await the approval"
+                ))
+            })?;
+
+        get_runtime_state()
+            .borrow_mut()
+            .operations
+            .update(operation_id, operation.clone());
+        get_runtime()
+            .borrow()
+            .schedule_operation(operation_id, operation);
+
+        Ok(())
+    }
+
+    /// Enables the deposit fee computed from the current Bitcoin fee rate with the given
+    /// configuration, or switches to the fixed deposit fee if `None`.
+    #[update]
+    pub fn admin_configure_dynamic_deposit_fee(&self, config: Option<DepositFeeConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_dynamic_deposit_fee_config(config);
+    }
+
+    /// Sets the Bitcoin fee rate estimation configuration. If `None`, the median of the IC fee
+    /// percentiles is used.
+    #[update]
+    pub fn admin_configure_fee_estimator(&self, config: Option<FeeEstimatorConfig>) {
+        inspect_is_owner(self.config());
+
+        get_brc20_state()
+            .borrow_mut()
+            .set_fee_estimator_config(config);
+    }
+
+    /// Strips the headers of the indexer responses, so the HTTP outcall replicas agree on them.
+    #[query]
+    pub fn transform_indexer_response(&self, args: TransformArgs) -> HttpResponse {
+        bridge_utils::http::transform_indexer_response(args)
+    }
+
     pub fn idl() -> Idl {
         generate_idl!()
     }
@@ -198,6 +398,23 @@ fn init_runtime() -> SharedRuntime {
         config,
     ));
 
+    let tokens_refresh_service = Rc::new(ServiceTimer::new(
+        Brc20TokensRefreshService::new(state.clone()),
+        BRC20_TOKENS_REFRESH_INTERVAL,
+    ));
+
+    let deposit_watcher_service = Rc::new(ServiceTimer::new(
+        DepositWatcherService::new(state.clone()),
+        DEPOSIT_WATCHER_INTERVAL,
+    ));
+
+    let memo_deposit_service = Rc::new(ServiceTimer::new(
+        MemoDepositService::new(state.clone()),
+        MEMO_DEPOSIT_INTERVAL,
+    ));
+
+    let batch_transfer_service = Rc::new(BatchTransferService::new(state.clone()));
+
     let services = state.borrow().services.clone();
     services.borrow_mut().add_service(
         ServiceOrder::BeforeOperations,
@@ -219,6 +436,26 @@ fn init_runtime() -> SharedRuntime {
         SEND_MINT_TX_SERVICE_ID,
         mint_tx_service,
     );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        BRC20_TOKENS_REFRESH_SERVICE_ID,
+        tokens_refresh_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        DEPOSIT_WATCHER_SERVICE_ID,
+        deposit_watcher_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        MEMO_DEPOSIT_SERVICE_ID,
+        memo_deposit_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        BATCH_TRANSFER_SERVICE_ID,
+        batch_transfer_service,
+    );
 
     runtime
 }
@@ -2,3 +2,24 @@ use std::time::Duration;
 
 /// The interval at which the fee rate is updated (10 minutes)
 pub const FEE_RATE_UPDATE_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Time after which an unconfirmed transfer transaction gets replaced by a transaction paying a
+/// higher fee (1 hour)
+pub const FEE_BUMP_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// The interval at which the tokens deployed since the last refresh are requested from the
+/// indexers (10 minutes)
+pub const BRC20_TOKENS_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Maximum number of tokens added to the token info cache by a single refresh
+pub const BRC20_TOKENS_REFRESH_LIMIT: usize = 600;
+
+/// The interval at which the deposit watcher scans the registered deposit addresses for new
+/// deposits (2 minutes)
+pub const DEPOSIT_WATCHER_INTERVAL: Duration = Duration::from_secs(60 * 2);
+
+/// The interval at which the transfers to the memo deposit address are checked (2 minutes)
+pub const MEMO_DEPOSIT_INTERVAL: Duration = Duration::from_secs(60 * 2);
+
+/// Number of transfers requested from the indexer at once when looking for memo deposits
+pub const MEMO_DEPOSIT_PAGE_SIZE: usize = 60;
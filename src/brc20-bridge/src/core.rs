@@ -1,4 +1,3 @@
 pub mod deposit;
 pub mod index_provider;
-pub mod utxo_provider;
 pub mod withdrawal;
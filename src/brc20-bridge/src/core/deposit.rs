@@ -1,11 +1,16 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
 
 use bitcoin::{Address, Network};
+use bitcoin_bridge_core::http::IcHttpClient;
+use bitcoin_bridge_core::key::{KeyError, get_derivation_path_ic};
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
 use bridge_canister::runtime::RuntimeState;
 use bridge_did::brc20_info::{Brc20Info, Brc20Tick};
 use bridge_did::id256::Id256;
+use bridge_did::inscription::InscriptionId;
 use bridge_did::order::{MintOrder, SignedMintOrder};
 use candid::{CandidType, Deserialize};
 use did::{H160, H256};
@@ -13,12 +18,11 @@ use ic_exports::ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, Ut
 use rust_decimal::Decimal;
 use serde::Serialize;
 
-use super::index_provider::IcHttpClient;
 use crate::canister::{get_brc20_state, get_runtime_state};
-use crate::core::index_provider::{Brc20IndexProvider, OrdIndexProvider};
-use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::constants::BRC20_TOKENS_REFRESH_LIMIT;
+use crate::core::index_provider::{Brc20IndexProvider, Brc20Transfer, OrdIndexProvider};
 use crate::interface::DepositError;
-use crate::key::{BtcSignerType, KeyError, get_derivation_path_ic};
+use crate::key::BtcSignerType;
 use crate::ledger::UtxoKey;
 use crate::ops::Brc20BridgeOpImpl;
 use crate::state::Brc20State;
@@ -109,6 +113,12 @@ impl Brc20DepositPayload {
     }
 }
 
+/// Returns the EVM address whose deposit address is the memo deposit address of the bridge. It
+/// is the zero EVM address, which is never used by the per-user deposits.
+pub fn memo_deposit_account() -> H160 {
+    H160::default()
+}
+
 pub(crate) struct Brc20Deposit<
     UTXO: UtxoProvider = IcUtxoProvider,
     INDEX: Brc20IndexProvider = OrdIndexProvider<IcHttpClient>,
@@ -136,7 +146,9 @@ impl Brc20Deposit<IcUtxoProvider, OrdIndexProvider<IcHttpClient>> {
 
         let network = state_ref.network();
         let ic_network = state_ref.ic_btc_network();
+        let fee_estimator = state_ref.fee_estimator();
         let indexer_urls = state_ref.indexer_urls();
+        let indexer_headers = state_ref.indexer_headers();
         let signer = state_ref
             .btc_signer(&signing_strategy)
             .ok_or(DepositError::SignerNotInitialized)?;
@@ -149,9 +161,9 @@ impl Brc20Deposit<IcUtxoProvider, OrdIndexProvider<IcHttpClient>> {
             runtime_state,
             network,
             signer,
-            utxo_provider: IcUtxoProvider::new(ic_network),
+            utxo_provider: IcUtxoProvider::new(ic_network, Duration::ZERO, fee_estimator),
             index_provider: OrdIndexProvider::new(
-                IcHttpClient {},
+                IcHttpClient::new(indexer_headers),
                 indexer_urls,
                 consensus_threshold,
             ),
@@ -166,10 +178,18 @@ impl Brc20Deposit<IcUtxoProvider, OrdIndexProvider<IcHttpClient>> {
 impl<UTXO: UtxoProvider, INDEX: Brc20IndexProvider> Brc20Deposit<UTXO, INDEX> {
     // Get input utxos
     pub async fn get_inputs(&self, dst_address: &H160) -> Result<Vec<Utxo>, DepositError> {
+        Ok(self.get_address_utxos(dst_address).await?.utxos)
+    }
+
+    /// Returns the unused utxos at the deposit address of the given EVM address.
+    pub async fn get_address_utxos(
+        &self,
+        dst_address: &H160,
+    ) -> Result<GetUtxosResponse, DepositError> {
         let transit_address = self.get_transit_address(dst_address).await?;
         log::debug!("Getting inputs for address: {transit_address}");
 
-        Ok(self.get_deposit_utxos(&transit_address).await?.utxos)
+        self.get_deposit_utxos(&transit_address).await
     }
 
     pub async fn get_brc20_balance(
@@ -183,23 +203,64 @@ impl<UTXO: UtxoProvider, INDEX: Brc20IndexProvider> Brc20Deposit<UTXO, INDEX> {
             .get_brc20_balances(&transit_address)
             .await?;
 
+        let amount = balances.get(tick).copied().unwrap_or_default();
+
+        self.to_integer_amount(tick, amount).await
+    }
+
+    /// Returns the address receiving the deposits with the recipient encoded in the inscription
+    /// metadata.
+    pub async fn get_memo_deposit_address(&self) -> Result<Address, DepositError> {
+        Ok(self.get_transit_address(&memo_deposit_account()).await?)
+    }
+
+    /// Returns the BRC20 transfers sent from or to the given address, from the newest to the
+    /// oldest, skipping the first `offset` transfers.
+    pub async fn get_brc20_transfers(
+        &self,
+        address: &Address,
+        offset: u64,
+        limit: usize,
+    ) -> Result<Vec<Brc20Transfer>, DepositError> {
+        self.index_provider
+            .get_brc20_transfers(address, offset, limit)
+            .await
+    }
+
+    /// Returns the metadata of the inscription, if any.
+    pub async fn get_inscription_metadata(
+        &self,
+        inscription_id: &InscriptionId,
+    ) -> Result<Option<serde_json::Value>, DepositError> {
+        self.index_provider
+            .get_inscription_metadata(inscription_id)
+            .await
+    }
+
+    /// Converts the amount of the token to its integer representation.
+    pub async fn to_integer_amount(
+        &self,
+        tick: &Brc20Tick,
+        amount: Decimal,
+    ) -> Result<u128, DepositError> {
         let info = self.get_brc20_info(tick).await.ok_or_else(|| {
             DepositError::Unavailable(format!(
                 "Brc20 information for {tick} is not available. Please try again later."
             ))
         })?;
 
-        let amount = balances.get(tick).copied().unwrap_or_default();
-
         Self::get_integer_amount(amount, info.decimals)
     }
 
     /// Converts the amount to the integer representation of the token.
-    fn get_integer_amount(amount: Decimal, decimals: u8) -> Result<u128, DepositError> {
+    pub(crate) fn get_integer_amount(amount: Decimal, decimals: u8) -> Result<u128, DepositError> {
         use rust_decimal::prelude::ToPrimitive;
 
         let factor = Decimal::new(10i64.pow(decimals as u32), 0);
-        (amount * factor).trunc().to_u128().ok_or_else(|| {
+        let integer_amount = amount
+            .checked_mul(factor)
+            .and_then(|amount| amount.trunc().to_u128());
+        integer_amount.ok_or_else(|| {
             DepositError::AmountTooBig(format!(
                 "Amount {amount} with {decimals} decimals is too large to be represented as u128."
             ))
@@ -282,25 +343,37 @@ impl<UTXO: UtxoProvider, INDEX: Brc20IndexProvider> Brc20Deposit<UTXO, INDEX> {
     }
 
     async fn get_brc20_info_from_indexer(&self, tick: &Brc20Tick) -> Option<Brc20Info> {
-        let brc20_list = self.index_provider.get_brc20_tokens().await.ok()?;
-        let brc20s: HashMap<Brc20Tick, Brc20Info> = brc20_list
-            .iter()
-            .map(|(brc20_id, info)| (*brc20_id, *info))
-            .collect();
-
-        let res = match brc20s.get(tick) {
-            Some(v) => Some(*v),
-            None => {
+        let info = match self.index_provider.get_brc20_token(tick).await {
+            Ok(info) => info,
+            Err(err) => {
                 log::error!(
-                    "Ord indexer didn't return a brc20 information for brc20 {tick} that was present in an UTXO"
+                    "Failed to get brc20 information for brc20 {tick} that was present in an UTXO: {err:?}"
                 );
-                None
+                return None;
             }
         };
 
-        self.brc20_state.borrow_mut().update_brc20_tokens(brc20s);
+        self.brc20_state.borrow_mut().add_brc20_tokens([info]);
+
+        Some(info)
+    }
+
+    /// Requests the tokens deployed since the previous refresh and adds them to the cache.
+    ///
+    /// Returns the number of the added tokens.
+    pub async fn refresh_brc20_tokens(&self) -> Result<usize, DepositError> {
+        let offset = self.brc20_state.borrow().brc20_tokens_refresh_offset();
+        let tokens = self
+            .index_provider
+            .get_brc20_tokens(offset, BRC20_TOKENS_REFRESH_LIMIT)
+            .await?;
+
+        let count = tokens.len();
+        let mut state = self.brc20_state.borrow_mut();
+        state.add_brc20_tokens(tokens);
+        state.set_brc20_tokens_refresh_offset(offset + count as u64);
 
-        res
+        Ok(count)
     }
 
     /// Create the unsigned mint order
@@ -316,7 +389,7 @@ impl<UTXO: UtxoProvider, INDEX: Brc20IndexProvider> Brc20Deposit<UTXO, INDEX> {
 
         let sender_chain_id = state_ref.btc_chain_id();
         let sender = Id256::from_evm_address(dst_address, sender_chain_id);
-        let src_token = Id256::from_brc20_tick(brc20_info.tick.inner());
+        let src_token = Id256::from(brc20_info.tick);
 
         let recipient_chain_id = self
             .runtime_state
@@ -1,18 +1,20 @@
 mod hiro;
 
 use std::collections::{HashMap, HashSet};
-use std::future::Future;
 use std::str::FromStr;
 
-use bitcoin::Address;
+use bitcoin::{Address, Txid};
+use bitcoin_bridge_core::consensus::get_indexer_consensus;
+use bitcoin_bridge_core::http::HttpClient;
 use bridge_did::brc20_info::{Brc20Info, Brc20Tick};
-use ic_exports::ic_cdk::api::management_canister::http_request::{
-    CanisterHttpRequestArgument, HttpHeader, HttpMethod, http_request,
-};
+use bridge_did::inscription::InscriptionId;
 use rust_decimal::Decimal;
 use serde::de::DeserializeOwned;
 
-use self::hiro::{GetBrc20BalancesResponse, GetBrc20TokensResponse};
+use self::hiro::{
+    Brc20ActivityResponse, Brc20TokenResponse, GetBrc20ActivityResponse, GetBrc20BalancesResponse,
+    GetBrc20TokenResponse, GetBrc20TokensResponse, GetInscriptionResponse,
+};
 use crate::interface::DepositError;
 
 pub(crate) trait Brc20IndexProvider {
@@ -22,67 +24,53 @@ pub(crate) trait Brc20IndexProvider {
         address: &Address,
     ) -> Result<HashMap<Brc20Tick, Decimal>, DepositError>;
 
-    /// Get list of BRC20 tokens.
-    async fn get_brc20_tokens(&self) -> Result<HashMap<Brc20Tick, Brc20Info>, DepositError>;
-}
-
-const CYCLES_PER_HTTP_REQUEST: u128 = 500_000_000;
-const MAX_RESPONSE_BYTES: u64 = 10_000;
-const HIRO_MAX_LIMIT: u64 = 60;
+    /// Get information about the BRC20 token with the given tick.
+    async fn get_brc20_token(&self, tick: &Brc20Tick) -> Result<Brc20Info, DepositError>;
 
-/// Trait for a generic HTTP client that can be used to make requests to the indexer.
-pub trait HttpClient {
-    fn http_request<R: DeserializeOwned>(
+    /// Get at most `limit` BRC20 tokens in the order of their deployment, skipping the first
+    /// `offset` tokens.
+    async fn get_brc20_tokens(
         &self,
-        url: &str,
-        uri: &str,
-    ) -> impl Future<Output = Result<R, DepositError>>;
-}
+        offset: u64,
+        limit: usize,
+    ) -> Result<Vec<Brc20Info>, DepositError>;
 
-/// HTTP client implementation for the Internet Computer canisters.
-pub struct IcHttpClient;
+    /// Get at most `limit` BRC20 transfers sent from or to the given address, from the newest to
+    /// the oldest, skipping the first `offset` transfers.
+    async fn get_brc20_transfers(
+        &self,
+        address: &Address,
+        offset: u64,
+        limit: usize,
+    ) -> Result<Vec<Brc20Transfer>, DepositError>;
 
-impl HttpClient for IcHttpClient {
-    async fn http_request<R: DeserializeOwned>(
+    /// Get the metadata of the inscription, if any.
+    async fn get_inscription_metadata(
         &self,
-        url: &str,
-        uri: &str,
-    ) -> Result<R, DepositError> {
-        let url = format!("{url}/{}", uri.trim_start_matches('/'));
-
-        log::trace!("Sending indexer request to: {url}");
-
-        let request_params = CanisterHttpRequestArgument {
-            url,
-            max_response_bytes: Some(MAX_RESPONSE_BYTES),
-            method: HttpMethod::GET,
-            headers: vec![HttpHeader {
-                name: "Accept".to_string(),
-                value: "application/json".to_string(),
-            }],
-            body: None,
-            transform: None,
-        };
-
-        let result = http_request(request_params, CYCLES_PER_HTTP_REQUEST)
-            .await
-            .map_err(|err| DepositError::Unavailable(format!("Indexer unavailable: {err:?}")))?
-            .0;
-
-        log::trace!(
-            "Indexer responded with: {} {:?} BODY: {}",
-            result.status,
-            result.headers,
-            String::from_utf8_lossy(&result.body)
-        );
+        inscription_id: &InscriptionId,
+    ) -> Result<Option<serde_json::Value>, DepositError>;
+}
 
-        serde_json::from_slice(&result.body).map_err(|err| {
-            log::error!("Failed to get rune balance from the indexer: {err:?}");
-            DepositError::Unavailable(format!("Unexpected response from indexer: {err:?}"))
-        })
-    }
+/// BRC20 transfer inscription sent from one address to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Brc20Transfer {
+    pub inscription_id: InscriptionId,
+    pub tick: Brc20Tick,
+    pub amount: Decimal,
+    pub from_address: String,
+    /// `None` if the inscription was burnt.
+    pub to_address: Option<String>,
+    pub block_height: u64,
+    /// Transaction sending the inscription.
+    pub txid: Txid,
 }
 
+/// Maximum size of a response describing a single entity, e.g. a BRC20 token.
+const MAX_RESPONSE_BYTES: u64 = 10_000;
+/// Maximum size of a page of a list response.
+const MAX_PAGE_RESPONSE_BYTES: u64 = 200_000;
+const HIRO_MAX_LIMIT: u64 = 60;
+
 /// Implementation of the `RuneIndexProvider` trait that uses the `HttpClient` to make requests to
 pub struct OrdIndexProvider<C: HttpClient> {
     client: C,
@@ -102,52 +90,24 @@ where
         }
     }
 
-    /// Get consensus response from the indexer.
+    /// Get consensus response from the indexers.
     ///
-    /// All indexers must return the same response for the same input, other
-    /// the function will return an error.
-    async fn get_consensus_response<T>(&self, uri: &str) -> Result<T, DepositError>
+    /// All indexers must return the same response for the same input, otherwise the function
+    /// returns an error.
+    async fn get_consensus_response<T>(
+        &self,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> Result<T, DepositError>
     where
-        T: Clone + DeserializeOwned + PartialEq + std::fmt::Debug,
+        T: DeserializeOwned + PartialEq + std::fmt::Debug,
     {
-        let mut failed_urls = Vec::with_capacity(self.indexer_urls.len());
-        let mut responses: Vec<(String, T)> = Vec::new();
-        let mut indexers_agree = true;
-
-        for url in &self.indexer_urls {
-            match self.client.http_request::<T>(url, uri).await {
-                Ok(response) => {
-                    if !responses.is_empty() && responses[0].1 != response {
-                        indexers_agree = false;
-                    }
-
-                    responses.push((url.clone(), response));
-                }
-                Err(e) => {
-                    log::warn!("Failed to get response from indexer {}: {:?}", url, e);
-                    failed_urls.push(url.clone());
-                }
-            }
-        }
-
-        if responses.len() < self.indexer_consensus_threshold as usize {
-            Err(DepositError::InsufficientConsensus {
-                received_responses: responses.len(),
-                required_responses: self.indexer_consensus_threshold,
-                checked_indexers: self.indexer_urls.len(),
-            })
-        } else if !indexers_agree {
-            // TODO: After https://infinityswap.atlassian.net/browse/EPROD-971 is done, return
-            // actual values here instead of formated response
-            Err(DepositError::IndexersDisagree {
-                indexer_responses: responses
-                    .into_iter()
-                    .map(|(url, response)| (url, format!("{response:?}")))
-                    .collect(),
-            })
-        } else {
-            Ok(responses.pop().expect("responses vector is empty").1)
-        }
+        Ok(get_indexer_consensus(
+            self.indexer_urls.iter(),
+            self.indexer_consensus_threshold,
+            |url| self.client.http_request::<T>(url, uri, max_response_bytes),
+        )
+        .await?)
     }
 }
 
@@ -168,7 +128,7 @@ where
                 "/ordinals/v1/brc-20/balances/{address}?offset={offset}&limit={HIRO_MAX_LIMIT}"
             );
             let response = self
-                .get_consensus_response::<GetBrc20BalancesResponse>(&uri)
+                .get_consensus_response::<GetBrc20BalancesResponse>(&uri, MAX_PAGE_RESPONSE_BYTES)
                 .await?;
 
             // update total
@@ -191,40 +151,200 @@ where
         Ok(balances)
     }
 
-    async fn get_brc20_tokens(&self) -> Result<HashMap<Brc20Tick, Brc20Info>, DepositError> {
-        let mut tokens = HashMap::new();
-        let mut offset = 0;
-        let mut total = usize::MAX;
+    async fn get_brc20_token(&self, tick: &Brc20Tick) -> Result<Brc20Info, DepositError> {
+        let uri = format!("/ordinals/v1/brc-20/tokens/{}", encode_tick(tick));
+        let response = self
+            .get_consensus_response::<GetBrc20TokenResponse>(&uri, MAX_RESPONSE_BYTES)
+            .await?;
+
+        let info = parse_brc20_token(&response.token)?;
+        if info.tick != *tick {
+            return Err(DepositError::Unavailable(format!(
+                "Indexer returned BRC20 token {} instead of {tick}",
+                info.tick
+            )));
+        }
 
-        while offset < total {
-            let uri = format!("/ordinals/v1/brc-20/tokens?offset={offset}&limit={HIRO_MAX_LIMIT}");
+        Ok(info)
+    }
+
+    async fn get_brc20_tokens(
+        &self,
+        offset: u64,
+        limit: usize,
+    ) -> Result<Vec<Brc20Info>, DepositError> {
+        let mut tokens = vec![];
+        let mut offset = offset;
+
+        while tokens.len() < limit {
+            let page_limit = HIRO_MAX_LIMIT.min((limit - tokens.len()) as u64);
+            let uri = format!(
+                "/ordinals/v1/brc-20/tokens?order_by=index&order=asc&offset={offset}&limit={page_limit}"
+            );
             let response = self
-                .get_consensus_response::<GetBrc20TokensResponse>(&uri)
+                .get_consensus_response::<GetBrc20TokensResponse>(&uri, MAX_PAGE_RESPONSE_BYTES)
                 .await?;
 
-            // update total
-            total = response.total as usize;
-            // increment offset
-            offset += response.results.len();
+            offset += response.results.len() as u64;
 
-            for result in response.results {
-                let tick = Brc20Tick::from_str(&result.ticker).map_err(|_| {
-                    DepositError::Unavailable(format!(
-                        "Invalid BRC20 token ticker: {}",
-                        result.ticker
-                    ))
-                })?;
+            for result in &response.results {
+                tokens.push(parse_brc20_token(result)?);
+            }
 
-                tokens.insert(
-                    tick,
-                    Brc20Info {
-                        tick,
-                        decimals: result.decimals,
-                    },
-                );
+            if response.results.is_empty() || offset >= response.total {
+                break;
             }
         }
 
         Ok(tokens)
     }
+
+    async fn get_brc20_transfers(
+        &self,
+        address: &Address,
+        offset: u64,
+        limit: usize,
+    ) -> Result<Vec<Brc20Transfer>, DepositError> {
+        let limit = HIRO_MAX_LIMIT.min(limit as u64);
+        let uri = format!(
+            "/ordinals/v1/brc-20/activity?operation=transfer_send&address={address}&offset={offset}&limit={limit}"
+        );
+        let response = self
+            .get_consensus_response::<GetBrc20ActivityResponse>(&uri, MAX_PAGE_RESPONSE_BYTES)
+            .await?;
+
+        response.results.iter().map(parse_brc20_transfer).collect()
+    }
+
+    async fn get_inscription_metadata(
+        &self,
+        inscription_id: &InscriptionId,
+    ) -> Result<Option<serde_json::Value>, DepositError> {
+        let uri = format!("/ordinals/v1/inscriptions/{inscription_id}");
+        let response = self
+            .get_consensus_response::<GetInscriptionResponse>(&uri, MAX_RESPONSE_BYTES)
+            .await?;
+
+        Ok(response.metadata)
+    }
+}
+
+/// Percent-encodes the tick to be used as a path segment, since ticks may contain any UTF-8
+/// characters.
+fn encode_tick(tick: &Brc20Tick) -> String {
+    tick.as_bytes()
+        .iter()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (*byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn parse_brc20_token(token: &Brc20TokenResponse) -> Result<Brc20Info, DepositError> {
+    let tick = Brc20Tick::from_str(&token.ticker).map_err(|_| {
+        DepositError::Unavailable(format!("Invalid BRC20 token ticker: {}", token.ticker))
+    })?;
+
+    Ok(Brc20Info {
+        tick,
+        decimals: token.decimals,
+    })
+}
+
+fn parse_brc20_transfer(activity: &Brc20ActivityResponse) -> Result<Brc20Transfer, DepositError> {
+    let tick = Brc20Tick::from_str(&activity.ticker).map_err(|_| {
+        DepositError::Unavailable(format!("Invalid BRC20 token ticker: {}", activity.ticker))
+    })?;
+    let inscription_id = InscriptionId::from_str(&activity.inscription_id).map_err(|_| {
+        DepositError::Unavailable(format!(
+            "Invalid inscription id: {}",
+            activity.inscription_id
+        ))
+    })?;
+    let txid = Txid::from_str(&activity.tx_id).map_err(|_| {
+        DepositError::Unavailable(format!("Invalid transaction id: {}", activity.tx_id))
+    })?;
+    let transfer = activity.transfer_send.as_ref().ok_or_else(|| {
+        DepositError::Unavailable(format!(
+            "Indexer returned activity of inscription {inscription_id} without transfer details"
+        ))
+    })?;
+
+    Ok(Brc20Transfer {
+        inscription_id,
+        tick,
+        amount: transfer.amount,
+        from_address: transfer.from_address.clone(),
+        to_address: transfer.to_address.clone(),
+        block_height: activity.block_height,
+        txid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_encode_tick_for_uri() {
+        assert_eq!(encode_tick(&Brc20Tick::from_str("ordi").unwrap()), "ordi");
+        assert_eq!(
+            encode_tick(&Brc20Tick::from_str("$ORDI").unwrap()),
+            "%24ordi"
+        );
+        assert_eq!(
+            encode_tick(&Brc20Tick::from_str("🐸").unwrap()),
+            "%F0%9F%90%B8"
+        );
+    }
+
+    #[test]
+    fn test_should_parse_long_ticker() {
+        let info = parse_brc20_token(&Brc20TokenResponse {
+            ticker: "PIZZA".to_string(),
+            decimals: 18,
+        })
+        .unwrap();
+
+        assert_eq!(info.tick.as_str(), "pizza");
+        assert_eq!(info.decimals, 18);
+    }
+
+    #[test]
+    fn test_should_parse_transfer_activity() {
+        let response: GetBrc20ActivityResponse = serde_json::from_str(
+            r#"{
+                "total": 1,
+                "results": [{
+                    "operation": "transfer_send",
+                    "ticker": "ordi",
+                    "inscription_id": "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0",
+                    "block_height": 840000,
+                    "tx_id": "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799",
+                    "transfer_send": {
+                        "amount": "100.500000000000000000",
+                        "from_address": "bc1qsender",
+                        "to_address": "bc1qbridge"
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let transfer = parse_brc20_transfer(&response.results[0]).unwrap();
+
+        assert_eq!(transfer.tick.as_str(), "ordi");
+        assert_eq!(transfer.inscription_id.index, 0);
+        assert_eq!(transfer.amount, Decimal::new(1005, 1));
+        assert_eq!(transfer.from_address, "bc1qsender");
+        assert_eq!(transfer.to_address.as_deref(), Some("bc1qbridge"));
+        assert_eq!(transfer.block_height, 840_000);
+        assert_eq!(
+            transfer.txid.to_string(),
+            "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799"
+        );
+    }
 }
@@ -2,6 +2,9 @@ use rust_decimal::Decimal;
 use serde::Deserialize;
 
 /// Response for `/ordinals/v1/brc-20/tokens` endpoint.
+///
+/// With `order_by=index&order=asc` the tokens are listed in the order of their deployment, so
+/// the tokens deployed since the previous request are always at the end of the list.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct GetBrc20TokensResponse {
     pub total: u64,
@@ -14,6 +17,12 @@ pub struct Brc20TokenResponse {
     pub decimals: u8,
 }
 
+/// Response for `/ordinals/v1/brc-20/tokens/{ticker}` endpoint.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GetBrc20TokenResponse {
+    pub token: Brc20TokenResponse,
+}
+
 /// Response for `/ordinals/v1/brc-20/balances/{address}` endpoint.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct GetBrc20BalancesResponse {
@@ -26,3 +35,36 @@ pub struct Brc20BalanceResponse {
     pub ticker: String,
     pub overall_balance: Decimal,
 }
+
+/// Response for `/ordinals/v1/brc-20/activity` endpoint. The activity is listed from the newest
+/// to the oldest.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GetBrc20ActivityResponse {
+    pub total: u64,
+    pub results: Vec<Brc20ActivityResponse>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Brc20ActivityResponse {
+    pub ticker: String,
+    pub inscription_id: String,
+    pub block_height: u64,
+    pub tx_id: String,
+    /// Set for the `transfer_send` operations only.
+    pub transfer_send: Option<Brc20TransferSendResponse>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Brc20TransferSendResponse {
+    pub amount: Decimal,
+    pub from_address: String,
+    /// `None` if the inscription was burnt.
+    pub to_address: Option<String>,
+}
+
+/// Response for `/ordinals/v1/inscriptions/{id}` endpoint.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GetInscriptionResponse {
+    /// Inscription metadata decoded from CBOR.
+    pub metadata: Option<serde_json::Value>,
+}
@@ -1,6 +1,8 @@
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::Duration;
 
 use bitcoin::absolute::LockTime;
 use bitcoin::bip32::DerivationPath;
@@ -11,6 +13,11 @@ use bitcoin::{
     Address, Amount, FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
     Txid, Witness,
 };
+use bitcoin_bridge_core::funding::{
+    DUST_THRESHOLD, greedy_funding_candidates, replacement_fee, select_funding_utxos, split_fee,
+};
+use bitcoin_bridge_core::key::get_derivation_path;
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
 use bridge_did::brc20_info::{Brc20Info, Brc20Tick};
 use bridge_did::event_data::BurntEventData;
 use bridge_did::id256::Id256;
@@ -25,11 +32,10 @@ use ord_rs::{
     RevealTransactionArgs, SignCommitTransactionArgs,
 };
 
-use super::utxo_provider::{IcUtxoProvider, UtxoProvider};
 use crate::canister::{get_brc20_state, get_runtime_state};
 use crate::constants::FEE_RATE_UPDATE_INTERVAL;
 use crate::interface::WithdrawError;
-use crate::key::{BtcSignerType, get_derivation_path};
+use crate::key::BtcSignerType;
 use crate::ledger::UtxoKey;
 use crate::state::Brc20State;
 
@@ -88,9 +94,21 @@ pub fn new_withdraw_payload(
         request_ts: ic::time(),
         sender,
         dst_address: address.assume_checked().to_string(),
+        funding_account: None,
     })
 }
 
+/// Parses the recipient address of the withdrawal.
+pub fn parse_dst_address(dst_address: &str) -> Result<Address, WithdrawError> {
+    Address::from_str(dst_address)
+        .map(|address| address.assume_checked())
+        .map_err(|_| {
+            WithdrawError::InvalidRequest(format!(
+                "Failed to decode recipient address from string: {dst_address}"
+            ))
+        })
+}
+
 pub(crate) struct Withdrawal<UTXO: UtxoProvider> {
     state: Rc<RefCell<Brc20State>>,
     utxo_provider: UTXO,
@@ -110,6 +128,7 @@ impl Withdrawal<IcUtxoProvider> {
 
         let network = state_ref.network();
         let ic_network = state_ref.ic_btc_network();
+        let fee_estimator = state_ref.fee_estimator();
         let signer = state_ref
             .btc_signer(&signing_strategy)
             .ok_or(WithdrawError::SignerNotInitialized)?;
@@ -120,7 +139,7 @@ impl Withdrawal<IcUtxoProvider> {
             state,
             network,
             signer,
-            utxo_provider: IcUtxoProvider::new(ic_network),
+            utxo_provider: IcUtxoProvider::new(ic_network, Duration::ZERO, fee_estimator),
         })
     }
 
@@ -135,15 +154,15 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
         &self,
         payload: Brc20WithdrawalPayload,
     ) -> Result<Brc20Transactions, WithdrawError> {
+        let funding_account = payload.funding_account().clone();
         let Brc20WithdrawalPayload {
-            sender,
             amount,
             brc20_info: Brc20Info { tick, decimals },
             ..
         } = payload;
 
         let fee_rate = self.get_fee_rate().await?;
-        let funding_address = self.get_funding_address(&sender).await?;
+        let funding_address = self.get_funding_address(&funding_account).await?;
         let reveal_recipient_address = funding_address.clone();
         log::debug!(
             "funding address: {funding_address}; reveal recipient address: {reveal_recipient_address}"
@@ -154,7 +173,7 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
         let funding_utxos = self.get_funding_utxos(&funding_address).await?;
 
         // make brc20 transfer inscription
-        let derivation_path = get_derivation_path(&sender)?;
+        let derivation_path = get_derivation_path(&funding_account)?;
         let mut inscriber = self.get_inscriber(&derivation_path).await?;
         let CommitTransaction {
             create_commit_transaction,
@@ -256,10 +275,10 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
     /// Await inscription reveal transaction
     pub async fn await_inscription_transactions(
         &self,
-        sender_address: &H160,
+        funding_account: &H160,
         reveal_utxo: RevealUtxo,
     ) -> Result<Utxo, WithdrawError> {
-        let reveal_recipient_address = self.get_funding_address(sender_address).await?;
+        let reveal_recipient_address = self.get_funding_address(funding_account).await?;
         let txid = Txid::from_slice(&reveal_utxo.txid).unwrap();
         log::debug!(
             "checking whether the reveal transaction {txid} is confirmed for address {reveal_recipient_address}"
@@ -277,32 +296,205 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             .ok_or(WithdrawError::TxNotConfirmed)
     }
 
+    /// Check whether the transfer transaction or one of the transactions it replaced is
+    /// confirmed. Returns the id of the confirmed transaction.
+    ///
+    /// The transaction is confirmed when its output has enough confirmations at the recipient
+    /// address. As the recipient may spend the output right away, the transaction is also
+    /// considered confirmed once its reveal utxo input is spent; only one of the versions of the
+    /// transaction could spend it.
+    pub async fn await_transfer_transaction(
+        &self,
+        funding_account: &H160,
+        tx: &Transaction,
+        replaced_txids: &[Txid],
+    ) -> Result<Txid, WithdrawError> {
+        let txid = tx.txid();
+        let output = tx
+            .output
+            .first()
+            .ok_or(WithdrawError::TransactionCreation)?;
+        let recipient = Address::from_script(&output.script_pubkey, self.network)
+            .map_err(|_| WithdrawError::TransactionCreation)?;
+
+        let response = self
+            .utxo_provider
+            .get_utxos(&recipient)
+            .await
+            .map_err(|_| WithdrawError::TxNotConfirmed)?;
+        let min_confirmations = self.state.borrow().min_confirmations();
+        let confirmed_txid = [txid].iter().chain(replaced_txids).find(|txid| {
+            response.utxos.iter().any(|utxo| {
+                utxo.outpoint.txid == txid.as_byte_array()
+                    && utxo.outpoint.vout == 0
+                    && (response.tip_height + 1).saturating_sub(utxo.height) >= min_confirmations
+            })
+        });
+        if let Some(confirmed_txid) = confirmed_txid {
+            return Ok(*confirmed_txid);
+        }
+
+        let reveal_input = tx.input.first().ok_or(WithdrawError::NoInputs)?;
+        let reveal_owner = self.get_funding_address(funding_account).await?;
+        let reveal_unspent = self
+            .utxo_provider
+            .get_utxos(&reveal_owner)
+            .await
+            .map_err(|_| WithdrawError::TxNotConfirmed)?
+            .utxos
+            .iter()
+            .any(|utxo| {
+                utxo.outpoint.txid == reveal_input.previous_output.txid.as_byte_array()
+                    && utxo.outpoint.vout == reveal_input.previous_output.vout
+            });
+
+        if reveal_unspent {
+            log::debug!("transfer transaction {txid} is not confirmed yet");
+            Err(WithdrawError::TxNotConfirmed)
+        } else {
+            Ok(txid)
+        }
+    }
+
+    /// Creates a transaction replacing the given transfer transaction (BIP-125) with a higher
+    /// fee.
+    ///
+    /// The transfer transaction spends all the value of its funding inputs on the fee, so the
+    /// replacement adds more funding utxos of the funding account to pay the higher fee and
+    /// returns the rest of them to the funding address in a change output.
+    pub async fn replace_transfer_transaction(
+        &self,
+        funding_account: &H160,
+        tx: &Transaction,
+    ) -> Result<Transaction, WithdrawError> {
+        let funding_address = self.get_funding_address(funding_account).await?;
+        let derivation_path = get_derivation_path(funding_account)?;
+        let fee_rate = self.get_fee_rate().await?;
+
+        // the inputs of the unconfirmed transaction are still unspent
+        let unspent = self
+            .utxo_provider
+            .get_utxos(&funding_address)
+            .await
+            .map_err(|_| WithdrawError::NoInputs)?
+            .utxos;
+        let mut inputs = Vec::with_capacity(tx.input.len() + 1);
+        for input in &tx.input {
+            let utxo = unspent
+                .iter()
+                .find(|utxo| {
+                    utxo.outpoint.txid == input.previous_output.txid.as_byte_array()
+                        && utxo.outpoint.vout == input.previous_output.vout
+                })
+                .ok_or(WithdrawError::NoInputs)?;
+            inputs.push(utxo.clone());
+        }
+
+        let inputs_value: u64 = inputs.iter().map(|utxo| utxo.value).sum();
+        let outputs_value: u64 = tx.output.iter().map(|output| output.value.to_sat()).sum();
+        let fee = inputs_value.saturating_sub(outputs_value);
+
+        let mut outputs = tx.output.clone();
+        outputs.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: funding_address.script_pubkey(),
+        });
+        let replacement_fee_for = |extra_inputs: usize| {
+            let vsize = estimate_transaction_fees(
+                ScriptType::P2WSH,
+                inputs.len() + extra_inputs,
+                FeeRate::BROADCAST_MIN,
+                &None,
+                outputs.clone(),
+            )
+            .to_sat();
+            replacement_fee(fee, vsize, fee_rate)
+        };
+
+        let candidates: Vec<Utxo> = self
+            .get_funding_utxos(&funding_address)
+            .await?
+            .into_iter()
+            .filter(|utxo| {
+                !inputs.iter().any(|input| {
+                    input.outpoint.txid == utxo.outpoint.txid
+                        && input.outpoint.vout == utxo.outpoint.vout
+                })
+            })
+            .collect();
+        let extra_utxos = select_funding_utxos(candidates, |count| {
+            (replacement_fee_for(count) + DUST_THRESHOLD).saturating_sub(fee)
+        })
+        .ok_or(WithdrawError::InsufficientFunds)?;
+
+        let new_fee = replacement_fee_for(extra_utxos.len());
+        inputs.extend(extra_utxos);
+        let change = inputs.iter().map(|utxo| utxo.value).sum::<u64>() - outputs_value - new_fee;
+        if let Some(change_output) = outputs.last_mut() {
+            change_output.value = Amount::from_sat(change);
+        }
+
+        let mut tx_in = Vec::with_capacity(inputs.len());
+        let mut tx_input_info = Vec::with_capacity(inputs.len());
+        for utxo in &inputs {
+            let outpoint = OutPoint {
+                txid: Txid::from_slice(&utxo.outpoint.txid)
+                    .map_err(|_| WithdrawError::InvalidTxid(utxo.outpoint.txid.to_vec()))?,
+                vout: utxo.outpoint.vout,
+            };
+            tx_in.push(TxIn {
+                previous_output: outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            });
+            tx_input_info.push(TxInputInfo {
+                outpoint,
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: funding_address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            });
+        }
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: tx_in,
+            output: outputs,
+        };
+        let replacement = self
+            .sign_transfer_transaction(unsigned_tx, &tx_input_info)
+            .await?;
+
+        log::info!(
+            "Created transfer transaction {} replacing {} with fee {new_fee}",
+            replacement.txid(),
+            tx.txid()
+        );
+
+        Ok(replacement)
+    }
+
     /// Build transfer transaction
     ///
     /// The transfer transaction has the following inputs:
     ///
     /// - the reveal utxo, owned by the change address
-    /// - the funding utxos, owned by the address associated with the sender
+    /// - the funding utxos, owned by the address of the funding account of the withdrawal
     pub async fn build_transfer_transaction(
         &self,
         payload: Brc20WithdrawalPayload,
         reveal_utxo: Utxo,
     ) -> Result<DidTransaction, WithdrawError> {
-        let Brc20WithdrawalPayload {
-            dst_address,
-            sender,
-            ..
-        } = payload;
+        let funding_account = payload.funding_account().clone();
+        let Brc20WithdrawalPayload { dst_address, .. } = payload;
 
-        let funding_address = self.get_funding_address(&sender).await?;
+        let funding_address = self.get_funding_address(&funding_account).await?;
         let fee_rate = self.get_fee_rate().await?;
 
-        let Ok(dst_address) = Address::from_str(&dst_address) else {
-            return Err(WithdrawError::InvalidRequest(format!(
-                "Failed to decode recipient address from string: {dst_address}"
-            )));
-        };
-        let dst_address = dst_address.assume_checked();
+        let dst_address = parse_dst_address(&dst_address)?;
 
         // get greedy funding utxos
         let funding_utxos = self
@@ -322,7 +514,7 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             self.build_unsigned_transfer_transaction(&reveal_utxo, &funding_utxos, dst_address)?;
 
         // get transaction input info
-        let funding_dp = get_derivation_path(&sender)?;
+        let funding_dp = get_derivation_path(&funding_account)?;
         let tx_input_info = self.transfer_tx_input_info(
             &reveal_utxo,
             &funding_utxos,
@@ -338,6 +530,178 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             .map(DidTransaction)
     }
 
+    /// Creates a single transfer transaction for all the given withdrawals.
+    ///
+    /// The reveal utxos holding the transfer inscriptions are the first inputs of the
+    /// transaction and the destination outputs are the first outputs, in the same order, so
+    /// every inscription ends up at the first sat of its destination output. The transaction fee
+    /// is split between the withdrawals pro-rata to the number of inputs and outputs each of them
+    /// adds to the transaction. Withdrawals whose funding addresses don't have enough BTC to pay
+    /// their share are left out of the batch.
+    pub async fn create_batch_transfer_transaction(
+        &self,
+        transfers: &[(Brc20WithdrawalPayload, Utxo)],
+    ) -> Result<BatchTransferTransaction, WithdrawError> {
+        let fee_rate = self.get_fee_rate().await?;
+
+        let mut participants = Vec::with_capacity(transfers.len());
+        for (index, (payload, reveal_utxo)) in transfers.iter().enumerate() {
+            // destinations are validated when the transfers are added to the batch, so an
+            // invalid one here must not prevent the others from being sent
+            let dst_address = match parse_dst_address(&payload.dst_address) {
+                Ok(address) => address,
+                Err(err) => {
+                    log::warn!("Transfer {index} is left out of the batch: {err:?}");
+                    continue;
+                }
+            };
+
+            let funding_address = self.get_funding_address(payload.funding_account()).await?;
+            let mut funding_utxos = self.get_funding_utxos(&funding_address).await?;
+            // sort the utxos by value; ascending, so the biggest one is popped first
+            funding_utxos.sort_by(|a, b| a.value.cmp(&b.value));
+
+            participants.push(BatchParticipant {
+                index,
+                reveal_utxo: reveal_utxo.clone(),
+                dst_address,
+                funding_address,
+                derivation_path: get_derivation_path(payload.funding_account())?,
+                available_utxos: funding_utxos,
+                selected_utxos: vec![],
+            });
+        }
+
+        let mut taken_utxos = HashSet::new();
+        let fee_shares = loop {
+            participants.retain_mut(|participant| {
+                !participant.selected_utxos.is_empty()
+                    || participant.select_next_utxo(&mut taken_utxos)
+            });
+
+            if participants.is_empty() {
+                return Err(WithdrawError::InsufficientFunds);
+            }
+
+            let outputs = batch_transfer_outputs(&participants, &vec![0; participants.len()]);
+            let inputs_count = participants
+                .iter()
+                .map(|participant| participant.selected_utxos.len() + 1)
+                .sum::<usize>();
+            let fee = estimate_transaction_fees(
+                ScriptType::P2WSH,
+                inputs_count,
+                fee_rate,
+                &None,
+                outputs,
+            )
+            .to_sat();
+
+            let weights: Vec<u64> = participants.iter().map(BatchParticipant::weight).collect();
+            let fee_shares = split_fee(fee, &weights);
+
+            let mut changed = false;
+            let mut excluded = vec![];
+            for (participant, fee_share) in participants.iter_mut().zip(&fee_shares) {
+                if participant.funding_value() >= *fee_share {
+                    continue;
+                }
+
+                changed = true;
+                if !participant.select_next_utxo(&mut taken_utxos) {
+                    log::debug!(
+                        "Transfer to {} is left out of the batch: not enough funds to pay the fee share {fee_share}",
+                        participant.dst_address
+                    );
+                    excluded.push(participant.index);
+                }
+            }
+
+            participants.retain(|participant| {
+                if excluded.contains(&participant.index) {
+                    // release the utxos so they can be used by other withdrawals of the same user
+                    for utxo in &participant.selected_utxos {
+                        taken_utxos.remove(&UtxoKey::from(&utxo.outpoint));
+                    }
+                    false
+                } else {
+                    true
+                }
+            });
+
+            if !changed {
+                break fee_shares;
+            }
+        };
+
+        let mut reveal_inputs = Vec::with_capacity(participants.len());
+        let mut funding_inputs = vec![];
+        for participant in &participants {
+            let mut participant_inputs = self
+                .transfer_tx_input_info(
+                    &participant.reveal_utxo,
+                    &participant.selected_utxos,
+                    &participant.derivation_path,
+                    &participant.funding_address,
+                )?
+                .into_iter();
+            reveal_inputs.extend(participant_inputs.next());
+            funding_inputs.extend(participant_inputs);
+        }
+        // reveal utxos go first, so the inscriptions are transferred to the destination outputs
+        let reveal_outpoints: Vec<OutPoint> =
+            reveal_inputs.iter().map(|input| input.outpoint).collect();
+        let mut inputs = reveal_inputs;
+        inputs.extend(funding_inputs);
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::from_consensus(0xffffffff),
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: batch_transfer_outputs(&participants, &fee_shares),
+        };
+
+        let transaction = self.sign_transfer_transaction(unsigned_tx, &inputs).await?;
+
+        log::info!(
+            "Created batch transfer transaction {} for {} withdrawals",
+            transaction.txid(),
+            participants.len()
+        );
+
+        Ok(BatchTransferTransaction {
+            transaction,
+            fee_shares: participants
+                .iter()
+                .map(|participant| participant.index)
+                .zip(fee_shares)
+                .collect(),
+            reveal_outpoints,
+        })
+    }
+
+    /// Sends the batch transfer transaction and marks the reveal utxos it spends as used.
+    pub async fn send_batch_transfer_transaction(
+        &self,
+        batch: &BatchTransferTransaction,
+    ) -> Result<(), WithdrawError> {
+        self.send_transaction(batch.transaction.clone()).await?;
+
+        for outpoint in &batch.reveal_outpoints {
+            self.mark_reveal_utxo_as_used(outpoint);
+        }
+
+        Ok(())
+    }
+
     /// Build commit transaction
     async fn build_commit_transaction(
         &self,
@@ -345,32 +709,26 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
     ) -> Result<CommitTransaction, WithdrawError> {
         let BuildCommitTransactionArgs {
             inscriber,
-            mut funding_utxos,
+            funding_utxos,
             tick,
             amount,
             wallet_address,
             fee_rate,
             derivation_path,
         } = args;
-        let mut input_count = 1;
-        // sort the utxos by value; descending
-        funding_utxos.sort_by(|a, b| b.value.cmp(&a.value));
-        // convert utxos to ord_utxos
-        let funding_utxos: Vec<ord_rs::Utxo> = funding_utxos
-            .iter()
-            .filter_map(|utxo| {
-                Some(ord_rs::Utxo {
-                    id: Txid::from_slice(&utxo.outpoint.txid).ok()?,
-                    index: utxo.outpoint.vout,
-                    amount: Amount::from_sat(utxo.value),
-                })
-            })
-            .collect();
-
         // try to fund the transaction with the minimum number of utxos
-        while input_count <= funding_utxos.len() {
+        for candidate in greedy_funding_candidates(funding_utxos) {
             let inscription = Brc20::transfer(tick, amount);
-            let inputs: Vec<_> = funding_utxos[0..input_count].to_vec();
+            let inputs: Vec<_> = candidate
+                .iter()
+                .filter_map(|utxo| {
+                    Some(ord_rs::Utxo {
+                        id: Txid::from_slice(&utxo.outpoint.txid).ok()?,
+                        index: utxo.outpoint.vout,
+                        amount: Amount::from_sat(utxo.value),
+                    })
+                })
+                .collect();
 
             log::info!("input_utxos utxos: {}", inputs.len());
             log::debug!("input_utxos: {inputs:?}");
@@ -402,10 +760,9 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                     available,
                 }) => {
                     log::debug!(
-                        "Failed to build commit transaction with {input_count} utxos; required {required}; available {available}, trying with {} utxos",
-                        input_count + 1
+                        "Failed to build commit transaction with {} utxos; required {required}; available {available}, trying with more utxos",
+                        inputs.len()
                     );
-                    input_count += 1;
                 }
                 Err(e) => {
                     log::error!("Failed to build commit transaction: {e}");
@@ -435,7 +792,8 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                     vout: utxo.outpoint.vout,
                 },
                 script_sig: ScriptBuf::new(),
-                sequence: Sequence::from_consensus(0xffffffff),
+                // signal replaceability, so the fee can be bumped if the transaction gets stuck
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
                 witness: Witness::new(),
             });
         }
@@ -577,14 +935,9 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
         &self,
         args: GetGreedyFundingUtxosArgs,
     ) -> Result<Option<Vec<Utxo>>, WithdrawError> {
-        let mut funding_utxos = self.get_funding_utxos(&args.funding_address).await?;
-        let mut utxos_count = 1;
-        // sort the utxos by value; descending
-        funding_utxos.sort_by(|a, b| b.value.cmp(&a.value));
-
-        // try to fund the transaction with the minimum number of utxos
-        while utxos_count <= funding_utxos.len() {
-            let required_fee = estimate_transaction_fees(
+        let funding_utxos = self.get_funding_utxos(&args.funding_address).await?;
+        let solution = select_funding_utxos(funding_utxos, |utxos_count| {
+            estimate_transaction_fees(
                 ScriptType::P2WSH,
                 utxos_count + 1,
                 args.fee_rate,
@@ -593,24 +946,14 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                     value: Amount::ZERO,
                     script_pubkey: args.recipient_address.script_pubkey(),
                 }],
-            );
-
-            // find the minimum number of utxos that can fund the transaction
-            let solution = &funding_utxos[0..utxos_count];
-            let solution_value =
-                Amount::from_sat(solution.iter().map(|utxo| utxo.value).sum::<u64>());
-            if solution_value >= required_fee {
-                log::debug!(
-                    "Found a funding solution with {utxos_count} utxos; required fee {required_fee}; fee funds: {solution_value}"
-                );
-                return Ok(Some(solution.to_vec()));
-            }
+            )
+            .to_sat()
+        });
 
-            // otherwise try with more utxos
-            utxos_count += 1;
+        if let Some(solution) = &solution {
+            log::debug!("Found a funding solution with {} utxos", solution.len());
         }
-
-        Ok(None)
+        Ok(solution)
     }
 
     /// Convert the ERC20 amount to the BRC20 amount.
@@ -632,7 +975,8 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
 
     /// Get utxos available for funding the transaction
     ///
-    /// It will discard utxos that are reveal utxos
+    /// It will discard utxos that are reveal utxos, and the outputs of the transfers to the
+    /// memo deposit address not handled yet, as they pay the deposit fee of these transfers.
     async fn get_funding_utxos(&self, address: &Address) -> Result<Vec<Utxo>, WithdrawError> {
         let utxos = self
             .utxo_provider
@@ -643,6 +987,7 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
 
         let state_ref = self.state.borrow();
         let ledger = state_ref.ledger();
+        let pending_memo_txids = state_ref.pending_memo_deposit_txids();
 
         Ok(utxos
             .into_iter()
@@ -652,10 +997,84 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                     vout: utxo.outpoint.vout,
                 })
             })
+            .filter(|utxo| !pending_memo_txids.contains(utxo.outpoint.txid.as_slice()))
             .collect())
     }
 }
 
+/// Transfer transaction shared by several withdrawals.
+pub struct BatchTransferTransaction {
+    pub transaction: Transaction,
+    /// Indices of the withdrawals included into the transaction with the part of the fee each
+    /// of them pays.
+    pub fee_shares: Vec<(usize, u64)>,
+    reveal_outpoints: Vec<OutPoint>,
+}
+
+/// Withdrawal included into a batch transfer transaction.
+struct BatchParticipant {
+    /// Index of the withdrawal in the batch.
+    index: usize,
+    reveal_utxo: Utxo,
+    dst_address: Address,
+    funding_address: Address,
+    derivation_path: DerivationPath,
+    /// Funding utxos not selected yet, sorted by value; ascending.
+    available_utxos: Vec<Utxo>,
+    selected_utxos: Vec<Utxo>,
+}
+
+impl BatchParticipant {
+    /// Selects the biggest available funding utxo not taken by another participant.
+    fn select_next_utxo(&mut self, taken_utxos: &mut HashSet<UtxoKey>) -> bool {
+        while let Some(utxo) = self.available_utxos.pop() {
+            if taken_utxos.insert(UtxoKey::from(&utxo.outpoint)) {
+                self.selected_utxos.push(utxo);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn funding_value(&self) -> u64 {
+        self.selected_utxos.iter().map(|utxo| utxo.value).sum()
+    }
+
+    /// Number of inputs and outputs the participant adds to the transaction: the reveal and
+    /// funding inputs, the destination output and the change output.
+    fn weight(&self) -> u64 {
+        self.selected_utxos.len() as u64 + 3
+    }
+}
+
+/// Outputs of the batch transfer transaction: a destination output for every participant,
+/// holding the value of its reveal utxo, and then the BTC change outputs of the participants.
+fn batch_transfer_outputs(participants: &[BatchParticipant], fee_shares: &[u64]) -> Vec<TxOut> {
+    let mut outputs = Vec::with_capacity(participants.len() * 2);
+    for participant in participants {
+        outputs.push(TxOut {
+            value: Amount::from_sat(participant.reveal_utxo.value),
+            script_pubkey: participant.dst_address.script_pubkey(),
+        });
+    }
+
+    for (participant, fee_share) in participants.iter().zip(fee_shares) {
+        let change = participant.funding_value().saturating_sub(*fee_share);
+        // zero change is used during fee estimation
+        if change != 0 && change < DUST_THRESHOLD {
+            continue;
+        }
+
+        outputs.push(TxOut {
+            value: Amount::from_sat(change),
+            script_pubkey: participant.funding_address.script_pubkey(),
+        });
+    }
+
+    outputs
+}
+
 /// Arguments for the `get_greedy_funding_utxos` function.
 struct GetGreedyFundingUtxosArgs {
     /// The address that will be used to fund the transaction
@@ -694,6 +1113,7 @@ struct BuildCommitTransactionArgs<'a> {
 mod test {
 
     use bitcoin::PrivateKey;
+    use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
     use ic_exports::ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, Outpoint};
     use ic_exports::ic_kit::MockContext;
     use ord_rs::constants::POSTAGE;
@@ -869,6 +1289,51 @@ mod test {
         assert_eq!(funding_utxos[0].outpoint.txid, vec![2; 32]);
     }
 
+    #[test]
+    fn test_batch_transfer_outputs_should_follow_reveal_inputs() {
+        let participant = |id: u8, reveal_value: u64, funding_value: u64| BatchParticipant {
+            index: id as usize,
+            reveal_utxo: Utxo {
+                outpoint: Outpoint {
+                    txid: vec![id; 32],
+                    vout: 0,
+                },
+                value: reveal_value,
+                height: 0,
+            },
+            dst_address: Address::from_str("bc1quyc49rn6q9rmlk5rz96pqy8ug827xwvamqm0vh")
+                .unwrap()
+                .assume_checked(),
+            funding_address: Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+                .unwrap()
+                .assume_checked(),
+            derivation_path: DerivationPath::default(),
+            available_utxos: vec![],
+            selected_utxos: vec![Utxo {
+                outpoint: Outpoint {
+                    txid: vec![id + 100; 32],
+                    vout: 0,
+                },
+                value: funding_value,
+                height: 0,
+            }],
+        };
+        let participants = vec![participant(1, POSTAGE, 10_000), participant(2, 600, 1_000)];
+
+        let outputs = batch_transfer_outputs(&participants, &[2_000, 500]);
+
+        // destination outputs hold the reveal values, so each inscription lands on its own
+        // destination; the change below the dust threshold is left to the fee
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[0].value, Amount::from_sat(POSTAGE));
+        assert_eq!(outputs[1].value, Amount::from_sat(600));
+        assert_eq!(outputs[2].value, Amount::from_sat(8_000));
+        assert_eq!(
+            outputs[2].script_pubkey,
+            participants[0].funding_address.script_pubkey()
+        );
+    }
+
     struct TestUtxoProvider {
         utxos: Vec<Utxo>,
     }
@@ -877,7 +1342,7 @@ mod test {
         async fn get_utxos(
             &self,
             _address: &Address,
-        ) -> Result<GetUtxosResponse, crate::interface::DepositError> {
+        ) -> Result<GetUtxosResponse, UtxoProviderError> {
             Ok(GetUtxosResponse {
                 utxos: self.utxos.clone(),
                 tip_block_hash: vec![],
@@ -886,11 +1351,11 @@ mod test {
             })
         }
 
-        async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
+        async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError> {
             unimplemented!()
         }
 
-        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), WithdrawError> {
+        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), UtxoProviderError> {
             Ok(())
         }
     }
@@ -1,3 +1,6 @@
+use bitcoin_bridge_core::consensus::ConsensusError;
+use bitcoin_bridge_core::key::KeyError;
+use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
 use bridge_did::order::SignedMintOrder;
 use candid::CandidType;
 use did::H256;
@@ -5,7 +8,6 @@ use serde::Deserialize;
 use thiserror::Error;
 
 use crate::core::deposit::Brc20DepositPayload;
-use crate::key::KeyError;
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct PendingUtxo {}
@@ -57,6 +59,12 @@ pub enum Erc20MintError {
 pub enum GetAddressError {
     #[error("key error: {0}")]
     Key(String),
+    #[error("deposit watcher is disabled")]
+    DepositWatcherDisabled,
+    #[error("maximum number of watched deposit addresses is reached")]
+    TooManyWatchedAddresses,
+    #[error("registration fee of {required} cycles is not paid, attached {attached} cycles")]
+    InsufficientCycles { required: u64, attached: u64 },
 }
 
 impl From<KeyError> for GetAddressError {
@@ -92,9 +100,10 @@ pub enum DepositError {
     AmountTooBig(String),
     #[error("key error: {0}")]
     KeyError(String),
-    #[error("indexers disagree: {indexer_responses:?}")]
+    #[error("indexers disagree: {first_response}; {another_response}")]
     IndexersDisagree {
-        indexer_responses: Vec<(String, String)>,
+        first_response: String,
+        another_response: String,
     },
     #[error(
         "insufficient consensus: received {received_responses}/{required_responses}, checked {checked_indexers}"
@@ -130,6 +139,35 @@ impl From<KeyError> for DepositError {
     }
 }
 
+impl From<ConsensusError> for DepositError {
+    fn from(err: ConsensusError) -> Self {
+        match err {
+            ConsensusError::IndexersDisagree {
+                first_response,
+                another_response,
+            } => Self::IndexersDisagree {
+                first_response,
+                another_response,
+            },
+            ConsensusError::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            } => Self::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            },
+        }
+    }
+}
+
+impl From<UtxoProviderError> for DepositError {
+    fn from(e: UtxoProviderError) -> Self {
+        Self::Unavailable(e.to_string())
+    }
+}
+
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub enum WithdrawError {
     AmountTooBig(u128),
@@ -158,6 +196,17 @@ impl From<KeyError> for WithdrawError {
     }
 }
 
+impl From<UtxoProviderError> for WithdrawError {
+    fn from(e: UtxoProviderError) -> Self {
+        match e {
+            UtxoProviderError::BtcAdapter(_) => Self::NoInputs,
+            UtxoProviderError::FeeRateRequest => Self::FeeRateRequest,
+            UtxoProviderError::TransactionSerialization => Self::TransactionSerialization,
+            UtxoProviderError::TransactionSending => Self::TransactionSending,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, CandidType, Deserialize, Hash, PartialEq, Eq)]
 pub struct RuneIdDid {
     pub block_id: u64,
@@ -169,3 +218,14 @@ pub struct DepositStateResponse {
     pub current_ts: u64,
     pub deposits: Vec<Brc20DepositPayload>,
 }
+
+/// Summary of the utxos known to the bridge.
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct LedgerSummary {
+    /// Number of reveal utxos of the withdrawal inscriptions.
+    pub reveal_utxos: u64,
+    /// Number of deposit utxos already processed by the bridge.
+    pub used_utxos: u64,
+    /// Total value of the processed deposit utxos in satoshis.
+    pub used_utxos_value: u64,
+}
@@ -1,52 +1,29 @@
 use std::cell::RefCell;
 
 use async_trait::async_trait;
-use bitcoin::address::Error as BitcoinAddressError;
-use bitcoin::bip32::{ChildNumber, DerivationPath, Error as Bip32Error, Xpub};
+use bitcoin::bip32::DerivationPath;
 use bitcoin::secp256k1::ecdsa::Signature;
-use bitcoin::secp256k1::{Error as Secp256Error, Message, Secp256k1};
+use bitcoin::secp256k1::{Error as Secp256Error, Message};
 use bitcoin::{Address, Network, PublicKey, XOnlyPublicKey};
+use bitcoin_bridge_core::key::{
+    AddressType, IcEcdsaSigner, KeyError, KeyResult, derivation_path_to_ic, derive_transit_address,
+    get_derivation_path, sign_with_ic_schnorr,
+};
+use bitcoin_bridge_core::master_key::MasterKey;
 use bridge_did::schnorr::{
     ManagementCanisterSchnorrPublicKeyReply, ManagementCanisterSchnorrPublicKeyRequest,
-    ManagementCanisterSignatureReply, ManagementCanisterSignatureRequest, SchnorrKeyId,
+    SchnorrKeyId,
 };
 use candid::Principal;
 use did::H160;
 use ic_exports::ic_cdk;
-use ic_exports::ic_cdk::api::management_canister::ecdsa::{SignWithEcdsaArgument, sign_with_ecdsa};
 use ord_rs::wallet::LocalSigner;
 use ord_rs::{BtcTxSigner, OrdError, OrdResult};
-use thiserror::Error;
-
-use crate::state::{Brc20State, MasterKey};
-
-/// Key result type
-pub type KeyResult<T> = Result<T, KeyError>;
-
-/// Key and signer error types
-#[derive(Debug, Error)]
-pub enum KeyError {
-    #[error("bip32 error: {0}")]
-    Bip32(#[from] Bip32Error),
-    #[error("failed to derive address: {0}")]
-    BitcoinAddress(#[from] BitcoinAddressError),
-    #[error("invalid derivation path")]
-    InvalidDerivationPath,
-    #[error("invalid public key")]
-    InvalidPublicKey,
-    #[error("ord error error: {0}")]
-    OrdError(#[from] ord_rs::OrdError),
-    #[error("secp256 error: {0}")]
-    Secp256(#[from] Secp256Error),
-    #[error("signer not initialized")]
-    SignerNotInitialized,
-}
 
-pub const DERIVATION_PATH_PREFIX: u8 = 7;
+use crate::state::Brc20State;
 
 pub struct IcBtcSigner {
-    master_key: MasterKey,
-    network: Network,
+    ecdsa: IcEcdsaSigner,
     schnorr_key_id: SchnorrKeyId,
 }
 
@@ -55,8 +32,7 @@ impl IcBtcSigner {
 
     pub fn new(master_key: MasterKey, network: Network, schnorr_key_id: SchnorrKeyId) -> Self {
         Self {
-            master_key,
-            network,
+            ecdsa: IcEcdsaSigner::new(master_key, network),
             schnorr_key_id,
         }
     }
@@ -65,20 +41,7 @@ impl IcBtcSigner {
 #[async_trait]
 impl BtcTxSigner for IcBtcSigner {
     async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
-        let x_public_key = Xpub {
-            network: self.network,
-            depth: 0,
-            parent_fingerprint: Default::default(),
-            child_number: ChildNumber::from_normal_idx(0).expect("Failed to create child number"),
-            public_key: self.master_key.public_key().expect("invalid pubkey").inner,
-            chain_code: self.master_key.chain_code(),
-        };
-        let public_key = x_public_key
-            .derive_pub(&Secp256k1::new(), derivation_path)
-            .map_err(|_| OrdError::Custom("Failed to derive public key".to_string()))?
-            .public_key;
-
-        Ok(PublicKey::from(public_key))
+        self.ecdsa.public_key(derivation_path)
     }
 
     async fn sign_with_ecdsa(
@@ -86,18 +49,7 @@ impl BtcTxSigner for IcBtcSigner {
         message: Message,
         derivation_path: &DerivationPath,
     ) -> Result<Signature, Secp256Error> {
-        let request = SignWithEcdsaArgument {
-            message_hash: message.as_ref().to_vec(),
-            derivation_path: derivation_path_to_ic(derivation_path.clone()),
-            key_id: self.master_key.key_id.clone(),
-        };
-
-        let response = sign_with_ecdsa(request)
-            .await
-            .expect("sign_with_ecdsa failed")
-            .0;
-
-        Signature::from_compact(&response.signature)
+        self.ecdsa.sign(message, derivation_path).await
     }
 
     async fn schnorr_public_key(
@@ -136,26 +88,7 @@ impl BtcTxSigner for IcBtcSigner {
         message: Message,
         derivation_path: &DerivationPath,
     ) -> Result<bitcoin::secp256k1::schnorr::Signature, Secp256Error> {
-        let internal_request = ManagementCanisterSignatureRequest {
-            message: message.as_ref().to_vec(),
-            derivation_path: derivation_path_to_ic(derivation_path.clone()),
-            key_id: self.schnorr_key_id.clone(),
-        };
-
-        let (internal_reply,): (ManagementCanisterSignatureReply,) =
-            ic_exports::ic_cdk::api::call::call_with_payment(
-                Principal::management_canister(),
-                "sign_with_schnorr",
-                (internal_request,),
-                25_000_000_000,
-            )
-            .await
-            .map_err(|e| {
-                log::error!("Failed to call sign_with_schnorr: {:?}", e);
-                Secp256Error::InvalidSignature
-            })?;
-
-        bitcoin::secp256k1::schnorr::Signature::from_slice(&internal_reply.signature)
+        sign_with_ic_schnorr(&self.schnorr_key_id, message, derivation_path, None).await
     }
 }
 
@@ -223,58 +156,12 @@ pub fn get_transit_address(state: &RefCell<Brc20State>, eth_address: &H160) -> K
     let state = state.borrow();
     let public_key = state.public_key().ok_or(KeyError::SignerNotInitialized)?;
     let chain_code = state.chain_code().ok_or(KeyError::SignerNotInitialized)?;
-    let x_public_key = Xpub {
-        network: state.network(),
-        depth: 0,
-        parent_fingerprint: Default::default(),
-        child_number: ChildNumber::from_normal_idx(0)?,
-        public_key: public_key.inner,
-        chain_code,
-    };
-    let derivation_path = get_derivation_path(eth_address)?;
-    let public_key = x_public_key
-        .derive_pub(&Secp256k1::new(), &derivation_path)?
-        .public_key;
-
-    Ok(Address::p2wpkh(&public_key.into(), state.network())?)
-}
-
-pub fn get_derivation_path_ic(eth_address: &H160) -> Vec<Vec<u8>> {
-    let mut bytes = vec![DERIVATION_PATH_PREFIX];
-    bytes.append(&mut eth_address.0.0.to_vec());
-
-    let mut dp = vec![];
-    for slice in bytes.chunks_exact(3) {
-        let mut part = vec![0];
-        part.append(&mut slice.to_vec());
-        dp.push(part);
-    }
-
-    dp
-}
 
-pub fn get_derivation_path(eth_address: &H160) -> KeyResult<DerivationPath> {
-    ic_dp_to_derivation_path(&get_derivation_path_ic(eth_address))
-}
-
-pub fn ic_dp_to_derivation_path(ic_derivation_path: &[Vec<u8>]) -> KeyResult<DerivationPath> {
-    let mut parts = vec![];
-    for part in ic_derivation_path.iter() {
-        let child_idx = u32::from_be_bytes(
-            part[..]
-                .try_into()
-                .map_err(|_| KeyError::InvalidDerivationPath)?,
-        );
-        let child = ChildNumber::from_normal_idx(child_idx)?;
-        parts.push(child);
-    }
-
-    Ok(DerivationPath::from(parts))
-}
-
-fn derivation_path_to_ic(derivation_path: DerivationPath) -> Vec<Vec<u8>> {
-    let vec: Vec<_> = derivation_path.into();
-    vec.into_iter()
-        .map(|child| u32::from(child).to_be_bytes().to_vec())
-        .collect()
+    derive_transit_address(
+        public_key,
+        chain_code,
+        state.network(),
+        eth_address,
+        AddressType::P2wpkh,
+    )
 }
@@ -1,16 +1,15 @@
-mod used_utxo_details;
 mod utxo_details;
-mod utxo_key;
 
 use bitcoin::hashes::Hash;
 use bitcoin::{Address, Txid};
+pub use bitcoin_bridge_core::ledger::UtxoKey;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
 use ic_stable_structures::stable_structures::Memory;
 use ic_stable_structures::{BTreeMapStructure, MemoryId, MemoryManager, StableBTreeMap};
 use ord_rs::wallet::TxInputInfo;
 
 use self::utxo_details::UtxoDetails;
-pub use self::utxo_key::UtxoKey;
+use crate::interface::LedgerSummary;
 use crate::memory::{REVEAL_UTXOS_MEMORY_ID, USED_UTXOS_MEMORY_ID};
 
 /// Data structure to keep track of utxos owned by the canister.
@@ -93,10 +92,29 @@ where
     pub fn remove_reveal_utxo(&mut self, key: &UtxoKey) {
         self.reveal_utxos.remove(key);
     }
+
+    /// Summarizes the utxos in the store.
+    pub fn summary(&self) -> LedgerSummary {
+        let (used_utxos, used_utxos_value) = self
+            .used_utxos
+            .iter()
+            .fold((0, 0), |(count, value), (_, details)| {
+                (count + 1, value + details.value)
+            });
+
+        LedgerSummary {
+            reveal_utxos: self.reveal_utxos.iter().count() as u64,
+            used_utxos,
+            used_utxos_value,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
     use ic_exports::ic_kit::MockContext;
 
     use super::*;
@@ -151,4 +169,39 @@ mod tests {
             vout: 1
         }));
     }
+
+    #[test]
+    fn test_should_summarize_utxos() {
+        MockContext::new().inject();
+        let address = Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked();
+
+        let state = get_brc20_state();
+        {
+            let mut state = state.borrow_mut();
+            let ledger = state.ledger_mut();
+            ledger.deposit_reveal(Txid::from_byte_array([0xde; 32]), 0);
+            for tx_id in [1, 2] {
+                let utxo = Utxo {
+                    outpoint: Outpoint {
+                        txid: vec![tx_id; 32],
+                        vout: 0,
+                    },
+                    value: 1000,
+                    height: 0,
+                };
+                ledger.mark_as_used(utxo, &address, vec![]);
+            }
+        }
+
+        assert_eq!(
+            state.borrow().ledger().summary(),
+            LedgerSummary {
+                reveal_utxos: 1,
+                used_utxos: 2,
+                used_utxos_value: 2000,
+            }
+        );
+    }
 }
@@ -33,10 +33,10 @@ mod test {
     use std::str::FromStr;
 
     use bitcoin::{Address, Network, PublicKey};
+    use bitcoin_bridge_core::key::get_derivation_path_ic;
     use did::H160;
 
     use super::*;
-    use crate::key::get_derivation_path_ic;
 
     #[test]
     fn test_should_serialize_and_deserialize_details() {
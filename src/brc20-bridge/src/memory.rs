@@ -8,3 +8,10 @@ pub const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(100);
 pub const MASTER_KEY_MEMORY_ID: MemoryId = MemoryId::new(101);
 pub const REVEAL_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(102);
 pub const USED_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(103);
+pub const BRC20_TOKENS_MEMORY_ID: MemoryId = MemoryId::new(104);
+pub const BRC20_TOKENS_OFFSET_MEMORY_ID: MemoryId = MemoryId::new(105);
+pub const WATCHED_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(106);
+pub const STATE_VERSION_MEMORY_ID: MemoryId = MemoryId::new(107);
+pub const MEMO_DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(108);
+pub const PENDING_MEMO_DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(109);
+pub const REFUNDABLE_MEMO_DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(110);
@@ -1,7 +1,11 @@
+mod batch_transfer;
 mod deposit;
+mod deposit_watcher;
 mod events_handler;
+mod memo_deposit;
 mod mint_order_handler;
 mod mint_tx_handler;
+mod tokens_refresh;
 mod withdraw;
 
 use bitcoin::Network;
@@ -22,16 +26,25 @@ use ic_task_scheduler::task::TaskOptions;
 use serde::Serialize;
 use withdraw::Brc20BridgeWithdrawOpImpl;
 
+pub use self::batch_transfer::BatchTransferService;
 pub use self::deposit::Brc20BridgeDepositOpImpl;
+pub use self::deposit_watcher::DepositWatcherService;
 pub use self::events_handler::Brc20BtfEventsHandler;
+pub use self::memo_deposit::{MemoDepositService, refund_memo_deposit};
 pub use self::mint_order_handler::Brc20MintOrderHandler;
 pub use self::mint_tx_handler::Brc20MintTxHandler;
+pub use self::tokens_refresh::Brc20TokensRefreshService;
 use crate::canister::get_brc20_state;
+use crate::core::withdrawal::parse_dst_address;
 
 pub const REFRESH_PARAMS_SERVICE_ID: ServiceId = 0;
 pub const FETCH_BTF_EVENTS_SERVICE_ID: ServiceId = 1;
 pub const SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 2;
 pub const SEND_MINT_TX_SERVICE_ID: ServiceId = 3;
+pub const BRC20_TOKENS_REFRESH_SERVICE_ID: ServiceId = 4;
+pub const DEPOSIT_WATCHER_SERVICE_ID: ServiceId = 5;
+pub const MEMO_DEPOSIT_SERVICE_ID: ServiceId = 6;
+pub const BATCH_TRANSFER_SERVICE_ID: ServiceId = 7;
 
 /// BRC20 bridge operations
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
@@ -43,6 +56,21 @@ impl From<Brc20BridgeOp> for Brc20BridgeOpImpl {
     }
 }
 
+impl Brc20BridgeOpImpl {
+    /// Returns the next step of the withdrawal approved by the owner, or `None` if the operation
+    /// does not await the approval.
+    pub fn approve_withdrawal(self) -> Option<Self> {
+        match self.0 {
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitOwnerApproval(payload)) => {
+                Some(Self(Brc20BridgeOp::Withdraw(
+                    Brc20BridgeWithdrawOp::CreateInscriptionTxs(payload),
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
 impl Operation for Brc20BridgeOpImpl {
     async fn progress(
         self,
@@ -58,6 +86,11 @@ impl Operation for Brc20BridgeOpImpl {
                 log::debug!("Brc20BridgeDepositOp::AwaitConfirmations {deposit:?} {utxos:?}");
                 Brc20BridgeDepositOpImpl::await_confirmations(ctx, deposit, utxos, id.nonce()).await
             }
+            Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::CreateMemoMintOrder(memo_deposit)) => {
+                log::debug!("Brc20BridgeDepositOp::CreateMemoMintOrder {memo_deposit:?}");
+                Brc20BridgeDepositOpImpl::create_memo_mint_order(ctx, memo_deposit, id.nonce())
+                    .await
+            }
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SignMintOrder(mint_order)) => {
                 log::debug!("Brc20BridgeDepositOp::SignMintOrder {mint_order:?}");
 
@@ -75,9 +108,14 @@ impl Operation for Brc20BridgeOpImpl {
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::MintOrderConfirmed { .. }) => Err(
                 Error::FailedToProgress("MintOrderConfirmed task cannot be progressed".into()),
             ),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitOwnerApproval(_)) => {
+                Err(Error::FailedToProgress(
+                    "AwaitOwnerApproval task progresses only when approved by the owner".into(),
+                ))
+            }
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateInscriptionTxs(payload)) => {
                 log::debug!("Brc20BridgeWithdrawOp::CreateInscriptionTxs {payload:?}");
-                Brc20BridgeWithdrawOpImpl::create_inscription_txs(payload).await
+                Brc20BridgeWithdrawOpImpl::create_inscription_txs(ctx, payload).await
             }
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendCommitTx {
                 payload,
@@ -122,15 +160,64 @@ impl Operation for Brc20BridgeOpImpl {
                 reveal_utxo,
             }) => {
                 log::debug!("Brc20BridgeWithdrawOp::CreateTransferTx {payload:?} {reveal_utxo:?}");
+
+                if get_brc20_state()
+                    .borrow()
+                    .withdrawal_batch_config()
+                    .is_some()
+                {
+                    // a transfer which cannot be sent would block the whole batch, so it fails
+                    // alone before being added to it
+                    parse_dst_address(&payload.dst_address).map_err(|err| {
+                        Error::CannotProgress(format!("invalid withdrawal: {err:?}"))
+                    })?;
+
+                    return Ok(OperationProgress::AddToService(BATCH_TRANSFER_SERVICE_ID));
+                }
+
                 Brc20BridgeWithdrawOpImpl::create_transfer_transaction(payload, reveal_utxo).await
             }
-            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendTransferTx { from_address, tx }) => {
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendTransferTx {
+                from_address,
+                tx,
+                funding_account,
+            }) => {
                 log::debug!("Brc20BridgeWithdrawOp::SendTransferTx {from_address:?} {tx:?}");
-                Brc20BridgeWithdrawOpImpl::send_transfer_transaction(from_address, tx).await
+                Brc20BridgeWithdrawOpImpl::send_transfer_transaction(
+                    from_address,
+                    tx,
+                    funding_account,
+                )
+                .await
+            }
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                from_address,
+                tx,
+                funding_account,
+                sent_at,
+                replaced_txids,
+            }) => {
+                log::debug!(
+                    "Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {from_address:?} {tx:?}"
+                );
+                Brc20BridgeWithdrawOpImpl::await_transfer_transaction(
+                    from_address,
+                    tx,
+                    funding_account,
+                    sent_at,
+                    replaced_txids,
+                )
+                .await
             }
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent { .. }) => Err(
                 Error::FailedToProgress("TransferTxSent task cannot be progressed".into()),
             ),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed { .. }) => Err(
+                Error::FailedToProgress("TransferTxConfirmed task cannot be progressed".into()),
+            ),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::BatchTransferTxSent { .. }) => Err(
+                Error::FailedToProgress("BatchTransferTxSent task cannot be progressed".into()),
+            ),
         }?;
 
         Ok(OperationProgress::Progress(next_step))
@@ -138,6 +225,28 @@ impl Operation for Brc20BridgeOpImpl {
 
     fn scheduling_options(&self) -> Option<ic_task_scheduler::task::TaskOptions> {
         match self.0 {
+            // Scheduled again when approved by the owner
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitOwnerApproval(_)) => None,
+            // Transfer transactions may stay unconfirmed for days, so they are polled until
+            // they are confirmed, bumping their fee in the meantime.
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                ..
+            }) => {
+                let network = get_brc20_state().borrow().network();
+
+                match network {
+                    Network::Bitcoin => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(u32::MAX)
+                            .with_fixed_backoff_policy(300),
+                    ),
+                    _ => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(u32::MAX)
+                            .with_fixed_backoff_policy(10),
+                    ),
+                }
+            }
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitInscriptionTxs { .. }) => {
                 let network = {
                     let state_ref = get_brc20_state();
@@ -170,6 +279,8 @@ impl Operation for Brc20BridgeOpImpl {
                     .with_max_retries_policy(10),
             ),
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent { .. })
+            | Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed { .. })
+            | Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::BatchTransferTxSent { .. })
             | Brc20BridgeOp::Deposit(_) => Some(
                 TaskOptions::new()
                     .with_max_retries_policy(10)
@@ -182,17 +293,24 @@ impl Operation for Brc20BridgeOpImpl {
         match self.0 {
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::AwaitInputs { .. }) => false,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::AwaitConfirmations { .. }) => false,
+            Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::CreateMemoMintOrder { .. }) => false,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SignMintOrder { .. }) => false,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SendMintOrder { .. }) => false,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::WaitForMintConfirm { .. }) => false,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::MintOrderConfirmed { .. }) => true,
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitOwnerApproval { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateInscriptionTxs { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendCommitTx { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendRevealTx { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitInscriptionTxs { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateTransferTx { .. }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendTransferTx { .. }) => false,
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                ..
+            }) => false,
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent { .. }) => true,
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed { .. }) => true,
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::BatchTransferTxSent { .. }) => true,
         }
     }
 
@@ -205,6 +323,9 @@ impl Operation for Brc20BridgeOpImpl {
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::AwaitConfirmations {
                 deposit, ..
             }) => deposit.dst_address.clone(),
+            Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::CreateMemoMintOrder(memo_deposit)) => {
+                memo_deposit.dst_address.clone()
+            }
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SignMintOrder(MintOrder {
                 recipient,
                 ..
@@ -219,6 +340,9 @@ impl Operation for Brc20BridgeOpImpl {
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::MintOrderConfirmed { data }) => {
                 data.recipient.clone()
             }
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitOwnerApproval(payload)) => {
+                payload.sender.clone()
+            }
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateInscriptionTxs(payload)) => {
                 payload.sender.clone()
             }
@@ -240,6 +364,18 @@ impl Operation for Brc20BridgeOpImpl {
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent {
                 from_address, ..
             }) => from_address.clone(),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                from_address,
+                ..
+            }) => from_address.clone(),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed {
+                from_address,
+                ..
+            }) => from_address.clone(),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::BatchTransferTxSent {
+                from_address,
+                ..
+            }) => from_address.clone(),
         }
     }
 }
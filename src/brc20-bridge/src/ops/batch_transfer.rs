@@ -0,0 +1,178 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+
+use bitcoin::hashes::Hash as _;
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{Brc20BridgeOp, Brc20BridgeWithdrawOp, Brc20WithdrawalPayload};
+use did::H256;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
+
+use super::Brc20BridgeOpImpl;
+use crate::canister::get_brc20_state;
+use crate::core::withdrawal::Withdrawal;
+
+/// Service to send the BRC20 transfers of pending withdrawals in batch transactions.
+///
+/// Transfers are accumulated until the configured number of them is pending or the batch window
+/// since the first pending transfer has elapsed.
+pub struct BatchTransferService {
+    state: RuntimeState<Brc20BridgeOpImpl>,
+    pending: RefCell<BTreeMap<OperationId, (Brc20WithdrawalPayload, Utxo)>>,
+    /// Timestamp of the first pending transfer in nanoseconds.
+    first_pushed_at: Cell<Option<u64>>,
+}
+
+impl BatchTransferService {
+    /// Creates a new instance of BatchTransferService.
+    pub fn new(state: RuntimeState<Brc20BridgeOpImpl>) -> Self {
+        Self {
+            state,
+            pending: Default::default(),
+            first_pushed_at: Default::default(),
+        }
+    }
+
+    /// Checks if the pending transfers should be sent now.
+    fn batch_ready(&self, now: u64) -> bool {
+        let pending_count = self.pending.borrow().len();
+        if pending_count == 0 {
+            return false;
+        }
+
+        let Some(config) = get_brc20_state().borrow().withdrawal_batch_config() else {
+            // batching was turned off, send everything that is left
+            return true;
+        };
+
+        let window_elapsed = self
+            .first_pushed_at
+            .get()
+            .map(|first| now.saturating_sub(first) >= config.window_secs * 1_000_000_000)
+            .unwrap_or(true);
+
+        pending_count >= config.max_batch_size as usize || window_elapsed
+    }
+
+    fn max_batch_size(&self) -> usize {
+        get_brc20_state()
+            .borrow()
+            .withdrawal_batch_config()
+            .map(|config| config.max_batch_size.max(1) as usize)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn set_batch_transfer_sent(&self, id: OperationId, txid: H256, fee_share: u64) {
+        let Some(op) = self.state.borrow().operations.get(id) else {
+            log::info!("Batch transfer service failed to update operation {id}: not found.");
+            return;
+        };
+
+        let Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateTransferTx { payload, .. }) = op.0
+        else {
+            log::error!("Batch transfer service failed to update operation {id}: unexpected state");
+            return;
+        };
+
+        self.state.borrow_mut().operations.update(
+            id,
+            Brc20BridgeOpImpl(Brc20BridgeOp::Withdraw(
+                Brc20BridgeWithdrawOp::BatchTransferTxSent {
+                    from_address: payload.sender,
+                    txid,
+                    fee_share,
+                },
+            )),
+        );
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for BatchTransferService {
+    fn push_operation(&self, id: OperationId) -> BTFResult<()> {
+        let op = self
+            .state
+            .borrow()
+            .operations
+            .get(id)
+            .ok_or(Error::OperationNotFound(id))?;
+
+        let Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::CreateTransferTx {
+            payload,
+            reveal_utxo,
+        }) = op.0
+        else {
+            return Err(Error::FailedToProgress(format!(
+                "operation {id} is not a pending transfer"
+            )));
+        };
+
+        let mut pending = self.pending.borrow_mut();
+        if pending.is_empty() {
+            self.first_pushed_at.set(Some(ic::time()));
+        }
+        pending.insert(id, (payload, reveal_utxo));
+
+        Ok(())
+    }
+
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running BatchTransferService");
+
+        if !self.batch_ready(ic::time()) {
+            log::trace!("Transfer batch is not ready yet.");
+            return Ok(());
+        }
+
+        let batch: Vec<(OperationId, (Brc20WithdrawalPayload, Utxo))> = self
+            .pending
+            .borrow()
+            .iter()
+            .take(self.max_batch_size())
+            .map(|(id, transfer)| (*id, transfer.clone()))
+            .collect();
+        let transfers: Vec<(Brc20WithdrawalPayload, Utxo)> =
+            batch.iter().map(|(_, transfer)| transfer.clone()).collect();
+
+        log::trace!("Sending batch of {} transfers.", batch.len());
+
+        let withdrawal = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+        let batch_tx = withdrawal
+            .create_batch_transfer_transaction(&transfers)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("cannot create batch transfer tx: {err:?}"))
+            })?;
+        withdrawal
+            .send_batch_transfer_transaction(&batch_tx)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to send batch transfer tx: {err:?}"))
+            })?;
+
+        let txid = H256::from_slice(batch_tx.transaction.txid().as_byte_array());
+        {
+            let mut pending = self.pending.borrow_mut();
+            for (index, fee_share) in &batch_tx.fee_shares {
+                let id = batch[*index].0;
+                pending.remove(&id);
+                self.set_batch_transfer_sent(id, txid.clone(), *fee_share);
+            }
+
+            // transfers left out of the batch wait for the next one
+            self.first_pushed_at
+                .set((!pending.is_empty()).then(ic::time));
+        }
+
+        log::debug!(
+            "Batch transfer transaction {txid} sent for {} withdrawals.",
+            batch_tx.fee_shares.len()
+        );
+
+        Ok(())
+    }
+}
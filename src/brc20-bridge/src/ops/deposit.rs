@@ -1,6 +1,6 @@
 use bridge_canister::runtime::RuntimeState;
 use bridge_did::error::{BTFResult, Error};
-use bridge_did::operations::{Brc20BridgeDepositOp, DepositRequest};
+use bridge_did::operations::{Brc20BridgeDepositOp, DepositRequest, MemoDeposit};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
 
 use super::{Brc20BridgeOp, Brc20BridgeOpImpl};
@@ -48,6 +48,7 @@ impl Brc20BridgeDepositOpImpl {
             brc20_tick,
             dst_address,
             dst_token,
+            ..
         } = deposit_request;
 
         let deposit = Brc20Deposit::get(state.clone())
@@ -90,4 +91,34 @@ impl Brc20BridgeDepositOpImpl {
 
         Ok(Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SignMintOrder(unsigned_mint_order)).into())
     }
+
+    /// Create the mint order for the transfer to the memo deposit address
+    pub async fn create_memo_mint_order(
+        state: RuntimeState<Brc20BridgeOpImpl>,
+        memo_deposit: MemoDeposit,
+        nonce: u32,
+    ) -> BTFResult<Brc20BridgeOpImpl> {
+        let MemoDeposit {
+            brc20_tick,
+            amount,
+            dst_address,
+            dst_token,
+            ..
+        } = memo_deposit;
+
+        let deposit = Brc20Deposit::get(state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot deposit: {err:?}")))?;
+        let brc20_info =
+            deposit
+                .get_brc20_info(&brc20_tick)
+                .await
+                .ok_or(Error::FailedToProgress(format!(
+                    "cannot get brc20 info for {brc20_tick}"
+                )))?;
+
+        let unsigned_mint_order =
+            deposit.create_unsigned_mint_order(&dst_address, &dst_token, amount, brc20_info, nonce);
+
+        Ok(Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::SignMintOrder(unsigned_mint_order)).into())
+    }
 }
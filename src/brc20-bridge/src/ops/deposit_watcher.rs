@@ -0,0 +1,133 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{Brc20BridgeDepositOp, Brc20BridgeOp, DepositRequest};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+use super::Brc20BridgeOpImpl;
+use crate::canister::{get_brc20_state, get_runtime};
+use crate::core::deposit::Brc20Deposit;
+use crate::state::WatchedAddress;
+
+/// Service to detect the deposits without the deposit requests of the users.
+///
+/// Scans the registered deposit addresses and creates a deposit operation for every registered
+/// token with a balance at the address, once new utxos are received. Does nothing unless the
+/// deposit watcher is configured.
+pub struct DepositWatcherService {
+    runtime_state: RuntimeState<Brc20BridgeOpImpl>,
+}
+
+impl DepositWatcherService {
+    pub fn new(runtime_state: RuntimeState<Brc20BridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+
+    /// Creates deposit operations for the balances of the address if it received new utxos.
+    /// Returns the utxos of the address handled by the watcher.
+    async fn scan_address(
+        &self,
+        deposit: &Brc20Deposit,
+        eth_address: &H160,
+        watched: WatchedAddress,
+    ) -> Vec<Outpoint> {
+        let mut response = match deposit.get_address_utxos(eth_address).await {
+            Ok(response) => response,
+            Err(err) => {
+                log::warn!("Failed to get utxos of the deposit address of {eth_address}: {err}");
+                return watched.known_utxos;
+            }
+        };
+
+        // forget the spent utxos
+        let mut known_utxos = watched.known_utxos;
+        known_utxos.retain(|outpoint| response.utxos.iter().any(|utxo| utxo.outpoint == *outpoint));
+
+        response
+            .utxos
+            .retain(|utxo| !known_utxos.contains(&utxo.outpoint));
+        if response.utxos.is_empty() {
+            return known_utxos;
+        }
+
+        let mut deposits = vec![];
+        for (brc20_tick, dst_token) in watched.dst_tokens {
+            match deposit.get_brc20_balance(eth_address, &brc20_tick).await {
+                Ok(0) => {}
+                Ok(amount) => deposits.push(DepositRequest {
+                    amount,
+                    brc20_tick,
+                    dst_address: eth_address.clone(),
+                    dst_token,
+                    deposit_fee: Some(get_brc20_state().borrow().deposit_fee_quote().fee),
+                }),
+                Err(err) => {
+                    // the new utxos are checked again on the next run
+                    log::debug!("Failed to get {brc20_tick} balance of {eth_address}: {err}");
+                    return known_utxos;
+                }
+            }
+        }
+
+        if deposits.is_empty() {
+            // the indexers may not know about the inscriptions of fresh utxos yet
+            if deposit.validate_utxo_confirmations(&response).is_ok() {
+                known_utxos.extend(response.utxos.into_iter().map(|utxo| utxo.outpoint));
+            }
+
+            return known_utxos;
+        }
+
+        for request in deposits {
+            let operation: Brc20BridgeOpImpl =
+                Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::AwaitInputs(request)).into();
+            let id = self
+                .runtime_state
+                .borrow_mut()
+                .operations
+                .new_operation(operation.clone(), None);
+            get_runtime().borrow().schedule_operation(id, operation);
+            log::info!("Created deposit operation {id} for {eth_address}");
+        }
+        known_utxos.extend(response.utxos.into_iter().map(|utxo| utxo.outpoint));
+
+        known_utxos
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for DepositWatcherService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running DepositWatcherService");
+
+        let Some(config) = get_brc20_state().borrow().deposit_watcher_config() else {
+            return Ok(());
+        };
+
+        let addresses = get_brc20_state()
+            .borrow_mut()
+            .next_watched_addresses(&config);
+        if addresses.is_empty() {
+            return Ok(());
+        }
+
+        let deposit = Brc20Deposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        for (eth_address, watched) in addresses {
+            let known_utxos = self.scan_address(&deposit, &eth_address, watched).await;
+            get_brc20_state()
+                .borrow_mut()
+                .set_known_deposit_utxos(&eth_address, known_utxos);
+        }
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the DepositWatcherService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
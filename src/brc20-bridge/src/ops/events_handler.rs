@@ -47,7 +47,15 @@ impl BtfBridgeEventHandler<Brc20BridgeOpImpl> for Brc20BtfEventsHandler {
     ) -> Option<OperationAction<Brc20BridgeOpImpl>> {
         log::debug!("on_wrapped_token_burnt {event:?}");
         let memo = event.memo();
-        let op = match withdrawal::new_withdraw_payload(event, &self.brc20_state.borrow()) {
+        let state = self.brc20_state.borrow();
+        let op = match withdrawal::new_withdraw_payload(event, &state) {
+            Ok(payload)
+                if state.requires_withdrawal_approval(&payload.brc20_info.tick, payload.amount) =>
+            {
+                Brc20BridgeOpImpl(Brc20BridgeOp::Withdraw(
+                    Brc20BridgeWithdrawOp::AwaitOwnerApproval(payload),
+                ))
+            }
             Ok(payload) => Brc20BridgeOpImpl(Brc20BridgeOp::Withdraw(
                 Brc20BridgeWithdrawOp::CreateInscriptionTxs(payload),
             )),
@@ -80,6 +88,7 @@ impl BtfBridgeEventHandler<Brc20BridgeOpImpl> for Brc20BtfEventsHandler {
                         brc20_tick: payload.brc20_tick,
                         dst_address: payload.dst_address,
                         dst_token: payload.dst_token,
+                        deposit_fee: Some(self.brc20_state.borrow().deposit_fee_quote().fee),
                     }),
                 ));
 
@@ -0,0 +1,400 @@
+use std::str::FromStr as _;
+
+use bitcoin::hashes::Hash as _;
+use bitcoin::{Address, Txid};
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::inscription::InscriptionId;
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{
+    Brc20BridgeDepositOp, Brc20BridgeOp, Brc20BridgeWithdrawOp, Brc20WithdrawalPayload, MemoDeposit,
+};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
+use rust_decimal::Decimal;
+
+use super::Brc20BridgeOpImpl;
+use crate::canister::{get_brc20_state, get_runtime};
+use crate::constants::MEMO_DEPOSIT_PAGE_SIZE;
+use crate::core::deposit::{Brc20Deposit, memo_deposit_account};
+use crate::core::index_provider::Brc20Transfer;
+use crate::interface::{DepositError, WithdrawError};
+use crate::state::{PendingMemoDeposit, RefundableMemoDeposit};
+
+/// Service to mint the BRC20 transfers sent to the memo deposit address of the bridge.
+///
+/// The recipient of such a deposit is taken from the metadata of the transfer inscription, so the
+/// users don't need a deposit address of their own. Does nothing unless the memo deposits are
+/// configured.
+pub struct MemoDepositService {
+    runtime_state: RuntimeState<Brc20BridgeOpImpl>,
+}
+
+impl MemoDepositService {
+    pub fn new(runtime_state: RuntimeState<Brc20BridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+
+    /// Returns the transfers from or to the memo deposit address not handled yet, from the
+    /// oldest to the newest.
+    ///
+    /// The transfers are handled in the order they were made, so the handled transfers are
+    /// always older than the new ones.
+    async fn new_transfers(
+        deposit: &Brc20Deposit,
+        address: &Address,
+    ) -> BTFResult<Vec<Brc20Transfer>> {
+        let mut transfers = vec![];
+        let mut offset = 0;
+        loop {
+            let page = deposit
+                .get_brc20_transfers(address, offset, MEMO_DEPOSIT_PAGE_SIZE)
+                .await
+                .map_err(|err| {
+                    Error::FailedToProgress(format!("cannot get memo deposit transfers: {err}"))
+                })?;
+            if page.is_empty() {
+                break;
+            }
+
+            offset += page.len() as u64;
+            let state = get_brc20_state();
+            let state = state.borrow();
+            for transfer in page {
+                if state.is_memo_deposit_handled(&transfer.inscription_id) {
+                    transfers.reverse();
+                    return Ok(transfers);
+                }
+
+                transfers.push(transfer);
+            }
+        }
+
+        transfers.reverse();
+        Ok(transfers)
+    }
+
+    /// Returns the deposit to be minted for the transfer, or the reason it can only be refunded.
+    ///
+    /// The transfer must pay the deposit fee quoted when it was found: the BTC it sends to the
+    /// memo deposit address, including the postage of the inscription, must cover the fee. This
+    /// BTC funds the withdrawals of the tokens held by the memo deposit address.
+    async fn memo_deposit(
+        deposit: &Brc20Deposit,
+        address: &Address,
+        utxos: &[Utxo],
+        transfer: &Brc20Transfer,
+    ) -> BTFResult<MemoTransfer> {
+        let inscription_id = transfer.inscription_id;
+        if transfer.to_address != Some(address.to_string()) {
+            log::trace!("Skipping transfer {inscription_id} sent from the memo deposit address");
+            return Ok(MemoTransfer::Skipped);
+        }
+
+        let refundable = |reason: String| {
+            log::warn!("Transfer {inscription_id} cannot be minted: {reason}");
+            MemoTransfer::Refundable(RefundableMemoDeposit {
+                tick: transfer.tick,
+                amount: transfer.amount.to_string(),
+                refund_address: transfer.from_address.clone(),
+                block_height: transfer.block_height,
+                reason,
+            })
+        };
+
+        let deposit_fee = {
+            let state = get_brc20_state();
+            let state = state.borrow();
+            state
+                .pending_memo_deposit(&inscription_id)
+                .map(|pending| pending.deposit_fee)
+                .unwrap_or_else(|| state.deposit_fee_quote().fee)
+        };
+        let paid_fee = transfer_value(utxos, &transfer.txid);
+        if paid_fee < deposit_fee {
+            return Ok(refundable(format!(
+                "paid {paid_fee} sats, less than the deposit fee {deposit_fee}"
+            )));
+        }
+
+        let metadata = deposit
+            .get_inscription_metadata(&inscription_id)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!(
+                    "cannot get metadata of inscription {inscription_id}: {err}"
+                ))
+            })?;
+        let Some((dst_address, dst_token)) = metadata.as_ref().and_then(parse_memo) else {
+            return Ok(refundable(format!("no valid deposit memo: {metadata:?}")));
+        };
+
+        let amount = match deposit
+            .to_integer_amount(&transfer.tick, transfer.amount)
+            .await
+        {
+            Ok(0) => {
+                log::warn!("Transfer {inscription_id} has zero amount");
+                return Ok(MemoTransfer::Skipped);
+            }
+            Ok(amount) => amount,
+            Err(DepositError::AmountTooBig(err)) => return Ok(refundable(err)),
+            Err(err) => {
+                return Err(Error::FailedToProgress(format!(
+                    "cannot get amount of transfer {inscription_id}: {err}"
+                )));
+            }
+        };
+
+        Ok(MemoTransfer::Deposit(MemoDeposit {
+            inscription_id,
+            brc20_tick: transfer.tick,
+            amount,
+            dst_address,
+            dst_token,
+            deposit_fee: Some(deposit_fee),
+        }))
+    }
+
+    /// Adds the new transfers to the memo deposit address to the pending ones. They keep the
+    /// deposit fee quoted now, and their outputs don't fund the withdrawals until they are
+    /// handled.
+    fn add_pending_transfers(address: &Address, transfers: &[Brc20Transfer]) {
+        let state = get_brc20_state();
+        let mut state = state.borrow_mut();
+        let deposit_fee = state.deposit_fee_quote().fee;
+        for transfer in transfers
+            .iter()
+            .filter(|transfer| transfer.to_address == Some(address.to_string()))
+        {
+            state.add_pending_memo_deposit(
+                transfer.inscription_id,
+                PendingMemoDeposit {
+                    txid: transfer.txid.to_byte_array(),
+                    deposit_fee,
+                },
+            );
+        }
+    }
+
+    fn create_deposit_operation(&self, memo_deposit: MemoDeposit) {
+        let inscription_id = memo_deposit.inscription_id;
+        let operation: Brc20BridgeOpImpl =
+            Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::CreateMemoMintOrder(memo_deposit)).into();
+        let id = self
+            .runtime_state
+            .borrow_mut()
+            .operations
+            .new_operation(operation.clone(), None);
+        get_runtime().borrow().schedule_operation(id, operation);
+        log::info!("Created memo deposit operation {id} for transfer {inscription_id}");
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for MemoDepositService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running MemoDepositService");
+
+        let Some(config) = get_brc20_state().borrow().memo_deposit_config() else {
+            return Ok(());
+        };
+
+        let deposit = Brc20Deposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        let address = deposit.get_memo_deposit_address().await.map_err(|err| {
+            Error::FailedToProgress(format!("cannot get memo deposit address: {err}"))
+        })?;
+
+        let transfers = Self::new_transfers(&deposit, &address).await?;
+        if transfers.is_empty() {
+            return Ok(());
+        }
+        Self::add_pending_transfers(&address, &transfers);
+
+        let utxos = deposit.get_deposit_utxos(&address).await.map_err(|err| {
+            Error::FailedToProgress(format!("cannot get memo deposit utxos: {err}"))
+        })?;
+        let min_confirmations = get_brc20_state().borrow().min_confirmations();
+
+        for transfer in transfers
+            .into_iter()
+            .take(config.max_transfers_per_run as usize)
+        {
+            let confirmations = (utxos.tip_height as u64 + 1).saturating_sub(transfer.block_height);
+            if confirmations < min_confirmations as u64 {
+                // the newer transfers are not confirmed either
+                break;
+            }
+
+            let memo_transfer =
+                Self::memo_deposit(&deposit, &address, &utxos.utxos, &transfer).await?;
+            let state = get_brc20_state();
+            state
+                .borrow_mut()
+                .set_memo_deposit_handled(transfer.inscription_id, transfer.block_height);
+            match memo_transfer {
+                MemoTransfer::Deposit(memo_deposit) => self.create_deposit_operation(memo_deposit),
+                MemoTransfer::Refundable(refundable) => state
+                    .borrow_mut()
+                    .add_refundable_memo_deposit(transfer.inscription_id, refundable),
+                MemoTransfer::Skipped => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the MemoDepositService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
+
+/// Outcome of a transfer found at the memo deposit address.
+enum MemoTransfer {
+    /// The transfer is minted to the recipient of its memo.
+    Deposit(MemoDeposit),
+    /// The transfer cannot be minted, its tokens can be refunded by the owner.
+    Refundable(RefundableMemoDeposit),
+    /// The transfer is not a deposit, or has nothing to mint.
+    Skipped,
+}
+
+/// Creates the withdrawal sending the tokens of the memo deposit transfer, which could not be
+/// minted, back to the address it came from. The withdrawal is funded by the memo deposit
+/// address.
+pub fn refund_memo_deposit(
+    runtime_state: RuntimeState<Brc20BridgeOpImpl>,
+    inscription_id: InscriptionId,
+) -> Result<OperationId, WithdrawError> {
+    let state = get_brc20_state();
+    let refundable = state
+        .borrow()
+        .refundable_memo_deposit(&inscription_id)
+        .ok_or_else(|| {
+            WithdrawError::InvalidRequest(format!("transfer {inscription_id} is not refundable"))
+        })?;
+    let brc20_info = state.borrow().brc20_info(&refundable.tick).ok_or_else(|| {
+        WithdrawError::InvalidRequest(format!("unknown brc20 token {}", refundable.tick))
+    })?;
+    let amount = Decimal::from_str(&refundable.amount).map_err(|err| {
+        WithdrawError::InvalidRequest(format!("invalid amount {}: {err}", refundable.amount))
+    })?;
+    let amount = <Brc20Deposit>::get_integer_amount(amount, brc20_info.decimals)
+        .map_err(|err| WithdrawError::InvalidRequest(err.to_string()))?;
+
+    let operation: Brc20BridgeOpImpl = Brc20BridgeOp::Withdraw(
+        Brc20BridgeWithdrawOp::CreateInscriptionTxs(Brc20WithdrawalPayload {
+            brc20_info,
+            amount,
+            request_ts: ic::time(),
+            sender: memo_deposit_account(),
+            dst_address: refundable.refund_address,
+            funding_account: Some(memo_deposit_account()),
+        }),
+    )
+    .into();
+    let id = runtime_state
+        .borrow_mut()
+        .operations
+        .new_operation(operation.clone(), None);
+    get_runtime().borrow().schedule_operation(id, operation);
+    state
+        .borrow_mut()
+        .remove_refundable_memo_deposit(&inscription_id);
+    log::info!("Created refund operation {id} for memo deposit transfer {inscription_id}");
+
+    Ok(id)
+}
+
+/// Returns the value in sats of the utxos created by the transaction.
+fn transfer_value(utxos: &[Utxo], txid: &Txid) -> u64 {
+    utxos
+        .iter()
+        .filter(|utxo| utxo.outpoint.txid == txid.as_byte_array())
+        .map(|utxo| utxo.value)
+        .sum()
+}
+
+/// Parses the EVM recipient and the wrapped token from the inscription metadata, e.g.
+/// `{"dst_address": "0x...", "dst_token": "0x..."}`.
+fn parse_memo(metadata: &serde_json::Value) -> Option<(H160, H160)> {
+    let dst_address = H160::from_hex_str(metadata.get("dst_address")?.as_str()?).ok()?;
+    let dst_token = H160::from_hex_str(metadata.get("dst_token")?.as_str()?).ok()?;
+    if dst_address == H160::default() || dst_token == H160::default() {
+        return None;
+    }
+
+    Some((dst_address, dst_token))
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+    use serde_json::json;
+
+    use super::*;
+
+    const DST_ADDRESS: &str = "0xe57e761aa806c9afe7e06fb0601b17bec310f9c4";
+    const DST_TOKEN: &str = "0x0dc9f6938e9b47fd8553df50bcbdb62d67239007";
+
+    #[test]
+    fn test_should_parse_memo() {
+        let memo = parse_memo(&json!({
+            "dst_address": DST_ADDRESS,
+            "dst_token": DST_TOKEN,
+        }));
+
+        assert_eq!(
+            memo,
+            Some((
+                H160::from_hex_str(DST_ADDRESS).unwrap(),
+                H160::from_hex_str(DST_TOKEN).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_not_parse_invalid_memo() {
+        assert_eq!(parse_memo(&json!(DST_ADDRESS)), None);
+        assert_eq!(parse_memo(&json!({ "dst_address": DST_ADDRESS })), None);
+        assert_eq!(
+            parse_memo(&json!({ "dst_address": "0x1234", "dst_token": DST_TOKEN })),
+            None
+        );
+        assert_eq!(
+            parse_memo(&json!({
+                "dst_address": "0x0000000000000000000000000000000000000000",
+                "dst_token": DST_TOKEN,
+            })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_should_sum_value_of_transfer_outputs() {
+        let utxo = |txid: [u8; 32], vout: u32, value: u64| Utxo {
+            outpoint: Outpoint {
+                txid: txid.to_vec(),
+                vout,
+            },
+            value,
+            height: 1,
+        };
+        let utxos = [
+            utxo([1; 32], 0, 546),
+            utxo([2; 32], 0, 10_000),
+            utxo([1; 32], 1, 1_000),
+        ];
+
+        assert_eq!(
+            transfer_value(&utxos, &Txid::from_byte_array([1; 32])),
+            1_546
+        );
+        assert_eq!(transfer_value(&utxos, &Txid::from_byte_array([3; 32])), 0);
+    }
+}
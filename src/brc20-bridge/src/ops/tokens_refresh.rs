@@ -0,0 +1,41 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+
+use super::Brc20BridgeOpImpl;
+use crate::core::deposit::Brc20Deposit;
+
+/// Service to add the recently deployed BRC20 tokens to the token info cache.
+pub struct Brc20TokensRefreshService {
+    runtime_state: RuntimeState<Brc20BridgeOpImpl>,
+}
+
+impl Brc20TokensRefreshService {
+    pub fn new(runtime_state: RuntimeState<Brc20BridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for Brc20TokensRefreshService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running Brc20TokensRefreshService");
+
+        let deposit = Brc20Deposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        let count = deposit.refresh_brc20_tokens().await.map_err(|err| {
+            Error::FailedToProgress(format!("failed to refresh brc20 tokens: {err:?}"))
+        })?;
+
+        log::debug!("Added {count} tokens to the brc20 token info cache.");
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the Brc20TokensRefreshService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
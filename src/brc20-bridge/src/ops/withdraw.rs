@@ -1,20 +1,34 @@
+use std::time::Duration;
+
+use bitcoin::Txid;
+use bitcoin::hashes::Hash as _;
+use bridge_canister::runtime::RuntimeState;
 use bridge_did::error::{BTFResult, Error};
 use bridge_did::operations::{
     Brc20BridgeWithdrawOp, Brc20WithdrawalPayload, DidTransaction, RevealUtxo,
 };
-use did::H160;
+use did::{H160, H256};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
 
 use super::{Brc20BridgeOp, Brc20BridgeOpImpl};
+use crate::constants::FEE_BUMP_TIMEOUT;
+use crate::core::deposit::{Brc20Deposit, memo_deposit_account};
 use crate::core::withdrawal::{Brc20Transactions, Withdrawal};
+use crate::interface::WithdrawError;
 
 pub struct Brc20BridgeWithdrawOpImpl;
 
 impl Brc20BridgeWithdrawOpImpl {
     /// Create BRC20 transfer inscription transactions
     pub async fn create_inscription_txs(
-        payload: Brc20WithdrawalPayload,
+        state: RuntimeState<Brc20BridgeOpImpl>,
+        mut payload: Brc20WithdrawalPayload,
     ) -> BTFResult<Brc20BridgeOpImpl> {
+        if payload.funding_account.is_none() {
+            payload.funding_account = Some(Self::select_funding_account(state, &payload).await?);
+        }
+
         let withdraw = Withdrawal::get()
             .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
 
@@ -42,6 +56,37 @@ impl Brc20BridgeWithdrawOpImpl {
         )
     }
 
+    /// Selects the account whose transit address holds the tokens to withdraw and the BTC to
+    /// pay for the withdrawal transactions.
+    ///
+    /// The tokens deposited to the address of the sender are withdrawn from there; otherwise they
+    /// are withdrawn from the memo deposit address, which holds the tokens of all memo deposits.
+    async fn select_funding_account(
+        state: RuntimeState<Brc20BridgeOpImpl>,
+        payload: &Brc20WithdrawalPayload,
+    ) -> BTFResult<H160> {
+        let deposit = Brc20Deposit::get(state)
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        let tick = payload.brc20_info.tick;
+
+        for account in [payload.sender.clone(), memo_deposit_account()] {
+            let balance = deposit
+                .get_brc20_balance(&account, &tick)
+                .await
+                .map_err(|err| {
+                    Error::FailedToProgress(format!("cannot get {tick} balance: {err}"))
+                })?;
+            if balance >= payload.amount {
+                return Ok(account);
+            }
+        }
+
+        Err(Error::FailedToProgress(format!(
+            "no deposit address holds {} {tick} to withdraw",
+            payload.amount
+        )))
+    }
+
     /// Send BRC20 transfer commit transaction
     pub async fn send_commit_transaction(
         payload: Brc20WithdrawalPayload,
@@ -99,7 +144,7 @@ impl Brc20BridgeWithdrawOpImpl {
             .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
 
         let reveal_utxo = withdraw
-            .await_inscription_transactions(&payload.sender, reveal_utxo)
+            .await_inscription_transactions(payload.funding_account(), reveal_utxo)
             .await
             .map_err(|err| {
                 Error::FailedToProgress(format!(
@@ -135,6 +180,7 @@ impl Brc20BridgeWithdrawOpImpl {
             Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::SendTransferTx {
                 from_address: payload.sender,
                 tx,
+                funding_account: payload.funding_account,
             })
             .into(),
         )
@@ -144,6 +190,7 @@ impl Brc20BridgeWithdrawOpImpl {
     pub async fn send_transfer_transaction(
         from_address: H160,
         tx: DidTransaction,
+        funding_account: Option<H160>,
     ) -> BTFResult<Brc20BridgeOpImpl> {
         let withdraw = Withdrawal::get()
             .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
@@ -158,8 +205,88 @@ impl Brc20BridgeWithdrawOpImpl {
         withdraw.mark_reveal_utxo_as_used(&outpoint);
 
         Ok(
-            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent { from_address, tx })
-                .into(),
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                from_address,
+                tx,
+                funding_account,
+                sent_at: ic::time(),
+                replaced_txids: vec![],
+            })
+            .into(),
+        )
+    }
+
+    /// Check whether the transfer transaction is confirmed. If it is not confirmed for
+    /// `FEE_BUMP_TIMEOUT`, replace it with a transaction paying a higher fee.
+    pub async fn await_transfer_transaction(
+        from_address: H160,
+        tx: DidTransaction,
+        funding_account: Option<H160>,
+        sent_at: u64,
+        mut replaced_txids: Vec<H256>,
+    ) -> BTFResult<Brc20BridgeOpImpl> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        let funder = funding_account.as_ref().unwrap_or(&from_address);
+        let replaced = replaced_txids
+            .iter()
+            .map(|txid| Txid::from_slice(txid.0.as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::CannotProgress(format!("invalid transaction id: {err:?}")))?;
+
+        let txid = match withdraw
+            .await_transfer_transaction(funder, &tx.0, &replaced)
+            .await
+        {
+            Ok(txid) => txid,
+            Err(WithdrawError::TxNotConfirmed) => {
+                let pending_for = Duration::from_nanos(ic::time().saturating_sub(sent_at));
+                if pending_for < FEE_BUMP_TIMEOUT {
+                    return Err(Error::FailedToProgress(format!(
+                        "transfer transaction {} is not confirmed yet",
+                        tx.0.txid()
+                    )));
+                }
+
+                let replacement = withdraw
+                    .replace_transfer_transaction(funder, &tx.0)
+                    .await
+                    .map_err(|err| {
+                        Error::FailedToProgress(format!("cannot replace transfer tx: {err:?}"))
+                    })?;
+                withdraw
+                    .send_transaction(replacement.clone())
+                    .await
+                    .map_err(|err| {
+                        Error::FailedToProgress(format!("cannot send replacement tx: {err:?}"))
+                    })?;
+
+                replaced_txids.push(H256::from_slice(tx.0.txid().as_byte_array()));
+                return Ok(Brc20BridgeOp::Withdraw(
+                    Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation {
+                        from_address,
+                        tx: DidTransaction(replacement),
+                        funding_account,
+                        sent_at: ic::time(),
+                        replaced_txids,
+                    },
+                )
+                .into());
+            }
+            Err(err) => {
+                return Err(Error::FailedToProgress(format!(
+                    "failed to await transfer transaction: {err:?}"
+                )));
+            }
+        };
+
+        Ok(
+            Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed {
+                from_address,
+                txid: H256::from_slice(txid.as_byte_array()),
+            })
+            .into(),
         )
     }
 }
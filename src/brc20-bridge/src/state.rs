@@ -1,5 +1,8 @@
 mod config;
-mod master_key;
+mod memo_deposits;
+mod migration;
+mod tokens;
+mod watched_addresses;
 
 use core::panic;
 use std::collections::{HashMap, HashSet};
@@ -7,12 +10,22 @@ use std::time::Duration;
 
 use bitcoin::bip32::ChainCode;
 use bitcoin::{FeeRate, Network, PrivateKey, PublicKey};
+use bitcoin_bridge_core::fee_rate::FeeRateState;
+pub use bitcoin_bridge_core::master_key::MasterKey;
+use bitcoin_bridge_core::master_key::MasterKeyStorage;
 use bridge_canister::memory::MEMORY_MANAGER;
 use bridge_did::brc20_info::{Brc20Info, Brc20Tick};
-use bridge_did::init::brc20::Brc20BridgeConfig;
+use bridge_did::init::brc20::{Brc20BridgeConfig, MemoDepositConfig};
+use bridge_did::init::{
+    DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig, IndexerHeader,
+    WithdrawalApprovalConfig, WithdrawalBatchConfig,
+};
+use bridge_did::inscription::InscriptionId;
 use bridge_did::schnorr::{SchnorrAlgorithm, SchnorrKeyId};
+use bridge_utils::fee_estimator::IcFeeEstimator;
+use did::H160;
 use eth_signer::sign_strategy::SigningStrategy;
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{BitcoinNetwork, Outpoint};
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyResponse,
 };
@@ -23,50 +36,44 @@ use ord_rs::Wallet;
 use ord_rs::wallet::LocalSigner;
 
 use self::config::Brc20BridgeConfigStorage;
-pub use self::master_key::MasterKey;
-use self::master_key::MasterKeyStorage;
+use self::memo_deposits::MemoDepositStorage;
+pub use self::memo_deposits::{PendingMemoDeposit, RefundableMemoDeposit};
+use self::tokens::Brc20TokenStorage;
+pub use self::watched_addresses::WatchedAddress;
+use self::watched_addresses::WatchedAddressStorage;
+use crate::interface::GetAddressError;
 use crate::key::{BtcSignerType, IcBtcSigner};
 use crate::ledger::UtxoLedger;
+use crate::memory::MASTER_KEY_MEMORY_ID;
 use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
 
 /// Minimum number of indexers required to start the bridge.
 const MIN_INDEXERS: usize = 2;
 
 pub struct Brc20State {
-    pub(crate) brc20_tokens: HashMap<Brc20Tick, Brc20Info>,
+    pub(crate) brc20_tokens: Brc20TokenStorage<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) config: Brc20BridgeConfigStorage<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) fee_rate_state: FeeRateState,
     pub(crate) ledger: UtxoLedger<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) master_key: MasterKeyStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) memo_deposits: MemoDepositStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) watched_addresses: WatchedAddressStorage<VirtualMemory<DefaultMemoryImpl>>,
 }
 
 impl Default for Brc20State {
     fn default() -> Self {
         MEMORY_MANAGER.with(|memory_manager| Self {
-            brc20_tokens: HashMap::default(),
+            brc20_tokens: Brc20TokenStorage::new(memory_manager),
             config: Brc20BridgeConfigStorage::new(memory_manager),
-            master_key: MasterKeyStorage::new(memory_manager),
+            master_key: MasterKeyStorage::new(memory_manager, MASTER_KEY_MEMORY_ID),
             ledger: UtxoLedger::new(memory_manager),
             fee_rate_state: FeeRateState::default(),
+            memo_deposits: MemoDepositStorage::new(memory_manager),
+            watched_addresses: WatchedAddressStorage::new(memory_manager),
         })
     }
 }
 
-pub struct FeeRateState {
-    fee_rate: FeeRate,
-    /// Last update timestamp in nanoseconds
-    last_update_timestamp: u64,
-}
-
-impl Default for FeeRateState {
-    fn default() -> Self {
-        Self {
-            fee_rate: FeeRate::ZERO,
-            last_update_timestamp: 0,
-        }
-    }
-}
-
 impl Brc20State {
     /// Returns id of the IC ECDSA key used by the canister.
     pub fn ecdsa_key_id(&self, signing_strategy: &SigningStrategy) -> EcdsaKeyId {
@@ -144,9 +151,35 @@ impl Brc20State {
         Some(Wallet::new_with_signer(self.btc_signer(signing_strategy)?))
     }
 
-    /// BTC fee in SATs for a deposit request.
-    pub fn deposit_fee(&self) -> u64 {
-        self.config.get().deposit_fee
+    /// BTC fee in SATs for a deposit requested now.
+    ///
+    /// If the dynamic deposit fee is configured, the fee is computed from the cached fee rate,
+    /// otherwise the fixed fee of the configuration is used.
+    pub fn deposit_fee_quote(&self) -> DepositFeeQuote {
+        let config = self.config.get();
+        match &config.dynamic_deposit_fee {
+            Some(fee_config) => {
+                let fee_rate = self.fee_rate().to_sat_per_vb_ceil();
+                DepositFeeQuote {
+                    fee: fee_config.fee(fee_rate),
+                    fee_rate: Some(fee_rate),
+                }
+            }
+            None => DepositFeeQuote {
+                fee: config.deposit_fee,
+                fee_rate: None,
+            },
+        }
+    }
+
+    /// Sets the dynamic deposit fee configuration. If `None`, the fixed deposit fee is used.
+    pub fn set_dynamic_deposit_fee_config(&mut self, fee_config: Option<DepositFeeConfig>) {
+        if let Some(Err(err)) = fee_config.as_ref().map(DepositFeeConfig::validate) {
+            panic!("Invalid deposit fee configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.dynamic_deposit_fee = fee_config);
     }
 
     /// Url of the `ord` indexer this canister rely on.
@@ -185,6 +218,12 @@ impl Brc20State {
             .iter()
             .map(|url| url.strip_suffix('/').unwrap_or(url).to_owned())
             .collect();
+        config.indexer_headers = config.indexer_headers.map(|headers| {
+            headers
+                .into_iter()
+                .map(|(url, headers)| (url.strip_suffix('/').unwrap_or(&url).to_owned(), headers))
+                .collect()
+        });
 
         self.config.set(config);
     }
@@ -228,45 +267,92 @@ impl Brc20State {
                 .iter()
                 .map(|url| url.strip_suffix('/').unwrap_or(url).to_owned())
                 .collect();
+
+            // headers of the removed indexers are not needed anymore
+            if let Some(headers) = &mut config.indexer_headers {
+                headers.retain(|url, _| config.indexer_urls.contains(url));
+            }
         });
     }
 
+    /// Headers sent with the requests to each of the indexers.
+    pub fn indexer_headers(&self) -> HashMap<String, Vec<IndexerHeader>> {
+        self.config
+            .get()
+            .indexer_headers
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Sets the headers sent with the requests to the indexers. Panics if the headers are invalid
+    /// or set for an unknown indexer.
+    pub fn set_indexer_headers(&mut self, headers: HashMap<String, Vec<IndexerHeader>>) {
+        let mut config = self.config.get().clone();
+        config.indexer_headers = Some(headers);
+        self.configure(config);
+    }
+
     pub fn mempool_timeout(&self) -> Duration {
         self.config.get().mempool_timeout
     }
 
     /// Update fee rate and the last update timestamp.
     pub fn update_fee_rate(&mut self, fee_rate: FeeRate) {
-        self.fee_rate_state.fee_rate = fee_rate;
-        self.fee_rate_state.last_update_timestamp = ic::time();
+        self.fee_rate_state.update(fee_rate);
     }
 
     /// Fee rate used by the canister.
     pub fn fee_rate(&self) -> FeeRate {
-        self.fee_rate_state.fee_rate
+        self.fee_rate_state.fee_rate()
+    }
+
+    /// Returns the fee estimation configuration.
+    pub fn fee_estimator_config(&self) -> FeeEstimatorConfig {
+        self.config.get().fee_estimator.clone().unwrap_or_default()
+    }
+
+    /// Sets the fee estimation configuration. If `None`, the median of the IC fee percentiles
+    /// is used.
+    ///
+    /// The cached fee rate is dropped, so the next transaction uses the new configuration.
+    pub fn set_fee_estimator_config(&mut self, fee_estimator: Option<FeeEstimatorConfig>) {
+        if let Some(Err(err)) = fee_estimator.as_ref().map(FeeEstimatorConfig::validate) {
+            panic!("Invalid fee estimator configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.fee_estimator = fee_estimator);
+        self.fee_rate_state = FeeRateState::default();
+    }
+
+    /// Fee estimator to request the current fee rate with.
+    pub fn fee_estimator(&self) -> IcFeeEstimator {
+        IcFeeEstimator::new(self.ic_btc_network(), self.fee_estimator_config())
     }
 
     /// Elapsed time since the last fee rate update. (nano seconds)
     pub fn last_fee_rate_update_elapsed(&self) -> Duration {
-        ic::time()
-            .checked_sub(self.fee_rate_state.last_update_timestamp)
-            .map(Duration::from_nanos)
-            .unwrap_or_default()
+        self.fee_rate_state.last_update_elapsed()
     }
 
-    pub fn brc20_tokens(&self) -> &HashMap<Brc20Tick, Brc20Info> {
-        &self.brc20_tokens
+    pub fn brc20_info(&self, tick: &Brc20Tick) -> Option<Brc20Info> {
+        self.brc20_tokens.get(tick)
     }
 
-    pub fn brc20_info(&self, tick: &Brc20Tick) -> Option<Brc20Info> {
-        self.brc20_tokens
-            .values()
-            .find(|info| &info.tick == tick)
-            .copied()
+    /// Adds the tokens received from the indexers to the token info cache.
+    pub fn add_brc20_tokens(&mut self, brc20_tokens: impl IntoIterator<Item = Brc20Info>) {
+        for info in brc20_tokens {
+            self.brc20_tokens.insert(info);
+        }
+    }
+
+    /// Number of the deployed tokens already requested by the token list refresh.
+    pub fn brc20_tokens_refresh_offset(&self) -> u64 {
+        self.brc20_tokens.refresh_offset()
     }
 
-    pub fn update_brc20_tokens(&mut self, brc20_tokens: HashMap<Brc20Tick, Brc20Info>) {
-        self.brc20_tokens = brc20_tokens;
+    pub fn set_brc20_tokens_refresh_offset(&mut self, offset: u64) {
+        self.brc20_tokens.set_refresh_offset(offset);
     }
 
     /// Returns the number of indexers required to reach consensus.
@@ -279,10 +365,192 @@ impl Brc20State {
         self.config
             .with_borrow_mut(|config| config.indexer_consensus_threshold = threshold);
     }
+
+    /// Returns the deposit watcher configuration. If `None`, deposits are not detected
+    /// automatically.
+    pub fn deposit_watcher_config(&self) -> Option<DepositWatcherConfig> {
+        self.config.get().deposit_watcher
+    }
+
+    /// Sets the deposit watcher configuration.
+    pub fn set_deposit_watcher_config(&mut self, deposit_watcher: Option<DepositWatcherConfig>) {
+        if let Some(Err(err)) = deposit_watcher.as_ref().map(DepositWatcherConfig::validate) {
+            panic!("Invalid deposit watcher configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.deposit_watcher = deposit_watcher);
+    }
+
+    /// Starts watching the deposit address of the given user for new deposits.
+    ///
+    /// New addresses are rejected while the maximum number of the watched addresses is reached.
+    /// Returns the cycles to charge for the registration.
+    pub fn watch_deposit_address(
+        &mut self,
+        eth_address: H160,
+        dst_tokens: HashMap<Brc20Tick, H160>,
+        attached_cycles: u64,
+    ) -> Result<u64, GetAddressError> {
+        let config = self
+            .deposit_watcher_config()
+            .ok_or(GetAddressError::DepositWatcherDisabled)?;
+        if attached_cycles < config.registration_fee_cycles {
+            return Err(GetAddressError::InsufficientCycles {
+                required: config.registration_fee_cycles,
+                attached: attached_cycles,
+            });
+        }
+
+        if !self
+            .watched_addresses
+            .can_register(&eth_address, config.max_watched_addresses)
+        {
+            return Err(GetAddressError::TooManyWatchedAddresses);
+        }
+
+        self.watched_addresses
+            .register(eth_address, dst_tokens, ic::time());
+
+        Ok(config.registration_fee_cycles)
+    }
+
+    /// Returns the next batch of the watched deposit addresses to scan. Addresses watched for
+    /// longer than the configured period are dropped.
+    pub fn next_watched_addresses(
+        &mut self,
+        config: &DepositWatcherConfig,
+    ) -> Vec<(H160, WatchedAddress)> {
+        let watch_period = Duration::from_secs(config.watch_period_secs).as_nanos() as u64;
+        let expired_before = ic::time().saturating_sub(watch_period);
+        self.watched_addresses
+            .next_batch(config.max_addresses_per_run as usize, expired_before)
+    }
+
+    /// Sets the utxos of the watched deposit address already handled by the deposit watcher.
+    pub fn set_known_deposit_utxos(&mut self, eth_address: &H160, known_utxos: Vec<Outpoint>) {
+        self.watched_addresses
+            .set_known_utxos(eth_address, known_utxos);
+    }
+
+    /// Returns the memo deposit configuration. If `None`, the transfers to the memo deposit
+    /// address are not minted.
+    pub fn memo_deposit_config(&self) -> Option<MemoDepositConfig> {
+        self.config.get().memo_deposits
+    }
+
+    /// Sets the memo deposit configuration.
+    pub fn set_memo_deposit_config(&mut self, memo_deposits: Option<MemoDepositConfig>) {
+        if let Some(Err(err)) = memo_deposits.as_ref().map(MemoDepositConfig::validate) {
+            panic!("Invalid memo deposit configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.memo_deposits = memo_deposits);
+    }
+
+    /// Returns the withdrawal batching configuration. If `None`, the BRC20 transfers of the
+    /// withdrawals are not batched.
+    pub fn withdrawal_batch_config(&self) -> Option<WithdrawalBatchConfig> {
+        self.config.get().withdrawal_batch
+    }
+
+    /// Sets the withdrawal batching configuration.
+    pub fn set_withdrawal_batch_config(&mut self, batch_config: Option<WithdrawalBatchConfig>) {
+        self.config
+            .with_borrow_mut(|config| config.withdrawal_batch = batch_config);
+    }
+
+    /// Returns true if the withdrawal of the given amount of the BRC20 token must be approved by
+    /// the owner before its transactions are created.
+    pub fn requires_withdrawal_approval(&self, tick: &Brc20Tick, amount: u128) -> bool {
+        self.config
+            .get()
+            .withdrawal_approval
+            .as_ref()
+            .is_some_and(|approval| approval.requires_approval(tick.as_str(), amount))
+    }
+
+    /// Sets the configuration of the withdrawals approved by the owner.
+    pub fn set_withdrawal_approval_config(
+        &mut self,
+        withdrawal_approval: Option<WithdrawalApprovalConfig>,
+    ) {
+        if let Some(Err(err)) = withdrawal_approval
+            .as_ref()
+            .map(WithdrawalApprovalConfig::validate)
+        {
+            panic!("Invalid withdrawal approval configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.withdrawal_approval = withdrawal_approval);
+    }
+
+    /// Checks whether the transfer of the inscription to the memo deposit address has already
+    /// been handled.
+    pub fn is_memo_deposit_handled(&self, inscription_id: &InscriptionId) -> bool {
+        self.memo_deposits.is_handled(inscription_id)
+    }
+
+    /// Marks the transfer of the inscription to the memo deposit address as handled.
+    pub fn set_memo_deposit_handled(&mut self, inscription_id: InscriptionId, block_height: u64) {
+        self.memo_deposits.set_handled(inscription_id, block_height);
+    }
+
+    /// Adds the transfer to the memo deposit address to the pending ones, if it is new.
+    pub fn add_pending_memo_deposit(
+        &mut self,
+        inscription_id: InscriptionId,
+        deposit: PendingMemoDeposit,
+    ) {
+        self.memo_deposits.add_pending(inscription_id, deposit);
+    }
+
+    /// Returns the transfer to the memo deposit address which is not handled yet.
+    pub fn pending_memo_deposit(
+        &self,
+        inscription_id: &InscriptionId,
+    ) -> Option<PendingMemoDeposit> {
+        self.memo_deposits.get_pending(inscription_id)
+    }
+
+    /// Returns the ids of the transactions of the transfers to the memo deposit address which are
+    /// not handled yet.
+    pub fn pending_memo_deposit_txids(&self) -> HashSet<[u8; 32]> {
+        self.memo_deposits.pending_txids()
+    }
+
+    /// Stores the transfer to the memo deposit address which could not be minted.
+    pub fn add_refundable_memo_deposit(
+        &mut self,
+        inscription_id: InscriptionId,
+        deposit: RefundableMemoDeposit,
+    ) {
+        self.memo_deposits.add_refundable(inscription_id, deposit);
+    }
+
+    pub fn refundable_memo_deposit(
+        &self,
+        inscription_id: &InscriptionId,
+    ) -> Option<RefundableMemoDeposit> {
+        self.memo_deposits.get_refundable(inscription_id)
+    }
+
+    pub fn remove_refundable_memo_deposit(&mut self, inscription_id: &InscriptionId) {
+        self.memo_deposits.remove_refundable(inscription_id);
+    }
+
+    /// Lists the transfers to the memo deposit address which could not be minted.
+    pub fn refundable_memo_deposits(&self) -> Vec<(InscriptionId, RefundableMemoDeposit)> {
+        self.memo_deposits.list_refundable()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr as _;
+
     use ic_exports::ic_kit::MockContext;
 
     use super::*;
@@ -330,6 +598,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_should_set_indexer_headers() {
+        MockContext::new().inject();
+        let mut state = Brc20State::default();
+        state.configure(Brc20BridgeConfig {
+            indexer_urls: HashSet::from_iter(vec![
+                "https://indexer1.com".to_string(),
+                "https://indexer2.com".to_string(),
+            ]),
+            ..Default::default()
+        });
+
+        let headers = HashMap::from([(
+            "https://indexer1.com/".to_string(),
+            vec![IndexerHeader {
+                name: "x-api-key".to_string(),
+                value: "secret".to_string(),
+            }],
+        )]);
+        state.set_indexer_headers(headers);
+
+        assert_eq!(
+            state.indexer_headers()["https://indexer1.com"][0].value,
+            "secret"
+        );
+
+        state.configure_indexers(HashSet::from_iter(vec![
+            "https://indexer2.com".to_string(),
+            "https://indexer3.com".to_string(),
+        ]));
+        assert!(state.indexer_headers().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid configuration")]
+    fn test_should_not_set_headers_of_unknown_indexer() {
+        MockContext::new().inject();
+        let mut state = Brc20State::default();
+        state.configure(Brc20BridgeConfig {
+            indexer_urls: HashSet::from_iter(vec!["https://indexer1.com".to_string()]),
+            ..Default::default()
+        });
+
+        state.set_indexer_headers(HashMap::from([(
+            "https://indexer2.com".to_string(),
+            vec![],
+        )]));
+    }
+
+    #[test]
+    fn test_should_require_approval_of_large_withdrawals() {
+        MockContext::new().inject();
+        let mut state = Brc20State::default();
+        let tick = Brc20Tick::from_str("ORDI").unwrap();
+        assert!(!state.requires_withdrawal_approval(&tick, u128::MAX));
+
+        state.set_withdrawal_approval_config(Some(WithdrawalApprovalConfig {
+            thresholds: HashMap::from([("ordi".to_string(), 1_000)]),
+        }));
+        assert!(state.requires_withdrawal_approval(&tick, 1_000));
+        assert!(!state.requires_withdrawal_approval(&tick, 999));
+
+        state.set_withdrawal_approval_config(None);
+        assert!(!state.requires_withdrawal_approval(&tick, 1_000));
+    }
+
     #[test]
     fn test_configure_indexers_valid() {
         let mut state = Brc20State::default();
@@ -402,7 +736,7 @@ mod tests {
         let mut state = Brc20State::default();
 
         assert_eq!(state.fee_rate(), FeeRate::ZERO);
-        assert_eq!(state.fee_rate_state.last_update_timestamp, 0);
+        assert_eq!(state.fee_rate_state.last_update_timestamp(), 0);
 
         let fee_rate = FeeRate::from_sat_per_vb(1000).unwrap();
         state.update_fee_rate(fee_rate);
@@ -411,4 +745,20 @@ mod tests {
         ctx.add_time(Duration::from_secs(1).as_nanos() as u64);
         assert!(state.last_fee_rate_update_elapsed() >= Duration::from_secs(1));
     }
+
+    #[test]
+    fn test_should_cache_brc20_tokens() {
+        let mut state = Brc20State::default();
+        let info = Brc20Info {
+            tick: Brc20Tick::from_str("ordi").unwrap(),
+            decimals: 18,
+        };
+        assert_eq!(state.brc20_info(&info.tick), None);
+
+        state.add_brc20_tokens([info]);
+        state.set_brc20_tokens_refresh_offset(1);
+
+        assert_eq!(state.brc20_info(&info.tick), Some(info));
+        assert_eq!(state.brc20_tokens_refresh_offset(), 1);
+    }
 }
@@ -0,0 +1,204 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use bridge_did::brc20_info::Brc20Tick;
+use bridge_did::inscription::InscriptionId;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, MemoryId, MemoryManager, StableBTreeMap, Storable,
+};
+
+use crate::memory::{
+    MEMO_DEPOSITS_MEMORY_ID, PENDING_MEMO_DEPOSITS_MEMORY_ID, REFUNDABLE_MEMO_DEPOSITS_MEMORY_ID,
+};
+
+/// Transfer inscriptions received at the memo deposit address and already handled by the bridge.
+pub struct MemoDepositStorage<M: Memory> {
+    /// Block height of the transfer by the id of the transfer inscription.
+    handled: StableBTreeMap<InscriptionId, u64, M>,
+    /// Transfers found at the memo deposit address, but not handled yet.
+    pending: StableBTreeMap<InscriptionId, PendingMemoDeposit, M>,
+    /// Handled transfers which could not be minted, and can be refunded by the owner.
+    refundable: StableBTreeMap<InscriptionId, RefundableMemoDeposit, M>,
+}
+
+impl<M> MemoDepositStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            handled: StableBTreeMap::new(memory_manager.get(MEMO_DEPOSITS_MEMORY_ID)),
+            pending: StableBTreeMap::new(memory_manager.get(PENDING_MEMO_DEPOSITS_MEMORY_ID)),
+            refundable: StableBTreeMap::new(memory_manager.get(REFUNDABLE_MEMO_DEPOSITS_MEMORY_ID)),
+        }
+    }
+
+    /// Checks whether the transfer of the inscription has already been handled.
+    pub fn is_handled(&self, inscription_id: &InscriptionId) -> bool {
+        self.handled.get(inscription_id).is_some()
+    }
+
+    /// Marks the transfer of the inscription included into the given block as handled, so no
+    /// other deposit is created for it.
+    pub fn set_handled(&mut self, inscription_id: InscriptionId, block_height: u64) {
+        self.pending.remove(&inscription_id);
+        self.handled.insert(inscription_id, block_height);
+    }
+
+    /// Adds the transfer to the pending ones, unless it is already known. The deposit fee quoted
+    /// when the transfer is found first is kept for it.
+    pub fn add_pending(&mut self, inscription_id: InscriptionId, deposit: PendingMemoDeposit) {
+        if self.is_handled(&inscription_id) || self.pending.get(&inscription_id).is_some() {
+            return;
+        }
+
+        self.pending.insert(inscription_id, deposit);
+    }
+
+    /// Returns the pending transfer of the inscription.
+    pub fn get_pending(&self, inscription_id: &InscriptionId) -> Option<PendingMemoDeposit> {
+        self.pending.get(inscription_id)
+    }
+
+    /// Returns the ids of the transactions of the pending transfers.
+    pub fn pending_txids(&self) -> HashSet<[u8; 32]> {
+        self.pending
+            .iter()
+            .map(|(_, deposit)| deposit.txid)
+            .collect()
+    }
+
+    /// Stores the handled transfer which could not be minted.
+    pub fn add_refundable(
+        &mut self,
+        inscription_id: InscriptionId,
+        deposit: RefundableMemoDeposit,
+    ) {
+        self.refundable.insert(inscription_id, deposit);
+    }
+
+    pub fn get_refundable(&self, inscription_id: &InscriptionId) -> Option<RefundableMemoDeposit> {
+        self.refundable.get(inscription_id)
+    }
+
+    /// Removes the transfer from the refundable ones once its refund is created.
+    pub fn remove_refundable(&mut self, inscription_id: &InscriptionId) {
+        self.refundable.remove(inscription_id);
+    }
+
+    pub fn list_refundable(&self) -> Vec<(InscriptionId, RefundableMemoDeposit)> {
+        self.refundable.iter().collect()
+    }
+}
+
+/// Transfer to the memo deposit address waiting for the confirmations.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct PendingMemoDeposit {
+    /// Transaction sending the inscription. Its outputs pay the deposit fee, so they must not
+    /// fund the withdrawals until the transfer is handled.
+    pub txid: [u8; 32],
+    /// Deposit fee in sats quoted when the transfer was found.
+    pub deposit_fee: u64,
+}
+
+impl Storable for PendingMemoDeposit {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode pending memo deposit"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode pending memo deposit")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Transfer to the memo deposit address which could not be minted.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct RefundableMemoDeposit {
+    pub tick: Brc20Tick,
+    /// Transferred amount as reported by the indexer.
+    pub amount: String,
+    /// Address the transfer was sent from. The tokens are refunded to it.
+    pub refund_address: String,
+    pub block_height: u64,
+    /// Reason the transfer was not minted.
+    pub reason: String,
+}
+
+impl Storable for RefundableMemoDeposit {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode refundable memo deposit"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode refundable memo deposit")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bridge_canister::memory::MEMORY_MANAGER;
+
+    use super::*;
+
+    fn inscription_id() -> InscriptionId {
+        InscriptionId::from_str(
+            "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_should_mark_transfer_as_handled() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| MemoDepositStorage::new(memory_manager));
+        let id = inscription_id();
+        assert!(!storage.is_handled(&id));
+
+        storage.set_handled(id, 840_000);
+        assert!(storage.is_handled(&id));
+    }
+
+    #[test]
+    fn test_should_keep_first_quote_of_pending_transfer() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| MemoDepositStorage::new(memory_manager));
+        let id = inscription_id();
+        let pending = PendingMemoDeposit {
+            txid: [1; 32],
+            deposit_fee: 1_000,
+        };
+
+        storage.add_pending(id, pending.clone());
+        storage.add_pending(
+            id,
+            PendingMemoDeposit {
+                txid: [1; 32],
+                deposit_fee: 5_000,
+            },
+        );
+        assert_eq!(storage.get_pending(&id), Some(pending));
+        assert_eq!(storage.pending_txids(), HashSet::from([[1; 32]]));
+
+        storage.set_handled(id, 840_000);
+        assert_eq!(storage.get_pending(&id), None);
+        assert!(storage.pending_txids().is_empty());
+
+        // handled transfers are not pending again
+        storage.add_pending(
+            id,
+            PendingMemoDeposit {
+                txid: [1; 32],
+                deposit_fee: 1_000,
+            },
+        );
+        assert_eq!(storage.get_pending(&id), None);
+    }
+}
@@ -0,0 +1,54 @@
+use bridge_canister::memory::memory_by_id;
+use bridge_canister::migration::{self, Migration};
+use bridge_did::error::BTFResult;
+
+use super::Brc20State;
+use crate::memory::STATE_VERSION_MEMORY_ID;
+
+/// Migrations of the BRC20 bridge state. The migration at index `i` upgrades the state from
+/// version `i` to version `i + 1`. New migrations must be appended to the end of the list.
+const MIGRATIONS: &[Migration<Brc20State>] = &[restart_brc20_tokens_refresh];
+
+impl Brc20State {
+    /// Marks the state of a freshly installed canister as being at the latest version.
+    pub fn init_state_version(&self) {
+        migration::init_state_version(memory_by_id(STATE_VERSION_MEMORY_ID), MIGRATIONS);
+    }
+
+    /// Applies the pending migrations of the state after an upgrade.
+    pub fn migrate(&mut self) -> BTFResult<()> {
+        let version =
+            migration::migrate_state(memory_by_id(STATE_VERSION_MEMORY_ID), self, MIGRATIONS)?;
+        log::info!("BRC20 bridge state is at version {version}");
+        Ok(())
+    }
+}
+
+/// Requests the token list from the beginning on the next refresh. The tokens with 5 and 6 byte
+/// tickers were skipped by the refreshes made before they were supported, so they are added
+/// to the token info cache only this way.
+fn restart_brc20_tokens_refresh(state: &mut Brc20State) -> BTFResult<()> {
+    state.set_brc20_tokens_refresh_offset(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    #[test]
+    fn test_should_restart_tokens_refresh_on_upgrade() {
+        MockContext::new().inject();
+        let mut state = Brc20State::default();
+        state.set_brc20_tokens_refresh_offset(100);
+
+        state.migrate().unwrap();
+        assert_eq!(state.brc20_tokens_refresh_offset(), 0);
+
+        state.set_brc20_tokens_refresh_offset(100);
+        state.migrate().unwrap();
+        assert_eq!(state.brc20_tokens_refresh_offset(), 100);
+    }
+}
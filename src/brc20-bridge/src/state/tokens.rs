@@ -0,0 +1,50 @@
+use bridge_did::brc20_info::{Brc20Info, Brc20Tick};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, CellStructure as _, MemoryId, MemoryManager, StableBTreeMap, StableCell,
+};
+
+use crate::memory::{BRC20_TOKENS_MEMORY_ID, BRC20_TOKENS_OFFSET_MEMORY_ID};
+
+/// Cache of the BRC20 token information received from the indexers.
+pub struct Brc20TokenStorage<M: Memory> {
+    tokens: StableBTreeMap<Brc20Tick, Brc20Info, M>,
+    /// Number of the deployed tokens already requested from the indexers. The next refresh
+    /// requests the tokens starting from this offset.
+    refresh_offset: StableCell<u64, M>,
+}
+
+impl<M> Brc20TokenStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            tokens: StableBTreeMap::new(memory_manager.get(BRC20_TOKENS_MEMORY_ID)),
+            refresh_offset: StableCell::new(memory_manager.get(BRC20_TOKENS_OFFSET_MEMORY_ID), 0)
+                .expect("stable memory brc20 tokens offset initialization failed"),
+        }
+    }
+
+    /// Returns information about the token with the given tick.
+    pub fn get(&self, tick: &Brc20Tick) -> Option<Brc20Info> {
+        self.tokens.get(tick)
+    }
+
+    /// Stores the token information, replacing the previous one for the same tick.
+    pub fn insert(&mut self, info: Brc20Info) {
+        self.tokens.insert(info.tick, info);
+    }
+
+    /// Offset of the next token list refresh.
+    pub fn refresh_offset(&self) -> u64 {
+        *self.refresh_offset.get()
+    }
+
+    /// Sets the offset of the next token list refresh.
+    pub fn set_refresh_offset(&mut self, offset: u64) {
+        self.refresh_offset
+            .set(offset)
+            .expect("failed to set brc20 tokens offset");
+    }
+}
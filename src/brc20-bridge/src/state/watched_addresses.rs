@@ -0,0 +1,202 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bridge_did::brc20_info::Brc20Tick;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, MemoryId, MemoryManager, StableBTreeMap, Storable,
+};
+
+use crate::memory::WATCHED_ADDRESSES_MEMORY_ID;
+
+/// Deposit addresses scanned by the deposit watcher, by the EVM address of their owners.
+pub struct WatchedAddressStorage<M: Memory> {
+    addresses: StableBTreeMap<H160, WatchedAddress, M>,
+    /// Index of the address to start the next batch from.
+    next_index: u64,
+}
+
+impl<M> WatchedAddressStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            addresses: StableBTreeMap::new(memory_manager.get(WATCHED_ADDRESSES_MEMORY_ID)),
+            next_index: 0,
+        }
+    }
+
+    /// Starts watching the deposit address of the given user from the given timestamp.
+    ///
+    /// The utxos already handled for the address are forgotten, so the utxos skipped because
+    /// of a missing wrapped token are picked up again after the registration with the token.
+    pub fn register(&mut self, eth_address: H160, dst_tokens: HashMap<Brc20Tick, H160>, now: u64) {
+        self.addresses.insert(
+            eth_address,
+            WatchedAddress {
+                dst_tokens,
+                registered_at: now,
+                known_utxos: vec![],
+            },
+        );
+    }
+
+    /// Checks if the address of the given user can be registered without exceeding the
+    /// given number of watched addresses. Registered addresses can always be registered again.
+    pub fn can_register(&self, eth_address: &H160, max_addresses: u64) -> bool {
+        self.addresses.contains_key(eth_address) || self.addresses.len() < max_addresses
+    }
+
+    pub fn get(&self, eth_address: &H160) -> Option<WatchedAddress> {
+        self.addresses.get(eth_address)
+    }
+
+    /// Sets the utxos of the address handled by the watcher.
+    pub fn set_known_utxos(&mut self, eth_address: &H160, known_utxos: Vec<Outpoint>) {
+        if let Some(mut address) = self.addresses.get(eth_address) {
+            address.known_utxos = known_utxos;
+            self.addresses.insert(eth_address.clone(), address);
+        }
+    }
+
+    /// Returns up to `count` addresses to scan, continuing from where the previous batch
+    /// stopped. The addresses registered before `expired_before` are removed instead.
+    pub fn next_batch(&mut self, count: usize, expired_before: u64) -> Vec<(H160, WatchedAddress)> {
+        let len = self.addresses.len();
+        if len == 0 {
+            return vec![];
+        }
+
+        let start = self.next_index % len;
+        let batch: Vec<(H160, WatchedAddress)> = self
+            .addresses
+            .iter()
+            .skip(start as usize)
+            .chain(self.addresses.iter())
+            .take(count.min(len as usize))
+            .collect();
+        self.next_index = start + batch.len() as u64;
+
+        let (expired, active): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .partition(|(_, address)| address.registered_at < expired_before);
+        for (eth_address, _) in expired {
+            log::debug!("Deposit address of {eth_address} is not watched anymore");
+            self.addresses.remove(&eth_address);
+        }
+
+        active
+    }
+}
+
+/// Deposit address registered for the automatic deposit detection.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WatchedAddress {
+    /// Wrapped tokens minted for the deposited BRC20 tokens.
+    pub dst_tokens: HashMap<Brc20Tick, H160>,
+    /// Registration timestamp in nanoseconds.
+    pub registered_at: u64,
+    /// Utxos of the address already handled by the watcher.
+    pub known_utxos: Vec<Outpoint>,
+}
+
+impl Storable for WatchedAddress {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode watched address"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode watched address")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge_canister::memory::MEMORY_MANAGER;
+
+    use super::*;
+
+    fn address(id: u8) -> H160 {
+        H160::from_slice(&[id; 20])
+    }
+
+    #[test]
+    fn test_should_rotate_watched_addresses() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| WatchedAddressStorage::new(memory_manager));
+        for id in 1..=3 {
+            storage.register(address(id), HashMap::new(), 100);
+        }
+
+        let batch: Vec<H160> = storage
+            .next_batch(2, 0)
+            .into_iter()
+            .map(|(a, _)| a)
+            .collect();
+        assert_eq!(batch, vec![address(1), address(2)]);
+
+        let batch: Vec<H160> = storage
+            .next_batch(2, 0)
+            .into_iter()
+            .map(|(a, _)| a)
+            .collect();
+        assert_eq!(batch, vec![address(3), address(1)]);
+
+        let batch: Vec<H160> = storage
+            .next_batch(10, 0)
+            .into_iter()
+            .map(|(a, _)| a)
+            .collect();
+        assert_eq!(batch.len(), 3);
+    }
+
+    #[test]
+    fn test_should_limit_watched_addresses() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| WatchedAddressStorage::new(memory_manager));
+        assert!(storage.can_register(&address(1), 1));
+        storage.register(address(1), HashMap::new(), 100);
+
+        assert!(storage.can_register(&address(1), 1));
+        assert!(!storage.can_register(&address(2), 1));
+        assert!(storage.can_register(&address(2), 2));
+    }
+
+    #[test]
+    fn test_should_remove_expired_addresses() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| WatchedAddressStorage::new(memory_manager));
+        storage.register(address(1), HashMap::new(), 100);
+        storage.register(address(2), HashMap::new(), 200);
+
+        let batch = storage.next_batch(10, 150);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].0, address(2));
+        assert!(storage.get(&address(1)).is_none());
+    }
+
+    #[test]
+    fn test_should_reset_known_utxos_on_registration() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| WatchedAddressStorage::new(memory_manager));
+        let outpoint = Outpoint {
+            txid: vec![1; 32],
+            vout: 0,
+        };
+        storage.register(address(1), HashMap::new(), 100);
+        storage.set_known_utxos(&address(1), vec![outpoint.clone()]);
+        assert_eq!(
+            storage.get(&address(1)).unwrap().known_utxos,
+            vec![outpoint]
+        );
+
+        storage.register(address(1), HashMap::new(), 200);
+        assert!(storage.get(&address(1)).unwrap().known_utxos.is_empty());
+    }
+}
@@ -12,6 +12,7 @@ mod build_data;
 mod canister;
 pub mod inspect;
 pub mod memory;
+pub mod migration;
 pub mod operation_store;
 pub mod runtime;
 
@@ -0,0 +1,140 @@
+//! Versioned migrations of the canister state stored in stable memory.
+//!
+//! The version of the state layout is stored in a dedicated memory. The canister provides the
+//! list of its migrations, where the migration at index `i` upgrades the state from version `i`
+//! to version `i + 1`, so the latest version is the number of the migrations. Canisters
+//! installed before the versioning was introduced are at version 0.
+
+use bridge_did::error::{BTFResult, Error};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{CellStructure as _, StableCell};
+
+/// Migration of the canister state from one version to the next one.
+///
+/// A failed migration should be reported as an error, so the upgrade is rejected as a whole.
+pub type Migration<S> = fn(&mut S) -> BTFResult<()>;
+
+/// Marks the state of a freshly installed canister as being at the latest version, so none of
+/// the migrations is applied to it on the next upgrade.
+pub fn init_state_version<S, M: Memory>(memory: M, migrations: &[Migration<S>]) {
+    let mut version = open_version(memory);
+    version
+        .set(migrations.len() as u32)
+        .expect("failed to set state version");
+}
+
+/// Applies the migrations the state has not passed yet. Returns the version of the state.
+///
+/// The version is stored after every applied migration, so a migration is never applied twice.
+/// If a migration fails, the version stays at the last successfully applied one and the error
+/// is returned.
+pub fn migrate_state<S, M: Memory>(
+    memory: M,
+    state: &mut S,
+    migrations: &[Migration<S>],
+) -> BTFResult<u32> {
+    let mut version = open_version(memory);
+    let current = *version.get();
+    if current as usize > migrations.len() {
+        return Err(Error::Initialization(format!(
+            "state version {current} is newer than the latest known version {}",
+            migrations.len()
+        )));
+    }
+
+    for (from, migration) in migrations.iter().enumerate().skip(current as usize) {
+        log::info!("Migrating the state from version {from} to {}", from + 1);
+        migration(state).map_err(|err| {
+            Error::Initialization(format!(
+                "failed to migrate the state from version {from}: {err}"
+            ))
+        })?;
+        version.set(from as u32 + 1).map_err(|err| {
+            Error::Initialization(format!("failed to set state version: {err:?}"))
+        })?;
+    }
+
+    Ok(*version.get())
+}
+
+fn open_version<M: Memory>(memory: M) -> StableCell<u32, M> {
+    StableCell::new(memory, 0).expect("stable memory state version initialization failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_stable_structures::MemoryId;
+
+    use super::*;
+    use crate::memory::memory_by_id;
+
+    const TEST_MEMORY_ID: MemoryId = MemoryId::new(250);
+
+    fn first(state: &mut Vec<u32>) -> BTFResult<()> {
+        state.push(1);
+        Ok(())
+    }
+
+    fn second(state: &mut Vec<u32>) -> BTFResult<()> {
+        state.push(2);
+        Ok(())
+    }
+
+    fn failing(_: &mut Vec<u32>) -> BTFResult<()> {
+        Err(Error::Initialization("test".to_string()))
+    }
+
+    #[test]
+    fn test_should_apply_pending_migrations_once() {
+        let migrations: &[Migration<Vec<u32>>] = &[first, second];
+        let mut state = vec![];
+
+        let version =
+            migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, &migrations[..1]).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(state, vec![1]);
+
+        let version = migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, migrations).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(state, vec![1, 2]);
+
+        let version = migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, migrations).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(state, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_should_skip_migrations_of_fresh_state() {
+        let migrations: &[Migration<Vec<u32>>] = &[first, second];
+        let mut state = vec![];
+
+        init_state_version(memory_by_id(TEST_MEMORY_ID), migrations);
+        let version = migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, migrations).unwrap();
+
+        assert_eq!(version, 2);
+        assert!(state.is_empty());
+    }
+
+    #[test]
+    fn test_should_keep_version_of_failed_migration() {
+        let mut state = vec![];
+
+        let failed: &[Migration<Vec<u32>>] = &[first, failing];
+        assert!(migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, failed).is_err());
+
+        // the failed migration is applied again on the next upgrade
+        let fixed: &[Migration<Vec<u32>>] = &[first, second];
+        let version = migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, fixed).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(state, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_should_reject_unknown_state_version() {
+        let migrations: &[Migration<Vec<u32>>] = &[first, second];
+        init_state_version(memory_by_id(TEST_MEMORY_ID), migrations);
+
+        let mut state = vec![];
+        assert!(migrate_state(memory_by_id(TEST_MEMORY_ID), &mut state, &migrations[..1]).is_err());
+    }
+}
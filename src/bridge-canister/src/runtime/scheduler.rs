@@ -116,6 +116,12 @@ impl<Op: Operation> BridgeTask<Op> {
             return Err(Error::OperationNotFound(self.op_id));
         };
 
+        // The operation may be completed by an event while its task is still scheduled.
+        if operation.is_complete() {
+            log::trace!("Operation #{} is already complete.", self.op_id);
+            return Ok(());
+        }
+
         let ctx_clone = ctx.clone();
         let progress = operation
             .progress(self.op_id, ctx.clone())
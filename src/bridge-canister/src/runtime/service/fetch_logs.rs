@@ -51,6 +51,19 @@ impl<Op: Operation, H: BtfBridgeEventHandler<Op>> FetchBtfBridgeEventsService<Op
             .await?;
         let events = collected.events;
 
+        // Burn events are verified against the logs of their transaction, so the logs
+        // without the transaction info are requested again instead of being dropped.
+        if let Some(event) = events.iter().find(|event| match event {
+            BridgeEvent::Burnt(data) => {
+                data.tx_hash.is_none() || data.block_number.is_none() || data.log_index.is_none()
+            }
+            _ => false,
+        }) {
+            return Err(Error::EvmRequestFailed(format!(
+                "burn event without transaction info: {event:?}"
+            )));
+        }
+
         self.evm_config
             .borrow_mut()
             .update_evm_params(|params| params.next_block = collected.last_block_number + 1);
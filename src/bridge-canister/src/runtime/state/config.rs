@@ -2,9 +2,11 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use alloy::consensus::{SignableTransaction as _, TxEnvelope, TxLegacy};
 use bridge_did::error::{BTFResult, Error};
 use bridge_did::evm_link::EvmLink;
 use bridge_did::init::BridgeInitData;
+use bridge_utils::btf_events::TxParams;
 use bridge_utils::evm_bridge::EvmParams;
 use bridge_utils::evm_link::EvmLinkClient;
 use bridge_utils::query::{
@@ -12,7 +14,7 @@ use bridge_utils::query::{
 };
 use candid::{CandidType, Principal};
 use did::rpc::id::Id;
-use did::{H160, U256, codec};
+use did::{H160, H256, U256, codec};
 use eth_signer::sign_strategy::{SigningStrategy, TxSigner};
 use ic_stable_structures::{CellStructure, StableCell, Storable};
 use serde::{Deserialize, Serialize};
@@ -116,6 +118,7 @@ impl ConfigStorage {
             signer.get_address().await?
         };
 
+        let nonce_before_query = config.borrow().get_evm_params()?.nonce;
         let responses = query::batch_query(
             &client,
             &[
@@ -136,7 +139,7 @@ impl ConfigStorage {
             .map_err(|e| Error::EvmRequestFailed(format!("failed to query gas price: {e}")))?;
 
         config.borrow_mut().update_evm_params(|p| {
-            p.nonce = nonce.0.to();
+            p.nonce = refreshed_nonce(nonce_before_query, p.nonce, nonce.0.to());
             p.gas_price = gas_price;
         });
 
@@ -145,6 +148,56 @@ impl ConfigStorage {
         Ok(())
     }
 
+    /// Signs the transaction created by `build_tx` with the bridge signer and sends it
+    /// to the EVM.
+    ///
+    /// The nonce of the transaction is allocated before the first await, so concurrently sent
+    /// transactions never share it. If the transaction is not sent, the nonce is released
+    /// unless a later one has already been allocated.
+    ///
+    /// Returns hash of the sent transaction.
+    pub async fn send_transaction(
+        config: Rc<RefCell<Self>>,
+        build_tx: impl FnOnce(TxParams) -> TxLegacy,
+    ) -> BTFResult<H256> {
+        let signer = config.borrow().get_signer()?;
+        let sender = signer.get_address().await?;
+        let bridge_contract = config.borrow().get_btf_bridge_contract().ok_or_else(|| {
+            Error::Initialization("btf bridge contract expected to be initialized".into())
+        })?;
+        let evm_params = {
+            let mut config = config.borrow_mut();
+            let evm_params = config.get_evm_params()?;
+            config.update_evm_params(|p| p.nonce += 1);
+            evm_params
+        };
+
+        let mut tx = build_tx(evm_params.create_tx_params(sender, bridge_contract));
+        let sent = async {
+            let signature = signer.sign_transaction(&mut tx).await?;
+            let envelope: TxEnvelope = tx.into_signed(signature.into()).into();
+
+            let client = config.borrow().get_evm_link().get_json_rpc_client();
+            let tx_hash = client.send_raw_transaction(&envelope).await.map_err(|e| {
+                log::error!("Failed to send transaction to EVM: {e}");
+                Error::EvmRequestFailed(format!("failed to send transaction to EVM: {e}"))
+            })?;
+
+            Ok::<_, Error>(tx_hash)
+        }
+        .await;
+
+        if sent.is_err() {
+            config.borrow_mut().update_evm_params(|p| {
+                if p.nonce == evm_params.nonce + 1 {
+                    p.nonce = evm_params.nonce;
+                }
+            });
+        }
+
+        sent
+    }
+
     /// Sets owner principal.
     pub fn set_owner(&mut self, new_owner: Principal) {
         self.update(|config| config.owner = new_owner);
@@ -266,12 +319,35 @@ impl Storable for Config {
     const BOUND: ic_stable_structures::Bound = ic_stable_structures::Bound::Unbounded;
 }
 
+/// Returns the nonce to store after the refresh of the EVM params.
+///
+/// The transactions sent while the nonce was being queried may be not counted by the EVM
+/// response, so if their nonces were allocated meanwhile, the stored nonce is never decreased.
+fn refreshed_nonce(nonce_before_query: u64, stored_nonce: u64, queried_nonce: u64) -> u64 {
+    if stored_nonce == nonce_before_query {
+        queried_nonce
+    } else {
+        stored_nonce.max(queried_nonce)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ic_stable_structures::Storable;
 
+    use super::refreshed_nonce;
     use crate::runtime::state::config::Config;
 
+    #[test]
+    fn refreshed_nonce_should_keep_nonces_allocated_during_query() {
+        // nothing was sent during the query
+        assert_eq!(refreshed_nonce(5, 5, 7), 7);
+        assert_eq!(refreshed_nonce(5, 5, 3), 3);
+        // a transaction with nonce 5 was sent during the query
+        assert_eq!(refreshed_nonce(5, 6, 5), 6);
+        assert_eq!(refreshed_nonce(5, 6, 8), 8);
+    }
+
     #[test]
     fn config_serialization() {
         let config = Config::default();
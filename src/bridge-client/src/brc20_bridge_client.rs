@@ -1,3 +1,10 @@
+use std::collections::HashMap;
+
+use bridge_did::init::brc20::MemoDepositConfig;
+use bridge_did::init::{
+    DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig, IndexerHeader,
+    WithdrawalApprovalConfig, WithdrawalBatchConfig,
+};
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::{Memo, OperationLog};
 use bridge_did::operations::Brc20BridgeOp;
@@ -16,6 +23,87 @@ impl<C: CanisterClient> Brc20BridgeClient<C> {
         Self { client }
     }
 
+    /// Enables automatic detection of the deposits to the registered deposit addresses with the
+    /// given configuration, or disables it if `None`.
+    pub async fn admin_configure_deposit_watcher(
+        &self,
+        config: Option<DepositWatcherConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_deposit_watcher", (config,))
+            .await
+    }
+
+    /// Enables minting of the BRC20 transfers sent to the memo deposit address with the given
+    /// configuration, or disables it if `None`.
+    pub async fn admin_configure_memo_deposits(
+        &self,
+        config: Option<MemoDepositConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_memo_deposits", (config,))
+            .await
+    }
+
+    /// Enables batching of the BRC20 transfers of the withdrawals with the given configuration,
+    /// or disables it if `None`.
+    pub async fn admin_configure_withdrawal_batch(
+        &self,
+        config: Option<WithdrawalBatchConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_withdrawal_batch", (config,))
+            .await
+    }
+
+    /// Sets the thresholds above which the withdrawals wait for the approval of the owner, or
+    /// sends all the withdrawals without approval if `None`.
+    pub async fn admin_configure_withdrawal_approval(
+        &self,
+        config: Option<WithdrawalApprovalConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_withdrawal_approval", (config,))
+            .await
+    }
+
+    /// Returns the BTC fee in sats charged for a deposit requested now.
+    pub async fn get_deposit_fee_quote(&self) -> CanisterClientResult<DepositFeeQuote> {
+        self.client.query("get_deposit_fee_quote", ()).await
+    }
+
+    /// Enables the deposit fee computed from the current Bitcoin fee rate with the given
+    /// configuration, or switches to the fixed deposit fee if `None`.
+    pub async fn admin_configure_dynamic_deposit_fee(
+        &self,
+        config: Option<DepositFeeConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_dynamic_deposit_fee", (config,))
+            .await
+    }
+
+    /// Sets the Bitcoin fee rate estimation configuration. If `None`, the median of the IC fee
+    /// percentiles is used.
+    pub async fn admin_configure_fee_estimator(
+        &self,
+        config: Option<FeeEstimatorConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_fee_estimator", (config,))
+            .await
+    }
+
+    /// Sets the headers sent with the requests to each of the indexer urls, e.g. API keys.
+    pub async fn admin_set_indexer_headers(
+        &self,
+        headers: HashMap<String, Vec<IndexerHeader>>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_set_indexer_headers", (headers,))
+            .await
+    }
+
     /// Retrieves all operations for the given ETH wallet address whose
     /// id is greater than or equal to `min_included_id` if provided.
     /// The operations are then paginated with the given `pagination` parameters,
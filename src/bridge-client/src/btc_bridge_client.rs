@@ -28,6 +28,22 @@ impl<C: CanisterClient> BtcBridgeClient<C> {
             .await
     }
 
+    /// Requests the threshold ECDSA master key used by the self-custody btc bridge.
+    pub async fn admin_configure_ecdsa(&self) -> CanisterClientResult<BTFResult<()>> {
+        self.client.update("admin_configure_ecdsa", ()).await
+    }
+
+    /// Returns the bitcoin address to deposit BTC to the self-custody btc bridge for the given
+    /// ETH wallet address.
+    pub async fn get_deposit_address(
+        &self,
+        eth_address: &H160,
+    ) -> CanisterClientResult<BTFResult<String>> {
+        self.client
+            .query("get_deposit_address", (eth_address,))
+            .await
+    }
+
     /// Retrieves all operations for the given ETH wallet address whose
     /// id is greater than or equal to `min_included_id` if provided.
     /// The operations are then paginated with the given `pagination` parameters,
@@ -1,7 +1,9 @@
 use bridge_did::error::BTFResult;
+use bridge_did::evm_link::EvmLink;
+use bridge_did::init::erc20::EvmLinkSettings;
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::{Memo, OperationLog};
-use bridge_did::operations::Erc20BridgeOp;
+use bridge_did::operations::{Erc20BridgeOp, Erc20TokenCapability};
 use bridge_utils::common::Pagination;
 use did::H160;
 use ic_canister_client::{CanisterClient, CanisterClientResult};
@@ -65,17 +67,73 @@ impl<C: CanisterClient> Erc20BridgeClient<C> {
             .await
     }
 
-    pub async fn set_base_btf_bridge_contract(&self, address: &H160) -> CanisterClientResult<()> {
+    pub async fn add_evm_link(
+        &self,
+        settings: &EvmLinkSettings,
+    ) -> CanisterClientResult<BTFResult<()>> {
+        self.client.update("add_evm_link", (settings,)).await
+    }
+
+    pub async fn get_evm_links(&self) -> CanisterClientResult<Vec<(u32, EvmLink)>> {
+        self.client.query("get_evm_links", ()).await
+    }
+
+    pub async fn set_evm_link_btf_bridge_contract(
+        &self,
+        chain_id: u32,
+        address: &H160,
+    ) -> CanisterClientResult<BTFResult<()>> {
+        self.client
+            .update("set_evm_link_btf_bridge_contract", (chain_id, address))
+            .await
+    }
+
+    pub async fn set_token_capability(
+        &self,
+        chain_id: u32,
+        token: &H160,
+        capability: Erc20TokenCapability,
+    ) -> CanisterClientResult<BTFResult<()>> {
+        self.client
+            .update("set_token_capability", (chain_id, token, capability))
+            .await
+    }
+
+    pub async fn remove_token_capability(
+        &self,
+        chain_id: u32,
+        token: &H160,
+    ) -> CanisterClientResult<BTFResult<()>> {
         self.client
-            .update("set_base_btf_bridge_contract", (address,))
+            .update("remove_token_capability", (chain_id, token))
             .await
     }
 
-    pub async fn get_bridge_canister_base_evm_address(
+    pub async fn get_token_capabilities(
+        &self,
+    ) -> CanisterClientResult<Vec<(u32, H160, Erc20TokenCapability)>> {
+        self.client.query("get_token_capabilities", ()).await
+    }
+
+    pub async fn set_max_mint_attempts(
+        &self,
+        attempts: u32,
+    ) -> CanisterClientResult<BTFResult<()>> {
+        self.client
+            .update("set_max_mint_attempts", (attempts,))
+            .await
+    }
+
+    pub async fn get_max_mint_attempts(&self) -> CanisterClientResult<u32> {
+        self.client.query("get_max_mint_attempts", ()).await
+    }
+
+    pub async fn get_bridge_canister_evm_link_address(
         &self,
+        chain_id: u32,
     ) -> CanisterClientResult<BTFResult<H160>> {
         self.client
-            .update("get_bridge_canister_base_evm_address", ())
+            .update("get_bridge_canister_evm_link_address", (chain_id,))
             .await
     }
 }
@@ -1,6 +1,12 @@
+use bridge_did::init::brc20::SchnorrKeyIds;
+use bridge_did::init::{
+    DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig,
+    UtxoConsolidationConfig, WithdrawalBatchConfig, WithdrawalCosigningConfig,
+};
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::OperationLog;
 use bridge_did::operations::RuneBridgeOp;
+use bridge_did::runes::EvmNativeToken;
 use bridge_utils::common::Pagination;
 use did::H160;
 use ic_canister_client::{CanisterClient, CanisterClientResult};
@@ -16,6 +22,104 @@ impl<C: CanisterClient> RuneBridgeClient<C> {
         Self { client }
     }
 
+    /// Enables batching of withdrawals with the given configuration, or disables it if `None`.
+    pub async fn admin_configure_withdrawal_batch(
+        &self,
+        config: Option<WithdrawalBatchConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_withdrawal_batch", (config,))
+            .await
+    }
+
+    /// Enables periodic consolidation of the bridge utxos with the given configuration, or
+    /// disables it if `None`.
+    pub async fn admin_configure_utxo_consolidation(
+        &self,
+        config: Option<UtxoConsolidationConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_utxo_consolidation", (config,))
+            .await
+    }
+
+    /// Enables automatic detection of the deposits to the registered deposit addresses with the
+    /// given configuration, or disables it if `None`.
+    pub async fn admin_configure_deposit_watcher(
+        &self,
+        config: Option<DepositWatcherConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_deposit_watcher", (config,))
+            .await
+    }
+
+    /// Returns the BTC fee in sats charged for a deposit requested now.
+    pub async fn get_deposit_fee_quote(&self) -> CanisterClientResult<DepositFeeQuote> {
+        self.client.query("get_deposit_fee_quote", ()).await
+    }
+
+    /// Enables the deposit fee computed from the current Bitcoin fee rate with the given
+    /// configuration, or switches to the fixed deposit fee if `None`.
+    pub async fn admin_configure_dynamic_deposit_fee(
+        &self,
+        config: Option<DepositFeeConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_dynamic_deposit_fee", (config,))
+            .await
+    }
+
+    /// Sets the thresholds above which the withdrawals must be co-signed by the external keys,
+    /// or signs all the withdrawals by the canister alone if `None`.
+    pub async fn admin_configure_withdrawal_cosigning(
+        &self,
+        config: Option<WithdrawalCosigningConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_withdrawal_cosigning", (config,))
+            .await
+    }
+
+    /// Sets the Bitcoin fee rate estimation configuration. If `None`, the median of the IC fee
+    /// percentiles is used.
+    pub async fn admin_configure_fee_estimator(
+        &self,
+        config: Option<FeeEstimatorConfig>,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_fee_estimator", (config,))
+            .await
+    }
+
+    /// Enables the taproot deposit and change addresses derived from the given schnorr key.
+    pub async fn admin_configure_schnorr(&self, key_id: SchnorrKeyIds) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_configure_schnorr", (key_id,))
+            .await
+    }
+
+    /// Links the canister to the base-side BTF bridge, which locks and releases the EVM-native
+    /// tokens etched as runes.
+    pub async fn admin_set_base_btf_bridge_contract(
+        &self,
+        address: &H160,
+    ) -> CanisterClientResult<()> {
+        self.client
+            .update("admin_set_base_btf_bridge_contract", (address,))
+            .await
+    }
+
+    /// Returns the address of the base-side BTF bridge.
+    pub async fn get_base_btf_bridge_contract(&self) -> CanisterClientResult<Option<H160>> {
+        self.client.query("get_base_btf_bridge_contract", ()).await
+    }
+
+    /// Returns the EVM-native tokens represented by the runes etched by the bridge.
+    pub async fn get_evm_native_tokens(&self) -> CanisterClientResult<Vec<EvmNativeToken>> {
+        self.client.query("get_evm_native_tokens", ()).await
+    }
+
     /// Retrieves all operations for the given ETH wallet address whose
     /// id is greater than or equal to `min_included_id` if provided.
     /// The operations are then paginated with the given `pagination` parameters,
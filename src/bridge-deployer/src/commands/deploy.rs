@@ -142,8 +142,14 @@ impl DeployCommands {
         // If the bridge type is BTC, we also deploy the Token contract for wrapped BTC
         if let Bridge::Btc { connection, .. } = &self.bridge_type {
             info!("Deploying wrapped BTC contract");
-            let wrapped_btc_addr =
-                self.deploy_wrapped_btc(network, pk, &btf_bridge, *connection, evm_link.clone())?;
+            let wrapped_btc_addr = self.deploy_wrapped_btc(
+                network,
+                pk,
+                &btf_bridge,
+                canister_id,
+                *connection,
+                evm_link.clone(),
+            )?;
 
             info!("Wrapped BTC contract deployed successfully with {wrapped_btc_addr:x}");
             println!("Wrapped BTC contract deployed with address {wrapped_btc_addr:x}");
@@ -151,6 +157,11 @@ impl DeployCommands {
             info!("Configuring BTC wrapped token on the BTC bridge");
             self.configure_btc_wrapped_token(&agent, &canister_id, wrapped_btc_addr)
                 .await?;
+
+            if connection.is_self_custody() {
+                info!("Configuring ECDSA key of the self-custody BTC bridge");
+                self.configure_btc_ecdsa(&agent, &canister_id).await?;
+            }
         }
 
         // configure minter
@@ -215,11 +226,17 @@ impl DeployCommands {
         network: IcNetwork,
         pk: B256,
         btf_bridge: &Address,
+        bridge_canister: Principal,
         btc_connection: BtcBridgeConnection,
         evm_link: EvmLink,
     ) -> anyhow::Result<Address> {
         let contract_deployer = SolidityContractDeployer::new(network.into(), pk, evm_link);
-        let base_token_id = Id256::from(btc_connection.ledger_principal());
+        // Self-custody bridge uses its own principal as the base token id.
+        let base_token_id = if btc_connection.is_self_custody() {
+            Id256::from(bridge_canister)
+        } else {
+            Id256::from(btc_connection.ledger_principal())
+        };
 
         contract_deployer.deploy_wrapped_token(
             btf_bridge,
@@ -255,6 +272,21 @@ impl DeployCommands {
         Ok(())
     }
 
+    /// Requests the threshold ECDSA master key for the self-custody BTC bridge
+    async fn configure_btc_ecdsa(
+        &self,
+        agent: &ic_agent::Agent,
+        principal: &Principal,
+    ) -> anyhow::Result<()> {
+        agent
+            .update(principal, "admin_configure_ecdsa")
+            .with_arg(Encode!(&())?)
+            .call_and_wait()
+            .await?;
+
+        Ok(())
+    }
+
     /// Gets the wallet canister principal to be used.
     ///
     /// If configured through CLI argument, will return the set one. Otherwise, will return the
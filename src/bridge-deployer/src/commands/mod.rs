@@ -6,7 +6,7 @@ use bridge_client::Erc20BridgeClient;
 use bridge_did::error::BTFResult;
 use bridge_did::evm_link::EvmLink;
 use bridge_did::init::BtcBridgeConfig;
-use bridge_did::init::erc20::{BaseEvmSettings, QueryDelays};
+use bridge_did::init::erc20::{EvmLinkSettings, QueryDelays};
 use candid::{Encode, Principal};
 use clap::{Args, Subcommand};
 use deploy::DeployCommands;
@@ -81,6 +81,9 @@ pub enum Bridge {
         init: config::InitBridgeConfig,
         #[command(flatten, next_help_heading = "ERC20 configuration")]
         erc: BaseEvmSettingsConfig,
+        /// Chain id of the base EVM
+        #[arg(long)]
+        base_evm_chain_id: u32,
         /// Amount of ETH tokens to transfer to the bridge canister
         #[arg(long)]
         base_eth: Option<u128>,
@@ -150,7 +153,12 @@ impl Bridge {
                 debug!("ICRC Bridge Config : {:?}", config);
                 Encode!(&config)?
             }
-            Bridge::Erc20 { init, erc, .. } => {
+            Bridge::Erc20 {
+                init,
+                erc,
+                base_evm_chain_id,
+                ..
+            } => {
                 trace!("Preparing ERC20 bridge configuration");
                 let signing_strategy = init.signing_key_id(evm_network).into();
                 let init = init
@@ -167,7 +175,8 @@ impl Bridge {
                 let evm_params_query =
                     Duration::from_secs(erc.params_query_delay_secs.unwrap_or(60));
                 let logs_query = Duration::from_secs(erc.logs_query_delay_secs.unwrap_or(10 * 60));
-                let erc = BaseEvmSettings {
+                let erc = EvmLinkSettings {
+                    chain_id: *base_evm_chain_id,
                     evm_link: erc.clone().into(),
                     signing_strategy,
                     delays: QueryDelays {
@@ -176,7 +185,7 @@ impl Bridge {
                     },
                 };
 
-                Encode!(&init, &erc)?
+                Encode!(&init, &vec![erc])?
             }
             Bridge::Btc { config, connection } => {
                 trace!("Preparing BTC bridge configuration");
@@ -206,7 +215,12 @@ impl Bridge {
         evm_link: EvmLink,
     ) -> anyhow::Result<Option<BtfDeployedContracts>> {
         match self {
-            Self::Erc20 { erc, base_eth, .. } => {
+            Self::Erc20 {
+                erc,
+                base_evm_chain_id,
+                base_eth,
+                ..
+            } => {
                 let network = if let Some(url) = &erc.base_evm_url {
                     NetworkConfig {
                         custom_network: Some(url.clone()),
@@ -234,8 +248,11 @@ impl Bridge {
                     agent.clone(),
                 ));
                 client
-                    .set_base_btf_bridge_contract(&contracts.btf_bridge.into())
-                    .await?;
+                    .set_evm_link_btf_bridge_contract(
+                        *base_evm_chain_id,
+                        &contracts.btf_bridge.into(),
+                    )
+                    .await??;
 
                 if let Some(eth) = base_eth {
                     let contract_deployer = SolidityContractDeployer::new(network, pk, evm_link);
@@ -352,15 +369,20 @@ impl BTFArgs {
             Address::default()
         };
 
-        let evm_address_method = if is_wrapped_side {
-            "get_bridge_canister_evm_address"
+        let minter_address = if is_wrapped_side {
+            canister_client
+                .update::<_, BTFResult<did::H160>>("get_bridge_canister_evm_address", ())
+                .await?
         } else {
-            "get_bridge_canister_base_evm_address"
-        };
-        let minter_address = canister_client
-            .update::<_, BTFResult<did::H160>>(evm_address_method, ())
-            .await?
-            .context("failed to get the bridge canister address")?;
+            let chain_id = contract_deployer.get_chain_id().await? as u32;
+            canister_client
+                .update::<_, BTFResult<did::H160>>(
+                    "get_bridge_canister_evm_link_address",
+                    (chain_id,),
+                )
+                .await?
+        }
+        .context("failed to get the bridge canister address")?;
 
         info!("Minter address: {:x}", minter_address);
 
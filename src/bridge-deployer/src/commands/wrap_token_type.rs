@@ -16,6 +16,8 @@ use crate::contracts::{IcNetwork, NetworkConfig, SolidityContractDeployer};
 #[derive(Debug, Subcommand)]
 pub enum WrapTokenType {
     Erc20(WrapErc20Args),
+    /// Wrap native token of the base EVM
+    Native(WrapNativeArgs),
 }
 
 impl WrapTokenType {
@@ -31,7 +33,7 @@ impl WrapTokenType {
             .await?;
 
         info!(
-            "Wrapped token contract for ERC20 token {} deployed",
+            "Wrapped token contract for token {} deployed",
             base_token_parameters.name
         );
         info!(
@@ -47,6 +49,7 @@ impl WrapTokenType {
             WrapTokenType::Erc20(erc) => {
                 Self::get_erc20_params(erc.base_evm_url.clone(), &erc.token_address, &pk).await
             }
+            WrapTokenType::Native(native) => Self::get_native_params(native).await,
         }
     }
 
@@ -56,6 +59,10 @@ impl WrapTokenType {
                 wrapped_btf_address,
                 ..
             }) => wrapped_btf_address,
+            WrapTokenType::Native(WrapNativeArgs {
+                wrapped_btf_address,
+                ..
+            }) => wrapped_btf_address,
         }
     }
 
@@ -135,6 +142,20 @@ impl WrapTokenType {
         })
     }
 
+    async fn get_native_params(args: &WrapNativeArgs) -> anyhow::Result<TokenParameters> {
+        let client = EthJsonRpcClient::new(ReqwestClient::new(args.base_evm_url.clone()));
+        let chain_id = client.get_chain_id().await?;
+
+        let id = Id256::from_evm_address(&Id256::native_address(), chain_id as u32);
+
+        Ok(TokenParameters {
+            name: args.name.clone(),
+            symbol: args.symbol.clone(),
+            decimals: args.decimals,
+            id,
+        })
+    }
+
     async fn request_contract(
         client: &EthJsonRpcClient<ReqwestClient>,
         wallet: &LocalWallet,
@@ -187,6 +208,27 @@ pub struct WrapErc20Args {
     token_address: Address,
 }
 
+#[derive(Debug, Args)]
+pub struct WrapNativeArgs {
+    #[arg(long)]
+    base_evm_url: String,
+
+    #[arg(long)]
+    wrapped_btf_address: Address,
+
+    /// Name of the wrapped native token
+    #[arg(long, default_value = "Wrapped Ether")]
+    name: String,
+
+    /// Symbol of the wrapped native token
+    #[arg(long, default_value = "WETH")]
+    symbol: String,
+
+    /// Decimals of the native token
+    #[arg(long, default_value_t = 18)]
+    decimals: u8,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,10 +53,17 @@ impl From<Brc20BridgeConfig> for bridge_did::init::brc20::Brc20BridgeConfig {
             network: value.bitcoin_network.into(),
             min_confirmations: value.min_confirmations,
             indexer_urls: value.indexer_urls.into_iter().collect(),
+            indexer_headers: None,
             deposit_fee: value.deposit_fee,
             mempool_timeout: Duration::from_secs(value.mempool_timeout),
             indexer_consensus_threshold: value.indexer_consensus_threshold,
             schnorr_key_id: SchnorrKeyIds::ProductionKey1,
+            fee_estimator: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            memo_deposits: None,
+            withdrawal_batch: None,
+            withdrawal_approval: None,
         }
     }
 }
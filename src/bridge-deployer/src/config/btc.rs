@@ -1,4 +1,4 @@
-use bridge_did::init::btc::BitcoinConnection;
+use bridge_did::init::btc::{BitcoinConnection, SelfCustodyConfig};
 use candid::{Deserialize, Principal};
 use clap::{Parser, ValueEnum};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
@@ -8,18 +8,25 @@ use serde::Serialize;
 pub struct BtcBridgeConnection {
     /// Bitcoin network to connect to.
     ///
-    /// If regtest is specified, `--ledger`, `--minter` and `--fee` arguments must also be provided.
+    /// If regtest is specified without `--self-custody`, `--ledger`, `--minter` and `--fee`
+    /// arguments must also be provided.
     #[arg(long)]
     network: BtcNetwork,
     /// ckBTC ledger canister principal.
-    #[arg(long, required_if_eq("network", "regtest"))]
+    #[arg(long, required_if_eq_all([("network", "regtest"), ("self_custody", "false")]))]
     ledger: Option<Principal>,
     /// ckBTC minter canister principal.
-    #[arg(long, required_if_eq("network", "regtest"))]
+    #[arg(long, required_if_eq_all([("network", "regtest"), ("self_custody", "false")]))]
     minter: Option<Principal>,
     /// ckBTC ledger fee in satoshi.
-    #[arg(long, required_if_eq("network", "regtest"))]
+    #[arg(long, required_if_eq_all([("network", "regtest"), ("self_custody", "false")]))]
     fee: Option<u64>,
+    /// Hold BTC directly in the bridge canister instead of using ckBTC.
+    #[arg(long, conflicts_with_all(["ledger", "minter", "fee"]))]
+    self_custody: bool,
+    /// Number of confirmations required to accept a deposit in the self-custody mode.
+    #[arg(long, default_value_t = 6)]
+    min_confirmations: u32,
 }
 
 #[derive(ValueEnum, Debug, Serialize, Deserialize, Clone, Copy)]
@@ -33,6 +40,11 @@ const MAINNET_CKBTC_LEDGER: &str = "mxzaz-hqaaa-aaaar-qaada-cai";
 const TESTNET_CKBTC_LEDGER: &str = "mc6ru-gyaaa-aaaar-qaaaq-cai";
 
 impl BtcBridgeConnection {
+    /// Returns true if the bridge holds BTC itself instead of using ckBTC.
+    pub fn is_self_custody(&self) -> bool {
+        self.self_custody
+    }
+
     pub fn ledger_principal(&self) -> Principal {
         if let Some(principal) = self.ledger {
             return principal;
@@ -58,7 +70,14 @@ impl From<BtcNetwork> for BitcoinNetwork {
 
 impl From<BtcBridgeConnection> for BitcoinConnection {
     fn from(value: BtcBridgeConnection) -> Self {
-        if value.ledger.is_some() {
+        if value.self_custody {
+            BitcoinConnection::SelfCustody(SelfCustodyConfig {
+                network: value.network.into(),
+                min_confirmations: value.min_confirmations,
+                btc_cache_timeout_secs: None,
+                fee_estimator: None,
+            })
+        } else if value.ledger.is_some() {
             let ledger = value.ledger.expect("ledger principal is not specified");
             let minter = value.minter.expect("ledger principal is not specified");
             let fee = value.fee.expect("fee is not specified");
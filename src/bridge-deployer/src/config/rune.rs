@@ -1,6 +1,7 @@
 use std::time::Duration;
 
-use bridge_did::init::IndexerType;
+use bridge_did::init::brc20::SchnorrKeyIds;
+use bridge_did::init::{IndexerType, UtxoConsolidationConfig, WithdrawalBatchConfig};
 use clap::{Parser, ValueEnum};
 use ic_exports::ic_cdk::api::management_canister::bitcoin;
 use serde::{Deserialize, Serialize};
@@ -26,12 +27,43 @@ pub struct RuneBridgeConfig {
     /// Note: The number of URLs must match the number of indexers specified above
     #[arg(long, value_delimiter = ',')]
     pub indexer_urls: Vec<String>,
+    /// The URLs of the Hiro Runes API indexers
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    pub hiro_indexer_urls: Vec<String>,
+    /// The URLs of the UniSat Open API indexers
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    pub unisat_indexer_urls: Vec<String>,
     /// The fee to charge for deposits
     #[arg(long)]
     pub deposit_fee: u64,
     /// The timeout for the mempool to confirm a transaction
     #[arg(long)]
     pub mempool_timeout: u64,
+    /// Maximum number of withdrawals sent in one Bitcoin transaction.
+    ///
+    /// If not set, every withdrawal is sent in its own transaction.
+    #[arg(long, requires = "withdrawal_batch_window_secs")]
+    pub withdrawal_batch_size: Option<u32>,
+    /// Maximum time in seconds a withdrawal waits for other withdrawals to join the batch.
+    #[arg(long, requires = "withdrawal_batch_size")]
+    pub withdrawal_batch_window_secs: Option<u64>,
+    /// Minimum number of utxos holding the same runes required to merge them together.
+    ///
+    /// If not set, utxos are never consolidated.
+    #[arg(long, requires_all = ["consolidation_max_inputs", "consolidation_max_fee_rate"])]
+    pub consolidation_min_utxos: Option<u32>,
+    /// Maximum number of utxos merged in one consolidation transaction.
+    #[arg(long, requires = "consolidation_min_utxos")]
+    pub consolidation_max_inputs: Option<u32>,
+    /// Maximum network fee rate (sat/vB) at which utxos are consolidated.
+    #[arg(long, requires = "consolidation_min_utxos")]
+    pub consolidation_max_fee_rate: Option<u64>,
+    /// Use taproot (P2TR) addresses signed with the schnorr key instead of P2WPKH addresses.
+    #[arg(long)]
+    #[serde(default)]
+    pub taproot_addresses: bool,
 }
 
 #[derive(ValueEnum, Serialize, Deserialize, Debug, Clone)]
@@ -60,11 +92,48 @@ impl From<RuneBridgeConfig> for bridge_did::init::RuneBridgeConfig {
             indexers: value
                 .indexer_urls
                 .into_iter()
-                .map(|url| IndexerType::OrdHttp { url })
+                .map(|url| IndexerType::OrdHttp { url, headers: None })
+                .chain(
+                    value
+                        .hiro_indexer_urls
+                        .into_iter()
+                        .map(|url| IndexerType::Hiro { url, headers: None }),
+                )
+                .chain(
+                    value
+                        .unisat_indexer_urls
+                        .into_iter()
+                        .map(|url| IndexerType::Unisat { url, headers: None }),
+                )
                 .collect(),
             deposit_fee: value.deposit_fee,
             mempool_timeout: Duration::from_secs(value.mempool_timeout),
             indexer_consensus_threshold: value.indexer_consensus_threshold,
+            withdrawal_batch: value
+                .withdrawal_batch_size
+                .zip(value.withdrawal_batch_window_secs)
+                .map(|(max_batch_size, window_secs)| WithdrawalBatchConfig {
+                    max_batch_size,
+                    window_secs,
+                }),
+            utxo_consolidation: value
+                .consolidation_min_utxos
+                .zip(value.consolidation_max_inputs)
+                .zip(value.consolidation_max_fee_rate)
+                .map(
+                    |((min_utxos, max_inputs), max_fee_rate)| UtxoConsolidationConfig {
+                        min_utxos,
+                        max_inputs,
+                        max_fee_rate,
+                    },
+                ),
+            fee_estimator: None,
+            schnorr_key_id: value
+                .taproot_addresses
+                .then_some(SchnorrKeyIds::ProductionKey1),
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            withdrawal_cosigning: None,
         }
     }
 }
@@ -383,6 +383,11 @@ impl SolidityContractDeployer {
         Ok(nonce)
     }
 
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        let chain_id = self.rpc_client()?.get_chain_id().await?;
+        Ok(chain_id)
+    }
+
     pub async fn transfer_eth(&self, to: &Address, amount: u128) -> Result<()> {
         info!(
             "Transferring {amount} ETH tokens to address {}",
@@ -13,6 +13,8 @@ pub enum BatchMintErrorCode {
     UnexpectedRecipientChainId,
     TokensNotBridged,
     ProcessingNotRequested,
+    /// The bridge contract doesn't hold enough native tokens to release.
+    InsufficientNativeBalance,
     /// Transaction reverted with a string error message.
     Reverted(String),
 }
@@ -22,6 +24,29 @@ impl BatchMintErrorCode {
     pub fn is_ok(&self) -> bool {
         matches!(self, BatchMintErrorCode::Ok)
     }
+
+    /// Returns whether the order will never be minted, regardless of the number of attempts.
+    pub fn is_permanent_failure(&self) -> bool {
+        matches!(
+            self,
+            BatchMintErrorCode::ZeroAmount
+                | BatchMintErrorCode::ZeroRecipient
+                | BatchMintErrorCode::UnexpectedRecipientChainId
+                | BatchMintErrorCode::TokensNotBridged
+        )
+    }
+
+    /// Returns whether tokens of the failed order may be returned to the sender, once the
+    /// order is cancelled. Orders failed because of low bridge liquidity are never refunded,
+    /// as they can be minted after the liquidity is replenished.
+    pub fn is_refundable(&self) -> bool {
+        !matches!(
+            self,
+            BatchMintErrorCode::Ok
+                | BatchMintErrorCode::UsedNonce
+                | BatchMintErrorCode::InsufficientNativeBalance
+        )
+    }
 }
 
 impl TryFrom<u8> for BatchMintErrorCode {
@@ -37,6 +62,7 @@ impl TryFrom<u8> for BatchMintErrorCode {
             5 => Ok(BatchMintErrorCode::UnexpectedRecipientChainId),
             6 => Ok(BatchMintErrorCode::TokensNotBridged),
             7 => Ok(BatchMintErrorCode::ProcessingNotRequested),
+            8 => Ok(BatchMintErrorCode::InsufficientNativeBalance),
             value => Err(BatchMintResultError::UnknownError(value)),
         }
     }
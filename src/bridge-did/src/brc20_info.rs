@@ -2,49 +2,70 @@ use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+use candid::types::{Serializer, Type};
 use candid::{CandidType, Deserialize};
 use ic_stable_structures::{Bound, Storable};
-use serde::Serialize;
+use serde::{Deserializer, Serialize};
 
 use crate::id256::Id256;
 
-/// Brc20Tick is a 4 bytes ASCII identifier for a BRC20 token.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize, Hash)]
-pub struct Brc20Tick([u8; 4]);
+/// Brc20Tick is a UTF-8 identifier of a BRC20 token.
+///
+/// Tickers are 4 bytes long for the original tokens, 5 bytes for the self-mint tokens and
+/// 6 bytes for the tokens deployed with the 6-byte ticker extension. Tickers are case
+/// insensitive, so they are stored in lower case.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Brc20Tick {
+    len: u8,
+    bytes: [u8; Self::MAX_LEN],
+}
 
 impl Brc20Tick {
-    pub fn inner(&self) -> [u8; 4] {
-        self.0
+    /// Minimum length of a ticker in bytes.
+    pub const MIN_LEN: usize = 4;
+    /// Maximum length of a ticker in bytes.
+    pub const MAX_LEN: usize = 6;
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(self.as_bytes()).expect("brc20 tick is always valid utf-8")
     }
 
     pub fn name_array(&self) -> [u8; 32] {
         let mut name = [0u8; 32];
-        name[0..4].copy_from_slice(&self.0);
+        name[..self.len as usize].copy_from_slice(self.as_bytes());
         name
     }
 
     pub fn symbol_array(&self) -> [u8; 16] {
         let mut name = [0u8; 16];
-        name[0..4].copy_from_slice(&self.0);
+        name[..self.len as usize].copy_from_slice(self.as_bytes());
         name
     }
 }
 
-impl From<[u8; 4]> for Brc20Tick {
-    fn from(tick: [u8; 4]) -> Self {
-        Brc20Tick(tick)
+impl TryFrom<&[u8]> for Brc20Tick {
+    type Error = ();
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let tick = std::str::from_utf8(bytes).map_err(|_| ())?;
+        Self::from_str(tick)
     }
 }
 
 impl From<Id256> for Brc20Tick {
     fn from(id: Id256) -> Self {
-        Brc20Tick(id.to_brc20_tick().expect("unexpected id256"))
+        let tick = id.to_brc20_tick().expect("unexpected id256");
+        Brc20Tick::try_from(tick.as_slice()).expect("invalid brc20 tick in id256")
     }
 }
 
 impl From<Brc20Tick> for Id256 {
     fn from(tick: Brc20Tick) -> Self {
-        Id256::from_brc20_tick(tick.inner())
+        Id256::from_brc20_tick(tick.as_bytes())
     }
 }
 
@@ -52,22 +73,81 @@ impl FromStr for Brc20Tick {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 4 {
+        let tick = s.to_lowercase();
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&tick.len()) {
             return Err(());
         }
 
-        let mut tick = [0u8; 4];
-        tick.copy_from_slice(s.as_bytes());
-        Ok(Brc20Tick(tick))
+        let mut bytes = [0u8; Self::MAX_LEN];
+        bytes[..tick.len()].copy_from_slice(tick.as_bytes());
+        Ok(Brc20Tick {
+            len: tick.len() as u8,
+            bytes,
+        })
     }
 }
 
 impl Display for Brc20Tick {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", String::from_utf8_lossy(&self.0))
+        f.write_str(self.as_str())
+    }
+}
+
+impl CandidType for Brc20Tick {
+    fn _ty() -> Type {
+        String::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_text(self.as_str())
+    }
+}
+
+impl Serialize for Brc20Tick {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Brc20Tick {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value)
+            .map_err(|_| serde::de::Error::custom(format!("invalid brc20 tick: {value}")))
     }
 }
 
+impl Storable for Brc20Tick {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = Vec::with_capacity(Self::BOUND.max_size() as usize);
+        buf.push(self.len);
+        buf.extend_from_slice(&self.bytes);
+
+        buf.into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self {
+            len: bytes[0],
+            bytes: bytes[1..1 + Self::MAX_LEN].try_into().unwrap(),
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 1 + Self::MAX_LEN as u32,
+        is_fixed_size: true,
+    };
+}
+
 /// Brc20 token information.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
 pub struct Brc20Info {
@@ -78,23 +158,21 @@ pub struct Brc20Info {
 impl Storable for Brc20Info {
     fn to_bytes(&self) -> Cow<[u8]> {
         let mut buf = Vec::with_capacity(Self::BOUND.max_size() as usize);
-        buf.extend_from_slice(&self.tick.inner());
+        buf.extend_from_slice(&self.tick.to_bytes());
         buf.extend_from_slice(&self.decimals.to_le_bytes());
 
         buf.into()
     }
 
     fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        let name = bytes[0..4].try_into().unwrap();
-        let decimals = u8::from_le_bytes(bytes[4..5].try_into().unwrap());
-        Self {
-            tick: Brc20Tick(name),
-            decimals,
-        }
+        let tick_size = Brc20Tick::BOUND.max_size() as usize;
+        let tick = Brc20Tick::from_bytes(Cow::Borrowed(&bytes[..tick_size]));
+        let decimals = u8::from_le_bytes(bytes[tick_size..tick_size + 1].try_into().unwrap());
+        Self { tick, decimals }
     }
 
     const BOUND: Bound = Bound::Bounded {
-        max_size: size_of::<u32>() as u32 + size_of::<u8>() as u32,
+        max_size: Brc20Tick::BOUND.max_size() + size_of::<u8>() as u32,
         is_fixed_size: true,
     };
 }
@@ -117,6 +195,14 @@ mod test {
         assert_eq!(info, decoded);
     }
 
+    #[test]
+    fn test_encode_decode_brc20_tick() {
+        for tick in ["ordi", "ordis", "bitmap", "🐸"] {
+            let tick = Brc20Tick::from_str(tick).unwrap();
+            assert_eq!(Brc20Tick::from_bytes(tick.to_bytes()), tick);
+        }
+    }
+
     #[test]
     fn test_brc20_tick_display() {
         let tick = Brc20Tick::from_str("ordi").unwrap();
@@ -126,7 +212,47 @@ mod test {
     #[test]
     fn test_brc20_tick_from_str() {
         let tick = Brc20Tick::from_str("ordi").unwrap();
-        assert_eq!(tick.inner(), [b'o', b'r', b'd', b'i']);
+        assert_eq!(tick.as_bytes(), b"ordi");
+        assert_eq!(tick.as_str(), "ordi");
+    }
+
+    #[test]
+    fn test_brc20_tick_should_be_case_insensitive() {
+        assert_eq!(
+            Brc20Tick::from_str("ORDI").unwrap(),
+            Brc20Tick::from_str("ordi").unwrap()
+        );
+        assert_eq!(Brc20Tick::from_str("OrDi").unwrap().to_string(), "ordi");
+    }
+
+    #[test]
+    fn test_should_parse_long_and_utf8_brc20_ticks() {
+        assert_eq!(Brc20Tick::from_str("ordis").unwrap().as_str(), "ordis");
+        assert_eq!(Brc20Tick::from_str("bitmap").unwrap().as_str(), "bitmap");
+        // 4-byte emoji
+        assert_eq!(Brc20Tick::from_str("🐸").unwrap().as_bytes().len(), 4);
+        // 2-byte characters
+        assert_eq!(Brc20Tick::from_str("ßßß").unwrap().as_str(), "ßßß");
+    }
+
+    #[test]
+    fn test_long_brc20_tick_from_id256() {
+        for tick in ["ordis", "bitmap", "🐸"] {
+            let tick = Brc20Tick::from_str(tick).unwrap();
+            assert_eq!(Brc20Tick::from(Id256::from(tick)), tick);
+        }
+    }
+
+    #[test]
+    fn test_brc20_tick_candid_encoding() {
+        let tick = Brc20Tick::from_str("ordis").unwrap();
+
+        let encoded = candid::encode_one(tick).unwrap();
+        assert_eq!(candid::decode_one::<String>(&encoded).unwrap(), "ordis");
+        assert_eq!(candid::decode_one::<Brc20Tick>(&encoded).unwrap(), tick);
+
+        let invalid = candid::encode_one("ordinals").unwrap();
+        assert!(candid::decode_one::<Brc20Tick>(&invalid).is_err());
     }
 
     #[test]
@@ -140,7 +266,7 @@ mod test {
 
     #[test]
     fn test_brc20_tick_from_str_fail() {
-        let tick = Brc20Tick::from_str("ordi1");
+        let tick = Brc20Tick::from_str("ordinal");
         assert!(tick.is_err());
     }
 
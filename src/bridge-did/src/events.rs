@@ -29,6 +29,12 @@ pub struct BurntEventData {
     pub symbol: Vec<u8>,
     pub decimals: u8,
     pub memo: Vec<u8>,
+    /// Hash of the transaction which emitted the event, if known.
+    pub tx_hash: Option<did::H256>,
+    /// Number of the block which includes the event transaction, if known.
+    pub block_number: Option<u64>,
+    /// Index of the event log in the block, if known.
+    pub log_index: Option<u64>,
 }
 
 impl BurntEventData {
@@ -61,6 +67,9 @@ impl From<BurnTokenEvent> for BurntEventData {
             symbol: event.symbol.0.into(),
             decimals: event.decimals,
             memo: event.memo.0.into(),
+            tx_hash: None,
+            block_number: None,
+            log_index: None,
         }
     }
 }
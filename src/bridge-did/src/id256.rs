@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 
+use bitcoin::hashes::{Hash as _, HashEngine as _, sha256};
 use candid::{CandidType, Principal};
 use did::H160;
 use ic_stable_structures::{Bound, Storable};
@@ -22,6 +23,14 @@ use crate::error::{BTFResult, Error};
 /// ## IC principals encoding
 /// [1] - principal data length,
 /// [2..] - principal data.
+///
+/// ## BRC20 ticks encoding
+/// [1..5] - 4-byte tick data (`BRC20_TICK_MARK`), or
+/// [1] - tick data length, [2..] - tick data (`BRC20_TICK_V2_MARK`).
+///
+/// ## Ordinal inscriptions encoding
+/// [1..32] - first 31 bytes of the SHA-256 hash of the inscription txid and index. The hash
+/// cannot be converted back, so the bridge keeps the ids of the inscriptions it holds.
 #[derive(
     Debug,
     Copy,
@@ -44,7 +53,14 @@ impl Id256 {
     pub const PRINCIPAL_MARK: u8 = 0;
     pub const EVM_ADDRESS_MARK: u8 = 1;
     pub const BTC_TX_MARK: u8 = 2;
+    /// Mark of the 4-byte BRC20 ticks, which are encoded without the length.
     pub const BRC20_TICK_MARK: u8 = 3;
+    /// Mark of the BRC20 ticks of any other length.
+    pub const BRC20_TICK_V2_MARK: u8 = 4;
+    pub const INSCRIPTION_MARK: u8 = 5;
+
+    /// Length of the BRC20 ticks encoded with the [`Self::BRC20_TICK_MARK`].
+    const BRC20_TICK_V1_LEN: usize = 4;
 
     /// Creates unique identifier for contract.
     /// Chain id required to make identifiers unique across all chains.
@@ -77,23 +93,60 @@ impl Id256 {
         Ok((chain_id, address))
     }
 
-    /// Convert ID256 into BRC20 tick.
-    pub fn to_brc20_tick(&self) -> BTFResult<[u8; 4]> {
-        if self.0[0] != Self::BRC20_TICK_MARK {
-            return Err(Error::Serialization(
+    /// Convert ID256 into BRC20 tick bytes.
+    pub fn to_brc20_tick(&self) -> BTFResult<Vec<u8>> {
+        match self.0[0] {
+            Self::BRC20_TICK_MARK => Ok(self.0[1..][..Self::BRC20_TICK_V1_LEN].to_vec()),
+            Self::BRC20_TICK_V2_MARK => {
+                let len = self.0[1] as usize;
+                if len > Self::BYTE_SIZE - 2 {
+                    return Err(Error::Serialization(
+                        "wrong brc20 tick length in Id256".into(),
+                    ));
+                }
+
+                Ok(self.0[2..][..len].to_vec())
+            }
+            _ => Err(Error::Serialization(
                 "wrong brc20 tick mark in Id256".into(),
-            ));
+            )),
         }
-
-        Ok(self.0[1..5].try_into().expect("we have exactly 4 bytes"))
     }
 
     /// Creates unique identifier for BRC20 token.
-    pub fn from_brc20_tick(tick: [u8; 4]) -> Self {
+    ///
+    /// 4-byte ticks keep the original encoding, so the identifiers of the already bridged
+    /// tokens don't change. Ticks of other lengths are prefixed with their length.
+    ///
+    /// # Panics
+    /// If the tick is longer than 30 bytes.
+    pub fn from_brc20_tick(tick: &[u8]) -> Self {
         let mut buf = [0u8; Self::BYTE_SIZE];
 
-        buf[0] = Self::BRC20_TICK_MARK;
-        buf[1..][..4].copy_from_slice(&tick);
+        if tick.len() == Self::BRC20_TICK_V1_LEN {
+            buf[0] = Self::BRC20_TICK_MARK;
+            buf[1..][..tick.len()].copy_from_slice(tick);
+        } else {
+            assert!(tick.len() <= Self::BYTE_SIZE - 2, "brc20 tick is too long");
+            buf[0] = Self::BRC20_TICK_V2_MARK;
+            buf[1] = tick.len() as u8;
+            buf[2..][..tick.len()].copy_from_slice(tick);
+        }
+
+        Self(buf)
+    }
+
+    /// Creates unique identifier for an ordinal inscription with the given reveal transaction
+    /// id and index.
+    pub fn from_inscription_id(txid: &[u8; 32], index: u32) -> Self {
+        let mut engine = sha256::Hash::engine();
+        engine.input(txid);
+        engine.input(&index.to_be_bytes());
+        let hash = sha256::Hash::from_engine(engine);
+
+        let mut buf = [0u8; Self::BYTE_SIZE];
+        buf[0] = Self::INSCRIPTION_MARK;
+        buf[1..].copy_from_slice(&hash.as_byte_array()[..Self::BYTE_SIZE - 1]);
 
         Self(buf)
     }
@@ -175,7 +228,9 @@ impl TryFrom<&[u8]> for Id256 {
             Self::PRINCIPAL_MARK
             | Self::EVM_ADDRESS_MARK
             | Self::BTC_TX_MARK
-            | Self::BRC20_TICK_MARK => Ok(Self(inner)),
+            | Self::BRC20_TICK_MARK
+            | Self::BRC20_TICK_V2_MARK
+            | Self::INSCRIPTION_MARK => Ok(Self(inner)),
             _ => Err(Error::Serialization(
                 "wrong Id256 mark in first byte".into(),
             )),
@@ -308,10 +363,21 @@ mod tests {
     #[test]
     fn test_should_convert_id256_to_brc20() {
         let tick = [b'o', b'r', b'd', b'i'];
-        let id = Id256::from_brc20_tick(tick);
+        let id = Id256::from_brc20_tick(&tick);
+        assert_eq!(id.0[0], Id256::BRC20_TICK_MARK);
         assert_eq!(id.to_brc20_tick().unwrap(), tick);
     }
 
+    #[test]
+    fn test_should_convert_id256_to_long_brc20_tick() {
+        for tick in ["ordis".as_bytes(), "bitmap".as_bytes(), "🐸🐸".as_bytes()] {
+            let id = Id256::from_brc20_tick(tick);
+            assert_eq!(id.0[0], Id256::BRC20_TICK_V2_MARK);
+            assert_eq!(id.to_brc20_tick().unwrap(), tick);
+            assert_eq!(Id256::from_slice(&id.0), Some(id));
+        }
+    }
+
     #[test]
     fn id256_to_principal_roundtrip() {
         let principal = Principal::from_slice(&[20; 29]);
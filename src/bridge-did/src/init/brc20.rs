@@ -1,6 +1,6 @@
 mod schnorr_key_id;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use candid::{CandidType, Decode, Encode};
@@ -9,13 +9,19 @@ use ic_stable_structures::Storable;
 use serde::Deserialize;
 
 pub use self::schnorr_key_id::SchnorrKeyIds;
-use super::{DEFAULT_DEPOSIT_FEE, DEFAULT_INDEXER_CONSENSUS_THRESHOLD, DEFAULT_MEMPOOL_TIMEOUT};
+use super::{
+    DEFAULT_DEPOSIT_FEE, DEFAULT_INDEXER_CONSENSUS_THRESHOLD, DEFAULT_MEMPOOL_TIMEOUT,
+    DepositFeeConfig, DepositWatcherConfig, FeeEstimatorConfig, IndexerHeader,
+    WithdrawalApprovalConfig, WithdrawalBatchConfig,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub struct Brc20BridgeConfig {
     pub network: BitcoinNetwork,
     pub min_confirmations: u32,
     pub indexer_urls: HashSet<String>,
+    /// Headers sent with every request to the indexer with the given url, e.g. API keys.
+    pub indexer_headers: Option<HashMap<String, Vec<IndexerHeader>>>,
     pub deposit_fee: u64,
     pub mempool_timeout: Duration,
     /// Minimum quantity of indexer nodes required to reach agreement on a
@@ -23,6 +29,21 @@ pub struct Brc20BridgeConfig {
     pub indexer_consensus_threshold: u8,
     /// Schnorr key ID for the management canister
     pub schnorr_key_id: SchnorrKeyIds,
+    /// Bitcoin fee rate estimation. If set to None, the median of the IC fee percentiles is used.
+    pub fee_estimator: Option<FeeEstimatorConfig>,
+    /// If set, the registered deposit addresses are scanned for new deposits automatically.
+    pub deposit_watcher: Option<DepositWatcherConfig>,
+    /// If set, the deposit fee is computed from the current Bitcoin fee rate instead of using
+    /// the fixed `deposit_fee`.
+    pub dynamic_deposit_fee: Option<DepositFeeConfig>,
+    /// If set, the BRC20 transfers to the memo deposit address of the bridge are minted to the
+    /// recipient encoded in the metadata of the transfer inscription.
+    pub memo_deposits: Option<MemoDepositConfig>,
+    /// If set, the BRC20 transfers of the withdrawals are accumulated and sent in a single
+    /// Bitcoin transaction.
+    pub withdrawal_batch: Option<WithdrawalBatchConfig>,
+    /// If set, large withdrawals are not sent until they are approved by the owner.
+    pub withdrawal_approval: Option<WithdrawalApprovalConfig>,
 }
 
 impl Storable for Brc20BridgeConfig {
@@ -45,10 +66,17 @@ impl Default for Brc20BridgeConfig {
             network: BitcoinNetwork::Regtest,
             min_confirmations: 12,
             indexer_urls: HashSet::default(),
+            indexer_headers: None,
             deposit_fee: DEFAULT_DEPOSIT_FEE,
             mempool_timeout: DEFAULT_MEMPOOL_TIMEOUT,
             indexer_consensus_threshold: DEFAULT_INDEXER_CONSENSUS_THRESHOLD,
             schnorr_key_id: SchnorrKeyIds::TestKey1,
+            fee_estimator: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            memo_deposits: None,
+            withdrawal_batch: None,
+            withdrawal_approval: None,
         }
     }
 }
@@ -67,6 +95,63 @@ impl Brc20BridgeConfig {
             return Err("Indexer url must etiher specify https url or be localhost".to_string());
         }
 
+        let normalized_urls: HashSet<&str> = self
+            .indexer_urls
+            .iter()
+            .map(|url| url.strip_suffix('/').unwrap_or(url))
+            .collect();
+        for (url, headers) in self.indexer_headers.iter().flatten() {
+            if !normalized_urls.contains(url.strip_suffix('/').unwrap_or(url)) {
+                return Err(format!("Headers are set for unknown indexer {url}"));
+            }
+
+            for header in headers {
+                header.validate()?;
+            }
+        }
+
+        if let Some(fee_estimator) = &self.fee_estimator {
+            fee_estimator.validate()?;
+        }
+
+        if let Some(deposit_watcher) = &self.deposit_watcher {
+            deposit_watcher.validate()?;
+        }
+
+        if let Some(dynamic_deposit_fee) = &self.dynamic_deposit_fee {
+            dynamic_deposit_fee.validate()?;
+        }
+
+        if let Some(memo_deposits) = &self.memo_deposits {
+            memo_deposits.validate()?;
+        }
+
+        if let Some(withdrawal_approval) = &self.withdrawal_approval {
+            withdrawal_approval.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Configuration of the deposits without per-user deposit addresses.
+///
+/// The users send their BRC20 `transfer` inscriptions to the single memo deposit address of the
+/// bridge. The inscription metadata holds the EVM recipient and the wrapped token, e.g.
+/// `{"dst_address": "0x...", "dst_token": "0x..."}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct MemoDepositConfig {
+    /// Maximum number of transfers handled in one run of the memo deposit scanner. Limits the
+    /// number of the indexer requests, and therefore the cycles spent on each run.
+    pub max_transfers_per_run: u32,
+}
+
+impl MemoDepositConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_transfers_per_run == 0 {
+            return Err("Maximum number of transfers per run must be positive".to_string());
+        }
+
         Ok(())
     }
 }
@@ -88,10 +173,23 @@ mod test {
             ]
             .into_iter()
             .collect(),
+            indexer_headers: Some(HashMap::from([(
+                "https://indexer1.com".to_string(),
+                vec![IndexerHeader {
+                    name: "x-api-key".to_string(),
+                    value: "secret".to_string(),
+                }],
+            )])),
             deposit_fee: 100,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 2,
             schnorr_key_id: SchnorrKeyIds::TestKey1,
+            fee_estimator: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            memo_deposits: None,
+            withdrawal_batch: None,
+            withdrawal_approval: None,
         };
 
         let bytes = config.to_bytes();
@@ -100,16 +198,40 @@ mod test {
         assert_eq!(config, decoded);
     }
 
+    #[test]
+    fn test_should_validate_memo_deposit_config() {
+        let mut config = Brc20BridgeConfig {
+            indexer_urls: HashSet::from(["https://indexer1.com".to_string()]),
+            memo_deposits: Some(MemoDepositConfig {
+                max_transfers_per_run: 10,
+            }),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.memo_deposits = Some(MemoDepositConfig {
+            max_transfers_per_run: 0,
+        });
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_should_encode_and_decode_config_with_empty_urls() {
         let config = Brc20BridgeConfig {
             network: BitcoinNetwork::Mainnet,
             min_confirmations: 12,
             indexer_urls: HashSet::new(),
+            indexer_headers: None,
             deposit_fee: 100,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 2,
             schnorr_key_id: SchnorrKeyIds::TestKey1,
+            fee_estimator: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            memo_deposits: None,
+            withdrawal_batch: None,
+            withdrawal_approval: None,
         };
 
         let bytes = config.to_bytes();
@@ -6,7 +6,7 @@ use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_stable_structures::{Bound, Storable};
 use serde::Serialize;
 
-use crate::init::BridgeInitData;
+use crate::init::{BridgeInitData, FeeEstimatorConfig};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
 pub struct WrappedTokenConfig {
@@ -57,7 +57,7 @@ pub struct BtcBridgeConfig {
     pub init_data: BridgeInitData,
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, CandidType, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub enum BitcoinConnection {
     #[default]
     Mainnet,
@@ -68,6 +68,33 @@ pub enum BitcoinConnection {
         ckbtc_ledger: Principal,
         ledger_fee: u64,
     },
+    /// The bridge holds BTC itself on the addresses derived from its threshold ECDSA key instead
+    /// of using ckBTC.
+    SelfCustody(SelfCustodyConfig),
+}
+
+/// Configuration of the self-custody BTC bridge.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct SelfCustodyConfig {
+    pub network: BitcoinNetwork,
+    /// Minimum number of confirmations a deposited utxo must have to be accepted.
+    pub min_confirmations: u32,
+    /// Timeout of the utxo requests cache. Caching is disabled if not set.
+    pub btc_cache_timeout_secs: Option<u32>,
+    /// Fee rate estimation configuration. If not set, the median of the IC fee percentiles is
+    /// used.
+    pub fee_estimator: Option<FeeEstimatorConfig>,
+}
+
+impl Default for SelfCustodyConfig {
+    fn default() -> Self {
+        Self {
+            network: BitcoinNetwork::Regtest,
+            min_confirmations: 12,
+            btc_cache_timeout_secs: None,
+            fee_estimator: None,
+        }
+    }
 }
 
 // https://internetcomputer.org/docs/current/developer-docs/multi-chain/chain-key-tokens/ckbtc/overview#how-it-works
@@ -86,29 +113,45 @@ impl BitcoinConnection {
             BitcoinConnection::Mainnet => BitcoinNetwork::Mainnet,
             BitcoinConnection::Testnet => BitcoinNetwork::Testnet,
             BitcoinConnection::Custom { network, .. } => *network,
+            BitcoinConnection::SelfCustody(config) => config.network,
         }
     }
-    pub fn ckbtc_minter(&self) -> Principal {
+
+    /// Returns ckBTC minter principal. `None` in self-custody mode.
+    pub fn ckbtc_minter(&self) -> Option<Principal> {
         match self {
-            BitcoinConnection::Mainnet => Principal::from_text(MAINNET_CKBTC_MINTER).unwrap(),
-            BitcoinConnection::Testnet => Principal::from_text(TESTNET_CKBTC_MINTER).unwrap(),
-            BitcoinConnection::Custom { ckbtc_minter, .. } => *ckbtc_minter,
+            BitcoinConnection::Mainnet => Some(Principal::from_text(MAINNET_CKBTC_MINTER).unwrap()),
+            BitcoinConnection::Testnet => Some(Principal::from_text(TESTNET_CKBTC_MINTER).unwrap()),
+            BitcoinConnection::Custom { ckbtc_minter, .. } => Some(*ckbtc_minter),
+            BitcoinConnection::SelfCustody(_) => None,
         }
     }
 
-    pub fn ckbtc_ledger(&self) -> Principal {
+    /// Returns ckBTC ledger principal. `None` in self-custody mode.
+    pub fn ckbtc_ledger(&self) -> Option<Principal> {
         match self {
-            BitcoinConnection::Mainnet => Principal::from_text(MAINNET_CKBTC_LEDGER).unwrap(),
-            BitcoinConnection::Testnet => Principal::from_text(TESTNET_CKBTC_LEDGER).unwrap(),
-            BitcoinConnection::Custom { ckbtc_ledger, .. } => *ckbtc_ledger,
+            BitcoinConnection::Mainnet => Some(Principal::from_text(MAINNET_CKBTC_LEDGER).unwrap()),
+            BitcoinConnection::Testnet => Some(Principal::from_text(TESTNET_CKBTC_LEDGER).unwrap()),
+            BitcoinConnection::Custom { ckbtc_ledger, .. } => Some(*ckbtc_ledger),
+            BitcoinConnection::SelfCustody(_) => None,
         }
     }
 
+    /// Returns ckBTC ledger fee. Zero in self-custody mode.
     pub fn ledger_fee(&self) -> u64 {
         match self {
             BitcoinConnection::Mainnet => CKBTC_TRANSFER_FEE,
             BitcoinConnection::Testnet => CKBTC_TRANSFER_FEE,
             BitcoinConnection::Custom { ledger_fee, .. } => *ledger_fee,
+            BitcoinConnection::SelfCustody(_) => 0,
+        }
+    }
+
+    /// Returns self-custody configuration if the bridge doesn't use ckBTC.
+    pub fn self_custody(&self) -> Option<SelfCustodyConfig> {
+        match self {
+            BitcoinConnection::SelfCustody(config) => Some(config.clone()),
+            _ => None,
         }
     }
 }
@@ -143,4 +186,21 @@ mod test {
 
         assert_eq!(config, decoded);
     }
+
+    #[test]
+    fn test_should_encode_decode_self_custody_connection() {
+        let connection = BitcoinConnection::SelfCustody(SelfCustodyConfig {
+            network: BitcoinNetwork::Testnet,
+            min_confirmations: 6,
+            btc_cache_timeout_secs: Some(60),
+            fee_estimator: Some(FeeEstimatorConfig::default()),
+        });
+
+        let decoded = BitcoinConnection::from_bytes(connection.to_bytes());
+
+        assert_eq!(decoded, connection);
+        assert_eq!(decoded.network(), BitcoinNetwork::Testnet);
+        assert!(decoded.ckbtc_minter().is_none());
+        assert!(decoded.ckbtc_ledger().is_none());
+    }
 }
@@ -0,0 +1,95 @@
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Configuration of the deposit fee computed from the current Bitcoin fee rate.
+///
+/// The fee covers the transaction moving the deposited assets out of the bridge, so it is the
+/// cost of a transaction of `tx_vsize` at the current fee rate, increased by the markup and
+/// limited to the given range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DepositFeeConfig {
+    /// Expected virtual size of the transaction spending the deposit, in vbytes.
+    pub tx_vsize: u64,
+    /// Percentage added on top of the transaction cost.
+    pub markup_percent: u32,
+    /// Minimum deposit fee in sats.
+    pub min_fee: u64,
+    /// Maximum deposit fee in sats. Also used while the current fee rate is unknown.
+    pub max_fee: u64,
+}
+
+impl DepositFeeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tx_vsize == 0 {
+            return Err("Transaction vsize must be positive".to_string());
+        }
+
+        if self.min_fee > self.max_fee {
+            return Err("Minimum deposit fee must not exceed the maximum fee".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the deposit fee in sats for the given fee rate in sats per vbyte.
+    pub fn fee(&self, fee_rate_sat_per_vb: u64) -> u64 {
+        if fee_rate_sat_per_vb == 0 {
+            return self.max_fee;
+        }
+
+        let tx_fee = fee_rate_sat_per_vb as u128 * self.tx_vsize as u128;
+        let fee = tx_fee * (100 + self.markup_percent as u128) / 100;
+
+        fee.clamp(self.min_fee as u128, self.max_fee as u128) as u64
+    }
+}
+
+/// Deposit fee charged by the bridge at the moment of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DepositFeeQuote {
+    /// Deposit fee in sats.
+    pub fee: u64,
+    /// Fee rate the fee is computed for, in sats per vbyte. `None` if the fee is fixed.
+    pub fee_rate: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: DepositFeeConfig = DepositFeeConfig {
+        tx_vsize: 200,
+        markup_percent: 50,
+        min_fee: 1_000,
+        max_fee: 100_000,
+    };
+
+    #[test]
+    fn test_should_validate_deposit_fee_config() {
+        assert!(CONFIG.validate().is_ok());
+        assert!(
+            DepositFeeConfig {
+                tx_vsize: 0,
+                ..CONFIG
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DepositFeeConfig {
+                min_fee: 200_000,
+                ..CONFIG
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_should_compute_deposit_fee() {
+        assert_eq!(CONFIG.fee(10), 3_000);
+        assert_eq!(CONFIG.fee(1), CONFIG.min_fee);
+        assert_eq!(CONFIG.fee(1_000), CONFIG.max_fee);
+        assert_eq!(CONFIG.fee(0), CONFIG.max_fee);
+    }
+}
@@ -0,0 +1,81 @@
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Configuration of the automatic deposit detection.
+///
+/// The deposit addresses registered by the users are scanned periodically, and a deposit
+/// operation is created for every new utxo holding the bridged assets, so the users don't need
+/// to request the deposit after sending the assets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct DepositWatcherConfig {
+    /// Maximum number of addresses scanned in one run of the watcher. Limits the number of the
+    /// Bitcoin API and indexer requests, and therefore the cycles spent on each run.
+    pub max_addresses_per_run: u32,
+    /// An address is watched for this time after its registration.
+    pub watch_period_secs: u64,
+    /// Maximum number of addresses watched at the same time. New registrations are rejected
+    /// while the limit is reached.
+    pub max_watched_addresses: u64,
+    /// Cycles charged for every registration. The caller must attach at least this amount to
+    /// the registration call.
+    pub registration_fee_cycles: u64,
+}
+
+impl DepositWatcherConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_addresses_per_run == 0 {
+            return Err("Maximum number of addresses per run must be positive".to_string());
+        }
+
+        if self.watch_period_secs == 0 {
+            return Err("Watch period must be positive".to_string());
+        }
+
+        if self.max_watched_addresses == 0 {
+            return Err("Maximum number of watched addresses must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_validate_deposit_watcher_config() {
+        let config = DepositWatcherConfig {
+            max_addresses_per_run: 10,
+            watch_period_secs: 3600,
+            max_watched_addresses: 1000,
+            registration_fee_cycles: 1_000_000_000,
+        };
+        assert!(config.validate().is_ok());
+
+        assert!(
+            DepositWatcherConfig {
+                max_addresses_per_run: 0,
+                ..config
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DepositWatcherConfig {
+                watch_period_secs: 0,
+                ..config
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DepositWatcherConfig {
+                max_watched_addresses: 0,
+                ..config
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
@@ -26,8 +26,11 @@ impl Storable for QueryDelays {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+/// Settings of an EVM linked to the ERC20 bridge.
 #[derive(Debug, Clone, Deserialize, CandidType)]
-pub struct BaseEvmSettings {
+pub struct EvmLinkSettings {
+    /// Chain id of the linked EVM. Mint orders are routed to the link by this id.
+    pub chain_id: u32,
     pub evm_link: EvmLink,
     pub signing_strategy: SigningStrategy,
     pub delays: QueryDelays,
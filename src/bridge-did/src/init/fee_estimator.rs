@@ -0,0 +1,190 @@
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Configuration of the Bitcoin fee rate estimation.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct FeeEstimatorConfig {
+    /// Sources the fee rate is requested from.
+    pub sources: Vec<FeeRateSource>,
+    /// Minimum number of sources that must respond to use the estimation. The median of the
+    /// responses is used as the fee rate.
+    pub consensus_threshold: u8,
+    /// The estimated fee rate is never lower than this value (sat/vB).
+    pub min_fee_rate: Option<u64>,
+    /// The estimated fee rate is never higher than this value (sat/vB).
+    pub max_fee_rate: Option<u64>,
+}
+
+impl Default for FeeEstimatorConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![FeeRateSource::IcPercentiles(PercentileTargets::default())],
+            consensus_threshold: 1,
+            min_fee_rate: None,
+            max_fee_rate: None,
+        }
+    }
+}
+
+impl FeeEstimatorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sources.is_empty() {
+            return Err("No fee rate sources specified".to_string());
+        }
+
+        if self.consensus_threshold == 0 || self.consensus_threshold as usize > self.sources.len() {
+            return Err(format!(
+                "Fee rate consensus threshold must be between 1 and {}",
+                self.sources.len()
+            ));
+        }
+
+        if self
+            .min_fee_rate
+            .zip(self.max_fee_rate)
+            .is_some_and(|(min, max)| min > max)
+        {
+            return Err("Minimum fee rate is higher than maximum fee rate".to_string());
+        }
+
+        for source in &self.sources {
+            source.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Source of the Bitcoin fee rate estimations.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub enum FeeRateSource {
+    /// Fee percentiles of the recent transactions provided by the IC Bitcoin API.
+    IcPercentiles(PercentileTargets),
+    /// HTTP oracle with the mempool.space compatible `/api/v1/fees/recommended` endpoint.
+    HttpOracle { url: String },
+}
+
+impl FeeRateSource {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::IcPercentiles(targets) => targets.validate(),
+            Self::HttpOracle { url }
+                if url.starts_with("https") || url.starts_with("http://localhost") =>
+            {
+                Ok(())
+            }
+            Self::HttpOracle { .. } => {
+                Err("Fee oracle url must either specify https url or be localhost".to_string())
+            }
+        }
+    }
+}
+
+/// Fee percentiles used for each of the transaction priorities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct PercentileTargets {
+    pub high: u8,
+    pub medium: u8,
+    pub low: u8,
+}
+
+impl Default for PercentileTargets {
+    fn default() -> Self {
+        Self {
+            high: 75,
+            medium: 50,
+            low: 25,
+        }
+    }
+}
+
+impl PercentileTargets {
+    /// Percentile used for the given priority.
+    pub fn percentile(&self, priority: FeePriority) -> u8 {
+        match priority {
+            FeePriority::High => self.high,
+            FeePriority::Medium => self.medium,
+            FeePriority::Low => self.low,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if [self.high, self.medium, self.low]
+            .iter()
+            .any(|percentile| *percentile > 100)
+        {
+            return Err("Fee percentile must not be higher than 100".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Priority of a transaction, defining how fast it should be confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum FeePriority {
+    /// Confirmation in the next block.
+    High,
+    /// Confirmation within half an hour.
+    Medium,
+    /// Confirmation within an hour.
+    Low,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_should_be_valid() {
+        assert!(FeeEstimatorConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_should_validate_config() {
+        let config = FeeEstimatorConfig {
+            sources: vec![
+                FeeRateSource::IcPercentiles(PercentileTargets::default()),
+                FeeRateSource::HttpOracle {
+                    url: "https://mempool.space".to_string(),
+                },
+            ],
+            consensus_threshold: 2,
+            min_fee_rate: Some(1),
+            max_fee_rate: Some(500),
+        };
+        assert!(config.validate().is_ok());
+
+        let invalid_threshold = FeeEstimatorConfig {
+            consensus_threshold: 3,
+            ..config.clone()
+        };
+        assert!(invalid_threshold.validate().is_err());
+
+        let invalid_clamp = FeeEstimatorConfig {
+            min_fee_rate: Some(600),
+            ..config.clone()
+        };
+        assert!(invalid_clamp.validate().is_err());
+
+        let invalid_url = FeeEstimatorConfig {
+            sources: vec![FeeRateSource::HttpOracle {
+                url: "http://mempool.space".to_string(),
+            }],
+            consensus_threshold: 1,
+            ..config.clone()
+        };
+        assert!(invalid_url.validate().is_err());
+
+        let invalid_percentile = FeeEstimatorConfig {
+            sources: vec![FeeRateSource::IcPercentiles(PercentileTargets {
+                high: 101,
+                medium: 50,
+                low: 25,
+            })],
+            consensus_threshold: 1,
+            ..config
+        };
+        assert!(invalid_percentile.validate().is_err());
+    }
+}
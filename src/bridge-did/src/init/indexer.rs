@@ -0,0 +1,71 @@
+use std::fmt;
+
+use candid::CandidType;
+use serde::Deserialize;
+
+/// HTTP header sent with every request to an indexer, e.g. an API key of a paid endpoint.
+///
+/// The header value is a secret, so it is omitted from the debug output.
+#[derive(Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct IndexerHeader {
+    pub name: String,
+    pub value: String,
+}
+
+impl IndexerHeader {
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_name =
+            !self.name.is_empty() && self.name.chars().all(|c| c.is_ascii_graphic() && c != ':');
+        if !valid_name {
+            return Err(format!("Invalid indexer header name: {}", self.name));
+        }
+
+        if self.value.contains(['\r', '\n']) {
+            return Err(format!("Invalid value of indexer header {}", self.name));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for IndexerHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexerHeader")
+            .field("name", &self.name)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &str, value: &str) -> IndexerHeader {
+        IndexerHeader {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_should_validate_header() {
+        assert!(header("x-api-key", "secret").validate().is_ok());
+        assert!(header("Authorization", "Bearer secret").validate().is_ok());
+        assert!(header("", "secret").validate().is_err());
+        assert!(header("x api key", "secret").validate().is_err());
+        assert!(header("x-api-key:", "secret").validate().is_err());
+        assert!(
+            header("x-api-key", "secret\r\nHost: evil")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_should_not_print_header_value() {
+        let debug = format!("{:?}", header("x-api-key", "secret"));
+        assert!(debug.contains("x-api-key"));
+        assert!(!debug.contains("secret"));
+    }
+}
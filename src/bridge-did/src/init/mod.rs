@@ -1,14 +1,27 @@
 pub mod brc20;
 mod bridge_data;
 pub mod btc;
+mod deposit_fee;
+mod deposit_watcher;
 pub mod erc20;
+mod fee_estimator;
+mod indexer;
+pub mod ordinals;
 mod rune;
+mod withdrawal_approval;
+mod withdrawal_cosigning;
 
 use std::time::Duration;
 
 pub use bridge_data::*;
 pub use btc::BtcBridgeConfig;
+pub use deposit_fee::*;
+pub use deposit_watcher::*;
+pub use fee_estimator::*;
+pub use indexer::*;
 pub use rune::*;
+pub use withdrawal_approval::*;
+pub use withdrawal_cosigning::*;
 
 pub const DEFAULT_DEPOSIT_FEE: u64 = 100_000;
 pub const DEFAULT_MEMPOOL_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
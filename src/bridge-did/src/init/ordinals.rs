@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+
+use candid::{CandidType, Decode, Encode};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use ic_stable_structures::Storable;
+use serde::Deserialize;
+
+use super::{DEFAULT_INDEXER_CONSENSUS_THRESHOLD, FeeEstimatorConfig, IndexerHeader};
+
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct OrdinalsBridgeConfig {
+    pub network: BitcoinNetwork,
+    pub min_confirmations: u32,
+    pub indexer_urls: HashSet<String>,
+    /// Headers sent with every request to the indexer with the given url, e.g. API keys.
+    pub indexer_headers: Option<HashMap<String, Vec<IndexerHeader>>>,
+    /// Minimum quantity of indexer nodes required to reach agreement on a
+    /// request
+    pub indexer_consensus_threshold: u8,
+    /// Bitcoin fee rate estimation. If set to None, the median of the IC fee percentiles is used.
+    pub fee_estimator: Option<FeeEstimatorConfig>,
+}
+
+impl Storable for OrdinalsBridgeConfig {
+    const BOUND: ic_stable_structures::Bound = ic_stable_structures::Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(self)
+            .expect("Failed to encode OrdinalsBridgeConfig")
+            .into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, OrdinalsBridgeConfig).expect("Failed to decode OrdinalsBridgeConfig")
+    }
+}
+
+impl Default for OrdinalsBridgeConfig {
+    fn default() -> Self {
+        Self {
+            network: BitcoinNetwork::Regtest,
+            min_confirmations: 12,
+            indexer_urls: HashSet::default(),
+            indexer_headers: None,
+            indexer_consensus_threshold: DEFAULT_INDEXER_CONSENSUS_THRESHOLD,
+            fee_estimator: None,
+        }
+    }
+}
+
+impl OrdinalsBridgeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.indexer_urls.is_empty() {
+            return Err("Indexer url is empty".to_string());
+        }
+
+        if self
+            .indexer_urls
+            .iter()
+            .any(|url| !url.starts_with("https") && !url.starts_with("http://localhost"))
+        {
+            return Err("Indexer url must etiher specify https url or be localhost".to_string());
+        }
+
+        if self.indexer_consensus_threshold as usize > self.indexer_urls.len() {
+            return Err(format!(
+                "Indexer consensus threshold {} is greater than the number of indexers {}",
+                self.indexer_consensus_threshold,
+                self.indexer_urls.len()
+            ));
+        }
+
+        let normalized_urls: HashSet<&str> = self
+            .indexer_urls
+            .iter()
+            .map(|url| url.strip_suffix('/').unwrap_or(url))
+            .collect();
+        for (url, headers) in self.indexer_headers.iter().flatten() {
+            if !normalized_urls.contains(url.strip_suffix('/').unwrap_or(url)) {
+                return Err(format!("Headers are set for unknown indexer {url}"));
+            }
+
+            for header in headers {
+                header.validate()?;
+            }
+        }
+
+        if let Some(fee_estimator) = &self.fee_estimator {
+            fee_estimator.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn config() -> OrdinalsBridgeConfig {
+        OrdinalsBridgeConfig {
+            network: BitcoinNetwork::Mainnet,
+            min_confirmations: 6,
+            indexer_urls: vec![
+                "https://indexer1.com".to_string(),
+                "https://indexer2.com/".to_string(),
+            ]
+            .into_iter()
+            .collect(),
+            indexer_headers: Some(HashMap::from([(
+                "https://indexer2.com".to_string(),
+                vec![IndexerHeader {
+                    name: "x-api-key".to_string(),
+                    value: "secret".to_string(),
+                }],
+            )])),
+            indexer_consensus_threshold: 2,
+            fee_estimator: None,
+        }
+    }
+
+    #[test]
+    fn test_should_encode_and_decode_config() {
+        let config = config();
+
+        let bytes = config.to_bytes();
+        let decoded = OrdinalsBridgeConfig::from_bytes(bytes.clone());
+
+        assert_eq!(config, decoded);
+    }
+
+    #[test]
+    fn test_should_validate_config() {
+        assert_eq!(config().validate(), Ok(()));
+
+        let mut invalid = config();
+        invalid
+            .indexer_urls
+            .insert("http://indexer3.com".to_string());
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config();
+        invalid.indexer_consensus_threshold = 3;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config();
+        invalid.indexer_headers = Some(HashMap::from([(
+            "https://indexer3.com".to_string(),
+            vec![],
+        )]));
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -6,7 +6,11 @@ use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_stable_structures::Storable;
 use serde::Deserialize;
 
-use super::{DEFAULT_DEPOSIT_FEE, DEFAULT_INDEXER_CONSENSUS_THRESHOLD, DEFAULT_MEMPOOL_TIMEOUT};
+use super::brc20::SchnorrKeyIds;
+use super::{
+    DEFAULT_DEPOSIT_FEE, DEFAULT_INDEXER_CONSENSUS_THRESHOLD, DEFAULT_MEMPOOL_TIMEOUT,
+    DepositFeeConfig, DepositWatcherConfig, FeeEstimatorConfig, WithdrawalCosigningConfig,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub struct RuneBridgeConfig {
@@ -22,6 +26,44 @@ pub struct RuneBridgeConfig {
     /// Minimum quantity of indexer nodes required to reach agreement on a
     /// request
     pub indexer_consensus_threshold: u8,
+    /// If set, withdrawals are accumulated and sent in a single Bitcoin transaction.
+    pub withdrawal_batch: Option<WithdrawalBatchConfig>,
+    /// If set, small utxos of the bridge are periodically merged together.
+    pub utxo_consolidation: Option<UtxoConsolidationConfig>,
+    /// Bitcoin fee rate estimation. If set to None, the median of the IC fee percentiles is used.
+    pub fee_estimator: Option<FeeEstimatorConfig>,
+    /// Schnorr key used to derive the taproot (P2TR) deposit, funding and change addresses. If
+    /// set to None, P2WPKH addresses derived from the ECDSA key are used.
+    pub schnorr_key_id: Option<SchnorrKeyIds>,
+    /// If set, the registered deposit addresses are scanned for new deposits automatically.
+    pub deposit_watcher: Option<DepositWatcherConfig>,
+    /// If set, the deposit fee is computed from the current Bitcoin fee rate instead of using
+    /// the fixed `deposit_fee`.
+    pub dynamic_deposit_fee: Option<DepositFeeConfig>,
+    /// If set, large withdrawals are not sent until they are co-signed by the keys held outside
+    /// of the canister.
+    pub withdrawal_cosigning: Option<WithdrawalCosigningConfig>,
+}
+
+/// Configuration of the withdrawal batching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WithdrawalBatchConfig {
+    /// Maximum number of withdrawals in one transaction. The batch is sent as soon as it is full.
+    pub max_batch_size: u32,
+    /// Maximum time a withdrawal waits for other withdrawals to join the batch.
+    pub window_secs: u64,
+}
+
+/// Configuration of the utxo consolidation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub struct UtxoConsolidationConfig {
+    /// Minimum number of utxos holding the same runes required to start a consolidation.
+    pub min_utxos: u32,
+    /// Maximum number of utxos merged in one transaction.
+    pub max_inputs: u32,
+    /// Consolidation transactions are sent only while the network fee rate is not higher than
+    /// this value (sat/vB).
+    pub max_fee_rate: u64,
 }
 
 impl Storable for RuneBridgeConfig {
@@ -47,6 +89,13 @@ impl Default for RuneBridgeConfig {
             deposit_fee: DEFAULT_DEPOSIT_FEE,
             mempool_timeout: DEFAULT_MEMPOOL_TIMEOUT,
             indexer_consensus_threshold: DEFAULT_INDEXER_CONSENSUS_THRESHOLD,
+            withdrawal_batch: None,
+            utxo_consolidation: None,
+            fee_estimator: None,
+            schnorr_key_id: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            withdrawal_cosigning: None,
         }
     }
 }
@@ -61,36 +110,91 @@ impl RuneBridgeConfig {
             indexer.validate()?;
         }
 
+        if let Some(fee_estimator) = &self.fee_estimator {
+            fee_estimator.validate()?;
+        }
+
+        if let Some(deposit_watcher) = &self.deposit_watcher {
+            deposit_watcher.validate()?;
+        }
+
+        if let Some(dynamic_deposit_fee) = &self.dynamic_deposit_fee {
+            dynamic_deposit_fee.validate()?;
+        }
+
+        if let Some(withdrawal_cosigning) = &self.withdrawal_cosigning {
+            withdrawal_cosigning.validate()?;
+        }
+
         Ok(())
     }
 }
 
+/// Rune indexer the bridge requests the rune balances from.
+///
+/// `headers` are sent with every request to the indexer, e.g. to authenticate with an API key.
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub enum IndexerType {
-    OrdHttp { url: String },
+    /// `ord` server with the JSON API enabled.
+    OrdHttp {
+        url: String,
+        headers: Option<Vec<IndexerHeader>>,
+    },
+    /// Hiro Runes API.
+    Hiro {
+        url: String,
+        headers: Option<Vec<IndexerHeader>>,
+    },
+    /// UniSat Open API.
+    Unisat {
+        url: String,
+        headers: Option<Vec<IndexerHeader>>,
+    },
 }
 
 impl IndexerType {
-    fn validate(&self) -> Result<(), String> {
+    /// Base url of the indexer API.
+    pub fn url(&self) -> &str {
         match self {
-            Self::OrdHttp { url }
-                if url.starts_with("https") || url.starts_with("http://localhost") =>
-            {
-                Ok(())
-            }
-            _ => Err("Indexer url must etiher specify https url or be localhost".to_string()),
+            Self::OrdHttp { url, .. } | Self::Hiro { url, .. } | Self::Unisat { url, .. } => url,
+        }
+    }
+
+    /// Headers sent with every request to the indexer.
+    pub fn headers(&self) -> &[IndexerHeader] {
+        match self {
+            Self::OrdHttp { headers, .. }
+            | Self::Hiro { headers, .. }
+            | Self::Unisat { headers, .. } => headers.as_deref().unwrap_or_default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let url = self.url();
+        if !url.starts_with("https") && !url.starts_with("http://localhost") {
+            return Err("Indexer url must etiher specify https url or be localhost".to_string());
+        }
+
+        for header in self.headers() {
+            header.validate()?;
         }
+
+        Ok(())
     }
 
     pub fn normalize(&mut self) {
         match self {
-            Self::OrdHttp { url } => *url = url.strip_suffix('/').unwrap_or(url).to_owned(),
+            Self::OrdHttp { url, .. } | Self::Hiro { url, .. } | Self::Unisat { url, .. } => {
+                *url = url.strip_suffix('/').unwrap_or(url).to_owned()
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use super::*;
 
     #[test]
@@ -102,17 +206,51 @@ mod test {
             indexers: vec![
                 IndexerType::OrdHttp {
                     url: "https://indexer1.com".to_string(),
+                    headers: None,
                 },
                 IndexerType::OrdHttp {
                     url: "https://indexer2.com".to_string(),
+                    headers: None,
                 },
                 IndexerType::OrdHttp {
                     url: "https://indexer3.com".to_string(),
+                    headers: None,
                 },
             ],
             deposit_fee: 100,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 2,
+            withdrawal_batch: Some(WithdrawalBatchConfig {
+                max_batch_size: 10,
+                window_secs: 600,
+            }),
+            utxo_consolidation: Some(UtxoConsolidationConfig {
+                min_utxos: 20,
+                max_inputs: 100,
+                max_fee_rate: 5,
+            }),
+            fee_estimator: Some(FeeEstimatorConfig::default()),
+            schnorr_key_id: Some(SchnorrKeyIds::TestKey1),
+            deposit_watcher: Some(DepositWatcherConfig {
+                max_addresses_per_run: 10,
+                watch_period_secs: 86400,
+                max_watched_addresses: 1000,
+                registration_fee_cycles: 1_000_000_000,
+            }),
+            dynamic_deposit_fee: Some(DepositFeeConfig {
+                tx_vsize: 200,
+                markup_percent: 20,
+                min_fee: 1_000,
+                max_fee: 100_000,
+            }),
+            withdrawal_cosigning: Some(WithdrawalCosigningConfig {
+                thresholds: HashMap::from([("SUPERRUNE".to_string(), 1_000)]),
+                cosigners: vec![
+                    "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+                        .to_string(),
+                ],
+                required_signatures: 1,
+            }),
         };
 
         let bytes = config.to_bytes();
@@ -131,6 +269,13 @@ mod test {
             deposit_fee: 100,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 2,
+            withdrawal_batch: None,
+            utxo_consolidation: None,
+            fee_estimator: None,
+            schnorr_key_id: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            withdrawal_cosigning: None,
         };
 
         let bytes = config.to_bytes();
@@ -138,4 +283,34 @@ mod test {
 
         assert_eq!(config, decoded);
     }
+
+    #[test]
+    fn test_should_validate_and_normalize_indexers() {
+        let mut indexer = IndexerType::Hiro {
+            url: "https://api.hiro.so/".to_string(),
+            headers: Some(vec![IndexerHeader {
+                name: "x-api-key".to_string(),
+                value: "secret".to_string(),
+            }]),
+        };
+        assert!(indexer.validate().is_ok());
+
+        indexer.normalize();
+        assert_eq!(indexer.url(), "https://api.hiro.so");
+
+        let indexer = IndexerType::Unisat {
+            url: "http://open-api.unisat.io".to_string(),
+            headers: None,
+        };
+        assert!(indexer.validate().is_err());
+
+        let indexer = IndexerType::OrdHttp {
+            url: "https://ord.com".to_string(),
+            headers: Some(vec![IndexerHeader {
+                name: String::new(),
+                value: "secret".to_string(),
+            }]),
+        };
+        assert!(indexer.validate().is_err());
+    }
 }
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Configuration of the BRC-20 withdrawals approved by the owner.
+///
+/// The inscriptions of the withdrawals are created and signed by the canister, so the approval
+/// only keeps large withdrawals from being sent until the owner reviews them.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WithdrawalApprovalConfig {
+    /// Withdrawals of at least the given amount of the token wait for the approval of the
+    /// owner. The tokens are identified by their ticks in lower case. Withdrawals of the tokens
+    /// not in the map are sent without approval.
+    pub thresholds: HashMap<String, u128>,
+}
+
+impl WithdrawalApprovalConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.thresholds.is_empty() {
+            return Err("Withdrawal approval thresholds must not be empty".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the withdrawal of the given amount of the token must be approved by the
+    /// owner.
+    pub fn requires_approval(&self, tick: &str, amount: u128) -> bool {
+        self.thresholds
+            .get(tick)
+            .is_some_and(|threshold| amount >= *threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_check_withdrawal_approval_threshold() {
+        let config = WithdrawalApprovalConfig {
+            thresholds: HashMap::from([("ordi".to_string(), 1_000)]),
+        };
+        assert!(config.validate().is_ok());
+
+        assert!(config.requires_approval("ordi", 1_000));
+        assert!(config.requires_approval("ordi", 5_000));
+        assert!(!config.requires_approval("ordi", 999));
+        assert!(!config.requires_approval("sats", 5_000));
+
+        let config = WithdrawalApprovalConfig {
+            thresholds: HashMap::new(),
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use bitcoin::PublicKey;
+use candid::CandidType;
+use serde::Deserialize;
+
+/// Configuration of the withdrawals co-signed by keys held outside of the canister, e.g. by
+/// hardware wallets.
+///
+/// Large withdrawals are exported as PSBTs. The canister signs and sends the transaction only
+/// after every input of the PSBT is signed by `required_signatures` of the co-signers, so with
+/// two co-signers and one required signature a withdrawal needs 2 of 3 keys.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct WithdrawalCosigningConfig {
+    /// Withdrawals of at least the given amount of the rune must be co-signed. The runes are
+    /// identified by their names without spacers. Withdrawals of the runes not in the map are
+    /// signed by the canister alone.
+    pub thresholds: HashMap<String, u128>,
+    /// Hex encoded public keys of the co-signers.
+    pub cosigners: Vec<String>,
+    /// Number of co-signers which must sign every input of the withdrawal.
+    pub required_signatures: u8,
+}
+
+impl WithdrawalCosigningConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.thresholds.is_empty() {
+            return Err("Withdrawal co-signing thresholds must not be empty".to_string());
+        }
+
+        let cosigners = self.cosigner_keys()?;
+        if cosigners.iter().collect::<HashSet<_>>().len() != cosigners.len() {
+            return Err("Withdrawal co-signers must be unique".to_string());
+        }
+
+        if self.required_signatures == 0 || self.required_signatures as usize > cosigners.len() {
+            return Err(format!(
+                "Required co-signatures must be between 1 and {}",
+                cosigners.len()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the withdrawal of the given amount of the rune must be co-signed.
+    pub fn requires_cosigning(&self, rune_name: &str, amount: u128) -> bool {
+        self.thresholds
+            .get(rune_name)
+            .is_some_and(|threshold| amount >= *threshold)
+    }
+
+    /// Returns the public keys of the co-signers.
+    pub fn cosigner_keys(&self) -> Result<Vec<PublicKey>, String> {
+        self.cosigners
+            .iter()
+            .map(|key| {
+                PublicKey::from_str(key)
+                    .map_err(|err| format!("Invalid co-signer public key {key}: {err}"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_KEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const SECOND_KEY: &str = "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+
+    fn config(cosigners: &[&str], required_signatures: u8) -> WithdrawalCosigningConfig {
+        WithdrawalCosigningConfig {
+            thresholds: HashMap::from([("SUPERRUNE".to_string(), 1_000)]),
+            cosigners: cosigners.iter().map(|key| key.to_string()).collect(),
+            required_signatures,
+        }
+    }
+
+    #[test]
+    fn test_should_check_withdrawal_cosigning_threshold() {
+        let config = config(&[FIRST_KEY, SECOND_KEY], 1);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.cosigner_keys().unwrap().len(), 2);
+
+        assert!(config.requires_cosigning("SUPERRUNE", 1_000));
+        assert!(config.requires_cosigning("SUPERRUNE", 5_000));
+        assert!(!config.requires_cosigning("SUPERRUNE", 999));
+        assert!(!config.requires_cosigning("OTHERRUNE", 5_000));
+    }
+
+    #[test]
+    fn test_should_validate_cosigners() {
+        assert!(config(&[FIRST_KEY, SECOND_KEY], 2).validate().is_ok());
+        assert!(config(&[FIRST_KEY, SECOND_KEY], 0).validate().is_err());
+        assert!(config(&[FIRST_KEY, SECOND_KEY], 3).validate().is_err());
+        assert!(config(&[], 0).validate().is_err());
+        assert!(config(&[FIRST_KEY, FIRST_KEY], 1).validate().is_err());
+        assert!(config(&[FIRST_KEY, "not a key"], 1).validate().is_err());
+
+        let mut no_thresholds = config(&[FIRST_KEY], 1);
+        no_thresholds.thresholds.clear();
+        assert!(no_thresholds.validate().is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use bitcoin::Txid;
+use bitcoin::hashes::Hash as _;
+use candid::types::{Serializer, Type};
+use candid::{CandidType, Deserialize};
+use ic_stable_structures::{Bound, Storable};
+use serde::{Deserializer, Serialize};
+
+use crate::id256::Id256;
+
+/// Identifier of an ordinal inscription: the id of the reveal transaction and the index of the
+/// inscription in it.
+///
+/// The text representation is the one used by `ord`: `<txid>i<index>`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InscriptionId {
+    pub txid: Txid,
+    pub index: u32,
+}
+
+impl InscriptionId {
+    /// Size of the inscription id in bytes.
+    const BYTE_SIZE: usize = 32 + 4;
+}
+
+impl FromStr for InscriptionId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (txid, index) = s.split_once('i').ok_or(())?;
+        if index.starts_with('+') {
+            return Err(());
+        }
+
+        Ok(Self {
+            txid: Txid::from_str(txid).map_err(|_| ())?,
+            index: index.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+impl Display for InscriptionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}i{}", self.txid, self.index)
+    }
+}
+
+impl From<InscriptionId> for Id256 {
+    fn from(id: InscriptionId) -> Self {
+        Id256::from_inscription_id(&id.txid.to_byte_array(), id.index)
+    }
+}
+
+impl CandidType for InscriptionId {
+    fn _ty() -> Type {
+        String::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_text(&self.to_string())
+    }
+}
+
+impl Serialize for InscriptionId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for InscriptionId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value)
+            .map_err(|_| serde::de::Error::custom(format!("invalid inscription id: {value}")))
+    }
+}
+
+impl Storable for InscriptionId {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = Vec::with_capacity(Self::BYTE_SIZE);
+        buf.extend_from_slice(self.txid.as_byte_array());
+        buf.extend_from_slice(&self.index.to_be_bytes());
+
+        buf.into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self {
+            txid: Txid::from_slice(&bytes[..32]).expect("invalid inscription txid"),
+            index: u32::from_be_bytes(bytes[32..].try_into().expect("invalid inscription index")),
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::BYTE_SIZE as u32,
+        is_fixed_size: true,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::{Decode, Encode};
+
+    use super::*;
+
+    const ID: &str = "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0";
+
+    #[test]
+    fn test_should_parse_and_print_inscription_id() {
+        let id = InscriptionId::from_str(ID).unwrap();
+
+        assert_eq!(id.index, 0);
+        assert_eq!(id.to_string(), ID);
+
+        let id = InscriptionId::from_str(&ID.replace("i0", "i12")).unwrap();
+        assert_eq!(id.index, 12);
+    }
+
+    #[test]
+    fn test_should_not_parse_invalid_inscription_id() {
+        assert!(InscriptionId::from_str("").is_err());
+        assert!(InscriptionId::from_str(&ID.replace("i0", "")).is_err());
+        assert!(InscriptionId::from_str(&ID.replace("i0", "i+1")).is_err());
+        assert!(InscriptionId::from_str(&ID.replace("i0", "i-1")).is_err());
+        assert!(InscriptionId::from_str(&ID[2..]).is_err());
+    }
+
+    #[test]
+    fn test_should_encode_and_decode_inscription_id() {
+        let id = InscriptionId::from_str(ID).unwrap();
+
+        assert_eq!(InscriptionId::from_bytes(id.to_bytes()), id);
+
+        let encoded = Encode!(&id).unwrap();
+        assert_eq!(Decode!(&encoded, String).unwrap(), ID);
+        assert_eq!(Decode!(&encoded, InscriptionId).unwrap(), id);
+    }
+
+    #[test]
+    fn test_should_convert_inscription_id_to_id256() {
+        let id = InscriptionId::from_str(ID).unwrap();
+        let other = InscriptionId::from_str(&ID.replace("i0", "i1")).unwrap();
+
+        let id256 = Id256::from(id);
+        assert_eq!(id256.0[0], Id256::INSCRIPTION_MARK);
+        assert_eq!(id256, Id256::from(id));
+        assert_ne!(id256, Id256::from(other));
+        assert_eq!(Id256::from_slice(&id256.0), Some(id256));
+    }
+}
@@ -12,6 +12,7 @@ pub mod schnorr;
 pub mod brc20_info;
 pub mod bridge_side;
 mod events;
+pub mod inscription;
 pub mod operations;
 #[cfg(feature = "runes")]
 pub mod runes;
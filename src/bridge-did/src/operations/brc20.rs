@@ -9,6 +9,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use crate::batch_mint_result::BatchMintErrorCode;
 use crate::brc20_info::{Brc20Info, Brc20Tick};
 use crate::events::MintedEventData;
+use crate::inscription::InscriptionId;
 use crate::order::{MintOrder, SignedOrders};
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
@@ -17,6 +18,24 @@ pub struct DepositRequest {
     pub brc20_tick: Brc20Tick,
     pub dst_address: H160,
     pub dst_token: H160,
+    /// Deposit fee in sats quoted when the deposit was requested. `None` for the deposits
+    /// requested before the fee was locked into the operation.
+    pub deposit_fee: Option<u64>,
+}
+
+/// BRC20 transfer to the memo deposit address of the bridge, with the recipient taken from the
+/// metadata of the transfer inscription.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct MemoDeposit {
+    pub inscription_id: InscriptionId,
+    pub brc20_tick: Brc20Tick,
+    /// Transferred amount in the integer representation of the token.
+    pub amount: u128,
+    pub dst_address: H160,
+    pub dst_token: H160,
+    /// Deposit fee in sats charged when the transfer was found. `None` for the deposits found
+    /// before the fee was charged for the memo deposits.
+    pub deposit_fee: Option<u64>,
 }
 
 /// BRC20 bridge operations
@@ -38,6 +57,8 @@ pub enum Brc20BridgeDepositOp {
         deposit: DepositRequest,
         utxos: Vec<Utxo>,
     },
+    /// Create the mint order for the transfer received at the memo deposit address
+    CreateMemoMintOrder(MemoDeposit),
     /// Sign the provided mint order
     SignMintOrder(MintOrder),
     /// Send the signed mint order to the bridge
@@ -55,6 +76,8 @@ pub enum Brc20BridgeDepositOp {
 /// BRC20 bridge withdraw operations
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub enum Brc20BridgeWithdrawOp {
+    /// Await the approval of the withdrawal by the owner before creating its transactions
+    AwaitOwnerApproval(Brc20WithdrawalPayload),
     /// Create BRC20 transfer inscription transactions
     CreateInscriptionTxs(Brc20WithdrawalPayload),
     /// Send BRC20 transfer commit transaction
@@ -84,12 +107,36 @@ pub enum Brc20BridgeWithdrawOp {
     SendTransferTx {
         from_address: H160,
         tx: DidTransaction,
+        /// EVM address whose deposit address funds the transfer. `None` stands for the sender.
+        funding_account: Option<H160>,
     },
-    /// Transfer transaction sent
+    /// Transfer transaction sent. Operations created before the confirmation tracking was
+    /// introduced end up in this state.
     TransferTxSent {
         from_address: H160,
         tx: DidTransaction,
     },
+    /// Await confirmations of the sent transfer transaction. If it is not confirmed in time, it
+    /// gets replaced by a transaction paying a higher fee.
+    AwaitTransferTxConfirmation {
+        from_address: H160,
+        tx: DidTransaction,
+        /// EVM address whose deposit address funds the transfer. `None` stands for the sender.
+        funding_account: Option<H160>,
+        /// Timestamp when the transaction was sent (in nanoseconds)
+        sent_at: u64,
+        /// Ids of the transactions replaced by this one
+        replaced_txids: Vec<H256>,
+    },
+    /// Transfer transaction confirmed
+    TransferTxConfirmed { from_address: H160, txid: H256 },
+    /// The BRC20 transfer has been sent in a transaction shared with other withdrawals
+    BatchTransferTxSent {
+        from_address: H160,
+        txid: H256,
+        /// Part of the transaction fee paid by this withdrawal (in satoshi)
+        fee_share: u64,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
@@ -106,6 +153,18 @@ pub struct Brc20WithdrawalPayload {
     pub request_ts: u64,
     pub sender: H160,
     pub dst_address: String,
+    /// EVM address whose deposit address holds the BRC20 balance and the BTC funding the
+    /// withdrawal. `None` until the funding deposit address is selected, and for the
+    /// withdrawals created before the selection was introduced, which are funded by the
+    /// deposit address of the sender.
+    pub funding_account: Option<H160>,
+}
+
+impl Brc20WithdrawalPayload {
+    /// Returns the EVM address whose deposit address funds the withdrawal.
+    pub fn funding_account(&self) -> &H160 {
+        self.funding_account.as_ref().unwrap_or(&self.sender)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::batch_mint_result::BatchMintErrorCode;
 use crate::events::{BurntEventData, MintedEventData};
+use crate::operations::DidTransaction;
 use crate::order::{MintOrder, SignedOrders};
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
@@ -36,9 +37,66 @@ pub enum BtcBridgeOp {
     },
     Erc20MintConfirmed(MintedEventData),
 
+    // Self-custody deposit operations:
+    /// Collect confirmed utxos sent to the deposit address of the given EVM address.
+    CollectBtcUtxos {
+        eth_address: H160,
+    },
+
     // Withdraw operations:
     WithdrawBtc(BurntEventData),
+    /// Send the signed self-custody withdrawal transaction to the Bitcoin network. If sending
+    /// fails, the transaction inputs are released and the withdrawn amount is refunded.
+    SendBtcWithdrawalTx {
+        event: BurntEventData,
+        tx: DidTransaction,
+    },
+    /// The ckBTC minter accepted the withdrawal request. The bridge polls the minter for the
+    /// request status until the BTC transaction is confirmed or the ckBTC is reimbursed.
+    WaitForBtcWithdrawal {
+        event: BurntEventData,
+        /// Index of the ckBTC burn block, which identifies the withdrawal request in the minter.
+        block_index: u64,
+        /// Last observed status of the withdrawal request.
+        status: BtcWithdrawStatus,
+    },
     BtcWithdrawConfirmed {
         eth_address: H160,
+        /// Index of the ckBTC burn block. `None` for withdrawals made before status tracking
+        /// and for self-custody withdrawals.
+        block_index: Option<u64>,
+        /// Final status of the withdrawal request. `None` for withdrawals made before status
+        /// tracking.
+        status: Option<BtcWithdrawStatus>,
+    },
+    /// The ckBTC minter burned the ckBTC of the withdrawal request, but will never send the BTC.
+    BtcWithdrawFailed {
+        eth_address: H160,
+        /// Index of the ckBTC burn block.
+        block_index: u64,
+        /// Final status of the withdrawal request.
+        status: BtcWithdrawStatus,
+    },
+}
+
+/// Status of a BTC withdrawal request in the ckBTC minter.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, PartialEq, Eq)]
+pub enum BtcWithdrawStatus {
+    /// The request is waiting in the minter queue or the transaction is being signed.
+    Pending,
+    /// The transaction is sent to the Bitcoin network.
+    Submitted { txid: H256 },
+    /// The transaction received enough confirmations.
+    Confirmed { txid: H256 },
+    /// The withdrawal amount is too low to cover the Bitcoin fees. The request is finalized
+    /// without sending BTC.
+    AmountTooLow,
+    /// The minter reimbursed the ckBTC to the bridge. The wrapped tokens are minted back to the
+    /// sender.
+    Reimbursed {
+        amount: u64,
+        /// Index of the ckBTC mint block of the reimbursement.
+        mint_block_index: u64,
+        reason: String,
     },
 }
@@ -1,5 +1,8 @@
+use std::borrow::Cow;
+
 use candid::CandidType;
-use did::H256;
+use did::{H160, H256, codec};
+use ic_stable_structures::{Bound, Storable};
 use serde::{Deserialize, Serialize};
 
 use crate::batch_mint_result::BatchMintErrorCode;
@@ -10,33 +13,225 @@ use crate::order::{MintOrder, SignedOrders};
 /// Erc20 bridge operation.
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub struct Erc20BridgeOp {
-    /// Side of the bridge to perform the operation.
-    pub side: BridgeSide,
+    /// Side of the bridge the operation was created for by the bridge with a single base EVM.
+    /// Not set for the operations created after the bridge supported multiple EVM links.
+    pub side: Option<BridgeSide>,
+
+    /// Chain id of the EVM where the operation mints tokens.
+    /// Not set for the operations created before the bridge supported multiple EVM links.
+    pub chain_id: Option<u32>,
 
     /// Stage of the operation.
     pub stage: Erc20OpStage,
 }
 
+impl Erc20BridgeOp {
+    /// Creates an operation, which mints tokens on the EVM with the given chain id.
+    pub fn new(chain_id: u32, stage: Erc20OpStage) -> Self {
+        Self {
+            side: None,
+            chain_id: Some(chain_id),
+            stage,
+        }
+    }
+
+    /// Returns chain id of the EVM where the operation mints tokens.
+    ///
+    /// Operations created before the bridge supported multiple EVM links store the bridge side
+    /// only, so their chain id is taken from the mint order.
+    pub fn chain_id(&self) -> Option<u32> {
+        self.chain_id.or_else(|| {
+            let order = match self.stage.mint_stage() {
+                Erc20OpStage::VerifyReceivedAmount { order, .. } => order.clone(),
+                Erc20OpStage::SignMintOrder(order) => order.clone(),
+                Erc20OpStage::SendMintTransaction(order) => order.decode_order()?,
+                Erc20OpStage::RetryMintTransaction { order, .. } => order.decode_order()?,
+                Erc20OpStage::CancelMintOrder { order, .. } => order.decode_order()?,
+                Erc20OpStage::WaitForMintOrderCancel { order, .. } => order.decode_order()?,
+                Erc20OpStage::WaitForMintConfirm { order, .. } => order.decode_order()?,
+                Erc20OpStage::TokenMintConfirmed(_) => return None,
+                Erc20OpStage::Refund { .. } => return None,
+            };
+
+            Some(order.recipient_chain_id)
+        })
+    }
+}
+
 /// Erc20 bridge operation stages.
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub enum Erc20OpStage {
+    /// Tokens were deposited to the bridge, but the amount actually received
+    /// by the bridge must be checked before the mint order is signed.
+    VerifyReceivedAmount {
+        order: MintOrder,
+        /// Token deposited to the bridge on the sender chain.
+        token: H160,
+        burn_tx_hash: H256,
+        burn_block_number: u64,
+        /// Index of the burn event log in the block.
+        burn_log_index: u64,
+    },
     SignMintOrder(MintOrder),
     SendMintTransaction(SignedOrders),
+    /// Previous attempts to mint the order failed, so the mint transaction is sent again.
+    RetryMintTransaction {
+        order: SignedOrders,
+        /// Number of previous attempts to mint the order, which failed.
+        failed_attempts: u32,
+    },
+    /// Mint of the order failed, so the order is cancelled on the destination EVM
+    /// before the tokens are refunded.
+    CancelMintOrder {
+        order: SignedOrders,
+        /// Reason of the mint failure.
+        reason: String,
+    },
+    /// Transaction cancelling the order was sent to the destination EVM.
+    /// The tokens are refunded only after the transaction succeeds.
+    WaitForMintOrderCancel {
+        order: SignedOrders,
+        reason: String,
+        tx_hash: H256,
+    },
     WaitForMintConfirm {
         order: SignedOrders,
         mint_results: Vec<BatchMintErrorCode>,
         tx_hash: Option<H256>,
     },
     TokenMintConfirmed(MintedEventData),
+    /// Mint on the destination EVM failed, so the tokens are returned to the sender
+    /// with a mint order on the source EVM.
+    Refund {
+        /// Recipient of the failed mint. The operation stays bound to this address.
+        recipient: H160,
+        /// Reason of the mint failure.
+        reason: String,
+        /// Stage of the refund mint order.
+        stage: Box<Erc20OpStage>,
+    },
 }
 
 impl Erc20OpStage {
     pub fn name(&self) -> String {
         match self {
+            Erc20OpStage::VerifyReceivedAmount { .. } => String::from("VerifyReceivedAmount"),
             Erc20OpStage::SignMintOrder(_) => String::from("SignMintOrder"),
             Erc20OpStage::SendMintTransaction(_) => String::from("SendMintTransaction"),
+            Erc20OpStage::RetryMintTransaction { .. } => String::from("RetryMintTransaction"),
+            Erc20OpStage::CancelMintOrder { .. } => String::from("CancelMintOrder"),
+            Erc20OpStage::WaitForMintOrderCancel { .. } => String::from("WaitForMintOrderCancel"),
             Erc20OpStage::WaitForMintConfirm { .. } => String::from("ConfirmMint"),
             Erc20OpStage::TokenMintConfirmed(_) => String::from("TokenMintConfirmed"),
+            Erc20OpStage::Refund { stage, .. } => format!("Refund{}", stage.name()),
+        }
+    }
+
+    /// Returns `true` if the operation returns tokens to the sender.
+    pub fn is_refund(&self) -> bool {
+        matches!(self, Erc20OpStage::Refund { .. })
+    }
+
+    /// Returns stage of the mint order processed by the operation.
+    /// For refunds it is the stage of the refund mint order.
+    pub fn mint_stage(&self) -> &Erc20OpStage {
+        match self {
+            Erc20OpStage::Refund { stage, .. } => stage.mint_stage(),
+            stage => stage,
+        }
+    }
+}
+
+/// Known behaviour of a base side ERC20 token, which affects how deposits are bridged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, CandidType)]
+pub enum Erc20TokenCapability {
+    /// Transfers move exactly the requested amount, so the deposited amount is trusted.
+    /// Tokens missing in the registry are treated this way.
+    Standard,
+    /// Transfers may deliver less than requested. The received amount is verified
+    /// for every deposit.
+    FeeOnTransfer,
+    /// Balances change without transfers. Deposits are refunded.
+    Rebasing,
+    /// Token is known to be incompatible with the bridge. Deposits are refunded.
+    Unsupported,
+}
+
+impl Erc20TokenCapability {
+    /// Returns `true` if deposits of the token can be bridged.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, Self::Standard | Self::FeeOnTransfer)
+    }
+
+    /// Returns `true` if the amount received by the bridge should be verified.
+    pub fn requires_amount_verification(&self) -> bool {
+        !matches!(self, Self::Standard)
+    }
+}
+
+impl Storable for Erc20TokenCapability {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        codec::encode(self).into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        codec::decode(bytes.as_ref())
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::{Decode, Encode};
+    use did::U256;
+
+    use super::*;
+    use crate::id256::Id256;
+
+    /// Operation as it was stored by the bridge with a single base EVM.
+    #[derive(CandidType)]
+    struct LegacyErc20BridgeOp {
+        side: BridgeSide,
+        stage: Erc20OpStage,
+    }
+
+    fn mint_order(recipient_chain_id: u32) -> MintOrder {
+        MintOrder {
+            amount: U256::from(100u64),
+            sender: Id256::from_evm_address(&H160::from_slice(&[1; 20]), 1),
+            src_token: Id256::from_evm_address(&H160::from_slice(&[2; 20]), 1),
+            recipient: H160::from_slice(&[3; 20]),
+            dst_token: H160::from_slice(&[4; 20]),
+            nonce: 42,
+            sender_chain_id: 1,
+            recipient_chain_id,
+            name: [5; 32],
+            symbol: [6; 16],
+            decimals: 18,
+            approve_spender: H160::default(),
+            approve_amount: U256::default(),
+            fee_payer: H160::default(),
         }
     }
+
+    #[test]
+    fn should_decode_legacy_operation() {
+        let legacy = LegacyErc20BridgeOp {
+            side: BridgeSide::Base,
+            stage: Erc20OpStage::SignMintOrder(mint_order(2)),
+        };
+
+        let op = Decode!(&Encode!(&legacy).unwrap(), Erc20BridgeOp).unwrap();
+
+        assert_eq!(op.side, Some(BridgeSide::Base));
+        assert_eq!(op.chain_id, None);
+        assert_eq!(op.chain_id(), Some(2));
+    }
+
+    #[test]
+    fn should_prefer_stored_chain_id() {
+        let op = Erc20BridgeOp::new(3, Erc20OpStage::SignMintOrder(mint_order(2)));
+        assert_eq!(op.chain_id(), Some(3));
+    }
 }
@@ -2,11 +2,13 @@ mod brc20;
 mod btc;
 mod erc20;
 mod icrc;
+mod ordinals;
 
 pub use brc20::*;
 pub use btc::*;
 pub use erc20::*;
 pub use icrc::*;
+pub use ordinals::*;
 
 #[cfg(feature = "runes")]
 mod rune;
@@ -0,0 +1,79 @@
+use candid::CandidType;
+use did::{H160, H256};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use serde::{Deserialize, Serialize};
+
+use super::DidTransaction;
+use crate::batch_mint_result::BatchMintErrorCode;
+use crate::events::MintedEventData;
+use crate::inscription::InscriptionId;
+use crate::order::{MintOrder, SignedOrders};
+
+/// Request to bridge an inscription sent to the deposit address of `dst_address`.
+///
+/// The inscription is minted as a token of the ERC-721 collection the bridge is linked to.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct InscriptionDepositRequest {
+    pub inscription_id: InscriptionId,
+    pub dst_address: H160,
+}
+
+/// Ordinals bridge operations
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub enum OrdinalsBridgeOp {
+    /// Deposit operations
+    Deposit(OrdinalsBridgeDepositOp),
+    /// Withdraw operations
+    Withdraw(OrdinalsBridgeWithdrawOp),
+}
+
+/// Ordinals bridge deposit operations
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub enum OrdinalsBridgeDepositOp {
+    /// Await for deposit inputs
+    AwaitInputs(InscriptionDepositRequest),
+    /// Await for minimum IC confirmations
+    AwaitConfirmations {
+        deposit: InscriptionDepositRequest,
+        utxos: Vec<Utxo>,
+    },
+    /// Sign the provided mint order
+    SignMintOrder(MintOrder),
+    /// Send the signed mint order to the bridge
+    SendMintOrder(SignedOrders),
+    /// Wait for the mint order to be confirmed
+    WaitForMintConfirm {
+        orders: SignedOrders,
+        mint_result: Vec<BatchMintErrorCode>,
+        tx_id: Option<H256>,
+    },
+    /// Mint order confirmed status
+    MintOrderConfirmed { data: MintedEventData },
+}
+
+/// Ordinals bridge withdraw operations
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub enum OrdinalsBridgeWithdrawOp {
+    /// Create the transaction sending the inscription to the recipient
+    CreateTransferTx(InscriptionWithdrawalPayload),
+    /// Send the transfer transaction
+    SendTransferTx {
+        payload: InscriptionWithdrawalPayload,
+        tx: DidTransaction,
+    },
+    /// Await confirmations of the sent transfer transaction
+    AwaitTransferTxConfirmation {
+        payload: InscriptionWithdrawalPayload,
+        tx: DidTransaction,
+    },
+    /// Transfer transaction confirmed
+    TransferTxConfirmed { from_address: H160, txid: H256 },
+}
+
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize)]
+pub struct InscriptionWithdrawalPayload {
+    pub inscription_id: InscriptionId,
+    pub request_ts: u64,
+    pub sender: H160,
+    pub dst_address: String,
+}
@@ -7,8 +7,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::batch_mint_result::BatchMintErrorCode;
 use crate::events::MintedEventData;
+use crate::op_id::OperationId;
 use crate::order::{MintOrder, SignedOrders};
-use crate::runes::{DidTransaction, RuneName, RuneToWrap, RuneWithdrawalPayload};
+use crate::runes::{
+    DidTransaction, DidTxInput, RuneInfo, RuneName, RuneRefund, RuneToWrap, RuneWithdrawalPayload,
+};
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone, PartialEq, Eq)]
 pub enum RuneBridgeDepositOp {
@@ -17,12 +20,30 @@ pub enum RuneBridgeDepositOp {
         dst_address: H160,
         dst_tokens: HashMap<RuneName, H160>,
         requested_amounts: Option<HashMap<RuneName, u128>>,
+        /// Deposit fee in sats quoted when the deposit was requested. `None` for the deposits
+        /// requested before the fee was locked into the operation.
+        deposit_fee: Option<u64>,
+        /// Bitcoin address the deposited runes without a wrapped token are refunded to. If
+        /// `None`, the deposit of such runes fails.
+        refund_address: Option<String>,
     },
     /// Await confirmations for the deposit
     AwaitConfirmations {
         dst_address: H160,
         utxo: Utxo,
         runes_to_wrap: Vec<RuneToWrap>,
+        /// Deposit fee in sats locked when the deposit was requested.
+        deposit_fee: Option<u64>,
+        /// Runes of the utxo without a wrapped token to be refunded to the user.
+        refund: Option<RuneRefund>,
+    },
+    /// Send the runes of the deposited utxo without a wrapped token back to the user. The
+    /// wrapped runes of the utxo are kept by the bridge.
+    RefundRunes {
+        dst_address: H160,
+        utxo: Utxo,
+        refund: RuneRefund,
+        wrapped_runes: Vec<RuneInfo>,
     },
     /// Sign the mint order
     SignMintOrder(MintOrder),
@@ -42,16 +63,75 @@ pub enum RuneBridgeDepositOp {
 pub enum RuneBridgeWithdrawOp {
     /// Create a withdrawal transaction
     CreateTransaction { payload: RuneWithdrawalPayload },
+    /// Await the co-signatures of the withdrawal transaction. The transaction is exported as a
+    /// PSBT, and it is signed by the canister once the PSBT co-signed by the external keys is
+    /// submitted.
+    AwaitCosignatures {
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+    },
     /// Send the withdrawal transaction
     SendTransaction {
         from_address: H160,
         transaction: DidTransaction,
+        /// Inputs of the transaction. `None` for the operations created before the confirmation
+        /// tracking was introduced; such transactions are not tracked after being sent.
+        inputs: Option<Vec<DidTxInput>>,
     },
-    /// The withdrawal transaction has been sent
+    /// Await confirmations of the sent withdrawal transaction. If it is not confirmed in time,
+    /// it gets replaced by a transaction paying a higher fee.
+    AwaitTransactionConfirmation {
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+        /// Timestamp when the transaction was sent (in nanoseconds)
+        sent_at: u64,
+        /// Ids of the transactions replaced by this one
+        replaced_txids: Vec<H256>,
+        /// Fee of the child transaction paying for this one (CPFP), if it was sent
+        child_fee: Option<u64>,
+    },
+    /// The withdrawal transaction has been sent. Operations created before the confirmation
+    /// tracking was introduced end up in this state.
     TransactionSent {
         from_address: H160,
         transaction: DidTransaction,
     },
+    /// The withdrawal transaction has been confirmed
+    TransactionConfirmed { from_address: H160, txid: H256 },
+    /// The withdrawal has been sent in a transaction shared with other withdrawals. Operations
+    /// created before the confirmation tracking of the batch transactions was introduced end up
+    /// in this state.
+    BatchTransactionSent {
+        from_address: H160,
+        txid: H256,
+        /// Part of the transaction fee paid by this withdrawal (in satoshi)
+        fee_share: u64,
+    },
+    /// Await confirmations of the transaction shared with other withdrawals. If it is not
+    /// confirmed in time, the first withdrawal of the batch bumps its fee for all of them.
+    AwaitBatchTransactionConfirmation {
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+        /// Part of the transaction fee paid by this withdrawal (in satoshi)
+        fee_share: u64,
+        /// Ids of all the withdrawal operations of the batch
+        batch_operations: Vec<OperationId>,
+        /// Timestamp when the transaction was sent (in nanoseconds)
+        sent_at: u64,
+        /// Ids of the transactions replaced by this one
+        replaced_txids: Vec<H256>,
+        /// Fee of the child transaction paying for this one (CPFP), if it was sent
+        child_fee: Option<u64>,
+    },
+    /// Await confirmations of the transaction merging the utxos of the bridge, sent by the utxo
+    /// consolidation or the taproot migration.
+    AwaitUtxoMergeConfirmation {
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone, PartialEq, Eq)]
@@ -60,3 +140,47 @@ pub enum RuneBridgeOp {
     Deposit(RuneBridgeDepositOp),
     Withdraw(RuneBridgeWithdrawOp),
 }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Transaction, TxIn};
+    use candid::{Decode, Encode};
+
+    use super::*;
+
+    /// Withdrawal operation as it was stored before the confirmation tracking was introduced.
+    #[derive(CandidType)]
+    enum LegacyRuneBridgeWithdrawOp {
+        SendTransaction {
+            from_address: H160,
+            transaction: DidTransaction,
+        },
+    }
+
+    #[test]
+    fn should_decode_legacy_send_transaction_operation() {
+        let transaction = DidTransaction::from(Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![],
+        });
+        let legacy = LegacyRuneBridgeWithdrawOp::SendTransaction {
+            from_address: H160::from_slice(&[1; 20]),
+            transaction: transaction.clone(),
+        };
+
+        let op = Decode!(&Encode!(&legacy).unwrap(), RuneBridgeWithdrawOp).unwrap();
+
+        assert_eq!(
+            op,
+            RuneBridgeWithdrawOp::SendTransaction {
+                from_address: H160::from_slice(&[1; 20]),
+                transaction,
+                inputs: None,
+            }
+        );
+    }
+}
@@ -460,6 +460,11 @@ impl SignedOrders {
             .expect("index should be less than orders number")
     }
 
+    /// Decodes the order data.
+    pub fn decode_order(&self) -> Option<MintOrder> {
+        MintOrder::decode_data(self.reader().0)
+    }
+
     /// Borrows all orders.
     pub fn all_orders(&self) -> &SignedOrdersData {
         &self.all_orders
@@ -1,7 +1,9 @@
+mod evm_token;
 mod rune_info;
 mod transaction;
 mod withdrawal;
 
+pub use evm_token::*;
 use ordinals::RuneId;
 pub use rune_info::*;
 pub use transaction::*;
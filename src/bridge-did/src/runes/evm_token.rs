@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Encode};
+use did::{H160, H256};
+use ic_stable_structures::{Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use super::rune_info::RuneInfo;
+
+/// Maximum divisibility of a rune.
+pub const MAX_RUNE_DIVISIBILITY: u8 = 38;
+
+/// Request to etch a rune representing an EVM-native ERC20 token.
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EvmTokenEtchingRequest {
+    /// Address of the ERC20 token.
+    pub erc20: H160,
+    /// Divisibility of the rune. Must be equal to the decimals of the ERC20 token, so the
+    /// amounts are bridged one to one.
+    pub divisibility: u8,
+    /// Currency symbol of the rune, a single character.
+    pub symbol: Option<String>,
+    /// Amount of the runes premined to the bridge. Runes are issued on Bitcoin only from the
+    /// premine, so it limits the amount of the ERC20 tokens which can be bridged.
+    pub premine: u128,
+}
+
+impl EvmTokenEtchingRequest {
+    /// Validates the etching parameters.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.erc20 == H160::default() {
+            return Err("ERC20 address must not be zero".to_string());
+        }
+
+        if self.divisibility > MAX_RUNE_DIVISIBILITY {
+            return Err(format!(
+                "divisibility must not be greater than {MAX_RUNE_DIVISIBILITY}"
+            ));
+        }
+
+        if self.premine == 0 {
+            return Err("premine must not be zero".to_string());
+        }
+
+        if self
+            .symbol
+            .as_ref()
+            .is_some_and(|symbol| symbol.chars().count() != 1)
+        {
+            return Err("symbol must be a single character".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Currency symbol of the rune.
+    pub fn symbol_char(&self) -> Option<char> {
+        self.symbol
+            .as_ref()
+            .and_then(|symbol| symbol.chars().next())
+    }
+}
+
+/// EVM-native ERC20 token represented on Bitcoin by a rune etched by the bridge.
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EvmNativeToken {
+    pub erc20: H160,
+    pub divisibility: u8,
+    pub premine: u128,
+    pub status: EvmNativeTokenStatus,
+}
+
+impl EvmNativeToken {
+    /// Information about the etched rune, if the etching is confirmed.
+    pub fn rune_info(&self) -> Option<RuneInfo> {
+        match self.status {
+            EvmNativeTokenStatus::Etching { .. } => None,
+            EvmNativeTokenStatus::Etched(rune_info) => Some(rune_info),
+        }
+    }
+}
+
+impl Storable for EvmNativeToken {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode evm native token"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode evm native token")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EvmNativeTokenStatus {
+    /// The etching transaction is sent, but the rune is not known to the indexers yet.
+    Etching {
+        /// Id of the etching transaction. The premined runes are in its output 1.
+        txid: H256,
+    },
+    /// The rune is etched and the premine is held by the bridge.
+    Etched(RuneInfo),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> EvmTokenEtchingRequest {
+        EvmTokenEtchingRequest {
+            erc20: H160::from_slice(&[1; 20]),
+            divisibility: 18,
+            symbol: Some("$".to_string()),
+            premine: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn should_validate_etching_request() {
+        assert!(request().validate().is_ok());
+        assert_eq!(request().symbol_char(), Some('$'));
+
+        let invalid = [
+            EvmTokenEtchingRequest {
+                erc20: H160::default(),
+                ..request()
+            },
+            EvmTokenEtchingRequest {
+                divisibility: 39,
+                ..request()
+            },
+            EvmTokenEtchingRequest {
+                premine: 0,
+                ..request()
+            },
+            EvmTokenEtchingRequest {
+                symbol: Some("AB".to_string()),
+                ..request()
+            },
+        ];
+        for request in invalid {
+            assert!(request.validate().is_err(), "{request:?}");
+        }
+    }
+
+    #[test]
+    fn should_encode_decode_evm_native_token() {
+        let token = EvmNativeToken {
+            erc20: H160::from_slice(&[1; 20]),
+            divisibility: 18,
+            premine: 1_000_000,
+            status: EvmNativeTokenStatus::Etching {
+                txid: H256::from_slice(&[2; 32]),
+            },
+        };
+
+        assert_eq!(EvmNativeToken::from_bytes(token.to_bytes()), token);
+        assert_eq!(token.rune_info(), None);
+    }
+}
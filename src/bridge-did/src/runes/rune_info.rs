@@ -175,6 +175,21 @@ pub struct RuneToWrap {
     pub wrapped_address: H160,
 }
 
+/// Rune of a deposited utxo without a wrapped token, which is sent back to the user.
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuneToRefund {
+    pub rune_info: RuneInfo,
+    pub amount: u128,
+}
+
+/// Refund of the deposited runes without a wrapped token.
+#[derive(Debug, Clone, CandidType, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuneRefund {
+    /// Bitcoin address receiving the refunded runes.
+    pub address: String,
+    pub runes: Vec<RuneToRefund>,
+}
+
 #[cfg(test)]
 mod test {
 
@@ -61,3 +61,16 @@ impl From<DidTransaction> for Transaction {
         value.0
     }
 }
+
+/// Input of a withdrawal transaction with the data required to sign it again, e.g. to replace
+/// the transaction with one paying a higher fee.
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
+pub struct DidTxInput {
+    pub txid: [u8; 32],
+    pub vout: u32,
+    /// Value of the spent output in satoshi.
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+    /// Derivation path of the key owning the spent output, in IC format.
+    pub derivation_path: Vec<Vec<u8>>,
+}
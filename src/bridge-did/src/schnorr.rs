@@ -29,6 +29,16 @@ pub struct ManagementCanisterSignatureRequest {
     pub message: Vec<u8>,
     pub derivation_path: Vec<Vec<u8>>,
     pub key_id: SchnorrKeyId,
+    pub aux: Option<SignWithSchnorrAux>,
+}
+
+/// Auxiliary parameters of a schnorr signature request.
+#[derive(CandidType, Serialize, Debug, Clone)]
+pub enum SignWithSchnorrAux {
+    /// Signs with the key tweaked as specified in BIP-341, e.g. to spend a taproot output by
+    /// the key path. The merkle root hash is empty for outputs without a script tree (BIP-86).
+    #[serde(rename = "bip341")]
+    Bip341 { merkle_root_hash: Vec<u8> },
 }
 
 #[derive(CandidType, Deserialize, Debug)]
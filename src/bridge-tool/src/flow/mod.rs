@@ -49,11 +49,15 @@ pub struct WithdrawToken {
 #[derive(Debug, Subcommand)]
 pub enum DepositTokenType {
     Erc20(DepositErc20Args),
+    /// Deposit native tokens of the base EVM
+    Native(DepositNativeArgs),
 }
 
 #[derive(Debug, Subcommand)]
 pub enum WithdrawTokenType {
     Erc20(WithdrawErc20Args),
+    /// Withdraw native tokens of the base EVM
+    Native(WithdrawNativeArgs),
 }
 
 #[derive(Debug, Args)]
@@ -142,6 +146,123 @@ pub struct WithdrawErc20Args {
     recipient: Option<Address>,
 }
 
+#[derive(Debug, Args)]
+pub struct DepositNativeArgs {
+    /// Base side EVM (localhost, testnet, mainnet or http address)
+    #[arg(long)]
+    base_evm: String,
+
+    /// Base side BTF bridge contract address
+    #[arg(long)]
+    base_btf: Address,
+
+    /// Wrapped side EVM (localhost, testnet, mainnet or http address)
+    #[clap(long)]
+    wrapped_evm: String,
+
+    /// Wrapped side BTF bridge contract address
+    #[arg(long)]
+    wrapped_btf: Address,
+
+    /// Wrapped native token address
+    #[arg(long)]
+    wrapped_token: Address,
+
+    /// HTTP address of the IC connection to be used
+    #[arg(long)]
+    ic_host: String,
+
+    /// Principal of the bridge canister
+    #[arg(long)]
+    bridge_canister: Principal,
+
+    /// Amount of native tokens to be transferred
+    #[arg(long)]
+    amount: u128,
+
+    /// Recipient of the wrapped tokens (if no set, caller wallet will be used)
+    #[arg(short, long)]
+    recipient: Option<Address>,
+}
+
+impl From<&DepositNativeArgs> for DepositErc20Args {
+    fn from(args: &DepositNativeArgs) -> Self {
+        Self {
+            base_evm: args.base_evm.clone(),
+            base_btf: args.base_btf,
+            base_token: native_token_address(),
+            wrapped_evm: args.wrapped_evm.clone(),
+            wrapped_btf: args.wrapped_btf,
+            wrapped_token: args.wrapped_token,
+            ic_host: args.ic_host.clone(),
+            bridge_canister: args.bridge_canister,
+            amount: args.amount,
+            recipient: args.recipient,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct WithdrawNativeArgs {
+    /// Base side EVM (localhost, testnet, mainnet or http address)
+    #[arg(long)]
+    base_evm: String,
+
+    /// Base side BTF bridge contract address
+    #[arg(long)]
+    base_btf: Address,
+
+    /// Wrapped side EVM (localhost, testnet, mainnet or http address)
+    #[clap(long)]
+    wrapped_evm: String,
+
+    /// Wrapped side BTF bridge contract address
+    #[arg(long)]
+    wrapped_btf: Address,
+
+    /// Wrapped native token address
+    #[arg(long)]
+    wrapped_token: Address,
+
+    /// HTTP address of the IC connection to be used
+    #[arg(long)]
+    ic_host: String,
+
+    /// Principal of the bridge canister
+    #[arg(long)]
+    bridge_canister: Principal,
+
+    /// Amount of wrapped tokens to be transferred
+    #[arg(long)]
+    amount: u128,
+
+    /// Recipient of the native tokens (if no set, caller wallet will be used)
+    #[arg(short, long)]
+    recipient: Option<Address>,
+}
+
+impl From<&WithdrawNativeArgs> for WithdrawErc20Args {
+    fn from(args: &WithdrawNativeArgs) -> Self {
+        Self {
+            base_evm: args.base_evm.clone(),
+            base_btf: args.base_btf,
+            base_token: native_token_address(),
+            wrapped_evm: args.wrapped_evm.clone(),
+            wrapped_btf: args.wrapped_btf,
+            wrapped_token: args.wrapped_token,
+            ic_host: args.ic_host.clone(),
+            bridge_canister: args.bridge_canister,
+            amount: args.amount,
+            recipient: args.recipient,
+        }
+    }
+}
+
+/// Address used by the BTF bridge in place of a token address for the native tokens.
+fn native_token_address() -> Address {
+    Id256::native_address().0
+}
+
 type RpcClient = EthJsonRpcClient<ReqwestClient>;
 
 struct Erc20BridgeFlow {
@@ -230,6 +351,11 @@ impl DepositToken {
                 let flow = Erc20BridgeFlow::new_deposit(self.private_key, erc20args);
                 flow.deposit(erc20args.amount, erc20args.recipient).await
             }
+            DepositTokenType::Native(native_args) => {
+                let args = DepositErc20Args::from(native_args);
+                let flow = Erc20BridgeFlow::new_deposit(self.private_key, &args);
+                flow.deposit(args.amount, args.recipient).await
+            }
         }
     }
 }
@@ -241,6 +367,11 @@ impl WithdrawToken {
                 let flow = Erc20BridgeFlow::new_withdraw(self.private_key, args);
                 flow.withdraw(args.amount, args.recipient).await
             }
+            WithdrawTokenType::Native(native_args) => {
+                let args = WithdrawErc20Args::from(native_args);
+                let flow = Erc20BridgeFlow::new_withdraw(self.private_key, &args);
+                flow.withdraw(args.amount, args.recipient).await
+            }
         }
     }
 }
@@ -288,7 +419,10 @@ impl Erc20BridgeFlow {
         let recipient = recipient.unwrap_or_else(|| self.wallet.address());
         let memo = Self::generate_memo();
 
-        self.approve_erc20(amount, side).await?;
+        let (_, _, from_token) = self.get_side(side);
+        if *from_token != native_token_address() {
+            self.approve_erc20(amount, side).await?;
+        }
         self.approve_fee(side.other(), FEE_APPROVE_AMOUNT).await?;
         self.burn_btf(side, amount, &recipient, memo).await?;
         self.track_operation(memo, side.other()).await
@@ -435,6 +569,14 @@ impl Erc20BridgeFlow {
                 curr_step = operation_log.current_step().clone();
             }
 
+            if let Erc20OpStage::Refund { stage, reason, .. } = &curr_step.stage {
+                if matches!(**stage, Erc20OpStage::TokenMintConfirmed(_)) {
+                    return Err(anyhow!(
+                        "Operation {operation_id} failed with {reason}; tokens are refunded to the sender"
+                    ));
+                }
+            }
+
             if matches!(curr_step.stage, Erc20OpStage::TokenMintConfirmed(_)) {
                 info!("Operation {operation_id} is completed successfully");
                 return Ok(());
@@ -447,7 +589,7 @@ impl Erc20BridgeFlow {
         if let Erc20OpStage::WaitForMintConfirm {
             tx_hash: Some(tx_hash),
             ..
-        } = curr_step.stage
+        } = curr_step.stage.mint_stage()
         {
             let tx_result = Self::wait_for_tx(evm_client, tx_hash.clone().into()).await;
             error!(
@@ -565,14 +707,26 @@ impl Erc20BridgeFlow {
         let recipient = recipient_id.0;
 
         let amount: U256 = amount.into();
-        let input = BTFBridge::burnCall {
-            amount: amount.into(),
-            fromERC20: from_token.0.into(),
-            toTokenID: alloy_sol_types::private::FixedBytes::from_slice(&to_token_id.0),
-            recipientID: alloy_sol_types::private::Bytes::copy_from_slice(&recipient),
-            memo: alloy_sol_types::private::FixedBytes(memo),
-        }
-        .abi_encode();
+        // Native tokens are deposited with the transaction value instead of ERC20 transfer.
+        let (input, value) = if *from_token == native_token_address() {
+            let input = BTFBridge::depositNativeCall {
+                toTokenID: alloy_sol_types::private::FixedBytes::from_slice(&to_token_id.0),
+                recipientID: alloy_sol_types::private::Bytes::copy_from_slice(&recipient),
+                memo: alloy_sol_types::private::FixedBytes(memo),
+            }
+            .abi_encode();
+            (input, amount)
+        } else {
+            let input = BTFBridge::burnCall {
+                amount: amount.into(),
+                fromERC20: from_token.0.into(),
+                toTokenID: alloy_sol_types::private::FixedBytes::from_slice(&to_token_id.0),
+                recipientID: alloy_sol_types::private::Bytes::copy_from_slice(&recipient),
+                memo: alloy_sol_types::private::FixedBytes(memo),
+            }
+            .abi_encode();
+            (input, 0u64.into())
+        };
 
         let nonce = client
             .get_transaction_count(self.wallet.address().into(), BlockNumber::Latest)
@@ -582,7 +736,7 @@ impl Erc20BridgeFlow {
             from: &self.wallet.address().into(),
             to: Some((*btf).into()),
             nonce: nonce.into(),
-            value: 0u64.into(),
+            value,
             gas: 5_000_000u64.into(),
             gas_price: (EIP1559_INITIAL_BASE_FEE * 2).into(),
             input,
@@ -136,7 +136,13 @@ impl TryFrom<Log> for BridgeEvent {
         let log = value.data();
 
         let event = BurnTokenEvent::decode_log_data(log, true)
-            .map(|event| Self::Burnt(event.into()))
+            .map(|event| {
+                let mut data = BurntEventData::from(event);
+                data.tx_hash = value.transaction_hash.map(did::H256);
+                data.block_number = value.block_number;
+                data.log_index = value.log_index;
+                Self::Burnt(data)
+            })
             .or_else(|_| {
                 MintTokenEvent::decode_log_data(log, true).map(|event| Self::Minted(event.into()))
             })
@@ -185,6 +191,58 @@ pub fn batch_mint_transaction(
     }
 }
 
+/// Sends transaction with given params to call `cancelMintOrder` function
+/// in Btfbridge contract.
+pub fn cancel_mint_order_transaction(
+    params: TxParams,
+    sender_id: [u8; 32],
+    nonce: u32,
+) -> TxLegacy {
+    let data = BTFBridge::cancelMintOrderCall {
+        senderID: sender_id.into(),
+        nonce,
+    }
+    .abi_encode();
+
+    TxLegacy {
+        chain_id: Some(params.chain_id),
+        nonce: params.nonce,
+        gas_price: params.gas_price.to(),
+        gas_limit: DEFAULT_TX_GAS_LIMIT,
+        to: TxKind::Call(params.bridge),
+        value: U256::ZERO,
+        input: data.into(),
+    }
+}
+
+/// Sends transaction with given params to call `updateWrappedTokenMetadata` function
+/// in Btfbridge contract.
+pub fn update_wrapped_token_metadata_transaction(
+    params: TxParams,
+    base_token_id: [u8; 32],
+    name: [u8; 32],
+    symbol: [u8; 16],
+    decimals: u8,
+) -> TxLegacy {
+    let data = BTFBridge::updateWrappedTokenMetadataCall {
+        baseTokenID: base_token_id.into(),
+        name: name.into(),
+        symbol: symbol.into(),
+        decimals,
+    }
+    .abi_encode();
+
+    TxLegacy {
+        chain_id: Some(params.chain_id),
+        nonce: params.nonce,
+        gas_price: params.gas_price.to(),
+        gas_limit: DEFAULT_TX_GAS_LIMIT,
+        to: TxKind::Call(params.bridge),
+        value: U256::ZERO,
+        input: data.into(),
+    }
+}
+
 /// Parse the output (slice of [`u8`]) of the `batchMint` function call to a [`Vec`] of [`BatchMintResult`].
 pub fn batch_mint_result(output: &[u8]) -> Result<Vec<BatchMintErrorCode>, BatchMintResultError> {
     let output = BTFBridge::batchMintCall::abi_decode_returns(output, true)?;
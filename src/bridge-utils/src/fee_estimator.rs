@@ -0,0 +1,238 @@
+use std::future::Future;
+
+use bitcoin::FeeRate;
+use bridge_did::init::{FeeEstimatorConfig, FeePriority, FeeRateSource, PercentileTargets};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{
+    BitcoinNetwork, GetCurrentFeePercentilesRequest, bitcoin_get_current_fee_percentiles,
+};
+use ic_exports::ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod, http_request,
+};
+use serde::Deserialize;
+
+use crate::http::{http_request_cycles, indexer_transform_context};
+
+/// Fee rate used in regtest, where the IC doesn't collect fee percentiles (millisatoshi/vbyte).
+const DEFAULT_REGTEST_FEE: u64 = 100_000 * 1_000;
+
+const MAX_RESPONSE_BYTES: u64 = 1_000;
+
+/// Path of the mempool.space compatible fee recommendations endpoint.
+const RECOMMENDED_FEES_PATH: &str = "api/v1/fees/recommended";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FeeEstimationError {
+    #[error("fee rate source is unavailable: {0}")]
+    Unavailable(String),
+    #[error("{responses} fee rate sources responded, but {required} are required")]
+    NoConsensus { responses: usize, required: usize },
+    #[error("invalid fee rate {0} sat/vB")]
+    InvalidFeeRate(u64),
+}
+
+/// Estimates the fee rate of Bitcoin transactions.
+pub trait FeeEstimator {
+    /// Returns the fee rate for a transaction to be confirmed with the given priority.
+    fn estimate_fee_rate(
+        &self,
+        priority: FeePriority,
+    ) -> impl Future<Output = Result<FeeRate, FeeEstimationError>>;
+}
+
+/// Fee estimator requesting the configured sources with the IC Bitcoin API and HTTP outcalls.
+pub struct IcFeeEstimator {
+    network: BitcoinNetwork,
+    config: FeeEstimatorConfig,
+}
+
+impl IcFeeEstimator {
+    pub fn new(network: BitcoinNetwork, config: FeeEstimatorConfig) -> Self {
+        Self { network, config }
+    }
+
+    async fn request_fee_rate(
+        &self,
+        source: &FeeRateSource,
+        priority: FeePriority,
+    ) -> Result<u64, FeeEstimationError> {
+        match source {
+            FeeRateSource::IcPercentiles(targets) => {
+                self.request_percentile_fee_rate(targets, priority).await
+            }
+            FeeRateSource::HttpOracle { url } => request_oracle_fee_rate(url, priority).await,
+        }
+    }
+
+    async fn request_percentile_fee_rate(
+        &self,
+        targets: &PercentileTargets,
+        priority: FeePriority,
+    ) -> Result<u64, FeeEstimationError> {
+        if self.network == BitcoinNetwork::Regtest {
+            return Ok(DEFAULT_REGTEST_FEE / 1000);
+        }
+
+        let args = GetCurrentFeePercentilesRequest {
+            network: self.network,
+        };
+        let response = bitcoin_get_current_fee_percentiles(args)
+            .await
+            .map_err(|err| FeeEstimationError::Unavailable(format!("{err:?}")))?
+            .0;
+
+        log::trace!("Received fee rate percentiles: {response:?}");
+
+        percentile_fee_rate(&response, targets.percentile(priority))
+            .ok_or_else(|| FeeEstimationError::Unavailable("empty fee percentiles".to_string()))
+    }
+}
+
+impl FeeEstimator for IcFeeEstimator {
+    async fn estimate_fee_rate(
+        &self,
+        priority: FeePriority,
+    ) -> Result<FeeRate, FeeEstimationError> {
+        let mut fee_rates = Vec::with_capacity(self.config.sources.len());
+        for source in &self.config.sources {
+            match self.request_fee_rate(source, priority).await {
+                Ok(fee_rate) => fee_rates.push(fee_rate),
+                Err(err) => log::warn!("Failed to get fee rate from {source:?}: {err}"),
+            }
+        }
+
+        let fee_rate = median_fee_rate(fee_rates, self.config.consensus_threshold as usize)?;
+        let fee_rate = clamp_fee_rate(fee_rate, self.config.min_fee_rate, self.config.max_fee_rate);
+
+        log::info!("Using fee rate {fee_rate} sat/vB for {priority:?} priority");
+
+        FeeRate::from_sat_per_vb(fee_rate).ok_or(FeeEstimationError::InvalidFeeRate(fee_rate))
+    }
+}
+
+/// Response of the mempool.space compatible fee recommendations endpoint (sat/vB).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecommendedFees {
+    fastest_fee: f64,
+    half_hour_fee: f64,
+    hour_fee: f64,
+}
+
+impl RecommendedFees {
+    fn fee_rate(&self, priority: FeePriority) -> u64 {
+        let fee_rate = match priority {
+            FeePriority::High => self.fastest_fee,
+            FeePriority::Medium => self.half_hour_fee,
+            FeePriority::Low => self.hour_fee,
+        };
+
+        fee_rate.ceil() as u64
+    }
+}
+
+async fn request_oracle_fee_rate(
+    url: &str,
+    priority: FeePriority,
+) -> Result<u64, FeeEstimationError> {
+    let url = format!("{}/{RECOMMENDED_FEES_PATH}", url.trim_end_matches('/'));
+
+    log::trace!("Sending fee rate request to: {url}");
+
+    let request_params = CanisterHttpRequestArgument {
+        url,
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method: HttpMethod::GET,
+        headers: vec![HttpHeader {
+            name: "Accept".to_string(),
+            value: "application/json".to_string(),
+        }],
+        body: None,
+        transform: Some(indexer_transform_context()),
+    };
+
+    let response = http_request(request_params, http_request_cycles(MAX_RESPONSE_BYTES))
+        .await
+        .map_err(|err| FeeEstimationError::Unavailable(format!("{err:?}")))?
+        .0;
+
+    let fees: RecommendedFees = serde_json::from_slice(&response.body).map_err(|err| {
+        FeeEstimationError::Unavailable(format!("unexpected fee oracle response: {err}"))
+    })?;
+
+    Ok(fees.fee_rate(priority))
+}
+
+/// Fee rate in sat/vB at the given percentile of the IC fee percentiles (millisatoshi/vbyte).
+fn percentile_fee_rate(percentiles: &[u64], percentile: u8) -> Option<u64> {
+    let last = percentiles.len().checked_sub(1)?;
+    let index = (percentiles.len() * percentile as usize / 100).min(last);
+
+    Some(percentiles[index] / 1000)
+}
+
+/// Median of the fee rates, if at least `threshold` of them are given.
+fn median_fee_rate(mut fee_rates: Vec<u64>, threshold: usize) -> Result<u64, FeeEstimationError> {
+    if fee_rates.is_empty() || fee_rates.len() < threshold {
+        return Err(FeeEstimationError::NoConsensus {
+            responses: fee_rates.len(),
+            required: threshold.max(1),
+        });
+    }
+
+    fee_rates.sort_unstable();
+    Ok(fee_rates[fee_rates.len() / 2])
+}
+
+fn clamp_fee_rate(fee_rate: u64, min: Option<u64>, max: Option<u64>) -> u64 {
+    let fee_rate = min.map_or(fee_rate, |min| fee_rate.max(min));
+    max.map_or(fee_rate, |max| fee_rate.min(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_select_percentile() {
+        let percentiles: Vec<u64> = (0..100).map(|i| i * 1000).collect();
+
+        assert_eq!(percentile_fee_rate(&percentiles, 50), Some(50));
+        assert_eq!(percentile_fee_rate(&percentiles, 0), Some(0));
+        assert_eq!(percentile_fee_rate(&percentiles, 100), Some(99));
+        assert_eq!(percentile_fee_rate(&[], 50), None);
+    }
+
+    #[test]
+    fn test_should_take_median_of_fee_rates() {
+        assert_eq!(median_fee_rate(vec![30, 10, 20], 2), Ok(20));
+        assert_eq!(median_fee_rate(vec![10], 1), Ok(10));
+        assert_eq!(
+            median_fee_rate(vec![10], 2),
+            Err(FeeEstimationError::NoConsensus {
+                responses: 1,
+                required: 2
+            })
+        );
+        assert!(median_fee_rate(vec![], 0).is_err());
+    }
+
+    #[test]
+    fn test_should_clamp_fee_rate() {
+        assert_eq!(clamp_fee_rate(5, Some(10), None), 10);
+        assert_eq!(clamp_fee_rate(500, None, Some(100)), 100);
+        assert_eq!(clamp_fee_rate(50, Some(10), Some(100)), 50);
+        assert_eq!(clamp_fee_rate(50, None, None), 50);
+    }
+
+    #[test]
+    fn test_should_parse_recommended_fees() {
+        let fees: RecommendedFees = serde_json::from_str(
+            r#"{"fastestFee":12,"halfHourFee":8.5,"hourFee":5,"economyFee":3,"minimumFee":1}"#,
+        )
+        .unwrap();
+
+        assert_eq!(fees.fee_rate(FeePriority::High), 12);
+        assert_eq!(fees.fee_rate(FeePriority::Medium), 9);
+        assert_eq!(fees.fee_rate(FeePriority::Low), 5);
+    }
+}
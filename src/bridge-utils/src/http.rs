@@ -0,0 +1,68 @@
+use ic_exports::ic_cdk::api::management_canister::http_request::{
+    HttpResponse, TransformArgs, TransformContext,
+};
+
+/// Cycles attached to every HTTP outcall in addition to the response size dependent part.
+const CYCLES_PER_HTTP_REQUEST: u128 = 500_000_000;
+
+/// Cycles attached for each byte of the maximum response size. The amount covers the outcall
+/// price on the largest subnets.
+const CYCLES_PER_RESPONSE_BYTE: u128 = 30_000;
+
+/// Name of the canister query method transforming the indexer responses.
+///
+/// Canisters making indexer or fee oracle requests must expose a query with this name, that
+/// calls [`transform_indexer_response`].
+pub const TRANSFORM_INDEXER_RESPONSE_METHOD: &str = "transform_indexer_response";
+
+/// Transform context of the indexer HTTP outcalls.
+pub fn indexer_transform_context() -> TransformContext {
+    TransformContext::from_name(TRANSFORM_INDEXER_RESPONSE_METHOD.to_string(), vec![])
+}
+
+/// Cycles to attach to an HTTP outcall with the given maximum response size.
+///
+/// The cycles not used by the outcall are refunded to the canister.
+pub fn http_request_cycles(max_response_bytes: u64) -> u128 {
+    CYCLES_PER_HTTP_REQUEST + CYCLES_PER_RESPONSE_BYTE * max_response_bytes as u128
+}
+
+/// Removes the headers of an indexer response.
+///
+/// Headers like `Date` or rate limiting counters differ between the responses received by the
+/// subnet replicas, which would prevent them from reaching consensus on the outcall result.
+pub fn transform_indexer_response(args: TransformArgs) -> HttpResponse {
+    HttpResponse {
+        status: args.response.status,
+        headers: vec![],
+        body: args.response.body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::http_request::HttpHeader;
+
+    use super::*;
+
+    #[test]
+    fn test_should_strip_response_headers() {
+        let response = HttpResponse {
+            status: 200u16.into(),
+            headers: vec![HttpHeader {
+                name: "Date".to_string(),
+                value: "Sun, 18 Oct 2026 10:00:00 GMT".to_string(),
+            }],
+            body: b"{}".to_vec(),
+        };
+
+        let transformed = transform_indexer_response(TransformArgs {
+            response: response.clone(),
+            context: vec![],
+        });
+
+        assert_eq!(transformed.status, response.status);
+        assert!(transformed.headers.is_empty());
+        assert_eq!(transformed.body, response.body);
+    }
+}
@@ -4,8 +4,11 @@ pub mod btf_events;
 pub mod common;
 pub mod evm_bridge;
 pub mod evm_link;
+pub mod fee_estimator;
+pub mod http;
 pub mod query;
 pub mod revert;
+pub mod token_transfers;
 
 pub use self::address::get_contract_address;
 
@@ -0,0 +1,230 @@
+use alloy::core::primitives::{Address, B256, U256};
+use alloy::rpc::types::Log;
+use alloy_sol_types::SolEvent;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::event_data::BurnTokenEvent;
+use ethereum_json_rpc_client::{Client, EthGetLogsParams, EthJsonRpcClient};
+
+use crate::WrappedToken;
+
+/// Returns the amount of `token` which the `bridge` contract actually received for the
+/// burn event with the `burn_log_index` log index in the transaction `tx_hash` included
+/// in the block `block_number`.
+///
+/// The amount is computed from the ERC20 `Transfer` logs of the transaction, so tokens
+/// which take a fee on transfer are accounted correctly. If the log index of the burn
+/// event is unknown, all the transfers of the transaction are taken into account.
+pub async fn query_received_amount(
+    evm_client: &EthJsonRpcClient<impl Client>,
+    token: Address,
+    bridge: Address,
+    block_number: u64,
+    tx_hash: B256,
+    burn_log_index: Option<u64>,
+) -> BTFResult<U256> {
+    let params = EthGetLogsParams {
+        address: Some(vec![token.into(), bridge.into()]),
+        from_block: block_number.into(),
+        to_block: block_number.into(),
+        topics: Some(vec![vec![
+            WrappedToken::Transfer::SIGNATURE_HASH.0.into(),
+            BurnTokenEvent::SIGNATURE_HASH.0.into(),
+        ]]),
+    };
+
+    let logs = evm_client.get_logs(params).await.map_err(|e| {
+        log::warn!("failed to collect transfer logs for tx {tx_hash}: {e}");
+        Error::EvmRequestFailed(e.to_string())
+    })?;
+
+    Ok(net_received_amount(
+        &logs,
+        token,
+        bridge,
+        tx_hash,
+        burn_log_index,
+    ))
+}
+
+/// Sums up `Transfer` logs of `token` attributed to the burn event with the `burn_log_index`
+/// log index within the `tx_hash` transaction and returns the difference between amounts
+/// transferred to and from the `bridge`.
+///
+/// The `BTFBridge` contract transfers the burnt tokens right before it emits the burn event,
+/// so the transfers of the event are the ones logged after the previous burn event of
+/// the transaction and before the event itself.
+pub fn net_received_amount(
+    logs: &[Log],
+    token: Address,
+    bridge: Address,
+    tx_hash: B256,
+    burn_log_index: Option<u64>,
+) -> U256 {
+    let tx_logs = || {
+        logs.iter()
+            .filter(move |log| log.transaction_hash == Some(tx_hash))
+    };
+
+    let previous_burn_log_index = burn_log_index.and_then(|burn_log_index| {
+        tx_logs()
+            .filter(|log| {
+                log.address() == bridge
+                    && log.topics().first() == Some(&BurnTokenEvent::SIGNATURE_HASH)
+            })
+            .filter_map(|log| log.log_index)
+            .filter(|&index| index < burn_log_index)
+            .max()
+    });
+
+    let is_attributed_to_burn = |log: &Log| match burn_log_index {
+        Some(burn_log_index) => log.log_index.is_some_and(|index| {
+            index < burn_log_index && previous_burn_log_index.is_none_or(|prev| index > prev)
+        }),
+        None => true,
+    };
+
+    let mut received = U256::ZERO;
+    let mut sent = U256::ZERO;
+
+    let transfers = tx_logs()
+        .filter(|log| log.address() == token && is_attributed_to_burn(log))
+        .filter_map(|log| WrappedToken::Transfer::decode_log_data(log.data(), true).ok());
+
+    for transfer in transfers {
+        if transfer.to == bridge {
+            received = received.saturating_add(transfer.value);
+        }
+        if transfer.from == bridge {
+            sent = sent.saturating_add(transfer.value);
+        }
+    }
+
+    received.saturating_sub(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::LogData;
+
+    use super::*;
+
+    fn log_with_data(
+        address: Address,
+        tx_hash: B256,
+        log_index: u64,
+        topics: Vec<B256>,
+        data: Vec<u8>,
+    ) -> Log {
+        Log {
+            block_hash: None,
+            block_number: Some(1),
+            transaction_hash: Some(tx_hash),
+            transaction_index: None,
+            log_index: Some(log_index),
+            removed: false,
+            block_timestamp: None,
+            inner: alloy::primitives::Log {
+                address,
+                data: LogData::new_unchecked(topics, data.into()),
+            },
+        }
+    }
+
+    fn transfer_log(
+        token: Address,
+        tx_hash: B256,
+        log_index: u64,
+        from: Address,
+        to: Address,
+        value: u64,
+    ) -> Log {
+        let event = WrappedToken::Transfer {
+            from,
+            to,
+            value: U256::from(value),
+        };
+
+        log_with_data(
+            token,
+            tx_hash,
+            log_index,
+            event.encode_topics().into_iter().map(|t| t.0).collect(),
+            event.encode_data(),
+        )
+    }
+
+    fn burn_log(bridge: Address, tx_hash: B256, log_index: u64) -> Log {
+        log_with_data(
+            bridge,
+            tx_hash,
+            log_index,
+            vec![BurnTokenEvent::SIGNATURE_HASH],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn should_compute_net_received_amount() {
+        let token = Address::repeat_byte(1);
+        let other_token = Address::repeat_byte(2);
+        let bridge = Address::repeat_byte(3);
+        let sender = Address::repeat_byte(4);
+        let fee_collector = Address::repeat_byte(5);
+        let tx_hash = B256::repeat_byte(6);
+        let other_tx_hash = B256::repeat_byte(7);
+
+        let logs = vec![
+            transfer_log(token, tx_hash, 0, sender, bridge, 100),
+            transfer_log(token, tx_hash, 1, bridge, fee_collector, 3),
+            transfer_log(token, tx_hash, 2, sender, fee_collector, 5),
+            transfer_log(other_token, tx_hash, 3, sender, bridge, 1000),
+            burn_log(bridge, tx_hash, 4),
+            transfer_log(token, other_tx_hash, 0, sender, bridge, 1000),
+        ];
+
+        let received = net_received_amount(&logs, token, bridge, tx_hash, Some(4));
+        assert_eq!(received, U256::from(97u64));
+
+        let received = net_received_amount(&logs, token, bridge, tx_hash, None);
+        assert_eq!(received, U256::from(97u64));
+    }
+
+    #[test]
+    fn should_attribute_transfers_to_burn_events() {
+        let token = Address::repeat_byte(1);
+        let bridge = Address::repeat_byte(3);
+        let sender = Address::repeat_byte(4);
+        let fee_collector = Address::repeat_byte(5);
+        let tx_hash = B256::repeat_byte(6);
+
+        let logs = vec![
+            transfer_log(token, tx_hash, 0, sender, bridge, 100),
+            burn_log(bridge, tx_hash, 1),
+            transfer_log(token, tx_hash, 2, sender, bridge, 50),
+            transfer_log(token, tx_hash, 3, bridge, fee_collector, 5),
+            burn_log(bridge, tx_hash, 4),
+            transfer_log(token, tx_hash, 5, sender, bridge, 1000),
+        ];
+
+        assert_eq!(
+            net_received_amount(&logs, token, bridge, tx_hash, Some(1)),
+            U256::from(100u64)
+        );
+        assert_eq!(
+            net_received_amount(&logs, token, bridge, tx_hash, Some(4)),
+            U256::from(45u64)
+        );
+    }
+
+    #[test]
+    fn should_return_zero_if_nothing_received() {
+        let token = Address::repeat_byte(1);
+        let bridge = Address::repeat_byte(3);
+        let tx_hash = B256::repeat_byte(6);
+
+        assert_eq!(
+            net_received_amount(&[], token, bridge, tx_hash, Some(0)),
+            U256::ZERO
+        );
+    }
+}
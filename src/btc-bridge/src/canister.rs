@@ -12,7 +12,7 @@ use bridge_canister::runtime::service::update_evm_params::RefreshEvmParamsServic
 use bridge_canister::runtime::state::SharedConfig;
 use bridge_canister::runtime::state::config::ConfigStorage;
 use bridge_canister::runtime::{BridgeRuntime, RuntimeState};
-use bridge_did::error::BTFResult;
+use bridge_did::error::{BTFResult, Error};
 use bridge_did::init::BtcBridgeConfig;
 use bridge_did::init::btc::WrappedTokenConfig;
 use bridge_did::op_id::OperationId;
@@ -27,11 +27,16 @@ use ic_canister::{
 };
 use ic_ckbtc_minter::updates::get_btc_address::GetBtcAddressArgs;
 use ic_exports::ic_cdk;
+use ic_exports::ic_cdk::api::management_canister::ecdsa::{
+    EcdsaPublicKeyArgument, ecdsa_public_key,
+};
+use ic_exports::ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
 use ic_exports::ledger::Subaccount;
 use ic_log::canister::{LogCanister, LogState};
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_storage::IcStorage;
 
+use crate::interface::{BtcBridgeError, ErrorCodes};
 use crate::ops::{
     BtcBridgeOpImpl, BtcEventsHandler, BtcMintOrderHandler, BtcMintTxHandler,
     FETCH_BTF_EVENTS_SERVICE_ID, REFRESH_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID,
@@ -127,12 +132,72 @@ impl BtcBridge {
             .get_memos_by_user_address(&user_id)
     }
 
+    /// Returns the bitcoin address to deposit BTC to. In the self-custody mode it is the deposit
+    /// address of the Ethereum address given by the first 20 bytes of the subaccount, otherwise
+    /// the address is requested from the ckBTC minter.
     #[update]
-    pub async fn get_btc_address(&self, args: GetBtcAddressArgs) -> String {
-        let ck_btc_minter = get_state().borrow().ck_btc_minter();
+    pub async fn get_btc_address(&self, args: GetBtcAddressArgs) -> BTFResult<String> {
+        if get_state().borrow().is_self_custody() {
+            let subaccount = args.subaccount.unwrap_or_default();
+            let eth_address = H160::from_slice(&subaccount[..20]);
+            return crate::key::get_transit_address(&get_state(), &eth_address)
+                .map(|address| address.to_string())
+                .map_err(|err| BtcBridgeError::from(err).into());
+        }
+
+        let ck_btc_minter = get_state().borrow().ck_btc_minter()?;
         virtual_canister_call!(ck_btc_minter, "get_btc_address", (args,), String)
             .await
-            .unwrap()
+            .map_err(|(code, msg)| Error::Custom {
+                code: ErrorCodes::CkBtcMinter as u32,
+                msg: format!("failed to get BTC address from ckBTC minter: {msg} ({code:?})"),
+            })
+    }
+
+    /// Returns the bitcoin address that a user has to use to deposit BTC to be received on the
+    /// given Ethereum address. Available only in the self-custody mode.
+    #[query]
+    pub fn get_deposit_address(&self, eth_address: H160) -> BTFResult<String> {
+        let state = get_state();
+        if !state.borrow().is_self_custody() {
+            return Err(Error::Initialization(
+                "deposit addresses are available only in the self-custody mode".into(),
+            ));
+        }
+
+        crate::key::get_transit_address(&state, &eth_address)
+            .map(|address| address.to_string())
+            .map_err(|err| BtcBridgeError::from(err).into())
+    }
+
+    /// Requests the threshold ECDSA master key of the canister. Must be called once before the
+    /// self-custody bridge can process deposits and withdrawals.
+    #[update]
+    pub async fn admin_configure_ecdsa(&self) -> BTFResult<()> {
+        Self::inspect_caller_is_owner()?;
+
+        let signing_strategy = get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_signing_strategy();
+
+        let key_id = get_state().borrow().ecdsa_key_id(&signing_strategy);
+
+        let (master_key,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+            canister_id: None,
+            derivation_path: vec![],
+            key_id: key_id.clone(),
+        })
+        .await
+        .map_err(|(code, msg)| {
+            Error::Initialization(format!("failed to get master key: {msg} ({code:?})"))
+        })?;
+
+        get_state()
+            .borrow_mut()
+            .configure_ecdsa(master_key, key_id)
+            .map_err(Error::Initialization)
     }
 
     #[update]
@@ -150,6 +215,13 @@ impl BtcBridge {
         bridge_canister::build_data!()
     }
 
+    /// Strips the headers of the fee oracle responses, so the HTTP outcall replicas agree on
+    /// them.
+    #[query]
+    pub fn transform_indexer_response(&self, args: TransformArgs) -> HttpResponse {
+        bridge_utils::http::transform_indexer_response(args)
+    }
+
     pub fn idl() -> Idl {
         generate_idl!()
     }
@@ -160,7 +232,7 @@ impl BtcBridge {
         if ic_cdk::caller() == owner {
             Ok(())
         } else {
-            Err(bridge_did::error::Error::AccessDenied)
+            Err(Error::AccessDenied)
         }
     }
 }
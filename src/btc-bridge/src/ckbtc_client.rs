@@ -3,8 +3,9 @@ mod ledger;
 mod minter;
 
 pub use interface::{
-    PendingUtxo, RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk, UpdateBalanceArgs,
-    UpdateBalanceError, UtxoStatus,
+    PendingUtxo, ReimburseDepositTask, ReimbursedDeposit, ReimbursementReason, RetrieveBtcArgs,
+    RetrieveBtcError, RetrieveBtcOk, RetrieveBtcStatusRequest, RetrieveBtcStatusV2, Txid,
+    UpdateBalanceArgs, UpdateBalanceError, UtxoStatus,
 };
 pub use ledger::CkBtcLedgerClient;
 pub use minter::CkBtcMinterClient;
@@ -4,6 +4,7 @@
 
 use candid::{CandidType, Deserialize, Principal};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::icrc_types::icrc1::account::Account;
 use ic_exports::ledger::Subaccount;
 use serde::Serialize;
 
@@ -131,3 +132,69 @@ pub enum RetrieveBtcError {
         error_code: u64,
     },
 }
+
+/// The arguments of the [retrieve_btc_status_v2] endpoint.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct RetrieveBtcStatusRequest {
+    // the index of the burn block on the ckbtc ledger
+    pub block_index: u64,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum RetrieveBtcStatusV2 {
+    /// The minter does not have any information on the specified retrieval request. It can be
+    /// that nobody submitted the request or the minter pruned the relevant information from the
+    /// history to save space.
+    Unknown,
+
+    /// The minter did not send a Bitcoin transaction for this request yet.
+    Pending,
+
+    /// The minter is obtaining all required ECDSA signatures on the Bitcoin transaction for this
+    /// request.
+    Signing,
+
+    /// The minter signed the transaction and is waiting for a reply from the Bitcoin canister.
+    Sending { txid: Txid },
+
+    /// The minter sent a transaction for the retrieve request. The payload contains the
+    /// identifier of the transaction on the Bitcoin network.
+    Submitted { txid: Txid },
+
+    /// The amount was too low to cover the transaction fees.
+    AmountTooLow,
+
+    /// The minter received enough confirmations for the Bitcoin transaction for this request.
+    /// The payload contains the identifier of the transaction on the Bitcoin network.
+    Confirmed { txid: Txid },
+
+    /// The retrieve Bitcoin request has been reimbursed.
+    Reimbursed(ReimbursedDeposit),
+
+    /// The minter will try to reimburse this transaction.
+    WillReimburse(ReimburseDepositTask),
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ReimbursedDeposit {
+    pub account: Account,
+    pub amount: u64,
+    pub reason: ReimbursementReason,
+    pub mint_block_index: u64,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ReimburseDepositTask {
+    pub account: Account,
+    pub amount: u64,
+    pub reason: ReimbursementReason,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum ReimbursementReason {
+    TaintedDestination {
+        kyt_provider: Principal,
+        kyt_fee: u64,
+    },
+    CallFailed,
+}
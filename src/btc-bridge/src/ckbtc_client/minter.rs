@@ -3,7 +3,9 @@ use ic_canister::virtual_canister_call;
 use ic_exports::ic_kit::RejectionCode;
 use ic_exports::ledger::Subaccount;
 
-use super::interface::{RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk};
+use super::interface::{
+    RetrieveBtcArgs, RetrieveBtcError, RetrieveBtcOk, RetrieveBtcStatusRequest, RetrieveBtcStatusV2,
+};
 use super::{UpdateBalanceArgs, UpdateBalanceError, UtxoStatus};
 
 pub struct CkBtcMinterClient(Principal);
@@ -53,4 +55,23 @@ impl CkBtcMinterClient {
         )
         .await
     }
+
+    /// Returns the status of the withdrawal request identified by the index of the ckBTC burn
+    /// block returned from [retrieve_btc].
+    ///
+    /// For more details, see [retrieve_btc_status_v2](https://internetcomputer.org/docs/current/references/ckbtc-reference#retrieve_btc_status_v2block_index-nat64).
+    pub async fn retrieve_btc_status_v2(
+        &self,
+        block_index: u64,
+    ) -> Result<RetrieveBtcStatusV2, (RejectionCode, String)> {
+        let args = RetrieveBtcStatusRequest { block_index };
+
+        virtual_canister_call!(
+            self.0,
+            "retrieve_btc_status_v2",
+            (args,),
+            RetrieveBtcStatusV2
+        )
+        .await
+    }
 }
@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bitcoin::{Address, Network};
+use bitcoin_bridge_core::ledger::UtxoKey;
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+
+use crate::canister::{get_runtime_state, get_state};
+use crate::interface::BtcBridgeError;
+use crate::key::{BtcSignerType, get_derivation_path_ic};
+use crate::state::State;
+
+/// Collects BTC deposited to the self-custody bridge.
+pub(crate) struct BtcDeposit<UTXO: UtxoProvider = IcUtxoProvider> {
+    state: Rc<RefCell<State>>,
+    network: Network,
+    signer: BtcSignerType,
+    utxo_provider: UTXO,
+}
+
+impl BtcDeposit<IcUtxoProvider> {
+    pub fn new(state: Rc<RefCell<State>>) -> Result<Self, BtcBridgeError> {
+        let state_ref = state.borrow();
+
+        let signing_strategy = get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_signing_strategy();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let cache_timeout = state_ref.utxo_cache_timeout();
+        let fee_estimator = state_ref.fee_estimator();
+        let signer = state_ref
+            .btc_signer(&signing_strategy)
+            .ok_or(BtcBridgeError::SignerNotInitialized)?;
+
+        drop(state_ref);
+
+        Ok(Self {
+            state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout, fee_estimator),
+        })
+    }
+
+    pub fn get() -> Result<Self, BtcBridgeError> {
+        Self::new(get_state())
+    }
+}
+
+impl<UTXO: UtxoProvider> BtcDeposit<UTXO> {
+    /// Moves all confirmed utxos at the deposit address of the given Ethereum address into the
+    /// bridge ledger and returns their total value in satoshis.
+    pub async fn collect_utxos(&self, eth_address: &H160) -> Result<u64, BtcBridgeError> {
+        let address = self.get_transit_address(eth_address).await?;
+        let response = self.utxo_provider.get_utxos(&address).await?;
+
+        let min_confirmations = self.state.borrow().min_confirmations();
+        let (confirmed, pending): (Vec<Utxo>, Vec<Utxo>) = {
+            let state_ref = self.state.borrow();
+            response
+                .utxos
+                .into_iter()
+                .filter(|utxo| !state_ref.ledger().contains(&UtxoKey::from(&utxo.outpoint)))
+                .partition(|utxo| {
+                    utxo_confirmations(response.tip_height, utxo) >= min_confirmations
+                })
+        };
+
+        if confirmed.is_empty() {
+            return if pending.is_empty() {
+                log::debug!("No new utxos found for {eth_address}");
+                Err(BtcBridgeError::NothingToMint)
+            } else {
+                log::debug!(
+                    "{} utxos for {eth_address} are waiting for {min_confirmations} confirmations",
+                    pending.len()
+                );
+                Err(BtcBridgeError::WaitingForConfirmations)
+            };
+        }
+
+        let derivation_path = get_derivation_path_ic(eth_address);
+        let mut amount = 0;
+        {
+            let mut state = self.state.borrow_mut();
+            let ledger = state.ledger_mut();
+            for utxo in confirmed {
+                // The utxo could be collected by a concurrent operation while we were waiting
+                // for the adapter response.
+                if ledger.contains(&UtxoKey::from(&utxo.outpoint)) {
+                    continue;
+                }
+
+                amount += utxo.value;
+                ledger.deposit(utxo, &address, derivation_path.clone());
+            }
+        }
+
+        if amount == 0 {
+            return Err(BtcBridgeError::NothingToMint);
+        }
+
+        log::debug!("Collected {amount} satoshi deposited for {eth_address}");
+
+        Ok(amount)
+    }
+
+    async fn get_transit_address(&self, eth_address: &H160) -> Result<Address, BtcBridgeError> {
+        self.signer
+            .get_transit_address(eth_address, self.network)
+            .await
+            .map_err(BtcBridgeError::from)
+    }
+}
+
+/// Number of confirmations of the utxo given the current height of the Bitcoin chain.
+fn utxo_confirmations(tip_height: u32, utxo: &Utxo) -> u32 {
+    (tip_height + 1).saturating_sub(utxo.height)
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+
+    fn utxo(height: u32) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![1; 32],
+                vout: 0,
+            },
+            value: 10_000,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_should_count_utxo_confirmations() {
+        assert_eq!(utxo_confirmations(100, &utxo(100)), 1);
+        assert_eq!(utxo_confirmations(100, &utxo(95)), 6);
+        assert_eq!(utxo_confirmations(100, &utxo(101)), 0);
+    }
+}
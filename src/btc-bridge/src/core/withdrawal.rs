@@ -0,0 +1,439 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash as _;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, FeeRate, Network, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+use bitcoin_bridge_core::funding::select_funding_utxos;
+use bitcoin_bridge_core::key::sign_transaction;
+use bitcoin_bridge_core::ledger::UtxoKey;
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use bridge_did::event_data::BurntEventData;
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
+use ord_rs::fees::estimate_transaction_fees;
+use ord_rs::wallet::TxInputInfo;
+
+use crate::canister::{get_runtime_state, get_state};
+use crate::constants::{DUST_THRESHOLD, FEE_RATE_UPDATE_INTERVAL};
+use crate::interface::BtcWithdrawError;
+use crate::key::{BtcSignerType, get_derivation_path_ic};
+use crate::state::State;
+
+/// Index of the change output in the withdrawal transaction.
+const CHANGE_OUTPUT_INDEX: usize = 1;
+
+/// Creates and sends the withdrawal transactions of the self-custody bridge.
+pub(crate) struct BtcWithdrawal<UTXO: UtxoProvider = IcUtxoProvider> {
+    state: Rc<RefCell<State>>,
+    network: Network,
+    signer: BtcSignerType,
+    utxo_provider: UTXO,
+}
+
+impl BtcWithdrawal<IcUtxoProvider> {
+    pub fn new(state: Rc<RefCell<State>>) -> Result<Self, BtcWithdrawError> {
+        let state_ref = state.borrow();
+
+        let signing_strategy = get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_signing_strategy();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let cache_timeout = state_ref.utxo_cache_timeout();
+        let fee_estimator = state_ref.fee_estimator();
+        let signer = state_ref
+            .btc_signer(&signing_strategy)
+            .ok_or(BtcWithdrawError::SignerNotInitialized)?;
+
+        drop(state_ref);
+
+        Ok(Self {
+            state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout, fee_estimator),
+        })
+    }
+
+    pub fn get() -> Result<Self, BtcWithdrawError> {
+        Self::new(get_state())
+    }
+}
+
+impl<UTXO: UtxoProvider> BtcWithdrawal<UTXO> {
+    /// Creates and signs a transaction sending the burnt amount to the recipient of the event.
+    ///
+    /// The transaction fee is deducted from the withdrawn amount. Inputs of the transaction are
+    /// marked as used in the ledger, so they cannot be spent by another withdrawal.
+    pub async fn create_withdrawal_transaction(
+        &self,
+        event: &BurntEventData,
+    ) -> Result<Transaction, BtcWithdrawError> {
+        let recipient = self.parse_recipient(&event.recipient_id)?;
+        let amount: u64 = event
+            .amount
+            .0
+            .try_into()
+            .map_err(|_| BtcWithdrawError::AmountTooBig(event.amount.0.to_string()))?;
+
+        let fee_rate = self.get_fee_rate().await?;
+        let change_address = self.get_change_address().await?;
+
+        let unspent_utxos: Vec<TxInputInfo> = self
+            .state
+            .borrow()
+            .ledger()
+            .load_unspent_utxos()?
+            .into_values()
+            .collect();
+
+        let inputs = select_funding_utxos(unspent_utxos, |_| amount)
+            .ok_or(BtcWithdrawError::InsufficientFunds)?;
+        let inputs_value: u64 = inputs.iter().map(|input| input.tx_out.value.to_sat()).sum();
+
+        let fee = estimate_transaction_fees(
+            self.signer.address_type().script_type(),
+            inputs.len(),
+            fee_rate,
+            &None,
+            vec![
+                TxOut {
+                    value: Amount::from_sat(amount),
+                    script_pubkey: recipient.script_pubkey(),
+                },
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: change_address.script_pubkey(),
+                },
+            ],
+        )
+        .to_sat();
+
+        let amount_minus_fee = amount.saturating_sub(fee);
+        if amount_minus_fee < DUST_THRESHOLD {
+            log::debug!("Withdrawal amount {amount} doesn't cover the fee {fee}");
+            return Err(BtcWithdrawError::AmountTooLow(amount));
+        }
+
+        let mut outputs = vec![TxOut {
+            value: Amount::from_sat(amount_minus_fee),
+            script_pubkey: recipient.script_pubkey(),
+        }];
+        let change = inputs_value - amount;
+        if change >= DUST_THRESHOLD {
+            outputs.push(TxOut {
+                value: Amount::from_sat(change),
+                script_pubkey: change_address.script_pubkey(),
+            });
+        }
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::from_consensus(0xffffffff),
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs,
+        };
+
+        let signed_tx = self.sign_transaction(unsigned_tx, &inputs).await?;
+
+        {
+            let mut state = self.state.borrow_mut();
+            let ledger = state.ledger_mut();
+            for input in inputs {
+                ledger.mark_as_used(input.outpoint.into(), recipient.clone());
+            }
+        }
+
+        log::debug!(
+            "Created withdrawal transaction {} of {amount_minus_fee} satoshi to {recipient} with fee {fee}",
+            signed_tx.txid()
+        );
+
+        Ok(signed_tx)
+    }
+
+    /// Sends the transaction to the Bitcoin network, marks its inputs as spent and adds its change
+    /// output to the ledger.
+    pub async fn send_transaction(&self, tx: &Transaction) -> Result<(), BtcWithdrawError> {
+        self.utxo_provider.send_tx(tx).await?;
+
+        {
+            let mut state = self.state.borrow_mut();
+            let ledger = state.ledger_mut();
+            for input in &tx.input {
+                ledger.mark_as_spent(&UtxoKey::from(input.previous_output));
+            }
+        }
+
+        let Some(change_output) = tx.output.get(CHANGE_OUTPUT_INDEX) else {
+            return Ok(());
+        };
+
+        let change_address = self.get_change_address().await?;
+        // Make sure that the transaction builder code is not changed and the change output
+        // is where we expect it to be. If not, panic until the code of the canister is fixed.
+        assert_eq!(change_output.script_pubkey, change_address.script_pubkey());
+
+        let change_utxo = Utxo {
+            outpoint: Outpoint {
+                txid: tx.txid().as_byte_array().to_vec(),
+                vout: CHANGE_OUTPUT_INDEX as u32,
+            },
+            value: change_output.value.to_sat(),
+            height: 0,
+        };
+
+        self.state.borrow_mut().ledger_mut().deposit(
+            change_utxo,
+            &change_address,
+            get_derivation_path_ic(&H160::default()),
+        );
+
+        Ok(())
+    }
+
+    fn parse_recipient(&self, recipient_id: &[u8]) -> Result<Address, BtcWithdrawError> {
+        let invalid_recipient = || BtcWithdrawError::InvalidRecipient(recipient_id.to_vec());
+
+        let address = String::from_utf8(recipient_id.to_vec()).map_err(|_| invalid_recipient())?;
+        Address::from_str(&address)
+            .map_err(|_| invalid_recipient())?
+            .require_network(self.network)
+            .map_err(|_| invalid_recipient())
+    }
+
+    async fn sign_transaction(
+        &self,
+        unsigned_tx: Transaction,
+        inputs: &[TxInputInfo],
+    ) -> Result<Transaction, BtcWithdrawError> {
+        sign_transaction(&self.signer, &unsigned_tx, inputs)
+            .await
+            .map_err(|err| {
+                log::error!("Failed to sign withdrawal transaction: {err:?}");
+                BtcWithdrawError::TransactionCreation(err.to_string())
+            })
+    }
+
+    /// Change of the withdrawal transactions is sent to the transit address of the zero
+    /// Ethereum address.
+    async fn get_change_address(&self) -> Result<Address, BtcWithdrawError> {
+        self.signer
+            .get_transit_address(&H160::default(), self.network)
+            .await
+            .map_err(BtcWithdrawError::from)
+    }
+
+    /// Get current fee rate, otherwise if too old, request a new one from the utxo provider.
+    async fn get_fee_rate(&self) -> Result<FeeRate, BtcWithdrawError> {
+        let (current_fee_rate, elapsed_since_last_fee_rate_update) = {
+            let state_ref = self.state.borrow();
+            (
+                state_ref.fee_rate(),
+                state_ref.last_fee_rate_update_elapsed(),
+            )
+        };
+
+        if elapsed_since_last_fee_rate_update > FEE_RATE_UPDATE_INTERVAL
+            || current_fee_rate == FeeRate::ZERO
+        {
+            let fee_rate = self.utxo_provider.get_fee_rate().await?;
+            self.state.borrow_mut().update_fee_rate(fee_rate);
+
+            Ok(fee_rate)
+        } else {
+            Ok(current_fee_rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::{PrivateKey, Txid};
+    use bitcoin_bridge_core::key::{AddressType, LocalBtcSigner};
+    use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::GetUtxosResponse;
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    struct FakeUtxoProvider {
+        fee_rate: FeeRate,
+    }
+
+    impl UtxoProvider for FakeUtxoProvider {
+        async fn get_utxos(
+            &self,
+            _address: &Address,
+        ) -> Result<GetUtxosResponse, UtxoProviderError> {
+            unimplemented!()
+        }
+
+        async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError> {
+            Ok(self.fee_rate)
+        }
+
+        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), UtxoProviderError> {
+            unimplemented!()
+        }
+    }
+
+    fn test_withdrawal() -> BtcWithdrawal<FakeUtxoProvider> {
+        BtcWithdrawal {
+            state: Rc::new(RefCell::new(State::default())),
+            network: Network::Regtest,
+            signer: BtcSignerType::Local(LocalBtcSigner::new(
+                PrivateKey::generate(Network::Regtest),
+                AddressType::P2wpkh,
+            )),
+            utxo_provider: FakeUtxoProvider {
+                fee_rate: FeeRate::from_sat_per_vb(1).unwrap(),
+            },
+        }
+    }
+
+    async fn deposit_utxo(withdrawal: &BtcWithdrawal<FakeUtxoProvider>, id: u8, value: u64) {
+        let address = withdrawal.get_change_address().await.unwrap();
+        let utxo = Utxo {
+            outpoint: Outpoint {
+                txid: Txid::from_slice(&[id; 32])
+                    .unwrap()
+                    .as_byte_array()
+                    .to_vec(),
+                vout: 0,
+            },
+            value,
+            height: 0,
+        };
+
+        withdrawal.state.borrow_mut().ledger_mut().deposit(
+            utxo,
+            &address,
+            get_derivation_path_ic(&H160::default()),
+        );
+    }
+
+    fn burnt_event(amount: did::U256) -> BurntEventData {
+        let private_key = PrivateKey::generate(Network::Regtest);
+        let recipient =
+            Address::p2wpkh(&private_key.public_key(&Secp256k1::new()), Network::Regtest).unwrap();
+
+        BurntEventData {
+            sender: H160::from_slice(&[1; 20]),
+            amount,
+            recipient_id: recipient.to_string().into_bytes(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_should_create_withdrawal_transaction() {
+        MockContext::new().inject();
+        let withdrawal = test_withdrawal();
+        deposit_utxo(&withdrawal, 1, 10_000).await;
+        deposit_utxo(&withdrawal, 2, 50_000).await;
+
+        let event = burnt_event(40_000u64.into());
+        let tx = withdrawal
+            .create_withdrawal_transaction(&event)
+            .await
+            .unwrap();
+
+        // The biggest utxo covers the amount, the fee is deducted from the withdrawn amount.
+        assert_eq!(tx.input.len(), 1);
+        assert_eq!(tx.output.len(), 2);
+        let withdrawn = tx.output[0].value.to_sat();
+        assert!(withdrawn < 40_000);
+        assert!(withdrawn > 40_000 - 1_000);
+        assert_eq!(
+            tx.output[CHANGE_OUTPUT_INDEX].script_pubkey,
+            withdrawal
+                .get_change_address()
+                .await
+                .unwrap()
+                .script_pubkey()
+        );
+        assert_eq!(tx.output[CHANGE_OUTPUT_INDEX].value.to_sat(), 10_000);
+        assert!(tx.input.iter().all(|input| !input.witness.is_empty()));
+
+        // The input is reserved for this withdrawal.
+        let unspent = withdrawal
+            .state
+            .borrow()
+            .ledger()
+            .load_unspent_utxos()
+            .unwrap();
+        assert_eq!(unspent.len(), 1);
+        assert!(!unspent.contains_key(&UtxoKey::from(tx.input[0].previous_output)));
+    }
+
+    #[tokio::test]
+    async fn test_should_not_create_withdrawal_transaction_if_not_enough_funds() {
+        MockContext::new().inject();
+        let withdrawal = test_withdrawal();
+        deposit_utxo(&withdrawal, 1, 10_000).await;
+        deposit_utxo(&withdrawal, 2, 5_000).await;
+
+        let result = withdrawal
+            .create_withdrawal_transaction(&burnt_event(15_001u64.into()))
+            .await;
+
+        assert_eq!(result, Err(BtcWithdrawError::InsufficientFunds));
+        assert_eq!(
+            withdrawal
+                .state
+                .borrow()
+                .ledger()
+                .load_unspent_utxos()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_not_create_withdrawal_transaction_if_amount_doesnt_cover_fee() {
+        MockContext::new().inject();
+        let withdrawal = test_withdrawal();
+        deposit_utxo(&withdrawal, 1, 10_000).await;
+
+        let result = withdrawal
+            .create_withdrawal_transaction(&burnt_event(500u64.into()))
+            .await;
+
+        assert_eq!(result, Err(BtcWithdrawError::AmountTooLow(500)));
+    }
+
+    #[tokio::test]
+    async fn test_should_reject_withdrawal_amount_exceeding_u64() {
+        MockContext::new().inject();
+        let withdrawal = test_withdrawal();
+
+        let amount: did::U256 = (u64::MAX as u128 + 1).into();
+        let result = withdrawal
+            .create_withdrawal_transaction(&burnt_event(amount.clone()))
+            .await;
+
+        assert_eq!(
+            result,
+            Err(BtcWithdrawError::AmountTooBig(amount.0.to_string()))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
 use bridge_did::order::SignedMintOrder;
 use candid::CandidType;
 use did::H256;
@@ -7,6 +8,7 @@ use ic_exports::icrc_types::icrc1::transfer::TransferError;
 use serde::Deserialize;
 
 use crate::ckbtc_client::{PendingUtxo, RetrieveBtcError, UpdateBalanceError};
+use crate::key::KeyError;
 
 /// Status of a pending BTC to ERC20 transfer.
 #[derive(Debug, CandidType, Deserialize, PartialEq, Eq)]
@@ -49,6 +51,18 @@ pub enum ErrorCodes {
     // Withdrawal errors
     InvalidRecipient = 9,
     RetrieveBtcError = 10,
+    WithdrawalInProgress = 11,
+    UnknownWithdrawal = 12,
+    // Self-custody errors
+    BtcAdapter = 13,
+    Key = 14,
+    SignerNotInitialized = 15,
+    InsufficientFunds = 16,
+    FeeRateRequest = 17,
+    TransactionCreation = 18,
+    TransactionSending = 19,
+    AmountTooLow = 20,
+    AmountTooBig = 21,
 }
 
 /// Error during BTC to ERC20 transfer.
@@ -76,6 +90,24 @@ pub enum BtcBridgeError {
     NothingToMint,
     /// Waiting for confirmations on the UTXOs.
     WaitingForConfirmations,
+    /// Error while requesting utxos from the IC Bitcoin adapter.
+    BtcAdapter(String),
+    /// Error while deriving the deposit address.
+    Key(String),
+    /// The threshold ECDSA key of the self-custody bridge is not configured.
+    SignerNotInitialized,
+}
+
+impl From<KeyError> for BtcBridgeError {
+    fn from(err: KeyError) -> Self {
+        Self::Key(err.to_string())
+    }
+}
+
+impl From<UtxoProviderError> for BtcBridgeError {
+    fn from(err: UtxoProviderError) -> Self {
+        Self::BtcAdapter(err.to_string())
+    }
 }
 
 impl From<TransferError> for BtcBridgeError {
@@ -124,6 +156,18 @@ impl From<BtcBridgeError> for bridge_did::error::Error {
                 msg: "Waiting for confirmations".to_string(),
             },
             BtcBridgeError::Evm(msg) => Self::EvmRequestFailed(msg),
+            BtcBridgeError::BtcAdapter(msg) => Self::Custom {
+                code: ErrorCodes::BtcAdapter as u32,
+                msg: format!("BTC adapter error: {msg}"),
+            },
+            BtcBridgeError::Key(msg) => Self::Custom {
+                code: ErrorCodes::Key as u32,
+                msg: format!("Key error: {msg}"),
+            },
+            BtcBridgeError::SignerNotInitialized => Self::Custom {
+                code: ErrorCodes::SignerNotInitialized as u32,
+                msg: "Signer not initialized".to_string(),
+            },
         }
     }
 }
@@ -133,6 +177,42 @@ impl From<BtcBridgeError> for bridge_did::error::Error {
 pub enum BtcWithdrawError {
     InvalidRecipient(Vec<u8>),
     RetrieveBtcError(String),
+    /// The withdrawal request with the given burn block index is not finished yet.
+    WithdrawalInProgress(u64),
+    /// The ckBTC minter has no information about the withdrawal request with the given burn
+    /// block index.
+    UnknownWithdrawal(u64),
+    /// The self-custody bridge doesn't hold enough BTC to fund the withdrawal.
+    InsufficientFunds,
+    /// Error while requesting the current fee rate.
+    FeeRateRequest,
+    /// Error while creating or signing the withdrawal transaction.
+    TransactionCreation(String),
+    /// Error while sending the withdrawal transaction to the Bitcoin network.
+    TransactionSending(String),
+    /// Error while deriving the bridge addresses.
+    Key(String),
+    /// The threshold ECDSA key of the self-custody bridge is not configured.
+    SignerNotInitialized,
+    /// The withdrawn amount doesn't cover the transaction fee.
+    AmountTooLow(u64),
+    /// The withdrawn amount doesn't fit into a satoshi amount.
+    AmountTooBig(String),
+}
+
+impl From<KeyError> for BtcWithdrawError {
+    fn from(err: KeyError) -> Self {
+        Self::Key(err.to_string())
+    }
+}
+
+impl From<UtxoProviderError> for BtcWithdrawError {
+    fn from(err: UtxoProviderError) -> Self {
+        match err {
+            UtxoProviderError::FeeRateRequest => Self::FeeRateRequest,
+            err => Self::TransactionSending(err.to_string()),
+        }
+    }
 }
 
 impl From<RetrieveBtcError> for BtcWithdrawError {
@@ -170,6 +250,46 @@ impl From<BtcWithdrawError> for bridge_did::error::Error {
                 code: ErrorCodes::RetrieveBtcError as u32,
                 msg,
             },
+            BtcWithdrawError::WithdrawalInProgress(block_index) => Self::Custom {
+                code: ErrorCodes::WithdrawalInProgress as u32,
+                msg: format!("Withdrawal {block_index} is in progress"),
+            },
+            BtcWithdrawError::UnknownWithdrawal(block_index) => Self::Custom {
+                code: ErrorCodes::UnknownWithdrawal as u32,
+                msg: format!("Unknown withdrawal {block_index}"),
+            },
+            BtcWithdrawError::InsufficientFunds => Self::Custom {
+                code: ErrorCodes::InsufficientFunds as u32,
+                msg: "Insufficient funds".to_string(),
+            },
+            BtcWithdrawError::FeeRateRequest => Self::Custom {
+                code: ErrorCodes::FeeRateRequest as u32,
+                msg: "Failed to get fee rate".to_string(),
+            },
+            BtcWithdrawError::TransactionCreation(msg) => Self::Custom {
+                code: ErrorCodes::TransactionCreation as u32,
+                msg: format!("Failed to create transaction: {msg}"),
+            },
+            BtcWithdrawError::TransactionSending(msg) => Self::Custom {
+                code: ErrorCodes::TransactionSending as u32,
+                msg: format!("Failed to send transaction: {msg}"),
+            },
+            BtcWithdrawError::Key(msg) => Self::Custom {
+                code: ErrorCodes::Key as u32,
+                msg: format!("Key error: {msg}"),
+            },
+            BtcWithdrawError::SignerNotInitialized => Self::Custom {
+                code: ErrorCodes::SignerNotInitialized as u32,
+                msg: "Signer not initialized".to_string(),
+            },
+            BtcWithdrawError::AmountTooLow(amount) => Self::Custom {
+                code: ErrorCodes::AmountTooLow as u32,
+                msg: format!("Amount too low: {amount}"),
+            },
+            BtcWithdrawError::AmountTooBig(amount) => Self::Custom {
+                code: ErrorCodes::AmountTooBig as u32,
+                msg: format!("Amount too big: {amount}"),
+            },
         }
     }
 }
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+
+use async_trait::async_trait;
+use bitcoin::bip32::DerivationPath;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{Error as Secp256Error, Message, schnorr};
+use bitcoin::{Address, Network, PublicKey, XOnlyPublicKey};
+pub use bitcoin_bridge_core::key::{
+    AddressType, IcEcdsaSigner, KeyError, KeyResult, LocalBtcSigner, get_derivation_path,
+    get_derivation_path_ic,
+};
+use did::H160;
+use ord_rs::{BtcTxSigner, OrdResult};
+
+use crate::state::State;
+
+/// Signer of the self-custody bridge transactions.
+pub enum BtcSignerType {
+    Local(LocalBtcSigner),
+    Ic(IcEcdsaSigner),
+}
+
+impl BtcSignerType {
+    /// Type of the addresses the bridge holds the bitcoins at.
+    pub fn address_type(&self) -> AddressType {
+        match self {
+            BtcSignerType::Local(v) => v.address_type(),
+            BtcSignerType::Ic(_) => AddressType::P2wpkh,
+        }
+    }
+
+    /// Returns the P2WPKH transit address of the given Ethereum address for the signer keys.
+    pub async fn get_transit_address(
+        &self,
+        eth_address: &H160,
+        network: Network,
+    ) -> KeyResult<Address> {
+        let derivation_path = get_derivation_path(eth_address)?;
+        let public_key = self.ecdsa_public_key(&derivation_path).await?;
+
+        Address::p2wpkh(&public_key, network).map_err(KeyError::BitcoinAddress)
+    }
+}
+
+#[async_trait]
+impl BtcTxSigner for BtcSignerType {
+    async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
+        match self {
+            BtcSignerType::Local(v) => v.ecdsa_public_key(derivation_path).await,
+            BtcSignerType::Ic(v) => v.ecdsa_public_key(derivation_path).await,
+        }
+    }
+
+    async fn sign_with_ecdsa(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<Signature, Secp256Error> {
+        match self {
+            BtcSignerType::Local(v) => v.sign_with_ecdsa(message, derivation_path).await,
+            BtcSignerType::Ic(v) => v.sign_with_ecdsa(message, derivation_path).await,
+        }
+    }
+
+    async fn schnorr_public_key(
+        &self,
+        derivation_path: &DerivationPath,
+    ) -> OrdResult<XOnlyPublicKey> {
+        match self {
+            BtcSignerType::Local(v) => v.schnorr_public_key(derivation_path).await,
+            BtcSignerType::Ic(v) => v.schnorr_public_key(derivation_path).await,
+        }
+    }
+
+    async fn sign_with_schnorr(
+        &self,
+        message: Message,
+        derivation_path: &DerivationPath,
+    ) -> Result<schnorr::Signature, Secp256Error> {
+        match self {
+            BtcSignerType::Local(v) => v.sign_with_schnorr(message, derivation_path).await,
+            BtcSignerType::Ic(v) => v.sign_with_schnorr(message, derivation_path).await,
+        }
+    }
+}
+
+/// Returns the P2WPKH deposit address of the given Ethereum address, derived from the master key
+/// of the canister.
+pub fn get_transit_address(state: &RefCell<State>, eth_address: &H160) -> KeyResult<Address> {
+    let state = state.borrow();
+    let public_key = state.public_key().ok_or(KeyError::SignerNotInitialized)?;
+    let chain_code = state.chain_code().ok_or(KeyError::SignerNotInitialized)?;
+
+    bitcoin_bridge_core::key::derive_transit_address(
+        public_key,
+        chain_code,
+        state.network(),
+        eth_address,
+        AddressType::P2wpkh,
+    )
+}
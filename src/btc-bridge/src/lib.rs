@@ -1,6 +1,9 @@
 pub mod canister;
 pub mod ckbtc_client;
+pub mod constants;
+pub mod core;
 pub mod interface;
+pub mod key;
 pub mod memory;
 pub mod ops;
 pub mod state;
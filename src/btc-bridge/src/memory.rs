@@ -6,3 +6,6 @@ use ic_stable_structures::MemoryId;
 
 pub const BTC_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(100);
 pub const WRAPPED_TOKEN_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(101);
+pub const MASTER_KEY_MEMORY_ID: MemoryId = MemoryId::new(102);
+pub const DEPOSITED_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(103);
+pub const USED_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(104);
@@ -4,6 +4,7 @@ mod mint_tx_handler;
 
 use std::cell::RefCell;
 
+use bitcoin::hashes::Hash as _;
 use bridge_canister::bridge::{Operation, OperationContext, OperationProgress};
 use bridge_canister::runtime::RuntimeState;
 use bridge_canister::runtime::service::ServiceId;
@@ -11,10 +12,10 @@ use bridge_did::error::{BTFResult, Error};
 use bridge_did::event_data::*;
 use bridge_did::id256::Id256;
 use bridge_did::op_id::OperationId;
-use bridge_did::operations::BtcBridgeOp;
+use bridge_did::operations::{BtcBridgeOp, BtcWithdrawStatus, DidTransaction};
 use bridge_did::order::{MintOrder, SignedOrders};
 use candid::{CandidType, Principal};
-use did::H160;
+use did::{H160, H256};
 use ic_canister::virtual_canister_call;
 use ic_exports::ic_kit::ic;
 use ic_exports::icrc_types::icrc1::account::Account as IcrcAccount;
@@ -29,9 +30,11 @@ pub use self::mint_order_handler::BtcMintOrderHandler;
 pub use self::mint_tx_handler::BtcMintTxHandler;
 use crate::canister::{eth_address_to_subaccount, get_state};
 use crate::ckbtc_client::{
-    CkBtcLedgerClient, CkBtcMinterClient, RetrieveBtcError, RetrieveBtcOk, UpdateBalanceError,
-    UtxoStatus,
+    CkBtcLedgerClient, CkBtcMinterClient, RetrieveBtcError, RetrieveBtcOk, RetrieveBtcStatusV2,
+    UpdateBalanceError, UtxoStatus,
 };
+use crate::core::deposit::BtcDeposit;
+use crate::core::withdrawal::BtcWithdrawal;
 use crate::interface::{BtcBridgeError, BtcWithdrawError};
 use crate::state::State;
 
@@ -52,14 +55,14 @@ impl Operation for BtcBridgeOpImpl {
         let next_step = match self.0 {
             BtcBridgeOp::UpdateCkBtcBalance { eth_address } => {
                 log::debug!("BtcBridgeOp::UpdateCkBtcBalance: Eth address {eth_address}");
-                let ckbtc_minter = get_state().borrow().ck_btc_minter();
+                let ckbtc_minter = get_state().borrow().ck_btc_minter()?;
                 Self::update_ckbtc_balance(ckbtc_minter, &eth_address).await?;
 
                 Ok(Self(BtcBridgeOp::CollectCkBtcBalance { eth_address }))
             }
             BtcBridgeOp::CollectCkBtcBalance { eth_address } => {
                 log::debug!("BtcBridgeOp::CollectCkBtcBalance: Eth address {eth_address}");
-                let ckbtc_ledger = get_state().borrow().ck_btc_ledger();
+                let ckbtc_ledger = get_state().borrow().ck_btc_ledger()?;
                 let ckbtc_balance = Self::collect_ckbtc_balance(ckbtc_ledger, &eth_address).await?;
 
                 Ok(Self(BtcBridgeOp::TransferCkBtc {
@@ -77,7 +80,7 @@ impl Operation for BtcBridgeOpImpl {
                 let (ckbtc_ledger, ckbtc_fee) = {
                     let state = get_state();
                     let state_ref = state.borrow();
-                    (state_ref.ck_btc_ledger(), state_ref.ck_btc_ledger_fee())
+                    (state_ref.ck_btc_ledger()?, state_ref.ck_btc_ledger_fee())
                 };
 
                 let amount_minus_fee =
@@ -89,6 +92,15 @@ impl Operation for BtcBridgeOpImpl {
                     amount: amount_minus_fee,
                 }))
             }
+            BtcBridgeOp::CollectBtcUtxos { eth_address } => {
+                log::debug!("BtcBridgeOp::CollectBtcUtxos: Eth address {eth_address}");
+                let amount = BtcDeposit::get()?.collect_utxos(&eth_address).await?;
+
+                Ok(Self(BtcBridgeOp::CreateMintOrder {
+                    eth_address,
+                    amount,
+                }))
+            }
             BtcBridgeOp::CreateMintOrder {
                 eth_address,
                 amount,
@@ -117,17 +129,122 @@ impl Operation for BtcBridgeOpImpl {
             BtcBridgeOp::Erc20MintConfirmed { .. } => Err(Error::FailedToProgress(
                 "BtcBridgeOp::Erc20MintConfirmed task should not progress".into(),
             )),
+            BtcBridgeOp::WithdrawBtc(event) if get_state().borrow().is_self_custody() => {
+                log::debug!(
+                    "BtcBridgeOp::WithdrawBtc: self-custody withdrawal for Eth address {}",
+                    event.sender
+                );
+                match BtcWithdrawal::get()?
+                    .create_withdrawal_transaction(&event)
+                    .await
+                {
+                    Ok(tx) => Ok(Self(BtcBridgeOp::SendBtcWithdrawalTx {
+                        event,
+                        tx: DidTransaction::from(tx),
+                    })),
+                    Err(BtcWithdrawError::AmountTooLow(amount)) => {
+                        log::debug!(
+                            "Withdrawal amount {amount} is too low to pay the fee, refunding it to {}",
+                            event.sender
+                        );
+                        Ok(Self(BtcBridgeOp::CreateMintOrder {
+                            eth_address: event.sender,
+                            amount,
+                        }))
+                    }
+                    Err(err) => Err(err.into()),
+                }
+            }
+            BtcBridgeOp::SendBtcWithdrawalTx { event, tx } => {
+                log::debug!(
+                    "BtcBridgeOp::SendBtcWithdrawalTx: Eth address {}, tx {}",
+                    event.sender,
+                    tx.0.txid()
+                );
+                // The transaction is signed, so it may be mined at any moment even if sending it
+                // reported an error. Its inputs are never released and the burnt amount is never
+                // refunded: the task is retried until the transaction is accepted.
+                BtcWithdrawal::get()?.send_transaction(&tx.0).await?;
+
+                Ok(Self(BtcBridgeOp::BtcWithdrawConfirmed {
+                    eth_address: event.sender,
+                    block_index: None,
+                    status: Some(BtcWithdrawStatus::Submitted {
+                        txid: H256::from_slice(tx.0.txid().as_byte_array()),
+                    }),
+                }))
+            }
             BtcBridgeOp::WithdrawBtc(event) => {
                 log::debug!("BtcBridgeOp::WithdrawBtc: Eth address {}", event.sender);
-                Self::withdraw_btc(&event).await?;
+                let RetrieveBtcOk { block_index } = Self::withdraw_btc(&event).await?;
 
-                Ok(Self(BtcBridgeOp::BtcWithdrawConfirmed {
+                Ok(Self(BtcBridgeOp::WaitForBtcWithdrawal {
+                    event,
+                    block_index,
+                    status: BtcWithdrawStatus::Pending,
+                }))
+            }
+            BtcBridgeOp::WaitForBtcWithdrawal {
+                event,
+                block_index,
+                status: BtcWithdrawStatus::Reimbursed { amount, .. },
+            } => {
+                log::debug!(
+                    "BtcBridgeOp::WaitForBtcWithdrawal: withdrawal {block_index} reimbursed, refunding {amount} to {}",
+                    event.sender
+                );
+
+                Ok(Self(BtcBridgeOp::CreateMintOrder {
                     eth_address: event.sender,
+                    amount,
                 }))
             }
+            BtcBridgeOp::WaitForBtcWithdrawal {
+                event,
+                block_index,
+                status,
+            } => {
+                log::debug!(
+                    "BtcBridgeOp::WaitForBtcWithdrawal: block index {block_index}, status {status:?}"
+                );
+                let ckbtc_minter = get_state().borrow().ck_btc_minter()?;
+                let new_status = Self::get_btc_withdrawal_status(ckbtc_minter, block_index).await?;
+
+                match new_status {
+                    BtcWithdrawStatus::Confirmed { .. } => {
+                        Ok(Self(BtcBridgeOp::BtcWithdrawConfirmed {
+                            eth_address: event.sender,
+                            block_index: Some(block_index),
+                            status: Some(new_status),
+                        }))
+                    }
+                    BtcWithdrawStatus::AmountTooLow => {
+                        log::warn!(
+                            "Withdrawal {block_index} of {} failed: amount too low",
+                            event.sender
+                        );
+                        Ok(Self(BtcBridgeOp::BtcWithdrawFailed {
+                            eth_address: event.sender,
+                            block_index,
+                            status: new_status,
+                        }))
+                    }
+                    new_status if new_status == status => {
+                        Err(BtcWithdrawError::WithdrawalInProgress(block_index).into())
+                    }
+                    new_status => Ok(Self(BtcBridgeOp::WaitForBtcWithdrawal {
+                        event,
+                        block_index,
+                        status: new_status,
+                    })),
+                }
+            }
             BtcBridgeOp::BtcWithdrawConfirmed { .. } => Err(Error::FailedToProgress(
                 "BtcBridgeOp::BtcWithdrawConfirmed task should not progress".into(),
             )),
+            BtcBridgeOp::BtcWithdrawFailed { .. } => Err(Error::FailedToProgress(
+                "BtcBridgeOp::BtcWithdrawFailed task should not progress".into(),
+            )),
         };
 
         Ok(OperationProgress::Progress(next_step?))
@@ -138,20 +255,26 @@ impl Operation for BtcBridgeOpImpl {
             BtcBridgeOp::UpdateCkBtcBalance { .. } => false,
             BtcBridgeOp::CollectCkBtcBalance { .. } => false,
             BtcBridgeOp::TransferCkBtc { .. } => false,
+            BtcBridgeOp::CollectBtcUtxos { .. } => false,
             BtcBridgeOp::CreateMintOrder { .. } => false,
             BtcBridgeOp::SignMintOrder { .. } => false,
             BtcBridgeOp::MintErc20 { .. } => false,
             BtcBridgeOp::WaitForErc20MintConfirm { .. } => false,
             BtcBridgeOp::Erc20MintConfirmed { .. } => true,
             BtcBridgeOp::WithdrawBtc { .. } => false,
+            BtcBridgeOp::SendBtcWithdrawalTx { .. } => false,
+            BtcBridgeOp::WaitForBtcWithdrawal { .. } => false,
             BtcBridgeOp::BtcWithdrawConfirmed { .. } => true,
+            BtcBridgeOp::BtcWithdrawFailed { .. } => true,
         }
     }
 
     fn evm_wallet_address(&self) -> H160 {
         match &self.0 {
-            BtcBridgeOp::BtcWithdrawConfirmed { eth_address } => eth_address.clone(),
+            BtcBridgeOp::BtcWithdrawConfirmed { eth_address, .. } => eth_address.clone(),
+            BtcBridgeOp::BtcWithdrawFailed { eth_address, .. } => eth_address.clone(),
             BtcBridgeOp::CollectCkBtcBalance { eth_address } => eth_address.clone(),
+            BtcBridgeOp::CollectBtcUtxos { eth_address } => eth_address.clone(),
             BtcBridgeOp::CreateMintOrder { eth_address, .. } => eth_address.clone(),
             BtcBridgeOp::WaitForErc20MintConfirm { order, .. } => order.reader().get_recipient(),
             BtcBridgeOp::Erc20MintConfirmed(MintedEventData { recipient, .. }) => recipient.clone(),
@@ -160,6 +283,8 @@ impl Operation for BtcBridgeOpImpl {
             BtcBridgeOp::TransferCkBtc { eth_address, .. } => eth_address.clone(),
             BtcBridgeOp::UpdateCkBtcBalance { eth_address } => eth_address.clone(),
             BtcBridgeOp::WithdrawBtc(BurntEventData { sender, .. }) => sender.clone(),
+            BtcBridgeOp::SendBtcWithdrawalTx { event, .. } => event.sender.clone(),
+            BtcBridgeOp::WaitForBtcWithdrawal { event, .. } => event.sender.clone(),
         }
     }
 
@@ -183,7 +308,30 @@ impl Operation for BtcBridgeOpImpl {
                         multiplier: 4,
                     }),
             ),
+            // A signed withdrawal transaction can't be cancelled, so it is re-broadcast until
+            // the Bitcoin canister accepts it.
+            BtcBridgeOp::SendBtcWithdrawalTx { .. } => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(u32::MAX)
+                    .with_backoff_policy(BackoffPolicy::Fixed { secs: 60 }),
+            ),
+            // Deposited utxos need several blocks to get enough confirmations, so the deposit
+            // address is checked every 10 minutes for about 6 hours.
+            BtcBridgeOp::CollectBtcUtxos { .. } => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(36)
+                    .with_backoff_policy(BackoffPolicy::Fixed { secs: 600 }),
+            ),
+            // Bitcoin transactions may take hours or days to be confirmed, so the status is
+            // polled until the request is finalized by the minter. Each status change starts
+            // a new polling task.
+            BtcBridgeOp::WaitForBtcWithdrawal { .. } => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(u32::MAX)
+                    .with_backoff_policy(BackoffPolicy::Fixed { secs: 60 }),
+            ),
             BtcBridgeOp::BtcWithdrawConfirmed { .. }
+            | BtcBridgeOp::BtcWithdrawFailed { .. }
             | BtcBridgeOp::WaitForErc20MintConfirm { .. }
             | BtcBridgeOp::Erc20MintConfirmed(_) => None,
         }
@@ -336,18 +484,22 @@ impl BtcBridgeOpImpl {
     }
 
     /// Withdraw BTC from the bridge to the recipient address.
-    async fn withdraw_btc(event: &BurntEventData) -> BTFResult<()> {
+    async fn withdraw_btc(event: &BurntEventData) -> BTFResult<RetrieveBtcOk> {
         let state = get_state();
 
         let Ok(address) = String::from_utf8(event.recipient_id.clone()) else {
             return Err(BtcWithdrawError::InvalidRecipient(event.recipient_id.clone()).into());
         };
 
-        let amount: u64 = event.amount.0.to();
+        let amount: u64 = event
+            .amount
+            .0
+            .try_into()
+            .map_err(|_| BtcWithdrawError::AmountTooBig(event.amount.0.to_string()))?;
         log::trace!("Transferring {amount} ckBTC to {address}");
 
-        let ck_btc_ledger = state.borrow().ck_btc_ledger();
-        let ck_btc_minter = state.borrow().ck_btc_minter();
+        let ck_btc_ledger = state.borrow().ck_btc_ledger()?;
+        let ck_btc_minter = state.borrow().ck_btc_minter()?;
         let fee = state.borrow().ck_btc_ledger_fee();
         log::trace!("Transferring {amount} ckBTC to {address} with fee {fee}");
 
@@ -363,9 +515,7 @@ impl BtcBridgeOpImpl {
 
         Self::transfer_ckbtc_to_minter(ck_btc_ledger, account, to_transfer, fee).await?;
 
-        Self::request_btc_withdrawal(ck_btc_minter, address.to_string(), to_transfer).await?;
-
-        Ok(())
+        Self::request_btc_withdrawal(ck_btc_minter, address.to_string(), to_transfer).await
     }
 
     /// Prepare mint order for the given Ethereum address.
@@ -382,7 +532,7 @@ impl BtcBridgeOpImpl {
 
         let sender_chain_id = state_ref.btc_chain_id();
         let sender = Id256::from_evm_address(&eth_address, sender_chain_id);
-        let src_token = (&state_ref.ck_btc_ledger()).into();
+        let src_token = state_ref.base_token_id();
 
         let recipient_chain_id = ctx.get_evm_params()?.chain_id;
 
@@ -479,6 +629,28 @@ impl BtcBridgeOpImpl {
         Ok(result)
     }
 
+    /// Get the status of the BTC withdrawal request identified by the ckBTC burn block index.
+    async fn get_btc_withdrawal_status(
+        ckbtc_minter: Principal,
+        block_index: u64,
+    ) -> BTFResult<BtcWithdrawStatus> {
+        log::trace!("Requesting status of btc withdrawal {block_index}");
+
+        let status = CkBtcMinterClient::from(ckbtc_minter)
+            .retrieve_btc_status_v2(block_index)
+            .await
+            .map_err(|err| {
+                log::error!("Failed to call retrieve_btc_status_v2: {err:?}");
+                BtcWithdrawError::from(RetrieveBtcError::TemporarilyUnavailable(
+                    "retrieve_btc_status_v2 call failed".to_string(),
+                ))
+            })?;
+
+        log::trace!("Status of btc withdrawal {block_index}: {status:?}");
+
+        withdraw_status_from_minter(block_index, status)
+    }
+
     pub fn get_signed_mint_order(&self) -> Option<SignedOrders> {
         match &self.0 {
             BtcBridgeOp::WaitForErc20MintConfirm { order, .. } => Some(order.clone()),
@@ -488,6 +660,37 @@ impl BtcBridgeOpImpl {
     }
 }
 
+/// Converts the ckBTC minter withdrawal status into the bridge one.
+fn withdraw_status_from_minter(
+    block_index: u64,
+    status: RetrieveBtcStatusV2,
+) -> BTFResult<BtcWithdrawStatus> {
+    let status = match status {
+        RetrieveBtcStatusV2::Unknown => {
+            return Err(BtcWithdrawError::UnknownWithdrawal(block_index).into());
+        }
+        RetrieveBtcStatusV2::Pending
+        | RetrieveBtcStatusV2::Signing
+        | RetrieveBtcStatusV2::WillReimburse(_) => BtcWithdrawStatus::Pending,
+        RetrieveBtcStatusV2::Sending { txid } | RetrieveBtcStatusV2::Submitted { txid } => {
+            BtcWithdrawStatus::Submitted {
+                txid: H256::from_slice(&txid.0),
+            }
+        }
+        RetrieveBtcStatusV2::Confirmed { txid } => BtcWithdrawStatus::Confirmed {
+            txid: H256::from_slice(&txid.0),
+        },
+        RetrieveBtcStatusV2::AmountTooLow => BtcWithdrawStatus::AmountTooLow,
+        RetrieveBtcStatusV2::Reimbursed(deposit) => BtcWithdrawStatus::Reimbursed {
+            amount: deposit.amount,
+            mint_block_index: deposit.mint_block_index,
+            reason: format!("{:?}", deposit.reason),
+        },
+    };
+
+    Ok(status)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -496,6 +699,7 @@ mod test {
     use ic_task_scheduler::task::{InnerScheduledTask, ScheduledTask, TaskStatus};
 
     use super::*;
+    use crate::ckbtc_client::{ReimbursedDeposit, ReimbursementReason, Txid};
 
     #[test]
     fn test_should_store_task() {
@@ -514,4 +718,45 @@ mod test {
         let _deserialize: InnerScheduledTask<BridgeTask<BtcBridgeOpImpl>> =
             InnerScheduledTask::from_bytes(bytes);
     }
+
+    #[test]
+    fn test_should_convert_withdraw_status() {
+        let txid = Txid([1; 32]);
+
+        assert!(withdraw_status_from_minter(1, RetrieveBtcStatusV2::Unknown).is_err());
+        assert_eq!(
+            withdraw_status_from_minter(1, RetrieveBtcStatusV2::Signing).unwrap(),
+            BtcWithdrawStatus::Pending
+        );
+        assert_eq!(
+            withdraw_status_from_minter(1, RetrieveBtcStatusV2::Sending { txid }).unwrap(),
+            BtcWithdrawStatus::Submitted {
+                txid: H256::from_slice(&[1; 32])
+            }
+        );
+        assert_eq!(
+            withdraw_status_from_minter(1, RetrieveBtcStatusV2::Confirmed { txid }).unwrap(),
+            BtcWithdrawStatus::Confirmed {
+                txid: H256::from_slice(&[1; 32])
+            }
+        );
+
+        let reimbursed = RetrieveBtcStatusV2::Reimbursed(ReimbursedDeposit {
+            account: IcrcAccount {
+                owner: Principal::anonymous(),
+                subaccount: None,
+            },
+            amount: 1000,
+            reason: ReimbursementReason::CallFailed,
+            mint_block_index: 42,
+        });
+        assert_eq!(
+            withdraw_status_from_minter(1, reimbursed).unwrap(),
+            BtcWithdrawStatus::Reimbursed {
+                amount: 1000,
+                mint_block_index: 42,
+                reason: "CallFailed".to_string(),
+            }
+        );
+    }
 }
@@ -6,6 +6,7 @@ use bridge_did::reason::BtcDeposit;
 use candid::Decode;
 
 use super::BtcBridgeOpImpl;
+use crate::canister::get_state;
 
 pub struct BtcEventsHandler;
 
@@ -51,9 +52,12 @@ impl BtfBridgeEventHandler<BtcBridgeOpImpl> for BtcEventsHandler {
 
         let memo = event.memo();
 
-        let op = BtcBridgeOpImpl(BtcBridgeOp::UpdateCkBtcBalance {
-            eth_address: btc_deposit.recipient,
-        });
+        let eth_address = btc_deposit.recipient;
+        let op = if get_state().borrow().is_self_custody() {
+            BtcBridgeOpImpl(BtcBridgeOp::CollectBtcUtxos { eth_address })
+        } else {
+            BtcBridgeOpImpl(BtcBridgeOp::UpdateCkBtcBalance { eth_address })
+        };
         Some(OperationAction::Create(op, memo))
     }
 }
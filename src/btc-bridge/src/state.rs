@@ -1,22 +1,46 @@
-use bridge_canister::memory::memory_by_id;
-use bridge_did::init::btc::{BitcoinConnection, WrappedTokenConfig};
+use std::time::Duration;
+
+use bitcoin::bip32::ChainCode;
+use bitcoin::{FeeRate, Network, PrivateKey, PublicKey};
+use bitcoin_bridge_core::fee_rate::FeeRateState;
+use bitcoin_bridge_core::ledger::UtxoLedger;
+pub use bitcoin_bridge_core::master_key::MasterKey;
+use bitcoin_bridge_core::master_key::MasterKeyStorage;
+use bridge_canister::memory::{MEMORY_MANAGER, memory_by_id};
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::id256::Id256;
+use bridge_did::init::FeeEstimatorConfig;
+use bridge_did::init::btc::{BitcoinConnection, SelfCustodyConfig, WrappedTokenConfig};
+use bridge_utils::fee_estimator::IcFeeEstimator;
 use candid::Principal;
 use did::H160;
+use eth_signer::sign_strategy::SigningStrategy;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use ic_exports::ic_cdk::api::management_canister::ecdsa::{
+    EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyResponse,
+};
+use ic_exports::ic_kit::ic;
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{CellStructure, StableCell, VirtualMemory};
 
-use crate::memory::{BTC_CONFIG_MEMORY_ID, WRAPPED_TOKEN_CONFIG_MEMORY_ID};
+use crate::key::{AddressType, BtcSignerType, IcEcdsaSigner, LocalBtcSigner};
+use crate::memory::{
+    BTC_CONFIG_MEMORY_ID, DEPOSITED_UTXOS_MEMORY_ID, MASTER_KEY_MEMORY_ID, USED_UTXOS_MEMORY_ID,
+    WRAPPED_TOKEN_CONFIG_MEMORY_ID,
+};
 use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
 
 pub struct State {
     pub btc_config: StableCell<BitcoinConnection, VirtualMemory<DefaultMemoryImpl>>,
     pub wrapped_token_config: StableCell<WrappedTokenConfig, VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) master_key: MasterKeyStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) ledger: UtxoLedger<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) fee_rate_state: FeeRateState,
 }
 
 impl Default for State {
     fn default() -> Self {
-        Self {
+        MEMORY_MANAGER.with(|memory_manager| Self {
             wrapped_token_config: StableCell::new(
                 memory_by_id(WRAPPED_TOKEN_CONFIG_MEMORY_ID),
                 WrappedTokenConfig::default(),
@@ -27,12 +51,26 @@ impl Default for State {
                 BitcoinConnection::default(),
             )
             .expect("stable memory config initialization failed"),
-        }
+            master_key: MasterKeyStorage::new(memory_manager, MASTER_KEY_MEMORY_ID),
+            ledger: UtxoLedger::new(
+                memory_manager,
+                DEPOSITED_UTXOS_MEMORY_ID,
+                USED_UTXOS_MEMORY_ID,
+            ),
+            fee_rate_state: FeeRateState::default(),
+        })
     }
 }
 
 impl State {
     pub fn configure_btc(&mut self, config: BitcoinConnection) {
+        let fee_estimator = config
+            .self_custody()
+            .and_then(|config| config.fee_estimator);
+        if let Some(Err(err)) = fee_estimator.as_ref().map(FeeEstimatorConfig::validate) {
+            panic!("Invalid fee estimator configuration: {err}");
+        }
+
         self.btc_config.set(config).expect("failed to set config");
     }
 
@@ -42,16 +80,37 @@ impl State {
             .expect("failed to set wrapped token config");
     }
 
-    pub fn ck_btc_minter(&self) -> Principal {
+    pub fn ck_btc_minter(&self) -> BTFResult<Principal> {
         self.with_btc_config(|config| config.ckbtc_minter())
+            .ok_or_else(Self::ckbtc_not_used_error)
     }
 
-    pub fn ck_btc_ledger(&self) -> Principal {
+    pub fn ck_btc_ledger(&self) -> BTFResult<Principal> {
         self.with_btc_config(|config| config.ckbtc_ledger())
+            .ok_or_else(Self::ckbtc_not_used_error)
+    }
+
+    /// Returns the self-custody configuration if the bridge holds BTC itself.
+    pub fn self_custody(&self) -> Option<SelfCustodyConfig> {
+        self.with_btc_config(|config| config.self_custody())
+    }
+
+    /// Returns true if the bridge holds BTC itself instead of using ckBTC.
+    pub fn is_self_custody(&self) -> bool {
+        self.self_custody().is_some()
+    }
+
+    /// Id of the base token of the wrapped BTC: ckBTC ledger or the bridge canister itself in
+    /// self-custody mode.
+    pub fn base_token_id(&self) -> Id256 {
+        match self.with_btc_config(|config| config.ckbtc_ledger()) {
+            Some(ledger) => (&ledger).into(),
+            None => (&ic::id()).into(),
+        }
     }
 
     pub fn btc_chain_id(&self) -> u32 {
-        match self.with_btc_config(|config| config.network()) {
+        match self.ic_btc_network() {
             BitcoinNetwork::Mainnet => MAINNET_CHAIN_ID,
             BitcoinNetwork::Testnet => TESTNET_CHAIN_ID,
             BitcoinNetwork::Regtest => REGTEST_CHAIN_ID,
@@ -78,6 +137,146 @@ impl State {
         self.with_wrapped_token_config(|config| config.decimals)
     }
 
+    /// Returns BTC network the canister works with (IC style).
+    pub fn ic_btc_network(&self) -> BitcoinNetwork {
+        self.with_btc_config(|config| config.network())
+    }
+
+    /// Returns BTC network the canister works with (BTC style).
+    pub fn network(&self) -> Network {
+        match self.ic_btc_network() {
+            BitcoinNetwork::Mainnet => Network::Bitcoin,
+            BitcoinNetwork::Testnet => Network::Testnet,
+            BitcoinNetwork::Regtest => Network::Regtest,
+        }
+    }
+
+    /// Minimum number of confirmations the self-custody bridge requires to accept a deposit.
+    pub fn min_confirmations(&self) -> u32 {
+        self.self_custody()
+            .map(|config| config.min_confirmations)
+            .unwrap_or_default()
+    }
+
+    pub fn utxo_cache_timeout(&self) -> Duration {
+        let timeout_secs = self
+            .self_custody()
+            .and_then(|config| config.btc_cache_timeout_secs)
+            .unwrap_or(0);
+
+        Duration::from_secs(timeout_secs as u64)
+    }
+
+    /// Returns id of the IC ECDSA key used by the canister.
+    pub fn ecdsa_key_id(&self, signing_strategy: &SigningStrategy) -> EcdsaKeyId {
+        let key_name = match signing_strategy {
+            SigningStrategy::Local { .. } => "none".to_string(),
+            SigningStrategy::ManagementCanister { key_id } => key_id.to_string(),
+        };
+
+        EcdsaKeyId {
+            curve: EcdsaCurve::Secp256k1,
+            name: key_name,
+        }
+    }
+
+    /// Returns master public key of the canister.
+    pub fn public_key(&self) -> Option<PublicKey> {
+        self.master_key
+            .get()
+            .as_ref()
+            .and_then(|key| key.public_key().ok())
+    }
+
+    /// Returns master chain code of the canister. Used for public key derivation.
+    pub fn chain_code(&self) -> Option<ChainCode> {
+        self.master_key.get().as_ref().map(|key| key.chain_code())
+    }
+
+    /// Master key of the canister.
+    fn master_key(&self) -> Option<MasterKey> {
+        self.master_key.get().clone()
+    }
+
+    pub fn btc_signer(&self, signing_strategy: &SigningStrategy) -> Option<BtcSignerType> {
+        Some(match signing_strategy {
+            SigningStrategy::Local { private_key } => BtcSignerType::Local(LocalBtcSigner::new(
+                PrivateKey::from_slice(private_key, self.network()).expect("invalid private key"),
+                AddressType::P2wpkh,
+            )),
+            SigningStrategy::ManagementCanister { .. } => {
+                BtcSignerType::Ic(IcEcdsaSigner::new(self.master_key()?, self.network()))
+            }
+        })
+    }
+
+    /// Updates the ecdsa signing configuration with the given master key information.
+    ///
+    /// This configuration is used to derive deposit addresses of the self-custody bridge, so it
+    /// must be set before any of the self-custody transactions can be processed.
+    pub fn configure_ecdsa(
+        &mut self,
+        master_key: EcdsaPublicKeyResponse,
+        key_id: EcdsaKeyId,
+    ) -> Result<(), String> {
+        let chain_code: [u8; 32] = master_key
+            .chain_code
+            .try_into()
+            .map_err(|e| format!("invalid chain code: {e:?}"))?;
+
+        let master_key = MasterKey::new(
+            PublicKey::from_slice(&master_key.public_key)
+                .map_err(|e| format!("invalid public key slice: {e}"))?,
+            ChainCode::from(chain_code),
+            key_id,
+        );
+
+        self.master_key.set(master_key);
+
+        Ok(())
+    }
+
+    /// Utxo ledger of the self-custody bridge.
+    pub fn ledger(&self) -> &UtxoLedger<VirtualMemory<DefaultMemoryImpl>> {
+        &self.ledger
+    }
+
+    /// Mutable reference to the utxo ledger of the self-custody bridge.
+    pub fn ledger_mut(&mut self) -> &mut UtxoLedger<VirtualMemory<DefaultMemoryImpl>> {
+        &mut self.ledger
+    }
+
+    /// Update fee rate and the last update timestamp.
+    pub fn update_fee_rate(&mut self, fee_rate: FeeRate) {
+        self.fee_rate_state.update(fee_rate);
+    }
+
+    /// Fee rate used by the canister.
+    pub fn fee_rate(&self) -> FeeRate {
+        self.fee_rate_state.fee_rate()
+    }
+
+    /// Elapsed time since the last fee rate update. (nano seconds)
+    pub fn last_fee_rate_update_elapsed(&self) -> Duration {
+        self.fee_rate_state.last_update_elapsed()
+    }
+
+    /// Returns the fee estimation configuration of the self-custody bridge.
+    pub fn fee_estimator_config(&self) -> FeeEstimatorConfig {
+        self.self_custody()
+            .and_then(|config| config.fee_estimator)
+            .unwrap_or_default()
+    }
+
+    /// Fee estimator to request the current fee rate with.
+    pub fn fee_estimator(&self) -> IcFeeEstimator {
+        IcFeeEstimator::new(self.ic_btc_network(), self.fee_estimator_config())
+    }
+
+    fn ckbtc_not_used_error() -> Error {
+        Error::Initialization("ckBTC is not used by the self-custody bridge".into())
+    }
+
     fn with_btc_config<F, T>(&self, f: F) -> T
     where
         F: FnOnce(&BitcoinConnection) -> T,
@@ -94,3 +293,35 @@ impl State {
         f(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    #[test]
+    fn test_should_use_self_custody_config() {
+        MockContext::new().inject();
+        let mut state = State::default();
+        assert!(!state.is_self_custody());
+        assert!(state.ck_btc_minter().is_ok());
+
+        state.configure_btc(BitcoinConnection::SelfCustody(SelfCustodyConfig {
+            network: BitcoinNetwork::Testnet,
+            min_confirmations: 3,
+            btc_cache_timeout_secs: Some(30),
+            fee_estimator: None,
+        }));
+
+        assert!(state.is_self_custody());
+        assert!(state.ck_btc_minter().is_err());
+        assert!(state.ck_btc_ledger().is_err());
+        assert_eq!(state.network(), Network::Testnet);
+        assert_eq!(state.btc_chain_id(), TESTNET_CHAIN_ID);
+        assert_eq!(state.min_confirmations(), 3);
+        assert_eq!(state.utxo_cache_timeout(), Duration::from_secs(30));
+        assert_eq!(state.fee_estimator_config(), FeeEstimatorConfig::default());
+        assert_eq!(state.base_token_id(), Id256::from(&ic::id()));
+    }
+}
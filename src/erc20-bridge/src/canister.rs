@@ -12,30 +12,34 @@ use bridge_canister::runtime::service::update_evm_params::RefreshEvmParamsServic
 use bridge_canister::runtime::state::SharedConfig;
 use bridge_canister::runtime::state::config::ConfigStorage;
 use bridge_canister::runtime::{BridgeRuntime, RuntimeState};
-use bridge_did::bridge_side::BridgeSide;
 use bridge_did::error::{BTFResult, Error};
+use bridge_did::evm_link::EvmLink;
+use bridge_did::id256::Id256;
 use bridge_did::init::BridgeInitData;
-use bridge_did::init::erc20::BaseEvmSettings;
+use bridge_did::init::erc20::EvmLinkSettings;
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::{Memo, OperationLog};
+use bridge_did::operations::Erc20TokenCapability;
 use bridge_utils::common::Pagination;
 use candid::Principal;
 use did::H160;
 use did::build::BuildData;
 use ic_canister::{Canister, Idl, PreUpdate, generate_idl, init, post_upgrade, query, update};
+use ic_exports::ic_kit::ic;
 use ic_log::canister::{LogCanister, LogState};
 use ic_metrics::{Metrics, MetricsStorage};
-use ic_stable_structures::StableCell;
+use ic_stable_structures::{CellStructure, StableCell};
 use ic_storage::IcStorage;
 
-use crate::memory::NONCE_COUNTER_MEMORY_ID;
+use crate::memory::{
+    MAX_MINT_ATTEMPTS_MEMORY_ID, NONCE_COUNTER_MEMORY_ID, TOKEN_CAPABILITIES_MEMORY_ID,
+};
 use crate::ops::events_handler::Erc20EventsHandler;
 use crate::ops::{
-    Erc20BridgeOpImpl, Erc20OrderHandler, Erc20ServiceSelector, FETCH_BASE_LOGS_SERVICE_ID,
-    FETCH_WRAPPED_LOGS_SERVICE_ID, REFRESH_BASE_PARAMS_SERVICE_ID,
-    REFRESH_WRAPPED_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID, SIGN_MINT_ORDER_SERVICE_ID,
+    Erc20BridgeOpImpl, Erc20OrderHandler, EvmLinkRouter, FETCH_LOGS_SERVICE_ID,
+    REFRESH_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID, SIGN_MINT_ORDER_SERVICE_ID,
 };
-use crate::state::SharedBaseEvmState;
+use crate::state::{EvmLinkState, SharedEvmLinks, TokenCapabilities};
 
 #[cfg(feature = "export-api")]
 pub mod inspect;
@@ -58,13 +62,23 @@ impl BridgeCanister for Erc20Bridge {
 
 impl Erc20Bridge {
     #[init]
-    pub fn init(&mut self, bridge_settings: BridgeInitData, base_evm_settings: BaseEvmSettings) {
-        get_base_evm_state().0.borrow_mut().reset(base_evm_settings);
+    pub fn init(&mut self, bridge_settings: BridgeInitData, evm_links: Vec<EvmLinkSettings>) {
+        for settings in evm_links {
+            get_evm_links()
+                .borrow_mut()
+                .add(settings)
+                .expect("failed to add evm link");
+        }
+        get_evm_links().borrow().init_state_version();
         self.init_bridge(bridge_settings, Self::run_scheduler);
     }
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        // The EVM links must be migrated before the runtime loads them.
+        if let Err(err) = get_evm_links().borrow_mut().migrate() {
+            ic::trap(&format!("Failed to migrate the state: {err}"));
+        }
         self.bridge_post_upgrade(Self::run_scheduler);
     }
 
@@ -73,15 +87,145 @@ impl Erc20Bridge {
         runtime.borrow_mut().run();
     }
 
+    /// Links the bridge to one more EVM. Mint orders with the `recipient_chain_id`
+    /// equal to the link chain id will be sent to this EVM.
     #[update]
-    fn set_base_btf_bridge_contract(&mut self, address: H160) {
+    pub fn add_evm_link(&mut self, settings: EvmLinkSettings) -> BTFResult<()> {
+        get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .check_owner(ic::caller())?;
+
+        let chain_id = settings.chain_id;
+        let link = get_evm_links().borrow_mut().add(settings)?;
+        get_evm_link_services().add_link(&get_runtime(), chain_id, link);
+
+        log::info!("Bridge canister linked to EVM with chain id {chain_id}");
+
+        Ok(())
+    }
+
+    /// Returns chain ids and links of the EVMs linked in addition to the main one.
+    #[query]
+    pub fn get_evm_links(&self) -> Vec<(u32, EvmLink)> {
+        get_evm_links()
+            .borrow()
+            .get_all()
+            .into_iter()
+            .map(|(chain_id, link)| (chain_id, link.config.borrow().get_evm_link()))
+            .collect()
+    }
+
+    #[update]
+    pub fn set_evm_link_btf_bridge_contract(
+        &mut self,
+        chain_id: u32,
+        address: H160,
+    ) -> BTFResult<()> {
         let config = get_runtime_state().borrow().config.clone();
         bridge_canister::inspect::inspect_set_btf_bridge_contract(config);
-        get_base_evm_config()
+        let link = get_evm_links()
+            .borrow()
+            .get(chain_id)
+            .ok_or(Error::ServiceNotFound)?;
+        link.config
             .borrow_mut()
             .set_btf_bridge_contract(address.clone());
 
-        log::info!("Bridge canister base EVM BTF bridge contract address changed to {address}");
+        log::info!(
+            "Bridge canister EVM {chain_id} BTF bridge contract address changed to {address}"
+        );
+
+        Ok(())
+    }
+
+    /// Registers known behaviour of the token on the given chain. Deposits of unsupported
+    /// tokens are refunded to the sender.
+    #[update]
+    pub fn set_token_capability(
+        &mut self,
+        chain_id: u32,
+        token: H160,
+        capability: Erc20TokenCapability,
+    ) -> BTFResult<()> {
+        get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .check_owner(ic::caller())?;
+
+        get_token_capabilities()
+            .borrow_mut()
+            .insert(Id256::from_evm_address(&token, chain_id), capability);
+
+        log::info!("Token {token} on chain {chain_id} capability set to {capability:?}");
+
+        Ok(())
+    }
+
+    /// Removes the token from the capabilities registry.
+    #[update]
+    pub fn remove_token_capability(&mut self, chain_id: u32, token: H160) -> BTFResult<()> {
+        get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .check_owner(ic::caller())?;
+
+        get_token_capabilities()
+            .borrow_mut()
+            .remove(&Id256::from_evm_address(&token, chain_id));
+
+        log::info!("Token {token} on chain {chain_id} removed from capabilities registry");
+
+        Ok(())
+    }
+
+    /// Returns all registered token capabilities as `(chain_id, token, capability)`.
+    #[query]
+    pub fn get_token_capabilities(&self) -> Vec<(u32, H160, Erc20TokenCapability)> {
+        get_token_capabilities()
+            .borrow()
+            .get_all()
+            .into_iter()
+            .filter_map(|(id, capability)| {
+                let (chain_id, token) = id.to_evm_address().ok()?;
+                Some((chain_id, token, capability))
+            })
+            .collect()
+    }
+
+    /// Sets the number of failed attempts to mint tokens on the destination EVM,
+    /// after which the tokens are refunded to the sender.
+    #[update]
+    pub fn set_max_mint_attempts(&mut self, attempts: u32) -> BTFResult<()> {
+        get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .check_owner(ic::caller())?;
+
+        if attempts == 0 {
+            return Err(Error::Initialization(
+                "max mint attempts should be greater than zero".into(),
+            ));
+        }
+
+        get_max_mint_attempts_cell()
+            .borrow_mut()
+            .set(attempts)
+            .expect("failed to update max mint attempts");
+
+        log::info!("Max mint attempts set to {attempts}");
+
+        Ok(())
+    }
+
+    /// Returns the number of failed attempts to mint tokens, after which the tokens are refunded.
+    #[query]
+    pub fn get_max_mint_attempts(&self) -> u32 {
+        get_max_mint_attempts()
     }
 
     /// Retrieves all operations for the given ETH wallet address whose
@@ -139,8 +283,12 @@ impl Erc20Bridge {
     }
 
     #[update]
-    pub async fn get_bridge_canister_base_evm_address(&self) -> BTFResult<H160> {
-        let signer = get_base_evm_config().borrow().get_signer()?;
+    pub async fn get_bridge_canister_evm_link_address(&self, chain_id: u32) -> BTFResult<H160> {
+        let link = get_evm_links()
+            .borrow()
+            .get(chain_id)
+            .ok_or(Error::ServiceNotFound)?;
+        let signer = link.config.borrow().get_signer()?;
         signer.get_address().await.map_err(|e| {
             Error::Initialization(format!("failed to get bridge canister address: {e}"))
         })
@@ -172,136 +320,189 @@ impl LogCanister for Erc20Bridge {
     }
 }
 
-fn init_runtime() -> SharedRuntime {
+/// Services of the EVM links. Each service routes operations by the chain id.
+pub struct EvmLinkServices {
+    refresh_params: Rc<EvmLinkRouter>,
+    fetch_logs: Rc<EvmLinkRouter>,
+    sign_orders: Rc<EvmLinkRouter>,
+    send_mint_tx: Rc<EvmLinkRouter>,
+}
+
+impl EvmLinkServices {
+    /// Creates services for the main EVM link and registers them in the runtime.
+    fn init(runtime: &SharedRuntime) -> Self {
+        let state = runtime.borrow().state().clone();
+        let scheduler = runtime.borrow().scheduler().clone();
+        let config = state.borrow().config.clone();
+
+        let refresh_params_service = RefreshEvmParamsService::new(config.clone());
+        let events_handler =
+            Erc20EventsHandler::new(get_mint_order_nonce_counter(), config.clone());
+        let fetch_logs_service =
+            FetchBtfBridgeEventsService::new(events_handler, runtime.clone(), config.clone());
+        let order_handler = Erc20OrderHandler::new(state.clone(), config.clone(), scheduler);
+        let sign_orders_service = SignMintOrdersService::new(order_handler.clone());
+        let send_mint_tx_service = SendMintTxService::new(order_handler);
+
+        let link_services = Self {
+            refresh_params: Rc::new(EvmLinkRouter::new(
+                config.clone(),
+                Rc::new(refresh_params_service),
+            )),
+            fetch_logs: Rc::new(EvmLinkRouter::new(
+                config.clone(),
+                Rc::new(fetch_logs_service),
+            )),
+            sign_orders: Rc::new(EvmLinkRouter::new(
+                config.clone(),
+                Rc::new(sign_orders_service),
+            )),
+            send_mint_tx: Rc::new(EvmLinkRouter::new(config, Rc::new(send_mint_tx_service))),
+        };
+
+        let services = state.borrow().services.clone();
+        services.borrow_mut().add_service(
+            ServiceOrder::BeforeOperations,
+            REFRESH_PARAMS_SERVICE_ID,
+            link_services.refresh_params.clone(),
+        );
+        services.borrow_mut().add_service(
+            ServiceOrder::BeforeOperations,
+            FETCH_LOGS_SERVICE_ID,
+            link_services.fetch_logs.clone(),
+        );
+        services.borrow_mut().add_service(
+            ServiceOrder::ConcurrentWithOperations,
+            SIGN_MINT_ORDER_SERVICE_ID,
+            link_services.sign_orders.clone(),
+        );
+        services.borrow_mut().add_service(
+            ServiceOrder::ConcurrentWithOperations,
+            SEND_MINT_TX_SERVICE_ID,
+            link_services.send_mint_tx.clone(),
+        );
+
+        link_services
+    }
+
+    /// Creates services for the EVM link with the given chain id.
+    pub fn add_link(&self, runtime: &SharedRuntime, chain_id: u32, link: EvmLinkState) {
+        let state = runtime.borrow().state().clone();
+        let scheduler = runtime.borrow().scheduler().clone();
+
+        log::trace!(
+            "initializing services for evm {chain_id} with params query interval {}s and log query interval {}s",
+            link.delays.evm_params_query.as_secs(),
+            link.delays.logs_query.as_secs(),
+        );
+
+        let refresh_params_service = ServiceTimer::new(
+            RefreshEvmParamsService::new(link.config.clone()),
+            link.delays.evm_params_query,
+        );
+        let events_handler =
+            Erc20EventsHandler::new(get_mint_order_nonce_counter(), link.config.clone());
+        let fetch_logs_service = ServiceTimer::new(
+            FetchBtfBridgeEventsService::new(events_handler, runtime.clone(), link.config.clone()),
+            link.delays.logs_query,
+        );
+        let order_handler = Erc20OrderHandler::new(state, link.config, scheduler);
+
+        self.refresh_params
+            .add_link(chain_id, Rc::new(refresh_params_service));
+        self.fetch_logs
+            .add_link(chain_id, Rc::new(fetch_logs_service));
+        self.sign_orders.add_link(
+            chain_id,
+            Rc::new(SignMintOrdersService::new(order_handler.clone())),
+        );
+        self.send_mint_tx
+            .add_link(chain_id, Rc::new(SendMintTxService::new(order_handler)));
+    }
+}
+
+fn init_runtime() -> (SharedRuntime, Rc<EvmLinkServices>) {
     let runtime = BridgeRuntime::default(ConfigStorage::get());
-    let base_state = get_base_evm_state();
-    let wrapped_state = runtime.state().clone();
-    let base_config = base_state.0.borrow().config.clone();
-    let wrapped_config = wrapped_state.borrow().config.clone();
-    let scheduler = runtime.scheduler().clone();
     let runtime = Rc::new(RefCell::new(runtime));
 
-    // Init refresh_evm_params services
-    let refresh_base_params_service = RefreshEvmParamsService::new(base_config.clone());
-    log::trace!(
-        "initializing evm params query service with interval: {}s",
-        base_state.query_delays().evm_params_query.as_secs()
-    );
-    let refresh_base_params_service_with_delay = ServiceTimer::new(
-        refresh_base_params_service,
-        base_state.query_delays().evm_params_query,
-    );
-    let refresh_wrapped_params_service = RefreshEvmParamsService::new(wrapped_config.clone());
-
-    // Init event listener services
-    let base_event_handler = Erc20EventsHandler::new(
-        get_mint_order_nonce_counter(),
-        BridgeSide::Base,
-        base_config.clone(),
-        wrapped_config.clone(),
-    );
-    let base_events_service =
-        FetchBtfBridgeEventsService::new(base_event_handler, runtime.clone(), base_config.clone());
-    log::trace!(
-        "initializing evm log query service with interval: {}s",
-        base_state.query_delays().logs_query.as_secs()
-    );
-    let base_events_service_with_delay =
-        ServiceTimer::new(base_events_service, base_state.query_delays().logs_query);
-    let wrapped_event_handler = Erc20EventsHandler::new(
-        get_mint_order_nonce_counter(),
-        BridgeSide::Wrapped,
-        wrapped_config.clone(),
-        base_config.clone(),
-    );
-    let wrapped_events_service = FetchBtfBridgeEventsService::new(
-        wrapped_event_handler,
-        runtime.clone(),
-        wrapped_config.clone(),
-    );
-
-    // Init operation handlers
-    let base_handler =
-        Erc20OrderHandler::new(wrapped_state.clone(), base_config, scheduler.clone());
-    let wrapped_handler =
-        Erc20OrderHandler::new(wrapped_state.clone(), wrapped_config, scheduler.clone());
-
-    // Init mint order signing service
-    let base_sign_service = SignMintOrdersService::new(base_handler.clone());
-    let wrapped_sign_service = SignMintOrdersService::new(wrapped_handler.clone());
-    let sign_service = Erc20ServiceSelector::new(base_sign_service, wrapped_sign_service);
-
-    // Init mint tx service
-    let base_mint_tx_service = SendMintTxService::new(base_handler);
-    let wrapped_mint_tx_service = SendMintTxService::new(wrapped_handler);
-    let send_mint_tx_service =
-        Erc20ServiceSelector::new(base_mint_tx_service, wrapped_mint_tx_service);
-
-    let services = wrapped_state.borrow().services.clone();
-    services.borrow_mut().add_service(
-        ServiceOrder::BeforeOperations,
-        REFRESH_BASE_PARAMS_SERVICE_ID,
-        Rc::new(refresh_base_params_service_with_delay),
-    );
-    services.borrow_mut().add_service(
-        ServiceOrder::BeforeOperations,
-        REFRESH_WRAPPED_PARAMS_SERVICE_ID,
-        Rc::new(refresh_wrapped_params_service),
-    );
-    services.borrow_mut().add_service(
-        ServiceOrder::BeforeOperations,
-        FETCH_BASE_LOGS_SERVICE_ID,
-        Rc::new(base_events_service_with_delay),
-    );
-    services.borrow_mut().add_service(
-        ServiceOrder::BeforeOperations,
-        FETCH_WRAPPED_LOGS_SERVICE_ID,
-        Rc::new(wrapped_events_service),
-    );
-    services.borrow_mut().add_service(
-        ServiceOrder::ConcurrentWithOperations,
-        SIGN_MINT_ORDER_SERVICE_ID,
-        Rc::new(sign_service),
-    );
-    services.borrow_mut().add_service(
-        ServiceOrder::ConcurrentWithOperations,
-        SEND_MINT_TX_SERVICE_ID,
-        Rc::new(send_mint_tx_service),
-    );
-
-    runtime
+    let link_services = EvmLinkServices::init(&runtime);
+    for (chain_id, link) in get_evm_links().borrow().get_all() {
+        link_services.add_link(&runtime, chain_id, link);
+    }
+
+    (runtime, Rc::new(link_services))
 }
 
 pub type SharedNonceCounter = Rc<RefCell<StableCell<u32, StableMemory>>>;
 
+pub type SharedTokenCapabilities = Rc<RefCell<TokenCapabilities<StableMemory>>>;
+
+pub type SharedMaxMintAttempts = Rc<RefCell<StableCell<u32, StableMemory>>>;
+
+/// Default number of failed mint attempts, after which the tokens are refunded.
+pub const DEFAULT_MAX_MINT_ATTEMPTS: u32 = 3;
+
 thread_local! {
-    pub static RUNTIME: SharedRuntime = init_runtime();
+    pub static RUNTIME: (SharedRuntime, Rc<EvmLinkServices>) = init_runtime();
 
-    pub static BASE_EVM_STATE: SharedBaseEvmState = SharedBaseEvmState::default();
+    pub static EVM_LINKS: SharedEvmLinks = SharedEvmLinks::default();
 
     pub static MINT_ORDER_NONCE_COUNTER: SharedNonceCounter =
         Rc::new(RefCell::new(
             StableCell::new(memory_by_id(NONCE_COUNTER_MEMORY_ID), 0)
                 .expect("failed to initialize nonce counter StableCell")
         ));
+
+    pub static TOKEN_CAPABILITIES: SharedTokenCapabilities =
+        Rc::new(RefCell::new(TokenCapabilities::new(memory_by_id(TOKEN_CAPABILITIES_MEMORY_ID))));
+
+    pub static MAX_MINT_ATTEMPTS: SharedMaxMintAttempts =
+        Rc::new(RefCell::new(
+            StableCell::new(memory_by_id(MAX_MINT_ATTEMPTS_MEMORY_ID), DEFAULT_MAX_MINT_ATTEMPTS)
+                .expect("failed to initialize max mint attempts StableCell")
+        ));
 }
 
 pub fn get_runtime() -> SharedRuntime {
-    RUNTIME.with(|r| r.clone())
+    RUNTIME.with(|r| r.0.clone())
+}
+
+pub fn get_evm_link_services() -> Rc<EvmLinkServices> {
+    RUNTIME.with(|r| r.1.clone())
 }
 
 pub fn get_runtime_state() -> RuntimeState<Erc20BridgeOpImpl> {
     get_runtime().borrow().state().clone()
 }
 
-pub fn get_base_evm_state() -> SharedBaseEvmState {
-    BASE_EVM_STATE.with(|s| s.clone())
+pub fn get_evm_links() -> SharedEvmLinks {
+    EVM_LINKS.with(|l| l.clone())
 }
 
-pub fn get_base_evm_config() -> Rc<RefCell<ConfigStorage>> {
-    BASE_EVM_STATE.with(|s| s.0.borrow().config.clone())
+/// Returns config of the EVM link with the given chain id. The main EVM link
+/// can be found only after its params are initialized.
+pub fn get_evm_link_config(chain_id: u32) -> Option<SharedConfig> {
+    if let Some(link) = get_evm_links().borrow().get(chain_id) {
+        return Some(link.config);
+    }
+
+    let main_config = get_runtime_state().borrow().config.clone();
+    let main_chain_id = main_config.borrow().get_evm_params().ok()?.chain_id as u32;
+    (main_chain_id == chain_id).then_some(main_config)
 }
 
 pub fn get_mint_order_nonce_counter() -> SharedNonceCounter {
     MINT_ORDER_NONCE_COUNTER.with(|c| c.clone())
 }
+
+pub fn get_token_capabilities() -> SharedTokenCapabilities {
+    TOKEN_CAPABILITIES.with(|c| c.clone())
+}
+
+fn get_max_mint_attempts_cell() -> SharedMaxMintAttempts {
+    MAX_MINT_ATTEMPTS.with(|c| c.clone())
+}
+
+pub fn get_max_mint_attempts() -> u32 {
+    *get_max_mint_attempts_cell().borrow().get()
+}
@@ -21,7 +21,11 @@ async fn inspect_message() {
 async fn inspect_method(method: &str) -> BTFResult<()> {
     let config = canister::get_runtime_state().borrow().config.clone();
     match method {
-        "set_base_btf_bridge_contract" => config.borrow().check_owner(ic::caller()),
+        "add_evm_link"
+        | "set_evm_link_btf_bridge_contract"
+        | "set_token_capability"
+        | "remove_token_capability"
+        | "set_max_mint_attempts" => config.borrow().check_owner(ic::caller()),
         _ => Ok(()),
     }
 }
@@ -1,5 +1,24 @@
 use ic_stable_structures::MemoryId;
 
-pub const BASE_EVM_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const MAX_MINT_ATTEMPTS_MEMORY_ID: MemoryId = MemoryId::new(16);
 pub const NONCE_COUNTER_MEMORY_ID: MemoryId = MemoryId::new(11);
-pub const DELAYS_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const TOKEN_CAPABILITIES_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const EVM_LINKS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const STATE_VERSION_MEMORY_ID: MemoryId = MemoryId::new(15);
+
+/// Memories of the base EVM config and query delays of the bridge with a single base EVM.
+/// They are read only by the state migration.
+pub const LEGACY_BASE_EVM_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const LEGACY_DELAYS_MEMORY_ID: MemoryId = MemoryId::new(12);
+
+/// Memory ids starting from this one are used to store configs of the linked EVMs.
+pub const EVM_LINK_CONFIG_FIRST_MEMORY_ID: u8 = 32;
+
+/// Max number of EVM links, each of which stores its config in a separate memory.
+pub const MAX_EVM_LINKS: u8 = 64;
+
+/// Returns id of the memory to store config of the EVM link in the given slot.
+pub fn evm_link_config_memory_id(slot: u8) -> MemoryId {
+    assert!(slot < MAX_EVM_LINKS, "evm link slot out of range");
+    MemoryId::new(EVM_LINK_CONFIG_FIRST_MEMORY_ID + slot)
+}
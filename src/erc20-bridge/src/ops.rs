@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
 use bridge_canister::bridge::{Operation, OperationProgress};
 use bridge_canister::memory::StableMemory;
 use bridge_canister::runtime::RuntimeState;
@@ -6,30 +10,36 @@ use bridge_canister::runtime::service::mint_tx::{MintTxHandler, MintTxResult};
 use bridge_canister::runtime::service::sign_orders::MintOrderHandler;
 use bridge_canister::runtime::service::{BridgeService, ServiceId};
 use bridge_canister::runtime::state::SharedConfig;
-use bridge_did::bridge_side::BridgeSide;
+use bridge_canister::runtime::state::config::ConfigStorage;
+use bridge_did::batch_mint_result::BatchMintErrorCode;
 use bridge_did::error::{BTFResult, Error};
 use bridge_did::id256::Id256;
 use bridge_did::op_id::OperationId;
 use bridge_did::operations::{Erc20BridgeOp, Erc20OpStage};
 use bridge_did::order::{MintOrder, SignedOrders};
+use bridge_utils::{btf_events, token_transfers};
 use candid::CandidType;
-use did::H160;
+use did::block::ExeResult;
+use did::{H160, H256, U256};
 use eth_signer::sign_strategy::TxSigner;
+use ic_task_scheduler::retry::BackoffPolicy;
 use ic_task_scheduler::scheduler::TaskScheduler;
 use ic_task_scheduler::task::{ScheduledTask, TaskOptions};
 use serde::{Deserialize, Serialize};
 
-use crate::canister::get_runtime_state;
+use crate::canister::{get_evm_link_config, get_max_mint_attempts, get_runtime_state};
 
 pub mod events_handler;
 
-pub const REFRESH_BASE_PARAMS_SERVICE_ID: ServiceId = 0;
-pub const REFRESH_WRAPPED_PARAMS_SERVICE_ID: ServiceId = 1;
-pub const FETCH_BASE_LOGS_SERVICE_ID: ServiceId = 2;
-pub const FETCH_WRAPPED_LOGS_SERVICE_ID: ServiceId = 3;
+// Service ids 0 and 2 were used by the base EVM services of the bridge with a single base EVM.
+pub const REFRESH_PARAMS_SERVICE_ID: ServiceId = 1;
+pub const FETCH_LOGS_SERVICE_ID: ServiceId = 3;
 pub const SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 4;
 pub const SEND_MINT_TX_SERVICE_ID: ServiceId = 5;
 
+/// Revert message of the mint order cancel transaction for the orders which are already minted.
+const MINTED_ORDER_CANCEL_REVERT_MESSAGE: &str = "Invalid nonce";
+
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub struct Erc20BridgeOpImpl(pub Erc20BridgeOp);
 
@@ -39,82 +49,126 @@ impl Operation for Erc20BridgeOpImpl {
         _id: OperationId,
         _ctx: RuntimeState<Self>,
     ) -> BTFResult<OperationProgress<Self>> {
-        let stage = Erc20OpStageImpl(self.0.stage);
-        let next_stage = match self.0.side {
-            BridgeSide::Base => stage.progress().await?,
-            BridgeSide::Wrapped => stage.progress().await?,
-        };
-
-        let progress = match next_stage {
-            OperationProgress::Progress(stage) => {
-                OperationProgress::Progress(Self(Erc20BridgeOp {
-                    side: self.0.side,
-                    stage: stage.0,
-                }))
+        let Erc20BridgeOp {
+            side,
+            chain_id,
+            stage,
+        } = self.0;
+
+        let stage = match stage {
+            Erc20OpStage::CancelMintOrder { order, reason } => {
+                log::debug!("ERC20OpStage::CancelMintOrder {order:?}: {reason}");
+                let tx_hash = send_cancel_mint_order_tx(&order).await?;
+                Erc20OpStage::WaitForMintOrderCancel {
+                    order,
+                    reason,
+                    tx_hash,
+                }
+            }
+            Erc20OpStage::WaitForMintOrderCancel {
+                order,
+                reason,
+                tx_hash,
+            } => {
+                log::debug!("ERC20OpStage::WaitForMintOrderCancel {tx_hash}");
+                match query_mint_order_cancel_result(&order, &tx_hash).await? {
+                    ExeResult::Success { .. } => {
+                        // The order can't be minted anymore, so the tokens are safe to refund.
+                        let refund = refund_op(&order, reason).ok_or_else(|| {
+                            Error::CannotProgress("failed to create refund order".into())
+                        })?;
+                        return Ok(OperationProgress::Progress(Self(refund)));
+                    }
+                    ExeResult::Revert { revert_message, .. } => {
+                        let revert_message = revert_message.unwrap_or_default();
+                        if revert_message.contains(MINTED_ORDER_CANCEL_REVERT_MESSAGE) {
+                            // The order is minted, so the operation is completed by the minted
+                            // event. The stage is polled until then.
+                            return Err(Error::FailedToProgress(format!(
+                                "mint order cancel tx {tx_hash} reverted, the order is minted"
+                            )));
+                        }
+
+                        log::warn!(
+                            "Mint order cancel tx {tx_hash} reverted: {revert_message}. Resending"
+                        );
+                        Erc20OpStage::CancelMintOrder { order, reason }
+                    }
+                    ExeResult::Halt { error, .. } => {
+                        log::warn!("Mint order cancel tx {tx_hash} halted: {error:?}. Resending");
+                        Erc20OpStage::CancelMintOrder { order, reason }
+                    }
+                }
             }
-            OperationProgress::AddToService(op_id) => OperationProgress::AddToService(op_id),
+            stage => match Erc20OpStageImpl(stage).progress().await? {
+                OperationProgress::Progress(stage) => stage.0,
+                OperationProgress::AddToService(op_id) => {
+                    return Ok(OperationProgress::AddToService(op_id));
+                }
+            },
         };
-        Ok(progress)
+
+        Ok(OperationProgress::Progress(Self(Erc20BridgeOp {
+            side,
+            chain_id,
+            stage,
+        })))
     }
 
     fn is_complete(&self) -> bool {
-        match self.0.stage {
+        match self.0.stage.mint_stage() {
+            Erc20OpStage::VerifyReceivedAmount { .. } => false,
             Erc20OpStage::SignMintOrder(_) => false,
             Erc20OpStage::SendMintTransaction(_) => false,
+            Erc20OpStage::RetryMintTransaction { .. } => false,
+            Erc20OpStage::CancelMintOrder { .. } => false,
+            Erc20OpStage::WaitForMintOrderCancel { .. } => false,
             Erc20OpStage::WaitForMintConfirm { .. } => false,
             Erc20OpStage::TokenMintConfirmed(_) => true,
+            Erc20OpStage::Refund { .. } => false,
         }
     }
 
     fn evm_wallet_address(&self) -> H160 {
-        match (self.0.side, &self.0.stage) {
-            // If withdrawal, then use sender address.
-            (BridgeSide::Base, Erc20OpStage::SignMintOrder(order)) => {
-                order.sender.to_evm_address().expect("evm address").1
-            }
-            (BridgeSide::Base, Erc20OpStage::SendMintTransaction(order)) => {
-                order
-                    .reader()
-                    .get_sender_id()
-                    .to_evm_address()
-                    .expect("evm address")
-                    .1
-            }
-            (BridgeSide::Base, Erc20OpStage::WaitForMintConfirm { order, .. }) => {
-                order
-                    .reader()
-                    .get_sender_id()
-                    .to_evm_address()
-                    .expect("evm address")
-                    .1
-            }
-            (BridgeSide::Base, Erc20OpStage::TokenMintConfirmed(event)) => {
-                Id256::from_slice(&event.sender_id)
-                    .and_then(|id| id.to_evm_address().ok())
-                    .expect("evm address")
-                    .1
-            }
-
-            // If deposit, use recipient address.
-            (BridgeSide::Wrapped, Erc20OpStage::SignMintOrder(order)) => order.recipient.clone(),
-            (BridgeSide::Wrapped, Erc20OpStage::SendMintTransaction(order)) => {
-                order.reader().get_recipient()
-            }
-            (BridgeSide::Wrapped, Erc20OpStage::WaitForMintConfirm { order, .. }) => {
-                order.reader().get_recipient()
-            }
-            (BridgeSide::Wrapped, Erc20OpStage::TokenMintConfirmed(event)) => {
-                event.recipient.clone()
-            }
+        // Operations are bound to the address, which receives minted tokens.
+        match &self.0.stage {
+            Erc20OpStage::VerifyReceivedAmount { order, .. } => order.recipient.clone(),
+            Erc20OpStage::SignMintOrder(order) => order.recipient.clone(),
+            Erc20OpStage::SendMintTransaction(order) => order.reader().get_recipient(),
+            Erc20OpStage::RetryMintTransaction { order, .. } => order.reader().get_recipient(),
+            Erc20OpStage::CancelMintOrder { order, .. } => order.reader().get_recipient(),
+            Erc20OpStage::WaitForMintOrderCancel { order, .. } => order.reader().get_recipient(),
+            Erc20OpStage::WaitForMintConfirm { order, .. } => order.reader().get_recipient(),
+            Erc20OpStage::TokenMintConfirmed(event) => event.recipient.clone(),
+            Erc20OpStage::Refund { recipient, .. } => recipient.clone(),
         }
     }
 
     fn scheduling_options(&self) -> Option<TaskOptions> {
-        match self.0.stage {
+        match self.0.stage.mint_stage() {
+            Erc20OpStage::VerifyReceivedAmount { .. } => Some(TaskOptions::default()),
             Erc20OpStage::SignMintOrder(_) => Some(TaskOptions::default()),
             Erc20OpStage::SendMintTransaction(_) => Some(TaskOptions::default()),
+            Erc20OpStage::RetryMintTransaction { .. } => Some(TaskOptions::default()),
+            Erc20OpStage::CancelMintOrder { .. } => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(10)
+                    .with_backoff_policy(BackoffPolicy::Exponential {
+                        secs: 2,
+                        multiplier: 2,
+                    }),
+            ),
+            // The tokens are refunded only after the cancel transaction is confirmed,
+            // so it is polled until the result is known. If the order turns out to be
+            // minted, it is polled until the minted event completes the operation.
+            Erc20OpStage::WaitForMintOrderCancel { .. } => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(u32::MAX)
+                    .with_backoff_policy(BackoffPolicy::Fixed { secs: 30 }),
+            ),
             Erc20OpStage::WaitForMintConfirm { .. } => None,
             Erc20OpStage::TokenMintConfirmed(_) => None,
+            Erc20OpStage::Refund { .. } => None,
         }
     }
 }
@@ -124,24 +178,64 @@ pub struct Erc20OpStageImpl(pub Erc20OpStage);
 impl Erc20OpStageImpl {
     /// Returns signed mint order if the stage contains it.
     pub fn get_signed_mint_order(&self) -> Option<&SignedOrders> {
-        match &self.0 {
+        match self.0.mint_stage() {
+            Erc20OpStage::VerifyReceivedAmount { .. } => None,
             Erc20OpStage::SignMintOrder(_) => None,
             Erc20OpStage::SendMintTransaction(order) => Some(order),
+            Erc20OpStage::RetryMintTransaction { order, .. } => Some(order),
+            // The order is being cancelled, so it must not be minted by the user.
+            Erc20OpStage::CancelMintOrder { .. } => None,
+            Erc20OpStage::WaitForMintOrderCancel { .. } => None,
             Erc20OpStage::WaitForMintConfirm { order, .. } => Some(order),
             Erc20OpStage::TokenMintConfirmed(_) => None,
+            Erc20OpStage::Refund { .. } => None,
         }
     }
 
     async fn progress(self) -> BTFResult<OperationProgress<Self>> {
         match self.0 {
+            Erc20OpStage::VerifyReceivedAmount {
+                order,
+                token,
+                burn_tx_hash,
+                burn_block_number,
+                burn_log_index,
+            } => {
+                log::debug!("ERC20OpStage::VerifyReceivedAmount {order:?}");
+                let order = verify_received_amount(
+                    order,
+                    token,
+                    burn_tx_hash,
+                    burn_block_number,
+                    burn_log_index,
+                )
+                .await?;
+                Ok(OperationProgress::Progress(Self(
+                    Erc20OpStage::SignMintOrder(order),
+                )))
+            }
             Erc20OpStage::SignMintOrder(data) => {
                 log::debug!("ERC20OpStage::SignMintOrder {data:?}");
                 Ok(OperationProgress::AddToService(SIGN_MINT_ORDER_SERVICE_ID))
             }
-            Erc20OpStage::SendMintTransaction(data) => {
-                log::debug!("ERC20OpStage::SendMintTransaction {data:?}",);
+            Erc20OpStage::SendMintTransaction(order) => {
+                log::debug!("ERC20OpStage::SendMintTransaction {order:?}");
                 Ok(OperationProgress::AddToService(SEND_MINT_TX_SERVICE_ID))
             }
+            Erc20OpStage::RetryMintTransaction {
+                order,
+                failed_attempts,
+            } => {
+                log::debug!(
+                    "ERC20OpStage::RetryMintTransaction {order:?}; failed attempts: {failed_attempts}"
+                );
+                Ok(OperationProgress::AddToService(SEND_MINT_TX_SERVICE_ID))
+            }
+            Erc20OpStage::CancelMintOrder { .. } | Erc20OpStage::WaitForMintOrderCancel { .. } => {
+                Err(Error::FailedToProgress(
+                    "mint order cancel should be progressed by the operation".into(),
+                ))
+            }
             Erc20OpStage::WaitForMintConfirm { mint_results, .. } => {
                 log::debug!("ERC20OpStage::WaitForMintConfirm {mint_results:?}");
                 Err(bridge_did::error::Error::FailedToProgress(
@@ -154,41 +248,263 @@ impl Erc20OpStageImpl {
                     "Erc20OpStage::TokenMintConfirmed should not progress".into(),
                 ))
             }
+            Erc20OpStage::Refund {
+                recipient,
+                reason,
+                stage,
+            } => {
+                log::debug!("ERC20OpStage::Refund to {recipient}: {reason}");
+                let progress = match Box::pin(Self(*stage).progress()).await? {
+                    OperationProgress::Progress(stage) => {
+                        OperationProgress::Progress(Self(Erc20OpStage::Refund {
+                            recipient,
+                            reason,
+                            stage: Box::new(stage.0),
+                        }))
+                    }
+                    OperationProgress::AddToService(id) => OperationProgress::AddToService(id),
+                };
+                Ok(progress)
+            }
         }
     }
 }
 
-/// Select base or wrapped service based on operation side.
-pub struct Erc20ServiceSelector<S> {
-    base: S,
-    wrapped: S,
+/// Replaces stage of the mint order processed by the operation with the `mint_stage`,
+/// keeping the refund information.
+fn with_mint_stage(stage: Erc20OpStage, mint_stage: Erc20OpStage) -> Erc20OpStage {
+    match stage {
+        Erc20OpStage::Refund {
+            recipient,
+            reason,
+            stage,
+        } => Erc20OpStage::Refund {
+            recipient,
+            reason,
+            stage: Box::new(with_mint_stage(*stage, mint_stage)),
+        },
+        _ => mint_stage,
+    }
+}
+
+/// Creates mint order, which returns tokens of the failed mint `order` back to the sender
+/// on the source EVM.
+pub fn refund_order(order: &MintOrder) -> Option<MintOrder> {
+    let recipient = order
+        .sender
+        .to_evm_address()
+        .inspect_err(|e| log::warn!("failed to get refund recipient address: {e}"))
+        .ok()?
+        .1;
+    let dst_token = order
+        .src_token
+        .to_evm_address()
+        .inspect_err(|e| log::warn!("failed to get refund token address: {e}"))
+        .ok()?
+        .1;
+
+    // The bridge checks that the source token of the order is paired with the minted one,
+    // so the token pair of the failed order is kept, but in the reverse direction.
+    Some(MintOrder {
+        amount: order.amount.clone(),
+        sender: Id256::from_evm_address(&order.recipient, order.recipient_chain_id),
+        src_token: Id256::from_evm_address(&order.dst_token, order.recipient_chain_id),
+        recipient,
+        dst_token,
+        nonce: order.nonce,
+        sender_chain_id: order.recipient_chain_id,
+        recipient_chain_id: order.sender_chain_id,
+        name: order.name,
+        symbol: order.symbol,
+        decimals: order.decimals,
+        approve_spender: H160::default(),
+        approve_amount: U256::default(),
+        fee_payer: order.fee_payer.clone(),
+    })
+}
+
+/// Creates operation, which refunds tokens of the failed and cancelled mint `order`.
+fn refund_op(order: &SignedOrders, reason: String) -> Option<Erc20BridgeOp> {
+    let Some(failed_order) = order.decode_order() else {
+        log::error!("failed to decode mint order for refund");
+        return None;
+    };
+    let refund_order = refund_order(&failed_order)?;
+
+    log::info!(
+        "Mint order {} failed with {reason}. Refunding to {} on chain {}",
+        failed_order.nonce,
+        refund_order.recipient,
+        refund_order.recipient_chain_id
+    );
+
+    Some(Erc20BridgeOp::new(
+        refund_order.recipient_chain_id,
+        Erc20OpStage::Refund {
+            recipient: failed_order.recipient,
+            reason,
+            stage: Box::new(Erc20OpStage::SignMintOrder(refund_order)),
+        },
+    ))
 }
 
-impl<S> Erc20ServiceSelector<S> {
-    pub fn new(base: S, wrapped: S) -> Self {
-        Self { base, wrapped }
+/// Sends transaction, which marks nonce of the mint `order` as used on the destination EVM,
+/// so the order can never be minted.
+async fn send_cancel_mint_order_tx(order: &SignedOrders) -> BTFResult<H256> {
+    let order = order
+        .decode_order()
+        .ok_or_else(|| Error::CannotProgress("failed to decode mint order".into()))?;
+    let config = get_evm_link_config(order.recipient_chain_id).ok_or_else(|| {
+        Error::FailedToProgress(format!("evm link {} not found", order.recipient_chain_id))
+    })?;
+
+    let tx_hash = ConfigStorage::send_transaction(config, |params| {
+        btf_events::cancel_mint_order_transaction(params, order.sender.0, order.nonce)
+    })
+    .await?;
+
+    log::debug!("Mint order {} cancel sent in tx {tx_hash}", order.nonce);
+
+    Ok(tx_hash)
+}
+
+/// Returns execution result of the transaction, which cancels the mint `order`.
+async fn query_mint_order_cancel_result(
+    order: &SignedOrders,
+    tx_hash: &H256,
+) -> BTFResult<ExeResult> {
+    let order = order
+        .decode_order()
+        .ok_or_else(|| Error::CannotProgress("failed to decode mint order".into()))?;
+    let config = get_evm_link_config(order.recipient_chain_id).ok_or_else(|| {
+        Error::FailedToProgress(format!("evm link {} not found", order.recipient_chain_id))
+    })?;
+    let client = config.borrow().get_evm_link().get_json_rpc_client();
+
+    let result = client
+        .get_tx_execution_result_by_hash(tx_hash.clone())
+        .await
+        .map_err(|e| {
+            Error::FailedToProgress(format!(
+                "mint order cancel tx {tx_hash} is not confirmed: {e:?}"
+            ))
+        })?;
+
+    Ok(result.exe_result)
+}
+
+/// Checks the amount of tokens actually received by the sender chain bridge for the burn
+/// event and updates the order amount accordingly.
+///
+/// The order amount is never increased over the burnt amount.
+async fn verify_received_amount(
+    mut order: MintOrder,
+    token: H160,
+    burn_tx_hash: H256,
+    burn_block_number: u64,
+    burn_log_index: u64,
+) -> BTFResult<MintOrder> {
+    let config = get_evm_link_config(order.sender_chain_id).ok_or_else(|| {
+        Error::FailedToProgress(format!("evm link {} not found", order.sender_chain_id))
+    })?;
+    let client = config.borrow().get_evm_link().get_json_rpc_client();
+    let bridge_contract = config
+        .borrow()
+        .get_btf_bridge_contract()
+        .ok_or_else(|| Error::Initialization("btf bridge contract not initialized".into()))?;
+
+    let received = token_transfers::query_received_amount(
+        &client,
+        token.0,
+        bridge_contract.0,
+        burn_block_number,
+        burn_tx_hash.0,
+        Some(burn_log_index),
+    )
+    .await?;
+
+    order.amount = credited_amount(order.amount, U256(received)).ok_or_else(|| {
+        Error::CannotProgress(format!(
+            "no tokens received by the bridge in transaction {burn_tx_hash}"
+        ))
+    })?;
+
+    Ok(order)
+}
+
+/// Returns the amount to credit for the burn of `burnt` tokens, if the bridge received
+/// any tokens for it.
+fn credited_amount(burnt: U256, received: U256) -> Option<U256> {
+    if received.0.is_zero() {
+        return None;
+    }
+
+    if received != burnt {
+        log::info!("Bridge received {received:?} tokens instead of {burnt:?}");
+    }
+
+    Some(U256(burnt.0.min(received.0)))
+}
+
+/// Routes operations to the service of the EVM link, where the operation mints tokens.
+pub struct EvmLinkRouter {
+    main_config: SharedConfig,
+    main: Rc<dyn BridgeService>,
+    links: RefCell<BTreeMap<u32, Rc<dyn BridgeService>>>,
+}
+
+impl EvmLinkRouter {
+    /// Creates a new router with the service of the main EVM link.
+    pub fn new(main_config: SharedConfig, main: Rc<dyn BridgeService>) -> Self {
+        Self {
+            main_config,
+            main,
+            links: RefCell::default(),
+        }
+    }
+
+    /// Adds a service of the EVM link with the given chain id.
+    pub fn add_link(&self, chain_id: u32, service: Rc<dyn BridgeService>) {
+        self.links.borrow_mut().insert(chain_id, service);
+    }
+
+    fn service_for_chain(&self, chain_id: u32) -> Option<Rc<dyn BridgeService>> {
+        if let Some(service) = self.links.borrow().get(&chain_id) {
+            return Some(service.clone());
+        }
+
+        let main_chain_id = self.main_config.borrow().get_evm_params().ok()?.chain_id as u32;
+        (main_chain_id == chain_id).then(|| self.main.clone())
     }
 }
 
 #[async_trait::async_trait(?Send)]
-impl<S: BridgeService> BridgeService for Erc20ServiceSelector<S> {
+impl BridgeService for EvmLinkRouter {
     async fn run(&self) -> BTFResult<()> {
-        let (base_result, wrapped_result) = futures::join!(self.base.run(), self.wrapped.run());
-        base_result?;
-        wrapped_result?;
-        Ok(())
+        let services = std::iter::once(self.main.clone())
+            .chain(self.links.borrow().values().cloned())
+            .collect::<Vec<_>>();
+        let results = futures::future::join_all(services.iter().map(|s| s.run())).await;
+        results.into_iter().collect()
     }
 
     fn push_operation(&self, id: OperationId) -> BTFResult<()> {
         let Some(op) = get_runtime_state().borrow().operations.get(id) else {
-            log::warn!("Attempt to add unexisting operataion to mint order sign service");
+            log::warn!("Attempt to add unexisting operataion to evm link service");
             return Err(Error::OperationNotFound(id));
         };
 
-        match op.0.side {
-            BridgeSide::Base => self.base.push_operation(id),
-            BridgeSide::Wrapped => self.wrapped.push_operation(id),
-        }
+        let Some(chain_id) = op.0.chain_id() else {
+            log::warn!("Failed to get chain id of operation {id}");
+            return Err(Error::ServiceNotFound);
+        };
+
+        let Some(service) = self.service_for_chain(chain_id) else {
+            log::warn!("Evm link service for chain {chain_id} not found");
+            return Err(Error::ServiceNotFound);
+        };
+
+        service.push_operation(id)
     }
 }
 
@@ -220,12 +536,12 @@ impl MintOrderHandler for Erc20OrderHandler {
 
     fn get_order(&self, id: OperationId) -> Option<MintOrder> {
         let op = self.state.borrow().operations.get(id)?;
-        let Erc20OpStage::SignMintOrder(order) = op.0.stage else {
+        let Erc20OpStage::SignMintOrder(order) = op.0.stage.mint_stage() else {
             log::error!("Mint order handler failed to get MintOrder: unexpected state.");
             return None;
         };
 
-        Some(order)
+        Some(order.clone())
     }
 
     fn set_signed_order(&self, id: OperationId, signed: SignedOrders) {
@@ -234,7 +550,7 @@ impl MintOrderHandler for Erc20OrderHandler {
             return;
         };
 
-        let Erc20OpStage::SignMintOrder(order) = op.0.stage else {
+        let Erc20OpStage::SignMintOrder(order) = op.0.stage.mint_stage() else {
             log::error!("Mint order handler failed to set MintOrder: unexpected state.");
             return;
         };
@@ -249,12 +565,13 @@ impl MintOrderHandler for Erc20OrderHandler {
                 mint_results: vec![],
             },
         };
+        let new_stage = with_mint_stage(op.0.stage, new_stage);
 
         log::trace!("New stage for operation {id}: {new_stage:?}");
 
         let new_op = Erc20BridgeOpImpl(Erc20BridgeOp {
-            side: op.0.side,
             stage: new_stage,
+            ..op.0
         });
         let scheduling_options = new_op.scheduling_options();
         self.state
@@ -283,14 +600,16 @@ impl MintTxHandler for Erc20OrderHandler {
             log::error!("Mint order handler failed to get MintOrder: operation not found.");
             return None;
         };
-        let Erc20OpStage::SendMintTransaction(order) = op.0.stage else {
-            log::error!(
-                "MintTxHandler failed to get mint order batch: unexpected operation state."
-            );
-            return None;
-        };
-
-        Some(order)
+        match op.0.stage.mint_stage() {
+            Erc20OpStage::SendMintTransaction(order) => Some(order.clone()),
+            Erc20OpStage::RetryMintTransaction { order, .. } => Some(order.clone()),
+            _ => {
+                log::error!(
+                    "MintTxHandler failed to get mint order batch: unexpected operation state."
+                );
+                None
+            }
+        }
     }
 
     fn mint_tx_sent(&self, id: OperationId, result: MintTxResult) {
@@ -298,9 +617,16 @@ impl MintTxHandler for Erc20OrderHandler {
             log::error!("MintTxHandler failed to update operation: not found.");
             return;
         };
-        let Erc20OpStage::SendMintTransaction(order) = op.0.stage else {
-            log::error!("MintTxHandler failed to update operation: unexpected state.");
-            return;
+        let (order, failed_attempts) = match op.0.stage.mint_stage() {
+            Erc20OpStage::SendMintTransaction(order) => (order.clone(), 0),
+            Erc20OpStage::RetryMintTransaction {
+                order,
+                failed_attempts,
+            } => (order.clone(), *failed_attempts),
+            _ => {
+                log::error!("MintTxHandler failed to update operation: unexpected state.");
+                return;
+            }
         };
 
         log::debug!(
@@ -308,16 +634,164 @@ impl MintTxHandler for Erc20OrderHandler {
             result.tx_hash,
             result.results
         );
-        self.state.borrow_mut().operations.update(
-            id,
-            Erc20BridgeOpImpl(Erc20BridgeOp {
-                side: op.0.side,
-                stage: Erc20OpStage::WaitForMintConfirm {
+
+        let mint_error = result
+            .results
+            .get(order.idx())
+            .filter(|code| !code.is_ok() && **code != BatchMintErrorCode::UsedNonce)
+            .cloned();
+
+        let new_stage = match mint_error {
+            // Refunds are not refunded again, they can be minted by the user with the signed order.
+            Some(error) if !op.0.stage.is_refund() => {
+                let failed_attempts = failed_attempts + 1;
+                if !error.is_permanent_failure() && failed_attempts < get_max_mint_attempts() {
+                    log::info!(
+                        "Mint of operation {id} failed with {error:?}, attempt {failed_attempts}. Retrying..."
+                    );
+                    Some(Erc20OpStage::RetryMintTransaction {
+                        order: order.clone(),
+                        failed_attempts,
+                    })
+                } else if error.is_refundable() && is_refund_possible(&order) {
+                    // The order is cancelled first, so it can't be minted after the refund.
+                    log::info!("Mint of operation {id} failed with {error:?}. Cancelling order...");
+                    Some(Erc20OpStage::CancelMintOrder {
+                        order: order.clone(),
+                        reason: format!("{error:?}"),
+                    })
+                } else {
+                    log::warn!(
+                        "Mint of operation {id} failed with {error:?} after {failed_attempts} attempts. The order can be minted with the signed order"
+                    );
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let new_stage = new_stage.unwrap_or_else(|| {
+            with_mint_stage(
+                op.0.stage,
+                Erc20OpStage::WaitForMintConfirm {
                     order,
                     tx_hash: result.tx_hash,
                     mint_results: result.results,
                 },
-            }),
+            )
+        });
+        let new_op = Erc20BridgeOpImpl(Erc20BridgeOp {
+            side: op.0.side,
+            chain_id: op.0.chain_id,
+            stage: new_stage,
+        });
+
+        let scheduling_options = new_op.scheduling_options();
+        self.state
+            .borrow_mut()
+            .operations
+            .update(id, new_op.clone());
+
+        if let Some(options) = scheduling_options {
+            let scheduled_task = ScheduledTask::with_options(BridgeTask::new(id, new_op), options);
+            self.scheduler.append_task(scheduled_task);
+        }
+    }
+}
+
+/// Returns `true` if tokens of the mint `order` can be returned to the sender.
+fn is_refund_possible(order: &SignedOrders) -> bool {
+    order
+        .decode_order()
+        .is_some_and(|order| refund_order(&order).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_cap_credited_amount() {
+        let burnt = U256::from(100u64);
+
+        assert_eq!(
+            credited_amount(burnt.clone(), U256::from(97u64)),
+            Some(U256::from(97u64))
+        );
+        assert_eq!(
+            credited_amount(burnt.clone(), U256::from(1000u64)),
+            Some(burnt.clone())
         );
+        assert_eq!(credited_amount(burnt, U256::default()), None);
+    }
+
+    #[test]
+    fn should_create_refund_order() {
+        let order = MintOrder {
+            amount: U256::from(100u64),
+            sender: Id256::from_evm_address(&H160::from_slice(&[1; 20]), 1),
+            src_token: Id256::from_evm_address(&H160::from_slice(&[2; 20]), 1),
+            recipient: H160::from_slice(&[3; 20]),
+            dst_token: H160::from_slice(&[4; 20]),
+            nonce: 42,
+            sender_chain_id: 1,
+            recipient_chain_id: 2,
+            name: [5; 32],
+            symbol: [6; 16],
+            decimals: 18,
+            approve_spender: H160::from_slice(&[7; 20]),
+            approve_amount: U256::from(10u64),
+            fee_payer: H160::from_slice(&[1; 20]),
+        };
+
+        let refund = refund_order(&order).unwrap();
+
+        assert_eq!(refund.amount, order.amount);
+        assert_eq!(refund.sender, Id256::from_evm_address(&order.recipient, 2));
+        assert_eq!(
+            refund.src_token,
+            Id256::from_evm_address(&order.dst_token, 2)
+        );
+        assert_eq!(refund.recipient, H160::from_slice(&[1; 20]));
+        assert_eq!(refund.dst_token, H160::from_slice(&[2; 20]));
+        assert_eq!(refund.nonce, order.nonce);
+        assert_eq!(refund.sender_chain_id, 2);
+        assert_eq!(refund.recipient_chain_id, 1);
+        assert_eq!(refund.approve_spender, H160::default());
+        assert_eq!(refund.approve_amount, U256::default());
+        assert_eq!(refund.fee_payer, order.fee_payer);
+    }
+
+    #[test]
+    fn should_replace_refund_mint_stage() {
+        let order = SignedOrders::new(
+            bridge_did::order::SignedOrdersData {
+                orders_data: vec![0; MintOrder::ENCODED_DATA_SIZE],
+                signature: vec![0; 65],
+            },
+            0,
+        )
+        .unwrap();
+        let refund = Erc20OpStage::Refund {
+            recipient: H160::from_slice(&[1; 20]),
+            reason: "Reverted".into(),
+            stage: Box::new(Erc20OpStage::SendMintTransaction(order.clone())),
+        };
+
+        let stage = with_mint_stage(
+            refund,
+            Erc20OpStage::WaitForMintConfirm {
+                order,
+                mint_results: vec![],
+                tx_hash: None,
+            },
+        );
+
+        assert!(stage.is_refund());
+        assert!(matches!(
+            stage.mint_stage(),
+            Erc20OpStage::WaitForMintConfirm { .. }
+        ));
+        assert_eq!(stage.name(), "RefundConfirmMint");
     }
 }
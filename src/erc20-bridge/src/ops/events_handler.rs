@@ -1,46 +1,45 @@
 use bridge_canister::bridge::OperationAction;
 use bridge_canister::runtime::service::fetch_logs::BtfBridgeEventHandler;
 use bridge_canister::runtime::state::SharedConfig;
-use bridge_did::bridge_side::BridgeSide;
 use bridge_did::event_data::{BurntEventData, MintedEventData, NotifyMinterEventData};
 use bridge_did::id256::Id256;
 use bridge_did::op_id::OperationId;
-use bridge_did::operations::{Erc20BridgeOp, Erc20OpStage};
+use bridge_did::operations::{Erc20BridgeOp, Erc20OpStage, Erc20TokenCapability};
 use bridge_did::order::MintOrder;
-use bridge_utils::evm_bridge::EvmParams;
 use did::{H160, U256};
 use ic_stable_structures::CellStructure;
 
-use crate::canister::SharedNonceCounter;
+use crate::canister::{
+    SharedNonceCounter, get_evm_link_config, get_runtime_state, get_token_capabilities,
+};
 use crate::ops::Erc20BridgeOpImpl;
 
 pub struct Erc20EventsHandler {
     nonce_counter: SharedNonceCounter,
-    side: BridgeSide,
 
     /// Listen events from this EVM.
-    src_evm_config: SharedConfig,
-
-    /// Send transactions to this EVM.
-    dst_evm_config: SharedConfig,
+    evm_config: SharedConfig,
 }
 
 impl Erc20EventsHandler {
-    /// Creates new events handler instance, which listens `src_evm` and
-    /// create operations for `dst_evm`.
-    pub fn new(
-        nonce_counter: SharedNonceCounter,
-        side: BridgeSide,
-        src_evm_config: SharedConfig,
-        dst_evm_config: SharedConfig,
-    ) -> Self {
+    /// Creates new events handler instance, which listens the given EVM and
+    /// creates operations for the EVM links, where tokens should be minted.
+    pub fn new(nonce_counter: SharedNonceCounter, evm_config: SharedConfig) -> Self {
         Self {
             nonce_counter,
-            side,
-            src_evm_config,
-            dst_evm_config,
+            evm_config,
         }
     }
+
+    fn chain_id(&self) -> u32 {
+        // Panic here to make the runtime re-process the events when EVM params will be initialized.
+        let evm_params = self
+            .evm_config
+            .borrow()
+            .get_evm_params()
+            .expect("events should not be handled if evm params are not initialized");
+        evm_params.chain_id as u32
+    }
 }
 
 impl BtfBridgeEventHandler<Erc20BridgeOpImpl> for Erc20EventsHandler {
@@ -51,10 +50,25 @@ impl BtfBridgeEventHandler<Erc20BridgeOpImpl> for Erc20EventsHandler {
         log::trace!("wrapped token minted. Updating operation to the complete state...");
 
         let nonce = event.nonce;
-        let update_to = Erc20BridgeOpImpl(Erc20BridgeOp {
-            side: self.side,
-            stage: Erc20OpStage::TokenMintConfirmed(event),
-        });
+        let stage = Erc20OpStage::TokenMintConfirmed(event);
+
+        // Refund orders keep the nonce of the operation, so the operation is found by it.
+        let op = get_runtime_state()
+            .borrow()
+            .operations
+            .get(OperationId::new(nonce as _));
+        let stage = match op.map(|op| op.0.stage) {
+            Some(Erc20OpStage::Refund {
+                recipient, reason, ..
+            }) => Erc20OpStage::Refund {
+                recipient,
+                reason,
+                stage: Box::new(stage),
+            },
+            _ => stage,
+        };
+
+        let update_to = Erc20BridgeOpImpl(Erc20BridgeOp::new(self.chain_id(), stage));
 
         Some(OperationAction::Update { nonce, update_to })
     }
@@ -65,13 +79,16 @@ impl BtfBridgeEventHandler<Erc20BridgeOpImpl> for Erc20EventsHandler {
     ) -> Option<OperationAction<Erc20BridgeOpImpl>> {
         log::trace!("Wrapped token burnt. Preparing mint order for other side...");
 
+        let src_chain_id = self.chain_id();
+
         // Panic here to make the runtime re-process the events when EVM params will be initialized.
-        let src_evm_params = self.src_evm_config.borrow().get_evm_params().expect(
-            "on_wrapped_token_burnt should not be called if source evm params are not initialized",
-        );
-        let dst_evm_params = self.dst_evm_config.borrow().get_evm_params().expect(
-            "on_wrapped_token_burnt should not be called if base evm params are not initialized",
-        );
+        // Main EVM params are required to find out if the destination chain is linked.
+        get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_evm_params()
+            .expect("on_wrapped_token_burnt should not be called if main evm params are not initialized");
 
         let nonce = {
             let mut counter = self.nonce_counter.borrow_mut();
@@ -82,22 +99,63 @@ impl BtfBridgeEventHandler<Erc20BridgeOpImpl> for Erc20EventsHandler {
             nonce
         };
 
-        let Some(order) =
-            mint_order_from_burnt_event(event.clone(), src_evm_params, dst_evm_params, nonce)
-        else {
+        // Native token deposits carry the exact transferred value, so they don't need verification.
+        let capability = if event.from_erc20 == Id256::native_address() {
+            Erc20TokenCapability::Standard
+        } else {
+            get_token_capabilities()
+                .borrow()
+                .get(&Id256::from_evm_address(&event.from_erc20, src_chain_id))
+        };
+        let dst_chain_id = Id256::from_slice(&event.to_token)
+            .map(|id| id.chain_id())
+            .filter(|chain_id| get_evm_link_config(*chain_id).is_some());
+
+        let (chain_id, order) = match dst_chain_id {
+            Some(dst_chain_id) if capability.is_supported() => {
+                let order =
+                    mint_order_from_burnt_event(event.clone(), src_chain_id, dst_chain_id, nonce);
+                (dst_chain_id, order)
+            }
+            _ => {
+                log::info!(
+                    "Refunding deposit of token {} to {}: capability {capability:?}, destination chain {dst_chain_id:?}",
+                    event.from_erc20,
+                    event.sender
+                );
+                let order = refund_order_from_burnt_event(event.clone(), src_chain_id, nonce);
+                (src_chain_id, order)
+            }
+        };
+
+        let Some(order) = order else {
             log::warn!("failed to create a mint order for event: {event:?}");
             return None;
         };
 
-        log::trace!(
-            "Mint order created for side {}: {order:?}",
-            self.side.other()
-        );
+        log::trace!("Mint order created for chain {chain_id}: {order:?}");
+
+        let stage = if capability.requires_amount_verification() {
+            // events without the tx info are requested again by the events service
+            let (Some(burn_tx_hash), Some(burn_block_number), Some(burn_log_index)) =
+                (event.tx_hash.clone(), event.block_number, event.log_index)
+            else {
+                log::error!("cannot verify received amount for event without tx info: {event:?}");
+                return None;
+            };
 
-        let operation = Erc20BridgeOpImpl(Erc20BridgeOp {
-            side: self.side.other(),
-            stage: Erc20OpStage::SignMintOrder(order),
-        });
+            Erc20OpStage::VerifyReceivedAmount {
+                order,
+                token: event.from_erc20.clone(),
+                burn_tx_hash,
+                burn_block_number,
+                burn_log_index,
+            }
+        } else {
+            Erc20OpStage::SignMintOrder(order)
+        };
+
+        let operation = Erc20BridgeOpImpl(Erc20BridgeOp::new(chain_id, stage));
         let memo = event.memo();
 
         let op_id = OperationId::new(nonce as _);
@@ -116,13 +174,12 @@ impl BtfBridgeEventHandler<Erc20BridgeOpImpl> for Erc20EventsHandler {
 /// Creates mint order based on burnt event.
 pub fn mint_order_from_burnt_event(
     event: BurntEventData,
-    burn_side_evm_params: EvmParams,
-    mint_side_evm_params: EvmParams,
+    sender_chain_id: u32,
+    recipient_chain_id: u32,
     nonce: u32,
 ) -> Option<MintOrder> {
-    let sender = Id256::from_evm_address(&event.sender, burn_side_evm_params.chain_id as u32);
-    let src_token =
-        Id256::from_evm_address(&event.from_erc20, burn_side_evm_params.chain_id as u32);
+    let sender = Id256::from_evm_address(&event.sender, sender_chain_id);
+    let src_token = Id256::from_evm_address(&event.from_erc20, sender_chain_id);
     let recipient = Id256::from_slice(&event.recipient_id)?
         .to_evm_address()
         .inspect_err(|err| {
@@ -147,8 +204,37 @@ pub fn mint_order_from_burnt_event(
         recipient,
         dst_token,
         nonce,
-        sender_chain_id: burn_side_evm_params.chain_id as u32,
-        recipient_chain_id: mint_side_evm_params.chain_id as u32,
+        sender_chain_id,
+        recipient_chain_id,
+        name: to_array(&event.name)?,
+        symbol: to_array(&event.symbol)?,
+        decimals: event.decimals,
+        approve_spender: H160::default(),
+        approve_amount: U256::default(),
+        fee_payer: event.sender,
+    };
+
+    Some(order)
+}
+
+/// Creates mint order, which returns the burnt tokens back to the sender on the burn side.
+pub fn refund_order_from_burnt_event(
+    event: BurntEventData,
+    chain_id: u32,
+    nonce: u32,
+) -> Option<MintOrder> {
+    // The bridge checks that the source token of the order is paired with the minted one,
+    // so the token pair of the burn is kept, but in the reverse direction.
+    let src_token = Id256::from_slice(&event.to_token)?;
+    let order = MintOrder {
+        amount: event.amount,
+        sender: Id256::from_evm_address(&event.sender, chain_id),
+        src_token,
+        recipient: event.sender.clone(),
+        dst_token: event.from_erc20,
+        nonce,
+        sender_chain_id: chain_id,
+        recipient_chain_id: chain_id,
         name: to_array(&event.name)?,
         symbol: to_array(&event.symbol)?,
         decimals: event.decimals,
@@ -1,101 +1,159 @@
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
-use std::time::Duration;
 
-use bridge_canister::bridge::OperationContext;
 use bridge_canister::memory::{StableMemory, memory_by_id};
 use bridge_canister::runtime::state::SharedConfig;
 use bridge_canister::runtime::state::config::ConfigStorage;
 use bridge_did::error::{BTFResult, Error};
-use bridge_did::evm_link::EvmLink;
-use bridge_did::init::erc20::{BaseEvmSettings, QueryDelays};
-use bridge_utils::evm_bridge::EvmParams;
-use candid::Principal;
-use eth_signer::sign_strategy::TxSigner;
-use ic_stable_structures::{CellStructure, StableCell};
+use bridge_did::init::erc20::{EvmLinkSettings, QueryDelays};
+use candid::{CandidType, Principal};
+use did::codec;
+use ic_stable_structures::{BTreeMapStructure, Bound, StableBTreeMap, Storable};
+use serde::{Deserialize, Serialize};
 
-use crate::memory::{BASE_EVM_CONFIG_MEMORY_ID, DELAYS_MEMORY_ID};
+use crate::memory::{EVM_LINKS_MEMORY_ID, MAX_EVM_LINKS, evm_link_config_memory_id};
 
-pub const BASE_EVM_DATA_REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
+mod migration;
+mod token_capabilities;
 
-/// Parameters of the Base EVM.
-pub struct BaseEvmState {
-    pub config: SharedConfig,
-    pub delays: StableCell<QueryDelays, StableMemory>,
+pub use self::token_capabilities::TokenCapabilities;
+
+/// Stored information about an EVM link.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, CandidType)]
+struct EvmLinkInfo {
+    /// Slot of the memory, which stores the link config.
+    slot: u8,
+    delays: QueryDelays,
 }
 
-impl Default for BaseEvmState {
-    fn default() -> Self {
-        let config = ConfigStorage::default(memory_by_id(BASE_EVM_CONFIG_MEMORY_ID));
-        Self {
-            config: Rc::new(RefCell::new(config)),
-            delays: StableCell::new(memory_by_id(DELAYS_MEMORY_ID), QueryDelays::default())
-                .expect("failed to initialize delays cell"),
-        }
+impl Storable for EvmLinkInfo {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        codec::encode(self).into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        codec::decode(bytes.as_ref())
     }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// State of an EVM linked to the bridge.
+#[derive(Clone)]
+pub struct EvmLinkState {
+    pub config: SharedConfig,
+    pub delays: QueryDelays,
 }
 
-impl BaseEvmState {
-    /// Reset the state using the given settings.
-    pub fn reset(&mut self, settings: BaseEvmSettings) {
-        self.config.borrow_mut().update(|config| {
+/// EVMs linked to the bridge in addition to the main one, keyed by chain id.
+pub struct EvmLinks {
+    info: StableBTreeMap<u32, EvmLinkInfo, StableMemory>,
+    configs: BTreeMap<u32, SharedConfig>,
+}
+
+impl EvmLinks {
+    /// Loads EVM links from the stable memory.
+    pub fn new() -> Self {
+        let info: StableBTreeMap<u32, EvmLinkInfo, StableMemory> =
+            StableBTreeMap::new(memory_by_id(EVM_LINKS_MEMORY_ID));
+        let configs = info
+            .iter()
+            .map(|(chain_id, link)| {
+                let memory = memory_by_id(evm_link_config_memory_id(link.slot));
+                let config = ConfigStorage::default(memory);
+                (chain_id, Rc::new(RefCell::new(config)))
+            })
+            .collect();
+
+        Self { info, configs }
+    }
+
+    /// Adds a new EVM link using the given settings.
+    pub fn add(&mut self, settings: EvmLinkSettings) -> BTFResult<EvmLinkState> {
+        if self.info.contains_key(&settings.chain_id) {
+            return Err(Error::Initialization(format!(
+                "evm link for chain {} already exists",
+                settings.chain_id
+            )));
+        }
+
+        let used_slots = self
+            .info
+            .iter()
+            .map(|(_, link)| link.slot)
+            .collect::<Vec<_>>();
+        let slot = (0..MAX_EVM_LINKS)
+            .find(|slot| !used_slots.contains(slot))
+            .ok_or_else(|| Error::Initialization("max number of evm links reached".into()))?;
+
+        let mut config = ConfigStorage::default(memory_by_id(evm_link_config_memory_id(slot)));
+        config.update(|config| {
             config.owner = Principal::anonymous();
             config.evm_link = settings.evm_link;
             config.signing_strategy = settings.signing_strategy;
             config.evm_params = None;
             config.btf_bridge_contract_address = None;
         });
-        self.delays
-            .set(settings.delays)
-            .expect("failed to set delays");
-    }
-}
+        let config = Rc::new(RefCell::new(config));
 
-/// Newtype for base EVM state
-#[derive(Default, Clone)]
-pub struct SharedBaseEvmState(pub Rc<RefCell<BaseEvmState>>);
-
-impl SharedBaseEvmState {
-    pub fn query_delays(&self) -> QueryDelays {
-        *self.0.borrow().delays.get()
-    }
-}
+        self.info.insert(
+            settings.chain_id,
+            EvmLinkInfo {
+                slot,
+                delays: settings.delays,
+            },
+        );
+        self.configs.insert(settings.chain_id, config.clone());
 
-impl OperationContext for SharedBaseEvmState {
-    fn get_evm_link(&self) -> EvmLink {
-        self.0.borrow().config.borrow().get_evm_link()
+        Ok(EvmLinkState {
+            config,
+            delays: settings.delays,
+        })
     }
 
-    fn get_bridge_contract_address(&self) -> BTFResult<did::H160> {
-        self.0
-            .borrow()
-            .config
-            .borrow()
-            .get_btf_bridge_contract()
-            .ok_or_else(|| Error::Initialization("base btf bridge contract not initialized".into()))
+    /// Returns the link for the given chain id.
+    pub fn get(&self, chain_id: u32) -> Option<EvmLinkState> {
+        let info = self.info.get(&chain_id)?;
+        let config = self.configs.get(&chain_id)?.clone();
+        Some(EvmLinkState {
+            config,
+            delays: info.delays,
+        })
     }
 
-    fn get_evm_params(&self) -> BTFResult<EvmParams> {
-        self.0.borrow().config.borrow().get_evm_params()
+    /// Returns all links with their chain ids.
+    pub fn get_all(&self) -> Vec<(u32, EvmLinkState)> {
+        self.info
+            .iter()
+            .filter_map(|(chain_id, _)| self.get(chain_id).map(|link| (chain_id, link)))
+            .collect()
     }
+}
 
-    fn get_signer(&self) -> BTFResult<TxSigner> {
-        self.0.borrow().config.borrow().get_signer()
+impl Default for EvmLinks {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Shared EVM links.
+pub type SharedEvmLinks = Rc<RefCell<EvmLinks>>;
+
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
 
+    use bridge_did::evm_link::EvmLink;
     use eth_signer::ic_sign::SigningKeyId;
     use eth_signer::sign_strategy::SigningStrategy;
 
     use super::*;
 
-    #[test]
-    fn test_should_reset_evm_state() {
-        let mut state = BaseEvmState::default();
-        let settings = BaseEvmSettings {
+    fn settings(chain_id: u32) -> EvmLinkSettings {
+        EvmLinkSettings {
+            chain_id,
             evm_link: EvmLink::Ic(Principal::management_canister()),
             signing_strategy: SigningStrategy::ManagementCanister {
                 key_id: SigningKeyId::Dfx,
@@ -104,14 +162,52 @@ mod test {
                 logs_query: Duration::from_secs(10),
                 evm_params_query: Duration::from_secs(20),
             },
-        };
-        state.reset(settings.clone());
+        }
+    }
+
+    #[test]
+    fn test_should_add_evm_links() {
+        let mut links = EvmLinks::new();
+        links.add(settings(1)).unwrap();
+        links.add(settings(2)).unwrap();
+
+        let link = links.get(2).unwrap();
+        assert_eq!(link.config.borrow().get_evm_link(), settings(2).evm_link);
+        assert_eq!(link.delays.logs_query, Duration::from_secs(10));
+        assert_eq!(link.delays.evm_params_query, Duration::from_secs(20));
+
+        let chain_ids = links
+            .get_all()
+            .into_iter()
+            .map(|(chain_id, _)| chain_id)
+            .collect::<Vec<_>>();
+        assert_eq!(chain_ids, vec![1, 2]);
+        assert!(links.get(3).is_none());
+    }
+
+    #[test]
+    fn test_should_not_add_link_twice() {
+        let mut links = EvmLinks::new();
+        links.add(settings(1)).unwrap();
+        assert!(links.add(settings(1)).is_err());
+    }
+
+    #[test]
+    fn test_should_reload_evm_links() {
+        let mut links = EvmLinks::new();
+        links.add(settings(1)).unwrap();
+        links
+            .get(1)
+            .unwrap()
+            .config
+            .borrow_mut()
+            .set_btf_bridge_contract(did::H160::from_slice(&[1; 20]));
 
-        assert_eq!(state.config.borrow().get_evm_link(), settings.evm_link);
+        let links = EvmLinks::new();
+        let link = links.get(1).unwrap();
         assert_eq!(
-            state.delays.get().evm_params_query,
-            settings.delays.evm_params_query
+            link.config.borrow().get_btf_bridge_contract(),
+            Some(did::H160::from_slice(&[1; 20]))
         );
-        assert_eq!(state.delays.get().logs_query, settings.delays.logs_query);
     }
 }
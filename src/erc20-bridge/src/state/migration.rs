@@ -0,0 +1,155 @@
+use bridge_canister::memory::{StableMemory, memory_by_id};
+use bridge_canister::migration::{self, Migration};
+use bridge_canister::runtime::state::config::ConfigStorage;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::init::erc20::{EvmLinkSettings, QueryDelays};
+use ic_stable_structures::{CellStructure, StableCell};
+
+use super::EvmLinks;
+use crate::memory::{
+    LEGACY_BASE_EVM_CONFIG_MEMORY_ID, LEGACY_DELAYS_MEMORY_ID, STATE_VERSION_MEMORY_ID,
+};
+
+/// Migrations of the ERC20 bridge state. The migration at index `i` upgrades the state from
+/// version `i` to version `i + 1`. New migrations must be appended to the end of the list.
+const MIGRATIONS: &[Migration<EvmLinks>] = &[move_base_evm_to_evm_link];
+
+impl EvmLinks {
+    /// Marks the state of a freshly installed canister as being at the latest version.
+    pub fn init_state_version(&self) {
+        migration::init_state_version(memory_by_id(STATE_VERSION_MEMORY_ID), MIGRATIONS);
+    }
+
+    /// Applies the pending migrations of the state after an upgrade.
+    pub fn migrate(&mut self) -> BTFResult<()> {
+        let version =
+            migration::migrate_state(memory_by_id(STATE_VERSION_MEMORY_ID), self, MIGRATIONS)?;
+        log::info!("ERC20 bridge state is at version {version}");
+        Ok(())
+    }
+}
+
+/// Moves the base EVM config of the bridge with a single base EVM into an EVM link, keyed by
+/// the base EVM chain id. Operations of the base side are routed to the link by the chain id
+/// of their mint orders.
+///
+/// The chain id of the base EVM is known only from its params, so the migration fails if the
+/// bridge has not fetched them yet.
+fn move_base_evm_to_evm_link(links: &mut EvmLinks) -> BTFResult<()> {
+    let legacy_config = ConfigStorage::default(memory_by_id(LEGACY_BASE_EVM_CONFIG_MEMORY_ID));
+    let evm_params = legacy_config.get_evm_params().map_err(|_| {
+        Error::Initialization(
+            "base EVM params are not initialized, so the base EVM chain id is unknown".into(),
+        )
+    })?;
+
+    let legacy_delays: StableCell<QueryDelays, StableMemory> = StableCell::new(
+        memory_by_id(LEGACY_DELAYS_MEMORY_ID),
+        QueryDelays::default(),
+    )
+    .map_err(|err| Error::Initialization(format!("failed to read base EVM delays: {err:?}")))?;
+
+    let chain_id = evm_params.chain_id as u32;
+    let settings = EvmLinkSettings {
+        chain_id,
+        evm_link: legacy_config.get_evm_link(),
+        signing_strategy: legacy_config.get_signing_strategy(),
+        delays: *legacy_delays.get(),
+    };
+    let link = links.add(settings)?;
+
+    link.config.borrow_mut().update(|config| {
+        config.evm_params = Some(evm_params);
+        config.btf_bridge_contract_address = legacy_config.get_btf_bridge_contract();
+    });
+
+    log::info!("Base EVM config moved to the EVM link with chain id {chain_id}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bridge_did::evm_link::EvmLink;
+    use bridge_utils::evm_bridge::EvmParams;
+    use candid::Principal;
+    use did::H160;
+    use eth_signer::ic_sign::SigningKeyId;
+    use eth_signer::sign_strategy::SigningStrategy;
+
+    use super::*;
+
+    fn store_legacy_base_evm(evm_params: Option<EvmParams>) {
+        let mut config = ConfigStorage::default(memory_by_id(LEGACY_BASE_EVM_CONFIG_MEMORY_ID));
+        config.update(|config| {
+            config.evm_link = EvmLink::Ic(Principal::management_canister());
+            config.signing_strategy = SigningStrategy::ManagementCanister {
+                key_id: SigningKeyId::Dfx,
+            };
+            config.evm_params = evm_params;
+            config.btf_bridge_contract_address = Some(H160::from_slice(&[1; 20]));
+        });
+
+        let mut delays: StableCell<QueryDelays, StableMemory> = StableCell::new(
+            memory_by_id(LEGACY_DELAYS_MEMORY_ID),
+            QueryDelays::default(),
+        )
+        .unwrap();
+        delays
+            .set(QueryDelays {
+                logs_query: Duration::from_secs(10),
+                evm_params_query: Duration::from_secs(20),
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_should_move_base_evm_to_evm_link() {
+        let evm_params = EvmParams::new(355113, 100, 7, Default::default());
+        store_legacy_base_evm(Some(evm_params.clone()));
+
+        let mut links = EvmLinks::new();
+        links.migrate().unwrap();
+
+        let link = links.get(355113).unwrap();
+        let config = link.config.borrow();
+        assert_eq!(
+            config.get_evm_link(),
+            EvmLink::Ic(Principal::management_canister())
+        );
+        assert_eq!(config.get_evm_params().unwrap(), evm_params);
+        assert_eq!(
+            config.get_btf_bridge_contract(),
+            Some(H160::from_slice(&[1; 20]))
+        );
+        assert_eq!(link.delays.logs_query, Duration::from_secs(10));
+        assert_eq!(link.delays.evm_params_query, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_should_reject_migration_of_uninitialized_base_evm() {
+        store_legacy_base_evm(None);
+
+        let mut links = EvmLinks::new();
+        assert!(links.migrate().is_err());
+        assert!(links.get_all().is_empty());
+
+        // the migration is not marked as applied, so the next upgrade retries it
+        store_legacy_base_evm(Some(EvmParams::new(355113, 100, 7, Default::default())));
+        links.migrate().unwrap();
+        assert!(links.get(355113).is_some());
+    }
+
+    #[test]
+    fn test_should_not_migrate_fresh_state() {
+        store_legacy_base_evm(Some(EvmParams::new(355113, 100, 7, Default::default())));
+
+        let mut links = EvmLinks::new();
+        links.init_state_version();
+        links.migrate().unwrap();
+
+        assert!(links.get_all().is_empty());
+    }
+}
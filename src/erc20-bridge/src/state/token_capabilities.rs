@@ -0,0 +1,65 @@
+use bridge_did::id256::Id256;
+use bridge_did::operations::Erc20TokenCapability;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap};
+
+/// Registry of known token behaviours, keyed by the token id, which includes the chain id.
+pub struct TokenCapabilities<M: Memory> {
+    capabilities: StableBTreeMap<Id256, Erc20TokenCapability, M>,
+}
+
+impl<M: Memory> TokenCapabilities<M> {
+    pub fn new(memory: M) -> Self {
+        Self {
+            capabilities: StableBTreeMap::new(memory),
+        }
+    }
+
+    /// Returns capability of the token. Tokens which are not registered are treated
+    /// as standard ones, so the received amount is verified only for the tokens registered
+    /// as fee-on-transfer.
+    pub fn get(&self, token: &Id256) -> Erc20TokenCapability {
+        self.capabilities
+            .get(token)
+            .unwrap_or(Erc20TokenCapability::Standard)
+    }
+
+    pub fn insert(&mut self, token: Id256, capability: Erc20TokenCapability) {
+        self.capabilities.insert(token, capability);
+    }
+
+    pub fn remove(&mut self, token: &Id256) {
+        self.capabilities.remove(token);
+    }
+
+    pub fn get_all(&self) -> Vec<(Id256, Erc20TokenCapability)> {
+        self.capabilities.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge_canister::memory::memory_by_id;
+
+    use super::*;
+    use crate::memory::TOKEN_CAPABILITIES_MEMORY_ID;
+
+    #[test]
+    fn test_token_capabilities() {
+        let mut registry = TokenCapabilities::new(memory_by_id(TOKEN_CAPABILITIES_MEMORY_ID));
+        let token = Id256::from_evm_address(&did::H160::from_slice(&[1; 20]), 1);
+
+        assert_eq!(registry.get(&token), Erc20TokenCapability::Standard);
+
+        registry.insert(token, Erc20TokenCapability::Rebasing);
+        assert_eq!(registry.get(&token), Erc20TokenCapability::Rebasing);
+        assert_eq!(
+            registry.get_all(),
+            vec![(token, Erc20TokenCapability::Rebasing)]
+        );
+
+        registry.remove(&token);
+        assert_eq!(registry.get(&token), Erc20TokenCapability::Standard);
+        assert!(registry.get_all().is_empty());
+    }
+}
@@ -6,6 +6,7 @@ use bridge_canister::runtime::service::ServiceOrder;
 use bridge_canister::runtime::service::fetch_logs::FetchBtfBridgeEventsService;
 use bridge_canister::runtime::service::mint_tx::SendMintTxService;
 use bridge_canister::runtime::service::sign_orders::SignMintOrdersService;
+use bridge_canister::runtime::service::timer::ServiceTimer;
 use bridge_canister::runtime::service::update_evm_params::RefreshEvmParamsService;
 use bridge_canister::runtime::state::SharedConfig;
 use bridge_canister::runtime::state::config::ConfigStorage;
@@ -27,10 +28,13 @@ use ic_log::canister::{LogCanister, LogState};
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_storage::IcStorage;
 
+use crate::constant::TOKEN_METADATA_REFRESH_INTERVAL;
 use crate::ops::events_handler::IcrcEventsHandler;
+use crate::ops::refresh_metadata::RefreshTokenMetadataService;
 use crate::ops::{
     FETCH_BTF_EVENTS_SERVICE_ID, IcrcBridgeOpImpl, IcrcMintOrderHandler, IcrcMintTxHandler,
-    REFRESH_PARAMS_SERVICE_ID, SEND_MINT_TX_SERVICE_ID, SIGN_MINT_ORDER_SERVICE_ID,
+    REFRESH_PARAMS_SERVICE_ID, REFRESH_TOKEN_METADATA_SERVICE_ID, SEND_MINT_TX_SERVICE_ID,
+    SIGN_MINT_ORDER_SERVICE_ID,
 };
 use crate::state::IcrcState;
 
@@ -226,6 +230,11 @@ fn init_runtime() -> SharedRuntime {
 
     let refresh_params_service = RefreshEvmParamsService::new(config.clone());
 
+    let refresh_token_metadata_service = ServiceTimer::new(
+        RefreshTokenMetadataService::new(config.clone()),
+        TOKEN_METADATA_REFRESH_INTERVAL,
+    );
+
     let fetch_btf_events_service =
         FetchBtfBridgeEventsService::new(IcrcEventsHandler, runtime.clone(), config);
 
@@ -246,6 +255,13 @@ fn init_runtime() -> SharedRuntime {
         FETCH_BTF_EVENTS_SERVICE_ID,
         Rc::new(fetch_btf_events_service),
     );
+    // Runs before operations, so metadata update transactions do not compete
+    // with mint transactions for the same nonce.
+    services.borrow_mut().add_service(
+        ServiceOrder::BeforeOperations,
+        REFRESH_TOKEN_METADATA_SERVICE_ID,
+        Rc::new(refresh_token_metadata_service),
+    );
     services.borrow_mut().add_service(
         ServiceOrder::ConcurrentWithOperations,
         SIGN_MINT_ORDER_SERVICE_ID,
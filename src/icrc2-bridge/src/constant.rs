@@ -1,5 +1,16 @@
+use std::time::Duration;
+
 use ic_stable_structures::MemoryId;
 
 pub const ACCESS_LIST_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub const TOKEN_CONFIGURATION_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub const WRAPPED_TOKEN_METADATA_MEMORY_ID: MemoryId = MemoryId::new(22);
 
 pub const IC_CHAIN_ID: u32 = 0;
+
+/// Interval between ICRC token metadata refreshes.
+pub const TOKEN_METADATA_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Time after which an unconfirmed wrapped token metadata update is considered dropped and sent
+/// again (1 hour).
+pub const TOKEN_METADATA_UPDATE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
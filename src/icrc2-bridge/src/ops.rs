@@ -30,11 +30,13 @@ use crate::tokens::icrc1::{self, IcrcCanisterError};
 use crate::tokens::icrc2::{self, Success};
 
 pub mod events_handler;
+pub mod refresh_metadata;
 
 pub const REFRESH_PARAMS_SERVICE_ID: ServiceId = 0;
 pub const FETCH_BTF_EVENTS_SERVICE_ID: ServiceId = 1;
 pub const SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 2;
 pub const SEND_MINT_TX_SERVICE_ID: ServiceId = 3;
+pub const REFRESH_TOKEN_METADATA_SERVICE_ID: ServiceId = 4;
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub struct IcrcBridgeOpImpl(pub IcrcBridgeOp);
@@ -151,6 +153,9 @@ impl IcrcBridgeOpImpl {
 
         log::trace!("got token info: {token_info:?}");
 
+        // The wrapped token is deployed with the metadata of its first mint order.
+        icrc1::init_wrapped_token_metadata(burn_info.icrc2_token_principal, &token_info);
+
         let name = order::fit_str_to_array(&token_info.name);
         let symbol = order::fit_str_to_array(&token_info.symbol);
 
@@ -308,6 +313,7 @@ pub enum ErrorCodes {
     IcrcMetadataRequestFailed = 0,
     IcrcBurnFailed = 1,
     IcrcMintFailed = 2,
+    WrappedTokenMetadataUpdateFailed = 3,
 }
 
 /// Allows Signing service to handle MintOrders of ICRC bridge.
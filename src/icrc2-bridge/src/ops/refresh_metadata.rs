@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bridge_canister::runtime::service::BridgeService;
+use bridge_canister::runtime::state::SharedConfig;
+use bridge_canister::runtime::state::config::ConfigStorage;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::id256::Id256;
+use bridge_did::op_id::OperationId;
+use bridge_did::order;
+use bridge_utils::btf_events;
+use candid::Principal;
+use did::H256;
+use did::block::ExeResult;
+use ic_exports::ic_kit::ic;
+
+use super::ErrorCodes;
+use crate::constant::TOKEN_METADATA_UPDATE_TIMEOUT;
+use crate::tokens::icrc1::{self, TokenInfo};
+
+/// Service to periodically refresh configuration of the ICRC tokens known to the bridge.
+///
+/// If name or symbol of a token differs from the metadata last set in the BTFBridge contract,
+/// the service updates metadata of the wrapped token. The new metadata is stored only after
+/// the update transaction is confirmed, so a reverted update is retried on the next run.
+pub struct RefreshTokenMetadataService {
+    config: SharedConfig,
+    pending_updates: RefCell<HashMap<Principal, PendingUpdate>>,
+}
+
+/// Wrapped token metadata update sent to the EVM, but not confirmed yet.
+struct PendingUpdate {
+    tx_hash: H256,
+    info: TokenInfo,
+    /// Timestamp when the update transaction was sent (in nanoseconds).
+    sent_at: u64,
+}
+
+impl RefreshTokenMetadataService {
+    pub fn new(config: SharedConfig) -> Self {
+        Self {
+            config,
+            pending_updates: RefCell::default(),
+        }
+    }
+
+    async fn refresh_token(&self, token: Principal) -> BTFResult<()> {
+        if self.pending_updates.borrow().contains_key(&token) {
+            return self.check_pending_update(token).await;
+        }
+
+        let Some(cached) = icrc1::get_cached_token_configuration(token) else {
+            return Ok(());
+        };
+
+        let queried = icrc1::query_token_configuration(token)
+            .await
+            .map_err(|e| Error::Custom {
+                code: ErrorCodes::IcrcMetadataRequestFailed as _,
+                msg: format!("failed to query Icrc token configuration: {e}"),
+            })?;
+
+        // Tokens bridged before the metadata tracking was introduced were deployed
+        // with the cached metadata.
+        icrc1::init_wrapped_token_metadata(token, &cached.info);
+        let wrapped = icrc1::get_wrapped_token_metadata(token).unwrap_or(cached.info);
+
+        if is_metadata_changed(&wrapped, &queried.info) {
+            log::info!(
+                "Metadata of ICRC token {token} changed from {wrapped:?} to {:?}. Updating wrapped token...",
+                queried.info
+            );
+            self.send_update(token, queried.info.clone()).await?;
+        }
+
+        icrc1::cache_ic_token_configuration(queried);
+
+        Ok(())
+    }
+
+    /// Sends the wrapped token metadata update and keeps it pending until it is confirmed.
+    async fn send_update(&self, token: Principal, info: TokenInfo) -> BTFResult<()> {
+        let tx_hash = self.update_wrapped_token_metadata(token, &info).await?;
+        self.pending_updates.borrow_mut().insert(
+            token,
+            PendingUpdate {
+                tx_hash,
+                info,
+                sent_at: ic::time(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Stores the metadata of the pending update once its transaction is confirmed.
+    ///
+    /// The update not confirmed within `TOKEN_METADATA_UPDATE_TIMEOUT` is considered dropped by
+    /// the EVM and is sent again.
+    async fn check_pending_update(&self, token: Principal) -> BTFResult<()> {
+        let Some((tx_hash, sent_at)) = self
+            .pending_updates
+            .borrow()
+            .get(&token)
+            .map(|update| (update.tx_hash.clone(), update.sent_at))
+        else {
+            return Ok(());
+        };
+
+        let client = self.config.borrow().get_evm_link().get_json_rpc_client();
+        let Ok(result) = client
+            .get_tx_execution_result_by_hash(tx_hash.clone())
+            .await
+        else {
+            let pending_for = Duration::from_nanos(ic::time().saturating_sub(sent_at));
+            if pending_for < TOKEN_METADATA_UPDATE_TIMEOUT {
+                log::trace!("Wrapped token metadata update tx {tx_hash} is not confirmed yet");
+                return Ok(());
+            }
+
+            let Some(update) = self.pending_updates.borrow_mut().remove(&token) else {
+                return Ok(());
+            };
+            log::warn!(
+                "Wrapped token metadata update tx {tx_hash} for {token} is not confirmed in time. Sending it again..."
+            );
+            return self.send_update(token, update.info).await;
+        };
+
+        let Some(update) = self.pending_updates.borrow_mut().remove(&token) else {
+            return Ok(());
+        };
+
+        match result.exe_result {
+            ExeResult::Success { .. } => {
+                log::debug!("Wrapped token metadata update for {token} confirmed in tx {tx_hash}");
+                icrc1::set_wrapped_token_metadata(token, update.info);
+                Ok(())
+            }
+            ExeResult::Revert { revert_message, .. } => Err(Error::Custom {
+                code: ErrorCodes::WrappedTokenMetadataUpdateFailed as _,
+                msg: format!(
+                    "wrapped token metadata update tx {tx_hash} reverted: {}",
+                    revert_message.unwrap_or_default()
+                ),
+            }),
+            ExeResult::Halt { error, .. } => Err(Error::Custom {
+                code: ErrorCodes::WrappedTokenMetadataUpdateFailed as _,
+                msg: format!("wrapped token metadata update tx {tx_hash} halted: {error:?}"),
+            }),
+        }
+    }
+
+    async fn update_wrapped_token_metadata(
+        &self,
+        token: Principal,
+        info: &TokenInfo,
+    ) -> BTFResult<H256> {
+        let base_token_id = Id256::from(&token).0;
+        let name = order::fit_str_to_array(&info.name);
+        let symbol = order::fit_str_to_array(&info.symbol);
+        let decimals = info.decimals;
+
+        let tx_hash = ConfigStorage::send_transaction(self.config.clone(), |params| {
+            btf_events::update_wrapped_token_metadata_transaction(
+                params,
+                base_token_id,
+                name,
+                symbol,
+                decimals,
+            )
+        })
+        .await
+        .map_err(|e| Error::Custom {
+            code: ErrorCodes::WrappedTokenMetadataUpdateFailed as _,
+            msg: format!("failed to update wrapped token metadata: {e}"),
+        })?;
+
+        log::debug!("Wrapped token metadata update for {token} sent in tx {tx_hash}");
+
+        Ok(tx_hash)
+    }
+}
+
+/// Wrapped token metadata should be updated only if the name or symbol changed.
+fn is_metadata_changed(wrapped: &TokenInfo, queried: &TokenInfo) -> bool {
+    wrapped.name != queried.name || wrapped.symbol != queried.symbol
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for RefreshTokenMetadataService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running RefreshTokenMetadataService");
+
+        for token in icrc1::get_cached_tokens() {
+            if let Err(e) = self.refresh_token(token).await {
+                log::warn!("Failed to refresh metadata of ICRC token {token}: {e}");
+            }
+        }
+
+        log::trace!("RefreshTokenMetadataService run finished.");
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the RefreshTokenMetadataService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn token_info(name: &str, symbol: &str, decimals: u8) -> TokenInfo {
+        TokenInfo {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            decimals,
+        }
+    }
+
+    #[test]
+    fn test_should_detect_metadata_change() {
+        let wrapped = token_info("Token", "TKN", 8);
+
+        assert!(!is_metadata_changed(
+            &wrapped,
+            &token_info("Token", "TKN", 8)
+        ));
+        assert!(is_metadata_changed(
+            &wrapped,
+            &token_info("New Token", "TKN", 8)
+        ));
+        assert!(is_metadata_changed(
+            &wrapped,
+            &token_info("Token", "NTKN", 8)
+        ));
+        assert!(!is_metadata_changed(
+            &wrapped,
+            &token_info("Token", "TKN", 18)
+        ));
+    }
+}
@@ -2,15 +2,25 @@ use access_list::AccessList;
 pub use eth_signer::sign_strategy::{SigningStrategy, TxSigner};
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ic_stable_structures::{VirtualMemory, default_ic_memory_manager};
+use token_configuration::TokenConfigurationStorage;
+use wrapped_token_metadata::WrappedTokenMetadataStorage;
 
-use crate::constant::ACCESS_LIST_MEMORY_ID;
+use crate::constant::{
+    ACCESS_LIST_MEMORY_ID, TOKEN_CONFIGURATION_MEMORY_ID, WRAPPED_TOKEN_METADATA_MEMORY_ID,
+};
 
 mod access_list;
+mod token_configuration;
+mod wrapped_token_metadata;
 
 /// State of a bridge canister.
 pub struct IcrcState {
     /// Bridge canister configuration.
     pub access_list: AccessList<VirtualMemory<DefaultMemoryImpl>>,
+    /// Configuration of ICRC tokens used by the bridge.
+    pub token_configs: TokenConfigurationStorage<VirtualMemory<DefaultMemoryImpl>>,
+    /// Metadata of the wrapped tokens as it is known to be set in the BTFBridge contract.
+    pub wrapped_token_metadata: WrappedTokenMetadataStorage<VirtualMemory<DefaultMemoryImpl>>,
 }
 
 impl Default for IcrcState {
@@ -18,6 +28,12 @@ impl Default for IcrcState {
         let memory_manager = default_ic_memory_manager();
         Self {
             access_list: AccessList::new(memory_manager.get(ACCESS_LIST_MEMORY_ID)),
+            token_configs: TokenConfigurationStorage::new(
+                memory_manager.get(TOKEN_CONFIGURATION_MEMORY_ID),
+            ),
+            wrapped_token_metadata: WrappedTokenMetadataStorage::new(
+                memory_manager.get(WRAPPED_TOKEN_METADATA_MEMORY_ID),
+            ),
         }
     }
 }
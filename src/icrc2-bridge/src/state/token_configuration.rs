@@ -0,0 +1,92 @@
+use candid::Principal;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap};
+
+use crate::tokens::icrc1::TokenConfiguration;
+
+/// Stores configuration of ICRC tokens used by the bridge, so it survives canister upgrades.
+pub struct TokenConfigurationStorage<M: Memory> {
+    configs: StableBTreeMap<Principal, TokenConfiguration, M>,
+}
+
+impl<M: Memory> TokenConfigurationStorage<M> {
+    pub fn new(m: M) -> Self {
+        Self {
+            configs: StableBTreeMap::new(m),
+        }
+    }
+
+    pub fn get(&self, token: &Principal) -> Option<TokenConfiguration> {
+        self.configs.get(token)
+    }
+
+    pub fn insert(&mut self, config: TokenConfiguration) {
+        self.configs.insert(config.principal, config);
+    }
+
+    pub fn get_all_tokens(&self) -> Vec<Principal> {
+        self.configs.iter().map(|(token, _)| token).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge_canister::memory::MEMORY_MANAGER;
+    use candid::Nat;
+    use ic_exports::ic_kit::MockContext;
+    use icrc_client::account::Account;
+
+    use super::*;
+    use crate::constant::TOKEN_CONFIGURATION_MEMORY_ID;
+    use crate::tokens::icrc1::TokenInfo;
+
+    fn test_config(token: Principal) -> TokenConfiguration {
+        TokenConfiguration {
+            principal: token,
+            fee: Nat::from(24_u64),
+            minting_account: Account {
+                owner: Principal::from_slice(&[43; 20]),
+                subaccount: None,
+            },
+            info: TokenInfo {
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                decimals: 18,
+            },
+        }
+    }
+
+    #[test]
+    fn test_token_configuration_storage() {
+        MockContext::new().inject();
+
+        let mut storage = TokenConfigurationStorage::new(
+            MEMORY_MANAGER.with(|mm| mm.get(TOKEN_CONFIGURATION_MEMORY_ID)),
+        );
+        let token = Principal::from_slice(&[42; 20]);
+        assert!(storage.get(&token).is_none());
+
+        let config = test_config(token);
+        storage.insert(config.clone());
+        assert_eq!(storage.get(&token), Some(config));
+        assert_eq!(storage.get_all_tokens(), vec![token]);
+    }
+
+    #[test]
+    fn test_token_configuration_storage_overwrite() {
+        MockContext::new().inject();
+
+        let mut storage = TokenConfigurationStorage::new(
+            MEMORY_MANAGER.with(|mm| mm.get(TOKEN_CONFIGURATION_MEMORY_ID)),
+        );
+        let token = Principal::from_slice(&[42; 20]);
+        storage.insert(test_config(token));
+
+        let mut updated = test_config(token);
+        updated.info.name = "Renamed Token".to_string();
+        storage.insert(updated.clone());
+
+        assert_eq!(storage.get(&token), Some(updated));
+        assert_eq!(storage.get_all_tokens().len(), 1);
+    }
+}
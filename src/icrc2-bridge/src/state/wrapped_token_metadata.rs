@@ -0,0 +1,57 @@
+use candid::Principal;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, StableBTreeMap};
+
+use crate::tokens::icrc1::TokenInfo;
+
+/// Stores metadata of the wrapped tokens as it was set in the BTFBridge contract.
+///
+/// The ICRC token configuration cache is refreshed from several places, so the metadata
+/// pushed to the EVM is tracked separately to detect the changes which are not propagated yet.
+pub struct WrappedTokenMetadataStorage<M: Memory> {
+    metadata: StableBTreeMap<Principal, TokenInfo, M>,
+}
+
+impl<M: Memory> WrappedTokenMetadataStorage<M> {
+    pub fn new(m: M) -> Self {
+        Self {
+            metadata: StableBTreeMap::new(m),
+        }
+    }
+
+    pub fn get(&self, token: &Principal) -> Option<TokenInfo> {
+        self.metadata.get(token)
+    }
+
+    pub fn insert(&mut self, token: Principal, info: TokenInfo) {
+        self.metadata.insert(token, info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge_canister::memory::MEMORY_MANAGER;
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+    use crate::constant::WRAPPED_TOKEN_METADATA_MEMORY_ID;
+
+    #[test]
+    fn test_wrapped_token_metadata_storage() {
+        MockContext::new().inject();
+
+        let mut storage = WrappedTokenMetadataStorage::new(
+            MEMORY_MANAGER.with(|mm| mm.get(WRAPPED_TOKEN_METADATA_MEMORY_ID)),
+        );
+        let token = Principal::from_slice(&[42; 20]);
+        assert!(storage.get(&token).is_none());
+
+        let info = TokenInfo {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            decimals: 18,
+        };
+        storage.insert(token, info.clone());
+        assert_eq!(storage.get(&token), Some(info));
+    }
+}
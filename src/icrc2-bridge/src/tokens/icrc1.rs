@@ -1,9 +1,9 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::borrow::Cow;
 
-use candid::{CandidType, Nat, Principal};
+use candid::{CandidType, Decode, Encode, Nat, Principal};
 use evm_canister_client::{CanisterClient, CanisterClientError, IcCanisterClient};
 use ic_exports::ic_kit::RejectionCode;
+use ic_stable_structures::{Bound, Storable};
 use icrc_client::IcrcCanisterClient;
 use icrc_client::account::Account;
 use icrc_client::transfer::TransferError;
@@ -12,50 +12,48 @@ use num_traits::ToPrimitive as _;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::canister::get_icrc_state;
+
 const ICRC1_METADATA_DECIMALS: &str = "icrc1:decimals";
 const ICRC1_METADATA_NAME: &str = "icrc1:name";
 const ICRC1_METADATA_SYMBOL: &str = "icrc1:symbol";
 
-thread_local! {
-    static TOKEN_CONFIGURATION: RefCell<HashMap<Principal, TokenConfiguration>> = RefCell::new(HashMap::default());
-}
-
 /// Get ICRC1 token configuration from cache if cached, otherwise fetch it and store it into the cache.
 pub async fn get_token_configuration(
     ic_token: Principal,
 ) -> Result<TokenConfiguration, IcrcCanisterError> {
-    if let Some(config) = TOKEN_CONFIGURATION
-        .with(|token_configuration| token_configuration.borrow().get(&ic_token).cloned())
-    {
+    if let Some(config) = get_cached_token_configuration(ic_token) {
         Ok(config)
     } else {
-        let config = query_icrc1_configuration(ic_token).await?;
-        cache_ic_token_configuration(config.clone());
-
-        Ok(config)
+        refresh_token_configuration(ic_token).await
     }
 }
 
 /// Get ICRC1 token configuration from cache.
 pub fn get_cached_token_configuration(ic_token: Principal) -> Option<TokenConfiguration> {
-    TOKEN_CONFIGURATION
-        .with(|token_configuration| token_configuration.borrow().get(&ic_token).cloned())
+    get_icrc_state().borrow().token_configs.get(&ic_token)
 }
 
 /// Query token info from token canister and store it to cache.
 /// Read the info from cache if query fails.
+///
+/// If the token is not cached yet, the whole token configuration is queried,
+/// so the token metadata will be tracked by the metadata refresh service.
 pub async fn query_token_info_or_read_from_cache(token: Principal) -> Option<TokenInfo> {
-    let icrc_client = IcrcCanisterClient::new(IcCanisterClient::new(token));
+    let Some(mut config) = get_cached_token_configuration(token) else {
+        return refresh_token_configuration(token)
+            .await
+            .ok()
+            .map(|config| config.info);
+    };
 
+    let icrc_client = IcrcCanisterClient::new(IcCanisterClient::new(token));
     let Ok(queried) = query_icrc1_token_info(&icrc_client).await else {
-        return get_cached_token_configuration(token).map(|config| config.info);
+        return Some(config.info);
     };
 
-    // If we store token config in cache, update the config with new info.
-    if let Some(mut config) = get_cached_token_configuration(token) {
-        config.info = queried.clone();
-        cache_ic_token_configuration(config);
-    }
+    config.info = queried.clone();
+    cache_ic_token_configuration(config);
 
     Some(queried)
 }
@@ -69,6 +67,18 @@ pub async fn refresh_token_configuration(
     Ok(config)
 }
 
+/// Get ICRC1 token configuration from token canister without storing it to cache.
+pub async fn query_token_configuration(
+    ic_token: Principal,
+) -> Result<TokenConfiguration, IcrcCanisterError> {
+    query_icrc1_configuration(ic_token).await
+}
+
+/// Returns principals of all tokens with cached configuration.
+pub fn get_cached_tokens() -> Vec<Principal> {
+    get_icrc_state().borrow().token_configs.get_all_tokens()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, CandidType)]
 pub struct TokenInfo {
     pub name: String,
@@ -84,6 +94,30 @@ pub struct TokenConfiguration {
     pub info: TokenInfo,
 }
 
+impl Storable for TokenInfo {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode token info"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode token info")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TokenConfiguration {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode token configuration"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).expect("failed to decode token configuration")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 /// Requests fee and minting account configuration from an ICRC-1 canister.
 async fn query_icrc1_configuration(
     token: Principal,
@@ -151,28 +185,48 @@ fn get_metadata_value<'a>(
 }
 
 /// Cache the token configuration value in the cache
-fn cache_ic_token_configuration(config: TokenConfiguration) {
-    TOKEN_CONFIGURATION.with(|token_configuration| {
-        token_configuration
-            .borrow_mut()
-            .insert(config.principal, config);
-    });
+pub fn cache_ic_token_configuration(config: TokenConfiguration) {
+    get_icrc_state().borrow_mut().token_configs.insert(config);
+}
+
+/// Returns the metadata of the wrapped token as it is set in the BTFBridge contract.
+pub fn get_wrapped_token_metadata(ic_token: Principal) -> Option<TokenInfo> {
+    get_icrc_state()
+        .borrow()
+        .wrapped_token_metadata
+        .get(&ic_token)
+}
+
+/// Stores the metadata of the wrapped token once it is set in the BTFBridge contract.
+pub fn set_wrapped_token_metadata(ic_token: Principal, info: TokenInfo) {
+    get_icrc_state()
+        .borrow_mut()
+        .wrapped_token_metadata
+        .insert(ic_token, info);
+}
+
+/// Stores the metadata the wrapped token is deployed with, unless the metadata is already known.
+pub fn init_wrapped_token_metadata(ic_token: Principal, info: &TokenInfo) {
+    if get_wrapped_token_metadata(ic_token).is_none() {
+        set_wrapped_token_metadata(ic_token, info.clone());
+    }
 }
 
 #[cfg(test)]
 mod test {
     use candid::Nat;
     use evm_canister_client::{CanisterClient, CanisterClientResult};
+    use ic_exports::ic_kit::MockContext;
     use ic_exports::icrc_types::icrc1::account::Account;
 
     use super::*;
 
     #[tokio::test]
     async fn should_cache_config() {
+        MockContext::new().inject();
         let ic_token = Principal::from_slice(&[42; 20]);
 
-        let config = TOKEN_CONFIGURATION
-            .with(|token_configuration| token_configuration.borrow().get(&ic_token).cloned());
+        let config = get_cached_token_configuration(ic_token);
 
         assert!(config.is_none());
 
@@ -192,14 +246,65 @@ mod test {
 
         cache_ic_token_configuration(config.clone());
 
-        let cached_config = TOKEN_CONFIGURATION
-            .with(|token_configuration| token_configuration.borrow().get(&ic_token).cloned())
-            .unwrap();
+        let cached_config = get_cached_token_configuration(ic_token).unwrap();
 
         assert_eq!(config.principal, cached_config.principal);
         assert_eq!(config.fee, cached_config.fee);
         assert_eq!(config.minting_account, cached_config.minting_account);
         assert_eq!(config.info, cached_config.info);
+        assert_eq!(get_cached_tokens(), vec![ic_token]);
+    }
+
+    #[test]
+    fn should_keep_wrapped_token_metadata_apart_from_cache() {
+        MockContext::new().inject();
+        let ic_token = Principal::from_slice(&[42; 20]);
+        let deployed = TokenInfo {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            decimals: 18,
+        };
+        let renamed = TokenInfo {
+            name: "Renamed Token".to_string(),
+            ..deployed.clone()
+        };
+
+        init_wrapped_token_metadata(ic_token, &deployed);
+        init_wrapped_token_metadata(ic_token, &renamed);
+        cache_ic_token_configuration(TokenConfiguration {
+            principal: ic_token,
+            fee: Nat::from(24_u64),
+            minting_account: Account {
+                owner: Principal::from_slice(&[43; 20]),
+                subaccount: None,
+            },
+            info: renamed.clone(),
+        });
+
+        assert_eq!(get_wrapped_token_metadata(ic_token), Some(deployed));
+
+        set_wrapped_token_metadata(ic_token, renamed.clone());
+        assert_eq!(get_wrapped_token_metadata(ic_token), Some(renamed));
+    }
+
+    #[test]
+    fn should_encode_decode_token_configuration() {
+        let config = TokenConfiguration {
+            principal: Principal::from_slice(&[42; 20]),
+            fee: Nat::from(24_u64),
+            minting_account: Account {
+                owner: Principal::from_slice(&[43; 20]),
+                subaccount: Some([1; 32]),
+            },
+            info: TokenInfo {
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                decimals: 18,
+            },
+        };
+
+        let decoded = TokenConfiguration::from_bytes(config.to_bytes());
+        assert_eq!(config, decoded);
     }
 
     #[tokio::test]
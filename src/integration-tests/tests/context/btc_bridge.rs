@@ -8,6 +8,7 @@ use std::time::{Duration, Instant};
 use alloy_sol_types::SolCall;
 use bitcoin::{Address, Amount, Txid};
 use bridge_client::BridgeCanisterClient as _;
+use bridge_did::error::BTFResult;
 use bridge_did::id256::Id256;
 use bridge_did::init::btc::WrappedTokenConfig;
 use bridge_did::operation_log::Memo;
@@ -486,14 +487,14 @@ where
                 self.context.canisters().btc_bridge(),
                 self.context.admin_name(),
             )
-            .update::<(GetBtcAddressArgs,), String>(
+            .update::<(GetBtcAddressArgs,), BTFResult<String>>(
                 "get_btc_address",
                 (GetBtcAddressArgs {
                     owner: Some(account.owner),
                     subaccount: account.subaccount,
                 },),
             )
-            .await?;
+            .await??;
 
         Ok(Address::from_str(&addr)?.assume_checked())
     }
@@ -13,7 +13,7 @@ use bridge_did::error::BTFResult as McResult;
 use bridge_did::id256::Id256;
 use bridge_did::init::brc20::{Brc20BridgeConfig, SchnorrKeyIds};
 use bridge_did::init::btc::BitcoinConnection;
-use bridge_did::init::erc20::{BaseEvmSettings, QueryDelays};
+use bridge_did::init::erc20::{EvmLinkSettings, QueryDelays};
 use bridge_did::operation_log::Memo;
 use bridge_did::order::SignedOrders;
 use bridge_did::reason::{ApproveAfterMint, Icrc2Burn};
@@ -1236,7 +1236,13 @@ where
                     self.sign_key(),
                 );
 
-                let base_evm_settings = BaseEvmSettings {
+                let base_chain_id = self
+                    .base_evm()
+                    .chain_id()
+                    .await
+                    .expect("failed to get base evm chain id");
+                let base_evm_settings = EvmLinkSettings {
+                    chain_id: base_chain_id as u32,
                     evm_link: self.base_evm_link(),
                     signing_strategy: SigningStrategy::ManagementCanister {
                         key_id: self.sign_key(),
@@ -1256,7 +1262,7 @@ where
                 self.install_canister(
                     self.canisters().erc20_bridge(),
                     wasm,
-                    (init_data, base_evm_settings),
+                    (init_data, vec![base_evm_settings]),
                 )
                 .await
                 .unwrap();
@@ -1457,10 +1463,18 @@ pub fn rune_bridge_canister_init_data(
             min_confirmations: 1,
             indexers: vec![IndexerType::OrdHttp {
                 url: "http://localhost:8000".to_string(),
+                headers: None,
             }],
             deposit_fee: 500_000,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 1,
+            withdrawal_batch: None,
+            utxo_consolidation: None,
+            fee_estimator: None,
+            schnorr_key_id: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            withdrawal_cosigning: None,
         },
     )
 }
@@ -1478,10 +1492,17 @@ pub fn brc20_bridge_canister_init_data(
             network: BitcoinNetwork::Regtest,
             min_confirmations: 1,
             indexer_urls: HashSet::from_iter(["http://localhost:8004".to_string()]),
+            indexer_headers: None,
             deposit_fee: 500_000,
             mempool_timeout: Duration::from_secs(60),
             indexer_consensus_threshold: 1,
             schnorr_key_id: SchnorrKeyIds::TestKeyLocalDevelopment,
+            fee_estimator: None,
+            deposit_watcher: None,
+            dynamic_deposit_fee: None,
+            memo_deposits: None,
+            withdrawal_batch: None,
+            withdrawal_approval: None,
         },
     )
 }
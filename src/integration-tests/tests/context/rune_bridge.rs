@@ -13,7 +13,7 @@ use bridge_did::event_data::MinterNotificationType;
 use bridge_did::id256::Id256;
 use bridge_did::op_id::OperationId;
 use bridge_did::operations::{RuneBridgeDepositOp, RuneBridgeOp};
-use bridge_did::runes::RuneName;
+use bridge_did::runes::{EvmTokenEtchingRequest, RuneName};
 use bridge_utils::BTFBridge;
 use candid::{Encode, Principal};
 use did::constant::EIP1559_INITIAL_BASE_FEE;
@@ -23,7 +23,7 @@ use eth_signer::transaction::{SigningMethod, TransactionBuilder};
 use ic_canister_client::CanisterClient;
 use ord_rs::Utxo;
 use ordinals::{Etching, Rune, RuneId, Terms};
-use rune_bridge::interface::{DepositError, GetAddressError};
+use rune_bridge::interface::{DepositError, GetAddressError, WithdrawError};
 use rune_bridge::ops::RuneDepositRequestData;
 use tokio::sync::{Mutex, RwLock};
 use tokio::time::Instant;
@@ -665,6 +665,142 @@ where
         Ok(())
     }
 
+    /// Deploys the base-side BTF bridge with the canister as the minter and links the canister to
+    /// it. Returns the address of the bridge.
+    pub async fn link_base_btf_bridge(&self) -> anyhow::Result<H160> {
+        let minter_address = self
+            .inner
+            .rune_bridge_client(self.inner.admin_name())
+            .get_bridge_canister_evm_address()
+            .await?
+            .map_err(|err| anyhow::anyhow!("failed to get minter address: {err:?}"))?;
+
+        let wrapped_token_deployer = self
+            .inner
+            .initialize_wrapped_token_deployer_contract(&self.eth_wallet)
+            .await?;
+        let base_bridge = self
+            .inner
+            .initialize_btf_bridge_with_minter(
+                &self.eth_wallet,
+                minter_address,
+                None,
+                wrapped_token_deployer,
+                false,
+            )
+            .await?;
+
+        self.inner
+            .rune_bridge_client(self.inner.admin_name())
+            .admin_set_base_btf_bridge_contract(&base_bridge)
+            .await?;
+        println!("base-side btf bridge contract linked: {base_bridge}");
+
+        self.inner.advance_time(Duration::from_secs(2)).await;
+
+        Ok(base_bridge)
+    }
+
+    /// Etches a rune for the EVM-native ERC20 token and waits until the canister confirms the
+    /// etching. The rune is added to the test runes with the ERC20 token as its EVM token.
+    pub async fn etch_evm_token_rune(
+        &mut self,
+        erc20: &H160,
+        divisibility: u8,
+        premine: u128,
+    ) -> anyhow::Result<RuneId> {
+        // the etching transaction is paid from the deposit address of the token
+        let fee_address = self.get_deposit_address(erc20).await;
+        self.send_btc(&fee_address, Amount::from_int_btc(1)).await?;
+
+        let request = EvmTokenEtchingRequest {
+            erc20: erc20.clone(),
+            divisibility,
+            symbol: None,
+            premine,
+        };
+        let txid = self
+            .inner
+            .client(self.bridge(), self.inner.admin_name())
+            .update::<_, Result<H256, WithdrawError>>("admin_etch_evm_token_rune", (request,))
+            .await?
+            .map_err(|err| anyhow::anyhow!("failed to etch rune: {err:?}"))?;
+        println!("Rune for token {erc20} etched by transaction {txid}");
+
+        self.wait_for_blocks(REQUIRED_CONFIRMATIONS).await;
+
+        const MAX_WAIT: Duration = Duration::from_secs(180);
+        let start = Instant::now();
+        while start.elapsed() < MAX_WAIT {
+            let rune_info = self
+                .inner
+                .rune_bridge_client(self.inner.admin_name())
+                .get_evm_native_tokens()
+                .await?
+                .into_iter()
+                .find(|token| token.erc20 == *erc20)
+                .and_then(|token| token.rune_info());
+
+            if let Some(rune_info) = rune_info {
+                let rune_id = rune_info.id();
+                self.runes.runes.insert(
+                    rune_id,
+                    RuneWalletInfo {
+                        id256: rune_id.into(),
+                        name: rune_info.name().to_string(),
+                    },
+                );
+                self.tokens.write().await.insert(rune_id, erc20.clone());
+
+                return Ok(rune_id);
+            }
+
+            self.inner.advance_time(Duration::from_secs(5)).await;
+        }
+
+        anyhow::bail!("etching of the rune for token {erc20} not confirmed")
+    }
+
+    /// Locks the EVM-native tokens in the base-side BTF bridge, so the canister sends the runes
+    /// representing them to the recipient.
+    pub async fn lock_evm_native_tokens(
+        &self,
+        base_bridge: &H160,
+        recipient: &Address,
+        rune_id: &RuneId,
+        amount: u128,
+    ) -> anyhow::Result<()> {
+        let token_address = self
+            .tokens
+            .read()
+            .await
+            .get(rune_id)
+            .expect("token not found")
+            .clone();
+        let rune_info = self.runes.runes.get(rune_id).expect("rune not found");
+
+        println!("Locking {amount} of {token_address} for {rune_id} to {recipient}");
+
+        let client = self.inner.wrapped_evm();
+        self.inner
+            .burn_erc_20_tokens_raw(
+                &client,
+                &self.eth_wallet,
+                &token_address,
+                rune_info.id256.0.as_slice(),
+                recipient.to_string().as_bytes().to_vec(),
+                base_bridge,
+                amount,
+                false,
+                None,
+            )
+            .await?;
+
+        self.wait_for_blocks(REQUIRED_CONFIRMATIONS).await;
+
+        Ok(())
+    }
+
     pub async fn create_wrapped_token(
         &self,
         wallet: &LocalWallet,
@@ -150,7 +150,7 @@ where
     }
 
     fn token_id256(&self, token_id: Self::TokenId) -> Id256 {
-        Id256::from_brc20_tick(token_id.inner())
+        Id256::from(token_id)
     }
 
     async fn bridge_canister_evm_address(&self) -> Result<did::H160> {
@@ -343,7 +343,10 @@ where
         Ok(matches!(
             op,
             Brc20BridgeOp::Deposit(Brc20BridgeDepositOp::MintOrderConfirmed { .. })
-                | Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxSent { .. })
+                | Brc20BridgeOp::Withdraw(
+                    Brc20BridgeWithdrawOp::AwaitTransferTxConfirmation { .. }
+                )
+                | Brc20BridgeOp::Withdraw(Brc20BridgeWithdrawOp::TransferTxConfirmed { .. })
         ))
     }
 }
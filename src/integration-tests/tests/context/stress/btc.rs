@@ -5,7 +5,7 @@ use std::time::Duration;
 use bitcoin::Amount;
 use bridge_client::BridgeCanisterClient as _;
 use bridge_did::id256::Id256;
-use bridge_did::operations::BtcBridgeOp;
+use bridge_did::operations::{BtcBridgeOp, BtcWithdrawStatus};
 use did::H160;
 use eth_signer::LocalWallet;
 use tokio::sync::RwLock;
@@ -287,9 +287,17 @@ where
             }
         };
 
+        // Withdrawal is considered finished once the BTC transaction is submitted, since
+        // confirmations depend on the Bitcoin network rather than on the bridge.
         Ok(matches!(
             op,
-            BtcBridgeOp::Erc20MintConfirmed(_) | BtcBridgeOp::BtcWithdrawConfirmed { .. }
+            BtcBridgeOp::Erc20MintConfirmed(_)
+                | BtcBridgeOp::WaitForBtcWithdrawal {
+                    status: BtcWithdrawStatus::Submitted { .. },
+                    ..
+                }
+                | BtcBridgeOp::BtcWithdrawConfirmed { .. }
+                | BtcBridgeOp::BtcWithdrawFailed { .. }
         ))
     }
 }
@@ -71,8 +71,9 @@ where
 
         // Mint tokens for bridge canister
         let bridge_client = ctx.erc20_bridge_client(ctx.admin_name());
+        let base_chain_id = base_evm_client.chain_id().await? as u32;
         let bridge_address = bridge_client
-            .get_bridge_canister_base_evm_address()
+            .get_bridge_canister_evm_link_address(base_chain_id)
             .await
             .unwrap()
             .unwrap();
@@ -118,9 +119,11 @@ where
             .await
             .unwrap();
 
+        let base_chain_id = base_evm_client.chain_id().await.unwrap() as u32;
         erc20_bridge_client
-            .set_base_btf_bridge_contract(&addr)
+            .set_evm_link_btf_bridge_contract(base_chain_id, &addr)
             .await
+            .unwrap()
             .unwrap();
 
         println!("BTFBridge contract initialized on base EVM");
@@ -433,3 +433,112 @@ async fn bail_out_of_impossible_deposit() {
 
     ctx.stop().await
 }
+
+#[tokio::test]
+async fn evm_native_and_wrapped_runes_are_bridged_by_one_canister() {
+    let mut ctx =
+        RunesContext::dfx(&[generate_rune_name()], test_evm(EvmSide::Wrapped).await).await;
+    let base_bridge = ctx
+        .link_base_btf_bridge()
+        .await
+        .expect("failed to link base-side bridge");
+
+    let evm = ctx.inner.wrapped_evm();
+    let erc20 = ctx
+        .inner
+        .deploy_test_wtm_token_on_evm(&evm, &ctx.eth_wallet, 1_000_000u64.into())
+        .await
+        .expect("failed to deploy erc20 token");
+    let evm_native_rune_id = ctx
+        .etch_evm_token_rune(&erc20, 0, 1_000_000)
+        .await
+        .expect("failed to etch rune");
+    let wrapped_rune_id = ctx
+        .runes
+        .runes
+        .keys()
+        .find(|id| **id != evm_native_rune_id)
+        .copied()
+        .unwrap();
+
+    // wrapped rune is minted by the wrapped-side bridge
+    let wallet_address = ctx.eth_wallet.address().into();
+    let nonce = evm
+        .get_next_nonce(&wallet_address)
+        .await
+        .expect("failed to get nonce");
+    ctx.deposit_runes_to(
+        &[(&wrapped_rune_id, 100)],
+        &wallet_address,
+        &ctx.eth_wallet,
+        nonce,
+        None,
+        RuneDepositStrategy::AllInOne,
+    )
+    .await
+    .expect("wrapped rune deposit failed");
+    assert_eq!(
+        ctx.wrapped_balance(&wrapped_rune_id, &ctx.eth_wallet).await,
+        100
+    );
+
+    // EVM-native token is locked by the base-side bridge and issued as the rune on Bitcoin
+    let recipient = ctx.runes.ord_wallet.address.clone();
+    ctx.lock_evm_native_tokens(&base_bridge, &recipient, &evm_native_rune_id, 1_000)
+        .await
+        .expect("failed to lock erc20 tokens");
+    assert_eq!(
+        ctx.wrapped_balance(&evm_native_rune_id, &ctx.eth_wallet)
+            .await,
+        999_000
+    );
+
+    let ctx = Arc::new(ctx);
+    let ctx_t = ctx.clone();
+    block_until_succeeds(
+        move || {
+            let ctx_t = ctx_t.clone();
+            let recipient = recipient.clone();
+            Box::pin(async move {
+                let balance = ctx_t
+                    .ord_rune_balance(&recipient, &evm_native_rune_id)
+                    .await?;
+                if balance == 1_000 {
+                    return Ok(());
+                }
+
+                Err(anyhow::anyhow!("Expected rune balance 1000; got {balance}"))
+            })
+        },
+        &ctx.inner,
+        Duration::from_secs(180),
+    )
+    .await;
+
+    // depositing the rune back releases the EVM-native token from the base-side bridge
+    let nonce = evm
+        .get_next_nonce(&wallet_address)
+        .await
+        .expect("failed to get nonce");
+    ctx.deposit_runes_to(
+        &[(&evm_native_rune_id, 400)],
+        &wallet_address,
+        &ctx.eth_wallet,
+        nonce,
+        None,
+        RuneDepositStrategy::AllInOne,
+    )
+    .await
+    .expect("evm-native rune deposit failed");
+    assert_eq!(
+        ctx.wrapped_balance(&evm_native_rune_id, &ctx.eth_wallet)
+            .await,
+        999_400
+    );
+    assert_eq!(
+        ctx.wrapped_balance(&wrapped_rune_id, &ctx.eth_wallet).await,
+        100
+    );
+
+    ctx.stop().await
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use bitcoin::Amount;
-use bridge_did::operations::BtcBridgeOp;
+use bridge_did::operations::{BtcBridgeOp, BtcWithdrawStatus};
 use btc_bridge::canister::eth_address_to_subaccount;
 use icrc_client::account::Account;
 
@@ -604,8 +604,17 @@ async fn test_should_track_deposit_and_withdrawal_operation() {
         .expect("error trying to retrieve withdrawal operation")
         .expect("withdrawal operation not found");
 
+    // BTC is received by the recipient, so the transaction is at least submitted.
     assert!(
-        matches!(&withdrawal_operation, BtcBridgeOp::BtcWithdrawConfirmed { eth_address } if *eth_address == wallet.address().into()),
+        matches!(
+            &withdrawal_operation,
+            BtcBridgeOp::WaitForBtcWithdrawal { event, status: BtcWithdrawStatus::Submitted { .. }, .. }
+                if event.sender == wallet.address().into()
+        ) || matches!(
+            &withdrawal_operation,
+            BtcBridgeOp::BtcWithdrawConfirmed { eth_address, status: Some(BtcWithdrawStatus::Confirmed { .. }), .. }
+                if *eth_address == wallet.address().into()
+        ),
         "Incorrect operation result: {withdrawal_operation:?}"
     );
 
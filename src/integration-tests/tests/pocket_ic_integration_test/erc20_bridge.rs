@@ -3,7 +3,6 @@ use std::time::Duration;
 use alloy_sol_types::{SolCall, SolConstructor};
 use bridge_canister::bridge::Operation;
 use bridge_client::{BridgeCanisterClient, Erc20BridgeClient};
-use bridge_did::bridge_side::BridgeSide;
 use bridge_did::id256::Id256;
 use bridge_did::operations::{Erc20BridgeOp, Erc20OpStage};
 use bridge_utils::{BTFBridge, UUPSProxy};
@@ -95,21 +94,23 @@ impl ContextWithBridges {
         let base_btf_bridge = create_btf_bridge(
             &ctx,
             &bob_wallet,
-            BridgeSide::Base,
+            false,
             expected_fee_charge_address.into(),
             base_wrapped_token_deployer,
             erc20_bridge_address.clone(),
         )
         .await;
+        let base_chain_id = ctx.base_evm().chain_id().await.unwrap() as u32;
         erc20_bridge_client
-            .set_base_btf_bridge_contract(&base_btf_bridge)
+            .set_evm_link_btf_bridge_contract(base_chain_id, &base_btf_bridge)
             .await
+            .unwrap()
             .unwrap();
 
         let wrapped_btf_bridge = create_btf_bridge(
             &ctx,
             &bob_wallet,
-            BridgeSide::Wrapped,
+            true,
             expected_fee_charge_address.into(),
             wrapped_wrapped_token_deployer,
             erc20_bridge_address.clone(),
@@ -722,7 +723,7 @@ async fn test_should_get_batch_mint_errors_if_mint_fails() {
     let ctx_t = ctx.context.clone();
     let alice_wallet_t = alice_wallet.clone();
 
-    let (tx_hash, mint_results) = block_until_succeeds(
+    let reason = block_until_succeeds(
         move || {
             let ctx = ctx_t.clone();
             let erc20_bridge_client = ctx.erc20_bridge_client(ADMIN);
@@ -736,15 +737,10 @@ async fn test_should_get_batch_mint_errors_if_mint_fails() {
                 if operation_id.as_u64() == expected_operation_id as u64 {
                     match op {
                         Erc20BridgeOp {
-                            stage:
-                                Erc20OpStage::WaitForMintConfirm {
-                                    tx_hash,
-                                    mint_results,
-                                    ..
-                                },
+                            stage: Erc20OpStage::Refund { reason, .. },
                             ..
-                        } => Ok((tx_hash, mint_results)),
-                        _ => anyhow::bail!("Expected WaitForMintConfirm stage: {op:?}"),
+                        } => Ok(reason),
+                        _ => anyhow::bail!("Expected Refund stage: {op:?}"),
                     }
                 } else {
                     anyhow::bail!(
@@ -754,41 +750,30 @@ async fn test_should_get_batch_mint_errors_if_mint_fails() {
             })
         },
         &ctx.context,
-        Duration::from_secs(90),
+        Duration::from_secs(180),
     )
     .await;
 
-    println!("Result: {tx_hash:?} | {mint_results:?}");
-    assert!(tx_hash.is_none(), "mint tx should not have been sent");
-    assert_eq!(mint_results.len(), 1);
-    assert_eq!(
-        mint_results[0],
-        bridge_did::batch_mint_result::BatchMintErrorCode::Reverted(
-            "Invalid token pair".to_string()
-        )
-    );
+    // The failed order is cancelled on the wrapped EVM before the tokens are refunded.
+    println!("Refund reason: {reason}");
+    assert!(reason.contains("Invalid token pair"), "{reason}");
 }
 
 async fn create_btf_bridge(
     ctx: &PocketIcTestContext,
     wallet: &LocalWallet,
-    side: BridgeSide,
+    is_wrapped: bool,
     fee_charge: H160,
     wrapped_token_deployer: H160,
     minter_address: H160,
 ) -> H160 {
-    let is_wrapped = match side {
-        BridgeSide::Base => false,
-        BridgeSide::Wrapped => true,
-    };
-
     let mut btf_input = BTFBridge::BYTECODE.to_vec();
     let constructor = BTFBridge::constructorCall {}.abi_encode();
     btf_input.extend_from_slice(&constructor);
 
-    let evm = match side {
-        BridgeSide::Base => ctx.base_evm(),
-        BridgeSide::Wrapped => ctx.wrapped_evm(),
+    let evm = match is_wrapped {
+        false => ctx.base_evm(),
+        true => ctx.wrapped_evm(),
     };
 
     let bridge_address = ctx
@@ -0,0 +1,364 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use bitcoin::{Address, Network};
+use bitcoin_bridge_core::http::IcHttpClient;
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use bridge_canister::runtime::RuntimeState;
+use bridge_did::id256::Id256;
+use bridge_did::inscription::InscriptionId;
+use bridge_did::order::MintOrder;
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, Utxo};
+
+use super::index_provider::InscriptionLocation;
+use crate::canister::{get_ordinals_state, get_runtime_state};
+use crate::core::index_provider::{InscriptionIndexProvider, OrdIndexProvider};
+use crate::interface::DepositError;
+use crate::key::{BtcSignerType, KeyError, get_derivation_path_ic};
+use crate::ledger::{InscriptionUtxo, UtxoKey};
+use crate::ops::OrdinalsBridgeOpImpl;
+use crate::state::OrdinalsState;
+
+/// Symbol of the wrapped inscription tokens.
+const INSCRIPTION_SYMBOL: &[u8] = b"ORD";
+
+pub(crate) struct InscriptionDeposit<
+    UTXO: UtxoProvider = IcUtxoProvider,
+    INDEX: InscriptionIndexProvider = OrdIndexProvider<IcHttpClient>,
+> {
+    ordinals_state: Rc<RefCell<OrdinalsState>>,
+    runtime_state: RuntimeState<OrdinalsBridgeOpImpl>,
+    network: Network,
+    signer: BtcSignerType,
+    utxo_provider: UTXO,
+    index_provider: INDEX,
+}
+
+impl InscriptionDeposit<IcUtxoProvider, OrdIndexProvider<IcHttpClient>> {
+    pub fn new(
+        state: Rc<RefCell<OrdinalsState>>,
+        runtime_state: RuntimeState<OrdinalsBridgeOpImpl>,
+    ) -> Result<Self, DepositError> {
+        let state_ref = state.borrow();
+
+        let signing_strategy = get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_signing_strategy();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let fee_estimator = state_ref.fee_estimator();
+        let indexer_urls = state_ref.indexer_urls();
+        let indexer_headers = state_ref.indexer_headers();
+        let signer = state_ref
+            .btc_signer(&signing_strategy)
+            .ok_or(DepositError::SignerNotInitialized)?;
+        let consensus_threshold = state_ref.indexer_consensus_threshold();
+
+        drop(state_ref);
+
+        Ok(Self {
+            ordinals_state: state,
+            runtime_state,
+            network,
+            signer,
+            utxo_provider: IcUtxoProvider::new(ic_network, Duration::ZERO, fee_estimator),
+            index_provider: OrdIndexProvider::new(
+                IcHttpClient::new(indexer_headers),
+                indexer_urls,
+                consensus_threshold,
+            ),
+        })
+    }
+
+    pub fn get(runtime_state: RuntimeState<OrdinalsBridgeOpImpl>) -> Result<Self, DepositError> {
+        Self::new(get_ordinals_state(), runtime_state)
+    }
+}
+
+impl<UTXO: UtxoProvider, INDEX: InscriptionIndexProvider> InscriptionDeposit<UTXO, INDEX> {
+    // Get input utxos
+    pub async fn get_inputs(&self, dst_address: &H160) -> Result<Vec<Utxo>, DepositError> {
+        let transit_address = self.get_transit_address(dst_address).await?;
+        log::debug!("Getting inputs for address: {transit_address}");
+
+        Ok(self.get_deposit_utxos(&transit_address).await?.utxos)
+    }
+
+    /// Check for confirmations
+    pub async fn check_confirmations(
+        &self,
+        dst_address: &H160,
+        utxos: &[Utxo],
+    ) -> Result<(), DepositError> {
+        let transit_address = self.get_transit_address(dst_address).await?;
+        let mut utxo_response = self.get_deposit_utxos(&transit_address).await?;
+        utxo_response.utxos.retain(|v| utxos.contains(v));
+
+        self.validate_utxo_confirmations(&utxo_response)
+            .map_err(|_| DepositError::UtxosNotConfirmed)
+    }
+
+    /// Finds the utxo holding the inscription among the given deposit utxos.
+    ///
+    /// The location of the inscription must be agreed by the indexers, and the inscription
+    /// must not be held by the bridge already. The utxo must not hold other inscriptions, since
+    /// only the deposited one could be withdrawn.
+    pub async fn find_inscription(
+        &self,
+        dst_address: &H160,
+        inscription_id: &InscriptionId,
+        utxos: &[Utxo],
+    ) -> Result<(InscriptionLocation, Utxo), DepositError> {
+        if self
+            .ordinals_state
+            .borrow()
+            .ledger()
+            .inscription(&Id256::from(*inscription_id))
+            .is_some()
+        {
+            return Err(DepositError::InscriptionAlreadyBridged(
+                inscription_id.to_string(),
+            ));
+        }
+
+        let transit_address = self.get_transit_address(dst_address).await?;
+        let locations = self
+            .index_provider
+            .get_inscriptions(&transit_address)
+            .await?;
+        let location = find_single_inscription(&locations, inscription_id)?;
+
+        let utxo = utxos
+            .iter()
+            .find(|utxo| UtxoKey::from(&utxo.outpoint) == location.utxo)
+            .ok_or_else(|| DepositError::InscriptionNotFound(inscription_id.to_string()))?;
+
+        if location.offset >= utxo.value {
+            return Err(DepositError::Unavailable(format!(
+                "Inscription {inscription_id} offset {} is outside of the utxo {}",
+                location.offset, location.utxo
+            )));
+        }
+
+        Ok((location, utxo.clone()))
+    }
+
+    pub async fn get_deposit_utxos(
+        &self,
+        transit_address: &Address,
+    ) -> Result<GetUtxosResponse, DepositError> {
+        let mut utxo_response = self.utxo_provider.get_utxos(transit_address).await?;
+
+        log::trace!(
+            "Found {} utxos at address {transit_address}: {:?}.",
+            utxo_response.utxos.len(),
+            utxo_response.utxos
+        );
+
+        self.filter_out_used_utxos(&mut utxo_response);
+
+        log::trace!(
+            "Utxos at address {transit_address} after filtering out used utxos: {:?}",
+            utxo_response.utxos
+        );
+
+        Ok(utxo_response)
+    }
+
+    async fn get_transit_address(&self, eth_address: &H160) -> Result<Address, KeyError> {
+        self.signer
+            .get_transit_address(eth_address, self.network)
+            .await
+    }
+
+    pub fn validate_utxo_confirmations(&self, utxo_info: &GetUtxosResponse) -> Result<(), u32> {
+        let min_confirmations = self.ordinals_state.borrow().min_confirmations();
+        let utxo_min_confirmations = utxo_info
+            .utxos
+            .iter()
+            .map(|utxo| utxo_info.tip_height - utxo.height + 1)
+            .min()
+            .unwrap_or_default();
+
+        if min_confirmations > utxo_min_confirmations {
+            Err(utxo_min_confirmations)
+        } else {
+            log::trace!(
+                "Current utxo confirmations {} satisfies minimum {}. Proceeding.",
+                utxo_min_confirmations,
+                min_confirmations
+            );
+            Ok(())
+        }
+    }
+
+    /// Create the unsigned mint order of the ERC-721 token of the inscription.
+    ///
+    /// The token is minted by the collection contract the bridge is linked to, with the id of
+    /// the token being the `Id256` of the inscription.
+    pub fn create_unsigned_mint_order(
+        &self,
+        dst_address: &H160,
+        location: &InscriptionLocation,
+        nonce: u32,
+    ) -> MintOrder {
+        let state_ref = self.ordinals_state.borrow();
+
+        let sender_chain_id = state_ref.btc_chain_id();
+        let sender = Id256::from_evm_address(dst_address, sender_chain_id);
+        let src_token = Id256::from(location.id);
+
+        let runtime_state = self.runtime_state.borrow();
+        let config = runtime_state.config.borrow();
+        let recipient_chain_id = config.get_evm_params().unwrap().chain_id;
+        let collection = config
+            .get_btf_bridge_contract()
+            .expect("collection contract is set before deposits are requested");
+
+        let mut symbol = [0; 16];
+        symbol[..INSCRIPTION_SYMBOL.len()].copy_from_slice(INSCRIPTION_SYMBOL);
+
+        MintOrder {
+            amount: 1u128.into(),
+            sender,
+            src_token,
+            recipient: dst_address.clone(),
+            dst_token: collection,
+            nonce,
+            sender_chain_id,
+            recipient_chain_id: recipient_chain_id as u32,
+            name: inscription_name(location.number),
+            symbol,
+            decimals: 0,
+            approve_spender: Default::default(),
+            approve_amount: Default::default(),
+            fee_payer: H160::default(),
+        }
+    }
+
+    /// Adds the utxo holding the inscription to the ledger.
+    pub async fn mark_inscription_as_deposited(
+        &self,
+        eth_address: &H160,
+        location: &InscriptionLocation,
+        utxo: Utxo,
+    ) -> Result<(), DepositError> {
+        let address = self.get_transit_address(eth_address).await?;
+
+        self.ordinals_state
+            .borrow_mut()
+            .ledger_mut()
+            .deposit(InscriptionUtxo {
+                inscription_id: location.id,
+                utxo,
+                offset: location.offset,
+                script_buf: address.script_pubkey().into_bytes(),
+                derivation_path: get_derivation_path_ic(eth_address),
+            });
+
+        Ok(())
+    }
+
+    /// Filter out used utxos from the response
+    fn filter_out_used_utxos(&self, get_utxos_response: &mut GetUtxosResponse) {
+        let state_ref = self.ordinals_state.borrow();
+        let ledger = state_ref.ledger();
+
+        get_utxos_response
+            .utxos
+            .retain(|utxo| !ledger.used_utxo_contains(&UtxoKey::from(&utxo.outpoint)));
+    }
+}
+
+/// Finds the location of the inscription, which must be the only inscription of its utxo.
+fn find_single_inscription(
+    locations: &[InscriptionLocation],
+    inscription_id: &InscriptionId,
+) -> Result<InscriptionLocation, DepositError> {
+    let location = locations
+        .iter()
+        .find(|location| location.id == *inscription_id)
+        .ok_or_else(|| DepositError::InscriptionNotFound(inscription_id.to_string()))?;
+
+    if locations
+        .iter()
+        .any(|other| other.utxo == location.utxo && other.id != location.id)
+    {
+        return Err(DepositError::MultipleInscriptions(
+            inscription_id.to_string(),
+        ));
+    }
+
+    Ok(location.clone())
+}
+
+/// Name of the wrapped token of the inscription with the given number.
+fn inscription_name(number: i64) -> [u8; 32] {
+    let name = format!("Inscription #{number}");
+    let len = name.len().min(32);
+
+    let mut buf = [0; 32];
+    buf[..len].copy_from_slice(&name.as_bytes()[..len]);
+    buf
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn location(id: &str, utxo: UtxoKey, offset: u64) -> InscriptionLocation {
+        InscriptionLocation {
+            id: InscriptionId::from_str(id).unwrap(),
+            number: 1,
+            utxo,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_should_reject_utxo_with_multiple_inscriptions() {
+        let utxo = UtxoKey {
+            tx_id: [1; 32],
+            vout: 0,
+        };
+        let other_utxo = UtxoKey {
+            tx_id: [2; 32],
+            vout: 0,
+        };
+        let first_id = "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0";
+        let second_id = "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i1";
+        let first = location(first_id, utxo, 0);
+
+        let found = find_single_inscription(
+            &[first.clone(), location(second_id, other_utxo, 0)],
+            &first.id,
+        )
+        .unwrap();
+        assert_eq!(found, first);
+
+        let result =
+            find_single_inscription(&[first.clone(), location(second_id, utxo, 100)], &first.id);
+        assert!(matches!(result, Err(DepositError::MultipleInscriptions(_))));
+
+        let result = find_single_inscription(&[], &first.id);
+        assert!(matches!(result, Err(DepositError::InscriptionNotFound(_))));
+    }
+
+    #[test]
+    fn test_should_create_inscription_name() {
+        assert_eq!(&inscription_name(42)[..16], b"Inscription #42\0");
+        assert_eq!(&inscription_name(-7)[..15], b"Inscription #-7");
+        assert_eq!(
+            &inscription_name(i64::MIN)[..],
+            b"Inscription #-922337203685477580"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+mod hiro;
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use bitcoin::{Address, OutPoint, Txid};
+use bitcoin_bridge_core::consensus::get_indexer_consensus;
+use bitcoin_bridge_core::http::HttpClient;
+use bridge_did::inscription::InscriptionId;
+use serde::de::DeserializeOwned;
+
+use self::hiro::{GetInscriptionsResponse, InscriptionResponse};
+use crate::interface::DepositError;
+use crate::ledger::UtxoKey;
+
+pub(crate) trait InscriptionIndexProvider {
+    /// Get the inscriptions held by the given address.
+    async fn get_inscriptions(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<InscriptionLocation>, DepositError>;
+}
+
+/// Current location of an inscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InscriptionLocation {
+    pub id: InscriptionId,
+    pub number: i64,
+    /// Utxo holding the inscribed sat.
+    pub utxo: UtxoKey,
+    /// Offset of the inscribed sat in the utxo.
+    pub offset: u64,
+}
+
+/// Maximum size of a page of a list response.
+const MAX_PAGE_RESPONSE_BYTES: u64 = 200_000;
+const HIRO_MAX_LIMIT: u64 = 60;
+
+/// Implementation of the `InscriptionIndexProvider` trait that uses the `HttpClient` to make
+/// requests to the `ord` indexers.
+pub struct OrdIndexProvider<C: HttpClient> {
+    client: C,
+    indexer_urls: HashSet<String>,
+    indexer_consensus_threshold: u8,
+}
+
+impl<C> OrdIndexProvider<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, indexer_urls: HashSet<String>, indexer_consensus_threshold: u8) -> Self {
+        Self {
+            client,
+            indexer_urls,
+            indexer_consensus_threshold,
+        }
+    }
+
+    /// Get consensus response from the indexers.
+    ///
+    /// All indexers must return the same response for the same input, otherwise the function
+    /// returns an error.
+    async fn get_consensus_response<T>(
+        &self,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> Result<T, DepositError>
+    where
+        T: DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        Ok(get_indexer_consensus(
+            self.indexer_urls.iter(),
+            self.indexer_consensus_threshold,
+            |url| self.client.http_request::<T>(url, uri, max_response_bytes),
+        )
+        .await?)
+    }
+}
+
+impl<C> InscriptionIndexProvider for OrdIndexProvider<C>
+where
+    C: HttpClient,
+{
+    async fn get_inscriptions(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<InscriptionLocation>, DepositError> {
+        let mut inscriptions = vec![];
+        let mut offset = 0;
+        let mut total = u64::MAX;
+
+        while offset < total {
+            let uri = format!(
+                "/ordinals/v1/inscriptions?address={address}&offset={offset}&limit={HIRO_MAX_LIMIT}"
+            );
+            let response = self
+                .get_consensus_response::<GetInscriptionsResponse>(&uri, MAX_PAGE_RESPONSE_BYTES)
+                .await?;
+
+            total = response.total;
+            offset += response.results.len() as u64;
+
+            for result in &response.results {
+                inscriptions.push(parse_inscription(result)?);
+            }
+
+            if response.results.is_empty() {
+                break;
+            }
+        }
+
+        Ok(inscriptions)
+    }
+}
+
+fn parse_inscription(
+    inscription: &InscriptionResponse,
+) -> Result<InscriptionLocation, DepositError> {
+    let invalid = || {
+        DepositError::Unavailable(format!(
+            "Invalid inscription in the indexer response: {inscription:?}"
+        ))
+    };
+
+    let id = InscriptionId::from_str(&inscription.id).map_err(|_| invalid())?;
+
+    let mut location = inscription.location.split(':');
+    let (Some(txid), Some(vout), Some(offset), None) = (
+        location.next(),
+        location.next(),
+        location.next(),
+        location.next(),
+    ) else {
+        return Err(invalid());
+    };
+
+    let outpoint = OutPoint {
+        txid: Txid::from_str(txid).map_err(|_| invalid())?,
+        vout: vout.parse().map_err(|_| invalid())?,
+    };
+
+    Ok(InscriptionLocation {
+        id,
+        number: inscription.number,
+        utxo: UtxoKey::from(outpoint),
+        offset: offset.parse().map_err(|_| invalid())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash as _;
+
+    use super::*;
+
+    const TXID: &str = "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799";
+
+    fn response(location: &str) -> InscriptionResponse {
+        InscriptionResponse {
+            id: format!("{TXID}i0"),
+            number: 42,
+            location: location.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_should_parse_inscription_location() {
+        let location = parse_inscription(&response(&format!("{TXID}:1:2500"))).unwrap();
+
+        assert_eq!(location.id.to_string(), format!("{TXID}i0"));
+        assert_eq!(location.number, 42);
+        assert_eq!(
+            location.utxo,
+            UtxoKey {
+                tx_id: Txid::from_str(TXID).unwrap().to_byte_array(),
+                vout: 1,
+            }
+        );
+        assert_eq!(location.offset, 2500);
+    }
+
+    #[test]
+    fn test_should_not_parse_invalid_inscription_location() {
+        assert!(parse_inscription(&response(&format!("{TXID}:1"))).is_err());
+        assert!(parse_inscription(&response(&format!("{TXID}:1:0:0"))).is_err());
+        assert!(parse_inscription(&response(&format!("{TXID}:a:0"))).is_err());
+        assert!(parse_inscription(&response("txid:1:0")).is_err());
+    }
+}
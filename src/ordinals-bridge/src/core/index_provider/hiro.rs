@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+/// Response for `/ordinals/v1/inscriptions?address={address}` endpoint.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GetInscriptionsResponse {
+    pub total: u64,
+    pub results: Vec<InscriptionResponse>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct InscriptionResponse {
+    pub id: String,
+    pub number: i64,
+    /// Location of the inscribed sat: `<txid>:<vout>:<offset>`.
+    pub location: String,
+}
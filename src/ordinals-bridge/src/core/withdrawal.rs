@@ -0,0 +1,592 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash as _;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Txid, Witness,
+};
+use bitcoin_bridge_core::funding::select_funding_utxos;
+use bitcoin_bridge_core::http::IcHttpClient;
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use bridge_did::event_data::BurntEventData;
+use bridge_did::id256::Id256;
+use bridge_did::operations::{DidTransaction, InscriptionWithdrawalPayload};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
+use ord_rs::fees::estimate_transaction_fees;
+use ord_rs::wallet::{ScriptType, TxInputInfo};
+
+use super::index_provider::{InscriptionIndexProvider, InscriptionLocation, OrdIndexProvider};
+use crate::canister::{get_ordinals_state, get_runtime_state};
+use crate::constants::{DUST_THRESHOLD, FEE_RATE_UPDATE_INTERVAL, INSCRIPTION_POSTAGE};
+use crate::interface::WithdrawError;
+use crate::key::{BtcSignerType, get_derivation_path, ic_dp_to_derivation_path};
+use crate::ledger::{InscriptionUtxo, UtxoKey};
+use crate::state::OrdinalsState;
+
+pub fn new_withdraw_payload(
+    burnt_event_data: BurntEventData,
+    state: &OrdinalsState,
+) -> Result<InscriptionWithdrawalPayload, WithdrawError> {
+    let BurntEventData {
+        recipient_id,
+        amount,
+        to_token,
+        sender,
+        ..
+    } = burnt_event_data;
+
+    let amount: u128 = amount.0.to();
+    if amount != 1 {
+        return Err(WithdrawError::InvalidRequest(format!(
+            "Inscription tokens are burnt one at a time, but {amount} tokens are burnt"
+        )));
+    }
+
+    let Ok(address_string) = String::from_utf8(recipient_id.clone()) else {
+        return Err(WithdrawError::InvalidRequest(format!(
+            "Failed to decode recipient address from raw data: {recipient_id:?}"
+        )));
+    };
+
+    let Ok(address) = Address::from_str(&address_string) else {
+        return Err(WithdrawError::InvalidRequest(format!(
+            "Failed to decode recipient address from string: {address_string}"
+        )));
+    };
+
+    let Some(token_id) = Id256::from_slice(&to_token) else {
+        return Err(WithdrawError::InvalidRequest(format!(
+            "Failed to decode token id from the value {to_token:?}"
+        )));
+    };
+
+    let Some(inscription) = state.ledger().inscription(&token_id) else {
+        return Err(WithdrawError::UnknownInscription(format!(
+            "No inscription is held by the bridge for the token id {token_id:?}"
+        )));
+    };
+
+    Ok(InscriptionWithdrawalPayload {
+        inscription_id: inscription.inscription_id,
+        request_ts: ic::time(),
+        sender,
+        dst_address: address.assume_checked().to_string(),
+    })
+}
+
+pub(crate) struct Withdrawal<
+    UTXO: UtxoProvider = IcUtxoProvider,
+    INDEX: InscriptionIndexProvider = OrdIndexProvider<IcHttpClient>,
+> {
+    state: Rc<RefCell<OrdinalsState>>,
+    utxo_provider: UTXO,
+    index_provider: INDEX,
+    signer: BtcSignerType,
+    network: Network,
+}
+
+impl Withdrawal<IcUtxoProvider, OrdIndexProvider<IcHttpClient>> {
+    pub fn new(state: Rc<RefCell<OrdinalsState>>) -> Result<Self, WithdrawError> {
+        let state_ref = state.borrow();
+
+        let signing_strategy = get_runtime_state()
+            .borrow()
+            .config
+            .borrow()
+            .get_signing_strategy();
+
+        let network = state_ref.network();
+        let ic_network = state_ref.ic_btc_network();
+        let fee_estimator = state_ref.fee_estimator();
+        let index_provider = OrdIndexProvider::new(
+            IcHttpClient::new(state_ref.indexer_headers()),
+            state_ref.indexer_urls(),
+            state_ref.indexer_consensus_threshold(),
+        );
+        let signer = state_ref
+            .btc_signer(&signing_strategy)
+            .ok_or(WithdrawError::SignerNotInitialized)?;
+
+        drop(state_ref);
+
+        Ok(Self {
+            state,
+            utxo_provider: IcUtxoProvider::new(ic_network, Duration::ZERO, fee_estimator),
+            index_provider,
+            signer,
+            network,
+        })
+    }
+
+    pub fn get() -> Result<Self, WithdrawError> {
+        Self::new(get_ordinals_state())
+    }
+}
+
+impl<UTXO: UtxoProvider, INDEX: InscriptionIndexProvider> Withdrawal<UTXO, INDEX> {
+    /// Build the transaction sending the inscribed sat to the recipient.
+    ///
+    /// The first input of the transaction is the utxo holding the inscription, so the sats of
+    /// the outputs are taken from it in order:
+    ///
+    /// - the sats preceding the inscribed sat are split off into an output to the funding
+    ///   address, unless there are too few of them to make a non-dust output;
+    /// - the recipient output starts with the inscribed sat, or holds it at the same offset as
+    ///   the deposit utxo if the preceding sats are not split off;
+    /// - the rest of the inscription utxo and the funding utxos pay the fee, and the change
+    ///   is returned to the funding address.
+    ///
+    /// The funding utxos are owned by the address associated with the sender. Utxos holding
+    /// inscriptions are never used for funding.
+    pub async fn build_transfer_transaction(
+        &self,
+        payload: &InscriptionWithdrawalPayload,
+    ) -> Result<DidTransaction, WithdrawError> {
+        let inscription = self.get_inscription_utxo(payload)?;
+
+        let Ok(dst_address) = Address::from_str(&payload.dst_address) else {
+            return Err(WithdrawError::InvalidRequest(format!(
+                "Failed to decode recipient address from string: {}",
+                payload.dst_address
+            )));
+        };
+        let dst_address = dst_address.assume_checked();
+
+        let funding_address = self.get_funding_address(&payload.sender).await?;
+        let fee_rate = self.get_fee_rate().await?;
+        let funding_utxos = self
+            .get_funding_utxos(&funding_address, &inscription)
+            .await?;
+
+        let (funding_utxos, outputs) = plan_transfer(TransferPlanArgs {
+            inscription_value: inscription.utxo.value,
+            offset: inscription.offset,
+            funding_utxos: &funding_utxos,
+            recipient_script: dst_address.script_pubkey(),
+            change_script: funding_address.script_pubkey(),
+            script_type: self.signer.address_type().script_type(),
+            fee_rate,
+        })
+        .ok_or(WithdrawError::InsufficientFunds)?;
+
+        log::info!("Funding utxos: {}", funding_utxos.len());
+        log::debug!("Funding utxos: {funding_utxos:?}");
+
+        let inputs = self.transfer_tx_input_info(
+            &inscription,
+            &funding_utxos,
+            &payload.sender,
+            &funding_address,
+        )?;
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs,
+        };
+
+        self.sign_transaction(unsigned_tx, &inputs)
+            .await
+            .map(DidTransaction)
+    }
+
+    /// Sends the transfer transaction and removes the inscription from the ledger.
+    ///
+    /// The inputs of the transaction are marked as used, so they are not spent again while the
+    /// transaction is not confirmed.
+    pub async fn send_transaction(
+        &self,
+        payload: &InscriptionWithdrawalPayload,
+        tx: &Transaction,
+    ) -> Result<(), WithdrawError> {
+        self.utxo_provider.send_tx(tx).await?;
+
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for input in &tx.input {
+            ledger.mark_as_used(UtxoKey::from(input.previous_output));
+        }
+        ledger.remove_inscription(&Id256::from(payload.inscription_id));
+
+        Ok(())
+    }
+
+    /// Check whether the transfer transaction is confirmed
+    ///
+    /// The transaction is confirmed when its recipient output has enough confirmations. As the
+    /// recipient may spend the output right away, the transaction is also considered confirmed
+    /// once the funding address doesn't hold any of its inputs anymore.
+    pub async fn await_transfer_transaction(
+        &self,
+        payload: &InscriptionWithdrawalPayload,
+        tx: &Transaction,
+    ) -> Result<(), WithdrawError> {
+        let txid = tx.txid();
+        let recipient = Address::from_str(&payload.dst_address)
+            .map_err(|_| WithdrawError::InvalidRequest(payload.dst_address.clone()))?
+            .assume_checked();
+        let recipient_vout = tx
+            .output
+            .iter()
+            .position(|output| output.script_pubkey == recipient.script_pubkey())
+            .ok_or(WithdrawError::NoInputs)? as u32;
+
+        let response = self
+            .utxo_provider
+            .get_utxos(&recipient)
+            .await
+            .map_err(|_| WithdrawError::TxNotConfirmed)?;
+        let min_confirmations = self.state.borrow().min_confirmations();
+        let output_confirmed = response.utxos.iter().any(|utxo| {
+            utxo.outpoint.txid == txid.as_byte_array()
+                && utxo.outpoint.vout == recipient_vout
+                && (response.tip_height + 1).saturating_sub(utxo.height) >= min_confirmations
+        });
+        if output_confirmed {
+            return Ok(());
+        }
+
+        let funding_address = self.get_funding_address(&payload.sender).await?;
+        let inputs: HashSet<UtxoKey> = tx
+            .input
+            .iter()
+            .map(|input| UtxoKey::from(input.previous_output))
+            .collect();
+        let inputs_unspent = self
+            .utxo_provider
+            .get_utxos(&funding_address)
+            .await
+            .map_err(|_| WithdrawError::TxNotConfirmed)?
+            .utxos
+            .iter()
+            .any(|utxo| inputs.contains(&UtxoKey::from(&utxo.outpoint)));
+
+        if inputs_unspent {
+            log::debug!("transfer transaction {txid} is not confirmed yet");
+            Err(WithdrawError::TxNotConfirmed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_inscription_utxo(
+        &self,
+        payload: &InscriptionWithdrawalPayload,
+    ) -> Result<InscriptionUtxo, WithdrawError> {
+        self.state
+            .borrow()
+            .ledger()
+            .inscription(&Id256::from(payload.inscription_id))
+            .ok_or_else(|| WithdrawError::UnknownInscription(payload.inscription_id.to_string()))
+    }
+
+    /// Get the transaction input info for the transfer transaction
+    fn transfer_tx_input_info(
+        &self,
+        inscription: &InscriptionUtxo,
+        funding_utxos: &[Utxo],
+        sender: &H160,
+        funding_address: &Address,
+    ) -> Result<Vec<TxInputInfo>, WithdrawError> {
+        let mut tx_input_info = Vec::with_capacity(funding_utxos.len() + 1);
+        tx_input_info.push(TxInputInfo {
+            outpoint: outpoint(&inscription.utxo)?,
+            tx_out: TxOut {
+                value: Amount::from_sat(inscription.utxo.value),
+                script_pubkey: ScriptBuf::from_bytes(inscription.script_buf.clone()),
+            },
+            derivation_path: ic_dp_to_derivation_path(&inscription.derivation_path)?,
+        });
+
+        let derivation_path = get_derivation_path(sender)?;
+        for utxo in funding_utxos {
+            tx_input_info.push(TxInputInfo {
+                outpoint: outpoint(utxo)?,
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: funding_address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            });
+        }
+
+        Ok(tx_input_info)
+    }
+
+    async fn sign_transaction(
+        &self,
+        unsigned_tx: Transaction,
+        tx_input_info: &[TxInputInfo],
+    ) -> Result<Transaction, WithdrawError> {
+        let signer = self
+            .state
+            .borrow()
+            .wallet(
+                &get_runtime_state()
+                    .borrow()
+                    .config
+                    .borrow()
+                    .get_signing_strategy(),
+            )
+            .ok_or(WithdrawError::SignerNotInitialized)?;
+
+        signer
+            .sign_transaction(&unsigned_tx, tx_input_info)
+            .await
+            .map_err(|e| WithdrawError::TransactionSigning(e.to_string()))
+    }
+
+    /// Get current fee rate, otherwise if too old, request a new one from the utxo provider.
+    async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
+        let (current_fee_rate, elapsed_since_last_fee_rate_update) = {
+            let state_ref = self.state.borrow();
+            (
+                state_ref.fee_rate(),
+                state_ref.last_fee_rate_update_elapsed(),
+            )
+        };
+
+        if elapsed_since_last_fee_rate_update > FEE_RATE_UPDATE_INTERVAL {
+            let fee_rate = self.utxo_provider.get_fee_rate().await?;
+            let mut state_ref = self.state.borrow_mut();
+            state_ref.update_fee_rate(fee_rate);
+
+            Ok(fee_rate)
+        } else {
+            Ok(current_fee_rate)
+        }
+    }
+
+    /// Get the BTC address that will be used to fund the transaction.
+    async fn get_funding_address(&self, eth_address: &H160) -> Result<Address, WithdrawError> {
+        self.signer
+            .get_transit_address(eth_address, self.network)
+            .await
+            .map_err(WithdrawError::from)
+    }
+
+    /// Get utxos available for funding the transaction, the biggest first.
+    ///
+    /// Used utxos and utxos holding any inscriptions are discarded.
+    async fn get_funding_utxos(
+        &self,
+        address: &Address,
+        inscription: &InscriptionUtxo,
+    ) -> Result<Vec<Utxo>, WithdrawError> {
+        let mut utxos = self
+            .utxo_provider
+            .get_utxos(address)
+            .await
+            .map(|utxos| utxos.utxos)
+            .map_err(|_| WithdrawError::NoInputs)?;
+
+        let inscribed_utxos: HashSet<UtxoKey> = self
+            .index_provider
+            .get_inscriptions(address)
+            .await
+            .map_err(|err| {
+                log::warn!("Failed to get inscriptions of the funding address: {err:?}");
+                WithdrawError::NoInputs
+            })?
+            .into_iter()
+            .map(|location: InscriptionLocation| location.utxo)
+            .collect();
+        let inscription_key = UtxoKey::from(&inscription.utxo.outpoint);
+
+        let state_ref = self.state.borrow();
+        let ledger = state_ref.ledger();
+        utxos.retain(|utxo| {
+            let key = UtxoKey::from(&utxo.outpoint);
+            key != inscription_key
+                && !inscribed_utxos.contains(&key)
+                && !ledger.used_utxo_contains(&key)
+        });
+
+        Ok(utxos)
+    }
+}
+
+fn outpoint(utxo: &Utxo) -> Result<OutPoint, WithdrawError> {
+    Ok(OutPoint {
+        txid: Txid::from_slice(&utxo.outpoint.txid)
+            .map_err(|_| WithdrawError::InvalidTxid(utxo.outpoint.txid.clone()))?,
+        vout: utxo.outpoint.vout,
+    })
+}
+
+/// Arguments for the `plan_transfer` function.
+struct TransferPlanArgs<'a> {
+    /// Value of the utxo holding the inscription
+    inscription_value: u64,
+    /// Offset of the inscribed sat in the utxo
+    offset: u64,
+    /// Utxos available for funding
+    funding_utxos: &'a [Utxo],
+    recipient_script: ScriptBuf,
+    change_script: ScriptBuf,
+    /// Script type of the spent utxos
+    script_type: ScriptType,
+    fee_rate: FeeRate,
+}
+
+/// Chooses the outputs of the transfer transaction and the funding utxos required to pay for it.
+/// Returns `None` if the funding utxos are not enough.
+fn plan_transfer(args: TransferPlanArgs) -> Option<(Vec<Utxo>, Vec<TxOut>)> {
+    let mut outputs = vec![];
+    let recipient_value = if args.offset >= DUST_THRESHOLD {
+        outputs.push(TxOut {
+            value: Amount::from_sat(args.offset),
+            script_pubkey: args.change_script.clone(),
+        });
+        INSCRIPTION_POSTAGE
+    } else {
+        args.offset + INSCRIPTION_POSTAGE
+    };
+    outputs.push(TxOut {
+        value: Amount::from_sat(recipient_value),
+        script_pubkey: args.recipient_script,
+    });
+    let outputs_value: u64 = outputs.iter().map(|output| output.value.to_sat()).sum();
+
+    let fee = |funding_count: usize, outputs: &[TxOut]| {
+        estimate_transaction_fees(
+            args.script_type,
+            funding_count + 1,
+            args.fee_rate,
+            &None,
+            outputs.to_vec(),
+        )
+        .to_sat()
+    };
+
+    // the inscription utxo may be enough to pay the fee by itself
+    let required_value = |funding_count: usize| {
+        (outputs_value + fee(funding_count, &outputs)).saturating_sub(args.inscription_value)
+    };
+    let funding_utxos = if required_value(0) == 0 {
+        vec![]
+    } else {
+        select_funding_utxos(args.funding_utxos.to_vec(), required_value)?
+    };
+    let inputs_value =
+        args.inscription_value + funding_utxos.iter().map(|utxo| utxo.value).sum::<u64>();
+
+    let mut outputs_with_change = outputs.clone();
+    outputs_with_change.push(TxOut {
+        value: Amount::ZERO,
+        script_pubkey: args.change_script,
+    });
+    let fee_with_change = fee(funding_utxos.len(), &outputs_with_change);
+
+    let change = inputs_value.saturating_sub(outputs_value + fee_with_change);
+    if change >= DUST_THRESHOLD {
+        outputs_with_change
+            .last_mut()
+            .expect("change output is added")
+            .value = Amount::from_sat(change);
+
+        return Some((funding_utxos, outputs_with_change));
+    }
+
+    // the change is too small, so it is left for the miners
+    Some((funding_utxos, outputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+
+    fn utxo(value: u64) -> Utxo {
+        Utxo {
+            outpoint: Outpoint {
+                txid: vec![1; 32],
+                vout: 0,
+            },
+            value,
+            height: 0,
+        }
+    }
+
+    fn args(inscription_value: u64, offset: u64, funding_utxos: &[Utxo]) -> TransferPlanArgs<'_> {
+        TransferPlanArgs {
+            inscription_value,
+            offset,
+            funding_utxos,
+            recipient_script: ScriptBuf::from_bytes(vec![1; 22]),
+            change_script: ScriptBuf::from_bytes(vec![2; 22]),
+            script_type: ScriptType::P2WSH,
+            fee_rate: FeeRate::from_sat_per_vb(10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_should_send_inscribed_sat_first_in_recipient_output() {
+        let (funding_utxos, outputs) = plan_transfer(args(10_000, 2_000, &[utxo(50_000)])).unwrap();
+
+        assert_eq!(funding_utxos.len(), 1);
+        assert_eq!(outputs.len(), 3);
+        // the sats before the inscription are split off
+        assert_eq!(outputs[0].value.to_sat(), 2_000);
+        assert_eq!(outputs[0].script_pubkey, ScriptBuf::from_bytes(vec![2; 22]));
+        // so the inscribed sat is the first sat of the recipient output
+        assert_eq!(outputs[1].value.to_sat(), INSCRIPTION_POSTAGE);
+        assert_eq!(outputs[1].script_pubkey, ScriptBuf::from_bytes(vec![1; 22]));
+        assert_eq!(outputs[2].script_pubkey, ScriptBuf::from_bytes(vec![2; 22]));
+        assert!(outputs[2].value.to_sat() >= DUST_THRESHOLD);
+        assert!(outputs[2].value.to_sat() < 50_000);
+    }
+
+    #[test]
+    fn test_should_keep_small_offset_in_recipient_output() {
+        let (_, outputs) = plan_transfer(args(10_000, 100, &[utxo(50_000)])).unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].value.to_sat(), 100 + INSCRIPTION_POSTAGE);
+        assert_eq!(outputs[0].script_pubkey, ScriptBuf::from_bytes(vec![1; 22]));
+    }
+
+    #[test]
+    fn test_should_pay_fee_from_inscription_utxo() {
+        let (funding_utxos, outputs) = plan_transfer(args(100_000, 0, &[utxo(50_000)])).unwrap();
+
+        assert!(funding_utxos.is_empty());
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].value.to_sat(), INSCRIPTION_POSTAGE);
+
+        let outputs_value: u64 = outputs.iter().map(|output| output.value.to_sat()).sum();
+        assert!(outputs_value < 100_000);
+    }
+
+    #[test]
+    fn test_should_use_several_funding_utxos() {
+        let funding_utxos = [utxo(8_000), utxo(8_000), utxo(8_000)];
+        let (funding_utxos, outputs) = plan_transfer(args(546, 0, &funding_utxos)).unwrap();
+
+        assert_eq!(funding_utxos.len(), 2);
+        assert_eq!(outputs[0].value.to_sat(), INSCRIPTION_POSTAGE);
+    }
+
+    #[test]
+    fn test_should_not_plan_without_funds() {
+        assert!(plan_transfer(args(546, 0, &[])).is_none());
+        assert!(plan_transfer(args(546, 0, &[utxo(1_000)])).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+use bitcoin_bridge_core::consensus::ConsensusError;
+use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
+use candid::CandidType;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::key::KeyError;
+
+#[derive(Debug, Error, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum GetAddressError {
+    #[error("key error: {0}")]
+    Key(String),
+}
+
+impl From<KeyError> for GetAddressError {
+    fn from(e: KeyError) -> Self {
+        Self::Key(e.to_string())
+    }
+}
+
+#[derive(Debug, Error, Clone, CandidType, Deserialize)]
+pub enum DepositError {
+    #[error("signer not initialized")]
+    SignerNotInitialized,
+    #[error("deposit UTXOs are not confirmed")]
+    UtxosNotConfirmed,
+    #[error("inscription {0} is not found at the deposit address")]
+    InscriptionNotFound(String),
+    #[error("inscription {0} is already bridged")]
+    InscriptionAlreadyBridged(String),
+    #[error("utxo holding inscription {0} holds other inscriptions too")]
+    MultipleInscriptions(String),
+    #[error("key error: {0}")]
+    KeyError(String),
+    #[error("indexers disagree: {first_response}; {another_response}")]
+    IndexersDisagree {
+        first_response: String,
+        another_response: String,
+    },
+    #[error(
+        "insufficient consensus: received {received_responses}/{required_responses}, checked {checked_indexers}"
+    )]
+    InsufficientConsensus {
+        received_responses: usize,
+        required_responses: u8,
+        checked_indexers: usize,
+    },
+    #[error("unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl From<KeyError> for DepositError {
+    fn from(e: KeyError) -> Self {
+        Self::KeyError(e.to_string())
+    }
+}
+
+impl From<ConsensusError> for DepositError {
+    fn from(err: ConsensusError) -> Self {
+        match err {
+            ConsensusError::IndexersDisagree {
+                first_response,
+                another_response,
+            } => Self::IndexersDisagree {
+                first_response,
+                another_response,
+            },
+            ConsensusError::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            } => Self::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            },
+        }
+    }
+}
+
+impl From<UtxoProviderError> for DepositError {
+    fn from(e: UtxoProviderError) -> Self {
+        Self::Unavailable(e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum WithdrawError {
+    NoInputs,
+    UnknownInscription(String),
+    TxNotConfirmed,
+    InvalidTxid(Vec<u8>),
+    TransactionSigning(String),
+    TransactionSerialization,
+    TransactionSending,
+    FeeRateRequest,
+    InsufficientFunds,
+    SignerNotInitialized,
+    KeyError(String),
+    InvalidRequest(String),
+}
+
+impl From<KeyError> for WithdrawError {
+    fn from(e: KeyError) -> Self {
+        Self::KeyError(e.to_string())
+    }
+}
+
+impl From<UtxoProviderError> for WithdrawError {
+    fn from(e: UtxoProviderError) -> Self {
+        match e {
+            UtxoProviderError::BtcAdapter(_) => Self::NoInputs,
+            UtxoProviderError::FeeRateRequest => Self::FeeRateRequest,
+            UtxoProviderError::TransactionSerialization => Self::TransactionSerialization,
+            UtxoProviderError::TransactionSending => Self::TransactionSending,
+        }
+    }
+}
+
+/// Summary of the inscriptions held by the bridge.
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct LedgerSummary {
+    /// Number of the bridged inscriptions held by the bridge.
+    pub inscriptions: u64,
+    /// Total value of the utxos holding the bridged inscriptions in satoshis.
+    pub inscriptions_value: u64,
+    /// Number of deposit utxos already processed by the bridge.
+    pub used_utxos: u64,
+}
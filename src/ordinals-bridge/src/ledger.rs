@@ -0,0 +1,160 @@
+mod inscription_utxo;
+
+pub use bitcoin_bridge_core::ledger::UtxoKey;
+use bridge_did::id256::Id256;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, MemoryId, MemoryManager, StableBTreeMap};
+
+pub use self::inscription_utxo::InscriptionUtxo;
+use crate::interface::LedgerSummary;
+use crate::memory::{INSCRIPTION_UTXOS_MEMORY_ID, USED_UTXOS_MEMORY_ID};
+
+/// Data structure to keep track of the inscriptions held by the canister.
+pub struct InscriptionLedger<M: Memory> {
+    /// utxos of the bridged inscriptions by the ids of their wrapped tokens
+    inscriptions: StableBTreeMap<Id256, InscriptionUtxo, M>,
+    /// contains a list of used utxos already processed in the deposit
+    used_utxos: StableBTreeMap<UtxoKey, (), M>,
+}
+
+impl<M> InscriptionLedger<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            inscriptions: StableBTreeMap::new(memory_manager.get(INSCRIPTION_UTXOS_MEMORY_ID)),
+            used_utxos: StableBTreeMap::new(memory_manager.get(USED_UTXOS_MEMORY_ID)),
+        }
+    }
+
+    /// Adds the utxo of the deposited inscription to the store and marks it as used, so it
+    /// cannot be deposited again.
+    pub fn deposit(&mut self, inscription_utxo: InscriptionUtxo) {
+        let key = UtxoKey::from(&inscription_utxo.utxo.outpoint);
+        let token_id = Id256::from(inscription_utxo.inscription_id);
+
+        log::debug!(
+            "Added inscription {} in utxo {key} to the ledger",
+            inscription_utxo.inscription_id
+        );
+
+        self.used_utxos.insert(key, ());
+        self.inscriptions.insert(token_id, inscription_utxo);
+    }
+
+    /// Returns the utxo of the inscription wrapped into the token with the given id.
+    pub fn inscription(&self, token_id: &Id256) -> Option<InscriptionUtxo> {
+        self.inscriptions.get(token_id)
+    }
+
+    /// Removes the inscription from the store once it is sent back to BTC.
+    pub fn remove_inscription(&mut self, token_id: &Id256) -> Option<InscriptionUtxo> {
+        self.inscriptions.remove(token_id)
+    }
+
+    /// Marks the utxo as used, so it is not spent by another transaction before the spending
+    /// transaction is confirmed.
+    pub fn mark_as_used(&mut self, utxo: UtxoKey) {
+        self.used_utxos.insert(utxo, ());
+
+        log::debug!("Marked utxo {utxo} as used");
+    }
+
+    /// Checks if the used utxos contains the given utxo.
+    pub fn used_utxo_contains(&self, utxo: &UtxoKey) -> bool {
+        self.used_utxos.contains_key(utxo)
+    }
+
+    /// Summarizes the inscriptions in the store.
+    pub fn summary(&self) -> LedgerSummary {
+        let (inscriptions, inscriptions_value) = self
+            .inscriptions
+            .iter()
+            .fold((0, 0), |(count, value), (_, inscription)| {
+                (count + 1, value + inscription.utxo.value)
+            });
+
+        LedgerSummary {
+            inscriptions,
+            inscriptions_value,
+            used_utxos: self.used_utxos.iter().count() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bridge_did::inscription::InscriptionId;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+    use crate::canister::get_ordinals_state;
+
+    fn inscription_utxo(tx_id: u8) -> InscriptionUtxo {
+        InscriptionUtxo {
+            inscription_id: InscriptionId::from_str(&format!("{}i0", hex::encode([tx_id; 32])))
+                .unwrap(),
+            utxo: Utxo {
+                outpoint: Outpoint {
+                    txid: vec![tx_id; 32],
+                    vout: 0,
+                },
+                value: 10_000,
+                height: 0,
+            },
+            offset: 0,
+            script_buf: vec![],
+            derivation_path: vec![],
+        }
+    }
+
+    #[test]
+    fn test_should_deposit_and_remove_inscription() {
+        MockContext::new().inject();
+
+        let utxo = inscription_utxo(1);
+        let token_id = Id256::from(utxo.inscription_id);
+        let utxo_key = UtxoKey::from(&utxo.utxo.outpoint);
+
+        let state = get_ordinals_state();
+        state.borrow_mut().ledger_mut().deposit(utxo.clone());
+
+        assert_eq!(state.borrow().ledger().inscription(&token_id), Some(utxo));
+        assert!(state.borrow().ledger().used_utxo_contains(&utxo_key));
+
+        state
+            .borrow_mut()
+            .ledger_mut()
+            .remove_inscription(&token_id);
+
+        assert_eq!(state.borrow().ledger().inscription(&token_id), None);
+        assert!(state.borrow().ledger().used_utxo_contains(&utxo_key));
+    }
+
+    #[test]
+    fn test_should_summarize_inscriptions() {
+        MockContext::new().inject();
+
+        let state = get_ordinals_state();
+        {
+            let mut state = state.borrow_mut();
+            let ledger = state.ledger_mut();
+            ledger.deposit(inscription_utxo(1));
+            ledger.deposit(inscription_utxo(2));
+            ledger.remove_inscription(&Id256::from(inscription_utxo(1).inscription_id));
+        }
+
+        assert_eq!(
+            state.borrow().ledger().summary(),
+            LedgerSummary {
+                inscriptions: 1,
+                inscriptions_value: 10_000,
+                used_utxos: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::borrow::Cow;
+
+use bridge_did::inscription::InscriptionId;
+use candid::{CandidType, Decode, Encode};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_stable_structures::{Bound, Storable};
+use serde::Deserialize;
+
+/// Utxo holding a bridged inscription.
+#[derive(Debug, Clone, Eq, PartialEq, CandidType, Deserialize)]
+pub struct InscriptionUtxo {
+    /// id of the inscription.
+    pub inscription_id: InscriptionId,
+    /// utxo holding the inscription.
+    pub utxo: Utxo,
+    /// offset of the inscribed sat in the utxo.
+    pub offset: u64,
+    /// script buffer of the utxo.
+    pub script_buf: Vec<u8>,
+    /// derivation path of the utxo.
+    pub derivation_path: Vec<Vec<u8>>,
+}
+
+impl Storable for InscriptionUtxo {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Encode!(self)
+            .expect("Failed to encode InscriptionUtxo")
+            .into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(&bytes, InscriptionUtxo).expect("Failed to decode InscriptionUtxo")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::str::FromStr;
+
+    use bitcoin::{Address, Network, PublicKey};
+    use did::H160;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+    use crate::key::get_derivation_path_ic;
+
+    #[test]
+    fn test_should_serialize_and_deserialize_inscription_utxo() {
+        let address = Address::p2wpkh(
+            &PublicKey::from_str(
+                "038f47dcd43ba6d97fc9ed2e3bba09b175a45fac55f0683e8cf771e8ced4572354",
+            )
+            .unwrap(),
+            Network::Signet,
+        )
+        .unwrap();
+        let derivation_path = get_derivation_path_ic(
+            &H160::from_hex_str("0x0dc9f6938e9b47fd8553df50bcbdb62d67239007").unwrap(),
+        );
+        let value = InscriptionUtxo {
+            inscription_id: InscriptionId::from_str(
+                "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0",
+            )
+            .unwrap(),
+            utxo: Utxo {
+                outpoint: Outpoint {
+                    txid: vec![1; 32],
+                    vout: 2,
+                },
+                value: 10_000,
+                height: 100,
+            },
+            offset: 1_000,
+            script_buf: address.script_pubkey().to_bytes(),
+            derivation_path,
+        };
+
+        let serialized = value.to_bytes();
+
+        let deserialized = InscriptionUtxo::from_bytes(serialized);
+        assert_eq!(deserialized, value);
+    }
+}
@@ -0,0 +1,33 @@
+//! Bridge of the ordinal inscriptions.
+//!
+//! An inscription sent to the deposit address of a user is wrapped into an ERC-721 token of the
+//! `InscriptionBridge` collection on the EVM side. When the token is burnt, the inscribed sat is
+//! sent back to the BTC address given in the burn request.
+
+pub mod canister;
+pub mod constants;
+pub mod core;
+pub mod interface;
+pub mod key;
+pub mod ledger;
+pub mod memory;
+pub mod ops;
+pub mod state;
+
+pub use crate::canister::OrdinalsBridge;
+
+const MAINNET_CHAIN_ID: u32 = 0;
+const TESTNET_CHAIN_ID: u32 = 1;
+const REGTEST_CHAIN_ID: u32 = 2;
+
+#[cfg(target_family = "wasm")]
+#[ic_canister::export_candid]
+pub fn idl() -> String {
+    use ic_metrics::Metrics;
+
+    let ordinals_bridge_idl = OrdinalsBridge::idl();
+    let mut metrics_idl = <OrdinalsBridge as Metrics>::get_idl();
+    metrics_idl.merge(&ordinals_bridge_idl);
+
+    candid::pretty::candid::compile(&metrics_idl.env.env, &Some(metrics_idl.actor))
+}
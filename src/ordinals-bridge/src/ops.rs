@@ -0,0 +1,231 @@
+mod deposit;
+mod events_handler;
+mod mint_order_handler;
+mod mint_tx_handler;
+mod withdraw;
+
+use bitcoin::Network;
+use bridge_canister::bridge::{Operation, OperationProgress};
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::ServiceId;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::event_data::{MinterNotificationType, NotifyMinterEventData};
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{
+    InscriptionDepositRequest, OrdinalsBridgeDepositOp, OrdinalsBridgeOp, OrdinalsBridgeWithdrawOp,
+};
+use bridge_did::order::MintOrder;
+use candid::{CandidType, Decode, Deserialize};
+use did::H160;
+use ic_task_scheduler::task::TaskOptions;
+use serde::Serialize;
+
+pub use self::deposit::OrdinalsBridgeDepositOpImpl;
+pub use self::events_handler::OrdinalsBtfEventsHandler;
+pub use self::mint_order_handler::OrdinalsMintOrderHandler;
+pub use self::mint_tx_handler::OrdinalsMintTxHandler;
+use self::withdraw::OrdinalsBridgeWithdrawOpImpl;
+use crate::canister::get_ordinals_state;
+
+pub const REFRESH_PARAMS_SERVICE_ID: ServiceId = 0;
+pub const FETCH_BTF_EVENTS_SERVICE_ID: ServiceId = 1;
+pub const SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 2;
+pub const SEND_MINT_TX_SERVICE_ID: ServiceId = 3;
+
+/// Ordinals bridge operations
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct OrdinalsBridgeOpImpl(pub OrdinalsBridgeOp);
+
+impl From<OrdinalsBridgeOp> for OrdinalsBridgeOpImpl {
+    fn from(op: OrdinalsBridgeOp) -> Self {
+        Self(op)
+    }
+}
+
+impl Operation for OrdinalsBridgeOpImpl {
+    async fn progress(
+        self,
+        id: OperationId,
+        ctx: RuntimeState<Self>,
+    ) -> BTFResult<OperationProgress<Self>> {
+        let next_step = match self.0 {
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitInputs(deposit)) => {
+                log::debug!("OrdinalsBridgeDepositOp::AwaitInputs {deposit:?}");
+                OrdinalsBridgeDepositOpImpl::await_inputs(ctx, deposit).await
+            }
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitConfirmations {
+                deposit,
+                utxos,
+            }) => {
+                log::debug!("OrdinalsBridgeDepositOp::AwaitConfirmations {deposit:?} {utxos:?}");
+                OrdinalsBridgeDepositOpImpl::await_confirmations(ctx, deposit, utxos, id.nonce())
+                    .await
+            }
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SignMintOrder(mint_order)) => {
+                log::debug!("OrdinalsBridgeDepositOp::SignMintOrder {mint_order:?}");
+                return Ok(OperationProgress::AddToService(SIGN_MINT_ORDER_SERVICE_ID));
+            }
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SendMintOrder(mint_order)) => {
+                log::debug!("OrdinalsBridgeDepositOp::SendMintOrder {mint_order:?}");
+                return Ok(OperationProgress::AddToService(SEND_MINT_TX_SERVICE_ID));
+            }
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::WaitForMintConfirm { .. }) => {
+                Err(Error::FailedToProgress(
+                    "ConfirmMintOrder task should progress only on the Minted EVM event".into(),
+                ))
+            }
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::MintOrderConfirmed { .. }) => Err(
+                Error::FailedToProgress("MintOrderConfirmed task cannot be progressed".into()),
+            ),
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::CreateTransferTx(payload)) => {
+                log::debug!("OrdinalsBridgeWithdrawOp::CreateTransferTx {payload:?}");
+                OrdinalsBridgeWithdrawOpImpl::create_transfer_transaction(payload).await
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::SendTransferTx {
+                payload,
+                tx,
+            }) => {
+                log::debug!("OrdinalsBridgeWithdrawOp::SendTransferTx {payload:?} {tx:?}");
+                OrdinalsBridgeWithdrawOpImpl::send_transfer_transaction(payload, tx).await
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {
+                payload,
+                tx,
+            }) => {
+                log::debug!(
+                    "OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {payload:?} {tx:?}"
+                );
+                OrdinalsBridgeWithdrawOpImpl::await_transfer_transaction(payload, tx).await
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::TransferTxConfirmed {
+                ..
+            }) => Err(Error::FailedToProgress(
+                "TransferTxConfirmed task cannot be progressed".into(),
+            )),
+        }?;
+
+        Ok(OperationProgress::Progress(next_step))
+    }
+
+    fn scheduling_options(&self) -> Option<TaskOptions> {
+        match self.0 {
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {
+                ..
+            }) => {
+                let network = get_ordinals_state().borrow().network();
+
+                // On mainnet wait longer for Bitcoin transactions
+                match network {
+                    Network::Bitcoin => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(20)
+                            .with_fixed_backoff_policy(300), // 10 blocks, each 5 minutes
+                    ),
+                    _ => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(10)
+                            .with_fixed_backoff_policy(10),
+                    ),
+                }
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::CreateTransferTx { .. })
+            | OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::SendTransferTx { .. }) => Some(
+                TaskOptions::new()
+                    .with_fixed_backoff_policy(2)
+                    .with_max_retries_policy(10),
+            ),
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::TransferTxConfirmed {
+                ..
+            })
+            | OrdinalsBridgeOp::Deposit(_) => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(10)
+                    .with_fixed_backoff_policy(5),
+            ),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.0 {
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitInputs { .. }) => false,
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitConfirmations { .. }) => false,
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SignMintOrder { .. }) => false,
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SendMintOrder { .. }) => false,
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::WaitForMintConfirm { .. }) => false,
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::MintOrderConfirmed { .. }) => true,
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::CreateTransferTx { .. }) => false,
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::SendTransferTx { .. }) => false,
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {
+                ..
+            }) => false,
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::TransferTxConfirmed {
+                ..
+            }) => true,
+        }
+    }
+
+    fn evm_wallet_address(&self) -> H160 {
+        match &self.0 {
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitInputs(
+                InscriptionDepositRequest { dst_address, .. },
+            )) => dst_address.clone(),
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitConfirmations {
+                deposit,
+                ..
+            }) => deposit.dst_address.clone(),
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SignMintOrder(MintOrder {
+                recipient,
+                ..
+            })) => recipient.clone(),
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SendMintOrder(
+                signed_mint_order,
+            )) => signed_mint_order.reader().get_recipient(),
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::WaitForMintConfirm {
+                orders: signed_mint_order,
+                ..
+            }) => signed_mint_order.reader().get_recipient(),
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::MintOrderConfirmed { data }) => {
+                data.recipient.clone()
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::CreateTransferTx(payload)) => {
+                payload.sender.clone()
+            }
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::SendTransferTx {
+                payload,
+                ..
+            }) => payload.sender.clone(),
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {
+                payload,
+                ..
+            }) => payload.sender.clone(),
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::TransferTxConfirmed {
+                from_address,
+                ..
+            }) => from_address.clone(),
+        }
+    }
+}
+
+pub enum OrdinalsMinterNotification {
+    Deposit(InscriptionDepositRequest),
+}
+
+impl OrdinalsMinterNotification {
+    fn decode(event_data: NotifyMinterEventData) -> Option<Self> {
+        match event_data.notification_type {
+            MinterNotificationType::DepositRequest => {
+                match Decode!(&event_data.user_data, InscriptionDepositRequest) {
+                    Ok(payload) => Some(OrdinalsMinterNotification::Deposit(payload)),
+                    Err(err) => {
+                        log::warn!("Failed to decode deposit request event data: {err:?}");
+                        None
+                    }
+                }
+            }
+            t => {
+                log::warn!("Unknown minter notify event type: {t}");
+                None
+            }
+        }
+    }
+}
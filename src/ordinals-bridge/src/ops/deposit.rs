@@ -0,0 +1,82 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::operations::{InscriptionDepositRequest, OrdinalsBridgeDepositOp};
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+
+use super::{OrdinalsBridgeOp, OrdinalsBridgeOpImpl};
+use crate::core::deposit::InscriptionDeposit;
+
+pub struct OrdinalsBridgeDepositOpImpl;
+
+impl OrdinalsBridgeDepositOpImpl {
+    /// Await for deposit inputs
+    pub async fn await_inputs(
+        state: RuntimeState<OrdinalsBridgeOpImpl>,
+        request: InscriptionDepositRequest,
+    ) -> BTFResult<OrdinalsBridgeOpImpl> {
+        let deposit = InscriptionDeposit::get(state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot deposit: {err:?}")))?;
+        let utxos = deposit
+            .get_inputs(&request.dst_address)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("cannot find deposit inputs: {err:?}"))
+            })?;
+
+        if utxos.is_empty() {
+            return Err(Error::FailedToProgress("no inputs".to_string()));
+        }
+
+        Ok(
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::AwaitConfirmations {
+                deposit: request,
+                utxos,
+            })
+            .into(),
+        )
+    }
+
+    /// Await for minimum IC confirmations
+    pub async fn await_confirmations(
+        state: RuntimeState<OrdinalsBridgeOpImpl>,
+        deposit_request: InscriptionDepositRequest,
+        utxos: Vec<Utxo>,
+        nonce: u32,
+    ) -> BTFResult<OrdinalsBridgeOpImpl> {
+        let InscriptionDepositRequest {
+            inscription_id,
+            dst_address,
+        } = deposit_request;
+
+        let deposit = InscriptionDeposit::get(state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot deposit: {err:?}")))?;
+        deposit
+            .check_confirmations(&dst_address, &utxos)
+            .await
+            .map_err(|err| Error::FailedToProgress(format!("inputs are not confirmed: {err:?}")))?;
+
+        let (location, utxo) = deposit
+            .find_inscription(&dst_address, &inscription_id, &utxos)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!(
+                    "cannot find inscription {inscription_id}: {err:?}"
+                ))
+            })?;
+
+        let unsigned_mint_order =
+            deposit.create_unsigned_mint_order(&dst_address, &location, nonce);
+
+        deposit
+            .mark_inscription_as_deposited(&dst_address, &location, utxo)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("cannot mark inscription as deposited: {err:?}"))
+            })?;
+
+        Ok(
+            OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SignMintOrder(unsigned_mint_order))
+                .into(),
+        )
+    }
+}
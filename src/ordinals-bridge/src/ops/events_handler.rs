@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bridge_canister::bridge::OperationAction;
+use bridge_canister::runtime::service::fetch_logs::BtfBridgeEventHandler;
+use bridge_did::event_data::{BurntEventData, MintedEventData, NotifyMinterEventData};
+use bridge_did::operations::{OrdinalsBridgeDepositOp, OrdinalsBridgeOp, OrdinalsBridgeWithdrawOp};
+
+use crate::core::withdrawal;
+use crate::ops::{OrdinalsBridgeOpImpl, OrdinalsMinterNotification};
+use crate::state::OrdinalsState;
+
+/// Describes event processing logic.
+pub struct OrdinalsBtfEventsHandler {
+    ordinals_state: Rc<RefCell<OrdinalsState>>,
+}
+
+impl OrdinalsBtfEventsHandler {
+    pub fn new(ordinals_state: Rc<RefCell<OrdinalsState>>) -> Self {
+        Self { ordinals_state }
+    }
+}
+
+impl BtfBridgeEventHandler<OrdinalsBridgeOpImpl> for OrdinalsBtfEventsHandler {
+    fn on_wrapped_token_minted(
+        &self,
+        event: MintedEventData,
+    ) -> Option<OperationAction<OrdinalsBridgeOpImpl>> {
+        log::debug!(
+            "on_wrapped_token_minted nonce {nonce} {event:?}",
+            nonce = event.nonce
+        );
+
+        let nonce = event.nonce;
+        let update_to = OrdinalsBridgeOpImpl(OrdinalsBridgeOp::Deposit(
+            OrdinalsBridgeDepositOp::MintOrderConfirmed { data: event },
+        ));
+
+        Some(OperationAction::Update { nonce, update_to })
+    }
+
+    fn on_wrapped_token_burnt(
+        &self,
+        event: BurntEventData,
+    ) -> Option<OperationAction<OrdinalsBridgeOpImpl>> {
+        log::debug!("on_wrapped_token_burnt {event:?}");
+        let memo = event.memo();
+        let op = match withdrawal::new_withdraw_payload(event, &self.ordinals_state.borrow()) {
+            Ok(payload) => OrdinalsBridgeOpImpl(OrdinalsBridgeOp::Withdraw(
+                OrdinalsBridgeWithdrawOp::CreateTransferTx(payload),
+            )),
+            Err(err) => {
+                log::warn!("Invalid withdrawal data: {err:?}");
+                return None;
+            }
+        };
+
+        Some(OperationAction::Create(op, memo))
+    }
+
+    fn on_minter_notification(
+        &self,
+        event: NotifyMinterEventData,
+    ) -> Option<OperationAction<OrdinalsBridgeOpImpl>> {
+        log::debug!("on_minter_notification {event:?}");
+
+        let memo = event.memo();
+        let Some(notification) = OrdinalsMinterNotification::decode(event.clone()) else {
+            log::warn!("Invalid minter notification: {event:?}");
+            return None;
+        };
+
+        match notification {
+            OrdinalsMinterNotification::Deposit(request) => {
+                let operation = OrdinalsBridgeOpImpl(OrdinalsBridgeOp::Deposit(
+                    OrdinalsBridgeDepositOp::AwaitInputs(request),
+                ));
+
+                Some(OperationAction::Create(operation, memo))
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bridge_canister::bridge::OperationContext as _;
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::mint_tx::{MintTxHandler, MintTxResult};
+use bridge_canister::runtime::state::SharedConfig;
+use bridge_did::error::BTFResult;
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{OrdinalsBridgeDepositOp, OrdinalsBridgeOp};
+use bridge_did::order::SignedOrders;
+use eth_signer::sign_strategy::TxSigner;
+
+use super::OrdinalsBridgeOpImpl;
+
+/// Allows MintTxService to handle MintTx of Ordinals bridge.
+pub struct OrdinalsMintTxHandler {
+    state: RuntimeState<OrdinalsBridgeOpImpl>,
+}
+
+impl OrdinalsMintTxHandler {
+    /// Creates a new instance of OrdinalsMintTxHandler.
+    pub fn new(state: RuntimeState<OrdinalsBridgeOpImpl>) -> Self {
+        Self { state }
+    }
+}
+
+impl MintTxHandler for OrdinalsMintTxHandler {
+    fn get_signer(&self) -> BTFResult<TxSigner> {
+        self.state.get_signer()
+    }
+
+    fn get_evm_config(&self) -> SharedConfig {
+        self.state.borrow().config.clone()
+    }
+
+    fn get_signed_orders(&self, id: OperationId) -> Option<SignedOrders> {
+        let op = self.state.borrow().operations.get(id)?;
+
+        let OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SendMintOrder(order)) = op.0 else {
+            log::info!(
+                "Mint order handler failed to get SignedOrders: unexpected state for operation {id}"
+            );
+            return None;
+        };
+
+        Some(order)
+    }
+
+    fn mint_tx_sent(&self, id: OperationId, result: MintTxResult) {
+        let op = self.state.borrow().operations.get(id);
+        let Some(OrdinalsBridgeOp::Deposit(OrdinalsBridgeDepositOp::SendMintOrder(orders))) =
+            op.map(|op| op.0)
+        else {
+            log::info!(
+                "Mint order handler failed to update operation state: unexpected state for operation {id}"
+            );
+            return;
+        };
+
+        log::debug!(
+            "Mint transaction successful: {:?}; op_id: {id}; results: {:?}",
+            result.tx_hash,
+            result.results
+        );
+        self.state.borrow_mut().operations.update(
+            id,
+            OrdinalsBridgeOpImpl(OrdinalsBridgeOp::Deposit(
+                OrdinalsBridgeDepositOp::WaitForMintConfirm {
+                    mint_result: result.results,
+                    orders,
+                    tx_id: result.tx_hash,
+                },
+            )),
+        );
+    }
+}
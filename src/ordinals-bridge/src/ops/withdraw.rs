@@ -0,0 +1,77 @@
+use bitcoin::hashes::Hash as _;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::operations::{
+    DidTransaction, InscriptionWithdrawalPayload, OrdinalsBridgeWithdrawOp,
+};
+use did::H256;
+
+use super::{OrdinalsBridgeOp, OrdinalsBridgeOpImpl};
+use crate::core::withdrawal::Withdrawal;
+
+pub struct OrdinalsBridgeWithdrawOpImpl;
+
+impl OrdinalsBridgeWithdrawOpImpl {
+    /// Create the transaction sending the inscription to the recipient
+    pub async fn create_transfer_transaction(
+        payload: InscriptionWithdrawalPayload,
+    ) -> BTFResult<OrdinalsBridgeOpImpl> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        let tx = withdraw
+            .build_transfer_transaction(&payload)
+            .await
+            .map_err(|err| Error::FailedToProgress(format!("cannot build transfer tx: {err:?}")))?;
+
+        Ok(
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::SendTransferTx { payload, tx })
+                .into(),
+        )
+    }
+
+    /// Send transfer transaction
+    pub async fn send_transfer_transaction(
+        payload: InscriptionWithdrawalPayload,
+        tx: DidTransaction,
+    ) -> BTFResult<OrdinalsBridgeOpImpl> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        withdraw
+            .send_transaction(&payload, &tx.0)
+            .await
+            .map_err(|err| Error::FailedToProgress(format!("cannot send transfer tx: {err:?}")))?;
+
+        Ok(
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::AwaitTransferTxConfirmation {
+                payload,
+                tx,
+            })
+            .into(),
+        )
+    }
+
+    /// Check whether the transfer transaction is confirmed
+    pub async fn await_transfer_transaction(
+        payload: InscriptionWithdrawalPayload,
+        tx: DidTransaction,
+    ) -> BTFResult<OrdinalsBridgeOpImpl> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        withdraw
+            .await_transfer_transaction(&payload, &tx.0)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to await transfer transaction: {err:?}"))
+            })?;
+
+        Ok(
+            OrdinalsBridgeOp::Withdraw(OrdinalsBridgeWithdrawOp::TransferTxConfirmed {
+                from_address: payload.sender,
+                txid: H256::from_slice(tx.0.txid().as_byte_array()),
+            })
+            .into(),
+        )
+    }
+}
@@ -0,0 +1,348 @@
+mod config;
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bitcoin::bip32::ChainCode;
+use bitcoin::{FeeRate, Network, PrivateKey, PublicKey};
+use bitcoin_bridge_core::fee_rate::FeeRateState;
+use bitcoin_bridge_core::key::{AddressType, IcEcdsaSigner, LocalBtcSigner};
+pub use bitcoin_bridge_core::master_key::MasterKey;
+use bitcoin_bridge_core::master_key::MasterKeyStorage;
+use bridge_canister::memory::MEMORY_MANAGER;
+use bridge_did::init::ordinals::OrdinalsBridgeConfig;
+use bridge_did::init::{FeeEstimatorConfig, IndexerHeader};
+use bridge_utils::fee_estimator::IcFeeEstimator;
+use eth_signer::sign_strategy::SigningStrategy;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use ic_exports::ic_cdk::api::management_canister::ecdsa::{
+    EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyResponse,
+};
+use ic_stable_structures::VirtualMemory;
+use ic_stable_structures::stable_structures::DefaultMemoryImpl;
+use ord_rs::Wallet;
+
+use self::config::OrdinalsBridgeConfigStorage;
+use crate::key::BtcSignerType;
+use crate::ledger::InscriptionLedger;
+use crate::memory::MASTER_KEY_MEMORY_ID;
+use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
+
+/// Minimum number of indexers required to start the bridge.
+const MIN_INDEXERS: usize = 2;
+
+pub struct OrdinalsState {
+    pub(crate) config: OrdinalsBridgeConfigStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) fee_rate_state: FeeRateState,
+    pub(crate) ledger: InscriptionLedger<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) master_key: MasterKeyStorage<VirtualMemory<DefaultMemoryImpl>>,
+}
+
+impl Default for OrdinalsState {
+    fn default() -> Self {
+        MEMORY_MANAGER.with(|memory_manager| Self {
+            config: OrdinalsBridgeConfigStorage::new(memory_manager),
+            master_key: MasterKeyStorage::new(memory_manager, MASTER_KEY_MEMORY_ID),
+            ledger: InscriptionLedger::new(memory_manager),
+            fee_rate_state: FeeRateState::default(),
+        })
+    }
+}
+
+impl OrdinalsState {
+    /// Returns id of the IC ECDSA key used by the canister.
+    pub fn ecdsa_key_id(&self, signing_strategy: &SigningStrategy) -> EcdsaKeyId {
+        let key_name = match signing_strategy {
+            SigningStrategy::Local { .. } => "none".to_string(),
+            SigningStrategy::ManagementCanister { key_id } => key_id.to_string(),
+        };
+
+        EcdsaKeyId {
+            curve: EcdsaCurve::Secp256k1,
+            name: key_name,
+        }
+    }
+
+    /// Returns master public key of the canister.
+    pub fn public_key(&self) -> Option<PublicKey> {
+        self.master_key
+            .get()
+            .as_ref()
+            .and_then(|key| key.public_key().ok())
+    }
+
+    /// Returns master chain code of the canister. Used for public key derivation.
+    pub fn chain_code(&self) -> Option<ChainCode> {
+        self.master_key.get().as_ref().map(|key| key.chain_code())
+    }
+
+    /// Returns BTC network the canister works with (IC style).
+    pub fn ic_btc_network(&self) -> BitcoinNetwork {
+        self.config.get().network
+    }
+
+    /// Returns BTC network the canister works with (BTC style).
+    pub fn network(&self) -> Network {
+        match self.config.get().network {
+            BitcoinNetwork::Mainnet => Network::Bitcoin,
+            BitcoinNetwork::Testnet => Network::Testnet,
+            BitcoinNetwork::Regtest => Network::Regtest,
+        }
+    }
+
+    /// Minimum number of confirmations the canister requires to consider a transaction to be confirmed.
+    pub fn min_confirmations(&self) -> u32 {
+        self.config.get().min_confirmations
+    }
+
+    /// Master key of the canister.
+    fn master_key(&self) -> Option<MasterKey> {
+        self.master_key.get().clone()
+    }
+
+    pub fn btc_signer(&self, signing_strategy: &SigningStrategy) -> Option<BtcSignerType> {
+        Some(match signing_strategy {
+            SigningStrategy::Local { private_key } => BtcSignerType::Local(LocalBtcSigner::new(
+                PrivateKey::from_slice(private_key, self.network()).expect("invalid private key"),
+                AddressType::P2wpkh,
+            )),
+            SigningStrategy::ManagementCanister { .. } => {
+                BtcSignerType::Ic(IcEcdsaSigner::new(self.master_key()?, self.network()))
+            }
+        })
+    }
+
+    /// Wallet to be used to sign transactions with the given derivation path.
+    pub fn wallet(&self, signing_strategy: &SigningStrategy) -> Option<Wallet> {
+        Some(Wallet::new_with_signer(self.btc_signer(signing_strategy)?))
+    }
+
+    /// Url of the `ord` indexer this canister rely on.
+    pub fn indexer_urls(&self) -> HashSet<String> {
+        self.config.get().indexer_urls.clone()
+    }
+
+    /// Inscription ledger.
+    pub fn ledger(&self) -> &InscriptionLedger<VirtualMemory<DefaultMemoryImpl>> {
+        &self.ledger
+    }
+
+    /// Mutable reference to the inscription ledger.
+    pub fn ledger_mut(&mut self) -> &mut InscriptionLedger<VirtualMemory<DefaultMemoryImpl>> {
+        &mut self.ledger
+    }
+
+    /// Chain id to be used for the inscriptions.
+    pub fn btc_chain_id(&self) -> u32 {
+        match self.config.get().network {
+            BitcoinNetwork::Mainnet => MAINNET_CHAIN_ID,
+            BitcoinNetwork::Testnet => TESTNET_CHAIN_ID,
+            BitcoinNetwork::Regtest => REGTEST_CHAIN_ID,
+        }
+    }
+
+    /// Validates the given configuration and sets it to the state. Panics in case the configuration
+    /// is invalid.
+    pub fn configure(&mut self, mut config: OrdinalsBridgeConfig) {
+        if let Err(err) = config.validate() {
+            panic!("Invalid configuration: {err}");
+        }
+
+        config.indexer_urls = config
+            .indexer_urls
+            .iter()
+            .map(|url| url.strip_suffix('/').unwrap_or(url).to_owned())
+            .collect();
+        config.indexer_headers = config.indexer_headers.map(|headers| {
+            headers
+                .into_iter()
+                .map(|(url, headers)| (url.strip_suffix('/').unwrap_or(&url).to_owned(), headers))
+                .collect()
+        });
+
+        self.config.set(config);
+    }
+
+    /// Updates the ecdsa signing configuration with the given master key information.
+    ///
+    /// This configuration is used to derive public keys for different user addresses, so this
+    /// configuration must be set before any of the transactions can be processed.
+    pub fn configure_ecdsa(
+        &mut self,
+        master_key: EcdsaPublicKeyResponse,
+        key_id: EcdsaKeyId,
+    ) -> Result<(), String> {
+        if master_key.chain_code.len() != 32 {
+            return Err("invalid chain code length".to_string());
+        }
+        let chain_code: [u8; 32] = master_key
+            .chain_code
+            .try_into()
+            .map_err(|e| format!("invalid chain code: {e:?}"))?;
+
+        let master_key = MasterKey::new(
+            PublicKey::from_slice(&master_key.public_key)
+                .map_err(|e| format!("invalid public key slice: {e}"))?,
+            ChainCode::from(chain_code),
+            key_id,
+        );
+
+        self.master_key.set(master_key);
+
+        Ok(())
+    }
+
+    pub fn configure_indexers(&mut self, indexer_urls: HashSet<String>) {
+        if indexer_urls.len() < MIN_INDEXERS {
+            panic!("number of indexers must be at least {}", MIN_INDEXERS)
+        }
+
+        self.config.with_borrow_mut(|config| {
+            config.indexer_urls = indexer_urls
+                .iter()
+                .map(|url| url.strip_suffix('/').unwrap_or(url).to_owned())
+                .collect();
+
+            // headers of the removed indexers are not needed anymore
+            if let Some(headers) = &mut config.indexer_headers {
+                headers.retain(|url, _| config.indexer_urls.contains(url));
+            }
+        });
+    }
+
+    /// Headers sent with the requests to each of the indexers.
+    pub fn indexer_headers(&self) -> HashMap<String, Vec<IndexerHeader>> {
+        self.config
+            .get()
+            .indexer_headers
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Sets the headers sent with the requests to the indexers. Panics if the headers are invalid
+    /// or set for an unknown indexer.
+    pub fn set_indexer_headers(&mut self, headers: HashMap<String, Vec<IndexerHeader>>) {
+        let mut config = self.config.get().clone();
+        config.indexer_headers = Some(headers);
+        self.configure(config);
+    }
+
+    /// Update fee rate and the last update timestamp.
+    pub fn update_fee_rate(&mut self, fee_rate: FeeRate) {
+        self.fee_rate_state.update(fee_rate);
+    }
+
+    /// Fee rate used by the canister.
+    pub fn fee_rate(&self) -> FeeRate {
+        self.fee_rate_state.fee_rate()
+    }
+
+    /// Returns the fee estimation configuration.
+    pub fn fee_estimator_config(&self) -> FeeEstimatorConfig {
+        self.config.get().fee_estimator.clone().unwrap_or_default()
+    }
+
+    /// Sets the fee estimation configuration. If `None`, the median of the IC fee percentiles
+    /// is used.
+    ///
+    /// The cached fee rate is dropped, so the next transaction uses the new configuration.
+    pub fn set_fee_estimator_config(&mut self, fee_estimator: Option<FeeEstimatorConfig>) {
+        if let Some(Err(err)) = fee_estimator.as_ref().map(FeeEstimatorConfig::validate) {
+            panic!("Invalid fee estimator configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.fee_estimator = fee_estimator);
+        self.fee_rate_state = FeeRateState::default();
+    }
+
+    /// Fee estimator to request the current fee rate with.
+    pub fn fee_estimator(&self) -> IcFeeEstimator {
+        IcFeeEstimator::new(self.ic_btc_network(), self.fee_estimator_config())
+    }
+
+    /// Elapsed time since the last fee rate update. (nano seconds)
+    pub fn last_fee_rate_update_elapsed(&self) -> Duration {
+        self.fee_rate_state.last_update_elapsed()
+    }
+
+    /// Returns the number of indexers required to reach consensus.
+    pub fn indexer_consensus_threshold(&self) -> u8 {
+        self.config.get().indexer_consensus_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_exports::ic_kit::MockContext;
+
+    use super::*;
+
+    fn config() -> OrdinalsBridgeConfig {
+        OrdinalsBridgeConfig {
+            indexer_urls: HashSet::from_iter(vec![
+                "https://indexer1.com/".to_string(),
+                "https://indexer2.com".to_string(),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_configure_with_trailing_slash() {
+        MockContext::new().inject();
+        let mut state = OrdinalsState::default();
+        state.configure(config());
+
+        assert_eq!(
+            state.indexer_urls(),
+            HashSet::from_iter(vec![
+                String::from("https://indexer1.com"),
+                String::from("https://indexer2.com")
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid configuration")]
+    fn test_should_not_configure_without_consensus() {
+        MockContext::new().inject();
+        let mut state = OrdinalsState::default();
+        state.configure(OrdinalsBridgeConfig {
+            indexer_urls: HashSet::from_iter(vec!["https://indexer1.com".to_string()]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_should_set_indexer_headers() {
+        MockContext::new().inject();
+        let mut state = OrdinalsState::default();
+        state.configure(config());
+
+        state.set_indexer_headers(HashMap::from([(
+            "https://indexer1.com/".to_string(),
+            vec![IndexerHeader {
+                name: "x-api-key".to_string(),
+                value: "secret".to_string(),
+            }],
+        )]));
+        assert_eq!(
+            state.indexer_headers()["https://indexer1.com"][0].value,
+            "secret"
+        );
+
+        state.configure_indexers(HashSet::from_iter(vec![
+            "https://indexer2.com".to_string(),
+            "https://indexer3.com".to_string(),
+        ]));
+        assert!(state.indexer_headers().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "number of indexers must be at least")]
+    fn test_configure_indexers_too_few_indexers() {
+        let mut state = OrdinalsState::default();
+        state.configure_indexers(HashSet::from_iter(vec!["https://indexer1.com".to_string()]));
+    }
+}
@@ -0,0 +1,43 @@
+use bridge_did::init::ordinals::OrdinalsBridgeConfig;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{CellStructure, MemoryId, MemoryManager, StableCell};
+
+use crate::memory::CONFIG_MEMORY_ID;
+
+pub struct OrdinalsBridgeConfigStorage<M: Memory> {
+    config: StableCell<OrdinalsBridgeConfig, M>,
+}
+
+impl<M> OrdinalsBridgeConfigStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            config: StableCell::new(
+                memory.get(CONFIG_MEMORY_ID),
+                OrdinalsBridgeConfig::default(),
+            )
+            .expect("stable memory config initialization failed"),
+        }
+    }
+
+    pub fn get(&self) -> &OrdinalsBridgeConfig {
+        self.config.get()
+    }
+
+    pub fn set(&mut self, config: OrdinalsBridgeConfig) {
+        self.config.set(config).expect("failed to set config");
+    }
+
+    pub fn with_borrow_mut<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut OrdinalsBridgeConfig),
+    {
+        let mut config = self.config.get().clone();
+
+        f(&mut config);
+
+        self.set(config);
+    }
+}
@@ -1,35 +1,77 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+use bitcoin::Transaction;
+use bitcoin::hashes::Hash as _;
+use bitcoin::psbt::Psbt;
 use bridge_canister::BridgeCanister;
+use bridge_canister::memory::memory_by_id;
 use bridge_canister::runtime::service::ServiceOrder;
 use bridge_canister::runtime::service::fetch_logs::FetchBtfBridgeEventsService;
 use bridge_canister::runtime::service::mint_tx::SendMintTxService;
 use bridge_canister::runtime::service::sign_orders::SignMintOrdersService;
+use bridge_canister::runtime::service::timer::ServiceTimer;
 use bridge_canister::runtime::service::update_evm_params::RefreshEvmParamsService;
+use bridge_canister::runtime::state::SharedConfig;
 use bridge_canister::runtime::state::config::ConfigStorage;
 use bridge_canister::runtime::{BridgeRuntime, RuntimeState};
-use bridge_did::init::{BridgeInitData, IndexerType, RuneBridgeConfig};
+use bridge_did::init::brc20::SchnorrKeyIds;
+use bridge_did::init::{
+    BridgeInitData, DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig,
+    IndexerType, RuneBridgeConfig, UtxoConsolidationConfig, WithdrawalBatchConfig,
+    WithdrawalCosigningConfig,
+};
 use bridge_did::op_id::OperationId;
 use bridge_did::operation_log::{Memo, OperationLog};
+use bridge_did::operations::{RuneBridgeOp, RuneBridgeWithdrawOp};
+use bridge_did::runes::{
+    DidTransaction, DidTxInput, EvmNativeToken, EvmTokenEtchingRequest, RuneName,
+};
+use bridge_did::schnorr::{
+    ManagementCanisterSchnorrPublicKeyReply, ManagementCanisterSchnorrPublicKeyRequest,
+    SchnorrAlgorithm,
+};
 use bridge_utils::common::Pagination;
 use candid::Principal;
-use did::H160;
+use did::{H160, H256};
 use ic_canister::{Canister, Idl, PreUpdate, generate_idl, init, post_upgrade, query, update};
+use ic_exports::ic_cdk;
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     EcdsaPublicKeyArgument, ecdsa_public_key,
 };
+use ic_exports::ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
+use ic_exports::ic_kit::ic;
 use ic_exports::ledger::Subaccount;
 use ic_log::canister::{LogCanister, LogState};
 use ic_metrics::{Metrics, MetricsStorage};
 use ic_storage::IcStorage;
 
-use crate::canister::inspect::{inspect_configure_ecdsa, inspect_configure_indexers};
-use crate::interface::GetAddressError;
-use crate::ops::events_handler::RuneEventsHandler;
+use crate::canister::inspect::{
+    inspect_configure_deposit_watcher, inspect_configure_dynamic_deposit_fee,
+    inspect_configure_ecdsa, inspect_configure_fee_estimator, inspect_configure_indexers,
+    inspect_configure_schnorr, inspect_configure_utxo_consolidation,
+    inspect_configure_withdrawal_batch, inspect_configure_withdrawal_cosigning,
+    inspect_etch_evm_token_rune, inspect_get_ledger_summary, inspect_get_withdrawal_psbt,
+    inspect_migrate_utxos_to_taproot, inspect_set_base_btf_bridge_contract,
+    inspect_submit_cosigned_psbt,
+};
+use crate::constants::{
+    DEPOSIT_WATCHER_INTERVAL, EVM_TOKEN_ETCHING_CHECK_INTERVAL, RUNE_LIST_REFRESH_INTERVAL,
+    UTXO_CONSOLIDATION_INTERVAL,
+};
+use crate::core::withdrawal::{Withdrawal, dust_limit, tx_input_info};
+use crate::interface::{GetAddressError, LedgerSummary, WithdrawError};
+use crate::memory::BASE_BRIDGE_CONFIG_MEMORY_ID;
+use crate::ops::events_handler::{BtfBridgeSide, RuneEventsHandler};
 use crate::ops::{
-    FETCH_BTF_EVENTS_SERVICE_ID, REFRESH_PARAMS_SERVICE_ID, RuneBridgeOpImpl, RuneMintOrderHandler,
+    BASE_FETCH_BTF_EVENTS_SERVICE_ID, BASE_REFRESH_PARAMS_SERVICE_ID, BASE_SEND_MINT_TX_SERVICE_ID,
+    BASE_SIGN_MINT_ORDER_SERVICE_ID, BATCH_WITHDRAWAL_SERVICE_ID, BatchWithdrawalService,
+    DEPOSIT_WATCHER_SERVICE_ID, DepositWatcherService, EVM_TOKEN_ETCHING_SERVICE_ID,
+    EvmTokenEtchingService, FETCH_BTF_EVENTS_SERVICE_ID, REFRESH_PARAMS_SERVICE_ID,
+    RUNE_LIST_REFRESH_SERVICE_ID, RuneBridgeOpImpl, RuneListRefreshService, RuneMintOrderHandler,
     RuneMintTxHandler, SEND_MINT_TX_SERVICE_ID, SIGN_MINT_ORDER_SERVICE_ID,
+    UTXO_CONSOLIDATION_SERVICE_ID, UtxoConsolidationService, track_utxo_merge,
 };
 use crate::state::RuneState;
 
@@ -53,11 +95,16 @@ impl RuneBridge {
     #[init]
     pub fn init(&mut self, bridge_init_data: BridgeInitData, rune_bridge_config: RuneBridgeConfig) {
         self.init_bridge(bridge_init_data, Self::run_scheduler);
-        get_rune_state().borrow_mut().configure(rune_bridge_config);
+        let state = get_rune_state();
+        state.borrow_mut().configure(rune_bridge_config);
+        state.borrow().init_state_version();
     }
 
     #[post_upgrade]
     pub fn post_upgrade(&mut self) {
+        if let Err(err) = get_rune_state().borrow_mut().migrate() {
+            ic::trap(&format!("Failed to migrate the state: {err}"));
+        }
         self.bridge_post_upgrade(Self::run_scheduler)
     }
 
@@ -74,6 +121,42 @@ impl RuneBridge {
             .map_err(GetAddressError::from)
     }
 
+    /// Returns the BTC fee in sats charged for a deposit requested now. The fee is locked into
+    /// the deposit operation when it is created.
+    #[query]
+    pub fn get_deposit_fee_quote(&self) -> DepositFeeQuote {
+        get_rune_state().borrow().deposit_fee_quote()
+    }
+
+    /// Returns the deposit address of the given Ethereum address and starts watching it, so that
+    /// the runes sent to the address are bridged to the given wrapped tokens without a deposit
+    /// request.
+    ///
+    /// If `refund_address` is set, the deposited runes without a wrapped token are sent back to
+    /// this Bitcoin address instead of being left at the deposit address.
+    ///
+    /// The registration fee configured for the deposit watcher must be attached to the call in
+    /// cycles.
+    #[update]
+    pub fn register_deposit_address(
+        &self,
+        eth_address: H160,
+        dst_tokens: HashMap<RuneName, H160>,
+        refund_address: Option<String>,
+    ) -> Result<String, GetAddressError> {
+        let state = get_rune_state();
+        let address = crate::key::get_transit_address(&state, &eth_address)?;
+        let fee = state.borrow_mut().watch_deposit_address(
+            eth_address,
+            dst_tokens,
+            refund_address,
+            ic_cdk::api::call::msg_cycles_available(),
+        )?;
+        ic_cdk::api::call::msg_cycles_accept(fee);
+
+        Ok(address.to_string())
+    }
+
     /// Retrieves all operations for the given ETH wallet address whose
     /// id is greater than or equal to `min_included_id` if provided.
     /// The operations are then paginated with the given `pagination` parameters,
@@ -154,6 +237,56 @@ impl RuneBridge {
             .expect("failed to configure ecdsa");
     }
 
+    /// Fetches the master key of the given schnorr key and enables the taproot (P2TR) deposit
+    /// and change addresses.
+    ///
+    /// The utxos received at the legacy P2WPKH addresses stay spendable, and can be moved to the
+    /// taproot addresses with `admin_migrate_utxos_to_taproot`.
+    #[update]
+    pub async fn admin_configure_schnorr(&self, key_id: SchnorrKeyIds) {
+        inspect_configure_schnorr(self.config());
+
+        let request = ManagementCanisterSchnorrPublicKeyRequest {
+            canister_id: None,
+            derivation_path: vec![],
+            key_id: key_id.to_key_id(SchnorrAlgorithm::Bip340Secp256k1),
+        };
+        let (master_key,): (ManagementCanisterSchnorrPublicKeyReply,) = ic_cdk::call(
+            Principal::management_canister(),
+            "schnorr_public_key",
+            (request,),
+        )
+        .await
+        .expect("failed to get schnorr master key");
+
+        get_rune_state()
+            .borrow_mut()
+            .configure_schnorr(key_id, master_key)
+            .expect("failed to configure schnorr");
+    }
+
+    /// Moves up to `max_inputs` utxos held at the legacy P2WPKH addresses to the taproot change
+    /// address. Returns the id of the sent transaction, or `None` if there is nothing to migrate.
+    #[update]
+    pub async fn admin_migrate_utxos_to_taproot(
+        &self,
+        max_inputs: u32,
+    ) -> Result<Option<H256>, WithdrawError> {
+        inspect_migrate_utxos_to_taproot(self.config());
+
+        let Some(merge) = Withdrawal::get()?
+            .migrate_utxos_to_taproot(max_inputs)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let txid = H256::from_slice(merge.transaction.txid().as_byte_array());
+        track_utxo_merge(merge);
+
+        Ok(Some(txid))
+    }
+
     #[update]
     pub fn admin_configure_indexers(&self, indexers: Vec<IndexerType>) {
         inspect_configure_indexers(self.config());
@@ -168,6 +301,246 @@ impl RuneBridge {
             .set_indexer_consensus_threshold(indexer_consensus_threshold)
     }
 
+    /// Enables batching of withdrawals with the given configuration, or disables it if `None`.
+    #[update]
+    pub fn admin_configure_withdrawal_batch(&self, config: Option<WithdrawalBatchConfig>) {
+        inspect_configure_withdrawal_batch(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_withdrawal_batch_config(config);
+    }
+
+    /// Enables periodic consolidation of the bridge utxos with the given configuration, or
+    /// disables it if `None`.
+    #[update]
+    pub fn admin_configure_utxo_consolidation(&self, config: Option<UtxoConsolidationConfig>) {
+        inspect_configure_utxo_consolidation(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_utxo_consolidation_config(config);
+    }
+
+    /// Enables automatic detection of the deposits to the registered deposit addresses with the
+    /// given configuration, or disables it if `None`.
+    #[update]
+    pub fn admin_configure_deposit_watcher(&self, config: Option<DepositWatcherConfig>) {
+        inspect_configure_deposit_watcher(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_deposit_watcher_config(config);
+    }
+
+    /// Enables the deposit fee computed from the current Bitcoin fee rate with the given
+    /// configuration, or switches to the fixed deposit fee if `None`.
+    #[update]
+    pub fn admin_configure_dynamic_deposit_fee(&self, config: Option<DepositFeeConfig>) {
+        inspect_configure_dynamic_deposit_fee(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_dynamic_deposit_fee_config(config);
+    }
+
+    /// Sets the Bitcoin fee rate estimation configuration. If `None`, the median of the IC fee
+    /// percentiles is used.
+    #[update]
+    pub fn admin_configure_fee_estimator(&self, config: Option<FeeEstimatorConfig>) {
+        inspect_configure_fee_estimator(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_fee_estimator_config(config);
+    }
+
+    /// Etches a rune representing the given EVM-native ERC20 token and premines its whole supply
+    /// to the bridge. Returns the id of the etching transaction.
+    ///
+    /// The transaction is paid from the deposit address of the ERC20 token address, which must be
+    /// funded with BTC beforehand. The rune can be bridged once the etching is confirmed.
+    #[update]
+    pub async fn admin_etch_evm_token_rune(
+        &self,
+        request: EvmTokenEtchingRequest,
+    ) -> Result<H256, WithdrawError> {
+        inspect_etch_evm_token_rune(self.config());
+
+        let txid = Withdrawal::get()?.etch_evm_token_rune(&request).await?;
+
+        Ok(H256::from_slice(txid.as_byte_array()))
+    }
+
+    /// Links the canister to the base-side BTF bridge, which locks and releases the EVM-native
+    /// tokens etched as runes. The wrapped tokens of the other runes are still minted and burnt by
+    /// the BTF bridge set with `set_btf_bridge_contract`.
+    ///
+    /// The base-side bridge must be deployed on the same EVM with the canister as the minter. The
+    /// EVM link and the signing strategy are copied from the main config when the bridge is linked.
+    #[update]
+    pub fn admin_set_base_btf_bridge_contract(&self, address: H160) {
+        inspect_set_base_btf_bridge_contract(self.config());
+
+        let main_config = self.config();
+        let base_config = get_base_bridge_config();
+        let is_linked = base_config.borrow().get_btf_bridge_contract().is_some();
+        base_config.borrow_mut().update(|config| {
+            let main_config = main_config.borrow();
+            config.owner = main_config.get_owner();
+            config.evm_link = main_config.get_evm_link();
+            config.signing_strategy = main_config.get_signing_strategy();
+            config.btf_bridge_contract_address = Some(address.clone());
+        });
+
+        if !is_linked {
+            add_base_bridge_services(&get_runtime());
+        }
+
+        log::info!("Bridge canister base-side BTF bridge contract address changed to {address}");
+    }
+
+    /// Returns the address of the base-side BTF bridge, which locks and releases the EVM-native
+    /// tokens etched as runes.
+    #[query]
+    pub fn get_base_btf_bridge_contract(&self) -> Option<H160> {
+        get_base_bridge_config().borrow().get_btf_bridge_contract()
+    }
+
+    /// Sets the thresholds above which the withdrawals must be co-signed by the external keys,
+    /// or signs all the withdrawals by the canister alone if `None`.
+    #[update]
+    pub fn admin_configure_withdrawal_cosigning(&self, config: Option<WithdrawalCosigningConfig>) {
+        inspect_configure_withdrawal_cosigning(self.config());
+
+        get_rune_state()
+            .borrow_mut()
+            .set_withdrawal_cosigning_config(config);
+    }
+
+    /// Returns the serialized PSBT (BIP-174) of the withdrawal awaiting the co-signatures, or
+    /// `None` if the operation does not await them. Only available to the owner.
+    #[query]
+    pub fn get_withdrawal_psbt(
+        &self,
+        operation_id: OperationId,
+    ) -> Result<Option<Vec<u8>>, WithdrawError> {
+        inspect_get_withdrawal_psbt(self.config());
+
+        let Some((_, transaction, inputs)) = Self::uncosigned_withdrawal(operation_id) else {
+            return Ok(None);
+        };
+
+        let unsigned_tx: Transaction = transaction.into();
+        let inputs = inputs
+            .iter()
+            .map(tx_input_info)
+            .collect::<Result<Vec<_>, _>>()?;
+        let psbt = Withdrawal::get()?.withdrawal_psbt(&unsigned_tx, &inputs)?;
+
+        Ok(Some(psbt.serialize()))
+    }
+
+    /// Submits the serialized PSBT of the withdrawal awaiting the co-signatures. Once the
+    /// co-signatures are verified, the canister signs the withdrawal transaction and sends it.
+    #[update]
+    pub async fn admin_submit_cosigned_psbt(
+        &self,
+        operation_id: OperationId,
+        psbt: Vec<u8>,
+    ) -> Result<(), WithdrawError> {
+        inspect_submit_cosigned_psbt(self.config());
+
+        let Some((from_address, transaction, inputs)) = Self::uncosigned_withdrawal(operation_id)
+        else {
+            return Err(WithdrawError::InvalidRequest(format!(
+                "operation {operation_id} does not await the co-signatures"
+            )));
+        };
+        let cosigning = get_rune_state()
+            .borrow()
+            .withdrawal_cosigning_config()
+            .ok_or_else(|| {
+                WithdrawError::InvalidRequest("withdrawal co-signing is not configured".to_string())
+            })?;
+        let psbt = Psbt::deserialize(&psbt)
+            .map_err(|err| WithdrawError::InvalidRequest(format!("invalid PSBT: {err}")))?;
+
+        let unsigned_tx: Transaction = transaction.into();
+        let tx_inputs = inputs
+            .iter()
+            .map(tx_input_info)
+            .collect::<Result<Vec<_>, _>>()?;
+        let signed_tx = Withdrawal::get()?
+            .sign_cosigned_transaction(&unsigned_tx, &tx_inputs, &psbt, &cosigning)
+            .await?;
+
+        // the PSBT could have been submitted again while the transaction was being signed
+        if Self::uncosigned_withdrawal(operation_id).is_none() {
+            return Err(WithdrawError::InvalidRequest(format!(
+                "operation {operation_id} does not await the co-signatures"
+            )));
+        }
+
+        let operation = RuneBridgeOpImpl(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::SendTransaction {
+                from_address,
+                transaction: signed_tx.into(),
+                inputs: Some(inputs),
+            },
+        ));
+        get_runtime_state()
+            .borrow_mut()
+            .operations
+            .update(operation_id, operation.clone());
+        get_runtime()
+            .borrow()
+            .schedule_operation(operation_id, operation);
+
+        Ok(())
+    }
+
+    /// Returns the sender, the unsigned transaction and the inputs of the withdrawal awaiting the
+    /// co-signatures.
+    fn uncosigned_withdrawal(
+        operation_id: OperationId,
+    ) -> Option<(H160, DidTransaction, Vec<DidTxInput>)> {
+        let operation = get_runtime_state().borrow().operations.get(operation_id)?;
+        match operation.0 {
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitCosignatures {
+                from_address,
+                transaction,
+                inputs,
+            }) => Some((from_address, transaction, inputs)),
+            _ => None,
+        }
+    }
+
+    /// Returns the EVM-native tokens represented by the runes etched by the bridge.
+    #[query]
+    pub fn get_evm_native_tokens(&self) -> Vec<EvmNativeToken> {
+        get_rune_state().borrow().evm_native_tokens()
+    }
+
+    /// Returns the summary of the utxos held by the bridge. Only available to the owner.
+    #[query]
+    pub fn get_ledger_summary(&self) -> LedgerSummary {
+        inspect_get_ledger_summary(self.config());
+
+        let state = get_rune_state();
+        let state = state.borrow();
+        state
+            .ledger()
+            .summary(dust_limit(state.fee_rate()))
+            .expect("failed to load utxos")
+    }
+
+    /// Strips the headers of the indexer responses, so the HTTP outcall replicas agree on them.
+    #[query]
+    pub fn transform_indexer_response(&self, args: TransformArgs) -> HttpResponse {
+        bridge_utils::http::transform_indexer_response(args)
+    }
+
     pub fn idl() -> Idl {
         generate_idl!()
     }
@@ -204,16 +577,38 @@ fn init_runtime() -> SharedRuntime {
 
     let refresh_params_service = RefreshEvmParamsService::new(config.clone());
 
-    let events_handler = RuneEventsHandler::new(get_rune_state());
+    let events_handler = RuneEventsHandler::new(get_rune_state(), BtfBridgeSide::Wrapped);
     let fetch_btf_events_service =
-        FetchBtfBridgeEventsService::new(events_handler, runtime.clone(), config);
+        FetchBtfBridgeEventsService::new(events_handler, runtime.clone(), config.clone());
 
     let sign_orders_handler = RuneMintOrderHandler::new(state.clone(), scheduler);
     let sign_mint_orders_service = Rc::new(SignMintOrdersService::new(sign_orders_handler));
 
-    let mint_tx_handler = RuneMintTxHandler::new(state.clone());
+    let mint_tx_handler = RuneMintTxHandler::new(state.clone(), config.clone());
     let mint_tx_service = Rc::new(SendMintTxService::new(mint_tx_handler));
 
+    let batch_withdrawal_service = Rc::new(BatchWithdrawalService::new(state.clone()));
+
+    let utxo_consolidation_service = Rc::new(ServiceTimer::new(
+        UtxoConsolidationService,
+        UTXO_CONSOLIDATION_INTERVAL,
+    ));
+
+    let rune_list_refresh_service = Rc::new(ServiceTimer::new(
+        RuneListRefreshService::new(state.clone()),
+        RUNE_LIST_REFRESH_INTERVAL,
+    ));
+
+    let evm_token_etching_service = Rc::new(ServiceTimer::new(
+        EvmTokenEtchingService::new(state.clone()),
+        EVM_TOKEN_ETCHING_CHECK_INTERVAL,
+    ));
+
+    let deposit_watcher_service = Rc::new(ServiceTimer::new(
+        DepositWatcherService::new(state.clone()),
+        DEPOSIT_WATCHER_INTERVAL,
+    ));
+
     let services = state.borrow().services.clone();
     services.borrow_mut().add_service(
         ServiceOrder::BeforeOperations,
@@ -235,14 +630,93 @@ fn init_runtime() -> SharedRuntime {
         SEND_MINT_TX_SERVICE_ID,
         mint_tx_service,
     );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        BATCH_WITHDRAWAL_SERVICE_ID,
+        batch_withdrawal_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        UTXO_CONSOLIDATION_SERVICE_ID,
+        utxo_consolidation_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        RUNE_LIST_REFRESH_SERVICE_ID,
+        rune_list_refresh_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        EVM_TOKEN_ETCHING_SERVICE_ID,
+        evm_token_etching_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        DEPOSIT_WATCHER_SERVICE_ID,
+        deposit_watcher_service,
+    );
+
+    if get_base_bridge_config()
+        .borrow()
+        .get_btf_bridge_contract()
+        .is_some()
+    {
+        add_base_bridge_services(&runtime);
+    }
 
     runtime
 }
 
+/// Registers the services of the base-side BTF bridge: the lock events are fetched from it and
+/// the release orders are signed and sent to it.
+fn add_base_bridge_services(runtime: &SharedRuntime) {
+    let state = runtime.borrow().state().clone();
+    let scheduler = runtime.borrow().scheduler().clone();
+    let config = get_base_bridge_config();
+
+    let refresh_params_service = RefreshEvmParamsService::new(config.clone());
+
+    let events_handler = RuneEventsHandler::new(get_rune_state(), BtfBridgeSide::Base);
+    let fetch_btf_events_service =
+        FetchBtfBridgeEventsService::new(events_handler, runtime.clone(), config.clone());
+
+    let sign_orders_handler = RuneMintOrderHandler::new(state.clone(), scheduler);
+    let sign_mint_orders_service = Rc::new(SignMintOrdersService::new(sign_orders_handler));
+
+    let mint_tx_handler = RuneMintTxHandler::new(state.clone(), config);
+    let mint_tx_service = Rc::new(SendMintTxService::new(mint_tx_handler));
+
+    let services = state.borrow().services.clone();
+    services.borrow_mut().add_service(
+        ServiceOrder::BeforeOperations,
+        BASE_REFRESH_PARAMS_SERVICE_ID,
+        Rc::new(refresh_params_service),
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::BeforeOperations,
+        BASE_FETCH_BTF_EVENTS_SERVICE_ID,
+        Rc::new(fetch_btf_events_service),
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        BASE_SIGN_MINT_ORDER_SERVICE_ID,
+        sign_mint_orders_service,
+    );
+    services.borrow_mut().add_service(
+        ServiceOrder::ConcurrentWithOperations,
+        BASE_SEND_MINT_TX_SERVICE_ID,
+        mint_tx_service,
+    );
+}
+
 thread_local! {
     pub static RUNTIME: SharedRuntime = init_runtime();
 
     pub static RUNE_STATE: Rc<RefCell<RuneState>> = Rc::default();
+
+    pub static BASE_BRIDGE_CONFIG: SharedConfig = Rc::new(RefCell::new(ConfigStorage::default(
+        memory_by_id(BASE_BRIDGE_CONFIG_MEMORY_ID),
+    )));
 }
 
 pub fn get_runtime() -> SharedRuntime {
@@ -256,3 +730,9 @@ pub fn get_runtime_state() -> RuntimeState<RuneBridgeOpImpl> {
 pub fn get_rune_state() -> Rc<RefCell<RuneState>> {
     RUNE_STATE.with(|s| s.clone())
 }
+
+/// Returns the config of the base-side BTF bridge, which locks and releases the EVM-native
+/// tokens etched as runes.
+pub fn get_base_bridge_config() -> SharedConfig {
+    BASE_BRIDGE_CONFIG.with(|c| c.clone())
+}
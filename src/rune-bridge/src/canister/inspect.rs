@@ -33,12 +33,101 @@ pub fn inspect_configure_indexers(config: SharedConfig) {
     inspect_caller_is_owner(owner, caller)
 }
 
+pub fn inspect_configure_withdrawal_batch(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_utxo_consolidation(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_deposit_watcher(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_dynamic_deposit_fee(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_fee_estimator(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_etch_evm_token_rune(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_set_base_btf_bridge_contract(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_schnorr(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_migrate_utxos_to_taproot(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_configure_withdrawal_cosigning(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_get_withdrawal_psbt(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_submit_cosigned_psbt(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
+pub fn inspect_get_ledger_summary(config: SharedConfig) {
+    let caller = ic::caller();
+    let owner = config.borrow().get_owner();
+    inspect_caller_is_owner(owner, caller)
+}
+
 #[cfg(feature = "export-api")]
 fn inspect_method(method: &str) {
     let config = ConfigStorage::get();
     match method {
         "admin_configure_ecdsa" => inspect_configure_ecdsa(config),
         "admin_configure_indexers" => inspect_configure_indexers(config),
+        "admin_configure_withdrawal_batch" => inspect_configure_withdrawal_batch(config),
+        "admin_configure_utxo_consolidation" => inspect_configure_utxo_consolidation(config),
+        "admin_configure_deposit_watcher" => inspect_configure_deposit_watcher(config),
+        "admin_configure_dynamic_deposit_fee" => inspect_configure_dynamic_deposit_fee(config),
+        "admin_configure_fee_estimator" => inspect_configure_fee_estimator(config),
+        "admin_etch_evm_token_rune" => inspect_etch_evm_token_rune(config),
+        "admin_set_base_btf_bridge_contract" => inspect_set_base_btf_bridge_contract(config),
+        "admin_configure_schnorr" => inspect_configure_schnorr(config),
+        "admin_migrate_utxos_to_taproot" => inspect_migrate_utxos_to_taproot(config),
+        "admin_configure_withdrawal_cosigning" => inspect_configure_withdrawal_cosigning(config),
+        "admin_submit_cosigned_psbt" => inspect_submit_cosigned_psbt(config),
         _ => {}
     }
 }
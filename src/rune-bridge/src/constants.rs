@@ -1,4 +1,31 @@
 use std::time::Duration;
 
+pub use bitcoin_bridge_core::funding::{DUST_THRESHOLD, INCREMENTAL_RELAY_FEE_RATE};
+
 /// The interval at which the fee rate is updated (10 minutes)
 pub const FEE_RATE_UPDATE_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Time after which an unconfirmed withdrawal transaction gets replaced by a transaction paying
+/// a higher fee (1 hour)
+pub const FEE_BUMP_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Virtual size of a transaction input spending a utxo of the bridge (vbytes)
+pub const INPUT_VSIZE: u64 = 68;
+
+/// The interval at which the bridge checks if its utxos should be consolidated (1 hour)
+pub const UTXO_CONSOLIDATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The interval at which the runes etched since the last refresh are requested from the
+/// indexers (10 minutes)
+pub const RUNE_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Maximum number of runes added to the rune info cache by a single refresh
+pub const RUNE_LIST_REFRESH_LIMIT: usize = 500;
+
+/// The interval at which the bridge checks if the rune etchings of the EVM-native tokens are
+/// confirmed (10 minutes)
+pub const EVM_TOKEN_ETCHING_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// The interval at which the deposit watcher scans the registered deposit addresses for new
+/// deposits (2 minutes)
+pub const DEPOSIT_WATCHER_INTERVAL: Duration = Duration::from_secs(60 * 2);
@@ -5,12 +5,14 @@ use std::fmt::Debug;
 use std::future::Future;
 use std::rc::Rc;
 
-use bitcoin::hashes::Hash;
 use bitcoin::{Address, Network};
+use bitcoin_bridge_core::consensus::get_indexer_consensus;
+use bitcoin_bridge_core::key::{KeyError, get_derivation_path_ic};
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
 use bridge_canister::runtime::RuntimeState;
 use bridge_did::id256::Id256;
 use bridge_did::order::{MintOrder, SignedMintOrder};
-use bridge_did::runes::{RuneInfo, RuneName, RuneToWrap};
+use bridge_did::runes::{EvmNativeToken, EvmNativeTokenStatus, RuneInfo, RuneName, RuneToWrap};
 use candid::{CandidType, Deserialize};
 use did::{H160, H256};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{GetUtxosResponse, Utxo};
@@ -18,13 +20,14 @@ use serde::Serialize;
 
 use super::index_provider::get_indexer;
 use crate::canister::{get_rune_state, get_runtime_state};
+use crate::constants::RUNE_LIST_REFRESH_LIMIT;
 use crate::core::index_provider::RuneIndexProvider;
 use crate::core::rune_inputs::{GetInputsError, RuneInput, RuneInputProvider, RuneInputs};
 use crate::core::utxo_handler::{UtxoHandler, UtxoHandlerError};
-use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::core::withdrawal::PREMINE_OUTPUT_INDEX;
 use crate::interface::DepositError;
-use crate::key::{BtcSignerType, KeyError, get_derivation_path_ic};
-use crate::ledger::UnspentUtxoInfo;
+use crate::key::BtcSignerType;
+use crate::ledger::UtxoKey;
 use crate::ops::RuneBridgeOpImpl;
 use crate::state::RuneState;
 
@@ -140,6 +143,7 @@ impl RuneDeposit<IcUtxoProvider> {
         let network = state_ref.network();
         let ic_network = state_ref.ic_btc_network();
         let cache_timeout = state_ref.utxo_cache_timeout();
+        let fee_estimator = state_ref.fee_estimator();
 
         let indexer_configs = state_ref.indexers_config();
         let indexers = indexer_configs.into_iter().map(get_indexer).collect();
@@ -156,7 +160,7 @@ impl RuneDeposit<IcUtxoProvider> {
             runtime_state,
             network,
             signer,
-            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout),
+            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout, fee_estimator),
             indexers,
             indexer_consensus_threshold: consensus_threshold,
         })
@@ -169,16 +173,10 @@ impl RuneDeposit<IcUtxoProvider> {
 
 impl<UTXO: UtxoProvider> RuneInputProvider for RuneDeposit<UTXO> {
     async fn get_inputs(&self, dst_address: &H160) -> Result<RuneInputs, GetInputsError> {
-        let transit_address = self.get_transit_address(dst_address).await?;
-        let utxos = self.get_deposit_utxos(&transit_address).await?.utxos;
+        let (utxos, _) = self.get_address_utxos(dst_address).await?;
         let mut inputs = RuneInputs::default();
         for utxo in utxos {
-            let amounts = self
-                .get_indexer_consensus(|indexer| {
-                    let utxo = utxo.clone();
-                    async move { indexer.get_rune_amounts(&utxo).await }
-                })
-                .await?;
+            let amounts = self.get_rune_amounts(&utxo).await?;
             if !amounts.is_empty() {
                 inputs.inputs.push(RuneInput {
                     utxo,
@@ -190,6 +188,13 @@ impl<UTXO: UtxoProvider> RuneInputProvider for RuneDeposit<UTXO> {
         Ok(inputs)
     }
 
+    async fn get_rune_amounts(
+        &self,
+        utxo: &Utxo,
+    ) -> Result<HashMap<RuneName, u128>, GetInputsError> {
+        RuneDeposit::get_rune_amounts(self, utxo).await
+    }
+
     async fn get_rune_infos(
         &self,
         rune_amounts: &HashMap<RuneName, u128>,
@@ -207,15 +212,11 @@ impl<UTXO: UtxoProvider> UtxoHandler for RuneDeposit<UTXO> {
         dst_address: &H160,
         utxo: &Utxo,
     ) -> Result<(), UtxoHandlerError> {
-        let transit_address = self.get_transit_address(dst_address).await?;
-        let utxo_response = self
-            .get_deposit_utxos(&transit_address)
-            .await
-            .map_err(|err| UtxoHandlerError::BtcAdapter(err.to_string()))?;
-        let block_height = utxo_response.tip_height;
-        // todo: height of the utxo may change. Replace the comparison here and wherever we compare
-        // utxos and add unit test for this case
-        let Some(found_utxo) = utxo_response.utxos.into_iter().find(|v| v == utxo) else {
+        let Some((_, found_utxo, block_height)) =
+            self.find_deposit_utxo(dst_address, utxo)
+                .await
+                .map_err(|err| UtxoHandlerError::BtcAdapter(err.to_string()))?
+        else {
             return Err(UtxoHandlerError::UtxoNotFound);
         };
 
@@ -237,24 +238,35 @@ impl<UTXO: UtxoProvider> UtxoHandler for RuneDeposit<UTXO> {
         utxo: &Utxo,
         dst_address: &H160,
         utxo_runes: Vec<RuneToWrap>,
+        refunded_runes: Vec<RuneInfo>,
     ) -> Result<Vec<MintOrder>, UtxoHandlerError> {
-        let address = self.get_transit_address(dst_address).await?;
+        let Some((address, _, _)) = self
+            .find_deposit_utxo(dst_address, utxo)
+            .await
+            .map_err(|err| UtxoHandlerError::BtcAdapter(err.to_string()))?
+        else {
+            return Err(UtxoHandlerError::UtxoNotFound);
+        };
         let derivation_path = get_derivation_path_ic(dst_address);
 
         {
             let mut state = self.rune_state.borrow_mut();
             let ledger = state.ledger_mut();
-            let existing = ledger.load_unspent_utxos()?;
-
-            if existing
-                .values()
-                .any(|v| Self::check_already_used_utxo(v, utxo))
-            {
+            if ledger.contains(&UtxoKey::from(&utxo.outpoint)) {
                 return Err(UtxoHandlerError::UtxoAlreadyUsed);
             }
 
-            let deposit_runes = utxo_runes.iter().map(|rune| rune.rune_info).collect();
+            let deposit_runes = utxo_runes
+                .iter()
+                .map(|rune| rune.rune_info)
+                .chain(refunded_runes.iter().copied())
+                .collect();
             ledger.deposit(utxo.clone(), &address, derivation_path, deposit_runes);
+
+            // the utxo is reserved for the refund transaction, so it is not spent by withdrawals
+            if !refunded_runes.is_empty() {
+                ledger.mark_as_used(UtxoKey::from(&utxo.outpoint), address.clone());
+            }
         }
 
         let mut mint_orders = vec![];
@@ -286,7 +298,7 @@ impl<UTXO: UtxoProvider> RuneDeposit<UTXO> {
             utxo_response.utxos
         );
 
-        self.filter_out_used_utxos(&mut utxo_response)?;
+        self.filter_out_used_utxos(&mut utxo_response);
 
         log::trace!(
             "Utxos at address {transit_address} after filtering out used utxos: {:?}",
@@ -296,66 +308,248 @@ impl<UTXO: UtxoProvider> RuneDeposit<UTXO> {
         Ok(utxo_response)
     }
 
-    async fn get_transit_address(&self, eth_address: &H160) -> Result<Address, KeyError> {
+    /// Returns the utxos at the transit addresses of the user not deposited yet, along with the
+    /// current block height.
+    pub async fn get_address_utxos(
+        &self,
+        dst_address: &H160,
+    ) -> Result<(Vec<Utxo>, u32), GetInputsError> {
+        let mut utxos = vec![];
+        let mut tip_height = 0;
+        for transit_address in self.get_transit_addresses(dst_address).await? {
+            let utxo_response = self.get_deposit_utxos(&transit_address).await?;
+            utxos.extend(utxo_response.utxos);
+            tip_height = tip_height.max(utxo_response.tip_height);
+        }
+
+        Ok((utxos, tip_height))
+    }
+
+    /// Returns the runes held by the utxo, as agreed by the indexers.
+    pub async fn get_rune_amounts(
+        &self,
+        utxo: &Utxo,
+    ) -> Result<HashMap<RuneName, u128>, GetInputsError> {
+        self.get_indexer_consensus(|indexer| {
+            let utxo = utxo.clone();
+            async move { indexer.get_rune_amounts(&utxo).await }
+        })
+        .await
+    }
+
+    /// Returns the current transit address of the user and, if the taproot addresses are
+    /// enabled, the legacy one, which may still receive deposits.
+    async fn get_transit_addresses(&self, eth_address: &H160) -> Result<Vec<Address>, KeyError> {
         self.signer
-            .get_transit_address(eth_address, self.network)
+            .get_transit_addresses(eth_address, self.network)
             .await
     }
 
+    /// Finds the given utxo at the transit addresses of the user. Returns the address holding the
+    /// utxo, the utxo as reported by the utxo provider and the current block height.
+    async fn find_deposit_utxo(
+        &self,
+        dst_address: &H160,
+        utxo: &Utxo,
+    ) -> Result<Option<(Address, Utxo, u32)>, GetInputsError> {
+        for transit_address in self.get_transit_addresses(dst_address).await? {
+            let utxo_response = self.get_deposit_utxos(&transit_address).await?;
+            // todo: height of the utxo may change. Replace the comparison here and wherever we
+            // compare utxos and add unit test for this case
+            if let Some(found_utxo) = utxo_response.utxos.into_iter().find(|v| v == utxo) {
+                return Ok(Some((
+                    transit_address,
+                    found_utxo,
+                    utxo_response.tip_height,
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
     fn get_rune_infos_from_state(
         &self,
         rune_amounts: &HashMap<RuneName, u128>,
     ) -> Option<Vec<(RuneInfo, u128)>> {
         let state = self.rune_state.borrow();
-        let runes = state.runes();
         let mut infos = vec![];
         for (rune_name, amount) in rune_amounts {
-            infos.push((*runes.get(rune_name)?, *amount));
+            infos.push((state.rune_info_by_name(rune_name)?, *amount));
         }
 
         Some(infos)
     }
 
+    /// Requests the runes missing in the cache one by one, instead of the whole rune list.
     async fn get_rune_infos_from_indexer(
         &self,
         rune_amounts: &HashMap<RuneName, u128>,
     ) -> Option<Vec<(RuneInfo, u128)>> {
-        let rune_list = self
-            .get_indexer_consensus(|indexer| async move { indexer.get_rune_list().await })
-            .await
-            .ok()?;
-        let runes: HashMap<RuneName, RuneInfo> = rune_list
-            .iter()
-            .map(|(rune_id, spaced_rune, decimals)| {
-                (
-                    spaced_rune.rune.into(),
-                    RuneInfo {
-                        name: spaced_rune.rune.into(),
-                        decimals: *decimals,
-                        block: rune_id.block,
-                        tx: rune_id.tx,
-                    },
-                )
-            })
-            .collect();
         let mut infos = vec![];
         for (rune_name, amount) in rune_amounts {
-            match runes.get(rune_name) {
-                Some(v) => infos.push((*v, *amount)),
-                None => {
+            let cached = self.rune_state.borrow().rune_info_by_name(rune_name);
+            if let Some(rune_info) = cached {
+                infos.push((rune_info, *amount));
+                continue;
+            }
+
+            let rune_info = match self
+                .get_indexer_consensus(|indexer| async move { indexer.get_rune(rune_name).await })
+                .await
+            {
+                Ok((rune_id, spaced_rune, decimals)) => RuneInfo {
+                    name: spaced_rune.rune.into(),
+                    decimals,
+                    block: rune_id.block,
+                    tx: rune_id.tx,
+                },
+                Err(err) => {
                     log::error!(
-                        "Ord indexer didn't return a rune information for rune {rune_name} that was present in an UTXO"
+                        "Failed to get information for rune {rune_name} that was present in an UTXO: {err:?}"
                     );
                     return None;
                 }
+            };
+
+            if rune_info.name != *rune_name {
+                log::error!(
+                    "Indexer returned information for rune {} instead of {rune_name}",
+                    rune_info.name
+                );
+                return None;
             }
-        }
 
-        self.rune_state.borrow_mut().update_rune_list(runes);
+            self.rune_state.borrow_mut().add_runes([rune_info]);
+            infos.push((rune_info, *amount));
+        }
 
         Some(infos)
     }
 
+    /// Requests the runes etched after the latest known rune and adds them to the cache.
+    ///
+    /// Returns the number of the added runes.
+    pub async fn refresh_rune_list(&self) -> Result<usize, GetInputsError> {
+        let latest_rune_id = self.rune_state.borrow().latest_rune_id();
+        let rune_list = self
+            .get_indexer_consensus(|indexer| async move {
+                indexer
+                    .get_rune_list(latest_rune_id, RUNE_LIST_REFRESH_LIMIT)
+                    .await
+            })
+            .await?;
+
+        let count = rune_list.len();
+        self.rune_state
+            .borrow_mut()
+            .add_runes(
+                rune_list
+                    .into_iter()
+                    .map(|(rune_id, spaced_rune, decimals)| RuneInfo {
+                        name: spaced_rune.rune.into(),
+                        decimals,
+                        block: rune_id.block,
+                        tx: rune_id.tx,
+                    }),
+            );
+
+        Ok(count)
+    }
+
+    /// Checks whether the etching transaction of the EVM-native token is confirmed and the
+    /// indexers know the etched rune. If so, adds the premined runes to the ledger.
+    ///
+    /// Returns the information about the etched rune, or `None` if the etching is not confirmed
+    /// yet.
+    pub async fn confirm_evm_token_etching(
+        &self,
+        token: &EvmNativeToken,
+    ) -> Result<Option<RuneInfo>, GetInputsError> {
+        let EvmNativeTokenStatus::Etching { txid } = &token.status else {
+            return Ok(token.rune_info());
+        };
+
+        // the etching may be sent before the taproot addresses are enabled
+        let mut premine = None;
+        for change_address in self.get_transit_addresses(&H160::default()).await? {
+            let utxo_response = self.utxo_provider.get_utxos(&change_address).await?;
+            let tip_height = utxo_response.tip_height;
+            if let Some(utxo) = utxo_response.utxos.into_iter().find(|utxo| {
+                H256::from_slice(&utxo.outpoint.txid) == *txid
+                    && utxo.outpoint.vout == PREMINE_OUTPUT_INDEX as u32
+            }) {
+                premine = Some((change_address, utxo, tip_height));
+                break;
+            }
+        }
+        let Some((change_address, premine_utxo, tip_height)) = premine else {
+            log::trace!(
+                "Etching transaction {txid} of the token {} is not mined yet",
+                token.erc20
+            );
+            return Ok(None);
+        };
+
+        let min_confirmations = self.rune_state.borrow().min_confirmations();
+        let current_confirmations = tip_height.saturating_sub(premine_utxo.height + 1);
+        if current_confirmations < min_confirmations {
+            log::trace!(
+                "Etching transaction {txid} has {current_confirmations}/{min_confirmations} confirmations"
+            );
+            return Ok(None);
+        }
+
+        let amounts = self
+            .get_indexer_consensus(|indexer| {
+                let utxo = premine_utxo.clone();
+                async move { indexer.get_rune_amounts(&utxo).await }
+            })
+            .await?;
+        if amounts.len() != 1 || amounts.values().any(|amount| *amount != token.premine) {
+            return Err(GetInputsError::IndexerError(format!(
+                "unexpected runes in the premine output of the etching transaction {txid}: {amounts:?}"
+            )));
+        }
+
+        let Some((rune_info, _)) = self
+            .get_rune_infos(&amounts)
+            .await
+            .and_then(|infos| infos.into_iter().next())
+        else {
+            return Ok(None);
+        };
+        if rune_info.decimals != token.divisibility {
+            return Err(GetInputsError::IndexerError(format!(
+                "rune {} has divisibility {} instead of {}",
+                rune_info.name, rune_info.decimals, token.divisibility
+            )));
+        }
+
+        {
+            let mut state = self.rune_state.borrow_mut();
+            state.ledger_mut().deposit(
+                premine_utxo,
+                &change_address,
+                get_derivation_path_ic(&H160::default()),
+                vec![rune_info],
+            );
+            state.set_evm_native_token(EvmNativeToken {
+                status: EvmNativeTokenStatus::Etched(rune_info),
+                ..token.clone()
+            });
+        }
+
+        log::info!(
+            "Rune {} ({}) of the token {} is etched",
+            rune_info.name,
+            rune_info.id(),
+            token.erc20
+        );
+
+        Ok(Some(rune_info))
+    }
+
     async fn get_indexer_consensus<
         'a,
         T: Debug + PartialEq,
@@ -365,52 +559,12 @@ impl<UTXO: UtxoProvider> RuneDeposit<UTXO> {
         &'a self,
         request: impl Fn(&'a dyn RuneIndexProvider) -> F,
     ) -> Result<T, GetInputsError> {
-        let consensus_threshold = self.indexer_consensus_threshold;
-        let mut first_result = None;
-        let mut requested = 0;
-        let mut received_responses = 0;
-        for indexer in &self.indexers {
-            let result = request(&**indexer).await;
-            requested += 1;
-            if let Ok(response) = result {
-                match first_result {
-                    None => first_result = Some(response),
-                    Some(ref r) => {
-                        received_responses += 1;
-                        if *r != response {
-                            return Err(GetInputsError::IndexersDisagree {
-                                first_response: format!("{r:?}"),
-                                another_response: format!("{response:?}"),
-                            });
-                        }
-                    }
-                }
-            } else {
-                log::warn!("Indexer responded with {result:?}");
-                if (self.indexers.len() as u8).saturating_sub(requested)
-                    < consensus_threshold.saturating_sub(received_responses)
-                {
-                    return Err(GetInputsError::InsufficientConsensus {
-                        received_responses: received_responses as usize,
-                        required_responses: consensus_threshold,
-                        checked_indexers: requested as usize,
-                    });
-                }
-            }
-        }
-
-        match first_result {
-            Some(v) => Ok(v),
-            None => {
-                // This means that `consensus_threshold` is 0 and none of the indexers are online
-                // or that there are not indexers configured
-                Err(GetInputsError::InsufficientConsensus {
-                    received_responses: 0,
-                    required_responses: consensus_threshold,
-                    checked_indexers: self.indexers.len(),
-                })
-            }
-        }
+        Ok(get_indexer_consensus(
+            self.indexers.iter().map(|indexer| &**indexer),
+            self.indexer_consensus_threshold,
+            request,
+        )
+        .await?)
     }
 
     pub fn create_unsigned_mint_order(
@@ -454,24 +608,15 @@ impl<UTXO: UtxoProvider> RuneDeposit<UTXO> {
         }
     }
 
-    fn filter_out_used_utxos(
-        &self,
-        get_utxos_response: &mut GetUtxosResponse,
-    ) -> Result<(), KeyError> {
-        let existing = self.rune_state.borrow().ledger().load_unspent_utxos()?;
-
-        get_utxos_response.utxos.retain(|utxo| {
-            !existing
-                .values()
-                .any(|v| Self::check_already_used_utxo(v, utxo))
-        });
-
-        Ok(())
-    }
+    /// Removes the utxos already known to the ledger: both the deposited and the ones used by
+    /// the bridge transactions.
+    fn filter_out_used_utxos(&self, get_utxos_response: &mut GetUtxosResponse) {
+        let state = self.rune_state.borrow();
+        let ledger = state.ledger();
 
-    fn check_already_used_utxo(v: &UnspentUtxoInfo, utxo: &Utxo) -> bool {
-        v.tx_input_info.outpoint.txid.as_byte_array()[..] == utxo.outpoint.txid
-            && v.tx_input_info.outpoint.vout == utxo.outpoint.vout
+        get_utxos_response
+            .utxos
+            .retain(|utxo| !ledger.contains(&UtxoKey::from(&utxo.outpoint)));
     }
 }
 
@@ -483,16 +628,16 @@ mod tests {
     use async_trait::async_trait;
     use bitcoin::secp256k1::Secp256k1;
     use bitcoin::{FeeRate, PrivateKey, Transaction};
+    use bitcoin_bridge_core::key::{AddressType, LocalBtcSigner};
+    use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
     use bridge_canister::memory::{StableMemory, memory_by_id};
     use bridge_canister::operation_store::OperationsMemory;
     use bridge_canister::runtime::state::config::ConfigStorage;
     use bridge_canister::runtime::state::{SharedConfig, State};
     use ic_stable_structures::MemoryId;
-    use ord_rs::wallet::LocalSigner;
     use ordinals::{RuneId, SpacedRune};
 
     use super::*;
-    use crate::interface::WithdrawError;
 
     fn op_memory() -> OperationsMemory<StableMemory> {
         OperationsMemory {
@@ -517,22 +662,28 @@ mod tests {
     fn signer() -> BtcSignerType {
         let s = Secp256k1::new();
         let keypair = s.generate_keypair(&mut rand::thread_rng());
-        let signer = LocalSigner::new(PrivateKey::new(keypair.0, Network::Bitcoin));
+        let signer = LocalBtcSigner::new(
+            PrivateKey::new(keypair.0, Network::Bitcoin),
+            AddressType::P2wpkh,
+        );
 
         BtcSignerType::Local(signer)
     }
 
     struct TestUtxoProvider;
     impl UtxoProvider for TestUtxoProvider {
-        async fn get_utxos(&self, _address: &Address) -> Result<GetUtxosResponse, GetInputsError> {
+        async fn get_utxos(
+            &self,
+            _address: &Address,
+        ) -> Result<GetUtxosResponse, UtxoProviderError> {
             unimplemented!()
         }
 
-        async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
+        async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError> {
             unimplemented!()
         }
 
-        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), WithdrawError> {
+        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), UtxoProviderError> {
             unimplemented!()
         }
     }
@@ -550,7 +701,18 @@ mod tests {
             Ok([(RuneName::from_str("A").unwrap(), self.value as u128)].into())
         }
 
-        async fn get_rune_list(&self) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
+        async fn get_rune_list(
+            &self,
+            _after: Option<RuneId>,
+            _limit: usize,
+        ) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
+            unimplemented!()
+        }
+
+        async fn get_rune(
+            &self,
+            _rune_name: &RuneName,
+        ) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
             unimplemented!()
         }
     }
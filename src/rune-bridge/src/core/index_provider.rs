@@ -1,21 +1,22 @@
+mod hiro;
+mod unisat;
+
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::future::Future;
 use std::str::FromStr as _;
 
 use async_trait::async_trait;
+use bitcoin_bridge_core::http::{HttpClient, IcHttpClient};
 use bridge_did::init::IndexerType;
 use bridge_did::runes::RuneName;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
-use ic_exports::ic_cdk::api::management_canister::http_request::{
-    CanisterHttpRequestArgument, HttpHeader, HttpMethod, http_request,
-};
 use ordinals::{RuneId, SpacedRune};
-use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+pub use self::hiro::HiroIndexProvider;
+pub use self::unisat::UnisatIndexProvider;
 use crate::core::rune_inputs::GetInputsError;
-use crate::interface::{DepositError, OutputResponse};
+use crate::interface::OutputResponse;
 
 #[async_trait(?Send)]
 pub(crate) trait RuneIndexProvider {
@@ -24,71 +25,36 @@ pub(crate) trait RuneIndexProvider {
         &self,
         utxo: &Utxo,
     ) -> Result<HashMap<RuneName, u128>, GetInputsError>;
-    /// Get the list of all runes in the indexer
-    async fn get_rune_list(&self) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError>;
+    /// Get the list of the most recently etched runes with ids greater than `after`, containing
+    /// at most `limit` runes. The list is ordered by the rune id.
+    async fn get_rune_list(
+        &self,
+        after: Option<RuneId>,
+        limit: usize,
+    ) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError>;
+    /// Get the information of a single rune.
+    async fn get_rune(
+        &self,
+        rune_name: &RuneName,
+    ) -> Result<(RuneId, SpacedRune, u8), GetInputsError>;
 }
 
 pub(crate) fn get_indexer(indexer_type: IndexerType) -> Box<dyn RuneIndexProvider> {
+    let client = IcHttpClient::new(HashMap::from([(
+        indexer_type.url().to_string(),
+        indexer_type.headers().to_vec(),
+    )]));
     match indexer_type {
-        IndexerType::OrdHttp { url } => Box::new(OrdIndexProvider::new(IcHttpClient, url)),
+        IndexerType::OrdHttp { url, .. } => Box::new(OrdIndexProvider::new(client, url)),
+        IndexerType::Hiro { url, .. } => Box::new(HiroIndexProvider::new(client, url)),
+        IndexerType::Unisat { url, .. } => Box::new(UnisatIndexProvider::new(client, url)),
     }
 }
 
-const CYCLES_PER_HTTP_REQUEST: u128 = 500_000_000;
+/// Maximum size of a response describing a single entity, e.g. runes of a utxo.
 const MAX_RESPONSE_BYTES: u64 = 10_000;
-
-/// Trait for a generic HTTP client that can be used to make requests to the indexer.
-pub trait HttpClient {
-    fn http_request<R: DeserializeOwned>(
-        &self,
-        url: &str,
-        uri: &str,
-    ) -> impl Future<Output = Result<R, DepositError>> + Send;
-}
-
-/// HTTP client implementation for the Internet Computer canisters.
-pub struct IcHttpClient;
-
-impl HttpClient for IcHttpClient {
-    async fn http_request<R: DeserializeOwned>(
-        &self,
-        url: &str,
-        uri: &str,
-    ) -> Result<R, DepositError> {
-        let url = format!("{url}/{}", uri.trim_start_matches('/'));
-
-        log::trace!("Sending indexer request to: {url}");
-
-        let request_params = CanisterHttpRequestArgument {
-            url,
-            max_response_bytes: Some(MAX_RESPONSE_BYTES),
-            method: HttpMethod::GET,
-            headers: vec![HttpHeader {
-                name: "Accept".to_string(),
-                value: "application/json".to_string(),
-            }],
-            body: None,
-            transform: None,
-        };
-
-        let result = http_request(request_params, CYCLES_PER_HTTP_REQUEST)
-            .await
-            .map_err(|err| DepositError::Unavailable(format!("Indexer unavailable: {err:?}")))?
-            .0;
-
-        log::trace!(
-            "Indexer responded with: {} {:?} BODY: {}",
-            result.status,
-            result.headers,
-            String::from_utf8_lossy(&result.body)
-        );
-
-        serde_json::from_slice(&result.body).map_err(|err| {
-            log::error!("Failed to get rune balance from the indexer: {err:?}");
-            DepositError::Unavailable(format!("Unexpected response from indexer: {err:?}"))
-        })
-    }
-}
+/// Maximum size of a page of a list response.
+const MAX_PAGE_RESPONSE_BYTES: u64 = 200_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 struct RuneInfo {
@@ -96,12 +62,20 @@ struct RuneInfo {
     divisibility: u8,
 }
 
+/// Response for `/runes/{page}` endpoint. The runes are ordered from the most recently etched.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 struct RunesResponse {
     entries: Vec<(RuneId, RuneInfo)>,
     next: Option<u64>,
 }
 
+/// Response for `/rune/{rune}` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct RuneResponse {
+    entry: RuneInfo,
+    id: RuneId,
+}
+
 /// Implementation of the `RuneIndexProvider` trait that uses the `HttpClient` to make requests to
 pub struct OrdIndexProvider<C: HttpClient> {
     client: C,
@@ -132,7 +106,7 @@ where
         let uri = format!("output/{outpoint}");
         let response: OutputResponse = self
             .client
-            .http_request(&self.url, &uri)
+            .http_request(&self.url, &uri, MAX_RESPONSE_BYTES)
             .await
             .map_err(|err| GetInputsError::IndexerError(format!("{err:?}")))?;
 
@@ -159,48 +133,134 @@ where
         Ok(amounts)
     }
 
-    async fn get_rune_list(&self) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
+    async fn get_rune_list(
+        &self,
+        after: Option<RuneId>,
+        limit: usize,
+    ) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
         let mut page = 0;
-        let mut entries = vec![];
+        let mut runes = vec![];
 
         loop {
             let uri = format!("runes/{page}");
             let response: RunesResponse = self
                 .client
-                .http_request(&self.url, &uri)
+                .http_request(&self.url, &uri, MAX_PAGE_RESPONSE_BYTES)
                 .await
                 .map_err(|err| GetInputsError::IndexerError(format!("{err:?}")))?;
-            entries.extend(response.entries);
 
-            if let Some(next) = response.next {
-                page = next;
-            } else {
-                break;
+            let entries = response
+                .entries
+                .into_iter()
+                .map(|(rune_id, info)| (rune_id, info.spaced_rune, info.divisibility));
+            let has_more = collect_new_runes(&mut runes, entries, after, limit);
+
+            match response.next {
+                Some(next) if has_more => page = next,
+                _ => break,
             }
         }
 
-        Ok(entries
-            .into_iter()
-            .map(|(rune_id, info)| (rune_id, info.spaced_rune, info.divisibility))
-            .collect())
+        Ok(sort_rune_list(runes))
+    }
+
+    async fn get_rune(
+        &self,
+        rune_name: &RuneName,
+    ) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
+        let uri = format!("rune/{rune_name}");
+        let response: RuneResponse = self
+            .client
+            .http_request(&self.url, &uri, MAX_RESPONSE_BYTES)
+            .await
+            .map_err(|err| GetInputsError::IndexerError(format!("{err:?}")))?;
+
+        Ok((
+            response.id,
+            response.entry.spaced_rune,
+            response.entry.divisibility,
+        ))
     }
 }
 
 fn format_outpoint(outpoint: &Outpoint) -> String {
+    format!("{}:{}", format_txid(&outpoint.txid), outpoint.vout)
+}
+
+fn format_txid(txid: &[u8]) -> String {
     // For some reason IC management canister returns bytes of tx_id in reversed order. It is
     // probably related to the fact that WASM uses little endian, but I'm not sure about that.
     // Nevertheless, to get the correct tx_id string we need to reverse the bytes first.
-    format!(
-        "{}:{}",
-        hex::encode(outpoint.txid.iter().copied().rev().collect::<Vec<u8>>()),
-        outpoint.vout
-    )
+    hex::encode(txid.iter().copied().rev().collect::<Vec<u8>>())
+}
+
+/// Adds the runes of a list page ordered from the most recently etched rune to `runes`.
+///
+/// Returns `false` when the following pages are not needed: either a rune etched not after
+/// `after` is reached, or `limit` runes are collected.
+fn collect_new_runes(
+    runes: &mut Vec<(RuneId, SpacedRune, u8)>,
+    page: impl IntoIterator<Item = (RuneId, SpacedRune, u8)>,
+    after: Option<RuneId>,
+    limit: usize,
+) -> bool {
+    for rune in page {
+        if runes.len() >= limit || after.is_some_and(|after| rune.0 <= after) {
+            return false;
+        }
+
+        runes.push(rune);
+    }
+
+    runes.len() < limit
+}
+
+/// Orders the rune list by rune id, so the lists of different indexers can be compared.
+fn sort_rune_list(mut runes: Vec<(RuneId, SpacedRune, u8)>) -> Vec<(RuneId, SpacedRune, u8)> {
+    runes.sort_by_key(|(rune_id, _, _)| *rune_id);
+    runes
+}
+
+fn parse_rune_id(rune_id: &str) -> Result<RuneId, GetInputsError> {
+    RuneId::from_str(rune_id)
+        .map_err(|err| GetInputsError::IndexerError(format!("invalid rune id {rune_id}: {err}")))
+}
+
+fn parse_spaced_rune(spaced_rune: &str) -> Result<SpacedRune, GetInputsError> {
+    SpacedRune::from_str(spaced_rune).map_err(|err| {
+        GetInputsError::IndexerError(format!("invalid rune name {spaced_rune}: {err}"))
+    })
+}
+
+/// Converts a decimal rune amount (e.g. `1.5`) to the rune base units.
+///
+/// Returns `None` if the amount has more fractional digits than the rune divisibility.
+fn parse_decimal_amount(amount: &str, divisibility: u8) -> Option<u128> {
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let fraction = fraction.trim_end_matches('0');
+    if integer.is_empty()
+        || fraction.len() > divisibility as usize
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let units = format!(
+        "{integer}{fraction:0<width$}",
+        width = divisibility as usize
+    );
+    units.parse().ok()
 }
 
 #[cfg(test)]
 mod tests {
 
+    use bitcoin_bridge_core::http::IndexerRequestError;
     use ordinals::Rune;
+    use serde::de::DeserializeOwned;
 
     use super::*;
 
@@ -218,6 +278,18 @@ mod tests {
         assert_eq!(&format_outpoint(&outpoint)[..], expected);
     }
 
+    #[test]
+    fn test_should_parse_decimal_amount() {
+        assert_eq!(parse_decimal_amount("1.5", 2), Some(150));
+        assert_eq!(parse_decimal_amount("7", 2), Some(700));
+        assert_eq!(parse_decimal_amount("0.000001", 6), Some(1));
+        assert_eq!(parse_decimal_amount("12.3400", 2), Some(1234));
+        assert_eq!(parse_decimal_amount("42", 0), Some(42));
+        assert_eq!(parse_decimal_amount("1.005", 2), None);
+        assert_eq!(parse_decimal_amount("-1", 2), None);
+        assert_eq!(parse_decimal_amount("", 2), None);
+    }
+
     #[tokio::test]
     async fn test_should_get_all_runes() {
         let mut runes = HashMap::new();
@@ -263,13 +335,85 @@ mod tests {
         let client = MockHttpClient { runes };
         let provider = OrdIndexProvider::new(client, "http://localhost:8080".into());
 
-        let runes = provider.get_rune_list().await.unwrap();
+        let runes = provider.get_rune_list(None, usize::MAX).await.unwrap();
         assert_eq!(runes.len(), 3);
         assert_eq!(runes[0].0, RuneId { block: 1, tx: 1 });
         assert_eq!(runes[1].0, RuneId { block: 1, tx: 2 });
         assert_eq!(runes[2].0, RuneId { block: 2, tx: 1 });
     }
 
+    fn rune_entry(block: u64, tx: u32) -> (RuneId, RuneInfo) {
+        (
+            RuneId { block, tx },
+            RuneInfo {
+                spaced_rune: SpacedRune {
+                    rune: Rune(block as u128 * 1000 + tx as u128),
+                    spacers: 0,
+                },
+                divisibility: 2,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_should_get_runes_etched_after_given_rune() {
+        let runes = HashMap::from([
+            (0u64, vec![rune_entry(3, 1), rune_entry(2, 2)]),
+            (1u64, vec![rune_entry(2, 1), rune_entry(1, 1)]),
+            (2u64, vec![rune_entry(0, 1)]),
+        ]);
+
+        let client = MockHttpClient { runes };
+        let provider = OrdIndexProvider::new(client, "http://localhost:8080".into());
+
+        let runes = provider
+            .get_rune_list(Some(RuneId { block: 2, tx: 1 }), usize::MAX)
+            .await
+            .unwrap();
+        let ids: Vec<RuneId> = runes.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(
+            ids,
+            vec![RuneId { block: 2, tx: 2 }, RuneId { block: 3, tx: 1 }]
+        );
+
+        let runes = provider.get_rune_list(None, 3).await.unwrap();
+        let ids: Vec<RuneId> = runes.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                RuneId { block: 2, tx: 1 },
+                RuneId { block: 2, tx: 2 },
+                RuneId { block: 3, tx: 1 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_stop_collecting_runes() {
+        let page = || {
+            [rune_entry(3, 1), rune_entry(2, 1)]
+                .into_iter()
+                .map(|(id, info)| (id, info.spaced_rune, info.divisibility))
+        };
+
+        let mut runes = vec![];
+        assert!(collect_new_runes(&mut runes, page(), None, 10));
+        assert_eq!(runes.len(), 2);
+
+        let mut runes = vec![];
+        assert!(!collect_new_runes(&mut runes, page(), None, 2));
+        assert_eq!(runes.len(), 2);
+
+        let mut runes = vec![];
+        assert!(!collect_new_runes(
+            &mut runes,
+            page(),
+            Some(RuneId { block: 2, tx: 1 }),
+            10
+        ));
+        assert_eq!(runes.len(), 1);
+    }
+
     struct MockHttpClient {
         /// Runes by page
         runes: HashMap<u64, Vec<(RuneId, RuneInfo)>>,
@@ -280,7 +424,8 @@ mod tests {
             &self,
             _url: &str,
             uri: &str,
-        ) -> Result<R, DepositError> {
+            _max_response_bytes: u64,
+        ) -> Result<R, IndexerRequestError> {
             let page = uri
                 .strip_prefix("runes/")
                 .and_then(|page| page.parse::<u64>().ok())
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bitcoin_bridge_core::http::HttpClient;
+use bridge_did::runes::RuneName;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ordinals::{RuneId, SpacedRune};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    MAX_PAGE_RESPONSE_BYTES, MAX_RESPONSE_BYTES, RuneIndexProvider, collect_new_runes,
+    format_outpoint, format_txid, parse_decimal_amount, parse_rune_id, parse_spaced_rune,
+    sort_rune_list,
+};
+use crate::core::rune_inputs::GetInputsError;
+
+const HIRO_MAX_LIMIT: u64 = 60;
+
+/// Operation of the activity entry, which credits runes to an output.
+const RECEIVE_OPERATION: &str = "receive";
+
+/// Response for `/runes/v1/etchings` and `/runes/v1/transactions/{tx_id}/activity` endpoints.
+///
+/// Etchings are ordered from the most recently etched rune.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct PaginatedResponse<T> {
+    total: u64,
+    results: Vec<T>,
+}
+
+/// Response for `/runes/v1/etchings/{etching}` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct EtchingResponse {
+    id: String,
+    spaced_name: String,
+    divisibility: u8,
+}
+
+impl EtchingResponse {
+    fn to_rune(&self) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
+        Ok((
+            parse_rune_id(&self.id)?,
+            parse_spaced_rune(&self.spaced_name)?,
+            self.divisibility,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct ActivityResponse {
+    rune: ActivityRune,
+    operation: String,
+    amount: Option<String>,
+    location: ActivityLocation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct ActivityRune {
+    id: String,
+    spaced_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct ActivityLocation {
+    output: String,
+}
+
+/// Implementation of the `RuneIndexProvider` trait over the Hiro Runes API.
+///
+/// Hiro reports rune amounts as decimal strings, so divisibility of each received rune is
+/// requested to convert them to the base units used by the other indexers.
+pub struct HiroIndexProvider<C: HttpClient> {
+    client: C,
+    url: String,
+}
+
+impl<C> HiroIndexProvider<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, url: String) -> Self {
+        Self { client, url }
+    }
+
+    async fn request<R: DeserializeOwned>(
+        &self,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> Result<R, GetInputsError> {
+        self.client
+            .http_request(&self.url, uri, max_response_bytes)
+            .await
+            .map_err(|err| GetInputsError::IndexerError(format!("{err:?}")))
+    }
+
+    /// Requests all the pages of the given paginated endpoint.
+    async fn request_all<R: DeserializeOwned>(&self, path: &str) -> Result<Vec<R>, GetInputsError> {
+        let mut results = vec![];
+        let mut offset = 0;
+        let mut total = usize::MAX;
+
+        while offset < total {
+            let uri = format!("{path}?offset={offset}&limit={HIRO_MAX_LIMIT}");
+            let response: PaginatedResponse<R> =
+                self.request(&uri, MAX_PAGE_RESPONSE_BYTES).await?;
+            if response.results.is_empty() {
+                break;
+            }
+
+            total = response.total as usize;
+            offset += response.results.len();
+            results.extend(response.results);
+        }
+
+        Ok(results)
+    }
+}
+
+#[async_trait(?Send)]
+impl<C> RuneIndexProvider for HiroIndexProvider<C>
+where
+    C: HttpClient,
+{
+    async fn get_rune_amounts(
+        &self,
+        utxo: &Utxo,
+    ) -> Result<HashMap<RuneName, u128>, GetInputsError> {
+        let outpoint = format_outpoint(&utxo.outpoint);
+        log::trace!("Requesting rune balances for utxo: {outpoint}");
+
+        let txid = format_txid(&utxo.outpoint.txid);
+        let activity: Vec<ActivityResponse> = self
+            .request_all(&format!("runes/v1/transactions/{txid}/activity"))
+            .await?;
+
+        let mut amounts = HashMap::new();
+        for entry in activity {
+            if entry.operation != RECEIVE_OPERATION || entry.location.output != outpoint {
+                continue;
+            }
+
+            let Some(amount) = entry.amount else {
+                continue;
+            };
+
+            let etching: EtchingResponse = self
+                .request(
+                    &format!("runes/v1/etchings/{}", entry.rune.id),
+                    MAX_RESPONSE_BYTES,
+                )
+                .await?;
+            let amount = parse_decimal_amount(&amount, etching.divisibility).ok_or_else(|| {
+                GetInputsError::IndexerError(format!(
+                    "invalid amount {amount} of rune {}",
+                    entry.rune.spaced_name
+                ))
+            })?;
+            let rune_name = RuneName::from(parse_spaced_rune(&entry.rune.spaced_name)?.rune);
+
+            let total: &mut u128 = amounts.entry(rune_name).or_default();
+            *total = total.checked_add(amount).ok_or_else(|| {
+                GetInputsError::IndexerError(format!(
+                    "amount of rune {} overflows",
+                    entry.rune.spaced_name
+                ))
+            })?;
+        }
+
+        log::trace!("Received rune balances for utxo {outpoint}: {amounts:?}");
+
+        Ok(amounts)
+    }
+
+    async fn get_rune_list(
+        &self,
+        after: Option<RuneId>,
+        limit: usize,
+    ) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
+        let mut runes = vec![];
+        let mut offset = 0;
+
+        loop {
+            let uri = format!("runes/v1/etchings?offset={offset}&limit={HIRO_MAX_LIMIT}");
+            let response: PaginatedResponse<EtchingResponse> =
+                self.request(&uri, MAX_PAGE_RESPONSE_BYTES).await?;
+            offset += response.results.len() as u64;
+
+            let page = response
+                .results
+                .iter()
+                .map(EtchingResponse::to_rune)
+                .collect::<Result<Vec<_>, _>>()?;
+            let has_more = collect_new_runes(&mut runes, page, after, limit);
+
+            if !has_more || offset >= response.total || response.results.is_empty() {
+                break;
+            }
+        }
+
+        Ok(sort_rune_list(runes))
+    }
+
+    async fn get_rune(
+        &self,
+        rune_name: &RuneName,
+    ) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
+        let etching: EtchingResponse = self
+            .request(
+                &format!("runes/v1/etchings/{rune_name}"),
+                MAX_RESPONSE_BYTES,
+            )
+            .await?;
+
+        etching.to_rune()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bitcoin_bridge_core::http::IndexerRequestError;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+
+    const TXID: &str = "1a4a16488b256849fe07d0995c067b3c97b575bc67d3b9f3119e3207b9b83f62";
+
+    struct MockHttpClient {
+        responses: HashMap<String, String>,
+    }
+
+    impl HttpClient for MockHttpClient {
+        async fn http_request<R: DeserializeOwned>(
+            &self,
+            _url: &str,
+            uri: &str,
+            _max_response_bytes: u64,
+        ) -> Result<R, IndexerRequestError> {
+            let response = self
+                .responses
+                .get(uri)
+                .ok_or_else(|| IndexerRequestError::Unavailable(format!("unexpected uri {uri}")))?;
+
+            Ok(serde_json::from_str(response).expect("Failed to deserialize response"))
+        }
+    }
+
+    fn provider(responses: &[(&str, &str)]) -> HiroIndexProvider<MockHttpClient> {
+        let client = MockHttpClient {
+            responses: responses
+                .iter()
+                .map(|(uri, response)| (uri.to_string(), response.to_string()))
+                .collect(),
+        };
+
+        HiroIndexProvider::new(client, "https://api.hiro.so".to_string())
+    }
+
+    fn utxo(vout: u32) -> Utxo {
+        let mut txid = hex::decode(TXID).unwrap();
+        txid.reverse();
+        Utxo {
+            outpoint: Outpoint { txid, vout },
+            value: 10_000,
+            height: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_should_get_rune_amounts_of_output() {
+        let activity_uri = format!("runes/v1/transactions/{TXID}/activity?offset=0&limit=60");
+        let activity = format!(
+            r#"{{"total":3,"results":[
+                {{"rune":{{"id":"840000:1","spaced_name":"UNCOMMON•GOODS"}},"operation":"receive","amount":"1.5","location":{{"output":"{TXID}:1"}}}},
+                {{"rune":{{"id":"840000:1","spaced_name":"UNCOMMON•GOODS"}},"operation":"receive","amount":"7","location":{{"output":"{TXID}:2"}}}},
+                {{"rune":{{"id":"840000:1","spaced_name":"UNCOMMON•GOODS"}},"operation":"send","amount":"8.5","location":{{"output":"{TXID}:0"}}}}
+            ]}}"#
+        );
+        let etching =
+            r#"{"id":"840000:1","spaced_name":"UNCOMMON•GOODS","divisibility":2}"#.to_string();
+
+        let provider = provider(&[
+            (&activity_uri, &activity),
+            ("runes/v1/etchings/840000:1", &etching),
+        ]);
+
+        let amounts = provider.get_rune_amounts(&utxo(1)).await.unwrap();
+        assert_eq!(
+            amounts,
+            HashMap::from([(RuneName::from_str("UNCOMMONGOODS").unwrap(), 150)])
+        );
+
+        let amounts = provider.get_rune_amounts(&utxo(3)).await.unwrap();
+        assert!(amounts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_should_get_all_pages_of_rune_list() {
+        let provider = provider(&[
+            (
+                "runes/v1/etchings?offset=0&limit=60",
+                r#"{"total":2,"results":[{"id":"840001:2","spaced_name":"B•B","divisibility":0}]}"#,
+            ),
+            (
+                "runes/v1/etchings?offset=1&limit=60",
+                r#"{"total":2,"results":[{"id":"840000:1","spaced_name":"A•A","divisibility":8}]}"#,
+            ),
+        ]);
+
+        let runes = provider.get_rune_list(None, usize::MAX).await.unwrap();
+        assert_eq!(
+            runes,
+            vec![
+                (
+                    RuneId {
+                        block: 840000,
+                        tx: 1
+                    },
+                    SpacedRune::from_str("A•A").unwrap(),
+                    8
+                ),
+                (
+                    RuneId {
+                        block: 840001,
+                        tx: 2
+                    },
+                    SpacedRune::from_str("B•B").unwrap(),
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_get_rune_by_name() {
+        let provider = provider(&[(
+            "runes/v1/etchings/UNCOMMONGOODS",
+            r#"{"id":"1:0","spaced_name":"UNCOMMON•GOODS","divisibility":0}"#,
+        )]);
+
+        let rune = provider
+            .get_rune(&RuneName::from_str("UNCOMMONGOODS").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            rune,
+            (
+                RuneId { block: 1, tx: 0 },
+                SpacedRune::from_str("UNCOMMON•GOODS").unwrap(),
+                0
+            )
+        );
+    }
+}
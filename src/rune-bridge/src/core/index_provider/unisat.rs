@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use bitcoin_bridge_core::http::HttpClient;
+use bridge_did::runes::RuneName;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ordinals::{RuneId, SpacedRune};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    MAX_PAGE_RESPONSE_BYTES, MAX_RESPONSE_BYTES, RuneIndexProvider, collect_new_runes,
+    format_outpoint, format_txid, parse_rune_id, parse_spaced_rune, sort_rune_list,
+};
+use crate::core::rune_inputs::GetInputsError;
+
+const UNISAT_MAX_LIMIT: u64 = 500;
+
+/// Code of the successful UniSat API response.
+const SUCCESS_CODE: i32 = 0;
+
+/// Envelope of all the UniSat API responses.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct UnisatResponse<T> {
+    code: i32,
+    msg: String,
+    data: Option<T>,
+}
+
+/// Data of the `/v1/indexer/utxo/{txid}/{index}/runes` endpoint response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct UtxoRuneResponse {
+    spaced_rune: String,
+    /// Amount in the rune base units.
+    amount: String,
+}
+
+/// Data of the `/v1/indexer/runes/info-list` endpoint response.
+///
+/// Runes are ordered from the most recently etched one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct RuneInfoListResponse {
+    total: u64,
+    detail: Vec<RuneInfoResponse>,
+}
+
+/// Data of the `/v1/indexer/runes/{runeid}/info` endpoint response and the rune list entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct RuneInfoResponse {
+    #[serde(rename = "runeid")]
+    rune_id: String,
+    spaced_rune: String,
+    divisibility: u8,
+}
+
+impl RuneInfoResponse {
+    fn to_rune(&self) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
+        Ok((
+            parse_rune_id(&self.rune_id)?,
+            parse_spaced_rune(&self.spaced_rune)?,
+            self.divisibility,
+        ))
+    }
+}
+
+/// Implementation of the `RuneIndexProvider` trait over the UniSat Open API.
+pub struct UnisatIndexProvider<C: HttpClient> {
+    client: C,
+    url: String,
+}
+
+impl<C> UnisatIndexProvider<C>
+where
+    C: HttpClient,
+{
+    pub fn new(client: C, url: String) -> Self {
+        Self { client, url }
+    }
+
+    async fn request<R: DeserializeOwned>(
+        &self,
+        uri: &str,
+        max_response_bytes: u64,
+    ) -> Result<R, GetInputsError> {
+        let response: UnisatResponse<R> = self
+            .client
+            .http_request(&self.url, uri, max_response_bytes)
+            .await
+            .map_err(|err| GetInputsError::IndexerError(format!("{err:?}")))?;
+
+        match response.data {
+            Some(data) if response.code == SUCCESS_CODE => Ok(data),
+            _ => Err(GetInputsError::IndexerError(format!(
+                "UniSat API error {}: {}",
+                response.code, response.msg
+            ))),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<C> RuneIndexProvider for UnisatIndexProvider<C>
+where
+    C: HttpClient,
+{
+    async fn get_rune_amounts(
+        &self,
+        utxo: &Utxo,
+    ) -> Result<HashMap<RuneName, u128>, GetInputsError> {
+        let outpoint = format_outpoint(&utxo.outpoint);
+        log::trace!("Requesting rune balances for utxo: {outpoint}");
+
+        let uri = format!(
+            "v1/indexer/utxo/{}/{}/runes",
+            format_txid(&utxo.outpoint.txid),
+            utxo.outpoint.vout
+        );
+        let balances: Vec<UtxoRuneResponse> = self.request(&uri, MAX_RESPONSE_BYTES).await?;
+
+        let mut amounts = HashMap::new();
+        for balance in balances {
+            let amount = balance.amount.parse::<u128>().map_err(|err| {
+                GetInputsError::IndexerError(format!(
+                    "invalid amount {} of rune {}: {err}",
+                    balance.amount, balance.spaced_rune
+                ))
+            })?;
+            let rune_name = RuneName::from(parse_spaced_rune(&balance.spaced_rune)?.rune);
+            amounts.insert(rune_name, amount);
+        }
+
+        log::trace!("Received rune balances for utxo {outpoint}: {amounts:?}");
+
+        Ok(amounts)
+    }
+
+    async fn get_rune_list(
+        &self,
+        after: Option<RuneId>,
+        limit: usize,
+    ) -> Result<Vec<(RuneId, SpacedRune, u8)>, GetInputsError> {
+        let mut runes = vec![];
+        let mut start = 0;
+
+        loop {
+            let uri = format!("v1/indexer/runes/info-list?start={start}&limit={UNISAT_MAX_LIMIT}");
+            let response: RuneInfoListResponse =
+                self.request(&uri, MAX_PAGE_RESPONSE_BYTES).await?;
+            start += response.detail.len() as u64;
+
+            let page = response
+                .detail
+                .iter()
+                .map(RuneInfoResponse::to_rune)
+                .collect::<Result<Vec<_>, _>>()?;
+            let has_more = collect_new_runes(&mut runes, page, after, limit);
+
+            if !has_more || start >= response.total || response.detail.is_empty() {
+                break;
+            }
+        }
+
+        Ok(sort_rune_list(runes))
+    }
+
+    async fn get_rune(
+        &self,
+        rune_name: &RuneName,
+    ) -> Result<(RuneId, SpacedRune, u8), GetInputsError> {
+        let info: RuneInfoResponse = self
+            .request(
+                &format!("v1/indexer/runes/{rune_name}/info"),
+                MAX_RESPONSE_BYTES,
+            )
+            .await?;
+
+        info.to_rune()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bitcoin_bridge_core::http::IndexerRequestError;
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+    use super::*;
+
+    const TXID: &str = "1a4a16488b256849fe07d0995c067b3c97b575bc67d3b9f3119e3207b9b83f62";
+
+    struct MockHttpClient {
+        responses: HashMap<String, String>,
+    }
+
+    impl HttpClient for MockHttpClient {
+        async fn http_request<R: DeserializeOwned>(
+            &self,
+            _url: &str,
+            uri: &str,
+            _max_response_bytes: u64,
+        ) -> Result<R, IndexerRequestError> {
+            let response = self
+                .responses
+                .get(uri)
+                .ok_or_else(|| IndexerRequestError::Unavailable(format!("unexpected uri {uri}")))?;
+
+            Ok(serde_json::from_str(response).expect("Failed to deserialize response"))
+        }
+    }
+
+    fn provider(responses: &[(&str, &str)]) -> UnisatIndexProvider<MockHttpClient> {
+        let client = MockHttpClient {
+            responses: responses
+                .iter()
+                .map(|(uri, response)| (uri.to_string(), response.to_string()))
+                .collect(),
+        };
+
+        UnisatIndexProvider::new(client, "https://open-api.unisat.io".to_string())
+    }
+
+    fn utxo(vout: u32) -> Utxo {
+        let mut txid = hex::decode(TXID).unwrap();
+        txid.reverse();
+        Utxo {
+            outpoint: Outpoint { txid, vout },
+            value: 10_000,
+            height: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_should_get_rune_amounts_of_output() {
+        let uri = format!("v1/indexer/utxo/{TXID}/1/runes");
+        let provider = provider(&[(
+            &uri,
+            r#"{"code":0,"msg":"ok","data":[{"runeid":"840000:1","spacedRune":"UNCOMMON•GOODS","amount":"150","divisibility":2}]}"#,
+        )]);
+
+        let amounts = provider.get_rune_amounts(&utxo(1)).await.unwrap();
+        assert_eq!(
+            amounts,
+            HashMap::from([(RuneName::from_str("UNCOMMONGOODS").unwrap(), 150)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_return_error_on_api_error_code() {
+        let uri = format!("v1/indexer/utxo/{TXID}/1/runes");
+        let provider = provider(&[(&uri, r#"{"code":-1,"msg":"utxo not found","data":null}"#)]);
+
+        assert!(matches!(
+            provider.get_rune_amounts(&utxo(1)).await,
+            Err(GetInputsError::IndexerError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_should_get_all_pages_of_rune_list() {
+        let provider = provider(&[
+            (
+                "v1/indexer/runes/info-list?start=0&limit=500",
+                r#"{"code":0,"msg":"ok","data":{"total":2,"detail":[{"runeid":"840001:2","spacedRune":"B•B","divisibility":0}]}}"#,
+            ),
+            (
+                "v1/indexer/runes/info-list?start=1&limit=500",
+                r#"{"code":0,"msg":"ok","data":{"total":2,"detail":[{"runeid":"840000:1","spacedRune":"A•A","divisibility":8}]}}"#,
+            ),
+        ]);
+
+        let runes = provider.get_rune_list(None, usize::MAX).await.unwrap();
+        assert_eq!(runes.len(), 2);
+        assert_eq!(
+            runes[0],
+            (
+                RuneId {
+                    block: 840000,
+                    tx: 1
+                },
+                SpacedRune::from_str("A•A").unwrap(),
+                8
+            )
+        );
+        assert_eq!(
+            runes[1].0,
+            RuneId {
+                block: 840001,
+                tx: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_get_rune_by_name() {
+        let provider = provider(&[(
+            "v1/indexer/runes/UNCOMMONGOODS/info",
+            r#"{"code":0,"msg":"ok","data":{"runeid":"1:0","spacedRune":"UNCOMMON•GOODS","divisibility":0}}"#,
+        )]);
+
+        let rune = provider
+            .get_rune(&RuneName::from_str("UNCOMMONGOODS").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            rune,
+            (
+                RuneId { block: 1, tx: 0 },
+                SpacedRune::from_str("UNCOMMON•GOODS").unwrap(),
+                0
+            )
+        );
+    }
+}
@@ -1,6 +1,5 @@
 pub mod deposit;
 pub mod index_provider;
-pub mod utxo_provider;
 pub mod withdrawal;
 
 pub mod rune_inputs;
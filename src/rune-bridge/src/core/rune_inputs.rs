@@ -1,12 +1,12 @@
 use std::collections::HashMap;
 
+use bitcoin_bridge_core::consensus::ConsensusError;
+use bitcoin_bridge_core::key::KeyError;
+use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
 use bridge_did::runes::{RuneInfo, RuneName};
 use did::H160;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
-use ic_exports::ic_kit::RejectionCode;
 use thiserror::Error;
-
-use crate::key::KeyError;
 #[derive(Debug, Clone, Default)]
 pub(crate) struct RuneInput {
     pub utxo: Utxo,
@@ -60,14 +60,44 @@ pub(crate) enum GetInputsError {
     },
 }
 
-impl GetInputsError {
-    pub fn btc(call_error: (RejectionCode, String)) -> Self {
-        Self::BtcAdapter(call_error.1)
+impl From<ConsensusError> for GetInputsError {
+    fn from(err: ConsensusError) -> Self {
+        match err {
+            ConsensusError::IndexersDisagree {
+                first_response,
+                another_response,
+            } => Self::IndexersDisagree {
+                first_response,
+                another_response,
+            },
+            ConsensusError::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            } => Self::InsufficientConsensus {
+                received_responses,
+                required_responses,
+                checked_indexers,
+            },
+        }
+    }
+}
+
+impl From<UtxoProviderError> for GetInputsError {
+    fn from(err: UtxoProviderError) -> Self {
+        match err {
+            UtxoProviderError::BtcAdapter(msg) => Self::BtcAdapter(msg),
+            err => Self::BtcAdapter(err.to_string()),
+        }
     }
 }
 
 pub(crate) trait RuneInputProvider {
     async fn get_inputs(&self, dst_address: &H160) -> Result<RuneInputs, GetInputsError>;
+    async fn get_rune_amounts(
+        &self,
+        utxo: &Utxo,
+    ) -> Result<HashMap<RuneName, u128>, GetInputsError>;
     async fn get_rune_infos(
         &self,
         rune_amounts: &HashMap<RuneName, u128>,
@@ -124,6 +154,19 @@ pub(crate) mod mock {
             self.inputs.clone()
         }
 
+        async fn get_rune_amounts(
+            &self,
+            utxo: &Utxo,
+        ) -> Result<HashMap<RuneName, u128>, GetInputsError> {
+            let inputs = self.inputs.clone()?;
+            Ok(inputs
+                .inputs
+                .into_iter()
+                .find(|input| input.utxo.outpoint == utxo.outpoint)
+                .map(|input| input.runes)
+                .unwrap_or_default())
+        }
+
         async fn get_rune_infos(
             &self,
             rune_amounts: &HashMap<RuneName, u128>,
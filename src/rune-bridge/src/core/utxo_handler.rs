@@ -1,11 +1,10 @@
+use bitcoin_bridge_core::key::KeyError;
 use bridge_did::order::MintOrder;
-use bridge_did::runes::RuneToWrap;
+use bridge_did::runes::{RuneInfo, RuneToWrap};
 use did::H160;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
 use thiserror::Error;
 
-use crate::key::KeyError;
-
 #[derive(Debug, Clone, PartialEq, Error)]
 pub(crate) enum UtxoHandlerError {
     #[error("key error {0}")]
@@ -32,11 +31,14 @@ pub(crate) trait UtxoHandler {
         utxo: &Utxo,
     ) -> Result<(), UtxoHandlerError>;
 
+    /// Adds the utxo to the ledger and creates the mint orders of the wrapped runes. The utxo
+    /// holding runes to be refunded is reserved for the refund transaction.
     async fn deposit(
         &self,
         utxo: &Utxo,
         dst_address: &H160,
         utxo_runes: Vec<RuneToWrap>,
+        refunded_runes: Vec<RuneInfo>,
     ) -> Result<Vec<MintOrder>, UtxoHandlerError>;
 }
 
@@ -81,6 +83,7 @@ pub mod test {
             _utxo: &Utxo,
             _dst_address: &H160,
             utxo_runes: Vec<RuneToWrap>,
+            _refunded_runes: Vec<RuneInfo>,
         ) -> Result<Vec<MintOrder>, UtxoHandlerError> {
             if self.is_utxo_used {
                 Err(UtxoHandlerError::UtxoAlreadyUsed)
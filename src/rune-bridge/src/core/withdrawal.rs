@@ -1,25 +1,49 @@
+mod consolidation;
+mod cosigning;
+mod etching;
+mod refund;
+
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::str::FromStr;
 
+use bitcoin::absolute::LockTime;
+use bitcoin::bip32::DerivationPath;
 use bitcoin::hashes::Hash;
-use bitcoin::{Address, Amount, FeeRate, Network, OutPoint, Transaction, TxOut, Txid};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Txid, Witness,
+};
+use bitcoin_bridge_core::funding::{replacement_fee, select_funding_utxos, split_fee};
+use bitcoin_bridge_core::key::{
+    self, derivation_path_to_ic, get_derivation_path, get_derivation_path_ic,
+    ic_dp_to_derivation_path,
+};
+use bitcoin_bridge_core::utxo_provider::{IcUtxoProvider, UtxoProvider};
 use bridge_did::event_data::BurntEventData;
 use bridge_did::id256::Id256;
-use bridge_did::runes::RuneWithdrawalPayload;
+use bridge_did::runes::{DidTxInput, RuneInfo, RuneName, RuneWithdrawalPayload};
 use did::H160;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
 use ic_exports::ic_kit::ic;
 use ord_rs::OrdTransactionBuilder;
-use ord_rs::fees::{EstimateEdictTxFeesArgs, estimate_edict_transaction_fees};
+use ord_rs::constants::POSTAGE;
+use ord_rs::fees::{
+    EstimateEdictTxFeesArgs, estimate_edict_transaction_fees, estimate_transaction_fees,
+};
 use ord_rs::wallet::{CreateEdictTxArgs, ScriptType, TxInputInfo};
-use ordinals::RuneId;
+use ordinals::{Edict, RuneId, Runestone};
 
+pub use self::consolidation::{UtxoMerge, dust_limit};
+pub use self::etching::PREMINE_OUTPUT_INDEX;
 use crate::canister::{get_rune_state, get_runtime_state};
-use crate::constants::FEE_RATE_UPDATE_INTERVAL;
-use crate::core::utxo_provider::{IcUtxoProvider, UtxoProvider};
+use crate::constants::{DUST_THRESHOLD, FEE_RATE_UPDATE_INTERVAL, INCREMENTAL_RELAY_FEE_RATE};
+use crate::core::rune_inputs::RuneInputProvider;
 use crate::interface::WithdrawError;
-use crate::key::{BtcSignerType, get_derivation_path, get_derivation_path_ic};
+use crate::key::BtcSignerType;
+use crate::ledger::UtxoKey;
 use crate::state::RuneState;
 
 pub struct RuneWithdrawalPayloadImpl(pub RuneWithdrawalPayload);
@@ -31,6 +55,8 @@ impl RuneWithdrawalPayloadImpl {
             amount,
             to_token,
             sender,
+            from_erc20,
+            decimals,
             ..
         } = burnt_event_data;
 
@@ -69,6 +95,21 @@ impl RuneWithdrawalPayloadImpl {
             )));
         };
 
+        match state.evm_native_token_by_rune(rune_id) {
+            Some(token) if token.erc20 != from_erc20 || token.divisibility != decimals => {
+                return Err(WithdrawError::InvalidRequest(format!(
+                    "Rune {rune_id} represents the token {} with {} decimals, but the token {from_erc20} with {decimals} decimals is locked",
+                    token.erc20, token.divisibility
+                )));
+            }
+            None if state.evm_native_token(&from_erc20).is_some() => {
+                return Err(WithdrawError::InvalidRequest(format!(
+                    "Token {from_erc20} is not represented by the rune {rune_id}"
+                )));
+            }
+            _ => {}
+        }
+
         Ok(Self(RuneWithdrawalPayload {
             rune_info,
             amount,
@@ -99,6 +140,7 @@ impl Withdrawal<IcUtxoProvider> {
         let network = state_ref.network();
         let ic_network = state_ref.ic_btc_network();
         let cache_timeout = state_ref.utxo_cache_timeout();
+        let fee_estimator = state_ref.fee_estimator();
         let signer = state_ref
             .btc_signer(&signing_strategy)
             .ok_or(WithdrawError::SignerNotInitialized)?;
@@ -109,7 +151,7 @@ impl Withdrawal<IcUtxoProvider> {
             state,
             network,
             signer,
-            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout),
+            utxo_provider: IcUtxoProvider::new(ic_network, cache_timeout, fee_estimator),
         })
     }
 
@@ -119,10 +161,25 @@ impl Withdrawal<IcUtxoProvider> {
 }
 
 impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    /// Creates and signs the withdrawal transaction. Returns the transaction together with its
+    /// inputs, which are required to replace the transaction later.
     pub async fn create_withdrawal_transaction(
         &self,
         payload: RuneWithdrawalPayload,
-    ) -> Result<Transaction, WithdrawError> {
+    ) -> Result<(Transaction, Vec<TxInputInfo>), WithdrawError> {
+        let (unsigned_tx, inputs, dst_address) = self.prepare_withdrawal(payload).await?;
+        let tx = self.sign_transaction(&unsigned_tx, &inputs).await?;
+        self.mark_inputs_used(&inputs, &dst_address);
+
+        Ok((tx, inputs))
+    }
+
+    /// Selects the inputs of the withdrawal and builds the unsigned transaction. Returns the
+    /// transaction with its inputs and the recipient address.
+    async fn prepare_withdrawal(
+        &self,
+        payload: RuneWithdrawalPayload,
+    ) -> Result<(Transaction, Vec<TxInputInfo>, Address), WithdrawError> {
         let dst_address = payload.dst_address;
 
         let RuneWithdrawalPayload {
@@ -166,12 +223,7 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             })
             .collect();
 
-        let Ok(dst_address) = Address::from_str(&dst_address) else {
-            return Err(WithdrawError::InvalidRequest(format!(
-                "Failed to decode recipient address from string: {dst_address}"
-            )));
-        };
-        let dst_address = dst_address.assume_checked();
+        let dst_address = parse_dst_address(&dst_address)?;
 
         // Get the utxos that can fund the transaction with the minimum number of utxos
         let mut funding_tx_inputs = vec![];
@@ -206,39 +258,54 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
         log::info!("input_utxos utxos: {}", input_utxos.len());
         log::debug!("input_utxos: {input_utxos:?}");
 
-        let tx = self
-            .build_withdraw_transaction(WithdrawalTransactionArgs {
-                change_address: funding_address,
-                dst_address: dst_address.clone(),
-                fee_rate,
-                inputs: input_utxos.clone(),
-                rune_change_address,
-                runes: vec![(rune_info.id(), amount)],
-            })
-            .await?;
+        let unsigned_tx = self.build_withdraw_transaction(WithdrawalTransactionArgs {
+            change_address: funding_address,
+            dst_address: dst_address.clone(),
+            fee_rate,
+            inputs: input_utxos.clone(),
+            rune_change_address,
+            runes: vec![(rune_info.id(), amount)],
+        })?;
 
-        {
-            let mut state = self.state.borrow_mut();
-            let ledger = state.ledger_mut();
-            for utxo in input_utxos {
-                ledger.mark_as_used(utxo.outpoint.into(), dst_address.clone());
-            }
-        }
+        Ok((unsigned_tx, input_utxos, dst_address))
+    }
 
-        Ok(tx)
+    /// Marks the rune inputs of the withdrawal transaction as used, so they are not spent by
+    /// other withdrawals.
+    fn mark_inputs_used(&self, inputs: &[TxInputInfo], dst_address: &Address) {
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for utxo in inputs {
+            ledger.mark_as_used(utxo.outpoint.into(), dst_address.clone());
+        }
     }
 
+    /// Sends the transaction and adds its rune change output to the ledger.
     pub async fn send_transaction(&self, tx: Transaction) -> Result<(), WithdrawError> {
+        self.send_transaction_with_change(tx, vec![]).await
+    }
+
+    async fn send_transaction_with_change(
+        &self,
+        tx: Transaction,
+        rune_info: Vec<RuneInfo>,
+    ) -> Result<(), WithdrawError> {
         self.utxo_provider.send_tx(&tx).await?;
-        let change_address = self.get_change_address().await?;
 
         const CHANGE_OUTPOINT_INDEX: usize = 1;
         // Make sure that the transaction builder code is not change and the change outpoint
         // is where we expect it to be. If not, panic until the code of the canister is fixed.
-        assert_eq!(
-            tx.output[CHANGE_OUTPOINT_INDEX].script_pubkey,
-            change_address.script_pubkey()
-        );
+        // Replacements of the transactions sent before the taproot addresses were enabled keep
+        // their legacy change address.
+        let change_address = self
+            .signer
+            .get_transit_addresses(&H160::default(), self.network)
+            .await?
+            .into_iter()
+            .find(|address| {
+                tx.output[CHANGE_OUTPOINT_INDEX].script_pubkey == address.script_pubkey()
+            })
+            .expect("rune change output is not sent to the change address");
 
         let change_utxo = Utxo {
             outpoint: Outpoint {
@@ -253,12 +320,718 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             change_utxo,
             &change_address,
             self.get_change_derivation_path(),
-            vec![],
+            rune_info,
+        );
+
+        Ok(())
+    }
+
+    /// Checks whether the withdrawal transaction or one of the transactions it replaced has been
+    /// included into a block.
+    ///
+    /// The confirmed version is found by its rune change output. As this output can be already
+    /// spent by another withdrawal, the transaction is also considered confirmed once its first
+    /// input is spent: only one of the versions of the transaction could spend it.
+    pub async fn transaction_status(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+        replaced_txids: &[Txid],
+    ) -> Result<TransactionStatus, WithdrawError> {
+        self.change_output_status(tx, inputs, replaced_txids, RUNE_CHANGE_OUTPUT_INDEX)
+            .await
+    }
+
+    /// Checks whether the output of the transaction with the given index, held at the change
+    /// address, or the same output of one of the replaced transactions is confirmed.
+    async fn change_output_status(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+        replaced_txids: &[Txid],
+        change_output_index: usize,
+    ) -> Result<TransactionStatus, WithdrawError> {
+        let change_address = self.get_change_address().await?;
+        let change_utxos = self
+            .utxo_provider
+            .get_utxos(&change_address)
+            .await
+            .map_err(|err| {
+                log::warn!("Failed to get utxos of the change address: {err:?}");
+                WithdrawError::TxNotConfirmed
+            })?
+            .utxos;
+
+        let current_txid = tx.txid();
+        let confirmed_change = [current_txid]
+            .iter()
+            .chain(replaced_txids)
+            .find_map(|txid| {
+                change_utxos.iter().find(|utxo| {
+                    utxo.outpoint.txid == txid.as_byte_array()
+                        && utxo.outpoint.vout == change_output_index as u32
+                })
+            });
+        if let Some(change_utxo) = confirmed_change {
+            return Ok(TransactionStatus::Confirmed(change_utxo.clone()));
+        }
+
+        let input = inputs.first().ok_or(WithdrawError::NoInputs)?;
+        let input_owner = Address::from_script(&input.tx_out.script_pubkey, self.network)
+            .map_err(|err| WithdrawError::InternalError(format!("invalid input script: {err}")))?;
+        let input_key = UtxoKey::from(input.outpoint);
+        let input_unspent = self
+            .utxo_provider
+            .get_utxos(&input_owner)
+            .await
+            .map_err(|err| {
+                log::warn!("Failed to get utxos of the address {input_owner}: {err:?}");
+                WithdrawError::TxNotConfirmed
+            })?
+            .utxos
+            .iter()
+            .any(|utxo| UtxoKey::from(&utxo.outpoint) == input_key);
+
+        if input_unspent {
+            Ok(TransactionStatus::Pending)
+        } else {
+            Ok(TransactionStatus::InputsSpent)
+        }
+    }
+
+    /// Updates the ledger after the withdrawal transaction has been confirmed.
+    ///
+    /// If one of the replaced transactions was confirmed instead of the last one, the rune change
+    /// of the last transaction is replaced by the change of the confirmed one.
+    pub fn complete_transaction(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+        confirmed_change: Option<Utxo>,
+    ) {
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for input in inputs {
+            ledger.remove_spent_utxo(&UtxoKey::from(input.outpoint));
+        }
+
+        let Some(change_utxo) = confirmed_change else {
+            return;
+        };
+
+        let change_key = UtxoKey::from(&change_utxo.outpoint);
+        let sent_change_key = UtxoKey::from(OutPoint {
+            txid: tx.txid(),
+            vout: RUNE_CHANGE_OUTPUT_INDEX as u32,
+        });
+        // the withdrawals of a batch complete the same transaction
+        if change_key == sent_change_key || ledger.is_unspent(&change_key) {
+            return;
+        }
+
+        log::info!(
+            "Replaced transaction {} was confirmed instead of {}",
+            hex::encode(&change_utxo.outpoint.txid),
+            tx.txid()
+        );
+
+        let rune_info = ledger
+            .load_unspent_utxos()
+            .ok()
+            .and_then(|mut unspent| unspent.remove(&sent_change_key))
+            .map(|info| (info.tx_input_info, info.rune_info));
+        ledger.remove_spent_utxo(&sent_change_key);
+
+        if let Some((sent_change, rune_info)) = rune_info {
+            let address = Address::from_script(&sent_change.tx_out.script_pubkey, self.network)
+                .expect("change address script must be valid");
+            ledger.deposit(
+                change_utxo,
+                &address,
+                derivation_path_to_ic(sent_change.derivation_path),
+                rune_info,
+            );
+        } else {
+            log::warn!(
+                "Rune change of the transaction {} was already spent; the change of the confirmed transaction is not added to the ledger",
+                tx.txid()
+            );
+        }
+    }
+
+    /// Keeps the inputs of an unconfirmed transaction marked as used.
+    pub fn refresh_inputs(&self, inputs: &[TxInputInfo]) {
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for input in inputs {
+            ledger.refresh_used_utxo(&UtxoKey::from(input.outpoint));
+        }
+    }
+
+    /// Bumps the fee of the unconfirmed transaction.
+    ///
+    /// The transaction is replaced (BIP-125) by one paying a higher fee out of the BTC change
+    /// outputs of the senders. If its rune change has already been spent by another withdrawal,
+    /// replacing it would invalidate that withdrawal, so a child transaction spending the BTC
+    /// change outputs pays for both of them instead (CPFP).
+    ///
+    /// `destinations` is the number of the destination outputs of the transaction, and
+    /// `change_owners` lists the scripts of the BTC change outputs with the derivation paths of
+    /// their keys. `child_fee` is the fee of the child transaction sent by the previous bump, if
+    /// any; a new child replaces it.
+    pub async fn bump_fee(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+        destinations: usize,
+        change_owners: &[(ScriptBuf, DerivationPath)],
+        child_fee: Option<u64>,
+    ) -> Result<FeeBump, WithdrawError> {
+        let rune_change_key = UtxoKey::from(OutPoint {
+            txid: tx.txid(),
+            vout: RUNE_CHANGE_OUTPUT_INDEX as u32,
+        });
+        let change_outputs = change_outputs(tx, destinations, change_owners);
+        if change_outputs.is_empty() {
+            return Err(WithdrawError::CannotReplaceTransaction(
+                "no change output".to_string(),
+            ));
+        }
+
+        let fee_rate = self.get_fee_rate().await?;
+        let inputs_value: u64 = inputs.iter().map(|input| input.tx_out.value.to_sat()).sum();
+        let outputs_value: u64 = tx.output.iter().map(|output| output.value.to_sat()).sum();
+        let fee = inputs_value.saturating_sub(outputs_value);
+
+        if self.state.borrow().ledger().is_unspent(&rune_change_key) {
+            let replacement = self
+                .replace_transaction(tx, inputs, &change_outputs, fee, fee_rate)
+                .await?;
+            return Ok(FeeBump::Replacement(replacement));
+        }
+
+        self.create_child_transaction(tx, &change_outputs, fee, fee_rate, child_fee)
+            .await
+    }
+
+    /// Creates a transaction replacing the given one (BIP-125) with a higher fee.
+    ///
+    /// The additional fee is split between the BTC change outputs pro-rata to the number of
+    /// inputs and outputs their owners add to the transaction.
+    async fn replace_transaction(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+        change_outputs: &[(usize, DerivationPath)],
+        fee: u64,
+        fee_rate: FeeRate,
+    ) -> Result<Transaction, WithdrawError> {
+        let extra_fee = replacement_fee(fee, tx.vsize() as u64, fee_rate) - fee;
+        let weights: Vec<u64> = change_outputs
+            .iter()
+            .map(|(index, _)| {
+                let script = &tx.output[*index].script_pubkey;
+                inputs
+                    .iter()
+                    .filter(|input| &input.tx_out.script_pubkey == script)
+                    .count() as u64
+                    + 2
+            })
+            .collect();
+
+        let mut unsigned_tx = tx.clone();
+        for ((index, _), extra_fee_share) in
+            change_outputs.iter().zip(split_fee(extra_fee, &weights))
+        {
+            let change_output = &mut unsigned_tx.output[*index];
+            let change = change_output
+                .value
+                .to_sat()
+                .checked_sub(extra_fee_share)
+                .filter(|change| *change >= DUST_THRESHOLD)
+                .ok_or(WithdrawError::InsufficientFunds)?;
+            change_output.value = Amount::from_sat(change);
+        }
+
+        for input in unsigned_tx.input.iter_mut() {
+            input.witness = Witness::new();
+        }
+
+        let replacement = self.sign_transaction(&unsigned_tx, inputs).await?;
+
+        log::info!(
+            "Created transaction {} replacing {} with additional fee {extra_fee}",
+            replacement.txid(),
+            tx.txid()
+        );
+
+        Ok(replacement)
+    }
+
+    /// Creates a transaction spending the BTC change outputs of the given one back to their
+    /// owners, paying the fee missing for both transactions to reach the current fee rate.
+    async fn create_child_transaction(
+        &self,
+        tx: &Transaction,
+        change_outputs: &[(usize, DerivationPath)],
+        parent_fee: u64,
+        fee_rate: FeeRate,
+        replaced_child_fee: Option<u64>,
+    ) -> Result<FeeBump, WithdrawError> {
+        let txid = tx.txid();
+        let inputs: Vec<TxInputInfo> = change_outputs
+            .iter()
+            .map(|(index, derivation_path)| TxInputInfo {
+                outpoint: OutPoint {
+                    txid,
+                    vout: *index as u32,
+                },
+                tx_out: tx.output[*index].clone(),
+                derivation_path: derivation_path.clone(),
+            })
+            .collect();
+        let mut outputs: Vec<TxOut> = inputs.iter().map(|input| input.tx_out.clone()).collect();
+
+        let child_vsize = estimate_transaction_fees(
+            self.signer.address_type().script_type(),
+            inputs.len(),
+            FeeRate::BROADCAST_MIN,
+            &None,
+            outputs.clone(),
+        )
+        .to_sat();
+        let fee = child_fee(
+            parent_fee,
+            tx.vsize() as u64,
+            child_vsize,
+            fee_rate,
+            replaced_child_fee,
+        );
+
+        for (output, fee_share) in outputs
+            .iter_mut()
+            .zip(split_fee(fee, &vec![1; change_outputs.len()]))
+        {
+            let value = output
+                .value
+                .to_sat()
+                .checked_sub(fee_share)
+                .filter(|value| *value >= DUST_THRESHOLD)
+                .ok_or(WithdrawError::InsufficientFunds)?;
+            output.value = Amount::from_sat(value);
+        }
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs,
+        };
+        let child = self.sign_transaction(&unsigned_tx, &inputs).await?;
+
+        log::info!(
+            "Created transaction {} paying fee {fee} for the stuck transaction {txid}",
+            child.txid()
+        );
+
+        Ok(FeeBump::Child {
+            transaction: child,
+            inputs,
+            fee,
+        })
+    }
+
+    /// Sends the replacement transaction and moves the rune change in the ledger from the
+    /// replaced transaction to the new one.
+    pub async fn send_replacement_transaction(
+        &self,
+        replaced_txid: Txid,
+        tx: Transaction,
+    ) -> Result<(), WithdrawError> {
+        let replaced_change_key = UtxoKey::from(OutPoint {
+            txid: replaced_txid,
+            vout: RUNE_CHANGE_OUTPUT_INDEX as u32,
+        });
+        let rune_info = self
+            .state
+            .borrow()
+            .ledger()
+            .load_unspent_utxos()?
+            .remove(&replaced_change_key)
+            .map(|info| info.rune_info)
+            .unwrap_or_default();
+
+        self.send_transaction_with_change(tx, rune_info).await?;
+        self.state
+            .borrow_mut()
+            .ledger_mut()
+            .remove_spent_utxo(&replaced_change_key);
+
+        Ok(())
+    }
+
+    /// Returns the scripts of the BTC change outputs of the withdrawals of the sender with the
+    /// derivation path of their key.
+    ///
+    /// The transaction may be sent before the taproot addresses are enabled, so all the transit
+    /// addresses of the sender are returned.
+    pub async fn sender_change_owners(
+        &self,
+        sender: &H160,
+    ) -> Result<Vec<(ScriptBuf, DerivationPath)>, WithdrawError> {
+        let derivation_path = get_derivation_path(sender)?;
+        Ok(self
+            .signer
+            .get_transit_addresses(sender, self.network)
+            .await?
+            .iter()
+            .map(|address| (address.script_pubkey(), derivation_path.clone()))
+            .collect())
+    }
+
+    /// Sends the child transaction paying for a stuck one. Its inputs are marked as used, so
+    /// they are not spent by other withdrawals once the stuck transaction is confirmed.
+    pub async fn send_child_transaction(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+    ) -> Result<(), WithdrawError> {
+        self.utxo_provider.send_tx(tx).await?;
+
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for input in inputs {
+            let owner =
+                Address::from_script(&input.tx_out.script_pubkey, self.network).map_err(|err| {
+                    WithdrawError::InternalError(format!("invalid input script: {err}"))
+                })?;
+            ledger.mark_as_used(input.outpoint.into(), owner);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a single transaction for all the given withdrawals.
+    ///
+    /// Every withdrawal gets its own edict in the runestone and its own destination output. The
+    /// transaction fee is split between the withdrawals pro-rata to the number of inputs and
+    /// outputs each of them adds to the transaction. Withdrawals whose funding addresses don't
+    /// have enough BTC to pay their share are left out of the batch.
+    pub async fn create_batch_transaction(
+        &self,
+        payloads: &[RuneWithdrawalPayload],
+        input_provider: &impl RuneInputProvider,
+    ) -> Result<BatchTransaction, WithdrawError> {
+        let fee_rate = self.get_fee_rate().await?;
+        let rune_change_address = self.get_change_address().await?;
+
+        let (unspent_utxo_info, known_utxos) = {
+            let state = self.state.borrow();
+            let ledger = state.ledger();
+            let unspent = ledger.load_unspent_utxos()?;
+            let mut known_utxos: HashSet<UtxoKey> = unspent.keys().copied().collect();
+            known_utxos.extend(ledger.load_used_utxos().into_iter().map(|(key, _)| key));
+
+            (unspent, known_utxos)
+        };
+
+        // get utxos holding any of the withdrawn runes
+        let batch_runes: HashSet<RuneId> = payloads
+            .iter()
+            .map(|payload| payload.rune_info.id())
+            .collect();
+        let mut rune_change_info: Vec<RuneInfo> = vec![];
+        let mut rune_inputs = vec![];
+        for info in unspent_utxo_info.into_values() {
+            if !info
+                .rune_info
+                .iter()
+                .any(|rune| batch_runes.contains(&rune.id()))
+            {
+                continue;
+            }
+
+            for rune in info.rune_info {
+                if !rune_change_info.contains(&rune) {
+                    rune_change_info.push(rune);
+                }
+            }
+            rune_inputs.push(info.tx_input_info);
+        }
+
+        if rune_inputs.is_empty() {
+            return Err(WithdrawError::NoInputs);
+        }
+
+        let mut participants = Vec::with_capacity(payloads.len());
+        for (index, payload) in payloads.iter().enumerate() {
+            // destinations are validated when the withdrawals are added to the batch, so an
+            // invalid one here must not prevent the others from being sent
+            let dst_address = match parse_dst_address(&payload.dst_address) {
+                Ok(address) => address,
+                Err(err) => {
+                    log::warn!("Withdrawal {index} is left out of the batch: {err:?}");
+                    continue;
+                }
+            };
+
+            let funding_address = self.get_transit_address(&payload.sender).await?;
+            let mut funding_utxos: Vec<Utxo> = self
+                .utxo_provider
+                .get_utxos(&funding_address)
+                .await
+                .map_err(|_| WithdrawError::NoInputs)?
+                .utxos
+                .into_iter()
+                .filter(|utxo| !known_utxos.contains(&UtxoKey::from(&utxo.outpoint)))
+                .collect();
+            // sort the utxos by value; ascending, so the biggest one is popped first
+            funding_utxos.sort_by(|a, b| a.value.cmp(&b.value));
+
+            participants.push(BatchParticipant {
+                index,
+                rune: payload.rune_info.id(),
+                amount: payload.amount,
+                dst_address,
+                funding_address,
+                derivation_path: get_derivation_path(&payload.sender)?,
+                available_utxos: funding_utxos,
+                selected_utxos: vec![],
+            });
+        }
+
+        // all the rune utxos are already spent by the batch, so the withdrawals exceeding the
+        // rune balance of the inputs must wait for more runes to be deposited
+        let mut rune_balances = HashMap::new();
+        for input in &rune_inputs {
+            let utxo = Utxo {
+                outpoint: Outpoint {
+                    txid: input.outpoint.txid.as_byte_array().to_vec(),
+                    vout: input.outpoint.vout,
+                },
+                value: input.tx_out.value.to_sat(),
+                height: 0,
+            };
+            let amounts = input_provider
+                .get_rune_amounts(&utxo)
+                .await
+                .map_err(|err| {
+                    WithdrawError::InternalError(format!(
+                        "cannot get rune amounts of input {}: {err}",
+                        input.outpoint
+                    ))
+                })?;
+            for (rune_name, amount) in amounts {
+                *rune_balances.entry(rune_name).or_default() += amount;
+            }
+        }
+
+        participants.retain(|participant| {
+            let rune_name = payloads[participant.index].rune_info.name;
+            let covered = take_rune_amount(&mut rune_balances, rune_name, participant.amount);
+            if !covered {
+                log::warn!(
+                    "Withdrawal to {} is left out of the batch: not enough runes {rune_name} in the inputs",
+                    participant.dst_address
+                );
+            }
+
+            covered
+        });
+
+        let rune_inputs_value: u64 = rune_inputs
+            .iter()
+            .map(|input| input.tx_out.value.to_sat())
+            .sum();
+        let rune_change_value = rune_inputs_value.max(POSTAGE);
+        // if the rune inputs don't hold enough BTC for the rune change output, participants pay
+        // for it together with the fee
+        let shared_cost = rune_change_value - rune_inputs_value;
+
+        let mut taken_utxos = HashSet::new();
+        let fee_shares = loop {
+            participants.retain_mut(|participant| {
+                !participant.selected_utxos.is_empty()
+                    || participant.select_next_utxo(&mut taken_utxos)
+            });
+
+            if participants.is_empty() {
+                return Err(WithdrawError::InsufficientFunds);
+            }
+
+            let outputs = batch_outputs(
+                &participants,
+                &rune_change_address,
+                rune_change_value,
+                &vec![0; participants.len()],
+            );
+            let inputs_count = rune_inputs.len()
+                + participants
+                    .iter()
+                    .map(|participant| participant.selected_utxos.len())
+                    .sum::<usize>();
+            let fee = estimate_transaction_fees(
+                self.signer.address_type().script_type(),
+                inputs_count,
+                fee_rate,
+                &None,
+                outputs,
+            )
+            .to_sat();
+
+            let weights: Vec<u64> = participants.iter().map(BatchParticipant::weight).collect();
+            let fee_shares = split_fee(fee + shared_cost, &weights);
+
+            let mut changed = false;
+            let mut excluded = vec![];
+            for (participant, fee_share) in participants.iter_mut().zip(&fee_shares) {
+                if participant.funding_value() >= fee_share + POSTAGE {
+                    continue;
+                }
+
+                changed = true;
+                if !participant.select_next_utxo(&mut taken_utxos) {
+                    log::debug!(
+                        "Withdrawal to {} is left out of the batch: not enough funds to pay the fee share {fee_share}",
+                        participant.dst_address
+                    );
+                    excluded.push(participant.index);
+                }
+            }
+
+            participants.retain(|participant| {
+                if excluded.contains(&participant.index) {
+                    // release the utxos so they can be used by other withdrawals of the same user
+                    for utxo in &participant.selected_utxos {
+                        taken_utxos.remove(&UtxoKey::from(&utxo.outpoint));
+                    }
+                    false
+                } else {
+                    true
+                }
+            });
+
+            if !changed {
+                break fee_shares;
+            }
+        };
+
+        let mut inputs = rune_inputs;
+        for participant in &participants {
+            for utxo in &participant.selected_utxos {
+                inputs.push(TxInputInfo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&utxo.outpoint.txid).map_err(|err| {
+                            WithdrawError::InternalError(format!(
+                                "invalid txid of funding utxo: {err}"
+                            ))
+                        })?,
+                        vout: utxo.outpoint.vout,
+                    },
+                    tx_out: TxOut {
+                        value: Amount::from_sat(utxo.value),
+                        script_pubkey: participant.funding_address.script_pubkey(),
+                    },
+                    derivation_path: participant.derivation_path.clone(),
+                });
+            }
+        }
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: batch_outputs(
+                &participants,
+                &rune_change_address,
+                rune_change_value,
+                &fee_shares,
+            ),
+        };
+
+        let transaction = self.sign_transaction(&unsigned_tx, &inputs).await?;
+
+        log::info!(
+            "Created batch withdrawal transaction {} for {} withdrawals",
+            transaction.txid(),
+            participants.len()
+        );
+
+        Ok(BatchTransaction {
+            transaction,
+            fee_shares: participants
+                .iter()
+                .map(|participant| participant.index)
+                .zip(fee_shares)
+                .collect(),
+            inputs,
+            rune_change_info,
+        })
+    }
+
+    /// Sends the batch transaction and updates the ledger with its inputs and the rune change.
+    pub async fn send_batch_transaction(
+        &self,
+        batch: &BatchTransaction,
+    ) -> Result<(), WithdrawError> {
+        let tx = &batch.transaction;
+        self.utxo_provider.send_tx(tx).await?;
+        let change_address = self.get_change_address().await?;
+
+        let change_utxo = Utxo {
+            outpoint: Outpoint {
+                txid: tx.txid().as_byte_array().to_vec(),
+                vout: RUNE_CHANGE_OUTPUT_INDEX as u32,
+            },
+            value: tx.output[RUNE_CHANGE_OUTPUT_INDEX].value.to_sat(),
+            height: 0,
+        };
+
+        let mut state = self.state.borrow_mut();
+        let ledger = state.ledger_mut();
+        for input in &batch.inputs {
+            ledger.mark_as_used(input.outpoint.into(), change_address.clone());
+        }
+        ledger.deposit(
+            change_utxo,
+            &change_address,
+            self.get_change_derivation_path(),
+            batch.rune_change_info.clone(),
         );
 
         Ok(())
     }
 
+    /// Signs all the inputs of the transaction with the keys of the canister.
+    pub async fn sign_transaction(
+        &self,
+        unsigned_tx: &Transaction,
+        inputs: &[TxInputInfo],
+    ) -> Result<Transaction, WithdrawError> {
+        key::sign_transaction(&self.signer, unsigned_tx, inputs)
+            .await
+            .map_err(|err| {
+                log::error!("Failed to sign withdraw transaction: {err:?}");
+                WithdrawError::TransactionSigning
+            })
+    }
+
     async fn get_transit_address(&self, eth_address: &H160) -> Result<Address, WithdrawError> {
         self.signer
             .get_transit_address(eth_address, self.network)
@@ -266,8 +1039,8 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             .map_err(WithdrawError::from)
     }
 
-    /// Build a withdrawal transaction.
-    async fn build_withdraw_transaction(
+    /// Build an unsigned withdrawal transaction.
+    fn build_withdraw_transaction(
         &self,
         args: WithdrawalTransactionArgs,
     ) -> Result<Transaction, WithdrawError> {
@@ -303,19 +1076,16 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
             rune_change_address: args.rune_change_address,
             fee_rate: args.fee_rate,
         };
-        let unsigned_tx = builder.create_edict_transaction(&args).map_err(|err| {
+        let mut unsigned_tx = builder.create_edict_transaction(&args).map_err(|err| {
             log::warn!("Failed to create withdraw transaction: {err:?}");
             WithdrawError::TransactionCreation
         })?;
-        let signed_tx = builder
-            .sign_transaction(&unsigned_tx, &args.inputs)
-            .await
-            .map_err(|err| {
-                log::error!("Failed to sign withdraw transaction: {err:?}");
-                WithdrawError::TransactionSigning
-            })?;
+        // signal replaceability, so the transaction fee can be bumped if it gets stuck
+        for input in unsigned_tx.input.iter_mut() {
+            input.sequence = Sequence::ENABLE_RBF_NO_LOCKTIME;
+        }
 
-        Ok(signed_tx)
+        Ok(unsigned_tx)
     }
 
     async fn get_change_address(&self) -> Result<Address, WithdrawError> {
@@ -351,14 +1121,9 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
     ///
     /// Returns the utxos that can fund the transaction with the minimum number of utxos.
     /// If the transaction cannot be funded with the minimum number of utxos, the function will return None.
-    fn get_greedy_funding_utxos(&self, mut args: GetGreedyFundingUtxosArgs) -> Option<Vec<Utxo>> {
-        let mut utxos_count = 1;
-        // sort the utxos by value; descending
-        args.funding_utxos.sort_by(|a, b| b.value.cmp(&a.value));
-
-        // try to fund the transaction with the minimum number of utxos
-        while utxos_count <= args.funding_utxos.len() {
-            let required_fee = estimate_edict_transaction_fees(EstimateEdictTxFeesArgs {
+    fn get_greedy_funding_utxos(&self, args: GetGreedyFundingUtxosArgs) -> Option<Vec<Utxo>> {
+        let solution = select_funding_utxos(args.funding_utxos, |utxos_count| {
+            estimate_edict_transaction_fees(EstimateEdictTxFeesArgs {
                 script_type: ScriptType::P2WSH,
                 number_of_inputs: utxos_count + args.rune_utxos_count,
                 current_fee_rate: args.fee_rate,
@@ -368,24 +1133,12 @@ impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
                 change_address: args.change_address.clone(),
                 rune: args.rune,
                 rune_amount: args.rune_amount,
-            });
-
-            // find the minimum number of utxos that can fund the transaction
-            let solution = &args.funding_utxos[0..utxos_count];
-            let solution_value =
-                Amount::from_sat(solution.iter().map(|utxo| utxo.value).sum::<u64>());
-            if solution_value >= required_fee {
-                log::debug!(
-                    "Found a funding solution with {utxos_count} utxos; required fee {required_fee}; fee funds: {solution_value}"
-                );
-                return Some(solution.to_vec());
-            }
-
-            // otherwise try with more utxos
-            utxos_count += 1;
-        }
+            })
+            .to_sat()
+        })?;
 
-        None
+        log::debug!("Found a funding solution with {} utxos", solution.len());
+        Some(solution)
     }
 }
 
@@ -401,6 +1154,261 @@ struct GetGreedyFundingUtxosArgs {
     fee_rate: FeeRate,
 }
 
+/// Status of a sent withdrawal transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// The transaction is not included into a block yet.
+    Pending,
+    /// One of the versions of the transaction is included into a block. Contains the change
+    /// utxo of the confirmed version.
+    Confirmed(Utxo),
+    /// The inputs of the transaction are spent, but the confirmed version is unknown because its
+    /// rune change is already spent.
+    InputsSpent,
+}
+
+/// Transaction bumping the fee of a stuck withdrawal transaction.
+#[derive(Debug, Clone)]
+pub enum FeeBump {
+    /// The transaction replacing the stuck one.
+    Replacement(Transaction),
+    /// The transaction spending the BTC change of the stuck one, paying for both of them.
+    Child {
+        transaction: Transaction,
+        inputs: Vec<TxInputInfo>,
+        fee: u64,
+    },
+}
+
+/// Parses the recipient address of the withdrawal.
+pub fn parse_dst_address(dst_address: &str) -> Result<Address, WithdrawError> {
+    Address::from_str(dst_address)
+        .map(|address| address.assume_checked())
+        .map_err(|_| {
+            WithdrawError::InvalidRequest(format!(
+                "Failed to decode recipient address from string: {dst_address}"
+            ))
+        })
+}
+
+/// Fee of a child transaction making the stuck parent transaction and itself pay the current fee
+/// rate together (CPFP).
+///
+/// A child replacing the one sent before must pay more than it plus the relay fee for its own
+/// size.
+fn child_fee(
+    parent_fee: u64,
+    parent_vsize: u64,
+    child_vsize: u64,
+    fee_rate: FeeRate,
+    replaced_child_fee: Option<u64>,
+) -> u64 {
+    let package_fee = fee_rate.to_sat_per_vb_ceil() * (parent_vsize + child_vsize);
+    let fee = package_fee
+        .saturating_sub(parent_fee)
+        .max(child_vsize * INCREMENTAL_RELAY_FEE_RATE);
+
+    match replaced_child_fee {
+        Some(replaced_fee) => fee.max(replaced_fee + child_vsize * INCREMENTAL_RELAY_FEE_RATE),
+        None => fee,
+    }
+}
+
+/// Returns the scripts of the inputs with the derivation paths of their keys. The BTC change of
+/// a batch transaction goes back to the addresses funding it.
+pub fn input_owners(inputs: &[TxInputInfo]) -> Vec<(ScriptBuf, DerivationPath)> {
+    let mut owners: Vec<(ScriptBuf, DerivationPath)> = vec![];
+    for input in inputs {
+        if !owners
+            .iter()
+            .any(|(script, _)| *script == input.tx_out.script_pubkey)
+        {
+            owners.push((
+                input.tx_out.script_pubkey.clone(),
+                input.derivation_path.clone(),
+            ));
+        }
+    }
+
+    owners
+}
+
+/// Finds the BTC change outputs of the transaction following its destination outputs. Returns
+/// their indices with the derivation paths of their owners.
+fn change_outputs(
+    tx: &Transaction,
+    destinations: usize,
+    change_owners: &[(ScriptBuf, DerivationPath)],
+) -> Vec<(usize, DerivationPath)> {
+    tx.output
+        .iter()
+        .enumerate()
+        .skip(FIRST_DESTINATION_OUTPUT_INDEX + destinations)
+        .filter_map(|(index, output)| {
+            change_owners
+                .iter()
+                .find(|(script, _)| *script == output.script_pubkey)
+                .map(|(_, derivation_path)| (index, derivation_path.clone()))
+        })
+        .collect()
+}
+
+/// Converts the transaction input into the form stored in the withdrawal operation.
+pub fn did_tx_input(input: &TxInputInfo) -> DidTxInput {
+    DidTxInput {
+        txid: input.outpoint.txid.to_byte_array(),
+        vout: input.outpoint.vout,
+        value: input.tx_out.value.to_sat(),
+        script_pubkey: input.tx_out.script_pubkey.to_bytes(),
+        derivation_path: derivation_path_to_ic(input.derivation_path.clone()),
+    }
+}
+
+/// Restores the transaction input stored in the withdrawal operation.
+pub fn tx_input_info(input: &DidTxInput) -> Result<TxInputInfo, WithdrawError> {
+    Ok(TxInputInfo {
+        outpoint: OutPoint {
+            txid: Txid::from_byte_array(input.txid),
+            vout: input.vout,
+        },
+        tx_out: TxOut {
+            value: Amount::from_sat(input.value),
+            script_pubkey: ScriptBuf::from_bytes(input.script_pubkey.clone()),
+        },
+        derivation_path: ic_dp_to_derivation_path(&input.derivation_path)?,
+    })
+}
+
+/// Withdrawal transaction shared by several withdrawals.
+pub struct BatchTransaction {
+    pub transaction: Transaction,
+    /// Indices of the withdrawals included into the transaction with the part of the fee each
+    /// of them pays.
+    pub fee_shares: Vec<(usize, u64)>,
+    pub inputs: Vec<TxInputInfo>,
+    rune_change_info: Vec<RuneInfo>,
+}
+
+/// Withdrawal included into a batch transaction.
+struct BatchParticipant {
+    /// Index of the withdrawal in the batch.
+    index: usize,
+    rune: RuneId,
+    amount: u128,
+    dst_address: Address,
+    funding_address: Address,
+    derivation_path: DerivationPath,
+    /// Funding utxos not selected yet, sorted by value; ascending.
+    available_utxos: Vec<Utxo>,
+    selected_utxos: Vec<Utxo>,
+}
+
+impl BatchParticipant {
+    /// Selects the biggest available funding utxo not taken by another participant.
+    fn select_next_utxo(&mut self, taken_utxos: &mut HashSet<UtxoKey>) -> bool {
+        while let Some(utxo) = self.available_utxos.pop() {
+            if taken_utxos.insert(UtxoKey::from(&utxo.outpoint)) {
+                self.selected_utxos.push(utxo);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn funding_value(&self) -> u64 {
+        self.selected_utxos.iter().map(|utxo| utxo.value).sum()
+    }
+
+    /// Number of inputs and outputs the participant adds to the transaction: funding inputs, the
+    /// destination output and the change output.
+    fn weight(&self) -> u64 {
+        self.selected_utxos.len() as u64 + 2
+    }
+}
+
+/// Takes the amount from the balance of the rune. Returns `false` and keeps the balance if it's
+/// not enough.
+fn take_rune_amount(balances: &mut HashMap<RuneName, u128>, rune: RuneName, amount: u128) -> bool {
+    match balances.get_mut(&rune) {
+        Some(balance) if *balance >= amount => {
+            *balance -= amount;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Output holding the runestone.
+const RUNESTONE_OUTPUT_INDEX: usize = 0;
+/// Output receiving the runes not allocated by the edicts.
+const RUNE_CHANGE_OUTPUT_INDEX: usize = 1;
+/// Output of the first withdrawal in the batch. Others follow it in the batch order.
+const FIRST_DESTINATION_OUTPUT_INDEX: usize = 2;
+
+/// Outputs of the batch transaction: the runestone, the rune change, a destination output for
+/// every participant and then the BTC change outputs of the participants.
+fn batch_outputs(
+    participants: &[BatchParticipant],
+    rune_change_address: &Address,
+    rune_change_value: u64,
+    fee_shares: &[u64],
+) -> Vec<TxOut> {
+    let runestone = Runestone {
+        edicts: participants
+            .iter()
+            .enumerate()
+            .map(|(idx, participant)| Edict {
+                id: participant.rune,
+                amount: participant.amount,
+                output: (FIRST_DESTINATION_OUTPUT_INDEX + idx) as u32,
+            })
+            .collect(),
+        pointer: Some(RUNE_CHANGE_OUTPUT_INDEX as u32),
+        ..Default::default()
+    };
+
+    let mut outputs = Vec::with_capacity(FIRST_DESTINATION_OUTPUT_INDEX + participants.len() * 2);
+    outputs.insert(
+        RUNESTONE_OUTPUT_INDEX,
+        TxOut {
+            value: Amount::ZERO,
+            script_pubkey: runestone.encipher(),
+        },
+    );
+    outputs.insert(
+        RUNE_CHANGE_OUTPUT_INDEX,
+        TxOut {
+            value: Amount::from_sat(rune_change_value),
+            script_pubkey: rune_change_address.script_pubkey(),
+        },
+    );
+
+    for participant in participants {
+        outputs.push(TxOut {
+            value: Amount::from_sat(POSTAGE),
+            script_pubkey: participant.dst_address.script_pubkey(),
+        });
+    }
+
+    for (participant, fee_share) in participants.iter().zip(fee_shares) {
+        let change = participant
+            .funding_value()
+            .saturating_sub(POSTAGE + fee_share);
+        // zero change is used during fee estimation
+        if change != 0 && change < DUST_THRESHOLD {
+            continue;
+        }
+
+        outputs.push(TxOut {
+            value: Amount::from_sat(change),
+            script_pubkey: participant.funding_address.script_pubkey(),
+        });
+    }
+
+    outputs
+}
+
 /// Arguments for the `build_withdraw_transaction` function.
 struct WithdrawalTransactionArgs {
     runes: Vec<(RuneId, u128)>,
@@ -414,14 +1422,13 @@ struct WithdrawalTransactionArgs {
 #[cfg(test)]
 mod test {
     use bitcoin::{Address, FeeRate, PrivateKey, Transaction};
+    use bitcoin_bridge_core::key::{AddressType, LocalBtcSigner};
+    use bitcoin_bridge_core::utxo_provider::{UtxoProvider, UtxoProviderError};
+    use bridge_did::runes::{EvmNativeToken, EvmNativeTokenStatus};
     use ic_exports::ic_cdk::api::management_canister::bitcoin::GetUtxosResponse;
     use ic_exports::ic_kit::MockContext;
-    use ord_rs::wallet::LocalSigner;
 
     use super::*;
-    use crate::core::rune_inputs::GetInputsError;
-    use crate::core::utxo_provider::UtxoProvider;
-    use crate::interface::WithdrawError;
     use crate::key::BtcSignerType;
     use crate::state::RuneState;
 
@@ -450,15 +1457,18 @@ mod test {
     }
 
     impl UtxoProvider for FakeUtxoProvider {
-        async fn get_utxos(&self, _address: &Address) -> Result<GetUtxosResponse, GetInputsError> {
+        async fn get_utxos(
+            &self,
+            _address: &Address,
+        ) -> Result<GetUtxosResponse, UtxoProviderError> {
             unimplemented!()
         }
 
-        async fn get_fee_rate(&self) -> Result<FeeRate, WithdrawError> {
+        async fn get_fee_rate(&self) -> Result<FeeRate, UtxoProviderError> {
             Ok(self.fee_rate)
         }
 
-        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), WithdrawError> {
+        async fn send_tx(&self, _transaction: &Transaction) -> Result<(), UtxoProviderError> {
             unimplemented!()
         }
     }
@@ -607,6 +1617,131 @@ mod test {
         assert!(greedy_funding_utxos.is_none());
     }
 
+    #[test]
+    fn test_should_take_rune_amount_within_balance() {
+        let rune = RuneName::from_str("A").unwrap();
+        let other_rune = RuneName::from_str("B").unwrap();
+        let mut balances = HashMap::from([(rune, 100)]);
+
+        assert!(take_rune_amount(&mut balances, rune, 60));
+        assert!(!take_rune_amount(&mut balances, rune, 50));
+        assert!(!take_rune_amount(&mut balances, other_rune, 1));
+        assert!(take_rune_amount(&mut balances, rune, 40));
+        assert_eq!(balances[&rune], 0);
+    }
+
+    #[test]
+    fn test_child_fee_should_cover_parent_fee_rate() {
+        let fee_rate = FeeRate::from_sat_per_vb(2).unwrap();
+        // the package of the parent (200 vB) and the child (100 vB) pays 2 sat/vB
+        assert_eq!(child_fee(100, 200, 100, fee_rate, None), 500);
+        // the parent pays enough, but the child must pay the relay fee for itself
+        assert_eq!(child_fee(1000, 200, 100, fee_rate, None), 100);
+        // the child replacing the previous one must pay more
+        assert_eq!(child_fee(100, 200, 100, fee_rate, Some(500)), 600);
+    }
+
+    #[test]
+    fn test_should_find_change_outputs_after_destinations() {
+        let sender_script = ScriptBuf::from_bytes(vec![1; 22]);
+        let other_script = ScriptBuf::from_bytes(vec![2; 22]);
+        let derivation_path = get_derivation_path(&H160::from_slice(&[42; 20])).unwrap();
+        let output = |script: &ScriptBuf| TxOut {
+            value: Amount::from_sat(1_000),
+            script_pubkey: script.clone(),
+        };
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                output(&other_script),
+                output(&other_script),
+                // the destination is not a change output, even if it is sent to the sender
+                output(&sender_script),
+                output(&other_script),
+                output(&sender_script),
+            ],
+        };
+
+        let change_outputs = change_outputs(&tx, 1, &[(sender_script, derivation_path.clone())]);
+
+        assert_eq!(change_outputs, vec![(4, derivation_path)]);
+    }
+
+    #[test]
+    fn test_should_convert_tx_input() {
+        let input = TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[7; 32]).unwrap(),
+                vout: 3,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::from_bytes(vec![0, 20, 1, 2, 3]),
+            },
+            derivation_path: get_derivation_path(&H160::from_slice(&[42; 20])).unwrap(),
+        };
+
+        let did_input = did_tx_input(&input);
+        assert_eq!(did_input.txid, [7; 32]);
+        assert_eq!(did_input.value, 10_000);
+
+        let restored = tx_input_info(&did_input).unwrap();
+        assert_eq!(restored.outpoint, input.outpoint);
+        assert_eq!(restored.tx_out, input.tx_out);
+        assert_eq!(restored.derivation_path, input.derivation_path);
+    }
+
+    #[test]
+    fn test_should_check_evm_native_token_of_withdrawal() {
+        MockContext::new().inject();
+
+        let rune_info = RuneInfo {
+            name: RuneName::from(ordinals::Rune(0xdeadbeef)),
+            decimals: 18,
+            block: 840_000,
+            tx: 3,
+        };
+        let erc20 = H160::from_slice(&[1; 20]);
+        let mut state = RuneState::default();
+        state.add_runes([rune_info]);
+        state.set_evm_native_token(EvmNativeToken {
+            erc20: erc20.clone(),
+            divisibility: 18,
+            premine: 1_000_000,
+            status: EvmNativeTokenStatus::Etched(rune_info),
+        });
+
+        let event = |from_erc20: &H160, decimals| BurntEventData {
+            amount: 100u64.into(),
+            from_erc20: from_erc20.clone(),
+            recipient_id: b"bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks".to_vec(),
+            to_token: Id256::from(rune_info.id()).0.to_vec(),
+            decimals,
+            ..Default::default()
+        };
+
+        let payload = RuneWithdrawalPayloadImpl::new(event(&erc20, 18), &state).unwrap();
+        assert_eq!(payload.0.rune_info, rune_info);
+        assert_eq!(payload.0.amount, 100);
+
+        let other_erc20 = H160::from_slice(&[2; 20]);
+        assert!(RuneWithdrawalPayloadImpl::new(event(&other_erc20, 18), &state).is_err());
+        assert!(RuneWithdrawalPayloadImpl::new(event(&erc20, 8), &state).is_err());
+
+        let other_rune = RuneInfo {
+            block: 840_001,
+            ..rune_info
+        };
+        state.add_runes([other_rune]);
+        let other_rune_event = BurntEventData {
+            to_token: Id256::from(other_rune.id()).0.to_vec(),
+            ..event(&erc20, 18)
+        };
+        assert!(RuneWithdrawalPayloadImpl::new(other_rune_event, &state).is_err());
+    }
+
     fn test_withdrawal() -> Withdrawal<FakeUtxoProvider> {
         let state = RuneState::default();
         let fake_utxo_provider = FakeUtxoProvider {
@@ -615,9 +1750,10 @@ mod test {
         Withdrawal {
             state: Rc::new(RefCell::new(state)),
             utxo_provider: fake_utxo_provider,
-            signer: BtcSignerType::Local(LocalSigner::new(PrivateKey::generate(
-                bitcoin::Network::Regtest,
-            ))),
+            signer: BtcSignerType::Local(LocalBtcSigner::new(
+                PrivateKey::generate(bitcoin::Network::Regtest),
+                AddressType::P2wpkh,
+            )),
             network: bitcoin::Network::Regtest,
         }
     }
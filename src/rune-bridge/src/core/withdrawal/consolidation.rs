@@ -0,0 +1,404 @@
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash as _;
+use bitcoin::transaction::Version;
+use bitcoin::{Amount, FeeRate, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use bitcoin_bridge_core::key::AddressType;
+use bitcoin_bridge_core::utxo_provider::UtxoProvider;
+use bridge_did::init::UtxoConsolidationConfig;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
+use ord_rs::fees::estimate_transaction_fees;
+use ord_rs::wallet::TxInputInfo;
+
+use super::{TransactionStatus, Withdrawal};
+use crate::constants::{DUST_THRESHOLD, INPUT_VSIZE};
+use crate::interface::WithdrawError;
+use crate::ledger::{UtxoGroup, UtxoKey};
+
+/// Index of the output holding the consolidated value and runes.
+///
+/// The consolidation transaction has no runestone, so all the runes of its inputs are
+/// transferred to the first output.
+const CONSOLIDATED_OUTPUT_INDEX: usize = 0;
+
+/// Transaction merging the utxos of the bridge into a single utxo.
+pub struct UtxoMerge {
+    pub transaction: Transaction,
+    pub inputs: Vec<TxInputInfo>,
+}
+
+impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    /// Merges the utxos of the ledger holding the same set of runes into a single utxo.
+    ///
+    /// Returns the sent transaction, or `None` if the current fee rate is higher than configured
+    /// or there are not enough utxos to consolidate.
+    pub async fn consolidate_utxos(
+        &self,
+        config: &UtxoConsolidationConfig,
+    ) -> Result<Option<UtxoMerge>, WithdrawError> {
+        let fee_rate = self.get_fee_rate().await?;
+        if fee_rate.to_sat_per_vb_ceil() > config.max_fee_rate {
+            log::trace!(
+                "Utxo consolidation is postponed: fee rate {fee_rate} is higher than {} sat/vB",
+                config.max_fee_rate
+            );
+            return Ok(None);
+        }
+
+        let groups = self.state.borrow().ledger().load_unspent_utxo_groups()?;
+        let Some(group) = select_consolidation_group(groups, config, dust_limit(fee_rate)) else {
+            log::trace!("No utxos to consolidate.");
+            return Ok(None);
+        };
+
+        self.merge_utxo_group(group, fee_rate).await
+    }
+
+    /// Moves the utxos held at the legacy P2WPKH addresses to the taproot change address.
+    ///
+    /// One group of utxos holding the same set of runes is moved at a time, up to `max_inputs`
+    /// utxos. Returns the sent transaction, or `None` if there is nothing to migrate.
+    pub async fn migrate_utxos_to_taproot(
+        &self,
+        max_inputs: u32,
+    ) -> Result<Option<UtxoMerge>, WithdrawError> {
+        if self.signer.address_type() != AddressType::P2tr {
+            return Err(WithdrawError::InvalidRequest(
+                "taproot addresses are not enabled".to_string(),
+            ));
+        }
+
+        let fee_rate = self.get_fee_rate().await?;
+        let groups = self.state.borrow().ledger().load_unspent_utxo_groups()?;
+        let Some(group) = select_migration_group(groups, max_inputs, dust_limit(fee_rate)) else {
+            log::trace!("No utxos to migrate.");
+            return Ok(None);
+        };
+
+        self.merge_utxo_group(group, fee_rate).await
+    }
+
+    /// Checks whether the utxo merge transaction has been included into a block.
+    pub async fn utxo_merge_status(
+        &self,
+        tx: &Transaction,
+        inputs: &[TxInputInfo],
+    ) -> Result<TransactionStatus, WithdrawError> {
+        self.change_output_status(tx, inputs, &[], CONSOLIDATED_OUTPUT_INDEX)
+            .await
+    }
+
+    /// Sends the utxos of the group to a single output at the change address.
+    async fn merge_utxo_group(
+        &self,
+        group: UtxoGroup,
+        fee_rate: FeeRate,
+    ) -> Result<Option<UtxoMerge>, WithdrawError> {
+        let change_address = self.get_change_address().await?;
+        let fee = estimate_transaction_fees(
+            self.signer.address_type().script_type(),
+            group.utxos.len(),
+            fee_rate,
+            &None,
+            vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: change_address.script_pubkey(),
+            }],
+        )
+        .to_sat();
+
+        let output_value = group.total_value().saturating_sub(fee);
+        if output_value < DUST_THRESHOLD {
+            log::debug!(
+                "Value of {} utxos doesn't cover the transaction fee {fee}",
+                group.utxos.len()
+            );
+            return Ok(None);
+        }
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: group
+                .utxos
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(output_value),
+                script_pubkey: change_address.script_pubkey(),
+            }],
+        };
+
+        let tx = self.sign_transaction(&unsigned_tx, &group.utxos).await?;
+
+        // The utxos could be taken by a withdrawal while the transaction was being signed.
+        let inputs_unspent = {
+            let state = self.state.borrow();
+            group
+                .utxos
+                .iter()
+                .all(|input| state.ledger().is_unspent(&UtxoKey::from(input.outpoint)))
+        };
+        if !inputs_unspent {
+            log::debug!("Utxo merging is cancelled: some of the inputs are already used.");
+            return Ok(None);
+        }
+
+        self.utxo_provider.send_tx(&tx).await?;
+
+        let txid = tx.txid();
+        let merged_utxo = Utxo {
+            outpoint: Outpoint {
+                txid: txid.as_byte_array().to_vec(),
+                vout: CONSOLIDATED_OUTPUT_INDEX as u32,
+            },
+            value: output_value,
+            height: 0,
+        };
+
+        {
+            let mut state = self.state.borrow_mut();
+            let ledger = state.ledger_mut();
+            for input in &group.utxos {
+                ledger.mark_as_used(input.outpoint.into(), change_address.clone());
+            }
+            ledger.deposit(
+                merged_utxo,
+                &change_address,
+                self.get_change_derivation_path(),
+                group.rune_info.clone(),
+            );
+        }
+
+        log::info!(
+            "Merged {} utxos with runes {:?} to {change_address} in transaction {txid} paying fee {fee}",
+            group.utxos.len(),
+            group.rune_info
+        );
+
+        Ok(Some(UtxoMerge {
+            transaction: tx,
+            inputs: group.utxos,
+        }))
+    }
+}
+
+/// Minimum value of a utxo without runes to be worth spending at the given fee rate.
+pub fn dust_limit(fee_rate: FeeRate) -> u64 {
+    fee_rate.to_sat_per_vb_ceil() * INPUT_VSIZE
+}
+
+/// Selects the group with the most utxos to consolidate.
+///
+/// Dust utxos without runes are left untouched, since spending them costs more than they are
+/// worth. The smallest utxos of the group are consolidated first.
+fn select_consolidation_group(
+    groups: Vec<UtxoGroup>,
+    config: &UtxoConsolidationConfig,
+    dust_limit: u64,
+) -> Option<UtxoGroup> {
+    let min_utxos = config.min_utxos.max(2) as usize;
+    let max_inputs = config.max_inputs.max(2) as usize;
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            if group.rune_info.is_empty() {
+                group
+                    .utxos
+                    .retain(|utxo| utxo.tx_out.value.to_sat() > dust_limit);
+            }
+            group
+        })
+        .filter(|group| group.utxos.len() >= min_utxos)
+        .max_by_key(|group| group.utxos.len())
+        .map(|mut group| {
+            group.utxos.sort_by_key(|utxo| utxo.tx_out.value);
+            group.utxos.truncate(max_inputs);
+            group
+        })
+}
+
+/// Selects the group with the most utxos held at the legacy (non-taproot) addresses.
+///
+/// Dust utxos without runes are left untouched, the same way as by the consolidation.
+fn select_migration_group(
+    groups: Vec<UtxoGroup>,
+    max_inputs: u32,
+    dust_limit: u64,
+) -> Option<UtxoGroup> {
+    let max_inputs = max_inputs.max(1) as usize;
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            let no_runes = group.rune_info.is_empty();
+            group.utxos.retain(|utxo| {
+                !utxo.tx_out.script_pubkey.is_p2tr()
+                    && (!no_runes || utxo.tx_out.value.to_sat() > dust_limit)
+            });
+            group
+        })
+        .filter(|group| !group.utxos.is_empty())
+        .max_by_key(|group| group.utxos.len())
+        .map(|mut group| {
+            group.utxos.sort_by_key(|utxo| utxo.tx_out.value);
+            group.utxos.truncate(max_inputs);
+            group
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::bip32::DerivationPath;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, Txid};
+    use bridge_did::runes::{RuneInfo, RuneName};
+    use ord_rs::wallet::TxInputInfo;
+    use ordinals::Rune;
+
+    use super::*;
+
+    fn input(id: u8, value: u64) -> TxInputInfo {
+        TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[id; 32]).unwrap(),
+                vout: 0,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::new(),
+            },
+            derivation_path: DerivationPath::default(),
+        }
+    }
+
+    fn rune_info() -> RuneInfo {
+        RuneInfo {
+            name: RuneName::from(Rune(0xdeadbeef)),
+            decimals: 0,
+            block: 100,
+            tx: 1,
+        }
+    }
+
+    fn config(min_utxos: u32, max_inputs: u32) -> UtxoConsolidationConfig {
+        UtxoConsolidationConfig {
+            min_utxos,
+            max_inputs,
+            max_fee_rate: 10,
+        }
+    }
+
+    #[test]
+    fn test_should_select_biggest_group() {
+        let groups = vec![
+            UtxoGroup {
+                rune_info: vec![],
+                utxos: vec![input(1, 1000), input(2, 2000)],
+            },
+            UtxoGroup {
+                rune_info: vec![rune_info()],
+                utxos: vec![input(3, 546), input(4, 546), input(5, 546)],
+            },
+        ];
+
+        let group = select_consolidation_group(groups, &config(2, 10), 100).unwrap();
+        assert_eq!(group.rune_info, vec![rune_info()]);
+        assert_eq!(group.utxos.len(), 3);
+    }
+
+    #[test]
+    fn test_should_skip_dust_utxos_without_runes() {
+        let groups = vec![
+            UtxoGroup {
+                rune_info: vec![],
+                utxos: vec![input(1, 50), input(2, 60), input(3, 2000)],
+            },
+            UtxoGroup {
+                rune_info: vec![rune_info()],
+                utxos: vec![input(4, 50), input(5, 50)],
+            },
+        ];
+
+        let group = select_consolidation_group(groups.clone(), &config(2, 10), 100).unwrap();
+        assert_eq!(group.rune_info, vec![rune_info()]);
+
+        assert!(select_consolidation_group(groups, &config(3, 10), 100).is_none());
+    }
+
+    #[test]
+    fn test_should_consolidate_smallest_utxos_first() {
+        let groups = vec![UtxoGroup {
+            rune_info: vec![],
+            utxos: vec![
+                input(1, 5000),
+                input(2, 1000),
+                input(3, 3000),
+                input(4, 2000),
+            ],
+        }];
+
+        let group = select_consolidation_group(groups, &config(2, 3), 100).unwrap();
+        let values: Vec<u64> = group
+            .utxos
+            .iter()
+            .map(|utxo| utxo.tx_out.value.to_sat())
+            .collect();
+        assert_eq!(values, vec![1000, 2000, 3000]);
+    }
+
+    #[test]
+    fn test_dust_limit_should_grow_with_fee_rate() {
+        assert_eq!(
+            dust_limit(FeeRate::from_sat_per_vb(1).unwrap()),
+            INPUT_VSIZE
+        );
+        assert_eq!(
+            dust_limit(FeeRate::from_sat_per_vb(10).unwrap()),
+            10 * INPUT_VSIZE
+        );
+    }
+
+    #[test]
+    fn test_should_select_legacy_utxos_to_migrate() {
+        let mut taproot = input(4, 3000);
+        taproot.tx_out.script_pubkey =
+            ScriptBuf::new_p2tr_tweaked(bitcoin::key::TweakedPublicKey::dangerous_assume_tweaked(
+                bitcoin::XOnlyPublicKey::from_slice(&[2; 32]).unwrap(),
+            ));
+        let groups = vec![
+            UtxoGroup {
+                rune_info: vec![],
+                utxos: vec![input(1, 50), input(2, 2000), input(3, 1000)],
+            },
+            UtxoGroup {
+                rune_info: vec![rune_info()],
+                utxos: vec![taproot.clone(), input(5, 546)],
+            },
+        ];
+
+        let group = select_migration_group(groups.clone(), 10, 100).unwrap();
+        let outpoints: Vec<OutPoint> = group.utxos.iter().map(|utxo| utxo.outpoint).collect();
+        assert_eq!(group.rune_info, vec![]);
+        assert_eq!(
+            outpoints,
+            vec![input(3, 1000).outpoint, input(2, 2000).outpoint]
+        );
+
+        let group = select_migration_group(groups, 1, 100).unwrap();
+        assert_eq!(group.utxos.len(), 1);
+        assert_eq!(group.utxos[0].outpoint, input(3, 1000).outpoint);
+
+        let groups = vec![UtxoGroup {
+            rune_info: vec![rune_info()],
+            utxos: vec![taproot],
+        }];
+        assert!(select_migration_group(groups, 10, 100).is_none());
+    }
+}
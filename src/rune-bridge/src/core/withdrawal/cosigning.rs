@@ -0,0 +1,74 @@
+use bitcoin::Transaction;
+use bitcoin::psbt::Psbt;
+use bitcoin_bridge_core::key::{build_psbt, verify_cosignatures};
+use bitcoin_bridge_core::utxo_provider::UtxoProvider;
+use bridge_did::init::WithdrawalCosigningConfig;
+use bridge_did::runes::RuneWithdrawalPayload;
+use ord_rs::wallet::TxInputInfo;
+
+use super::Withdrawal;
+use crate::interface::WithdrawError;
+
+impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    /// Creates the withdrawal transaction without signing it, so it can be exported as a PSBT
+    /// and co-signed outside of the canister. The inputs of the transaction are reserved for it.
+    pub async fn create_unsigned_withdrawal_transaction(
+        &self,
+        payload: RuneWithdrawalPayload,
+    ) -> Result<(Transaction, Vec<TxInputInfo>), WithdrawError> {
+        let (unsigned_tx, inputs, dst_address) = self.prepare_withdrawal(payload).await?;
+        self.mark_inputs_used(&inputs, &dst_address);
+
+        Ok((unsigned_tx, inputs))
+    }
+
+    /// Returns the PSBT of the unsigned withdrawal transaction.
+    pub fn withdrawal_psbt(
+        &self,
+        unsigned_tx: &Transaction,
+        inputs: &[TxInputInfo],
+    ) -> Result<Psbt, WithdrawError> {
+        let state = self.state.borrow();
+        let ecdsa_master_key = state.public_key().zip(state.chain_code());
+
+        Ok(build_psbt(
+            unsigned_tx,
+            inputs,
+            state.network(),
+            ecdsa_master_key,
+            state.schnorr_master_key(),
+        )?)
+    }
+
+    /// Signs the withdrawal transaction co-signed by the external keys.
+    ///
+    /// The PSBT must contain exactly the unsigned transaction, and every input must be signed
+    /// by the required number of co-signers. Only the signatures of the canister end up in the
+    /// transaction.
+    pub async fn sign_cosigned_transaction(
+        &self,
+        unsigned_tx: &Transaction,
+        inputs: &[TxInputInfo],
+        psbt: &Psbt,
+        cosigning: &WithdrawalCosigningConfig,
+    ) -> Result<Transaction, WithdrawError> {
+        if psbt.unsigned_tx != *unsigned_tx {
+            return Err(WithdrawError::InvalidRequest(
+                "PSBT does not match the withdrawal transaction".to_string(),
+            ));
+        }
+
+        let cosigners = cosigning
+            .cosigner_keys()
+            .map_err(WithdrawError::InternalError)?;
+        verify_cosignatures(
+            psbt,
+            inputs,
+            &cosigners,
+            cosigning.required_signatures as usize,
+        )
+        .map_err(|err| WithdrawError::InvalidRequest(err.to_string()))?;
+
+        self.sign_transaction(unsigned_tx, inputs).await
+    }
+}
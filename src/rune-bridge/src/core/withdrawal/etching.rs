@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash as _;
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use bitcoin_bridge_core::funding::select_funding_utxos;
+use bitcoin_bridge_core::key::get_derivation_path;
+use bitcoin_bridge_core::utxo_provider::UtxoProvider;
+use bridge_did::runes::{EvmNativeToken, EvmNativeTokenStatus, EvmTokenEtchingRequest};
+use did::H256;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ord_rs::constants::POSTAGE;
+use ord_rs::fees::estimate_transaction_fees;
+use ord_rs::wallet::{ScriptType, TxInputInfo};
+use ordinals::{Etching, Runestone};
+
+use super::Withdrawal;
+use crate::constants::DUST_THRESHOLD;
+use crate::interface::WithdrawError;
+use crate::ledger::UtxoKey;
+
+/// Index of the output receiving the premined runes of the etching transaction.
+pub const PREMINE_OUTPUT_INDEX: usize = 1;
+
+impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    /// Etches a rune representing the given EVM-native ERC20 token.
+    ///
+    /// The rune name is assigned by the protocol, so no commit transaction is needed. The whole
+    /// supply of the rune is premined to the rune change address of the canister, and the
+    /// etching transaction is paid from the transit address of the ERC20 token, which must be
+    /// funded beforehand.
+    pub async fn etch_evm_token_rune(
+        &self,
+        request: &EvmTokenEtchingRequest,
+    ) -> Result<Txid, WithdrawError> {
+        request.validate().map_err(WithdrawError::InvalidRequest)?;
+        if self
+            .state
+            .borrow()
+            .evm_native_token(&request.erc20)
+            .is_some()
+        {
+            return Err(WithdrawError::InvalidRequest(format!(
+                "Rune for the token {} is already etched",
+                request.erc20
+            )));
+        }
+
+        let fee_rate = self.get_fee_rate().await?;
+        let funding_address = self.get_transit_address(&request.erc20).await?;
+        let rune_change_address = self.get_change_address().await?;
+
+        let known_utxos: HashSet<UtxoKey> = {
+            let state = self.state.borrow();
+            let ledger = state.ledger();
+            let mut known: HashSet<UtxoKey> = ledger.load_unspent_utxos()?.into_keys().collect();
+            known.extend(ledger.load_used_utxos().into_iter().map(|(key, _)| key));
+            known
+        };
+        let funding_utxos: Vec<Utxo> = self
+            .utxo_provider
+            .get_utxos(&funding_address)
+            .await
+            .map_err(|_| WithdrawError::NoInputs)?
+            .utxos
+            .into_iter()
+            .filter(|utxo| !known_utxos.contains(&UtxoKey::from(&utxo.outpoint)))
+            .collect();
+
+        let runestone = etching_runestone(request);
+        let estimate_fee = |inputs_count| {
+            estimate_transaction_fees(
+                ScriptType::P2WSH,
+                inputs_count,
+                fee_rate,
+                &None,
+                etching_outputs(
+                    &runestone,
+                    &rune_change_address,
+                    &funding_address,
+                    DUST_THRESHOLD,
+                ),
+            )
+            .to_sat()
+        };
+        let selected = select_funding_utxos(funding_utxos, |count| estimate_fee(count) + POSTAGE)
+            .ok_or(WithdrawError::InsufficientFunds)?;
+
+        let inputs_value: u64 = selected.iter().map(|utxo| utxo.value).sum();
+        let change = inputs_value - POSTAGE - estimate_fee(selected.len());
+
+        let derivation_path = get_derivation_path(&request.erc20)?;
+        let inputs: Vec<TxInputInfo> = selected
+            .iter()
+            .map(|utxo| TxInputInfo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&utxo.outpoint.txid).unwrap(),
+                    vout: utxo.outpoint.vout,
+                },
+                tx_out: TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: funding_address.script_pubkey(),
+                },
+                derivation_path: derivation_path.clone(),
+            })
+            .collect();
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: etching_outputs(&runestone, &rune_change_address, &funding_address, change),
+        };
+
+        let tx = self.sign_transaction(&unsigned_tx, &inputs).await?;
+        self.utxo_provider.send_tx(&tx).await?;
+
+        let txid = tx.txid();
+        let mut state = self.state.borrow_mut();
+        for input in &inputs {
+            state
+                .ledger_mut()
+                .mark_as_used(input.outpoint.into(), rune_change_address.clone());
+        }
+        state.set_evm_native_token(EvmNativeToken {
+            erc20: request.erc20.clone(),
+            divisibility: request.divisibility,
+            premine: request.premine,
+            status: EvmNativeTokenStatus::Etching {
+                txid: H256::from_slice(txid.as_byte_array()),
+            },
+        });
+
+        log::info!(
+            "Sent transaction {txid} etching a rune for the token {}",
+            request.erc20
+        );
+
+        Ok(txid)
+    }
+}
+
+/// Runestone etching a rune with a protocol assigned name and premining the whole supply to
+/// the premine output.
+fn etching_runestone(request: &EvmTokenEtchingRequest) -> Runestone {
+    Runestone {
+        etching: Some(Etching {
+            divisibility: Some(request.divisibility),
+            premine: Some(request.premine),
+            rune: None,
+            spacers: None,
+            symbol: request.symbol_char(),
+            terms: None,
+            turbo: false,
+        }),
+        pointer: Some(PREMINE_OUTPUT_INDEX as u32),
+        ..Default::default()
+    }
+}
+
+/// Outputs of the etching transaction. The BTC change output is added only if it is not dust.
+fn etching_outputs(
+    runestone: &Runestone,
+    rune_change_address: &Address,
+    change_address: &Address,
+    change: u64,
+) -> Vec<TxOut> {
+    let mut outputs = vec![
+        TxOut {
+            value: Amount::ZERO,
+            script_pubkey: runestone.encipher(),
+        },
+        TxOut {
+            value: Amount::from_sat(POSTAGE),
+            script_pubkey: rune_change_address.script_pubkey(),
+        },
+    ];
+
+    if change >= DUST_THRESHOLD {
+        outputs.push(TxOut {
+            value: Amount::from_sat(change),
+            script_pubkey: change_address.script_pubkey(),
+        });
+    }
+
+    outputs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use did::H160;
+
+    use super::*;
+
+    fn address() -> Address {
+        Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked()
+    }
+
+    #[test]
+    fn test_should_build_etching_runestone() {
+        let request = EvmTokenEtchingRequest {
+            erc20: H160::from_slice(&[1; 20]),
+            divisibility: 6,
+            symbol: Some("T".to_string()),
+            premine: 21_000_000_000_000,
+        };
+
+        let runestone = etching_runestone(&request);
+        let etching = runestone.etching.unwrap();
+
+        assert_eq!(etching.rune, None);
+        assert_eq!(etching.divisibility, Some(6));
+        assert_eq!(etching.premine, Some(21_000_000_000_000));
+        assert_eq!(etching.symbol, Some('T'));
+        assert_eq!(etching.terms, None);
+        assert_eq!(runestone.pointer, Some(PREMINE_OUTPUT_INDEX as u32));
+    }
+
+    #[test]
+    fn test_should_skip_dust_change_output() {
+        let runestone = Runestone::default();
+
+        let outputs = etching_outputs(&runestone, &address(), &address(), 1_000);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[PREMINE_OUTPUT_INDEX].value.to_sat(), POSTAGE);
+        assert_eq!(outputs[2].value.to_sat(), 1_000);
+
+        let outputs = etching_outputs(&runestone, &address(), &address(), DUST_THRESHOLD - 1);
+        assert_eq!(outputs.len(), 2);
+    }
+}
@@ -0,0 +1,138 @@
+use std::str::FromStr;
+
+use bitcoin::hashes::Hash as _;
+use bitcoin::{Address, Amount, OutPoint, Transaction, TxOut, Txid};
+use bitcoin_bridge_core::key::get_derivation_path;
+use bitcoin_bridge_core::utxo_provider::UtxoProvider;
+use bridge_did::runes::{RuneInfo, RuneRefund};
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ord_rs::wallet::TxInputInfo;
+
+use super::{GetGreedyFundingUtxosArgs, Withdrawal, WithdrawalTransactionArgs};
+use crate::interface::WithdrawError;
+
+impl<UTXO: UtxoProvider> Withdrawal<UTXO> {
+    /// Creates and signs the transaction sending the refunded runes of the deposited utxo to the
+    /// refund address. The other runes of the utxo are sent to the rune change address.
+    ///
+    /// The transaction fee is paid from the BTC at the deposit address of the utxo.
+    pub async fn create_refund_transaction(
+        &self,
+        dst_address: &H160,
+        utxo: &Utxo,
+        refund: &RuneRefund,
+    ) -> Result<(Transaction, Vec<TxInputInfo>), WithdrawError> {
+        let first_rune = refund
+            .runes
+            .first()
+            .ok_or_else(|| WithdrawError::InvalidRequest("refund contains no runes".to_string()))?;
+        let refund_address = Address::from_str(&refund.address)
+            .map_err(|err| {
+                WithdrawError::InvalidRequest(format!(
+                    "invalid refund address {}: {err}",
+                    refund.address
+                ))
+            })?
+            .require_network(self.network)
+            .map_err(|err| {
+                WithdrawError::InvalidRequest(format!(
+                    "invalid refund address {}: {err}",
+                    refund.address
+                ))
+            })?;
+
+        let fee_rate = self.get_fee_rate().await?;
+        let rune_change_address = self.get_change_address().await?;
+        let (deposit_address, address_utxos) = self.find_deposit_address(dst_address, utxo).await?;
+
+        let derivation_path = get_derivation_path(dst_address)?;
+        let tx_input = |utxo: &Utxo| TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&utxo.outpoint.txid).expect("invalid txid"),
+                vout: utxo.outpoint.vout,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(utxo.value),
+                script_pubkey: deposit_address.script_pubkey(),
+            },
+            derivation_path: derivation_path.clone(),
+        };
+
+        let funding_utxos = address_utxos
+            .into_iter()
+            .filter(|address_utxo| address_utxo.outpoint != utxo.outpoint)
+            .collect();
+        let mut inputs = vec![tx_input(utxo)];
+        for funding_utxo in self
+            .get_greedy_funding_utxos(GetGreedyFundingUtxosArgs {
+                rune_utxos_count: 1,
+                funding_utxos,
+                rune_change_address: rune_change_address.clone(),
+                destination_address: refund_address.clone(),
+                change_address: deposit_address.clone(),
+                rune: first_rune.rune_info.id(),
+                rune_amount: first_rune.amount,
+                fee_rate,
+            })
+            .ok_or(WithdrawError::InsufficientFunds)?
+        {
+            inputs.push(tx_input(&funding_utxo));
+        }
+
+        let unsigned_tx = self.build_withdraw_transaction(WithdrawalTransactionArgs {
+            runes: refund
+                .runes
+                .iter()
+                .map(|rune| (rune.rune_info.id(), rune.amount))
+                .collect(),
+            dst_address: refund_address,
+            change_address: deposit_address,
+            inputs: inputs.clone(),
+            fee_rate,
+            rune_change_address,
+        })?;
+        let tx = self.sign_transaction(&unsigned_tx, &inputs).await?;
+
+        Ok((tx, inputs))
+    }
+
+    /// Sends the refund transaction and adds its rune change output holding the wrapped runes to
+    /// the ledger.
+    pub async fn send_refund_transaction(
+        &self,
+        tx: Transaction,
+        wrapped_runes: Vec<RuneInfo>,
+    ) -> Result<(), WithdrawError> {
+        self.send_transaction_with_change(tx, wrapped_runes).await
+    }
+
+    /// Returns the transit address of the user holding the utxo, along with all the utxos at
+    /// this address.
+    async fn find_deposit_address(
+        &self,
+        dst_address: &H160,
+        utxo: &Utxo,
+    ) -> Result<(Address, Vec<Utxo>), WithdrawError> {
+        let addresses = self
+            .signer
+            .get_transit_addresses(dst_address, self.network)
+            .await?;
+        for address in addresses {
+            let utxos = self
+                .utxo_provider
+                .get_utxos(&address)
+                .await
+                .map_err(|_| WithdrawError::NoInputs)?
+                .utxos;
+            if utxos
+                .iter()
+                .any(|address_utxo| address_utxo.outpoint == utxo.outpoint)
+            {
+                return Ok((address, utxos));
+            }
+        }
+
+        Err(WithdrawError::NoInputs)
+    }
+}
@@ -1,14 +1,15 @@
 use std::collections::HashMap;
 
+use bitcoin_bridge_core::key::KeyError;
+use bitcoin_bridge_core::utxo_provider::UtxoProviderError;
 use bridge_did::order::SignedMintOrder;
-use bridge_did::runes::RuneName;
+use bridge_did::runes::{RuneInfo, RuneName};
 use candid::CandidType;
 use did::H256;
 use ordinals::Pile;
 use serde::Deserialize;
 
 use crate::core::deposit::RuneDepositPayload;
-use crate::key::KeyError;
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct PendingUtxo {}
 
@@ -58,6 +59,15 @@ pub enum Erc20MintError {
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub enum GetAddressError {
     Key(String),
+    /// Deposit addresses can't be registered while the deposit watcher is disabled.
+    DepositWatcherDisabled,
+    /// Maximum number of the watched deposit addresses is reached.
+    TooManyWatchedAddresses,
+    /// Not enough cycles attached to pay the registration fee.
+    InsufficientCycles {
+        required: u64,
+        attached: u64,
+    },
 }
 
 impl From<KeyError> for GetAddressError {
@@ -125,6 +135,8 @@ pub enum WithdrawError {
     InvalidRequest(String),
     InternalError(String),
     KeyError(String),
+    TxNotConfirmed,
+    CannotReplaceTransaction(String),
 }
 
 impl From<KeyError> for WithdrawError {
@@ -133,6 +145,17 @@ impl From<KeyError> for WithdrawError {
     }
 }
 
+impl From<UtxoProviderError> for WithdrawError {
+    fn from(e: UtxoProviderError) -> Self {
+        match e {
+            UtxoProviderError::BtcAdapter(_) => WithdrawError::NoInputs,
+            UtxoProviderError::FeeRateRequest => WithdrawError::FeeRateRequest,
+            UtxoProviderError::TransactionSerialization => WithdrawError::TransactionSerialization,
+            UtxoProviderError::TransactionSending => WithdrawError::TransactionSending,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, CandidType, Deserialize, Hash, PartialEq, Eq)]
 pub struct RuneIdDid {
     pub block_id: u64,
@@ -152,3 +175,32 @@ pub struct DepositStateResponse {
     pub current_ts: u64,
     pub deposits: Vec<RuneDepositPayload>,
 }
+
+/// Summary of the utxos held by the bridge.
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct LedgerSummary {
+    /// Number of unspent utxos.
+    pub unspent_utxos: u64,
+    /// Total value of the unspent utxos in satoshis.
+    pub total_value: u64,
+    /// Number of utxos without runes whose value doesn't cover the fee of spending them at the
+    /// current fee rate.
+    pub dust_utxos: u64,
+    /// Number of utxos spent by transactions which are not confirmed yet.
+    pub used_utxos: u64,
+    /// Number of utxos that would be saved by merging all utxos holding the same runes together.
+    pub fragmentation: u64,
+    /// Unspent utxos grouped by the runes they hold.
+    pub groups: Vec<UtxoGroupSummary>,
+}
+
+/// Summary of the unspent utxos holding the same set of runes.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct UtxoGroupSummary {
+    /// Runes held by the utxos. Empty for the utxos holding only BTC.
+    pub runes: Vec<RuneInfo>,
+    /// Number of utxos in the group.
+    pub utxos: u64,
+    /// Total value of the utxos in satoshis.
+    pub total_value: u64,
+}
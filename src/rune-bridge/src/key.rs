@@ -1,134 +1,56 @@
 use std::cell::RefCell;
 
 use async_trait::async_trait;
-use bitcoin::address::Error as BitcoinAddressError;
-use bitcoin::bip32::{ChildNumber, DerivationPath, Error as Bip32Error, Xpub};
+use bitcoin::bip32::DerivationPath;
 use bitcoin::secp256k1::ecdsa::Signature;
-use bitcoin::secp256k1::{Error as Secp256Error, Message, Secp256k1};
+use bitcoin::secp256k1::{Error as Secp256Error, Message, schnorr};
 use bitcoin::{Address, Network, PublicKey, XOnlyPublicKey};
+use bitcoin_bridge_core::key::{
+    AddressType, IcBtcSigner, KeyError, KeyResult, LocalBtcSigner, derive_transit_address,
+    signer_transit_address,
+};
 use did::H160;
-use ic_exports::ic_cdk::api::management_canister::ecdsa::{SignWithEcdsaArgument, sign_with_ecdsa};
-use ord_rs::wallet::LocalSigner;
-use ord_rs::{BtcTxSigner, OrdError, OrdResult};
-use thiserror::Error;
-
-use crate::state::{MasterKey, RuneState};
-
-/// Key result type
-pub type KeyResult<T> = Result<T, KeyError>;
-
-/// Key and signer error types
-#[derive(Debug, Error, PartialEq, Eq, Clone)]
-pub enum KeyError {
-    #[error("bip32 error: {0}")]
-    Bip32(#[from] Bip32Error),
-    #[error("failed to derive address: {0}")]
-    BitcoinAddress(#[from] BitcoinAddressError),
-    #[error("invalid derivation path")]
-    InvalidDerivationPath,
-    #[error("invalid public key")]
-    InvalidPublicKey,
-    #[error("ord error error: {0}")]
-    OrdError(String),
-    #[error("secp256 error: {0}")]
-    Secp256(#[from] Secp256Error),
-    #[error("signer not initialized")]
-    SignerNotInitialized,
-}
+use ord_rs::{BtcTxSigner, OrdResult};
 
-impl From<OrdError> for KeyError {
-    fn from(e: OrdError) -> Self {
-        KeyError::OrdError(e.to_string())
-    }
-}
+use crate::state::RuneState;
 
-pub const DERIVATION_PATH_PREFIX: u8 = 7;
-
-pub struct IcBtcSigner {
-    master_key: MasterKey,
-    network: Network,
+pub enum BtcSignerType {
+    Local(LocalBtcSigner),
+    Ic(IcBtcSigner),
 }
 
-impl IcBtcSigner {
-    pub const DERIVATION_PATH_SIZE: u32 = 21 / 3 * 4;
-
-    pub fn new(master_key: MasterKey, network: Network) -> Self {
-        Self {
-            master_key,
-            network,
+impl BtcSignerType {
+    /// Type of the addresses new deposits and change are received at.
+    pub fn address_type(&self) -> AddressType {
+        match self {
+            BtcSignerType::Local(v) => v.address_type(),
+            BtcSignerType::Ic(v) => v.address_type(),
         }
     }
-}
-
-#[async_trait]
-impl BtcTxSigner for IcBtcSigner {
-    async fn ecdsa_public_key(&self, derivation_path: &DerivationPath) -> OrdResult<PublicKey> {
-        let x_public_key = Xpub {
-            network: self.network,
-            depth: 0,
-            parent_fingerprint: Default::default(),
-            child_number: ChildNumber::from_normal_idx(0).expect("Failed to create child number"),
-            public_key: self.master_key.public_key().expect("invalid pubkey").inner,
-            chain_code: self.master_key.chain_code(),
-        };
-        let public_key = x_public_key
-            .derive_pub(&Secp256k1::new(), derivation_path)
-            .map_err(|_| OrdError::Custom("Failed to derive public key".to_string()))?
-            .public_key;
-
-        Ok(PublicKey::from(public_key))
-    }
-
-    async fn sign_with_ecdsa(
-        &self,
-        message: Message,
-        derivation_path: &DerivationPath,
-    ) -> Result<Signature, Secp256Error> {
-        let request = SignWithEcdsaArgument {
-            message_hash: message.as_ref().to_vec(),
-            derivation_path: derivation_path_to_ic(derivation_path.clone()),
-            key_id: self.master_key.key_id.clone(),
-        };
-
-        let response = sign_with_ecdsa(request)
-            .await
-            .expect("sign_with_ecdsa failed")
-            .0;
-
-        Signature::from_compact(&response.signature)
-    }
 
-    async fn schnorr_public_key(
-        &self,
-        _derivation_path: &DerivationPath,
-    ) -> OrdResult<XOnlyPublicKey> {
-        Err(OrdError::Custom("Not implemented".to_string()))
-    }
-
-    async fn sign_with_schnorr(
+    pub async fn get_transit_address(
         &self,
-        _message: Message,
-        _derivation_path: &DerivationPath,
-    ) -> Result<bitcoin::secp256k1::schnorr::Signature, Secp256Error> {
-        Err(Secp256Error::IncorrectSignature)
+        eth_address: &H160,
+        network: Network,
+    ) -> KeyResult<Address> {
+        signer_transit_address(self, eth_address, network, self.address_type()).await
     }
-}
 
-pub enum BtcSignerType {
-    Local(LocalSigner),
-    Ic(IcBtcSigner),
-}
-
-impl BtcSignerType {
-    pub async fn get_transit_address(
+    /// Returns the transit addresses of the given user. The first one is the current address,
+    /// the second one is the legacy P2WPKH address if the taproot addresses are enabled.
+    pub async fn get_transit_addresses(
         &self,
         eth_address: &H160,
         network: Network,
-    ) -> KeyResult<Address> {
-        let derivation_path = get_derivation_path(eth_address)?;
-        let public_key = self.ecdsa_public_key(&derivation_path).await?;
+    ) -> KeyResult<Vec<Address>> {
+        let mut addresses = vec![self.get_transit_address(eth_address, network).await?];
+        if self.address_type() == AddressType::P2tr {
+            addresses.push(
+                signer_transit_address(self, eth_address, network, AddressType::P2wpkh).await?,
+            );
+        }
 
-        Address::p2wpkh(&public_key, network).map_err(KeyError::BitcoinAddress)
+        Ok(addresses)
     }
 }
 
@@ -166,7 +88,7 @@ impl BtcTxSigner for BtcSignerType {
         &self,
         message: Message,
         derivation_path: &DerivationPath,
-    ) -> Result<bitcoin::secp256k1::schnorr::Signature, Secp256Error> {
+    ) -> Result<schnorr::Signature, Secp256Error> {
         match self {
             BtcSignerType::Local(v) => v.sign_with_schnorr(message, derivation_path).await,
             BtcSignerType::Ic(v) => v.sign_with_schnorr(message, derivation_path).await,
@@ -174,62 +96,146 @@ impl BtcTxSigner for BtcSignerType {
     }
 }
 
+/// Returns the current transit address of the given user, derived from the master keys stored
+/// in the state.
 pub fn get_transit_address(state: &RefCell<RuneState>, eth_address: &H160) -> KeyResult<Address> {
     let state = state.borrow();
-    let public_key = state.public_key().ok_or(KeyError::SignerNotInitialized)?;
-    let chain_code = state.chain_code().ok_or(KeyError::SignerNotInitialized)?;
-    let x_public_key = Xpub {
-        network: state.network(),
-        depth: 0,
-        parent_fingerprint: Default::default(),
-        child_number: ChildNumber::from_normal_idx(0)?,
-        public_key: public_key.inner,
-        chain_code,
-    };
-    let derivation_path = get_derivation_path(eth_address)?;
-    let public_key = x_public_key
-        .derive_pub(&Secp256k1::new(), &derivation_path)?
-        .public_key;
-
-    Ok(Address::p2wpkh(&public_key.into(), state.network())?)
-}
+    let network = state.network();
 
-pub fn get_derivation_path_ic(eth_address: &H160) -> Vec<Vec<u8>> {
-    let mut bytes = vec![DERIVATION_PATH_PREFIX];
-    bytes.append(&mut eth_address.0.0.to_vec());
+    if state.schnorr_key_id().is_some() {
+        let master_key = state
+            .schnorr_master_key()
+            .ok_or(KeyError::SignerNotInitialized)?;
 
-    let mut dp = vec![];
-    for slice in bytes.chunks_exact(3) {
-        let mut part = vec![0];
-        part.append(&mut slice.to_vec());
-        dp.push(part);
+        return derive_transit_address(
+            master_key.public_key(),
+            master_key.chain_code(),
+            network,
+            eth_address,
+            AddressType::P2tr,
+        );
     }
 
-    dp
-}
+    let public_key = state.public_key().ok_or(KeyError::SignerNotInitialized)?;
+    let chain_code = state.chain_code().ok_or(KeyError::SignerNotInitialized)?;
 
-pub fn get_derivation_path(eth_address: &H160) -> KeyResult<DerivationPath> {
-    ic_dp_to_derivation_path(&get_derivation_path_ic(eth_address))
+    derive_transit_address(
+        public_key,
+        chain_code,
+        network,
+        eth_address,
+        AddressType::P2wpkh,
+    )
 }
 
-pub fn ic_dp_to_derivation_path(ic_derivation_path: &[Vec<u8>]) -> KeyResult<DerivationPath> {
-    let mut parts = vec![];
-    for part in ic_derivation_path.iter() {
-        let child_idx = u32::from_be_bytes(
-            part[..]
-                .try_into()
-                .map_err(|_| KeyError::InvalidDerivationPath)?,
-        );
-        let child = ChildNumber::from_normal_idx(child_idx)?;
-        parts.push(child);
+#[cfg(test)]
+mod tests {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash as _;
+    use bitcoin::key::TapTweak as _;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::sighash::{Prevouts, SighashCache};
+    use bitcoin::transaction::Version;
+    use bitcoin::{
+        Amount, EcdsaSighashType, OutPoint, PrivateKey, ScriptBuf, Sequence, TapSighashType, TxIn,
+        TxOut, Txid, Witness, ecdsa,
+    };
+    use bitcoin_bridge_core::key::sign_transaction;
+    use ord_rs::wallet::TxInputInfo;
+
+    use super::*;
+
+    fn input(signer_address: &Address, id: u8) -> TxInputInfo {
+        TxInputInfo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[id; 32]).unwrap(),
+                vout: 0,
+            },
+            tx_out: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: signer_address.script_pubkey(),
+            },
+            derivation_path: DerivationPath::default(),
+        }
     }
 
-    Ok(DerivationPath::from(parts))
-}
+    #[tokio::test]
+    async fn test_should_sign_taproot_and_segwit_inputs() {
+        let secp = Secp256k1::new();
+        let signer = BtcSignerType::Local(LocalBtcSigner::new(
+            PrivateKey::generate(Network::Regtest),
+            AddressType::P2tr,
+        ));
+        let eth_address = H160::from_slice(&[1; 20]);
+        let addresses = signer
+            .get_transit_addresses(&eth_address, Network::Regtest)
+            .await
+            .unwrap();
+        assert_eq!(addresses.len(), 2);
+        assert!(addresses[0].script_pubkey().is_p2tr());
+        assert!(addresses[1].script_pubkey().is_p2wpkh());
+
+        let inputs = vec![input(&addresses[0], 1), input(&addresses[1], 2)];
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|input| TxIn {
+                    previous_output: input.outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(19_000),
+                script_pubkey: addresses[0].script_pubkey(),
+            }],
+        };
+
+        let tx = sign_transaction(&signer, &unsigned_tx, &inputs)
+            .await
+            .unwrap();
 
-fn derivation_path_to_ic(derivation_path: DerivationPath) -> Vec<Vec<u8>> {
-    let vec: Vec<_> = derivation_path.into();
-    vec.into_iter()
-        .map(|child| u32::from(child).to_be_bytes().to_vec())
-        .collect()
+        let prevouts: Vec<TxOut> = inputs.iter().map(|input| input.tx_out.clone()).collect();
+        let mut sighash_cache = SighashCache::new(&unsigned_tx);
+
+        let sighash = sighash_cache
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .unwrap();
+        let internal_key = signer
+            .schnorr_public_key(&DerivationPath::default())
+            .await
+            .unwrap();
+        let (output_key, _) = internal_key.tap_tweak(&secp, None);
+        let signature = schnorr::Signature::from_slice(&tx.input[0].witness[0]).unwrap();
+        secp.verify_schnorr(
+            &signature,
+            &Message::from_digest(sighash.to_byte_array()),
+            &output_key.to_inner(),
+        )
+        .unwrap();
+
+        let sighash = sighash_cache
+            .p2wpkh_signature_hash(
+                1,
+                &prevouts[1].script_pubkey,
+                prevouts[1].value,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        let public_key = signer
+            .ecdsa_public_key(&DerivationPath::default())
+            .await
+            .unwrap();
+        let signature = ecdsa::Signature::from_slice(&tx.input[1].witness[0]).unwrap();
+        secp.verify_ecdsa(
+            &Message::from_digest(sighash.to_byte_array()),
+            &signature.sig,
+            &public_key.inner,
+        )
+        .unwrap();
+        assert_eq!(&tx.input[1].witness[1], &public_key.to_bytes()[..]);
+    }
 }
@@ -1,24 +1,20 @@
-mod used_utxo_details;
-mod utxo_details;
-mod utxo_key;
 mod utxo_runes;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use bitcoin::hashes::sha256d::Hash;
-use bitcoin::{Address, Amount, OutPoint, TxOut, Txid};
+use bitcoin::Address;
+use bitcoin_bridge_core::key::KeyError;
+use bitcoin_bridge_core::ledger::UsedUtxoDetails;
+pub use bitcoin_bridge_core::ledger::UtxoKey;
 use bridge_did::runes::RuneInfo;
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
-use ic_exports::ic_kit::ic;
 use ic_stable_structures::stable_structures::Memory;
 use ic_stable_structures::{BTreeMapStructure, MemoryId, MemoryManager, StableBTreeMap};
 use ord_rs::wallet::TxInputInfo;
+use ordinals::RuneId;
 
-use self::used_utxo_details::UsedUtxoDetails;
-use self::utxo_details::UtxoDetails;
-pub use self::utxo_key::UtxoKey;
 use self::utxo_runes::UtxoRunes;
-use crate::key::{KeyError, ic_dp_to_derivation_path};
+use crate::interface::{LedgerSummary, UtxoGroupSummary};
 use crate::memory::{DEPOSITED_UTXOS_MEMORY_ID, RUNE_INFO_BY_UTXO_MEMORY_ID, USED_UTXOS_MEMORY_ID};
 
 /// Information about the unspent utxo.
@@ -28,13 +24,31 @@ pub struct UnspentUtxoInfo {
     pub rune_info: Vec<RuneInfo>,
 }
 
+/// Unspent utxos holding the same set of runes.
+#[derive(Debug, Clone, Default)]
+pub struct UtxoGroup {
+    /// Runes held by the utxos, ordered by rune id. Empty for the utxos holding only BTC.
+    pub rune_info: Vec<RuneInfo>,
+    pub utxos: Vec<TxInputInfo>,
+}
+
+impl UtxoGroup {
+    /// Total value of the utxos in satoshis.
+    pub fn total_value(&self) -> u64 {
+        self.utxos
+            .iter()
+            .map(|utxo| utxo.tx_out.value.to_sat())
+            .sum()
+    }
+}
+
 /// Data structure to keep track of utxos owned by the canister.
+///
+/// The BTC utxos are kept in the ledger of `bitcoin-bridge-core`; this ledger adds the runes
+/// held by each of them.
 pub struct UtxoLedger<M: Memory> {
     rune_info_by_utxo: StableBTreeMap<UtxoKey, UtxoRunes, M>,
-    /// contains a list of utxos on the main canister account (which get there as a change from withdrawal transactions)
-    deposited_utxos: StableBTreeMap<UtxoKey, UtxoDetails, M>,
-    /// contains a list of utxos that are on user's deposit address.
-    used_utxos: StableBTreeMap<UtxoKey, UsedUtxoDetails, M>,
+    utxos: bitcoin_bridge_core::ledger::UtxoLedger<M>,
 }
 
 impl<M> UtxoLedger<M>
@@ -44,8 +58,11 @@ where
     pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
         Self {
             rune_info_by_utxo: StableBTreeMap::new(memory_manager.get(RUNE_INFO_BY_UTXO_MEMORY_ID)),
-            deposited_utxos: StableBTreeMap::new(memory_manager.get(DEPOSITED_UTXOS_MEMORY_ID)),
-            used_utxos: StableBTreeMap::new(memory_manager.get(USED_UTXOS_MEMORY_ID)),
+            utxos: bitcoin_bridge_core::ledger::UtxoLedger::new(
+                memory_manager,
+                DEPOSITED_UTXOS_MEMORY_ID,
+                USED_UTXOS_MEMORY_ID,
+            ),
         }
     }
 
@@ -57,102 +74,149 @@ where
         derivation_path: Vec<Vec<u8>>,
         rune_info: Vec<RuneInfo>,
     ) {
-        log::debug!("Depositing utxo: {:?}", utxo);
-        log::debug!("Depositing utxo with address: {:?}", address);
-        log::debug!(
-            "Depositing utxo with derivation path: {:?}",
-            derivation_path
-        );
         log::debug!("Depositing utxo with rune info: {:?}", rune_info);
-        let script = address.script_pubkey();
-
         let utxo_key = UtxoKey::from(&utxo.outpoint);
-
-        self.deposited_utxos.insert(
-            utxo_key,
-            UtxoDetails {
-                value: utxo.value,
-                script_buf: script.clone().into_bytes(),
-                derivation_path: derivation_path.clone(),
-            },
-        );
+        self.utxos.deposit(utxo, address, derivation_path);
 
         // Add rune info if it is present
         if !rune_info.is_empty() {
             self.rune_info_by_utxo.insert(utxo_key, rune_info.into());
         }
-
-        log::debug!(
-            "Added utxo {}:{} with value {} to the ledger",
-            hex::encode(&utxo.outpoint.txid),
-            utxo.outpoint.vout,
-            utxo.value
-        );
     }
 
     /// Lists all unspent utxos in the store.
     pub fn load_unspent_utxos(&self) -> Result<HashMap<UtxoKey, UnspentUtxoInfo>, KeyError> {
-        let mut map = HashMap::new();
-
-        for (key, details) in self.deposited_utxos.iter() {
-            map.insert(
-                key,
-                UnspentUtxoInfo {
-                    tx_input_info: TxInputInfo {
-                        outpoint: OutPoint {
-                            txid: Txid::from_raw_hash(*Hash::from_bytes_ref(&key.tx_id)),
-                            vout: key.vout,
-                        },
-                        tx_out: TxOut {
-                            value: Amount::from_sat(details.value),
-                            script_pubkey: details.script_buf.into(),
-                        },
-                        derivation_path: ic_dp_to_derivation_path(&details.derivation_path)?,
+        Ok(self
+            .utxos
+            .load_unspent_utxos()?
+            .into_iter()
+            .map(|(key, tx_input_info)| {
+                let rune_info = self
+                    .rune_info_by_utxo
+                    .get(&key)
+                    .map(|rune_info| rune_info.runes().to_vec())
+                    .unwrap_or_default();
+
+                (
+                    key,
+                    UnspentUtxoInfo {
+                        tx_input_info,
+                        rune_info,
                     },
-                    rune_info: self
-                        .rune_info_by_utxo
-                        .get(&key)
-                        .map(|rune_info| rune_info.runes().to_vec())
-                        .unwrap_or_default(),
-                },
-            );
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the information of the runes held by the utxos in the store. A rune held by
+    /// several utxos is returned once for each of them.
+    pub fn held_runes(&self) -> Vec<RuneInfo> {
+        self.rune_info_by_utxo
+            .iter()
+            .flat_map(|(_, utxo_runes)| utxo_runes.runes().to_vec())
+            .collect()
+    }
+
+    /// Lists all unspent utxos in the store grouped by the set of runes they hold.
+    pub fn load_unspent_utxo_groups(&self) -> Result<Vec<UtxoGroup>, KeyError> {
+        let mut groups: BTreeMap<Vec<RuneId>, UtxoGroup> = BTreeMap::new();
+        for info in self.load_unspent_utxos()?.into_values() {
+            let mut rune_info = info.rune_info;
+            rune_info.sort_by_key(|rune| rune.id());
+            rune_info.dedup();
+
+            let rune_ids = rune_info.iter().map(|rune| rune.id()).collect();
+            groups
+                .entry(rune_ids)
+                .or_insert_with(|| UtxoGroup {
+                    rune_info,
+                    utxos: vec![],
+                })
+                .utxos
+                .push(info.tx_input_info);
         }
 
-        Ok(map)
+        Ok(groups.into_values().collect())
+    }
+
+    /// Summarizes the utxos in the store.
+    ///
+    /// Utxos without runes with value not above `dust_limit` are counted as dust.
+    pub fn summary(&self, dust_limit: u64) -> Result<LedgerSummary, KeyError> {
+        let groups = self.load_unspent_utxo_groups()?;
+
+        let mut summary = LedgerSummary {
+            used_utxos: self.utxos.load_used_utxos().len() as u64,
+            ..Default::default()
+        };
+        for group in groups {
+            let utxos = group.utxos.len() as u64;
+            let total_value = group.total_value();
+
+            summary.unspent_utxos += utxos;
+            summary.total_value += total_value;
+            summary.fragmentation += utxos.saturating_sub(1);
+            if group.rune_info.is_empty() {
+                summary.dust_utxos += group
+                    .utxos
+                    .iter()
+                    .filter(|utxo| utxo.tx_out.value.to_sat() <= dust_limit)
+                    .count() as u64;
+            }
+
+            summary.groups.push(UtxoGroupSummary {
+                runes: group.rune_info,
+                utxos,
+                total_value,
+            });
+        }
+
+        Ok(summary)
     }
 
     /// Marks the utxo as used.
+    ///
+    /// The utxo is taken out of the deposited utxos right away. It stays in the used utxos until
+    /// the `RemoveUsedUtxosTask` finds it spent or still unspent.
     pub fn mark_as_used(&mut self, key: UtxoKey, address: Address) {
-        self.used_utxos.insert(
-            key,
-            UsedUtxoDetails {
-                used_at: ic::time(),
-                owner_address: address.to_string(),
-            },
-        );
-        self.deposited_utxos.remove(&key);
+        self.utxos.mark_as_used(key, address);
+        self.utxos.mark_as_spent(&key);
+    }
+
+    /// Checks whether the utxo is known to the store, either as unspent or as used.
+    pub fn contains(&self, key: &UtxoKey) -> bool {
+        self.utxos.contains(key)
+    }
+
+    /// Checks whether the utxo is in the store and not used yet.
+    pub fn is_unspent(&self, key: &UtxoKey) -> bool {
+        self.utxos.is_unspent(key)
+    }
 
-        log::trace!("Utxo {key} is marked as used.");
+    /// Updates the time of usage of the used utxo, so it is not checked by the
+    /// `RemoveUsedUtxosTask` while the transaction spending it is not confirmed yet.
+    pub fn refresh_used_utxo(&mut self, key: &UtxoKey) {
+        self.utxos.refresh_used_utxo(key);
     }
 
     /// Lists all used utxos in the store.
     pub fn load_used_utxos(&self) -> Vec<(UtxoKey, UsedUtxoDetails)> {
-        self.used_utxos.iter().collect()
+        self.utxos.load_used_utxos()
     }
 
     /// Removes the spent utxo from the store.
     ///
     /// It gets removed from both the utxo storage, the rune info registry and the used utxos registry.
     pub fn remove_spent_utxo(&mut self, key: &UtxoKey) {
-        self.deposited_utxos.remove(key);
-        self.used_utxos.remove(key);
+        self.utxos.remove_spent_utxo(key);
         self.rune_info_by_utxo.remove(key);
     }
 
     /// Removes the unspent utxo from the store.
     /// It gets removed only from the `used_utxos_registry`
     pub fn remove_unspent_utxo(&mut self, key: &UtxoKey) {
-        self.used_utxos.remove(key);
+        // the used utxo is not in the deposited utxos anymore, so only its usage is removed
+        self.utxos.remove_spent_utxo(key);
     }
 }
 
@@ -244,6 +308,10 @@ mod tests {
         assert_eq!(used_utxos.len(), 1);
         assert_eq!(used_utxos[0].0, keys[0]);
         assert_eq!(used_utxos[0].1.owner_address, address.to_string());
+
+        // the used utxo is not unspent anymore, but it is still known to the ledger
+        assert!(!state.borrow().ledger().is_unspent(&keys[0]));
+        assert!(state.borrow().ledger().contains(&keys[0]));
     }
 
     #[test]
@@ -486,4 +554,61 @@ mod tests {
 
         assert_eq!(utxos.get(&key).unwrap().rune_info, rune_info);
     }
+
+    #[test]
+    fn test_should_summarize_utxos_by_runes() {
+        MockContext::new().inject();
+        let address = Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked();
+
+        let utxo = |tx_id: u8, value: u64| Utxo {
+            outpoint: Outpoint {
+                txid: vec![tx_id; 32],
+                vout: 0,
+            },
+            value,
+            height: 0,
+        };
+        let rune = |tx: u32| RuneInfo {
+            name: RuneName::from(Rune(tx as u128)),
+            decimals: 0,
+            block: 100,
+            tx,
+        };
+
+        let state = get_rune_state();
+        {
+            let mut state = state.borrow_mut();
+            let ledger = state.ledger_mut();
+            ledger.deposit(utxo(1, 10_000), &address, vec![], vec![rune(1)]);
+            ledger.deposit(utxo(2, 10_000), &address, vec![], vec![rune(1)]);
+            ledger.deposit(utxo(3, 546), &address, vec![], vec![rune(2), rune(1)]);
+            ledger.deposit(utxo(4, 546), &address, vec![], vec![rune(1), rune(2)]);
+            ledger.deposit(utxo(5, 5_000), &address, vec![], vec![]);
+            ledger.deposit(utxo(6, 100), &address, vec![], vec![]);
+            ledger.mark_as_used(UtxoKey::from(&utxo(1, 10_000).outpoint), address.clone());
+        }
+
+        let summary = state.borrow().ledger().summary(500).unwrap();
+        assert_eq!(summary.unspent_utxos, 5);
+        assert_eq!(summary.total_value, 16_192);
+        assert_eq!(summary.dust_utxos, 1);
+        assert_eq!(summary.used_utxos, 1);
+        assert_eq!(summary.fragmentation, 2);
+
+        let groups: Vec<(Vec<RuneInfo>, u64, u64)> = summary
+            .groups
+            .into_iter()
+            .map(|group| (group.runes, group.utxos, group.total_value))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (vec![], 2, 5_100),
+                (vec![rune(1)], 1, 10_000),
+                (vec![rune(1), rune(2)], 2, 1_092),
+            ]
+        );
+    }
 }
@@ -9,3 +9,10 @@ pub const DEPOSITED_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(101);
 pub const USED_UTXOS_MEMORY_ID: MemoryId = MemoryId::new(102);
 pub const RUNE_INFO_BY_UTXO_MEMORY_ID: MemoryId = MemoryId::new(103);
 pub const MASTER_KEY_MEMORY_ID: MemoryId = MemoryId::new(104);
+pub const RUNE_INFO_MEMORY_ID: MemoryId = MemoryId::new(105);
+pub const RUNE_IDS_BY_NAME_MEMORY_ID: MemoryId = MemoryId::new(106);
+pub const EVM_NATIVE_TOKENS_MEMORY_ID: MemoryId = MemoryId::new(107);
+pub const SCHNORR_MASTER_KEY_MEMORY_ID: MemoryId = MemoryId::new(108);
+pub const WATCHED_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(109);
+pub const STATE_VERSION_MEMORY_ID: MemoryId = MemoryId::new(110);
+pub const BASE_BRIDGE_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(111);
@@ -1,33 +1,66 @@
+mod batch_withdrawal;
+mod deposit_watcher;
+mod evm_token_etching;
 mod mint_order_handler;
 mod mint_tx_handler;
+mod rune_list_refresh;
+mod utxo_consolidation;
 
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
 
+use bitcoin::bip32::DerivationPath;
+use bitcoin::hashes::Hash as _;
+use bitcoin::{Address, Network, ScriptBuf, Transaction, Txid};
 use bridge_canister::bridge::{Operation, OperationProgress};
 use bridge_canister::runtime::RuntimeState;
 use bridge_canister::runtime::service::ServiceId;
 use bridge_did::error::{BTFResult, Error};
 use bridge_did::op_id::OperationId;
 use bridge_did::operations::{RuneBridgeDepositOp, RuneBridgeOp, RuneBridgeWithdrawOp};
-use bridge_did::runes::{DidTransaction, RuneName, RuneToWrap, RuneWithdrawalPayload};
+use bridge_did::runes::{
+    DidTransaction, DidTxInput, RuneInfo, RuneName, RuneRefund, RuneToRefund, RuneToWrap,
+    RuneWithdrawalPayload,
+};
 use candid::{CandidType, Deserialize};
-use did::H160;
+use did::{H160, H256};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::Utxo;
+use ic_exports::ic_kit::ic;
 use ic_task_scheduler::task::TaskOptions;
+use ord_rs::wallet::TxInputInfo;
 use serde::Serialize;
 
+pub use self::batch_withdrawal::BatchWithdrawalService;
+pub use self::deposit_watcher::DepositWatcherService;
+pub use self::evm_token_etching::EvmTokenEtchingService;
 pub use self::mint_order_handler::RuneMintOrderHandler;
 pub use self::mint_tx_handler::RuneMintTxHandler;
-use crate::canister::get_runtime;
+pub use self::rune_list_refresh::RuneListRefreshService;
+pub use self::utxo_consolidation::{UtxoConsolidationService, track_utxo_merge};
+use crate::canister::{get_base_bridge_config, get_rune_state, get_runtime};
+use crate::constants::FEE_BUMP_TIMEOUT;
 use crate::core::deposit::RuneDeposit;
-use crate::core::rune_inputs::RuneInputProvider;
+use crate::core::rune_inputs::{RuneInput, RuneInputProvider};
 use crate::core::utxo_handler::UtxoHandler;
-use crate::core::withdrawal::Withdrawal;
+use crate::core::withdrawal::{
+    FeeBump, TransactionStatus, Withdrawal, did_tx_input, input_owners, parse_dst_address,
+    tx_input_info,
+};
 
 pub const REFRESH_PARAMS_SERVICE_ID: ServiceId = 0;
 pub const FETCH_BTF_EVENTS_SERVICE_ID: ServiceId = 1;
 pub const SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 2;
 pub const SEND_MINT_TX_SERVICE_ID: ServiceId = 3;
+pub const BATCH_WITHDRAWAL_SERVICE_ID: ServiceId = 4;
+pub const UTXO_CONSOLIDATION_SERVICE_ID: ServiceId = 5;
+pub const RUNE_LIST_REFRESH_SERVICE_ID: ServiceId = 6;
+pub const EVM_TOKEN_ETCHING_SERVICE_ID: ServiceId = 7;
+pub const DEPOSIT_WATCHER_SERVICE_ID: ServiceId = 8;
+pub const BASE_REFRESH_PARAMS_SERVICE_ID: ServiceId = 9;
+pub const BASE_FETCH_BTF_EVENTS_SERVICE_ID: ServiceId = 10;
+pub const BASE_SIGN_MINT_ORDER_SERVICE_ID: ServiceId = 11;
+pub const BASE_SEND_MINT_TX_SERVICE_ID: ServiceId = 12;
 
 pub mod events_handler;
 
@@ -45,12 +78,14 @@ impl Operation for RuneBridgeOpImpl {
                 dst_address,
                 dst_tokens,
                 requested_amounts,
+                deposit_fee,
+                refund_address,
             }) => {
                 let input_provider = RuneDeposit::get(ctx.clone()).map_err(|err| {
                     Error::FailedToProgress(format!("cannot get deposit: {err:?}"))
                 })?;
                 log::debug!(
-                    "RuneBridgeOp::AwaitInputs {dst_address} {dst_tokens:?} {requested_amounts:?}"
+                    "RuneBridgeOp::AwaitInputs {dst_address} {dst_tokens:?} {requested_amounts:?} {deposit_fee:?} {refund_address:?}"
                 );
                 Self::await_inputs(
                     ctx.clone(),
@@ -58,6 +93,8 @@ impl Operation for RuneBridgeOpImpl {
                     dst_address,
                     dst_tokens,
                     requested_amounts,
+                    deposit_fee,
+                    refund_address,
                 )
                 .await
             }
@@ -65,12 +102,14 @@ impl Operation for RuneBridgeOpImpl {
                 dst_address,
                 utxo,
                 runes_to_wrap,
+                deposit_fee,
+                refund,
             }) => {
                 let input_provider = RuneDeposit::get(ctx.clone()).map_err(|err| {
                     Error::FailedToProgress(format!("cannot get deposit: {err:?}"))
                 })?;
                 log::debug!(
-                    "RuneBridgeOp::AwaitConfirmations {dst_address} {utxo:?} {runes_to_wrap:?}"
+                    "RuneBridgeOp::AwaitConfirmations {dst_address} {utxo:?} {runes_to_wrap:?} {deposit_fee:?} {refund:?}"
                 );
                 Self::await_confirmations(
                     ctx.clone(),
@@ -78,9 +117,21 @@ impl Operation for RuneBridgeOpImpl {
                     dst_address,
                     utxo,
                     runes_to_wrap,
+                    refund,
                 )
                 .await
             }
+            RuneBridgeOp::Deposit(RuneBridgeDepositOp::RefundRunes {
+                dst_address,
+                utxo,
+                refund,
+                wrapped_runes,
+            }) => {
+                log::debug!(
+                    "RuneBridgeOp::RefundRunes {dst_address} {utxo:?} {refund:?} {wrapped_runes:?}"
+                );
+                Self::refund_runes(dst_address, utxo, refund, wrapped_runes).await
+            }
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(mut mint_order)) => {
                 log::debug!("RuneBridgeOp::SignMintOrder {mint_order:?}");
                 // set mint order nonce to new operation id
@@ -89,18 +140,30 @@ impl Operation for RuneBridgeOpImpl {
                     "RuneBridgeOp::SignMintOrder nonce updated to {}",
                     mint_order.nonce
                 );
+                let service_id = if Self::is_released_by_base_bridge(&mint_order.dst_token) {
+                    BASE_SIGN_MINT_ORDER_SERVICE_ID
+                } else {
+                    SIGN_MINT_ORDER_SERVICE_ID
+                };
                 let new_op = RuneBridgeOpImpl(RuneBridgeOp::Deposit(
                     RuneBridgeDepositOp::SignMintOrder(mint_order),
                 ));
                 // update the mint order
                 ctx.borrow_mut().operations.update(id, new_op.clone());
 
-                return Ok(OperationProgress::AddToService(SIGN_MINT_ORDER_SERVICE_ID));
+                return Ok(OperationProgress::AddToService(service_id));
             }
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::SendMintOrder(order)) => {
                 log::debug!("RuneBridgeOp::SendMintOrder {order:?}");
 
-                return Ok(OperationProgress::AddToService(SEND_MINT_TX_SERVICE_ID));
+                let service_id =
+                    if Self::is_released_by_base_bridge(&order.reader().get_dst_token()) {
+                        BASE_SEND_MINT_TX_SERVICE_ID
+                    } else {
+                        SEND_MINT_TX_SERVICE_ID
+                    };
+
+                return Ok(OperationProgress::AddToService(service_id));
             }
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::WaitForMintConfirm { .. }) => {
                 Err(Error::FailedToProgress(
@@ -112,18 +175,109 @@ impl Operation for RuneBridgeOpImpl {
             ),
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { payload }) => {
                 log::debug!("RuneBridgeOp::CreateTransaction {payload:?}");
+                if get_rune_state().borrow().requires_withdrawal_cosigning(
+                    &payload.rune_info.name().to_string(),
+                    payload.amount,
+                ) {
+                    return Self::create_uncosigned_withdrawal(payload)
+                        .await
+                        .map(OperationProgress::Progress);
+                }
+
+                if get_rune_state()
+                    .borrow()
+                    .withdrawal_batch_config()
+                    .is_some()
+                {
+                    // a withdrawal which cannot be sent would block the whole batch, so it
+                    // fails alone before being added to it
+                    parse_dst_address(&payload.dst_address).map_err(|err| {
+                        Error::CannotProgress(format!("invalid withdrawal: {err:?}"))
+                    })?;
+
+                    return Ok(OperationProgress::AddToService(BATCH_WITHDRAWAL_SERVICE_ID));
+                }
+
                 Self::create_withdrawal_transaction(payload).await
             }
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitCosignatures { .. }) => {
+                Err(Error::FailedToProgress(
+                    "AwaitCosignatures task progresses only when the co-signed PSBT is submitted"
+                        .into(),
+                ))
+            }
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::SendTransaction {
                 from_address,
                 transaction,
+                inputs,
             }) => {
                 log::debug!("RuneBridgeOp::SendTransaction {from_address} {transaction:?}");
-                Self::send_transaction(from_address, transaction).await
+                Self::send_transaction(from_address, transaction, inputs).await
+            }
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                from_address,
+                transaction,
+                inputs,
+                sent_at,
+                replaced_txids,
+                child_fee,
+            }) => {
+                log::debug!(
+                    "RuneBridgeOp::AwaitTransactionConfirmation {from_address} {transaction:?}"
+                );
+                Self::await_transaction_confirmation(
+                    from_address,
+                    transaction,
+                    inputs,
+                    sent_at,
+                    replaced_txids,
+                    child_fee,
+                )
+                .await
             }
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionSent { .. }) => Err(
                 Error::FailedToProgress("TransactionSent task cannot be progressed".into()),
             ),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionConfirmed { .. }) => Err(
+                Error::FailedToProgress("TransactionConfirmed task cannot be progressed".into()),
+            ),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::BatchTransactionSent { .. }) => Err(
+                Error::FailedToProgress("BatchTransactionSent task cannot be progressed".into()),
+            ),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                from_address,
+                transaction,
+                inputs,
+                fee_share,
+                batch_operations,
+                sent_at,
+                replaced_txids,
+                child_fee,
+            }) => {
+                log::debug!(
+                    "RuneBridgeOp::AwaitBatchTransactionConfirmation {from_address} {transaction:?}"
+                );
+                Self::await_batch_transaction_confirmation(
+                    ctx,
+                    id,
+                    from_address,
+                    transaction,
+                    inputs,
+                    fee_share,
+                    batch_operations,
+                    sent_at,
+                    replaced_txids,
+                    child_fee,
+                )
+                .await
+            }
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitUtxoMergeConfirmation {
+                transaction,
+                inputs,
+            }) => {
+                log::debug!("RuneBridgeOp::AwaitUtxoMergeConfirmation {transaction:?}");
+                Self::await_utxo_merge_confirmation(transaction, inputs).await
+            }
         };
         Ok(OperationProgress::Progress(next_step?))
     }
@@ -132,13 +286,26 @@ impl Operation for RuneBridgeOpImpl {
         match self.0 {
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::AwaitInputs { .. }) => false,
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::AwaitConfirmations { .. }) => false,
+            RuneBridgeOp::Deposit(RuneBridgeDepositOp::RefundRunes { .. }) => false,
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(_)) => false,
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::SendMintOrder(_)) => false,
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::WaitForMintConfirm { .. }) => false,
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::MintOrderConfirmed { .. }) => true,
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { .. }) => false,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitCosignatures { .. }) => false,
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::SendTransaction { .. }) => false,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                ..
+            }) => false,
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionSent { .. }) => true,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionConfirmed { .. }) => true,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::BatchTransactionSent { .. }) => true,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                ..
+            }) => false,
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitUtxoMergeConfirmation { .. }) => {
+                false
+            }
         }
     }
 
@@ -150,6 +317,9 @@ impl Operation for RuneBridgeOpImpl {
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::AwaitConfirmations {
                 dst_address, ..
             }) => dst_address.clone(),
+            RuneBridgeOp::Deposit(RuneBridgeDepositOp::RefundRunes { dst_address, .. }) => {
+                dst_address.clone()
+            }
             RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(mint_order)) => {
                 mint_order.recipient.clone()
             }
@@ -165,17 +335,75 @@ impl Operation for RuneBridgeOpImpl {
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { payload }) => {
                 payload.sender.clone()
             }
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitCosignatures {
+                from_address,
+                ..
+            }) => from_address.clone(),
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::SendTransaction {
                 from_address, ..
             }) => from_address.clone(),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                from_address,
+                ..
+            }) => from_address.clone(),
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionSent {
                 from_address, ..
             }) => from_address.clone(),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::TransactionConfirmed {
+                from_address,
+                ..
+            }) => from_address.clone(),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::BatchTransactionSent {
+                from_address,
+                ..
+            }) => from_address.clone(),
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                from_address,
+                ..
+            }) => from_address.clone(),
+            // utxo merges are made by the bridge itself, the same as its change address
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitUtxoMergeConfirmation { .. }) => {
+                H160::default()
+            }
         }
     }
 
     fn scheduling_options(&self) -> Option<ic_task_scheduler::task::TaskOptions> {
         match self.0 {
+            // Scheduled again when the co-signed PSBT is submitted
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitCosignatures { .. }) => None,
+            // Bitcoin transactions may stay unconfirmed for days, so they are polled until they
+            // are confirmed. The polling keeps their inputs from being released by the
+            // `RemoveUsedUtxosTask`, so it must be more frequent than the blocks.
+            RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                ..
+            })
+            | RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                ..
+            })
+            | RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::AwaitUtxoMergeConfirmation { .. }) => {
+                let network = get_rune_state().borrow().network();
+
+                match network {
+                    Network::Bitcoin => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(u32::MAX)
+                            .with_fixed_backoff_policy(300), // half a block
+                    ),
+                    _ => Some(
+                        TaskOptions::new()
+                            .with_max_retries_policy(u32::MAX)
+                            .with_fixed_backoff_policy(10),
+                    ),
+                }
+            }
+            // The refund fee is paid from the BTC at the deposit address, which the user may
+            // need to top up, so the refund is retried until it succeeds.
+            RuneBridgeOp::Deposit(RuneBridgeDepositOp::RefundRunes { .. }) => Some(
+                TaskOptions::new()
+                    .with_max_retries_policy(u32::MAX)
+                    .with_fixed_backoff_policy(600),
+            ),
             RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::SendTransaction { .. })
             | RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { .. }) => Some(
                 TaskOptions::new()
@@ -229,6 +457,8 @@ impl RuneBridgeOpImpl {
         dst_address: H160,
         dst_tokens: HashMap<RuneName, H160>,
         requested_amounts: Option<HashMap<RuneName, u128>>,
+        deposit_fee: Option<u64>,
+        refund_address: Option<String>,
     ) -> BTFResult<Self> {
         let inputs = input_provider
             .get_inputs(&dst_address)
@@ -261,36 +491,152 @@ impl RuneBridgeOpImpl {
 
         let mut operations = vec![];
         for input in inputs.inputs.iter() {
-            let infos = input_provider
-                .get_rune_infos(&input.runes)
-                .await
-                .ok_or_else(|| Error::FailedToProgress("rune info not found".into()))?;
-            let mut runes_to_wrap = vec![];
-            for (rune_info, amount) in infos.into_iter() {
-                let dst_token =
-                    dst_tokens
-                        .get(&rune_info.name())
-                        .ok_or(Error::FailedToProgress(format!(
-                            "wrapped token address for rune {} not found",
-                            rune_info.name()
-                        )))?;
-                runes_to_wrap.push(RuneToWrap {
-                    rune_info,
-                    amount,
-                    wrapped_address: dst_token.clone(),
-                });
+            operations.push(
+                Self::deposit_operation(
+                    input_provider,
+                    &dst_address,
+                    &dst_tokens,
+                    input,
+                    deposit_fee,
+                    refund_address.as_deref(),
+                )
+                .await?,
+            );
+        }
+
+        Ok(Self::split(state.clone(), operations))
+    }
+
+    /// Creates the operation awaiting the confirmations of the deposited input.
+    ///
+    /// If the refund address is given, the runes of the input without a wrapped token are
+    /// refunded to it, while the other runes are wrapped. Otherwise, all the runes of the input
+    /// must have a wrapped token.
+    async fn deposit_operation(
+        input_provider: &impl RuneInputProvider,
+        dst_address: &H160,
+        dst_tokens: &HashMap<RuneName, H160>,
+        input: &RuneInput,
+        deposit_fee: Option<u64>,
+        refund_address: Option<&str>,
+    ) -> BTFResult<Self> {
+        let infos = input_provider
+            .get_rune_infos(&input.runes)
+            .await
+            .ok_or_else(|| Error::FailedToProgress("rune info not found".into()))?;
+        let mut runes_to_wrap = vec![];
+        let mut runes_to_refund = vec![];
+        for (rune_info, amount) in infos.into_iter() {
+            if refund_address.is_some() && !Self::has_wrapped_token(dst_tokens, rune_info) {
+                runes_to_refund.push(RuneToRefund { rune_info, amount });
+                continue;
             }
 
-            operations.push(Self(RuneBridgeOp::Deposit(
-                RuneBridgeDepositOp::AwaitConfirmations {
-                    dst_address: dst_address.clone(),
-                    utxo: input.utxo.clone(),
-                    runes_to_wrap,
-                },
+            let wrapped_address = Self::deposit_token_address(dst_tokens, rune_info)?;
+            runes_to_wrap.push(RuneToWrap {
+                rune_info,
+                amount,
+                wrapped_address,
+            });
+        }
+
+        let refund = match refund_address {
+            Some(address) if !runes_to_refund.is_empty() => {
+                Self::validate_refund_address(address)?;
+                Some(RuneRefund {
+                    address: address.to_string(),
+                    runes: runes_to_refund,
+                })
+            }
+            _ => None,
+        };
+
+        Ok(Self(RuneBridgeOp::Deposit(
+            RuneBridgeDepositOp::AwaitConfirmations {
+                dst_address: dst_address.clone(),
+                utxo: input.utxo.clone(),
+                runes_to_wrap,
+                deposit_fee,
+                refund,
+            },
+        )))
+    }
+
+    /// Checks whether the deposited rune can be exchanged for an EVM token.
+    fn has_wrapped_token(dst_tokens: &HashMap<RuneName, H160>, rune_info: RuneInfo) -> bool {
+        dst_tokens.contains_key(&rune_info.name())
+            || get_rune_state()
+                .borrow()
+                .evm_native_token_by_rune(rune_info.id())
+                .is_some()
+    }
+
+    /// Checks that the refund address is a valid address of the bridge network.
+    fn validate_refund_address(address: &str) -> BTFResult<()> {
+        let network = get_rune_state().borrow().network();
+        Address::from_str(address)
+            .map_err(|err| err.to_string())
+            .and_then(|address| {
+                address
+                    .require_network(network)
+                    .map_err(|err| err.to_string())
+            })
+            .map_err(|err| {
+                Error::CannotProgress(format!("invalid refund address {address}: {err}"))
+            })?;
+
+        Ok(())
+    }
+
+    /// Returns the address of the EVM token to be minted for the deposited rune.
+    ///
+    /// Runes etched by the bridge for EVM-native tokens are always exchanged for their ERC20
+    /// tokens, which are released by the base-side BTF bridge instead of being minted. An ERC20
+    /// token represented by a rune cannot be used as a wrapped token of another rune.
+    fn deposit_token_address(
+        dst_tokens: &HashMap<RuneName, H160>,
+        rune_info: RuneInfo,
+    ) -> BTFResult<H160> {
+        let state = get_rune_state();
+        if let Some(token) = state.borrow().evm_native_token_by_rune(rune_info.id()) {
+            if get_base_bridge_config()
+                .borrow()
+                .get_btf_bridge_contract()
+                .is_none()
+            {
+                return Err(Error::FailedToProgress(format!(
+                    "base-side BTF bridge releasing the token {} is not linked",
+                    token.erc20
+                )));
+            }
+
+            return Ok(token.erc20);
+        }
+
+        let dst_token = dst_tokens
+            .get(&rune_info.name())
+            .ok_or(Error::FailedToProgress(format!(
+                "wrapped token address for rune {} not found",
+                rune_info.name()
+            )))?;
+
+        if state.borrow().evm_native_token(dst_token).is_some() {
+            return Err(Error::CannotProgress(format!(
+                "token {dst_token} is represented by another rune than {}",
+                rune_info.name()
             )));
         }
 
-        Ok(Self::split(state.clone(), operations))
+        Ok(dst_token.clone())
+    }
+
+    /// Checks whether the mint order of the given token is processed by the base-side BTF bridge,
+    /// which releases the locked EVM-native tokens.
+    fn is_released_by_base_bridge(dst_token: &H160) -> bool {
+        get_rune_state()
+            .borrow()
+            .evm_native_token(dst_token)
+            .is_some()
     }
 
     async fn await_confirmations(
@@ -299,18 +645,24 @@ impl RuneBridgeOpImpl {
         dst_address: H160,
         utxo: Utxo,
         runes_to_wrap: Vec<RuneToWrap>,
+        refund: Option<RuneRefund>,
     ) -> BTFResult<Self> {
         utxo_handler
             .check_confirmations(&dst_address, &utxo)
             .await
             .map_err(|err| Error::FailedToProgress(err.to_string()))?;
 
+        let wrapped_runes: Vec<_> = runes_to_wrap.iter().map(|rune| rune.rune_info).collect();
+        let refunded_runes = refund
+            .as_ref()
+            .map(|refund| refund.runes.iter().map(|rune| rune.rune_info).collect())
+            .unwrap_or_default();
         let mint_orders = utxo_handler
-            .deposit(&utxo, &dst_address, runes_to_wrap)
+            .deposit(&utxo, &dst_address, runes_to_wrap, refunded_runes)
             .await
             .map_err(|err| Error::FailedToProgress(err.to_string()))?;
 
-        let operations = mint_orders
+        let mut operations: Vec<_> = mint_orders
             .into_iter()
             .map(|mint_order| {
                 Self(RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(
@@ -318,15 +670,59 @@ impl RuneBridgeOpImpl {
                 )))
             })
             .collect();
+        if let Some(refund) = refund {
+            operations.push(Self(RuneBridgeOp::Deposit(
+                RuneBridgeDepositOp::RefundRunes {
+                    dst_address,
+                    utxo,
+                    refund,
+                    wrapped_runes,
+                },
+            )));
+        }
 
         Ok(Self::split(ctx, operations))
     }
 
+    /// Sends the refunded runes of the deposited utxo back to the user.
+    async fn refund_runes(
+        dst_address: H160,
+        utxo: Utxo,
+        refund: RuneRefund,
+        wrapped_runes: Vec<RuneInfo>,
+    ) -> BTFResult<Self> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+        let (transaction, inputs) = withdraw
+            .create_refund_transaction(&dst_address, &utxo, &refund)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("cannot create refund transaction: {err:?}"))
+            })?;
+        withdraw
+            .send_refund_transaction(transaction.clone(), wrapped_runes)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to send refund transaction: {err:?}"))
+            })?;
+
+        Ok(Self(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                from_address: dst_address,
+                transaction: transaction.into(),
+                inputs: inputs.iter().map(did_tx_input).collect(),
+                sent_at: ic::time(),
+                replaced_txids: vec![],
+                child_fee: None,
+            },
+        )))
+    }
+
     async fn create_withdrawal_transaction(payload: RuneWithdrawalPayload) -> BTFResult<Self> {
         let withdraw = Withdrawal::get()
             .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
         let from_address = payload.sender.clone();
-        let transaction = withdraw
+        let (transaction, inputs) = withdraw
             .create_withdrawal_transaction(payload)
             .await
             .map_err(|err| {
@@ -337,11 +733,39 @@ impl RuneBridgeOpImpl {
             RuneBridgeWithdrawOp::SendTransaction {
                 from_address,
                 transaction: transaction.into(),
+                inputs: Some(inputs.iter().map(did_tx_input).collect()),
             },
         )))
     }
 
-    async fn send_transaction(from_address: H160, transaction: DidTransaction) -> BTFResult<Self> {
+    /// Creates the unsigned withdrawal transaction to be co-signed by the external keys.
+    async fn create_uncosigned_withdrawal(payload: RuneWithdrawalPayload) -> BTFResult<Self> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+        let from_address = payload.sender.clone();
+        let (transaction, inputs) = withdraw
+            .create_unsigned_withdrawal_transaction(payload)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!(
+                    "cannot create unsigned withdrawal transaction: {err:?}"
+                ))
+            })?;
+
+        Ok(Self(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::AwaitCosignatures {
+                from_address,
+                transaction: transaction.into(),
+                inputs: inputs.iter().map(did_tx_input).collect(),
+            },
+        )))
+    }
+
+    async fn send_transaction(
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Option<Vec<DidTxInput>>,
+    ) -> BTFResult<Self> {
         let withdraw = Withdrawal::get()
             .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
         withdraw
@@ -351,13 +775,365 @@ impl RuneBridgeOpImpl {
                 Error::FailedToProgress(format!("failed to send transaction: {err:?}"))
             })?;
 
+        let Some(inputs) = inputs else {
+            return Ok(Self(RuneBridgeOp::Withdraw(
+                RuneBridgeWithdrawOp::TransactionSent {
+                    from_address,
+                    transaction,
+                },
+            )));
+        };
+
         Ok(Self(RuneBridgeOp::Withdraw(
-            RuneBridgeWithdrawOp::TransactionSent {
+            RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
                 from_address,
                 transaction,
+                inputs,
+                sent_at: ic::time(),
+                replaced_txids: vec![],
+                child_fee: None,
             },
         )))
     }
+
+    /// Waits for the withdrawal transaction to be included into a block. If it is not included
+    /// for `FEE_BUMP_TIMEOUT`, bumps its fee.
+    async fn await_transaction_confirmation(
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+        sent_at: u64,
+        mut replaced_txids: Vec<H256>,
+        child_fee: Option<u64>,
+    ) -> BTFResult<Self> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        let tx = Transaction::from(transaction);
+        let tx_inputs = Self::tx_inputs(&inputs)?;
+        let replaced = Self::txids(&replaced_txids)?;
+
+        let status = withdraw
+            .transaction_status(&tx, &tx_inputs, &replaced)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to check transaction status: {err:?}"))
+            })?;
+
+        let confirmed_change = match status {
+            TransactionStatus::Confirmed(change_utxo) => Some(change_utxo),
+            TransactionStatus::InputsSpent => None,
+            TransactionStatus::Pending => {
+                withdraw.refresh_inputs(&tx_inputs);
+
+                let pending_for = Duration::from_nanos(ic::time().saturating_sub(sent_at));
+                if pending_for < FEE_BUMP_TIMEOUT {
+                    return Err(Error::FailedToProgress(format!(
+                        "transaction {} is not confirmed yet",
+                        tx.txid()
+                    )));
+                }
+
+                let change_owners =
+                    withdraw
+                        .sender_change_owners(&from_address)
+                        .await
+                        .map_err(|err| {
+                            Error::FailedToProgress(format!("cannot get change addresses: {err:?}"))
+                        })?;
+                let fee_bump =
+                    Self::bump_fee(&withdraw, &tx, &tx_inputs, 1, &change_owners, child_fee)
+                        .await?;
+
+                let (transaction, child_fee) = match fee_bump {
+                    BumpedTransaction::Replacement(replacement) => {
+                        replaced_txids.push(H256::from_slice(tx.txid().as_byte_array()));
+                        (replacement, None)
+                    }
+                    BumpedTransaction::Child(fee) => (tx, Some(fee)),
+                };
+
+                return Ok(Self(RuneBridgeOp::Withdraw(
+                    RuneBridgeWithdrawOp::AwaitTransactionConfirmation {
+                        from_address,
+                        transaction: transaction.into(),
+                        inputs,
+                        sent_at: ic::time(),
+                        replaced_txids,
+                        child_fee,
+                    },
+                )));
+            }
+        };
+
+        let txid = confirmed_change
+            .as_ref()
+            .map(|utxo| H256::from_slice(&utxo.outpoint.txid))
+            .unwrap_or_else(|| H256::from_slice(tx.txid().as_byte_array()));
+        withdraw.complete_transaction(&tx, &tx_inputs, confirmed_change);
+
+        log::info!("Withdrawal transaction {txid} is confirmed");
+
+        Ok(Self(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::TransactionConfirmed { from_address, txid },
+        )))
+    }
+
+    /// Waits for the transaction shared by the withdrawals of a batch to be included into a
+    /// block.
+    ///
+    /// If it is not included for `FEE_BUMP_TIMEOUT`, the first withdrawal of the batch bumps its
+    /// fee and moves the other withdrawals to the replacement, so the transaction is bumped only
+    /// once.
+    #[allow(clippy::too_many_arguments)]
+    async fn await_batch_transaction_confirmation(
+        ctx: RuntimeState<Self>,
+        id: OperationId,
+        from_address: H160,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+        fee_share: u64,
+        batch_operations: Vec<OperationId>,
+        sent_at: u64,
+        mut replaced_txids: Vec<H256>,
+        child_fee: Option<u64>,
+    ) -> BTFResult<Self> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        let tx = Transaction::from(transaction);
+        let tx_inputs = Self::tx_inputs(&inputs)?;
+        let replaced = Self::txids(&replaced_txids)?;
+        let status = withdraw
+            .transaction_status(&tx, &tx_inputs, &replaced)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to check transaction status: {err:?}"))
+            })?;
+
+        let confirmed_change = match status {
+            TransactionStatus::Confirmed(change_utxo) => Some(change_utxo),
+            TransactionStatus::InputsSpent => None,
+            TransactionStatus::Pending => {
+                withdraw.refresh_inputs(&tx_inputs);
+
+                let pending_for = Duration::from_nanos(ic::time().saturating_sub(sent_at));
+                if batch_operations.first() != Some(&id) || pending_for < FEE_BUMP_TIMEOUT {
+                    return Err(Error::FailedToProgress(format!(
+                        "batch transaction {} is not confirmed yet",
+                        tx.txid()
+                    )));
+                }
+
+                let fee_bump = Self::bump_fee(
+                    &withdraw,
+                    &tx,
+                    &tx_inputs,
+                    batch_operations.len(),
+                    &input_owners(&tx_inputs),
+                    child_fee,
+                )
+                .await?;
+
+                let (transaction, child_fee) = match fee_bump {
+                    BumpedTransaction::Replacement(replacement) => {
+                        replaced_txids.push(H256::from_slice(tx.txid().as_byte_array()));
+                        Self::move_batch_to_replacement(
+                            &ctx,
+                            &batch_operations[1..],
+                            tx.txid(),
+                            &replacement,
+                        );
+                        (replacement, None)
+                    }
+                    BumpedTransaction::Child(fee) => (tx, Some(fee)),
+                };
+
+                return Ok(Self(RuneBridgeOp::Withdraw(
+                    RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                        from_address,
+                        transaction: transaction.into(),
+                        inputs,
+                        fee_share,
+                        batch_operations,
+                        sent_at: ic::time(),
+                        replaced_txids,
+                        child_fee,
+                    },
+                )));
+            }
+        };
+
+        let txid = confirmed_change
+            .as_ref()
+            .map(|utxo| H256::from_slice(&utxo.outpoint.txid))
+            .unwrap_or_else(|| H256::from_slice(tx.txid().as_byte_array()));
+        withdraw.complete_transaction(&tx, &tx_inputs, confirmed_change);
+
+        log::info!("Batch withdrawal transaction {txid} is confirmed");
+
+        Ok(Self(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::TransactionConfirmed { from_address, txid },
+        )))
+    }
+
+    /// Bumps the fee of the stuck transaction and sends the bumping transaction.
+    async fn bump_fee(
+        withdraw: &Withdrawal,
+        tx: &Transaction,
+        tx_inputs: &[TxInputInfo],
+        destinations: usize,
+        change_owners: &[(ScriptBuf, DerivationPath)],
+        child_fee: Option<u64>,
+    ) -> BTFResult<BumpedTransaction> {
+        let fee_bump = withdraw
+            .bump_fee(tx, tx_inputs, destinations, change_owners, child_fee)
+            .await
+            .map_err(|err| Error::FailedToProgress(format!("cannot bump fee: {err:?}")))?;
+
+        match fee_bump {
+            FeeBump::Replacement(replacement) => {
+                withdraw
+                    .send_replacement_transaction(tx.txid(), replacement.clone())
+                    .await
+                    .map_err(|err| {
+                        Error::FailedToProgress(format!(
+                            "failed to send replacement transaction: {err:?}"
+                        ))
+                    })?;
+
+                Ok(BumpedTransaction::Replacement(replacement))
+            }
+            FeeBump::Child {
+                transaction,
+                inputs,
+                fee,
+            } => {
+                withdraw
+                    .send_child_transaction(&transaction, &inputs)
+                    .await
+                    .map_err(|err| {
+                        Error::FailedToProgress(format!(
+                            "failed to send child transaction: {err:?}"
+                        ))
+                    })?;
+
+                Ok(BumpedTransaction::Child(fee))
+            }
+        }
+    }
+
+    /// Moves the other withdrawals of the batch from the replaced transaction to its
+    /// replacement.
+    fn move_batch_to_replacement(
+        ctx: &RuntimeState<Self>,
+        operations: &[OperationId],
+        replaced_txid: Txid,
+        replacement: &Transaction,
+    ) {
+        let mut state = ctx.borrow_mut();
+        for id in operations {
+            let Some(Self(RuneBridgeOp::Withdraw(
+                RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                    from_address,
+                    transaction,
+                    inputs,
+                    fee_share,
+                    batch_operations,
+                    mut replaced_txids,
+                    ..
+                },
+            ))) = state.operations.get(*id)
+            else {
+                log::warn!("Batch withdrawal {id} is not awaiting the batch transaction");
+                continue;
+            };
+
+            if Transaction::from(transaction).txid() != replaced_txid {
+                log::warn!("Batch withdrawal {id} awaits another transaction");
+                continue;
+            }
+
+            replaced_txids.push(H256::from_slice(replaced_txid.as_byte_array()));
+            state.operations.update(
+                *id,
+                Self(RuneBridgeOp::Withdraw(
+                    RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                        from_address,
+                        transaction: replacement.clone().into(),
+                        inputs,
+                        fee_share,
+                        batch_operations,
+                        sent_at: ic::time(),
+                        replaced_txids,
+                        child_fee: None,
+                    },
+                )),
+            );
+        }
+    }
+
+    /// Waits for the transaction merging the utxos of the bridge to be included into a block.
+    async fn await_utxo_merge_confirmation(
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+    ) -> BTFResult<Self> {
+        let withdraw = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+
+        let tx = Transaction::from(transaction);
+        let tx_inputs = Self::tx_inputs(&inputs)?;
+        let status = withdraw
+            .utxo_merge_status(&tx, &tx_inputs)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to check transaction status: {err:?}"))
+            })?;
+
+        if let TransactionStatus::Pending = status {
+            withdraw.refresh_inputs(&tx_inputs);
+            return Err(Error::FailedToProgress(format!(
+                "utxo merge transaction {} is not confirmed yet",
+                tx.txid()
+            )));
+        }
+        // the merged utxo is never replaced, so it is already in the ledger
+        withdraw.complete_transaction(&tx, &tx_inputs, None);
+
+        let txid = H256::from_slice(tx.txid().as_byte_array());
+        log::info!("Utxo merge transaction {txid} is confirmed");
+
+        Ok(Self(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::TransactionConfirmed {
+                from_address: H160::default(),
+                txid,
+            },
+        )))
+    }
+
+    fn txids(txids: &[H256]) -> BTFResult<Vec<Txid>> {
+        txids
+            .iter()
+            .map(|txid| Txid::from_slice(txid.0.as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::CannotProgress(format!("invalid transaction id: {err:?}")))
+    }
+
+    fn tx_inputs(inputs: &[DidTxInput]) -> BTFResult<Vec<TxInputInfo>> {
+        inputs
+            .iter()
+            .map(tx_input_info)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::CannotProgress(format!("invalid transaction input: {err:?}")))
+    }
+}
+
+/// Transaction sent to bump the fee of a stuck withdrawal transaction.
+enum BumpedTransaction {
+    /// The transaction replacing the stuck one.
+    Replacement(Transaction),
+    /// The child transaction paying for the stuck one, with its fee.
+    Child(u64),
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -365,6 +1141,8 @@ pub struct RuneDepositRequestData {
     pub dst_address: H160,
     pub dst_tokens: HashMap<RuneName, H160>,
     pub amounts: Option<HashMap<RuneName, u128>>,
+    /// Bitcoin address to refund the deposited runes without a wrapped token to.
+    pub refund_address: Option<String>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,205 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{RuneBridgeOp, RuneBridgeWithdrawOp};
+use bridge_did::runes::{DidTransaction, DidTxInput, RuneWithdrawalPayload};
+use ic_exports::ic_kit::ic;
+
+use super::RuneBridgeOpImpl;
+use crate::canister::{get_rune_state, get_runtime};
+use crate::core::deposit::RuneDeposit;
+use crate::core::withdrawal::{Withdrawal, did_tx_input};
+
+/// Service to send pending withdrawals in batch transactions.
+///
+/// Withdrawals are accumulated until the configured number of them is pending or the batch
+/// window since the first pending withdrawal has elapsed.
+pub struct BatchWithdrawalService {
+    state: RuntimeState<RuneBridgeOpImpl>,
+    pending: RefCell<BTreeMap<OperationId, RuneWithdrawalPayload>>,
+    /// Timestamp of the first pending withdrawal in nanoseconds.
+    first_pushed_at: Cell<Option<u64>>,
+}
+
+impl BatchWithdrawalService {
+    /// Creates a new instance of BatchWithdrawalService.
+    pub fn new(state: RuntimeState<RuneBridgeOpImpl>) -> Self {
+        Self {
+            state,
+            pending: Default::default(),
+            first_pushed_at: Default::default(),
+        }
+    }
+
+    /// Checks if the pending withdrawals should be sent now.
+    fn batch_ready(&self, now: u64) -> bool {
+        let pending_count = self.pending.borrow().len();
+        if pending_count == 0 {
+            return false;
+        }
+
+        let Some(config) = get_rune_state().borrow().withdrawal_batch_config() else {
+            // batching was turned off, send everything that is left
+            return true;
+        };
+
+        let window_elapsed = self
+            .first_pushed_at
+            .get()
+            .map(|first| now.saturating_sub(first) >= config.window_secs * 1_000_000_000)
+            .unwrap_or(true);
+
+        pending_count >= config.max_batch_size as usize || window_elapsed
+    }
+
+    fn max_batch_size(&self) -> usize {
+        get_rune_state()
+            .borrow()
+            .withdrawal_batch_config()
+            .map(|config| config.max_batch_size.max(1) as usize)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Moves the withdrawal to the confirmation tracking of the sent batch transaction.
+    fn track_batch_transaction(
+        &self,
+        id: OperationId,
+        transaction: DidTransaction,
+        inputs: Vec<DidTxInput>,
+        fee_share: u64,
+        batch_operations: Vec<OperationId>,
+    ) {
+        let Some(op) = self.state.borrow().operations.get(id) else {
+            log::info!("Batch withdrawal service failed to update operation {id}: not found.");
+            return;
+        };
+
+        let RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { payload }) = op.0
+        else {
+            log::error!(
+                "Batch withdrawal service failed to update operation {id}: unexpected state"
+            );
+            return;
+        };
+
+        let operation = RuneBridgeOpImpl(RuneBridgeOp::Withdraw(
+            RuneBridgeWithdrawOp::AwaitBatchTransactionConfirmation {
+                from_address: payload.sender,
+                transaction,
+                inputs,
+                fee_share,
+                batch_operations,
+                sent_at: ic::time(),
+                replaced_txids: vec![],
+                child_fee: None,
+            },
+        ));
+        self.state
+            .borrow_mut()
+            .operations
+            .update(id, operation.clone());
+        get_runtime().borrow().schedule_operation(id, operation);
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for BatchWithdrawalService {
+    fn push_operation(&self, id: OperationId) -> BTFResult<()> {
+        let op = self
+            .state
+            .borrow()
+            .operations
+            .get(id)
+            .ok_or(Error::OperationNotFound(id))?;
+
+        let RuneBridgeOp::Withdraw(RuneBridgeWithdrawOp::CreateTransaction { payload }) = op.0
+        else {
+            return Err(Error::FailedToProgress(format!(
+                "operation {id} is not a pending withdrawal"
+            )));
+        };
+
+        let mut pending = self.pending.borrow_mut();
+        if pending.is_empty() {
+            self.first_pushed_at.set(Some(ic::time()));
+        }
+        pending.insert(id, payload);
+
+        Ok(())
+    }
+
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running BatchWithdrawalService");
+
+        if !self.batch_ready(ic::time()) {
+            log::trace!("Withdrawal batch is not ready yet.");
+            return Ok(());
+        }
+
+        let batch: Vec<(OperationId, RuneWithdrawalPayload)> = self
+            .pending
+            .borrow()
+            .iter()
+            .take(self.max_batch_size())
+            .map(|(id, payload)| (*id, payload.clone()))
+            .collect();
+        let payloads: Vec<RuneWithdrawalPayload> =
+            batch.iter().map(|(_, payload)| payload.clone()).collect();
+
+        log::trace!("Sending batch of {} withdrawals.", batch.len());
+
+        let withdrawal = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+        let input_provider = RuneDeposit::get(self.state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        let batch_tx = withdrawal
+            .create_batch_transaction(&payloads, &input_provider)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("cannot create batch transaction: {err:?}"))
+            })?;
+        withdrawal
+            .send_batch_transaction(&batch_tx)
+            .await
+            .map_err(|err| {
+                Error::FailedToProgress(format!("failed to send batch transaction: {err:?}"))
+            })?;
+
+        let txid = batch_tx.transaction.txid();
+        let transaction = DidTransaction::from(batch_tx.transaction.clone());
+        let inputs: Vec<DidTxInput> = batch_tx.inputs.iter().map(did_tx_input).collect();
+        let batch_operations: Vec<OperationId> = batch_tx
+            .fee_shares
+            .iter()
+            .map(|(index, _)| batch[*index].0)
+            .collect();
+        {
+            let mut pending = self.pending.borrow_mut();
+            for (id, (_, fee_share)) in batch_operations.iter().zip(&batch_tx.fee_shares) {
+                pending.remove(id);
+                self.track_batch_transaction(
+                    *id,
+                    transaction.clone(),
+                    inputs.clone(),
+                    *fee_share,
+                    batch_operations.clone(),
+                );
+            }
+
+            // withdrawals left out of the batch wait for the next one
+            self.first_pushed_at
+                .set((!pending.is_empty()).then(ic::time));
+        }
+
+        log::debug!(
+            "Batch transaction {txid} sent for {} withdrawals.",
+            batch_tx.fee_shares.len()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+use did::H160;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::Outpoint;
+
+use super::RuneBridgeOpImpl;
+use crate::canister::{get_rune_state, get_runtime};
+use crate::core::deposit::RuneDeposit;
+use crate::core::rune_inputs::RuneInput;
+use crate::state::WatchedAddress;
+
+/// Service to detect the deposits without the deposit requests of the users.
+///
+/// Scans the registered deposit addresses and creates a deposit operation for every new utxo
+/// holding runes. Does nothing unless the deposit watcher is configured.
+pub struct DepositWatcherService {
+    runtime_state: RuntimeState<RuneBridgeOpImpl>,
+}
+
+impl DepositWatcherService {
+    pub fn new(runtime_state: RuntimeState<RuneBridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+
+    /// Creates deposit operations for the new utxos at the address. Returns the utxos of the
+    /// address handled by the watcher.
+    async fn scan_address(
+        &self,
+        deposit: &RuneDeposit,
+        eth_address: &H160,
+        watched: WatchedAddress,
+    ) -> Vec<Outpoint> {
+        let (utxos, tip_height) = match deposit.get_address_utxos(eth_address).await {
+            Ok(utxos) => utxos,
+            Err(err) => {
+                log::warn!("Failed to get utxos of the deposit address of {eth_address}: {err}");
+                return watched.known_utxos;
+            }
+        };
+
+        // forget the spent utxos
+        let mut known_utxos = watched.known_utxos;
+        known_utxos.retain(|outpoint| utxos.iter().any(|utxo| utxo.outpoint == *outpoint));
+
+        let min_confirmations = get_rune_state().borrow().min_confirmations();
+        for utxo in utxos {
+            if known_utxos.contains(&utxo.outpoint) {
+                continue;
+            }
+
+            let runes = match deposit.get_rune_amounts(&utxo).await {
+                Ok(runes) => runes,
+                Err(err) => {
+                    log::debug!("Failed to get runes of utxo {:?}: {err}", utxo.outpoint);
+                    continue;
+                }
+            };
+
+            if runes.is_empty() {
+                // the indexers may not know about the runes of a fresh utxo yet
+                let confirmations = (tip_height + 1).saturating_sub(utxo.height);
+                if confirmations >= min_confirmations {
+                    known_utxos.push(utxo.outpoint);
+                }
+                continue;
+            }
+
+            let outpoint = utxo.outpoint.clone();
+            let input = RuneInput { utxo, runes };
+            let deposit_fee = get_rune_state().borrow().deposit_fee_quote().fee;
+            match RuneBridgeOpImpl::deposit_operation(
+                deposit,
+                eth_address,
+                &watched.dst_tokens,
+                &input,
+                Some(deposit_fee),
+                watched.refund_address.as_deref(),
+            )
+            .await
+            {
+                Ok(operation) => {
+                    let id = self
+                        .runtime_state
+                        .borrow_mut()
+                        .operations
+                        .new_operation(operation.clone(), None);
+                    get_runtime().borrow().schedule_operation(id, operation);
+                    log::info!(
+                        "Created deposit operation {id} for utxo {outpoint:?} of {eth_address}"
+                    );
+                }
+                Err(err) => {
+                    // the user can register the address again with the missing wrapped tokens
+                    log::warn!("Cannot deposit utxo {outpoint:?} of {eth_address}: {err:?}");
+                }
+            }
+            known_utxos.push(outpoint);
+        }
+
+        known_utxos
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for DepositWatcherService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running DepositWatcherService");
+
+        let Some(config) = get_rune_state().borrow().deposit_watcher_config() else {
+            return Ok(());
+        };
+
+        let addresses = get_rune_state()
+            .borrow_mut()
+            .next_watched_addresses(&config);
+        if addresses.is_empty() {
+            return Ok(());
+        }
+
+        let deposit = RuneDeposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        for (eth_address, watched) in addresses {
+            let known_utxos = self.scan_address(&deposit, &eth_address, watched).await;
+            get_rune_state()
+                .borrow_mut()
+                .set_known_deposit_utxos(&eth_address, known_utxos);
+        }
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the DepositWatcherService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
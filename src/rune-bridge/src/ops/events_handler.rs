@@ -14,13 +14,23 @@ use crate::core::withdrawal::RuneWithdrawalPayloadImpl;
 use crate::ops::RuneDepositRequestData;
 use crate::state::RuneState;
 
+/// Side of the BTF bridge, the events of which are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BtfBridgeSide {
+    /// Wrapped-side bridge, which mints and burns the wrapped tokens of the runes.
+    Wrapped,
+    /// Base-side bridge, which locks and releases the EVM-native tokens etched as runes.
+    Base,
+}
+
 pub struct RuneEventsHandler {
     rune_state: Rc<RefCell<RuneState>>,
+    side: BtfBridgeSide,
 }
 
 impl RuneEventsHandler {
-    pub fn new(rune_state: Rc<RefCell<RuneState>>) -> Self {
-        Self { rune_state }
+    pub fn new(rune_state: Rc<RefCell<RuneState>>, side: BtfBridgeSide) -> Self {
+        Self { rune_state, side }
     }
 
     pub fn on_deposit_notification(
@@ -34,6 +44,8 @@ impl RuneEventsHandler {
                         dst_address: data.dst_address,
                         dst_tokens: data.dst_tokens,
                         requested_amounts: data.amounts,
+                        deposit_fee: Some(self.rune_state.borrow().deposit_fee_quote().fee),
+                        refund_address: data.refund_address,
                     }));
                 Some(OperationAction::Create(operation, event.memo()))
             }
@@ -67,6 +79,23 @@ impl BtfBridgeEventHandler<RuneBridgeOpImpl> for RuneEventsHandler {
         event: BurntEventData,
     ) -> Option<OperationAction<RuneBridgeOpImpl>> {
         log::debug!("on_wrapped_token_burnt {event:?}");
+
+        // EVM-native tokens are locked only by the base-side bridge, and the wrapped tokens are
+        // burnt only by the wrapped-side one.
+        let is_evm_native = self
+            .rune_state
+            .borrow()
+            .evm_native_token(&event.from_erc20)
+            .is_some();
+        if is_evm_native != (self.side == BtfBridgeSide::Base) {
+            log::warn!(
+                "Token {} cannot be bridged by the {:?}-side BTF bridge",
+                event.from_erc20,
+                self.side
+            );
+            return None;
+        }
+
         let memo = event.memo();
         match RuneWithdrawalPayloadImpl::new(event, &self.rune_state.borrow()) {
             Ok(payload) => {
@@ -0,0 +1,58 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+
+use super::RuneBridgeOpImpl;
+use crate::canister::get_rune_state;
+use crate::core::deposit::RuneDeposit;
+
+/// Service to complete the etchings of the runes representing EVM-native tokens.
+///
+/// Once an etching transaction is confirmed, the premined runes are added to the ledger, so they
+/// can be issued to the users locking the ERC20 tokens.
+pub struct EvmTokenEtchingService {
+    runtime_state: RuntimeState<RuneBridgeOpImpl>,
+}
+
+impl EvmTokenEtchingService {
+    pub fn new(runtime_state: RuntimeState<RuneBridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for EvmTokenEtchingService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running EvmTokenEtchingService");
+
+        let pending: Vec<_> = get_rune_state()
+            .borrow()
+            .evm_native_tokens()
+            .into_iter()
+            .filter(|token| token.rune_info().is_none())
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let deposit = RuneDeposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        for token in pending {
+            if let Err(err) = deposit.confirm_evm_token_etching(&token).await {
+                log::warn!(
+                    "Failed to confirm the rune etching of the token {}: {err}",
+                    token.erc20
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the EvmTokenEtchingService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
@@ -13,12 +13,14 @@ use super::RuneBridgeOpImpl;
 /// Allows MintTxService to handle MintTx of Rune bridge.
 pub struct RuneMintTxHandler {
     state: RuntimeState<RuneBridgeOpImpl>,
+    config: SharedConfig,
 }
 
 impl RuneMintTxHandler {
-    /// Creates a new instance of RuneMintTxHandler.
-    pub fn new(state: RuntimeState<RuneBridgeOpImpl>) -> Self {
-        Self { state }
+    /// Creates a new instance of RuneMintTxHandler, which sends the mint transactions to the
+    /// BTF bridge of the given config.
+    pub fn new(state: RuntimeState<RuneBridgeOpImpl>, config: SharedConfig) -> Self {
+        Self { state, config }
     }
 }
 
@@ -28,7 +30,7 @@ impl MintTxHandler for RuneMintTxHandler {
     }
 
     fn get_evm_config(&self) -> SharedConfig {
-        self.state.borrow().config.clone()
+        self.config.clone()
     }
 
     fn get_signed_orders(&self, id: OperationId) -> Option<SignedOrders> {
@@ -0,0 +1,41 @@
+use bridge_canister::runtime::RuntimeState;
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+
+use super::RuneBridgeOpImpl;
+use crate::core::deposit::RuneDeposit;
+
+/// Service to add the recently etched runes to the rune info cache.
+pub struct RuneListRefreshService {
+    runtime_state: RuntimeState<RuneBridgeOpImpl>,
+}
+
+impl RuneListRefreshService {
+    pub fn new(runtime_state: RuntimeState<RuneBridgeOpImpl>) -> Self {
+        Self { runtime_state }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for RuneListRefreshService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running RuneListRefreshService");
+
+        let deposit = RuneDeposit::get(self.runtime_state.clone())
+            .map_err(|err| Error::FailedToProgress(format!("cannot get deposit: {err:?}")))?;
+        let count = deposit.refresh_rune_list().await.map_err(|err| {
+            Error::FailedToProgress(format!("failed to refresh rune list: {err:?}"))
+        })?;
+
+        log::debug!("Added {count} runes to the rune info cache.");
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the RuneListRefreshService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
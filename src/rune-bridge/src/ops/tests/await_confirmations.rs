@@ -47,6 +47,7 @@ async fn await_confirmations_utxo_not_found() {
         tests::sender(),
         get_utxo(),
         get_to_wrap(1),
+        None,
     )
     .await;
 
@@ -69,6 +70,7 @@ async fn await_confirmations_not_confirmed() {
         tests::sender(),
         get_utxo(),
         get_to_wrap(1),
+        None,
     )
     .await;
 
@@ -92,6 +94,7 @@ async fn await_confirmations_btc_adapter_not_available() {
         tests::sender(),
         get_utxo(),
         get_to_wrap(1),
+        None,
     )
     .await;
 
@@ -114,6 +117,7 @@ async fn await_confirmations_utxo_already_used() {
         tests::sender(),
         get_utxo(),
         get_to_wrap(1),
+        None,
     )
     .await;
 
@@ -1,7 +1,8 @@
 use std::str::FromStr;
 
 use bridge_did::error::Error;
-use bridge_did::runes::RuneName;
+use bridge_did::operations::{RuneBridgeDepositOp, RuneBridgeOp};
+use bridge_did::runes::{RuneName, RuneRefund, RuneToRefund};
 use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
 use snapbox::{assert_data_eq, str};
 
@@ -18,6 +19,8 @@ async fn await_inputs_returns_error_if_no_inputs() {
         tests::sender(),
         tests::dst_tokens(),
         None,
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -37,6 +40,8 @@ async fn await_inputs_returns_error_if_provider_returns_btc_error() {
         tests::sender(),
         tests::dst_tokens(),
         None,
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -62,6 +67,8 @@ async fn await_inputs_returns_error_if_provider_returns_indexer_error() {
         tests::sender(),
         tests::dst_tokens(),
         None,
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -88,6 +95,8 @@ async fn await_inputs_returns_error_if_provider_returns_consensus_error() {
         tests::sender(),
         tests::dst_tokens(),
         None,
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -126,6 +135,8 @@ async fn await_inputs_returns_error_if_wrong_amounts_one_utxo() {
         tests::sender(),
         tests::dst_tokens(),
         Some([(RuneName::from_str("A").unwrap(), 1500)].into()),
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -156,6 +167,8 @@ async fn await_inputs_returns_error_if_wrong_amounts_multiple_utxos() {
             ]
             .into(),
         ),
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -178,6 +191,8 @@ async fn await_inputs_cannot_progress_if_requested_less_than_actual_single_utxo(
         tests::sender(),
         tests::dst_tokens(),
         Some([(RuneName::from_str("A").unwrap(), 500)].into()),
+        None,
+        None,
     )
     .await;
     let Err(Error::CannotProgress(message)) = result else {
@@ -208,6 +223,8 @@ async fn await_inputs_cannot_progress_if_requested_less_than_actual_multiple_utx
             ]
             .into(),
         ),
+        None,
+        None,
     )
     .await;
     let Err(Error::CannotProgress(message)) = result else {
@@ -230,6 +247,8 @@ async fn await_inputs_cannot_progress_if_exists_utxo_that_is_not_requested() {
         tests::sender(),
         tests::dst_tokens(),
         Some([(RuneName::from_str("A").unwrap(), 1000)].into()),
+        None,
+        None,
     )
     .await;
     let Err(Error::CannotProgress(message)) = result else {
@@ -252,6 +271,8 @@ async fn await_inputs_returns_error_if_no_token_address() {
         tests::sender(),
         [(RuneName::from_str("C").unwrap(), tests::token_address(5))].into(),
         None,
+        None,
+        None,
     )
     .await;
     let Err(Error::FailedToProgress(message)) = result else {
@@ -260,3 +281,108 @@ async fn await_inputs_returns_error_if_no_token_address() {
 
     assert_data_eq!(message, str!["wrapped token address for rune A not found"]);
 }
+
+fn multi_rune_input(runes: &[(&str, u128)]) -> RuneInput {
+    RuneInput {
+        runes: runes
+            .iter()
+            .map(|(name, amount)| (RuneName::from_str(name).unwrap(), *amount))
+            .collect(),
+        ..rune_input("A", 0)
+    }
+}
+
+#[tokio::test]
+async fn deposit_operation_refunds_runes_without_token_address() {
+    let input = multi_rune_input(&[("A", 1000), ("C", 500)]);
+    let provider = TestRuneInputProvider::with_input(input.clone());
+    let refund_address = "bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw";
+    let result = RuneBridgeOpImpl::deposit_operation(
+        &provider,
+        &tests::sender(),
+        &tests::dst_tokens(),
+        &input,
+        None,
+        Some(refund_address),
+    )
+    .await
+    .unwrap();
+
+    let RuneBridgeOp::Deposit(RuneBridgeDepositOp::AwaitConfirmations {
+        runes_to_wrap,
+        refund: Some(refund),
+        ..
+    }) = result.0
+    else {
+        panic!("Invalid result: {result:?}");
+    };
+
+    assert_eq!(runes_to_wrap.len(), 1);
+    assert_eq!(
+        runes_to_wrap[0].rune_info,
+        provider.rune_info(&tests::rune_name("A"))
+    );
+    assert_eq!(runes_to_wrap[0].wrapped_address, tests::token_address(3));
+    assert_eq!(
+        refund,
+        RuneRefund {
+            address: refund_address.to_string(),
+            runes: vec![RuneToRefund {
+                rune_info: provider.rune_info(&tests::rune_name("C")),
+                amount: 500,
+            }],
+        }
+    );
+}
+
+#[tokio::test]
+async fn deposit_operation_does_not_refund_wrapped_runes() {
+    let input = multi_rune_input(&[("A", 1000), ("B", 500)]);
+    let provider = TestRuneInputProvider::with_input(input.clone());
+    let result = RuneBridgeOpImpl::deposit_operation(
+        &provider,
+        &tests::sender(),
+        &tests::dst_tokens(),
+        &input,
+        None,
+        Some("bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw"),
+    )
+    .await
+    .unwrap();
+
+    let RuneBridgeOp::Deposit(RuneBridgeDepositOp::AwaitConfirmations {
+        runes_to_wrap,
+        refund,
+        ..
+    }) = result.0
+    else {
+        panic!("Invalid result: {result:?}");
+    };
+
+    assert_eq!(runes_to_wrap.len(), 2);
+    assert_eq!(refund, None);
+}
+
+#[tokio::test]
+async fn deposit_operation_cannot_progress_with_invalid_refund_address() {
+    let input = multi_rune_input(&[("A", 1000), ("C", 500)]);
+    let provider = TestRuneInputProvider::with_input(input.clone());
+    let result = RuneBridgeOpImpl::deposit_operation(
+        &provider,
+        &tests::sender(),
+        &tests::dst_tokens(),
+        &input,
+        None,
+        Some("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks"),
+    )
+    .await;
+
+    let Err(Error::CannotProgress(message)) = result else {
+        panic!("Invalid result: {result:?}");
+    };
+
+    assert_data_eq!(
+        message,
+        str!["invalid refund address bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks: [..]"]
+    );
+}
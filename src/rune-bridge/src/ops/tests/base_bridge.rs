@@ -0,0 +1,167 @@
+use bridge_canister::bridge::{Operation as _, OperationAction, OperationProgress};
+use bridge_canister::runtime::service::fetch_logs::BtfBridgeEventHandler;
+use bridge_did::error::Error;
+use bridge_did::event_data::BurntEventData;
+use bridge_did::id256::Id256;
+use bridge_did::op_id::OperationId;
+use bridge_did::order::MintOrder;
+use bridge_did::runes::{EvmNativeToken, EvmNativeTokenStatus, RuneInfo, RuneName};
+use did::H160;
+use ic_exports::ic_kit::MockContext;
+use ordinals::Rune;
+
+use crate::canister::{get_base_bridge_config, get_rune_state};
+use crate::ops::events_handler::{BtfBridgeSide, RuneEventsHandler};
+use crate::ops::{
+    BASE_SIGN_MINT_ORDER_SERVICE_ID, RuneBridgeDepositOp, RuneBridgeOp, RuneBridgeOpImpl,
+    SIGN_MINT_ORDER_SERVICE_ID, tests,
+};
+
+fn wrapped_rune() -> RuneInfo {
+    RuneInfo {
+        name: RuneName::from(Rune(0xdeadbeef)),
+        decimals: 0,
+        block: 840_000,
+        tx: 1,
+    }
+}
+
+fn evm_native_rune() -> RuneInfo {
+    RuneInfo {
+        name: RuneName::from(Rune(0xcafebabe)),
+        decimals: 18,
+        block: 840_000,
+        tx: 2,
+    }
+}
+
+fn wrapped_token() -> H160 {
+    tests::token_address(2)
+}
+
+fn evm_native_token() -> H160 {
+    tests::token_address(7)
+}
+
+/// Registers both runes in the rune state of the canister.
+fn init_rune_state() {
+    let state = get_rune_state();
+    let mut state = state.borrow_mut();
+    state.add_runes([wrapped_rune(), evm_native_rune()]);
+    state.set_evm_native_token(EvmNativeToken {
+        erc20: evm_native_token(),
+        divisibility: 18,
+        premine: 1_000_000,
+        status: EvmNativeTokenStatus::Etched(evm_native_rune()),
+    });
+}
+
+fn burnt_event(from_erc20: H160, rune_info: RuneInfo) -> BurntEventData {
+    BurntEventData {
+        amount: 100u64.into(),
+        from_erc20,
+        recipient_id: b"bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks".to_vec(),
+        to_token: Id256::from(rune_info.id()).0.to_vec(),
+        decimals: rune_info.decimals,
+        ..Default::default()
+    }
+}
+
+fn mint_order(dst_token: H160) -> MintOrder {
+    MintOrder {
+        amount: 100u64.into(),
+        sender: Id256::from_evm_address(&tests::sender(), 0),
+        src_token: Id256::from(evm_native_rune().id()),
+        recipient: tests::sender(),
+        dst_token,
+        nonce: 0,
+        sender_chain_id: 0,
+        recipient_chain_id: 1,
+        name: [0; 32],
+        symbol: [0; 16],
+        decimals: 18,
+        approve_spender: Default::default(),
+        approve_amount: Default::default(),
+        fee_payer: Default::default(),
+    }
+}
+
+#[test]
+fn wrapped_side_bridge_burns_only_wrapped_tokens() {
+    MockContext::new().inject();
+    init_rune_state();
+
+    let handler = RuneEventsHandler::new(get_rune_state(), BtfBridgeSide::Wrapped);
+    assert!(matches!(
+        handler.on_wrapped_token_burnt(burnt_event(wrapped_token(), wrapped_rune())),
+        Some(OperationAction::Create(..))
+    ));
+    assert!(
+        handler
+            .on_wrapped_token_burnt(burnt_event(evm_native_token(), evm_native_rune()))
+            .is_none()
+    );
+}
+
+#[test]
+fn base_side_bridge_locks_only_evm_native_tokens() {
+    MockContext::new().inject();
+    init_rune_state();
+
+    let handler = RuneEventsHandler::new(get_rune_state(), BtfBridgeSide::Base);
+    assert!(matches!(
+        handler.on_wrapped_token_burnt(burnt_event(evm_native_token(), evm_native_rune())),
+        Some(OperationAction::Create(..))
+    ));
+    assert!(
+        handler
+            .on_wrapped_token_burnt(burnt_event(wrapped_token(), wrapped_rune()))
+            .is_none()
+    );
+}
+
+#[test]
+fn evm_native_rune_deposit_awaits_base_bridge_link() {
+    MockContext::new().inject();
+    init_rune_state();
+
+    let dst_tokens = tests::dst_tokens();
+    let result = RuneBridgeOpImpl::deposit_token_address(&dst_tokens, evm_native_rune());
+    assert!(
+        matches!(result, Err(Error::FailedToProgress(_))),
+        "{result:?}"
+    );
+
+    get_base_bridge_config()
+        .borrow_mut()
+        .set_btf_bridge_contract(tests::token_address(42));
+    assert_eq!(
+        RuneBridgeOpImpl::deposit_token_address(&dst_tokens, evm_native_rune()).unwrap(),
+        evm_native_token()
+    );
+}
+
+#[tokio::test]
+async fn mint_orders_are_routed_by_the_side_of_the_token() {
+    MockContext::new().inject();
+    init_rune_state();
+
+    let id = OperationId::new(1);
+    let release = RuneBridgeOpImpl(RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(
+        mint_order(evm_native_token()),
+    )));
+    let progress = release.progress(id, tests::test_state()).await.unwrap();
+    assert!(matches!(
+        progress,
+        OperationProgress::AddToService(BASE_SIGN_MINT_ORDER_SERVICE_ID)
+    ));
+
+    let mint = RuneBridgeOpImpl(RuneBridgeOp::Deposit(RuneBridgeDepositOp::SignMintOrder(
+        mint_order(wrapped_token()),
+    )));
+    let progress = mint.progress(id, tests::test_state()).await.unwrap();
+    assert!(matches!(
+        progress,
+        OperationProgress::AddToService(SIGN_MINT_ORDER_SERVICE_ID)
+    ));
+}
@@ -3,9 +3,10 @@ use std::collections::HashMap;
 use bridge_canister::bridge::OperationAction;
 use bridge_canister::runtime::service::fetch_logs::BtfBridgeEventHandler;
 use bridge_did::event_data::*;
+use bridge_did::init::DEFAULT_DEPOSIT_FEE;
 use bridge_did::runes::RuneName;
 use candid::Encode;
-use tests::events_handler::RuneEventsHandler;
+use tests::events_handler::{BtfBridgeSide, RuneEventsHandler};
 
 use crate::ops::{
     RuneBridgeDepositOp, RuneBridgeOp, RuneBridgeOpImpl, RuneDepositRequestData, tests,
@@ -17,6 +18,7 @@ async fn invalid_notification_type_is_noop() {
         dst_address: tests::sender(),
         dst_tokens: tests::dst_tokens(),
         amounts: None,
+        refund_address: None,
     };
 
     let event = NotifyMinterEventData {
@@ -26,7 +28,7 @@ async fn invalid_notification_type_is_noop() {
         memo: vec![],
     };
 
-    let handler = RuneEventsHandler::new(tests::test_rune_state());
+    let handler = RuneEventsHandler::new(tests::test_rune_state(), BtfBridgeSide::Wrapped);
     let result = handler.on_minter_notification(event.clone());
     assert!(result.is_none());
 
@@ -44,6 +46,7 @@ async fn invalid_notification_payload_is_noop() {
         dst_address: tests::sender(),
         dst_tokens: tests::dst_tokens(),
         amounts: None,
+        refund_address: None,
     };
     let mut data = Encode!(&notification).unwrap();
     data.push(0);
@@ -55,7 +58,7 @@ async fn invalid_notification_payload_is_noop() {
         memo: vec![],
     };
 
-    let handler = RuneEventsHandler::new(tests::test_rune_state());
+    let handler = RuneEventsHandler::new(tests::test_rune_state(), BtfBridgeSide::Wrapped);
     let result = handler.on_minter_notification(event.clone());
     assert!(result.is_none());
 
@@ -73,6 +76,7 @@ async fn deposit_request_creates_correct_operation() {
         dst_address: tests::sender(),
         dst_tokens: tests::dst_tokens(),
         amounts: None,
+        refund_address: None,
     };
     let data = Encode!(&notification).unwrap();
 
@@ -83,7 +87,7 @@ async fn deposit_request_creates_correct_operation() {
         memo: vec![],
     };
 
-    let handler = RuneEventsHandler::new(tests::test_rune_state());
+    let handler = RuneEventsHandler::new(tests::test_rune_state(), BtfBridgeSide::Wrapped);
     let result = handler.on_minter_notification(event.clone());
     assert_eq!(
         result,
@@ -92,6 +96,8 @@ async fn deposit_request_creates_correct_operation() {
                 dst_address: tests::sender(),
                 dst_tokens: tests::dst_tokens(),
                 requested_amounts: None,
+                deposit_fee: Some(DEFAULT_DEPOSIT_FEE),
+                refund_address: None,
             })),
             None
         ))
@@ -105,6 +111,7 @@ async fn deposit_request_adds_amounts_to_operation() {
         dst_address: tests::sender(),
         dst_tokens: tests::dst_tokens(),
         amounts: Some(amounts.clone()),
+        refund_address: None,
     };
     let data = Encode!(&notification).unwrap();
 
@@ -115,7 +122,7 @@ async fn deposit_request_adds_amounts_to_operation() {
         memo: vec![],
     };
 
-    let handler = RuneEventsHandler::new(tests::test_rune_state());
+    let handler = RuneEventsHandler::new(tests::test_rune_state(), BtfBridgeSide::Wrapped);
     let result = handler.on_minter_notification(event.clone());
     assert_eq!(
         result,
@@ -124,6 +131,8 @@ async fn deposit_request_adds_amounts_to_operation() {
                 dst_address: tests::sender(),
                 dst_tokens: tests::dst_tokens(),
                 requested_amounts: Some(amounts),
+                deposit_fee: Some(DEFAULT_DEPOSIT_FEE),
+                refund_address: None,
             })),
             None
         ))
@@ -13,6 +13,7 @@ use crate::state::RuneState;
 
 mod await_confirmations;
 mod await_inputs;
+mod base_bridge;
 mod deposit_request;
 
 fn op_memory() -> OperationsMemory<StableMemory> {
@@ -64,10 +65,11 @@ pub mod minter_notification {
     use bridge_canister::bridge::OperationAction;
     use bridge_canister::runtime::service::fetch_logs::BtfBridgeEventHandler;
     use bridge_did::event_data::*;
+    use bridge_did::init::DEFAULT_DEPOSIT_FEE;
     use bridge_did::operations::{RuneBridgeDepositOp, RuneBridgeOp};
     use candid::Encode;
 
-    use crate::ops::events_handler::RuneEventsHandler;
+    use crate::ops::events_handler::{BtfBridgeSide, RuneEventsHandler};
     use crate::ops::tests::{dst_tokens, test_rune_state, token_address};
     use crate::ops::{RuneBridgeOpImpl, RuneDepositRequestData};
 
@@ -76,6 +78,7 @@ pub mod minter_notification {
             dst_address: token_address(7),
             dst_tokens: dst_tokens(),
             amounts: None,
+            refund_address: None,
         }
     }
 
@@ -92,7 +95,7 @@ pub mod minter_notification {
             memo: vec![],
         };
 
-        let handler = RuneEventsHandler::new(test_rune_state());
+        let handler = RuneEventsHandler::new(test_rune_state(), BtfBridgeSide::Wrapped);
         let result = handler.on_minter_notification(event);
         assert!(result.is_none());
     }
@@ -108,7 +111,7 @@ pub mod minter_notification {
             memo: vec![],
         };
 
-        let handler = RuneEventsHandler::new(test_rune_state());
+        let handler = RuneEventsHandler::new(test_rune_state(), BtfBridgeSide::Wrapped);
         let result = handler.on_minter_notification(event);
         assert!(result.is_none())
     }
@@ -122,7 +125,7 @@ pub mod minter_notification {
             memo: vec![],
         };
 
-        let handler = RuneEventsHandler::new(test_rune_state());
+        let handler = RuneEventsHandler::new(test_rune_state(), BtfBridgeSide::Wrapped);
         let result = handler.on_minter_notification(event);
         let expected = test_deposit_data();
         assert_eq!(
@@ -132,6 +135,8 @@ pub mod minter_notification {
                     dst_address: expected.dst_address,
                     dst_tokens: expected.dst_tokens,
                     requested_amounts: expected.amounts,
+                    deposit_fee: Some(DEFAULT_DEPOSIT_FEE),
+                    refund_address: None,
                 })),
                 None,
             ))
@@ -148,7 +153,7 @@ pub mod minter_notification {
             memo: memo.clone(),
         };
 
-        let handler = RuneEventsHandler::new(test_rune_state());
+        let handler = RuneEventsHandler::new(test_rune_state(), BtfBridgeSide::Wrapped);
         let result = handler.on_minter_notification(event);
         assert!(matches!(result, Some(OperationAction::Create(_, None))));
     }
@@ -163,7 +168,7 @@ pub mod minter_notification {
             memo: memo.clone(),
         };
 
-        let handler = RuneEventsHandler::new(test_rune_state());
+        let handler = RuneEventsHandler::new(test_rune_state(), BtfBridgeSide::Wrapped);
         let result = handler.on_minter_notification(event);
         assert!(
             matches!(result, Some(OperationAction::Create(_, Some(actual_memo))) if actual_memo.to_vec() == memo)
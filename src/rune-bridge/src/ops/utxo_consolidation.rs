@@ -0,0 +1,65 @@
+use bridge_canister::runtime::service::BridgeService;
+use bridge_did::error::{BTFResult, Error};
+use bridge_did::op_id::OperationId;
+use bridge_did::operations::{RuneBridgeOp, RuneBridgeWithdrawOp};
+
+use super::RuneBridgeOpImpl;
+use crate::canister::{get_rune_state, get_runtime, get_runtime_state};
+use crate::core::withdrawal::{UtxoMerge, Withdrawal, did_tx_input};
+
+/// Service to merge small utxos of the bridge while the network fees are low.
+///
+/// Does nothing unless the utxo consolidation is configured.
+pub struct UtxoConsolidationService;
+
+#[async_trait::async_trait(?Send)]
+impl BridgeService for UtxoConsolidationService {
+    async fn run(&self) -> BTFResult<()> {
+        log::trace!("Running UtxoConsolidationService");
+
+        let Some(config) = get_rune_state().borrow().utxo_consolidation_config() else {
+            return Ok(());
+        };
+
+        let withdrawal = Withdrawal::get()
+            .map_err(|err| Error::FailedToProgress(format!("cannot get withdraw: {err:?}")))?;
+        let merge = withdrawal.consolidate_utxos(&config).await.map_err(|err| {
+            Error::FailedToProgress(format!("failed to consolidate utxos: {err:?}"))
+        })?;
+
+        if let Some(merge) = merge {
+            log::debug!(
+                "Utxo consolidation transaction {} sent.",
+                merge.transaction.txid()
+            );
+            track_utxo_merge(merge);
+        }
+
+        Ok(())
+    }
+
+    fn push_operation(&self, _: OperationId) -> BTFResult<()> {
+        let msg = "Operations should not be pushed to the UtxoConsolidationService service";
+        log::warn!("{msg}");
+        Err(Error::FailedToProgress(msg.into()))
+    }
+}
+
+/// Creates an operation tracking the confirmation of the utxo merge transaction, so its inputs
+/// stay used until it is confirmed.
+pub fn track_utxo_merge(merge: UtxoMerge) -> OperationId {
+    let operation = RuneBridgeOpImpl(RuneBridgeOp::Withdraw(
+        RuneBridgeWithdrawOp::AwaitUtxoMergeConfirmation {
+            transaction: merge.transaction.into(),
+            inputs: merge.inputs.iter().map(did_tx_input).collect(),
+        },
+    ));
+    let id = get_runtime_state()
+        .borrow_mut()
+        .operations
+        .new_operation(operation.clone(), None);
+    get_runtime().borrow().schedule_operation(id, operation);
+    log::debug!("Created operation {id} tracking the utxo merge transaction");
+
+    id
+}
@@ -1,5 +1,8 @@
 mod config;
-mod master_key;
+mod evm_tokens;
+mod migration;
+mod runes;
+mod watched_addresses;
 
 use core::panic;
 use std::collections::HashMap;
@@ -7,11 +10,25 @@ use std::time::Duration;
 
 use bitcoin::bip32::ChainCode;
 use bitcoin::{FeeRate, Network, PrivateKey, PublicKey};
+use bitcoin_bridge_core::fee_rate::FeeRateState;
+use bitcoin_bridge_core::key::{AddressType, IcBtcSigner, IcTaprootKey, LocalBtcSigner};
+pub use bitcoin_bridge_core::master_key::{MasterKey, SchnorrMasterKey};
+use bitcoin_bridge_core::master_key::{MasterKeyStorage, SchnorrMasterKeyStorage};
 use bridge_canister::memory::MEMORY_MANAGER;
-use bridge_did::init::{IndexerType, MIN_INDEXERS, RuneBridgeConfig};
-use bridge_did::runes::{RuneInfo, RuneName};
+use bridge_did::init::brc20::SchnorrKeyIds;
+use bridge_did::init::{
+    DepositFeeConfig, DepositFeeQuote, DepositWatcherConfig, FeeEstimatorConfig, IndexerType,
+    MIN_INDEXERS, RuneBridgeConfig, UtxoConsolidationConfig, WithdrawalBatchConfig,
+    WithdrawalCosigningConfig,
+};
+use bridge_did::runes::{EvmNativeToken, RuneInfo, RuneName};
+use bridge_did::schnorr::{
+    ManagementCanisterSchnorrPublicKeyReply, SchnorrAlgorithm, SchnorrKeyId,
+};
+use bridge_utils::fee_estimator::IcFeeEstimator;
+use did::H160;
 use eth_signer::sign_strategy::SigningStrategy;
-use ic_exports::ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use ic_exports::ic_cdk::api::management_canister::bitcoin::{BitcoinNetwork, Outpoint};
 use ic_exports::ic_cdk::api::management_canister::ecdsa::{
     EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyResponse,
 };
@@ -19,21 +36,27 @@ use ic_exports::ic_kit::ic;
 use ic_stable_structures::VirtualMemory;
 use ic_stable_structures::stable_structures::DefaultMemoryImpl;
 use ord_rs::Wallet;
-use ord_rs::wallet::LocalSigner;
 use ordinals::RuneId;
 
 use self::config::RuneBridgeConfigStorage;
-pub use self::master_key::MasterKey;
-use self::master_key::MasterKeyStorage;
-use crate::key::{BtcSignerType, IcBtcSigner};
+use self::evm_tokens::EvmNativeTokenStorage;
+use self::runes::RuneInfoStorage;
+pub use self::watched_addresses::WatchedAddress;
+use self::watched_addresses::WatchedAddressStorage;
+use crate::interface::GetAddressError;
+use crate::key::BtcSignerType;
 use crate::ledger::UtxoLedger;
+use crate::memory::{MASTER_KEY_MEMORY_ID, SCHNORR_MASTER_KEY_MEMORY_ID};
 use crate::{MAINNET_CHAIN_ID, REGTEST_CHAIN_ID, TESTNET_CHAIN_ID};
 
 pub struct RuneState {
     pub(crate) config: RuneBridgeConfigStorage<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) master_key: MasterKeyStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) schnorr_master_key: SchnorrMasterKeyStorage<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) ledger: UtxoLedger<VirtualMemory<DefaultMemoryImpl>>,
-    pub(crate) runes: HashMap<RuneName, RuneInfo>,
+    pub(crate) runes: RuneInfoStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) evm_tokens: EvmNativeTokenStorage<VirtualMemory<DefaultMemoryImpl>>,
+    pub(crate) watched_addresses: WatchedAddressStorage<VirtualMemory<DefaultMemoryImpl>>,
     pub(crate) fee_rate_state: FeeRateState,
 }
 
@@ -43,27 +66,18 @@ impl Default for RuneState {
             config: RuneBridgeConfigStorage::new(memory_manager),
             fee_rate_state: FeeRateState::default(),
             ledger: UtxoLedger::new(memory_manager),
-            master_key: MasterKeyStorage::new(memory_manager),
-            runes: HashMap::default(),
+            master_key: MasterKeyStorage::new(memory_manager, MASTER_KEY_MEMORY_ID),
+            schnorr_master_key: SchnorrMasterKeyStorage::new(
+                memory_manager,
+                SCHNORR_MASTER_KEY_MEMORY_ID,
+            ),
+            runes: RuneInfoStorage::new(memory_manager),
+            evm_tokens: EvmNativeTokenStorage::new(memory_manager),
+            watched_addresses: WatchedAddressStorage::new(memory_manager),
         })
     }
 }
 
-pub struct FeeRateState {
-    fee_rate: FeeRate,
-    /// Last update timestamp in nanoseconds
-    last_update_timestamp: u64,
-}
-
-impl Default for FeeRateState {
-    fn default() -> Self {
-        Self {
-            fee_rate: FeeRate::ZERO,
-            last_update_timestamp: 0,
-        }
-    }
-}
-
 impl RuneState {
     /// Returns id of the IC ECDSA key used by the canister.
     pub fn ecdsa_key_id(&self, signing_strategy: &SigningStrategy) -> EcdsaKeyId {
@@ -78,19 +92,44 @@ impl RuneState {
         }
     }
 
-    pub fn runes(&self) -> &HashMap<RuneName, RuneInfo> {
-        &self.runes
+    pub fn rune_info(&self, rune_id: RuneId) -> Option<RuneInfo> {
+        self.runes.get(rune_id)
     }
 
-    pub fn rune_info(&self, rune_id: RuneId) -> Option<RuneInfo> {
-        self.runes
-            .values()
-            .find(|rune_info| rune_info.id() == rune_id)
-            .copied()
+    pub fn rune_info_by_name(&self, rune_name: &RuneName) -> Option<RuneInfo> {
+        self.runes.get_by_name(rune_name)
+    }
+
+    /// Id of the most recently etched rune known to the canister.
+    pub fn latest_rune_id(&self) -> Option<RuneId> {
+        self.runes.latest_id()
+    }
+
+    /// Adds the runes received from the indexers to the rune info cache.
+    pub fn add_runes(&mut self, runes: impl IntoIterator<Item = RuneInfo>) {
+        for rune_info in runes {
+            self.runes.insert(rune_info);
+        }
+    }
+
+    /// Returns the EVM-native token with the given ERC20 address.
+    pub fn evm_native_token(&self, erc20: &H160) -> Option<EvmNativeToken> {
+        self.evm_tokens.get(erc20)
+    }
+
+    /// Returns the EVM-native token represented by the rune with the given id.
+    pub fn evm_native_token_by_rune(&self, rune_id: RuneId) -> Option<EvmNativeToken> {
+        self.evm_tokens.get_by_rune(rune_id)
+    }
+
+    /// Returns all the EVM-native tokens etched by the canister.
+    pub fn evm_native_tokens(&self) -> Vec<EvmNativeToken> {
+        self.evm_tokens.all()
     }
 
-    pub fn update_rune_list(&mut self, runes: HashMap<RuneName, RuneInfo>) {
-        self.runes = runes;
+    /// Stores the EVM-native token, replacing the previous one with the same ERC20 address.
+    pub fn set_evm_native_token(&mut self, token: EvmNativeToken) {
+        self.evm_tokens.insert(token);
     }
 
     /// Returns master public key of the canister.
@@ -134,13 +173,47 @@ impl RuneState {
         self.master_key.get().clone()
     }
 
+    /// Id of the IC schnorr key used for the taproot addresses. If `None`, the P2WPKH addresses
+    /// of the ECDSA keys are used.
+    pub fn schnorr_key_id(&self) -> Option<SchnorrKeyId> {
+        self.config
+            .get()
+            .schnorr_key_id
+            .as_ref()
+            .map(|key_id| key_id.to_key_id(SchnorrAlgorithm::Bip340Secp256k1))
+    }
+
+    /// Schnorr master key of the canister. Used for the taproot key derivation.
+    pub fn schnorr_master_key(&self) -> Option<SchnorrMasterKey> {
+        *self.schnorr_master_key.get()
+    }
+
     pub fn btc_signer(&self, signing_strategy: &SigningStrategy) -> Option<BtcSignerType> {
         Some(match signing_strategy {
-            SigningStrategy::Local { private_key } => BtcSignerType::Local(LocalSigner::new(
-                PrivateKey::from_slice(private_key, self.network()).expect("invalid private key"),
-            )),
+            SigningStrategy::Local { private_key } => {
+                let address_type = match self.config.get().schnorr_key_id {
+                    Some(_) => AddressType::P2tr,
+                    None => AddressType::P2wpkh,
+                };
+                BtcSignerType::Local(LocalBtcSigner::new(
+                    PrivateKey::from_slice(private_key, self.network())
+                        .expect("invalid private key"),
+                    address_type,
+                ))
+            }
             SigningStrategy::ManagementCanister { .. } => {
-                BtcSignerType::Ic(IcBtcSigner::new(self.master_key()?, self.network()))
+                let taproot_key = match self.schnorr_key_id() {
+                    Some(key_id) => Some(IcTaprootKey {
+                        master_key: self.schnorr_master_key()?,
+                        key_id,
+                    }),
+                    None => None,
+                };
+                BtcSignerType::Ic(IcBtcSigner::new(
+                    self.master_key()?,
+                    self.network(),
+                    taproot_key,
+                ))
             }
         })
     }
@@ -150,9 +223,35 @@ impl RuneState {
         Some(Wallet::new_with_signer(self.btc_signer(signing_strategy)?))
     }
 
-    /// BTC fee in SATs for a deposit request.
-    pub fn deposit_fee(&self) -> u64 {
-        self.config.get().deposit_fee
+    /// BTC fee in SATs for a deposit requested now.
+    ///
+    /// If the dynamic deposit fee is configured, the fee is computed from the cached fee rate,
+    /// otherwise the fixed fee of the configuration is used.
+    pub fn deposit_fee_quote(&self) -> DepositFeeQuote {
+        let config = self.config.get();
+        match &config.dynamic_deposit_fee {
+            Some(fee_config) => {
+                let fee_rate = self.fee_rate().to_sat_per_vb_ceil();
+                DepositFeeQuote {
+                    fee: fee_config.fee(fee_rate),
+                    fee_rate: Some(fee_rate),
+                }
+            }
+            None => DepositFeeQuote {
+                fee: config.deposit_fee,
+                fee_rate: None,
+            },
+        }
+    }
+
+    /// Sets the dynamic deposit fee configuration. If `None`, the fixed deposit fee is used.
+    pub fn set_dynamic_deposit_fee_config(&mut self, fee_config: Option<DepositFeeConfig>) {
+        if let Some(Err(err)) = fee_config.as_ref().map(DepositFeeConfig::validate) {
+            panic!("Invalid deposit fee configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.dynamic_deposit_fee = fee_config);
     }
 
     /// Configuration of the indexers
@@ -223,12 +322,38 @@ impl RuneState {
         Ok(())
     }
 
+    /// Enables the taproot addresses with the given schnorr key and its master key information.
+    ///
+    /// New deposits and change are received at the taproot addresses from now on. The utxos held
+    /// at the legacy addresses stay spendable.
+    pub fn configure_schnorr(
+        &mut self,
+        key_id: SchnorrKeyIds,
+        master_key: ManagementCanisterSchnorrPublicKeyReply,
+    ) -> Result<(), String> {
+        let master_key = SchnorrMasterKey::new(&master_key.public_key, &master_key.chain_code)
+            .map_err(|e| format!("invalid schnorr master key: {e}"))?;
+
+        self.schnorr_master_key.set(master_key);
+        self.config
+            .with_borrow_mut(|config| config.schnorr_key_id = Some(key_id));
+
+        Ok(())
+    }
+
     pub fn configure_indexers(&mut self, mut indexers: Vec<IndexerType>) {
         if indexers.len() < MIN_INDEXERS {
             panic!("number of indexers must be at least {}", MIN_INDEXERS)
         }
 
-        indexers.iter_mut().for_each(|config| config.normalize());
+        for indexer in &mut indexers {
+            if let Err(err) = indexer.validate() {
+                panic!("Invalid indexer configuration: {err}");
+            }
+
+            indexer.normalize();
+        }
+
         self.config
             .with_borrow_mut(move |config| config.indexers = indexers);
     }
@@ -239,21 +364,41 @@ impl RuneState {
 
     /// Update fee rate and the last update timestamp.
     pub fn update_fee_rate(&mut self, fee_rate: FeeRate) {
-        self.fee_rate_state.fee_rate = fee_rate;
-        self.fee_rate_state.last_update_timestamp = ic::time();
+        self.fee_rate_state.update(fee_rate);
     }
 
     /// Fee rate used by the canister.
     pub fn fee_rate(&self) -> FeeRate {
-        self.fee_rate_state.fee_rate
+        self.fee_rate_state.fee_rate()
+    }
+
+    /// Returns the fee estimation configuration.
+    pub fn fee_estimator_config(&self) -> FeeEstimatorConfig {
+        self.config.get().fee_estimator.clone().unwrap_or_default()
+    }
+
+    /// Sets the fee estimation configuration. If `None`, the median of the IC fee percentiles
+    /// is used.
+    ///
+    /// The cached fee rate is dropped, so the next transaction uses the new configuration.
+    pub fn set_fee_estimator_config(&mut self, fee_estimator: Option<FeeEstimatorConfig>) {
+        if let Some(Err(err)) = fee_estimator.as_ref().map(FeeEstimatorConfig::validate) {
+            panic!("Invalid fee estimator configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.fee_estimator = fee_estimator);
+        self.fee_rate_state = FeeRateState::default();
+    }
+
+    /// Fee estimator to request the current fee rate with.
+    pub fn fee_estimator(&self) -> IcFeeEstimator {
+        IcFeeEstimator::new(self.ic_btc_network(), self.fee_estimator_config())
     }
 
     /// Elapsed time since the last fee rate update. (nano seconds)
     pub fn last_fee_rate_update_elapsed(&self) -> Duration {
-        ic::time()
-            .checked_sub(self.fee_rate_state.last_update_timestamp)
-            .map(Duration::from_nanos)
-            .unwrap_or_default()
+        self.fee_rate_state.last_update_elapsed()
     }
 
     /// Returns the number of indexers required to reach consensus.
@@ -266,10 +411,135 @@ impl RuneState {
         self.config
             .with_borrow_mut(|config| config.indexer_consensus_threshold = threshold);
     }
+
+    /// Returns the withdrawal batching configuration. If `None`, withdrawals are not batched.
+    pub fn withdrawal_batch_config(&self) -> Option<WithdrawalBatchConfig> {
+        self.config.get().withdrawal_batch
+    }
+
+    /// Sets the withdrawal batching configuration.
+    pub fn set_withdrawal_batch_config(&mut self, batch_config: Option<WithdrawalBatchConfig>) {
+        self.config
+            .with_borrow_mut(|config| config.withdrawal_batch = batch_config);
+    }
+
+    /// Returns the utxo consolidation configuration. If `None`, utxos are not consolidated.
+    pub fn utxo_consolidation_config(&self) -> Option<UtxoConsolidationConfig> {
+        self.config.get().utxo_consolidation
+    }
+
+    /// Sets the utxo consolidation configuration.
+    pub fn set_utxo_consolidation_config(
+        &mut self,
+        consolidation_config: Option<UtxoConsolidationConfig>,
+    ) {
+        self.config
+            .with_borrow_mut(|config| config.utxo_consolidation = consolidation_config);
+    }
+
+    /// Returns the deposit watcher configuration. If `None`, deposits are not detected
+    /// automatically.
+    pub fn deposit_watcher_config(&self) -> Option<DepositWatcherConfig> {
+        self.config.get().deposit_watcher
+    }
+
+    /// Sets the deposit watcher configuration.
+    pub fn set_deposit_watcher_config(&mut self, deposit_watcher: Option<DepositWatcherConfig>) {
+        if let Some(Err(err)) = deposit_watcher.as_ref().map(DepositWatcherConfig::validate) {
+            panic!("Invalid deposit watcher configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.deposit_watcher = deposit_watcher);
+    }
+
+    /// Returns true if the withdrawal of the given amount of the rune must be co-signed by the
+    /// external keys before being sent.
+    pub fn requires_withdrawal_cosigning(&self, rune_name: &str, amount: u128) -> bool {
+        self.config
+            .get()
+            .withdrawal_cosigning
+            .as_ref()
+            .is_some_and(|cosigning| cosigning.requires_cosigning(rune_name, amount))
+    }
+
+    /// Returns the configuration of the co-signed withdrawals.
+    pub fn withdrawal_cosigning_config(&self) -> Option<WithdrawalCosigningConfig> {
+        self.config.get().withdrawal_cosigning.clone()
+    }
+
+    /// Sets the configuration of the co-signed withdrawals.
+    pub fn set_withdrawal_cosigning_config(
+        &mut self,
+        withdrawal_cosigning: Option<WithdrawalCosigningConfig>,
+    ) {
+        if let Some(Err(err)) = withdrawal_cosigning
+            .as_ref()
+            .map(WithdrawalCosigningConfig::validate)
+        {
+            panic!("Invalid withdrawal co-signing configuration: {err}");
+        }
+
+        self.config
+            .with_borrow_mut(|config| config.withdrawal_cosigning = withdrawal_cosigning);
+    }
+
+    /// Starts watching the deposit address of the given user for new deposits.
+    ///
+    /// New addresses are rejected while the maximum number of the watched addresses is reached.
+    /// Returns the cycles to charge for the registration.
+    pub fn watch_deposit_address(
+        &mut self,
+        eth_address: H160,
+        dst_tokens: HashMap<RuneName, H160>,
+        refund_address: Option<String>,
+        attached_cycles: u64,
+    ) -> Result<u64, GetAddressError> {
+        let config = self
+            .deposit_watcher_config()
+            .ok_or(GetAddressError::DepositWatcherDisabled)?;
+        if attached_cycles < config.registration_fee_cycles {
+            return Err(GetAddressError::InsufficientCycles {
+                required: config.registration_fee_cycles,
+                attached: attached_cycles,
+            });
+        }
+
+        if !self
+            .watched_addresses
+            .can_register(&eth_address, config.max_watched_addresses)
+        {
+            return Err(GetAddressError::TooManyWatchedAddresses);
+        }
+
+        self.watched_addresses
+            .register(eth_address, dst_tokens, refund_address, ic::time());
+
+        Ok(config.registration_fee_cycles)
+    }
+
+    /// Returns the next batch of the watched deposit addresses to scan. Addresses watched for
+    /// longer than the configured period are dropped.
+    pub fn next_watched_addresses(
+        &mut self,
+        config: &DepositWatcherConfig,
+    ) -> Vec<(H160, WatchedAddress)> {
+        let watch_period = Duration::from_secs(config.watch_period_secs).as_nanos() as u64;
+        let expired_before = ic::time().saturating_sub(watch_period);
+        self.watched_addresses
+            .next_batch(config.max_addresses_per_run as usize, expired_before)
+    }
+
+    /// Sets the utxos of the watched deposit address already handled by the deposit watcher.
+    pub fn set_known_deposit_utxos(&mut self, eth_address: &H160, known_utxos: Vec<Outpoint>) {
+        self.watched_addresses
+            .set_known_utxos(eth_address, known_utxos);
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use bridge_did::init::IndexerHeader;
     use ic_exports::ic_kit::MockContext;
 
     use super::*;
@@ -288,6 +558,7 @@ mod tests {
         let config = RuneBridgeConfig {
             indexers: vec![IndexerType::OrdHttp {
                 url: "http://url.com".to_string(),
+                headers: None,
             }],
             ..Default::default()
         };
@@ -299,6 +570,7 @@ mod tests {
         let config = RuneBridgeConfig {
             indexers: vec![IndexerType::OrdHttp {
                 url: "https://url.com".to_string(),
+                headers: None,
             }],
             indexer_consensus_threshold: 1,
             ..Default::default()
@@ -312,6 +584,7 @@ mod tests {
         let config = RuneBridgeConfig {
             indexers: vec![IndexerType::OrdHttp {
                 url: "https://url.com/".to_string(),
+                headers: None,
             }],
             indexer_consensus_threshold: 1,
             ..Default::default()
@@ -322,7 +595,8 @@ mod tests {
         assert_eq!(
             state.indexers_config(),
             vec![IndexerType::OrdHttp {
-                url: "https://url.com".to_string()
+                url: "https://url.com".to_string(),
+                headers: None,
             }],
         );
     }
@@ -333,12 +607,15 @@ mod tests {
         let indexers = vec![
             IndexerType::OrdHttp {
                 url: "https://indexer1.com".to_string(),
+                headers: None,
             },
             IndexerType::OrdHttp {
                 url: "https://indexer2.com".to_string(),
+                headers: None,
             },
             IndexerType::OrdHttp {
                 url: "https://indexer3.com".to_string(),
+                headers: None,
             },
         ];
 
@@ -353,12 +630,15 @@ mod tests {
         let indexers = vec![
             IndexerType::OrdHttp {
                 url: "https://indexer1.com/".to_string(),
+                headers: None,
             },
             IndexerType::OrdHttp {
                 url: "https://indexer2.com/".to_string(),
+                headers: None,
             },
             IndexerType::OrdHttp {
                 url: "https://indexer3.com".to_string(),
+                headers: None,
             },
         ];
 
@@ -369,12 +649,15 @@ mod tests {
             vec![
                 IndexerType::OrdHttp {
                     url: "https://indexer1.com".to_string(),
+                    headers: None,
                 },
                 IndexerType::OrdHttp {
                     url: "https://indexer2.com".to_string(),
+                    headers: None,
                 },
                 IndexerType::OrdHttp {
                     url: "https://indexer3.com".to_string(),
+                    headers: None,
                 },
             ]
         );
@@ -395,18 +678,41 @@ mod tests {
         let mut state = RuneState::default();
         let indexers = vec![IndexerType::OrdHttp {
             url: "http://indexer1.com".to_string(),
+            headers: None,
         }];
 
         state.configure_indexers(indexers);
     }
 
+    #[test]
+    #[should_panic(expected = "Invalid indexer configuration")]
+    fn test_configure_indexers_invalid_header() {
+        let mut state = RuneState::default();
+        let headers = Some(vec![IndexerHeader {
+            name: "x-api-key".to_string(),
+            value: "secret\r\n".to_string(),
+        }]);
+        let indexers = vec![
+            IndexerType::Hiro {
+                url: "https://indexer1.com".to_string(),
+                headers: headers.clone(),
+            },
+            IndexerType::Unisat {
+                url: "https://indexer2.com".to_string(),
+                headers,
+            },
+        ];
+
+        state.configure_indexers(indexers);
+    }
+
     #[test]
     fn test_should_update_and_read_fee_rate() {
         let ctx = MockContext::new().inject();
         let mut state = RuneState::default();
 
         assert_eq!(state.fee_rate(), FeeRate::ZERO);
-        assert_eq!(state.fee_rate_state.last_update_timestamp, 0);
+        assert_eq!(state.fee_rate_state.last_update_timestamp(), 0);
 
         let fee_rate = FeeRate::from_sat_per_vb(1000).unwrap();
         state.update_fee_rate(fee_rate);
@@ -415,4 +721,100 @@ mod tests {
         ctx.add_time(Duration::from_secs(1).as_nanos() as u64);
         assert!(state.last_fee_rate_update_elapsed() >= Duration::from_secs(1));
     }
+
+    #[test]
+    fn test_should_quote_deposit_fee() {
+        MockContext::new().inject();
+        let mut state = RuneState::default();
+        assert_eq!(
+            state.deposit_fee_quote(),
+            DepositFeeQuote {
+                fee: state.config.get().deposit_fee,
+                fee_rate: None,
+            }
+        );
+
+        state.set_dynamic_deposit_fee_config(Some(DepositFeeConfig {
+            tx_vsize: 200,
+            markup_percent: 50,
+            min_fee: 1_000,
+            max_fee: 100_000,
+        }));
+        assert_eq!(state.deposit_fee_quote().fee, 100_000);
+
+        state.update_fee_rate(FeeRate::from_sat_per_vb(10).unwrap());
+        assert_eq!(
+            state.deposit_fee_quote(),
+            DepositFeeQuote {
+                fee: 3_000,
+                fee_rate: Some(10),
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_reset_fee_rate_on_fee_estimator_update() {
+        MockContext::new().inject();
+        let mut state = RuneState::default();
+        state.update_fee_rate(FeeRate::from_sat_per_vb(10).unwrap());
+
+        let config = FeeEstimatorConfig {
+            min_fee_rate: Some(2),
+            ..Default::default()
+        };
+        state.set_fee_estimator_config(Some(config.clone()));
+
+        assert_eq!(state.fee_estimator_config(), config);
+        assert_eq!(state.fee_rate(), FeeRate::ZERO);
+
+        state.set_fee_estimator_config(None);
+        assert_eq!(state.fee_estimator_config(), FeeEstimatorConfig::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid fee estimator configuration")]
+    fn test_should_reject_invalid_fee_estimator_config() {
+        MockContext::new().inject();
+        let mut state = RuneState::default();
+
+        state.set_fee_estimator_config(Some(FeeEstimatorConfig {
+            sources: vec![],
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_should_require_cosigning_of_large_withdrawals() {
+        MockContext::new().inject();
+        let mut state = RuneState::default();
+        assert!(!state.requires_withdrawal_cosigning("SUPERRUNE", u128::MAX));
+
+        let config = WithdrawalCosigningConfig {
+            thresholds: HashMap::from([("SUPERRUNE".to_string(), 1_000)]),
+            cosigners: vec![
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".to_string(),
+            ],
+            required_signatures: 1,
+        };
+        state.set_withdrawal_cosigning_config(Some(config.clone()));
+        assert_eq!(state.withdrawal_cosigning_config(), Some(config));
+        assert!(state.requires_withdrawal_cosigning("SUPERRUNE", 1_000));
+        assert!(!state.requires_withdrawal_cosigning("SUPERRUNE", 999));
+
+        state.set_withdrawal_cosigning_config(None);
+        assert!(!state.requires_withdrawal_cosigning("SUPERRUNE", 1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid withdrawal co-signing configuration")]
+    fn test_should_reject_cosigning_without_cosigners() {
+        MockContext::new().inject();
+        let mut state = RuneState::default();
+
+        state.set_withdrawal_cosigning_config(Some(WithdrawalCosigningConfig {
+            thresholds: HashMap::from([("SUPERRUNE".to_string(), 1_000)]),
+            cosigners: vec![],
+            required_signatures: 0,
+        }));
+    }
 }
@@ -0,0 +1,93 @@
+use bridge_did::runes::EvmNativeToken;
+use did::H160;
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{BTreeMapStructure, MemoryId, MemoryManager, StableBTreeMap};
+use ordinals::RuneId;
+
+use crate::memory::EVM_NATIVE_TOKENS_MEMORY_ID;
+
+/// EVM-native ERC20 tokens bridged to Bitcoin as runes etched by the canister.
+pub struct EvmNativeTokenStorage<M: Memory> {
+    tokens: StableBTreeMap<H160, EvmNativeToken, M>,
+}
+
+impl<M> EvmNativeTokenStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            tokens: StableBTreeMap::new(memory_manager.get(EVM_NATIVE_TOKENS_MEMORY_ID)),
+        }
+    }
+
+    /// Returns the token with the given ERC20 address.
+    pub fn get(&self, erc20: &H160) -> Option<EvmNativeToken> {
+        self.tokens.get(erc20)
+    }
+
+    /// Returns the token represented by the rune with the given id.
+    ///
+    /// The number of the EVM-native tokens is expected to be small, so the tokens are not
+    /// indexed by their runes.
+    pub fn get_by_rune(&self, rune_id: RuneId) -> Option<EvmNativeToken> {
+        self.tokens.iter().map(|(_, token)| token).find(|token| {
+            token
+                .rune_info()
+                .is_some_and(|rune_info| rune_info.id() == rune_id)
+        })
+    }
+
+    /// Stores the token, replacing the previous one with the same ERC20 address.
+    pub fn insert(&mut self, token: EvmNativeToken) {
+        self.tokens.insert(token.erc20.clone(), token);
+    }
+
+    /// Returns all the tokens.
+    pub fn all(&self) -> Vec<EvmNativeToken> {
+        self.tokens.iter().map(|(_, token)| token).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bridge_canister::memory::MEMORY_MANAGER;
+    use bridge_did::runes::{EvmNativeTokenStatus, RuneInfo, RuneName};
+    use did::H256;
+    use ordinals::Rune;
+
+    use super::*;
+
+    #[test]
+    fn test_should_store_and_find_evm_native_tokens() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| EvmNativeTokenStorage::new(memory_manager));
+
+        let rune_info = RuneInfo {
+            name: RuneName::from(Rune(0xdeadbeef)),
+            decimals: 18,
+            block: 840_000,
+            tx: 3,
+        };
+        let etching = EvmNativeToken {
+            erc20: H160::from_slice(&[1; 20]),
+            divisibility: 18,
+            premine: 1_000,
+            status: EvmNativeTokenStatus::Etching {
+                txid: H256::from_slice(&[1; 32]),
+            },
+        };
+        let etched = EvmNativeToken {
+            erc20: H160::from_slice(&[2; 20]),
+            status: EvmNativeTokenStatus::Etched(rune_info),
+            ..etching.clone()
+        };
+        storage.insert(etching.clone());
+        storage.insert(etched.clone());
+
+        assert_eq!(storage.get(&etching.erc20), Some(etching));
+        assert_eq!(storage.get_by_rune(rune_info.id()), Some(etched));
+        assert_eq!(storage.get_by_rune(RuneId { block: 1, tx: 1 }), None);
+        assert_eq!(storage.all().len(), 2);
+    }
+}
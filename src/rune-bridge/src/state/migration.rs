@@ -0,0 +1,104 @@
+use bridge_canister::memory::memory_by_id;
+use bridge_canister::migration::{self, Migration};
+use bridge_did::error::BTFResult;
+
+use super::RuneState;
+use crate::memory::STATE_VERSION_MEMORY_ID;
+
+/// Migrations of the rune bridge state. The migration at index `i` upgrades the state from
+/// version `i` to version `i + 1`. New migrations must be appended to the end of the list.
+const MIGRATIONS: &[Migration<RuneState>] = &[seed_rune_info_cache];
+
+impl RuneState {
+    /// Marks the state of a freshly installed canister as being at the latest version.
+    pub fn init_state_version(&self) {
+        migration::init_state_version(memory_by_id(STATE_VERSION_MEMORY_ID), MIGRATIONS);
+    }
+
+    /// Applies the pending migrations of the state after an upgrade.
+    pub fn migrate(&mut self) -> BTFResult<()> {
+        let version =
+            migration::migrate_state(memory_by_id(STATE_VERSION_MEMORY_ID), self, MIGRATIONS)?;
+        log::info!("Rune bridge state is at version {version}");
+        Ok(())
+    }
+}
+
+/// Adds the runes held by the bridge utxos to the rune info cache, so they can be withdrawn
+/// right after the upgrade, before the rune list is refreshed from the indexers.
+fn seed_rune_info_cache(state: &mut RuneState) -> BTFResult<()> {
+    let runes = state.ledger.held_runes();
+    log::info!(
+        "Seeding the rune info cache with {} held runes",
+        runes.len()
+    );
+    state.add_runes(runes);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::Address;
+    use bridge_did::runes::{RuneInfo, RuneName};
+    use ic_exports::ic_cdk::api::management_canister::bitcoin::{Outpoint, Utxo};
+    use ic_exports::ic_kit::MockContext;
+    use ordinals::Rune;
+
+    use super::*;
+
+    fn deposit_rune_utxo(state: &mut RuneState, rune_info: RuneInfo) {
+        let address = Address::from_str("bc1quyjp8qxkdc22cej962xaydd5arm7trwtcnkzks")
+            .unwrap()
+            .assume_checked();
+        let utxo = Utxo {
+            outpoint: Outpoint {
+                txid: vec![1; 32],
+                vout: 0,
+            },
+            value: 10_000,
+            height: 0,
+        };
+        state
+            .ledger_mut()
+            .deposit(utxo, &address, vec![], vec![rune_info]);
+    }
+
+    #[test]
+    fn test_should_seed_rune_info_cache_on_upgrade() {
+        MockContext::new().inject();
+        let rune_info = RuneInfo {
+            name: RuneName::from(Rune(0xdeadbeef)),
+            decimals: 2,
+            block: 840_000,
+            tx: 1,
+        };
+        let mut state = RuneState::default();
+        deposit_rune_utxo(&mut state, rune_info);
+        assert_eq!(state.rune_info(rune_info.id()), None);
+
+        state.migrate().unwrap();
+
+        assert_eq!(state.rune_info(rune_info.id()), Some(rune_info));
+        assert_eq!(state.rune_info_by_name(&rune_info.name), Some(rune_info));
+    }
+
+    #[test]
+    fn test_should_not_migrate_fresh_state() {
+        MockContext::new().inject();
+        let rune_info = RuneInfo {
+            name: RuneName::from(Rune(0xdeadbeef)),
+            decimals: 2,
+            block: 840_000,
+            tx: 1,
+        };
+        let mut state = RuneState::default();
+        state.init_state_version();
+        deposit_rune_utxo(&mut state, rune_info);
+
+        state.migrate().unwrap();
+
+        assert_eq!(state.rune_info(rune_info.id()), None);
+    }
+}
@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+
+use bridge_did::runes::{RuneInfo, RuneName};
+use ic_stable_structures::stable_structures::Memory;
+use ic_stable_structures::{
+    BTreeMapStructure, Bound, MemoryId, MemoryManager, StableBTreeMap, Storable,
+};
+use ordinals::RuneId;
+
+use crate::memory::{RUNE_IDS_BY_NAME_MEMORY_ID, RUNE_INFO_MEMORY_ID};
+
+/// Cache of the rune information received from the indexers.
+///
+/// Runes are ordered by their ids, so the most recently etched known rune can be used as the
+/// starting point of the next rune list refresh.
+pub struct RuneInfoStorage<M: Memory> {
+    runes: StableBTreeMap<RuneIdKey, RuneInfo, M>,
+    ids_by_name: StableBTreeMap<RuneNameKey, RuneIdKey, M>,
+}
+
+impl<M> RuneInfoStorage<M>
+where
+    M: Memory,
+{
+    pub fn new(memory_manager: &dyn MemoryManager<M, MemoryId>) -> Self {
+        Self {
+            runes: StableBTreeMap::new(memory_manager.get(RUNE_INFO_MEMORY_ID)),
+            ids_by_name: StableBTreeMap::new(memory_manager.get(RUNE_IDS_BY_NAME_MEMORY_ID)),
+        }
+    }
+
+    /// Returns information about the rune with the given id.
+    pub fn get(&self, rune_id: RuneId) -> Option<RuneInfo> {
+        self.runes.get(&rune_id.into())
+    }
+
+    /// Returns information about the rune with the given name.
+    pub fn get_by_name(&self, rune_name: &RuneName) -> Option<RuneInfo> {
+        let rune_id = self.ids_by_name.get(&RuneNameKey::from(*rune_name))?;
+        self.runes.get(&rune_id)
+    }
+
+    /// Stores the rune information, replacing the previous one for the same rune.
+    pub fn insert(&mut self, rune_info: RuneInfo) {
+        let key = RuneIdKey::from(rune_info.id());
+        self.runes.insert(key, rune_info);
+        self.ids_by_name.insert(rune_info.name.into(), key);
+    }
+
+    /// Id of the most recently etched rune in the storage.
+    pub fn latest_id(&self) -> Option<RuneId> {
+        self.runes.last_key_value().map(|(key, _)| key.into())
+    }
+}
+
+/// Rune id encoded in big endian, so the keys are ordered as the rune ids.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct RuneIdKey {
+    block: u64,
+    tx: u32,
+}
+
+impl Storable for RuneIdKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = Vec::with_capacity(Self::BOUND.max_size() as usize);
+        buf.extend_from_slice(&self.block.to_be_bytes());
+        buf.extend_from_slice(&self.tx.to_be_bytes());
+
+        buf.into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let block = u64::from_be_bytes(bytes[0..8].try_into().expect("invalid rune block"));
+        let tx = u32::from_be_bytes(bytes[8..12].try_into().expect("invalid rune tx"));
+
+        Self { block, tx }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 8 + 4,
+        is_fixed_size: true,
+    };
+}
+
+impl From<RuneId> for RuneIdKey {
+    fn from(value: RuneId) -> Self {
+        Self {
+            block: value.block,
+            tx: value.tx,
+        }
+    }
+}
+
+impl From<RuneIdKey> for RuneId {
+    fn from(value: RuneIdKey) -> Self {
+        Self {
+            block: value.block,
+            tx: value.tx,
+        }
+    }
+}
+
+/// Index key of the rune name.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct RuneNameKey(u128);
+
+impl Storable for RuneNameKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_be_bytes().to_vec().into()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(u128::from_be_bytes(
+            bytes[..].try_into().expect("invalid rune name"),
+        ))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 16,
+        is_fixed_size: true,
+    };
+}
+
+impl From<RuneName> for RuneNameKey {
+    fn from(value: RuneName) -> Self {
+        Self(value.inner().0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bridge_canister::memory::MEMORY_MANAGER;
+
+    use super::*;
+
+    fn rune_info(name: &str, block: u64, tx: u32) -> RuneInfo {
+        RuneInfo {
+            name: RuneName::from_str(name).unwrap(),
+            decimals: 2,
+            block,
+            tx,
+        }
+    }
+
+    #[test]
+    fn test_should_encode_and_decode_rune_id_key() {
+        let key = RuneIdKey::from(RuneId {
+            block: 840_000,
+            tx: 12,
+        });
+
+        let decoded = RuneIdKey::from_bytes(key.to_bytes());
+        assert_eq!(decoded, key);
+        assert!(
+            RuneIdKey { block: 1, tx: 256 }.to_bytes() < RuneIdKey { block: 2, tx: 0 }.to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_should_store_and_find_runes() {
+        let mut storage =
+            MEMORY_MANAGER.with(|memory_manager| RuneInfoStorage::new(memory_manager));
+
+        assert_eq!(storage.latest_id(), None);
+
+        let first = rune_info("AAA", 840_000, 1);
+        let second = rune_info("BBB", 840_010, 2);
+        storage.insert(second);
+        storage.insert(first);
+
+        assert_eq!(storage.get(first.id()), Some(first));
+        assert_eq!(
+            storage.get_by_name(&RuneName::from_str("BBB").unwrap()),
+            Some(second)
+        );
+        assert_eq!(
+            storage.get_by_name(&RuneName::from_str("CCC").unwrap()),
+            None
+        );
+        assert_eq!(storage.latest_id(), Some(second.id()));
+    }
+}